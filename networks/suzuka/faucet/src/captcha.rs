@@ -0,0 +1,14 @@
+use std::net::IpAddr;
+
+/// The header a faucet request carries its captcha token in.
+pub const CAPTCHA_TOKEN_HEADER: &str = "x-captcha-token";
+
+/// Checks the captcha tokens faucet requests carry when `FAUCET_REQUIRE_CAPTCHA` is set,
+/// typically against the captcha service which issued them.
+#[async_trait::async_trait]
+pub trait CaptchaVerifier: Send + Sync {
+	/// Whether `token`, presented by `client`, passes verification.
+	///
+	/// An error means the token could not be checked, rather than that it failed.
+	async fn verify(&self, token: &str, client: Option<IpAddr>) -> Result<bool, anyhow::Error>;
+}
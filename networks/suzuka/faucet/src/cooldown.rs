@@ -0,0 +1,114 @@
+use aptos_sdk::types::account_address::AccountAddress;
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// How many clients and addresses are tracked before cooldowns that have run out are dropped.
+const MAX_TRACKED_KEYS: usize = 100_000;
+
+/// Who or what waits out a cooldown.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CooldownKey {
+	/// The IP a request comes from
+	Client(IpAddr),
+	/// The address a request funds
+	Address(AccountAddress),
+}
+
+/// Admits one request per key in each `period`, starting the period at the admitted request.
+///
+/// The cooldowns are kept in memory, so they start over when the faucet restarts.
+#[derive(Debug)]
+pub struct Cooldown {
+	period: Duration,
+	started: Mutex<HashMap<CooldownKey, Instant>>,
+}
+
+impl Cooldown {
+	pub fn new(period: Duration) -> Self {
+		Self { period, started: Mutex::new(HashMap::new()) }
+	}
+
+	/// Admits a request for all of `keys` and starts their cooldowns,
+	/// or returns how long the last of them to cool down has to wait, starting none.
+	pub fn check(&self, keys: &[CooldownKey], now: Instant) -> Result<(), Duration> {
+		let mut started = self.started.lock().unwrap_or_else(PoisonError::into_inner);
+		if started.len() >= MAX_TRACKED_KEYS {
+			started.retain(|_, at| now.duration_since(*at) < self.period);
+		}
+		if let Some(wait) = self.longest_wait(&started, keys, now) {
+			return Err(wait);
+		}
+		for key in keys {
+			started.insert(*key, now);
+		}
+		Ok(())
+	}
+
+	/// Like [`Cooldown::check`], without starting any cooldown, so that a request can be
+	/// rejected before it is counted anywhere else.
+	pub fn peek(&self, keys: &[CooldownKey], now: Instant) -> Result<(), Duration> {
+		let started = self.started.lock().unwrap_or_else(PoisonError::into_inner);
+		self.longest_wait(&started, keys, now).map_or(Ok(()), Err)
+	}
+
+	fn longest_wait(
+		&self,
+		started: &HashMap<CooldownKey, Instant>,
+		keys: &[CooldownKey],
+		now: Instant,
+	) -> Option<Duration> {
+		keys.iter()
+			.filter_map(|key| started.get(key))
+			.filter_map(|at| self.period.checked_sub(now.duration_since(*at)))
+			.filter(|wait| !wait.is_zero())
+			.max()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_requests_wait_out_the_cooldown() {
+		let cooldown = Cooldown::new(Duration::from_secs(60));
+		let client = CooldownKey::Client("10.0.0.1".parse().unwrap());
+		let address = CooldownKey::Address(AccountAddress::ONE);
+		let start = Instant::now();
+
+		assert_eq!(cooldown.check(&[client, address], start), Ok(()));
+		assert_eq!(
+			cooldown.check(&[client], start + Duration::from_secs(20)),
+			Err(Duration::from_secs(40))
+		);
+		assert_eq!(cooldown.check(&[client, address], start + Duration::from_secs(60)), Ok(()));
+	}
+
+	#[test]
+	fn test_any_key_cooling_down_rejects_the_request() {
+		let cooldown = Cooldown::new(Duration::from_secs(60));
+		let address = CooldownKey::Address(AccountAddress::ONE);
+		let now = Instant::now();
+
+		assert_eq!(cooldown.check(&[address], now), Ok(()));
+		// The same address funded from another IP still waits.
+		let other_client = CooldownKey::Client("10.0.0.2".parse().unwrap());
+		assert!(cooldown.check(&[other_client, address], now).is_err());
+		// A rejected request starts no cooldown of its own.
+		assert_eq!(cooldown.check(&[other_client], now), Ok(()));
+	}
+
+	#[test]
+	fn test_peeking_starts_no_cooldown() {
+		let cooldown = Cooldown::new(Duration::from_secs(60));
+		let address = CooldownKey::Address(AccountAddress::ONE);
+		let now = Instant::now();
+
+		assert_eq!(cooldown.peek(&[address], now), Ok(()));
+		assert_eq!(cooldown.check(&[address], now), Ok(()));
+		assert_eq!(cooldown.peek(&[address], now), Err(Duration::from_secs(60)));
+	}
+}
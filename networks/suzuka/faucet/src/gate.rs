@@ -0,0 +1,654 @@
+//! A front for the faucet server which enforces the configured request and connection limits.
+//!
+//! The upstream faucet server has no hooks for these limits and binds a single address,
+//! so when any limit, address list or several listen hostnames are configured it is moved to
+//! a loopback port and this gate serves the public addresses in its place,
+//! forwarding every admitted request unchanged. Nor does the faucet server close idle
+//! connections, so the gate is in front of it unless the idle timeout is disabled.
+//!
+//! A faucet server also funds from a single account, so with several faucet keys one server
+//! runs for each and the gate hands requests to them in turn.
+//!
+//! The gate is also what serves the faucet under a base path, as the faucet server mounts
+//! its routes at the root: the prefix is stripped before a request is forwarded.
+//! Nor does the faucet server limit request bodies or restrict CORS to a list of origins,
+//! so the gate does when either is configured.
+
+use crate::captcha::{CaptchaVerifier, CAPTCHA_TOKEN_HEADER};
+use crate::cooldown::{Cooldown, CooldownKey};
+use crate::rate_limit::RateLimiter;
+
+use aptos_logger::info;
+use aptos_sdk::crypto::{ed25519::Ed25519PublicKey, ValidCryptoMaterialStringExt};
+use aptos_sdk::types::{
+	account_address::AccountAddress, transaction::authenticator::AuthenticationKey,
+};
+use maptos_execution_util::config::{
+	common::{
+		DEFAULT_FAUCET_MINT_AMOUNT, DEFAULT_IDLE_TIMEOUT_SECS, DEFAULT_LISTEN_BACKLOG,
+		DEFAULT_MAX_BODY_BYTES,
+	},
+	cors::CorsOrigins,
+	endpoints::{self, ConnectionLimits, ListenTarget},
+	faucet::Config,
+	reload::SharedRuntimeConfig,
+};
+use maptos_execution_util::listener;
+use poem::{
+	handler,
+	http::{header, StatusCode},
+	middleware::{Cors, SizeLimit},
+	web::Data,
+	Body, EndpointExt, IntoResponse, Request, Response, Route, Server,
+};
+
+use std::collections::HashSet;
+use std::net::IpAddr;
+use std::num::{NonZeroU32, NonZeroU64};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// The limits applied to faucet requests before they reach the faucet server.
+pub struct Gate {
+	/// The hourly request limit per client configured at startup.
+	rate_limit: Option<NonZeroU32>,
+	rate_limiter: RateLimiter,
+	/// The reloaded settings, whose rate limit replaces `rate_limit` once given.
+	runtime_config: Option<SharedRuntimeConfig>,
+	cooldown: Option<Cooldown>,
+	/// Whether requests must carry a captcha token which passes `captcha_verifier`.
+	require_captcha: bool,
+	captcha_verifier: Option<Arc<dyn CaptchaVerifier>>,
+	max_amount: Option<NonZeroU64>,
+	/// The amount filled in for requests naming none, when it differs from the faucet's own.
+	mint_amount: Option<u64>,
+	/// Addresses which are never funded, even when they are also allowlisted.
+	blocked_addresses: HashSet<AccountAddress>,
+	/// Addresses funded without counting toward the rate limit.
+	allowlist_addresses: HashSet<AccountAddress>,
+	connection_limits: ConnectionLimits,
+	/// How long a connection may sit idle before it is closed, `None` to keep it open.
+	idle_timeout: Option<Duration>,
+	/// Whether anything other than the default idle timeout put the gate in front.
+	configured: bool,
+	/// The prefix the faucet is served under, empty for the root.
+	base_path: String,
+	/// The origins allowed to call the faucet. Unless any origin is, the gate answers for
+	/// CORS in place of the faucet servers, which allow every origin.
+	cors_origins: CorsOrigins,
+	max_body_bytes: u64,
+	/// The faucet servers, one for each faucet account, taken in turn; known once they are bound.
+	upstreams: Vec<String>,
+	next_upstream: AtomicUsize,
+	client: reqwest::Client,
+}
+
+impl Gate {
+	/// Creates the gate in front of `upstream_count` faucet servers,
+	/// or returns `None` when no limit, not even an idle timeout, is configured and there is
+	/// a single server.
+	pub fn from_config(config: &Config, upstream_count: usize) -> Option<Self> {
+		let rate_limit = config.rate_limit_per_ip_per_hour();
+		let cooldown = config.cooldown().map(Cooldown::new);
+		let max_amount = config.max_amount_per_request();
+		let mint_amount =
+			Some(config.faucet_mint_amount).filter(|amount| *amount != DEFAULT_FAUCET_MINT_AMOUNT);
+		let connection_limited = config.maptos_faucet_rest_max_connections != 0
+			|| config.maptos_faucet_rest_listen_backlog != DEFAULT_LISTEN_BACKLOG
+			|| config.idle_timeout() != Some(Duration::from_secs(DEFAULT_IDLE_TIMEOUT_SECS));
+		let multi_homed =
+			endpoints::listen_hostnames(&config.maptos_faucet_rest_listen_hostname).len() > 1;
+		let base_path = config.base_path().to_string();
+		let cors_origins =
+			CorsOrigins::from_allowed(&config.maptos_faucet_rest_cors_allowed_origins);
+		let max_body_bytes = config.maptos_faucet_rest_max_body_bytes;
+		let blocked_addresses: HashSet<_> =
+			config.faucet_blocked_addresses.iter().copied().collect();
+		let allowlist_addresses: HashSet<_> =
+			config.faucet_allowlist_addresses.iter().copied().collect();
+		let configured = rate_limit.is_some()
+			|| cooldown.is_some()
+			|| config.faucet_require_captcha
+			|| max_amount.is_some()
+			|| mint_amount.is_some()
+			|| !blocked_addresses.is_empty()
+			|| !allowlist_addresses.is_empty()
+			|| connection_limited
+			|| multi_homed
+			|| !base_path.is_empty()
+			|| cors_origins != CorsOrigins::Any
+			|| max_body_bytes != DEFAULT_MAX_BODY_BYTES
+			|| upstream_count > 1;
+		if !configured && config.idle_timeout().is_none() {
+			return None;
+		}
+		Some(Self {
+			rate_limit,
+			rate_limiter: RateLimiter::per_hour(),
+			runtime_config: None,
+			cooldown,
+			require_captcha: config.faucet_require_captcha,
+			captcha_verifier: None,
+			max_amount,
+			mint_amount,
+			blocked_addresses,
+			allowlist_addresses,
+			connection_limits: config.connection_limits(),
+			idle_timeout: config.idle_timeout(),
+			configured,
+			base_path,
+			cors_origins,
+			max_body_bytes,
+			upstreams: Vec::new(),
+			next_upstream: AtomicUsize::new(0),
+			client: reqwest::Client::new(),
+		})
+	}
+
+	/// Whether the gate enforces anything beyond the default idle timeout.
+	pub fn is_configured(&self) -> bool {
+		self.configured
+	}
+
+	/// Takes the hourly request limit per client from `runtime_config`, so that a reload
+	/// changes it.
+	///
+	/// The gate is only in front of the faucet servers when it was needed at startup,
+	/// so a reload cannot set a limit on a faucet started without one.
+	pub fn with_runtime_config(mut self, runtime_config: SharedRuntimeConfig) -> Self {
+		self.runtime_config = Some(runtime_config);
+		self
+	}
+
+	/// The hourly request limit per client in effect, `None` for unlimited.
+	fn rate_limit(&self) -> Option<NonZeroU32> {
+		match &self.runtime_config {
+			Some(runtime_config) => {
+				NonZeroU32::new(runtime_config.load().faucet_rate_limit_per_ip_per_hour)
+			}
+			None => self.rate_limit,
+		}
+	}
+
+	/// Verifies the captcha tokens of requests with `verifier` when captchas are required.
+	pub fn with_captcha_verifier(mut self, verifier: Arc<dyn CaptchaVerifier>) -> Self {
+		self.captcha_verifier = Some(verifier);
+		self
+	}
+
+	/// Admits a request carrying `token` from `client` when captchas are not required
+	/// or the token passes verification, otherwise the response rejecting it.
+	async fn check_captcha(
+		&self,
+		token: Option<&str>,
+		client: Option<IpAddr>,
+	) -> Result<(), Response> {
+		if !self.require_captcha {
+			return Ok(());
+		}
+		let rejected = |reason: &str| (StatusCode::FORBIDDEN, reason.to_string()).into_response();
+		let (Some(verifier), Some(token)) = (&self.captcha_verifier, token) else {
+			return Err(rejected("a verified captcha token is required"));
+		};
+		match verifier.verify(token, client).await {
+			Ok(true) => Ok(()),
+			Ok(false) => Err(rejected("the captcha token did not pass verification")),
+			Err(e) => Err((StatusCode::BAD_GATEWAY, format!("captcha verification error: {}", e))
+				.into_response()),
+		}
+	}
+
+	/// Admits a request from `client` funding `addresses` when none of them is cooling down,
+	/// starting their cooldowns unless `peek`, otherwise the response rejecting it.
+	///
+	/// The addresses are those the request decodes to, so that an account named by its
+	/// authentication key or public key cools down as when named by its address.
+	fn check_cooldown(
+		&self,
+		client: Option<IpAddr>,
+		addresses: &[AccountAddress],
+		now: Instant,
+		peek: bool,
+	) -> Result<(), Response> {
+		let Some(cooldown) = &self.cooldown else {
+			return Ok(());
+		};
+		let keys: Vec<CooldownKey> = client
+			.map(CooldownKey::Client)
+			.into_iter()
+			.chain(addresses.iter().copied().map(CooldownKey::Address))
+			.collect();
+		let admitted = if peek { cooldown.peek(&keys, now) } else { cooldown.check(&keys, now) };
+		admitted.map_err(|wait| {
+			Response::builder()
+				.status(StatusCode::TOO_MANY_REQUESTS)
+				.header(header::RETRY_AFTER, retry_after_secs(wait))
+				.body("faucet requests from this client or for this address are cooling down")
+		})
+	}
+
+	/// Serves the gate on `listen_targets` in front of the faucet servers at `upstreams`,
+	/// until the server stops.
+	pub async fn run(
+		mut self,
+		listen_targets: Vec<ListenTarget>,
+		upstreams: Vec<String>,
+	) -> Result<(), anyhow::Error> {
+		self.upstreams = upstreams;
+		info!("Serving the faucet at {:?} in front of {:?}", listen_targets, self.upstreams);
+		let listener = listener::bind_all(listen_targets, self.connection_limits)?;
+		let server = match self.idle_timeout {
+			Some(idle_timeout) => Server::new(listener).idle_timeout(idle_timeout),
+			None => Server::new(listener),
+		};
+		let (cors_enabled, cors) = match &self.cors_origins {
+			CorsOrigins::List(origins) => {
+				(true, Cors::new().allow_origins(origins.iter().cloned()))
+			}
+			CorsOrigins::Disabled | CorsOrigins::Any => (false, Cors::new()),
+		};
+		let max_body_bytes = usize::try_from(self.max_body_bytes).unwrap_or(usize::MAX);
+		let endpoint = if self.base_path.is_empty() {
+			forward.data(Arc::new(self)).boxed()
+		} else {
+			// Nested routes see request paths with the prefix stripped, as the faucet expects.
+			let base_path = self.base_path.clone();
+			Route::new().nest(base_path, forward).data(Arc::new(self)).boxed()
+		};
+		let endpoint = endpoint.with_if(cors_enabled, cors).with(SizeLimit::new(max_body_bytes));
+		server
+			.run(endpoint)
+			.await
+			.map_err(|e| anyhow::anyhow!("Faucet gate error: {:?}", e))
+	}
+
+	/// The faucet server to forward the next request to, round-robin.
+	fn upstream(&self) -> &str {
+		let index = self.next_upstream.fetch_add(1, Ordering::Relaxed) % self.upstreams.len();
+		&self.upstreams[index]
+	}
+
+	async fn forward(
+		&self,
+		req: &Request,
+		query: Option<String>,
+		body: Vec<u8>,
+	) -> Result<Response, anyhow::Error> {
+		let path_and_query = match query {
+			Some(query) => format!("{}?{}", req.uri().path(), query),
+			None => req.uri().path().to_string(),
+		};
+		let method = reqwest::Method::from_bytes(req.method().as_str().as_bytes())?;
+		let mut upstream = self
+			.client
+			.request(method, format!("http://{}{}", self.upstream(), path_and_query));
+		for (name, value) in req.headers() {
+			// The body may have been rewritten, so it is sent with a length of its own.
+			if name != header::HOST && name != header::CONTENT_LENGTH {
+				upstream = upstream.header(name.as_str(), value.as_bytes());
+			}
+		}
+		let response = upstream.body(body).send().await?;
+
+		let mut builder =
+			Response::builder().status(StatusCode::from_u16(response.status().as_u16())?);
+		// The gate answers for CORS itself unless any origin is allowed.
+		let own_cors = self.cors_origins != CorsOrigins::Any;
+		for (name, value) in response.headers() {
+			// The body is buffered, so it is sent with a length of its own.
+			if name == "transfer-encoding" || name == "connection" || name == "content-length" {
+				continue;
+			}
+			if own_cors && name.as_str().starts_with("access-control-") {
+				continue;
+			}
+			builder = builder.header(name.as_str(), value.as_bytes());
+		}
+		Ok(builder.body(response.bytes().await?.to_vec()))
+	}
+}
+
+/// The fields naming the account a faucet request funds, whichever endpoint takes it.
+const RECEIVER_FIELDS: [&str; 3] = ["auth_key", "address", "pub_key"];
+
+/// The decoded parameters of a query string.
+fn query_pairs(query: Option<&str>) -> Vec<(String, String)> {
+	query
+		.map(|query| url::form_urlencoded::parse(query.as_bytes()).into_owned().collect())
+		.unwrap_or_default()
+}
+
+/// The amount a faucet request asks for, from the `amount` query parameter or JSON body field.
+fn requested_amount(query: Option<&str>, body: &[u8]) -> Option<u64> {
+	let from_query = query_pairs(query)
+		.into_iter()
+		.find(|(name, _)| name == "amount")
+		.and_then(|(_, amount)| amount.parse().ok());
+	from_query
+		.or_else(|| serde_json::from_slice::<serde_json::Value>(body).ok()?.get("amount")?.as_u64())
+}
+
+/// The account a receiver field names, decoded as the faucet server decodes it: an
+/// authentication key is the address of its account, and a public key is that of the account
+/// it authenticates.
+fn receiver(field: &str, value: &str) -> Option<AccountAddress> {
+	match field {
+		"pub_key" => Ed25519PublicKey::from_encoded_string(value)
+			.ok()
+			.map(|public_key| AuthenticationKey::ed25519(&public_key).account_address()),
+		_ => AccountAddress::from_str(value).ok(),
+	}
+}
+
+/// Every account a faucet request names, in its query or its JSON body, sorted.
+///
+/// The faucet server funds the one named where its endpoint reads the request,
+/// so the gate checks all of them rather than guess which that is.
+fn requested_addresses(query: Option<&str>, body: &[u8]) -> Vec<AccountAddress> {
+	let mut fields = query_pairs(query);
+	if let Ok(serde_json::Value::Object(body)) = serde_json::from_slice(body) {
+		fields.extend(
+			body.into_iter()
+				.filter_map(|(name, value)| Some((name, value.as_str()?.to_string()))),
+		);
+	}
+	let mut addresses: Vec<AccountAddress> = fields
+		.iter()
+		.filter(|(name, _)| RECEIVER_FIELDS.contains(&name.as_str()))
+		.filter_map(|(name, value)| receiver(name, value))
+		.collect();
+	addresses.sort();
+	addresses.dedup();
+	addresses
+}
+
+/// Fills in `amount` for a request naming none: in the JSON body when the body is
+/// a JSON object, as the faucet's `/fund` endpoint takes, otherwise in the query.
+fn with_default_amount(
+	query: Option<&str>,
+	body: Vec<u8>,
+	amount: u64,
+) -> (Option<String>, Vec<u8>) {
+	if let Ok(serde_json::Value::Object(mut fields)) = serde_json::from_slice(&body) {
+		fields.insert("amount".to_string(), amount.into());
+		let body = serde_json::to_vec(&fields).expect("a JSON object always serializes");
+		return (query.map(str::to_string), body);
+	}
+	let query = match query {
+		Some(query) if !query.is_empty() => format!("{}&amount={}", query, amount),
+		_ => format!("amount={}", amount),
+	};
+	(Some(query), body)
+}
+
+/// Whole seconds to wait, rounded up so that a client retrying on time is admitted.
+fn retry_after_secs(wait: Duration) -> u64 {
+	wait.as_secs() + u64::from(wait.subsec_nanos() > 0)
+}
+
+/// Forwards a request to the faucet server once it passes the configured limits.
+///
+/// A request naming a blocked address is rejected with 403 Forbidden,
+/// as is one without a captcha token which passes verification when captchas are required.
+/// A request naming no amount is given the configured mint amount.
+/// A request asking for more than the maximum amount is rejected with 400 Bad Request.
+/// A client over its hourly request limit is rejected with 429 Too Many Requests,
+/// and the `Retry-After` header gives the seconds until its next window opens.
+/// So is a request from a client, or for an address, still in its cooldown,
+/// with `Retry-After` giving the seconds until the cooldown ends.
+/// Rejected requests and requests naming only allowlisted addresses do not count toward the limit
+/// or start a cooldown.
+#[handler]
+async fn forward(req: &Request, body: Body, Data(gate): Data<&Arc<Gate>>) -> Response {
+	let body = match body.into_vec().await {
+		Ok(body) => body,
+		Err(e) => return (StatusCode::BAD_REQUEST, e.to_string()).into_response(),
+	};
+
+	let addresses = requested_addresses(req.uri().query(), &body);
+	if let Some(address) = addresses.iter().find(|address| gate.blocked_addresses.contains(address))
+	{
+		return (StatusCode::FORBIDDEN, format!("address {} may not be funded", address))
+			.into_response();
+	}
+	let allowlisted = !addresses.is_empty()
+		&& addresses.iter().all(|address| gate.allowlist_addresses.contains(address));
+
+	let client = req.remote_addr().as_socket_addr().map(|addr| addr.ip());
+	let captcha_token =
+		req.headers().get(CAPTCHA_TOKEN_HEADER).and_then(|token| token.to_str().ok());
+	if let Err(response) = gate.check_captcha(captcha_token, client).await {
+		return response;
+	}
+
+	let (query, body, amount) = match requested_amount(req.uri().query(), &body) {
+		Some(amount) => (req.uri().query().map(str::to_string), body, Some(amount)),
+		None => match gate.mint_amount {
+			Some(mint_amount) => {
+				let (query, body) = with_default_amount(req.uri().query(), body, mint_amount);
+				(query, body, Some(mint_amount))
+			}
+			None => (req.uri().query().map(str::to_string), body, None),
+		},
+	};
+
+	if let (Some(max_amount), Some(amount)) = (gate.max_amount, amount) {
+		if amount > max_amount.get() {
+			return (
+				StatusCode::BAD_REQUEST,
+				format!("amount {} exceeds the maximum of {} per request", amount, max_amount),
+			)
+				.into_response();
+		}
+	}
+
+	// A request still cooling down is rejected before it is counted toward the rate limit.
+	if !allowlisted {
+		if let Err(response) = gate.check_cooldown(client, &addresses, Instant::now(), true) {
+			return response;
+		}
+	}
+	if let (Some(rate_limit), Some(client), false) = (gate.rate_limit(), client, allowlisted) {
+		if let Err(wait) = gate.rate_limiter.check(client, rate_limit, Instant::now()) {
+			return Response::builder()
+				.status(StatusCode::TOO_MANY_REQUESTS)
+				.header(header::RETRY_AFTER, retry_after_secs(wait))
+				.body("too many faucet requests from this address");
+		}
+	}
+	if !allowlisted {
+		if let Err(response) = gate.check_cooldown(client, &addresses, Instant::now(), false) {
+			return response;
+		}
+	}
+
+	match gate.forward(req, query, body).await {
+		Ok(response) => response,
+		Err(e) => (StatusCode::BAD_GATEWAY, format!("faucet server error: {}", e)).into_response(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use aptos_sdk::crypto::ed25519::Ed25519PrivateKey;
+
+	#[test]
+	fn test_requested_amount() {
+		assert_eq!(requested_amount(Some("address=0x1&amount=100"), b""), Some(100));
+		assert_eq!(requested_amount(None, br#"{"address":"0x1","amount":250}"#), Some(250));
+		assert_eq!(requested_amount(Some("address=0x1"), b"not json"), None);
+		assert_eq!(requested_amount(Some("amount=%32%30"), b""), Some(20));
+	}
+
+	#[test]
+	fn test_requested_addresses() {
+		assert_eq!(
+			requested_addresses(Some("address=0x1&amount=100"), b""),
+			vec![AccountAddress::ONE]
+		);
+		assert_eq!(
+			requested_addresses(None, br#"{"address":"0x2","amount":250}"#),
+			vec![AccountAddress::TWO]
+		);
+		assert_eq!(requested_addresses(Some("address=0xnope"), b""), vec![]);
+		// Query values are decoded, as the faucet server decodes them.
+		assert_eq!(requested_addresses(Some("address=%30x1"), b""), vec![AccountAddress::ONE]);
+		// Both the query and the body are checked, whichever the endpoint funds from.
+		assert_eq!(
+			requested_addresses(Some("address=0x1"), br#"{"auth_key":"0x2"}"#),
+			vec![AccountAddress::ONE, AccountAddress::TWO]
+		);
+
+		let private_key = Ed25519PrivateKey::try_from(&[1u8; 32][..]).unwrap();
+		let public_key = Ed25519PublicKey::from(&private_key);
+		let account = AuthenticationKey::ed25519(&public_key).account_address();
+		let body = serde_json::json!({ "pub_key": public_key.to_encoded_string().unwrap() });
+		assert_eq!(requested_addresses(None, body.to_string().as_bytes()), vec![account]);
+	}
+
+	#[test]
+	fn test_default_amount_is_filled_in() {
+		let (query, body) = with_default_amount(Some("address=0x1"), Vec::new(), 500);
+		assert_eq!(query.as_deref(), Some("address=0x1&amount=500"));
+		assert_eq!(requested_amount(query.as_deref(), &body), Some(500));
+
+		let (query, body) = with_default_amount(None, br#"{"address":"0x1"}"#.to_vec(), 500);
+		assert_eq!(query, None);
+		assert_eq!(requested_amount(None, &body), Some(500));
+	}
+
+	/// A config the gate has nothing to enforce for, as idle connections are kept open.
+	fn ungated_config() -> Config {
+		let mut config = Config::default();
+		config.maptos_faucet_rest_idle_timeout_sec = Duration::ZERO.into();
+		config
+	}
+
+	#[test]
+	fn test_idle_timeout_puts_the_gate_in_front() {
+		let mut config = Config::default();
+		let gate = Gate::from_config(&config, 1).expect("the default idle timeout is enforced");
+		assert_eq!(gate.idle_timeout, Some(Duration::from_secs(DEFAULT_IDLE_TIMEOUT_SECS)));
+		assert!(!gate.is_configured());
+
+		config.maptos_faucet_rest_idle_timeout_sec = Duration::from_secs(15).into();
+		let gate = Gate::from_config(&config, 1).expect("an idle timeout is set");
+		assert_eq!(gate.idle_timeout, Some(Duration::from_secs(15)));
+		assert!(gate.is_configured());
+
+		assert!(Gate::from_config(&ungated_config(), 1).is_none());
+	}
+
+	#[test]
+	fn test_base_path_puts_the_gate_in_front() {
+		let mut config = ungated_config();
+		assert!(Gate::from_config(&config, 1).is_none());
+
+		config.maptos_faucet_rest_base_path = "/faucet/".to_string();
+		let gate = Gate::from_config(&config, 1).expect("a base path is set");
+		assert_eq!(gate.base_path, "/faucet");
+	}
+
+	#[test]
+	fn test_cors_and_body_limits_put_the_gate_in_front() {
+		let mut config = ungated_config();
+		config.maptos_faucet_rest_cors_allowed_origins = vec!["*".to_string()];
+		assert!(Gate::from_config(&config, 1).is_none());
+
+		config.maptos_faucet_rest_cors_allowed_origins = vec!["https://app.example".to_string()];
+		let gate = Gate::from_config(&config, 1).expect("the origins are restricted");
+		assert_eq!(gate.cors_origins, CorsOrigins::List(vec!["https://app.example".to_string()]));
+
+		config.maptos_faucet_rest_cors_allowed_origins = vec!["*".to_string()];
+		config.maptos_faucet_rest_max_body_bytes = 1024;
+		let gate = Gate::from_config(&config, 1).expect("a body limit is set");
+		assert_eq!(gate.max_body_bytes, 1024);
+	}
+
+	#[test]
+	fn test_several_faucet_servers_are_taken_in_turn() {
+		let mut gate = Gate::from_config(&Config::default(), 2)
+			.expect("several faucet servers are behind the gate");
+		gate.upstreams = vec!["127.0.0.1:1".to_string(), "127.0.0.1:2".to_string()];
+		let taken: Vec<String> = (0..3).map(|_| gate.upstream().to_string()).collect();
+		assert_eq!(taken, ["127.0.0.1:1", "127.0.0.1:2", "127.0.0.1:1"]);
+	}
+
+	/// Passes exactly the tokens equal to its own.
+	struct MockVerifier(&'static str);
+
+	#[async_trait::async_trait]
+	impl CaptchaVerifier for MockVerifier {
+		async fn verify(
+			&self,
+			token: &str,
+			_client: Option<IpAddr>,
+		) -> Result<bool, anyhow::Error> {
+			Ok(token == self.0)
+		}
+	}
+
+	#[tokio::test]
+	async fn test_required_captcha_rejects_requests_without_a_valid_token() {
+		let mut config = ungated_config();
+		config.faucet_require_captcha = true;
+		let gate =
+			Gate::from_config(&config, 1).expect("a required captcha puts the gate in front");
+		// Without a verifier no token can pass.
+		assert_eq!(
+			gate.check_captcha(Some("pass"), None).await.unwrap_err().status(),
+			StatusCode::FORBIDDEN
+		);
+
+		let gate = gate.with_captcha_verifier(Arc::new(MockVerifier("pass")));
+		assert!(gate.check_captcha(Some("pass"), None).await.is_ok());
+		for token in [None, Some("fail")] {
+			assert_eq!(
+				gate.check_captcha(token, None).await.unwrap_err().status(),
+				StatusCode::FORBIDDEN
+			);
+		}
+
+		config.faucet_require_captcha = false;
+		let gate = Gate::from_config(&config, 1);
+		assert!(gate.is_none());
+	}
+
+	#[test]
+	fn test_cooldown_rejects_requests_until_it_ends() {
+		let mut config = Config::default();
+		config.faucet_cooldown_sec = Duration::from_secs(60).into();
+		let gate = Gate::from_config(&config, 1).expect("a cooldown puts the gate in front");
+		let client: IpAddr = "10.0.0.1".parse().unwrap();
+		let start = Instant::now();
+
+		assert!(gate.check_cooldown(Some(client), &[AccountAddress::ONE], start, false).is_ok());
+		let response = gate
+			.check_cooldown(
+				Some(client),
+				&[AccountAddress::TWO],
+				start + Duration::from_millis(10_500),
+				false,
+			)
+			.unwrap_err();
+		assert_eq!(response.status(), StatusCode::TOO_MANY_REQUESTS);
+		assert_eq!(response.headers().get(header::RETRY_AFTER).unwrap(), "50");
+
+		// Peeking at a cooled-down client starts nothing.
+		let other: IpAddr = "10.0.0.2".parse().unwrap();
+		assert!(gate.check_cooldown(Some(other), &[AccountAddress::TWO], start, true).is_ok());
+		assert!(gate.check_cooldown(Some(other), &[AccountAddress::TWO], start, true).is_ok());
+
+		let later = start + Duration::from_secs(60);
+		assert!(gate.check_cooldown(Some(client), &[AccountAddress::ONE], later, false).is_ok());
+	}
+
+	#[test]
+	fn test_retry_after_rounds_up() {
+		assert_eq!(retry_after_secs(Duration::from_secs(30)), 30);
+		assert_eq!(retry_after_secs(Duration::from_millis(30_001)), 31);
+	}
+}
@@ -0,0 +1,254 @@
+// Copyright © Aptos Foundation
+// SPDX-License-Identifier: Apache-2.0
+
+//! The Suzuka faucet, run by the `suzuka-faucet-service` binary.
+//!
+//! Captcha services differ in how tokens are checked, so no verifier is built in: a
+//! deployment setting `FAUCET_REQUIRE_CAPTCHA` runs the faucet with [`run`] and a
+//! [`CaptchaVerifier`] of its own.
+
+use anyhow::Result;
+use aptos_config::keys::ConfigKey;
+use aptos_faucet_core::{
+	funder::FunderConfig,
+	server::{FunderKeyEnum, RunConfig, Server},
+};
+use aptos_logger::{info, warn};
+use clap::Parser;
+use maptos_execution_util::config::{
+	common::{DEFAULT_FAUCET_TXN_EXPIRATION_SECS, DEFAULT_MAX_BODY_BYTES},
+	cors::CorsOrigins,
+	endpoints,
+	reload::{ConfigReloader, ReloadSource},
+};
+
+use tokio::sync::oneshot;
+
+use std::sync::Arc;
+use std::time::Duration;
+
+pub mod captcha;
+mod cooldown;
+mod gate;
+mod rate_limit;
+
+#[derive(Clone, Debug, Parser)]
+pub struct Args {
+	#[clap(subcommand)]
+	server: Server,
+}
+
+impl Args {
+	pub async fn run_command(&self) -> Result<()> {
+		self.server.run_command().await
+	}
+}
+
+pub use captcha::CaptchaVerifier;
+
+/// Runs the faucet with the config of the node at `DOT_MOVEMENT_PATH` and the command line
+/// arguments, checking the captcha tokens of requests with `captcha_verifier` when
+/// `FAUCET_REQUIRE_CAPTCHA` is set.
+pub async fn run(captcha_verifier: Option<Arc<dyn CaptchaVerifier>>) -> Result<()> {
+	let dot_movement = dot_movement::DotMovement::try_from_env()?;
+	let mut config = dot_movement.try_get_config_from_json::<suzuka_config::Config>()?;
+	config.execution_config.maptos_config = config
+		.execution_config
+		.maptos_config
+		.finish_loading()
+		.map_err(|report| anyhow::anyhow!("Invalid maptos execution config:\n{}", report))?;
+
+	// Read replicas must not be able to mint.
+	if config.execution_config.maptos_config.is_read_only() {
+		aptos_logger::Logger::builder().level(aptos_logger::Level::Info).build();
+		warn!("MAPTOS_READ_ONLY is set, so the faucet does not start");
+		return Ok(());
+	}
+	if !config.execution_config.maptos_config.faucet_enabled() {
+		aptos_logger::Logger::builder().level(aptos_logger::Level::Info).build();
+		info!("FAUCET_ENABLED=false, so the faucet does not start");
+		return Ok(());
+	}
+	if config.execution_config.maptos_config.faucet.faucet_require_captcha
+		&& captcha_verifier.is_none()
+	{
+		anyhow::bail!(
+			"FAUCET_REQUIRE_CAPTCHA is set, but the faucet was run without a captcha verifier"
+		);
+	}
+
+	let cors_origins = config.execution_config.maptos_config.faucet_cors_origins();
+	let max_body_bytes =
+		config.execution_config.maptos_config.faucet.maptos_faucet_rest_max_body_bytes;
+	let shutdown_grace = config.execution_config.maptos_config.chain.shutdown_grace();
+	let txn_expiration = config.execution_config.maptos_config.faucet_txn_expiration();
+	let gas_unit_price = config.execution_config.maptos_config.chain.gas_unit_price();
+
+	// get the keys, which are the node's unless the faucet has its own
+	let faucet_signers = config.execution_config.maptos_config.faucet_signers();
+	let faucet_signers: Vec<_> =
+		faucet_signers.iter().map(|account| account.signer.clone()).collect();
+
+	// A faucet server funds from a single account, so one runs for each.
+	// With request or connection limits, an idle timeout, several listen hostnames, a base path
+	// or several accounts configured, the faucet servers are moved to loopback ports behind
+	// the gate, which learns their ports once they are bound.
+	// The reloadable settings are re-read from the config file on SIGHUP.
+	let reloader = Arc::new(ConfigReloader::new(
+		config.execution_config.maptos_config.clone(),
+		ReloadSource::node_config_json(dot_movement.get_config_json_path()),
+	));
+	let gate = gate::Gate::from_config(
+		&config.execution_config.maptos_config.faucet,
+		faucet_signers.len(),
+	)
+	.map(|gate| gate.with_runtime_config(reloader.shared()))
+	.map(|gate| match captcha_verifier {
+		Some(verifier) => gate.with_captcha_verifier(verifier),
+		None => gate,
+	});
+
+	// get the connection url, under the base path the node mounts its REST routes at
+	let connection_url = endpoints::with_base_path(
+		endpoints::connection_url(
+			"http",
+			&config.execution_config.maptos_config.faucet.maptos_rest_connection_hostname,
+			config.execution_config.maptos_config.faucet.maptos_rest_connection_port,
+		)?,
+		config.execution_config.maptos_config.chain.rest_base_path(),
+	)?;
+
+	// get the chain id
+	let chain_id = config.execution_config.maptos_config.chain.maptos_chain_id.clone();
+
+	// get the listener host and port
+	let listener_host =
+		config.execution_config.maptos_config.faucet.maptos_faucet_rest_listen_hostname;
+	let listener_port = config.execution_config.maptos_config.faucet.maptos_faucet_rest_listen_port;
+	let public_targets =
+		endpoints::listen_targets("FAUCET_API_LISTEN_HOSTNAME", &listener_host, listener_port)?;
+
+	let root_args = Args::parse();
+	// run-simple takes the defaults for how transactions are submitted, so the faucet servers
+	// are configured as it would, with the submission settings of the config in place.
+	let servers = match &root_args.server {
+		Server::RunSimple(server) => faucet_signers
+			.iter()
+			.map(|signer| -> Result<RunConfig> {
+				let (listen_address, listen_port) = if gate.is_some() {
+					("127.0.0.1".to_string(), 0)
+				} else {
+					(listener_host.to_string(), listener_port)
+				};
+				let mut run_config = RunConfig::build_for_cli(
+					connection_url.clone(),
+					listen_address,
+					listen_port,
+					FunderKeyEnum::Key(ConfigKey::new(signer.private_key.clone())),
+					server.do_not_delegate,
+					Some(chain_id),
+				);
+				if let FunderConfig::MintFunder(funder) = &mut run_config.funder_config {
+					let submission = &mut funder.transaction_submission_config;
+					submission.gas_unit_price_override = gas_unit_price;
+					submission.transaction_expiration_secs = txn_expiration.as_secs();
+				}
+				Ok(run_config)
+			})
+			.collect::<Result<Vec<_>>>()?,
+		_ => Vec::new(),
+	};
+
+	aptos_logger::Logger::builder().level(aptos_logger::Level::Info).build();
+
+	let faucet_accounts: Vec<String> =
+		faucet_signers.iter().map(|signer| signer.account_address.to_string()).collect();
+	info!("Funding faucet requests from accounts {}", faucet_accounts.join(", "));
+
+	// The other commands configure the faucet server themselves.
+	if !matches!(root_args.server, Server::RunSimple(_)) {
+		for (var, ignored) in [
+			("FAUCET_API_CORS_ORIGINS", cors_origins != CorsOrigins::Any),
+			("FAUCET_API_MAX_BODY_BYTES", max_body_bytes != DEFAULT_MAX_BODY_BYTES),
+			(
+				"FAUCET_TXN_EXPIRATION_SEC",
+				txn_expiration != Duration::from_secs(DEFAULT_FAUCET_TXN_EXPIRATION_SECS),
+			),
+			("MAPTOS_GAS_UNIT_PRICE", gas_unit_price.is_some()),
+		] {
+			if ignored {
+				warn!("{} only applies to the run-simple command and is ignored", var);
+			}
+		}
+	}
+
+	info!("Running with root args: {:#?}", root_args);
+	tokio::spawn(maptos_opt_executor::reload::on_sighup(reloader));
+
+	// The faucet server has no shutdown hook of its own,
+	// so in-flight requests are given the grace period before the process exits.
+	let server = async {
+		if !matches!(root_args.server, Server::RunSimple(_)) {
+			if gate.as_ref().is_some_and(gate::Gate::is_configured) {
+				warn!("The faucet request limits only apply to the run-simple command and are ignored");
+			}
+			return root_args.run_command().await;
+		}
+		match gate {
+			Some(gate) => {
+				let (port_txs, port_rxs): (Vec<_>, Vec<_>) =
+					servers.iter().map(|_| oneshot::channel()).unzip();
+				let servers = run_servers(servers.into_iter().zip(port_txs.into_iter().map(Some)));
+				let gate = async {
+					let mut upstreams = Vec::new();
+					for port in port_rxs {
+						upstreams.push(format!("127.0.0.1:{}", port.await?));
+					}
+					gate.run(public_targets, upstreams).await
+				};
+				tokio::try_join!(servers, gate).map(|_| ())
+			}
+			None => run_servers(servers.into_iter().map(|server| (server, None))).await,
+		}
+	};
+	tokio::pin!(server);
+	tokio::select! {
+		result = &mut server => result,
+		() = maptos_opt_executor::shutdown::signal() => {
+			info!("Shutdown requested, waiting up to {:?} for in-flight requests", shutdown_grace);
+			match tokio::time::timeout(shutdown_grace, server).await {
+				Ok(result) => result,
+				Err(_) => anyhow::bail!(
+					"the faucet requests in flight did not finish within {:?}",
+					shutdown_grace
+				),
+			}
+		}
+	}
+}
+
+/// Runs the faucet servers until one of them fails, sending the port each binds on the
+/// channel given with it.
+async fn run_servers(
+	servers: impl IntoIterator<Item = (RunConfig, Option<oneshot::Sender<u16>>)>,
+) -> Result<()> {
+	let mut set = tokio::task::JoinSet::new();
+	for (server, port_tx) in servers {
+		set.spawn(async move {
+			match port_tx {
+				Some(port_tx) => server.run_with_port_tx(port_tx).await,
+				None => server.run().await,
+			}
+		});
+	}
+	while let Some(result) = set.join_next().await {
+		result??;
+	}
+	Ok(())
+}
+
+#[test]
+fn verify_tool() {
+	use clap::CommandFactory;
+	Args::command().debug_assert()
+}
@@ -0,0 +1,97 @@
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::num::NonZeroU32;
+use std::sync::{Mutex, PoisonError};
+use std::time::{Duration, Instant};
+
+/// How many clients are tracked before windows that have run out are dropped.
+const MAX_TRACKED_CLIENTS: usize = 100_000;
+
+#[derive(Debug, Clone, Copy)]
+struct Window {
+	started: Instant,
+	requests: u32,
+}
+
+/// Admits at most a limit of requests per client IP in each fixed window.
+///
+/// The limit is given with each request, so that a reload changes it for the windows
+/// already open. The windows are kept in memory, so they start over when the faucet restarts.
+#[derive(Debug)]
+pub struct RateLimiter {
+	window: Duration,
+	clients: Mutex<HashMap<IpAddr, Window>>,
+}
+
+impl RateLimiter {
+	pub fn new(window: Duration) -> Self {
+		Self { window, clients: Mutex::new(HashMap::new()) }
+	}
+
+	pub fn per_hour() -> Self {
+		Self::new(Duration::from_secs(60 * 60))
+	}
+
+	/// Counts a request from `client` against `limit`, or returns how long it has to wait for
+	/// the next window.
+	pub fn check(&self, client: IpAddr, limit: NonZeroU32, now: Instant) -> Result<(), Duration> {
+		let mut clients = self.clients.lock().unwrap_or_else(PoisonError::into_inner);
+		if clients.len() >= MAX_TRACKED_CLIENTS {
+			clients.retain(|_, window| now.duration_since(window.started) < self.window);
+		}
+		let window = clients.entry(client).or_insert(Window { started: now, requests: 0 });
+		let elapsed = now.duration_since(window.started);
+		if elapsed >= self.window {
+			*window = Window { started: now, requests: 0 };
+		} else if window.requests >= limit.get() {
+			return Err(self.window - elapsed);
+		}
+		window.requests += 1;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn limit(limit: u32) -> NonZeroU32 {
+		NonZeroU32::new(limit).unwrap()
+	}
+
+	#[test]
+	fn test_requests_over_the_limit_wait_for_the_next_window() {
+		let limiter = RateLimiter::new(Duration::from_secs(60));
+		let client: IpAddr = "10.0.0.1".parse().unwrap();
+		let start = Instant::now();
+
+		assert_eq!(limiter.check(client, limit(2), start), Ok(()));
+		assert_eq!(limiter.check(client, limit(2), start + Duration::from_secs(10)), Ok(()));
+		assert_eq!(
+			limiter.check(client, limit(2), start + Duration::from_secs(20)),
+			Err(Duration::from_secs(40))
+		);
+		assert_eq!(limiter.check(client, limit(2), start + Duration::from_secs(60)), Ok(()));
+	}
+
+	#[test]
+	fn test_clients_are_limited_independently() {
+		let limiter = RateLimiter::new(Duration::from_secs(60));
+		let now = Instant::now();
+
+		assert_eq!(limiter.check("10.0.0.1".parse().unwrap(), limit(1), now), Ok(()));
+		assert_eq!(limiter.check("10.0.0.2".parse().unwrap(), limit(1), now), Ok(()));
+		assert!(limiter.check("10.0.0.1".parse().unwrap(), limit(1), now).is_err());
+	}
+
+	#[test]
+	fn test_a_raised_limit_applies_to_open_windows() {
+		let limiter = RateLimiter::new(Duration::from_secs(60));
+		let client: IpAddr = "10.0.0.1".parse().unwrap();
+		let now = Instant::now();
+
+		assert_eq!(limiter.check(client, limit(1), now), Ok(()));
+		assert!(limiter.check(client, limit(1), now).is_err());
+		assert_eq!(limiter.check(client, limit(2), now), Ok(()));
+	}
+}
@@ -45,14 +45,16 @@ impl Local {
 		dot_movement: DotMovement,
 		mut config: suzuka_config::Config,
 	) -> Result<suzuka_config::Config, anyhow::Error> {
-		// update the db path
+		// root the node's storage under the chain's directory, with the db alongside it
 		let chain_id = config.execution_config.maptos_config.chain.maptos_chain_id;
-		let db_path = dot_movement
-			.get_path()
-			.join("maptos")
-			.join(chain_id.to_string())
-			.join(".maptos");
-		config.execution_config.maptos_config.chain.maptos_db_path.replace(db_path);
+		let data_dir = dot_movement.get_path().join("maptos").join(chain_id.to_string());
+		let chain = &mut config.execution_config.maptos_config.chain;
+		chain.maptos_data_dir = data_dir;
+		chain.maptos_db_path.replace(".maptos".into());
+		// A local network is for development, so it may run under a key generated for it.
+		if chain.generate_private_key_if_unconfigured() {
+			tracing::info!("Generated a private key for the local network");
+		}
 
 		Ok(config)
 	}
@@ -4,7 +4,9 @@
 // use url::Url;
 use aptos_protos::indexer::v1::{raw_data_client::RawDataClient, GetTransactionsRequest};
 use futures::StreamExt;
+use maptos_execution_util::config::indexer::GrpcCompression;
 use once_cell::sync::Lazy;
+use tonic::codec::CompressionEncoding;
 
 static SUZUKA_CONFIG: Lazy<suzuka_config::Config> = Lazy::new(|| {
 	let dot_movement = dot_movement::DotMovement::try_from_env().unwrap();
@@ -32,6 +34,15 @@ static INDEXER_URL: Lazy<String> = Lazy::new(|| {
 	indexer_connection_url
 });
 
+/// The tonic encoding of a configured compression, `None` for uncompressed messages.
+fn compression_encoding(compression: GrpcCompression) -> Option<CompressionEncoding> {
+	match compression {
+		GrpcCompression::None => None,
+		GrpcCompression::Gzip => Some(CompressionEncoding::Gzip),
+		GrpcCompression::Zstd => Some(CompressionEncoding::Zstd),
+	}
+}
+
 #[tokio::test]
 async fn test_example_indexer_stream() -> Result<(), anyhow::Error> {
 	/*let channel = tonic::transport::Channel::from_shared(
@@ -40,7 +51,21 @@ async fn test_example_indexer_stream() -> Result<(), anyhow::Error> {
 		"[Parser] Failed to build GRPC channel, perhaps because the data service URL is invalid",
 	);*/
 
-	let mut client = RawDataClient::connect(INDEXER_URL.as_str()).await?;
+	let indexer_config = &SUZUKA_CONFIG.execution_config.maptos_config.indexer;
+	// Keepalive stops NATs and load balancers from silently dropping the idle stream.
+	let mut endpoint = tonic::transport::Endpoint::from_shared(INDEXER_URL.clone())?
+		.tcp_keepalive(indexer_config.tcp_keepalive());
+	if let Some(interval) = indexer_config.http2_keepalive() {
+		endpoint = endpoint.http2_keep_alive_interval(interval).keep_alive_while_idle(true);
+	}
+
+	// Large transaction batches exceed tonic's default 4 MiB decoding limit.
+	let mut client = RawDataClient::new(endpoint.connect().await?)
+		.max_decoding_message_size(indexer_config.max_message_bytes());
+	let compression = SUZUKA_CONFIG.execution_config.maptos_config.indexer_grpc_compression();
+	if let Some(encoding) = compression_encoding(compression) {
+		client = client.send_compressed(encoding).accept_compressed(encoding);
+	}
 
 	let request = GetTransactionsRequest {
 		starting_version: Some(1),
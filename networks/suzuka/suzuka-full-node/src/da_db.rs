@@ -1,4 +1,5 @@
-use rocksdb::{ColumnFamilyDescriptor, Options, DB};
+use maptos_execution_util::config::commit_batch::FsyncPolicy;
+use rocksdb::{ColumnFamilyDescriptor, Options, WriteOptions, DB};
 
 use std::path::Path;
 use std::sync::Arc;
@@ -13,13 +14,17 @@ use column_families::*;
 ///
 /// An async access API is provided to avoid blocking async tasks.
 /// The methods must be executed in the context of a Tokio runtime.
+///
+/// Recording a block as executed completes the writes for that block,
+/// so it is the write [`FsyncPolicy::Batch`] syncs.
 #[derive(Clone, Debug)]
 pub struct DaDB {
 	inner: Arc<DB>,
+	fsync_policy: FsyncPolicy,
 }
 
 impl DaDB {
-	pub fn open(path: impl AsRef<Path>) -> anyhow::Result<Self> {
+	pub fn open(path: impl AsRef<Path>, fsync_policy: FsyncPolicy) -> anyhow::Result<Self> {
 		let mut options = Options::default();
 		options.create_if_missing(true);
 		options.create_missing_column_families(true);
@@ -29,17 +34,25 @@ impl DaDB {
 
 		let db = DB::open_cf_descriptors(&options, path, vec![synced_height, executed_blocks])
 			.map_err(|e| anyhow::anyhow!("Failed to open DA DB: {:?}", e))?;
-		Ok(Self { inner: Arc::new(db) })
+		Ok(Self { inner: Arc::new(db), fsync_policy })
+	}
+
+	/// The options of a write, synced to disk as the fsync policy says.
+	fn write_options(&self, completes_batch: bool) -> WriteOptions {
+		let mut options = WriteOptions::default();
+		options.set_sync(self.fsync_policy.syncs(completes_batch));
+		options
 	}
 
 	pub async fn add_executed_block(&self, id: String) -> Result<(), anyhow::Error> {
 		let da_db = self.inner.clone();
+		let write_options = self.write_options(true);
 		tokio::task::spawn_blocking(move || {
 			let cf = da_db
 				.cf_handle(EXECUTED_BLOCKS)
 				.ok_or(anyhow::anyhow!("No executed_blocks column family"))?;
 			da_db
-				.put_cf(&cf, id.clone(), id)
+				.put_cf_opt(&cf, id.clone(), id, &write_options)
 				.map_err(|e| anyhow::anyhow!("Failed to add executed block: {:?}", e))
 		})
 		.await??;
@@ -63,6 +76,7 @@ impl DaDB {
 	pub async fn set_synced_height(&self, height: u64) -> Result<(), anyhow::Error> {
 		// This is heavy for this purpose, but progressively the contents of the DA DB will be used for more things
 		let da_db = self.inner.clone();
+		let write_options = self.write_options(false);
 		tokio::task::spawn_blocking(move || {
 			let cf = da_db
 				.cf_handle(SYNCED_HEIGHT)
@@ -70,7 +84,7 @@ impl DaDB {
 			let height = serde_json::to_string(&height)
 				.map_err(|e| anyhow::anyhow!("Failed to serialize synced height: {:?}", e))?;
 			da_db
-				.put_cf(&cf, "synced_height", height)
+				.put_cf_opt(&cf, "synced_height", height, &write_options)
 				.map_err(|e| anyhow::anyhow!("Failed to set synced height: {:?}", e))
 		})
 		.await??;
@@ -10,6 +10,14 @@ async fn main() -> Result<ExitCode, anyhow::Error> {
 	let tracing_config =
 		movement_tracing::Config { timing_log_path: env::var_os(TIMING_LOG_ENV).map(Into::into) };
 	let _guard = movement_tracing::init_tracing_subscriber(tracing_config);
+	// MAPTOS_LOG_LEVEL is applied once the config is loaded, and again on every reload.
+	maptos_execution_util::config::logging::register_log_level_handle(|level| {
+		movement_tracing::set_default_level(level.as_str()).map_err(|e| anyhow::anyhow!(e))
+	});
+
+	// Secret providers other than `env` are registered here with
+	// `maptos_execution_util::config::secrets::register_secret_provider`,
+	// before anything reads the config.
 
 	// get the config file
 	let dot_movement = dot_movement::DotMovement::try_from_env()?;
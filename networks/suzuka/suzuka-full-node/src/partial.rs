@@ -2,6 +2,8 @@ use crate::{da_db::DaDB, tasks};
 use m1_da_light_node_client::LightNodeServiceClient;
 use maptos_dof_execution::MakeOptFinServices;
 use maptos_dof_execution::{v1::Executor, DynOptFinExecutor};
+use maptos_execution_util::config::{chain::SyncMode, logging, reload::ReloadSource};
+use maptos_opt_executor::shutdown;
 use mcr_settlement_client::McrSettlementClient;
 use mcr_settlement_manager::CommitmentEventStream;
 use mcr_settlement_manager::McrSettlementManager;
@@ -10,8 +12,11 @@ use suzuka_config::Config;
 
 use anyhow::Context;
 use tokio::sync::mpsc;
-use tokio::try_join;
-use tracing::debug;
+use tokio::{select, try_join};
+use tracing::{debug, info};
+
+use std::future::Future;
+use std::path::Path;
 
 pub struct SuzukaPartialNode<T> {
 	executor: T,
@@ -50,6 +55,8 @@ where
 			self.light_node_client,
 			// FIXME: why are the struct member names so tautological?
 			self.config.m1_da_light_node.m1_da_light_node_config,
+			self.config.execution_config.maptos_config.chain.commit_batch_policy(),
+			self.config.execution_config.maptos_config.mempool_ordering(),
 		);
 
 		let (
@@ -59,8 +66,8 @@ where
 			services_result,
 		) = try_join!(
 			tokio::spawn(async move { exec_settle_task.run().await }),
-			tokio::spawn(async move { transaction_ingress_task.run().await }),
-			tokio::spawn(exec_background),
+			tokio::spawn(until_shutdown(async move { transaction_ingress_task.run().await })),
+			tokio::spawn(until_shutdown(exec_background)),
 			tokio::spawn(services.run()),
 			// tokio::spawn(async move { movement_rest.run_service().await }),
 		)?;
@@ -71,8 +78,18 @@ where
 	}
 }
 
+/// Runs a task which would otherwise never finish until the node is asked to shut down.
+async fn until_shutdown(
+	task: impl Future<Output = Result<(), anyhow::Error>>,
+) -> Result<(), anyhow::Error> {
+	select! {
+		result = task => result,
+		() = shutdown::requested() => Ok(()),
+	}
+}
+
 impl SuzukaPartialNode<Executor> {
-	pub async fn try_from_config(config: Config) -> Result<Self, anyhow::Error> {
+	pub async fn try_from_config(mut config: Config) -> Result<Self, anyhow::Error> {
 		// todo: extract into getter
 		let light_node_connection_hostname = config
 			.m1_da_light_node
@@ -96,9 +113,45 @@ impl SuzukaPartialNode<Executor> {
 		.await
 		.context("Failed to connect to light node")?;
 
+		config.execution_config.maptos_config =
+			config.execution_config.maptos_config.finish_loading().map_err(|report| {
+				anyhow::anyhow!("Invalid maptos execution config:\n{}", report)
+			})?;
+		logging::apply_log_level(config.execution_config.maptos_config.logging.maptos_log_level);
+		if maptos_execution_util::config::production::is_production_profile() {
+			config.execution_config.maptos_config.assert_production_safe().map_err(
+				|violations| {
+					let violations: Vec<String> =
+						violations.iter().map(ToString::to_string).collect();
+					anyhow::anyhow!(
+						"MAPTOS_PROFILE=production refuses the maptos execution config:\n{}",
+						violations.join("\n")
+					)
+				},
+			)?;
+		}
+		info!(
+			"Maptos config fingerprint: {:016x}",
+			config.execution_config.maptos_config.stable_hash()
+		);
+		info!("Maptos config: {}", config.execution_config.maptos_config.summary());
+
+		if config.execution_config.maptos_config.sync_mode() == SyncMode::Full {
+			let chain = &config.execution_config.maptos_config.chain;
+			tasks::execute_settle::clear_for_full_sync(
+				&chain.maptos_data_dir.join("full-sync-started"),
+				&[&chain.db_path(), Path::new(&config.da_db.da_db_path)],
+			)?;
+		}
+
 		debug!("Creating the executor");
-		let executor = Executor::try_from_config(&config.execution_config.maptos_config)
-			.context("Failed to create the inner executor")?;
+		// The config is re-read from the file it was loaded from on SIGHUP.
+		let config_path = dot_movement::DotMovement::try_from_env()?.get_config_json_path();
+		let executor = Executor::try_from_config_reloaded_from(
+			&config.execution_config.maptos_config,
+			ReloadSource::node_config_json(config_path),
+		)
+		.context("Failed to create the inner executor")?;
 
 		debug!("Creating the settlement client");
 		let settlement_client = McrSettlementClient::build_with_config(&config.mcr)
@@ -114,8 +167,11 @@ impl SuzukaPartialNode<Executor> {
 			MovementRest::try_from_env().context("Failed to create MovementRest")?;
 
 		debug!("Creating the DA DB");
-		let da_db =
-			DaDB::open(&config.da_db.da_db_path).context("Failed to create or get DA DB")?;
+		let da_db = DaDB::open(
+			&config.da_db.da_db_path,
+			config.execution_config.maptos_config.chain.fsync_policy(),
+		)
+		.context("Failed to create or get DA DB")?;
 
 		Ok(Self {
 			executor,
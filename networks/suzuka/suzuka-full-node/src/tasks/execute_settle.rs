@@ -10,6 +10,7 @@ use maptos_dof_execution::{
 	DynOptFinExecutor, ExecutableBlock, ExecutableTransactions, HashValue,
 	SignatureVerifiedTransaction, SignedTransaction, Transaction,
 };
+use maptos_opt_executor::shutdown;
 use mcr_settlement_manager::{CommitmentEventStream, McrSettlementManagerOperations};
 use movement_types::block::{Block, BlockCommitment, BlockCommitmentEvent};
 
@@ -20,6 +21,8 @@ use tokio::select;
 use tokio_stream::{Stream, StreamExt};
 use tracing::{debug, error, info, info_span, warn, Instrument};
 
+use std::path::Path;
+
 pub struct Task<E, S> {
 	executor: E,
 	settlement_manager: S,
@@ -59,6 +62,47 @@ impl<E, S> Task<E, S> {
 	}
 }
 
+/// Removes the databases at `paths` before a full sync opens them, for every block on the DA
+/// to be executed again from genesis.
+///
+/// Left in place, the blocks recorded as executed would be skipped as they are read. The
+/// databases are only removed once: `marker` is written after they are, and while it exists
+/// later starts resume the replay from the synced height instead of starting it over. Delete
+/// `marker` to replay the DA again.
+///
+/// Returns whether the databases were removed.
+pub(crate) fn clear_for_full_sync(marker: &Path, paths: &[&Path]) -> anyhow::Result<bool> {
+	if marker.exists() {
+		info!(
+			"Resuming the full sync started earlier (remove {} to start it over)",
+			marker.display()
+		);
+		return Ok(false);
+	}
+	for path in paths {
+		match std::fs::remove_dir_all(path) {
+			Ok(()) => {
+				info!("Removed {} to execute the DA again (MAPTOS_SYNC_MODE=full)", path.display())
+			}
+			Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+			Err(e) => {
+				return Err(e).with_context(|| format!("failed to remove {}", path.display()))
+			}
+		}
+	}
+	if let Some(parent) = marker.parent() {
+		std::fs::create_dir_all(parent)
+			.with_context(|| format!("failed to create {}", parent.display()))?;
+	}
+	std::fs::write(marker, b"").with_context(|| format!("failed to write {}", marker.display()))?;
+	Ok(true)
+}
+
+/// Whether the node has committed the version it is configured to stop at.
+fn reached_stop_version(stop_at_version: Option<u64>, committed_version: u64) -> bool {
+	stop_at_version.is_some_and(|stop_at_version| committed_version >= stop_at_version)
+}
+
 impl<E, S> Task<E, S>
 where
 	E: DynOptFinExecutor,
@@ -69,12 +113,17 @@ where
 		// (a) needs to be read from the DA and
 		// (b) requires modifications to Aptos Core.
 		self.executor.rollover_genesis_block().await?;
+		if self.stop_version_reached()? {
+			return Ok(());
+		}
 
+		let sync_mode = self.executor.config().sync_mode();
+		// A full sync empties the DA DB as it starts, so it reads from the first height.
+		let height = self.da_db.get_synced_height().await?;
+		info!("Reading blocks from DA height {} (MAPTOS_SYNC_MODE={})", height, sync_mode);
 		let mut blocks_from_da = self
 			.da_light_node_client
-			.stream_read_from_height(StreamReadFromHeightRequest {
-				height: self.da_db.get_synced_height().await?,
-			})
+			.stream_read_from_height(StreamReadFromHeightRequest { height })
 			.await?
 			.into_inner();
 
@@ -83,6 +132,9 @@ where
 				Some(res) = blocks_from_da.next() => {
 					let response = res.context("failed to get next block from DA")?;
 					self.process_block_from_da(response).await?;
+					if self.stop_version_reached()? {
+						break;
+					}
 				}
 				Some(res) = self.commitment_events.next() => {
 					let event = res.context("failed to get commitment event")?;
@@ -94,6 +146,24 @@ where
 		Ok(())
 	}
 
+	/// Requests a graceful shutdown once the configured stop version is committed.
+	fn stop_version_reached(&self) -> anyhow::Result<bool> {
+		let stop_at_version = self.executor.config().chain.stop_at_version();
+		if stop_at_version.is_none() {
+			return Ok(false);
+		}
+		let committed_version = self.executor.get_ledger_version()?;
+		if !reached_stop_version(stop_at_version, committed_version) {
+			return Ok(false);
+		}
+		info!(
+			"Committed version {} reached MAPTOS_STOP_AT_VERSION={:?}, shutting down",
+			committed_version, stop_at_version
+		);
+		shutdown::request();
+		Ok(true)
+	}
+
 	async fn process_block_from_da(
 		&mut self,
 		response: StreamReadFromHeightResponse,
@@ -266,3 +336,39 @@ where
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_stop_version_triggers_once_committed() {
+		let committed = [0, 10, 99, 100, 150];
+		let triggered: Vec<bool> = committed
+			.iter()
+			.map(|version| reached_stop_version(Some(100), *version))
+			.collect();
+		assert_eq!(triggered, [false, false, false, true, true]);
+		// Blocks can commit several versions at once, stepping past the stop version.
+		assert!(reached_stop_version(Some(100), 101));
+		assert!(committed.iter().all(|version| !reached_stop_version(None, *version)));
+	}
+
+	#[test]
+	fn test_full_sync_clears_the_databases_only_once() -> anyhow::Result<()> {
+		let dir = tempfile::tempdir()?;
+		let marker = dir.path().join("full-sync-started");
+		let db = dir.path().join("maptos-db");
+		std::fs::create_dir_all(&db)?;
+
+		assert!(clear_for_full_sync(&marker, &[&db])?);
+		assert!(!db.exists());
+		assert!(marker.exists());
+
+		// A restart in the middle of the replay keeps what it has executed so far.
+		std::fs::create_dir_all(&db)?;
+		assert!(!clear_for_full_sync(&marker, &[&db])?);
+		assert!(db.exists());
+		Ok(())
+	}
+}
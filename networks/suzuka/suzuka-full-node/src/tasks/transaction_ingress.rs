@@ -3,6 +3,10 @@
 use m1_da_light_node_client::{BatchWriteRequest, BlobWrite, LightNodeServiceClient};
 use m1_da_light_node_util::config::Config as LightNodeConfig;
 use maptos_dof_execution::SignedTransaction;
+use maptos_execution_util::config::{
+	commit_batch::CommitBatchPolicy, load_shedding::MempoolOrdering,
+};
+use maptos_opt_executor::ordering::OrderedTransactions;
 
 use tokio::sync::mpsc;
 use tracing::{info, warn};
@@ -17,6 +21,10 @@ pub struct Task {
 	transaction_receiver: mpsc::Receiver<SignedTransaction>,
 	da_light_node_client: LightNodeServiceClient<tonic::transport::Channel>,
 	da_light_node_config: LightNodeConfig,
+	// When a batch is written, capped at half the block building time
+	commit_batch: CommitBatchPolicy,
+	// The order the transactions of a batch are written in
+	ordering: MempoolOrdering,
 }
 
 impl Task {
@@ -24,8 +32,16 @@ impl Task {
 		transaction_receiver: mpsc::Receiver<SignedTransaction>,
 		da_light_node_client: LightNodeServiceClient<tonic::transport::Channel>,
 		da_light_node_config: LightNodeConfig,
+		commit_batch: CommitBatchPolicy,
+		ordering: MempoolOrdering,
 	) -> Self {
-		Task { transaction_receiver, da_light_node_client, da_light_node_config }
+		Task {
+			transaction_receiver,
+			da_light_node_client,
+			da_light_node_config,
+			commit_batch,
+			ordering,
+		}
 	}
 
 	pub async fn run(mut self) -> anyhow::Result<()> {
@@ -34,6 +50,9 @@ impl Task {
 	}
 
 	/// Constructs a batch of transactions then spawns the write request to the DA in the background.
+	///
+	/// The batch is written once it is full or its delay has passed, whichever is first, with
+	/// its transactions in the mempool ordering.
 	async fn spawn_write_next_transaction_batch(
 		&mut self,
 	) -> Result<ControlFlow<(), ()>, anyhow::Error> {
@@ -42,26 +61,22 @@ impl Task {
 		// limit the total time batching transactions
 		let start = Instant::now();
 		let (_, half_building_time) = self.da_light_node_config.try_block_building_parameters()?;
+		let commit_batch =
+			self.commit_batch.with_delay_cap(Duration::from_millis(half_building_time));
 
-		let mut transactions = Vec::new();
+		let mut transactions = OrderedTransactions::new(self.ordering);
 
 		let batch_id = LOGGING_UID.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
 		loop {
-			let remaining = match half_building_time.checked_sub(start.elapsed().as_millis() as u64)
-			{
+			let remaining = match commit_batch.remaining(start.elapsed()) {
 				Some(remaining) => remaining,
 				None => {
-					// we have exceeded the half building time
+					// we have exceeded the batch delay
 					break;
 				}
 			};
 
-			match tokio::time::timeout(
-				Duration::from_millis(remaining),
-				self.transaction_receiver.recv(),
-			)
-			.await
-			{
+			match tokio::time::timeout(remaining, self.transaction_receiver.recv()).await {
 				Ok(transaction) => match transaction {
 					Some(transaction) => {
 						info!(
@@ -72,13 +87,10 @@ impl Task {
 							sequence_number = transaction.sequence_number(),
 							"received transaction",
 						);
-						let serialized_aptos_transaction = serde_json::to_vec(&transaction)?;
-						let movement_transaction = movement_types::transaction::Transaction::new(
-							serialized_aptos_transaction,
-							transaction.sequence_number(),
-						);
-						let serialized_transaction = serde_json::to_vec(&movement_transaction)?;
-						transactions.push(BlobWrite { data: serialized_transaction });
+						transactions.push(transaction, ());
+						if commit_batch.should_flush(transactions.len(), start.elapsed()) {
+							break;
+						}
 					}
 					None => {
 						// The transaction stream is closed, terminate the task.
@@ -91,14 +103,25 @@ impl Task {
 			}
 		}
 
-		if transactions.len() > 0 {
+		if !transactions.is_empty() {
 			info!(
 				target: "movement_timing",
 				batch_id = %batch_id,
 				transaction_count = transactions.len(),
 				"built_batch_write"
 			);
-			let batch_write = BatchWriteRequest { blobs: transactions };
+			let blobs = transactions
+				.drain()
+				.map(|(transaction, ())| {
+					let serialized_aptos_transaction = serde_json::to_vec(&transaction)?;
+					let movement_transaction = movement_types::transaction::Transaction::new(
+						serialized_aptos_transaction,
+						transaction.sequence_number(),
+					);
+					Ok(BlobWrite { data: serde_json::to_vec(&movement_transaction)? })
+				})
+				.collect::<Result<Vec<_>, anyhow::Error>>()?;
+			let batch_write = BatchWriteRequest { blobs };
 			// spawn the actual batch write request in the background
 			let mut da_light_node_client = self.da_light_node_client.clone();
 			tokio::spawn(async move {
@@ -54,6 +54,9 @@ pub trait DynOptFinExecutor {
 	/// Get block head height.
 	fn get_block_head_height(&self) -> Result<u64, anyhow::Error>;
 
+	/// Get the latest committed ledger version.
+	fn get_ledger_version(&self) -> Result<u64, anyhow::Error>;
+
 	/// Build block metadata for a timestamp
 	fn build_block_metadata(
 		&self,
@@ -1,4 +1,5 @@
 use aptos_api::{runtime::Apis, Context};
+use maptos_opt_executor::BoundAddrs;
 use tokio::try_join;
 
 use std::sync::Arc;
@@ -13,6 +14,11 @@ impl Services {
 		Services { opt, fin }
 	}
 
+	/// Where the services are bound, as they come up; read it before running them.
+	pub fn bound_addrs(&self) -> BoundAddrs {
+		self.opt.bound_addrs()
+	}
+
 	pub fn opt_api_context(&self) -> Arc<Context> {
 		self.opt.api_context()
 	}
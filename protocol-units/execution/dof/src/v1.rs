@@ -2,7 +2,7 @@ use crate::{
 	BlockMetadata, DynOptFinExecutor, ExecutableBlock, HashValue, MakeOptFinServices, Services,
 	SignedTransaction,
 };
-use maptos_execution_util::config::Config;
+use maptos_execution_util::config::{reload::ReloadSource, Config};
 use maptos_fin_view::FinalityView;
 use maptos_opt_executor::{Context as OptContext, Executor as OptExecutor};
 use movement_types::block::BlockCommitment;
@@ -36,6 +36,15 @@ impl Executor {
 		let executor = OptExecutor::try_from_config(config)?;
 		Ok(Self::new(executor))
 	}
+
+	/// Like [`Executor::try_from_config`], re-reading the config from `source` on SIGHUP.
+	pub fn try_from_config_reloaded_from(
+		config: &Config,
+		source: ReloadSource,
+	) -> Result<Self, anyhow::Error> {
+		let executor = OptExecutor::try_from_config(config)?.with_reload_source(source);
+		Ok(Self::new(executor))
+	}
 }
 
 impl MakeOptFinServices for Context {
@@ -63,12 +72,28 @@ impl DynOptFinExecutor for Executor {
 			opt_context.mempool_client_sender(),
 			config,
 			opt_context.node_config().clone(),
+			opt_context.bound_addrs(),
+			opt_context.runtime_config(),
 		);
 		let indexer_runtime = opt_context.run_indexer_grpc_service()?;
+		let reload = maptos_opt_executor::reload::on_sighup(opt_context.reloader());
+		let db_size = self.executor.db_size_monitor();
 		let background = async move {
 			// The indexer runtime should live as long as the Tx pipe.
 			let _indexer_runtime = indexer_runtime;
-			transaction_pipe.run().await?;
+			let db_size = async move {
+				match db_size {
+					Some(monitor) => monitor.run().await,
+					None => std::future::pending().await,
+				}
+			};
+			// Reloads never finish and the database check only fails,
+			// so the Tx pipe decides when the background is done.
+			tokio::select! {
+				result = transaction_pipe.run() => result?,
+				result = db_size => result?,
+				() = reload => {}
+			}
 			Ok(())
 		};
 		Ok((Context { opt_context, fin_service }, background))
@@ -109,6 +134,10 @@ impl DynOptFinExecutor for Executor {
 		self.executor.get_block_head_height()
 	}
 
+	fn get_ledger_version(&self) -> Result<u64, anyhow::Error> {
+		self.executor.get_ledger_version()
+	}
+
 	/// Build block metadata for a timestamp
 	fn build_block_metadata(
 		&self,
@@ -3,7 +3,8 @@ use aptos_api::Context;
 use aptos_config::config::NodeConfig;
 use aptos_mempool::MempoolClientSender;
 use aptos_storage_interface::{finality_view::FinalityView as AptosFinalityView, DbReader};
-use maptos_execution_util::config::Config;
+use maptos_execution_util::config::{reload::SharedRuntimeConfig, Config};
+use maptos_opt_executor::BoundAddrs;
 
 use std::sync::Arc;
 
@@ -19,12 +20,16 @@ impl FinalityView {
 		Self { inner }
 	}
 
-	/// Instantiate the API service for this finality view.
+	/// Instantiate the API service for this finality view,
+	/// recording where it is bound in `bound_addrs` once it starts
+	/// and reading its request timeout from `runtime_config` so reloads apply.
 	pub fn service(
 		&self,
 		mempool_client_sender: MempoolClientSender,
 		maptos_config: &Config,
 		node_config: NodeConfig,
+		bound_addrs: BoundAddrs,
+		runtime_config: SharedRuntimeConfig,
 	) -> Service {
 		let context = Arc::new(Context::new(
 			maptos_config.chain.maptos_chain_id,
@@ -33,11 +38,14 @@ impl FinalityView {
 			node_config,
 			None,
 		));
-		let listen_url = format!(
-			"{}:{}",
-			maptos_config.fin.fin_rest_listen_hostname, maptos_config.fin.fin_rest_listen_port,
-		);
-		Service::new(context, listen_url)
+		Service::new(
+			context,
+			&maptos_config.fin,
+			maptos_config.chain.shutdown_grace(),
+			maptos_config.is_read_only(),
+			bound_addrs,
+			runtime_config,
+		)
 	}
 
 	/// Retrieve the finalized block height.
@@ -84,6 +92,8 @@ mod tests {
 			context.mempool_client_sender(),
 			&context.config(),
 			context.node_config().clone(),
+			context.bound_addrs(),
+			context.runtime_config(),
 		);
 
 		// Initialize a root account using a predefined keypair and the test root address.
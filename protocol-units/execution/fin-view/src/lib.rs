@@ -1,5 +1,6 @@
 mod fin_view;
 mod service;
+mod view_limit;
 
 pub use fin_view::FinalityView;
 pub use service::Service;
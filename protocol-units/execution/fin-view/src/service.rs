@@ -3,23 +3,83 @@ use aptos_api::{
 	Context,
 };
 
-use futures::prelude::*;
-use poem::{http::Method, listener::TcpListener, middleware::Cors, EndpointExt, Route, Server};
+use crate::view_limit::ViewLimiter;
+use maptos_execution_util::config::{
+	endpoints::{self, ConnectionLimits},
+	fin,
+	reload::SharedRuntimeConfig,
+};
+use maptos_execution_util::listener;
+use maptos_opt_executor::{server_runtime, service, shutdown, BoundAddrs};
+use poem::{
+	http::{Method, StatusCode},
+	listener::Listener,
+	middleware::Cors,
+	Endpoint, EndpointExt, IntoResponse, Route, Server,
+};
 use tracing::info;
 
 use std::future::Future;
 use std::sync::Arc;
+use std::time::Duration;
 
 #[derive(Clone)]
 /// The API service for the finality view.
 pub struct Service {
 	context: Arc<Context>,
 	listen_url: String,
+	listen_hostname: String,
+	listen_port: u16,
+	/// Holds the request timeout, read for every request so reloads apply
+	runtime_config: SharedRuntimeConfig,
+	connection_limits: ConnectionLimits,
+	shutdown_grace: Duration,
+	read_only: bool,
+	worker_threads: usize,
+	enabled: bool,
+	/// Caps the view function calls executing at once
+	views: ViewLimiter,
+	bound_addrs: BoundAddrs,
+}
+
+/// Resolves when the process is asked to stop.
+async fn shutdown_signal() {
+	shutdown::signal().await;
+	info!("Shutdown requested, draining in-flight fin view requests");
+}
+
+/// The response to a view function call made while `MAPTOS_FIN_VIEW_API_MAX_CONCURRENT_VIEWS`
+/// calls are executing.
+fn views_exhausted() -> poem::Response {
+	(StatusCode::SERVICE_UNAVAILABLE, "too many view function calls in progress").into_response()
 }
 
 impl Service {
-	pub(crate) fn new(context: Arc<Context>, listen_url: String) -> Self {
-		Service { context, listen_url }
+	pub(crate) fn new(
+		context: Arc<Context>,
+		fin_config: &fin::Config,
+		shutdown_grace: Duration,
+		read_only: bool,
+		bound_addrs: BoundAddrs,
+		runtime_config: SharedRuntimeConfig,
+	) -> Self {
+		let listen_hostname = fin_config.fin_rest_listen_hostname.clone();
+		let listen_port = fin_config.fin_rest_listen_port;
+		let listen_url = format!("{}:{}", listen_hostname, listen_port);
+		Service {
+			context,
+			listen_url,
+			listen_hostname,
+			listen_port,
+			runtime_config,
+			connection_limits: fin_config.connection_limits(),
+			shutdown_grace,
+			read_only,
+			worker_threads: fin_config.worker_threads(),
+			enabled: fin_config.fin_rest_enabled,
+			views: ViewLimiter::new(fin_config.max_concurrent_views()),
+			bound_addrs,
+		}
 	}
 
 	pub fn get_apis(&self) -> Apis {
@@ -27,7 +87,11 @@ impl Service {
 	}
 
 	pub fn run(&self) -> impl Future<Output = Result<(), anyhow::Error>> + Send {
-		info!("Starting maptos-fin-view services at: {:?}", self.listen_url);
+		if self.enabled {
+			info!("Starting maptos-fin-view services at: {:?}", self.listen_url);
+		} else {
+			info!("MAPTOS_FIN_VIEW_ENABLED=false, not serving the maptos-fin-view API");
+		}
 
 		let api_service =
 			get_api_service(self.context.clone()).server(format!("http://{:?}", self.listen_url));
@@ -37,10 +101,55 @@ impl Service {
 		let cors = Cors::new()
 			.allow_methods(vec![Method::GET, Method::POST])
 			.allow_credentials(true);
-		let app = Route::new().nest("/v1", api_service).nest("/spec", ui).with(cors);
+		let runtime_config = self.runtime_config.clone();
+		let shutdown_grace = self.shutdown_grace;
+		let connection_limits = self.connection_limits;
+		let read_only = self.read_only;
+		let views = self.views.clone();
+		let app = Route::new().nest("/v1", api_service).nest("/spec", ui).with(cors).around(
+			move |endpoint, request| {
+				let request_timeout = runtime_config.load().fin_request_timeout;
+				let views = views.clone();
+				async move {
+					if read_only
+						&& service::submits_transactions(request.method(), request.uri().path())
+					{
+						return Ok(service::read_only_rejection());
+					}
+					let view_call =
+						service::calls_view_function(request.method(), request.uri().path());
+					// Held until the call has been answered.
+					let _view_slot = match view_call.then(|| views.try_acquire()) {
+						Some(Ok(slot)) => slot,
+						Some(Err(_)) => return Ok(views_exhausted()),
+						None => None,
+					};
+					match tokio::time::timeout(request_timeout, endpoint.call(request)).await {
+						Ok(response) => response.map(IntoResponse::into_response),
+						Err(_) => Ok(StatusCode::GATEWAY_TIMEOUT.into_response()),
+					}
+				}
+			},
+		);
 
-		Server::new(TcpListener::bind(self.listen_url.clone()))
-			.run(app)
-			.map_err(|e| anyhow::anyhow!("Server error: {:?}", e))
+		let listen_targets = endpoints::listen_targets(
+			"MAPTOS_FIN_VIEW_API_LISTEN_HOSTNAME",
+			&self.listen_hostname,
+			self.listen_port,
+		);
+		let enabled = self.enabled;
+		let bound_addrs = self.bound_addrs.clone();
+		server_runtime::run_on_own_runtime("maptos-fin-view", self.worker_threads, async move {
+			if !enabled {
+				return Ok(());
+			}
+			let acceptor =
+				listener::bind_all(listen_targets?, connection_limits)?.into_acceptor().await?;
+			bound_addrs.record_acceptor(BoundAddrs::FIN_VIEW, &acceptor);
+			Server::new_with_acceptor(acceptor)
+				.run_with_graceful_shutdown(app, shutdown_signal(), Some(shutdown_grace))
+				.await
+				.map_err(|e| anyhow::anyhow!("Server error: {:?}", e))
+		})
 	}
 }
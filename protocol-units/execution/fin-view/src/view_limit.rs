@@ -0,0 +1,81 @@
+//! Admission of view function calls against the configured cap on those executing at once.
+
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, TryAcquireError};
+
+use std::num::NonZeroUsize;
+use std::sync::Arc;
+
+/// Caps the view function calls executing at once, so that a burst of them cannot starve
+/// the other requests of the fin view server.
+///
+/// Calls over the cap are refused rather than queued. Clones share the cap.
+#[derive(Debug, Clone)]
+pub(crate) struct ViewLimiter {
+	slots: Option<Arc<Semaphore>>,
+}
+
+impl ViewLimiter {
+	/// A limiter admitting `max_concurrent` calls at once, or any number when `None`.
+	pub(crate) fn new(max_concurrent: Option<NonZeroUsize>) -> Self {
+		Self { slots: max_concurrent.map(|max| Arc::new(Semaphore::new(max.get()))) }
+	}
+
+	/// Takes a slot for one call, held until the permit is dropped, or fails when all are
+	/// taken. No permit is needed when calls are unlimited.
+	pub(crate) fn try_acquire(&self) -> Result<Option<OwnedSemaphorePermit>, TryAcquireError> {
+		match &self.slots {
+			Some(slots) => slots.clone().try_acquire_owned().map(Some),
+			None => Ok(None),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use std::sync::atomic::{AtomicUsize, Ordering};
+	use std::time::Duration;
+
+	#[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+	async fn test_limiter_caps_simultaneous_calls() {
+		let limiter = ViewLimiter::new(NonZeroUsize::new(4));
+		let executing = Arc::new(AtomicUsize::new(0));
+		let peak = Arc::new(AtomicUsize::new(0));
+		let barrier = Arc::new(tokio::sync::Barrier::new(16));
+
+		let calls: Vec<_> = (0..16)
+			.map(|_| {
+				let (limiter, executing, peak, barrier) =
+					(limiter.clone(), executing.clone(), peak.clone(), barrier.clone());
+				tokio::spawn(async move {
+					barrier.wait().await;
+					let Ok(_permit) = limiter.try_acquire() else {
+						return false;
+					};
+					let now = executing.fetch_add(1, Ordering::SeqCst) + 1;
+					peak.fetch_max(now, Ordering::SeqCst);
+					tokio::time::sleep(Duration::from_millis(50)).await;
+					executing.fetch_sub(1, Ordering::SeqCst);
+					true
+				})
+			})
+			.collect();
+		let mut admitted = 0;
+		for call in calls {
+			admitted += usize::from(call.await.unwrap());
+		}
+
+		assert!(peak.load(Ordering::SeqCst) <= 4);
+		assert!(admitted >= 1 && admitted < 16, "{} calls admitted", admitted);
+		// Slots are freed as the calls finish.
+		assert!(limiter.try_acquire().unwrap().is_some());
+	}
+
+	#[test]
+	fn test_unlimited_calls_need_no_permit() {
+		let limiter = ViewLimiter::new(None);
+		let held: Vec<_> = (0..1000).map(|_| limiter.try_acquire().unwrap()).collect();
+		assert!(held.iter().all(Option::is_none));
+	}
+}
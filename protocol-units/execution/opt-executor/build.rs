@@ -0,0 +1,4 @@
+fn main() {
+	// The commit `/version` reports is read with `option_env!`, so it must rebuild when it changes.
+	println!("cargo:rerun-if-env-changed=MAPTOS_GIT_COMMIT");
+}
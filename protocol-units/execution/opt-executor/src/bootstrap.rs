@@ -3,6 +3,7 @@ use aptos_config::config::StorageDirPaths;
 use aptos_crypto::ed25519::Ed25519PublicKey;
 use aptos_db::AptosDB;
 use aptos_executor::db_bootstrapper;
+use aptos_framework::ReleaseBundle;
 use aptos_storage_interface::DbReaderWriter;
 use aptos_types::{
 	chain_id::ChainId,
@@ -15,15 +16,63 @@ use aptos_vm_genesis::{
 	default_gas_schedule, encode_genesis_change_set, GenesisConfiguration, TestValidator, Validator,
 };
 
+use anyhow::Context;
+
 use std::path::Path;
 
-fn genesis_change_set_and_validators(
+/// Where the genesis of an empty database comes from.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GenesisSource<'a> {
+	/// A BCS-encoded genesis transaction, used as is.
+	pub genesis_blob_path: Option<&'a Path>,
+	/// A framework release bundle to encode the built-in genesis with.
+	pub framework_path: Option<&'a Path>,
+}
+
+impl GenesisSource<'_> {
+	fn genesis_transaction(
+		&self,
+		chain_id: ChainId,
+		test_validators: &[TestValidator],
+		public_key: &Ed25519PublicKey,
+	) -> Result<Transaction, anyhow::Error> {
+		if let Some(path) = self.genesis_blob_path {
+			tracing::info!("Bootstrapping from the genesis transaction at {}", path.display());
+			let bytes = std::fs::read(path)
+				.with_context(|| format!("failed to read genesis blob {}", path.display()))?;
+			let transaction: Transaction = bcs::from_bytes(&bytes)
+				.with_context(|| format!("failed to decode genesis blob {}", path.display()))?;
+			anyhow::ensure!(
+				matches!(transaction, Transaction::GenesisTransaction(_)),
+				"{} does not hold a genesis transaction",
+				path.display()
+			);
+			return Ok(transaction);
+		}
+		let genesis = match self.framework_path {
+			Some(path) => {
+				tracing::info!("Encoding genesis with the framework at {}", path.display());
+				let framework = ReleaseBundle::read(path.to_path_buf())
+					.with_context(|| format!("failed to read framework {}", path.display()))?;
+				genesis_change_set(chain_id, test_validators, public_key, &framework)
+			}
+			None => genesis_change_set(
+				chain_id,
+				test_validators,
+				public_key,
+				aptos_cached_packages::head_release_bundle(),
+			),
+		};
+		Ok(Transaction::GenesisTransaction(WriteSetPayload::Direct(genesis)))
+	}
+}
+
+fn genesis_change_set(
 	chain_id: ChainId,
-	count: Option<usize>,
+	test_validators: &[TestValidator],
 	public_key: &Ed25519PublicKey,
-) -> (ChangeSet, Vec<TestValidator>) {
-	let framework = aptos_cached_packages::head_release_bundle();
-	let test_validators = TestValidator::new_test_set(count, Some(100_000_000));
+	framework: &ReleaseBundle,
+) -> ChangeSet {
 	let validators_: Vec<Validator> = test_validators.iter().map(|t| t.data.clone()).collect();
 	let validators = &validators_;
 
@@ -32,7 +81,7 @@ fn genesis_change_set_and_validators(
 	// This will last several centuries.
 	const EPOCH_DURATION_SECS: u64 = 60 * 60 * 24 * 1024 * 128;
 
-	let genesis = encode_genesis_change_set(
+	encode_genesis_change_set(
 		&public_key,
 		validators,
 		framework,
@@ -60,16 +109,19 @@ fn genesis_change_set_and_validators(
 		&OnChainConsensusConfig::default_for_genesis(),
 		&OnChainExecutionConfig::default_for_genesis(),
 		&default_gas_schedule(),
-	);
-	(genesis, test_validators)
+	)
 }
 
 /// Bootstrap a database with a genesis transaction if it is empty.
+///
+/// A custom genesis must name the same test validator as the built-in one,
+/// since the node signs its blocks with that validator's key.
 pub fn maybe_bootstrap_empty_db(
 	config: &NodeConfig,
 	db_dir: impl AsRef<Path> + Clone,
 	chain_id: ChainId,
 	public_key: &Ed25519PublicKey,
+	genesis_source: GenesisSource<'_>,
 ) -> Result<(DbReaderWriter, ValidatorSigner), anyhow::Error> {
 	let aptos_db = AptosDB::open(
 		StorageDirPaths::from_path(db_dir.clone()),
@@ -82,8 +134,7 @@ pub fn maybe_bootstrap_empty_db(
 	)?;
 
 	let db_rw = DbReaderWriter::new(aptos_db);
-	let (genesis, validators) = genesis_change_set_and_validators(chain_id, Some(1), public_key);
-	let genesis_txn = Transaction::GenesisTransaction(WriteSetPayload::Direct(genesis));
+	let validators = TestValidator::new_test_set(Some(1), Some(100_000_000));
 	let validator_signer =
 		ValidatorSigner::new(validators[0].data.owner_address, validators[0].consensus_key.clone());
 
@@ -98,6 +149,8 @@ pub fn maybe_bootstrap_empty_db(
 			// context does not exist
 			// simply continue
 			tracing::info!("No ledger info found, bootstrapping DB.");
+			let genesis_txn =
+				genesis_source.genesis_transaction(chain_id, &validators, public_key)?;
 			let waypoint = db_bootstrapper::generate_waypoint::<AptosVM>(&db_rw, &genesis_txn)?;
 			db_bootstrapper::maybe_bootstrap::<AptosVM>(&db_rw, &genesis_txn, waypoint)?
 				.ok_or(anyhow::anyhow!("Failed to bootstrap DB"))?;
@@ -0,0 +1,95 @@
+//! The addresses the servers actually bound, for configs which ask for port 0.
+
+use poem::listener::Acceptor;
+use tokio::sync::Notify;
+
+use std::collections::BTreeMap;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+/// The socket addresses each server bound, named as in [`BoundAddrs::REST`] and the like.
+///
+/// A server is listed once it has bound its listener, so a configured port of 0
+/// can be read back as the port the system picked. Clones share the addresses.
+#[derive(Debug, Clone, Default)]
+pub struct BoundAddrs {
+	addrs: Arc<Mutex<BTreeMap<&'static str, Vec<SocketAddr>>>>,
+	bound: Arc<Notify>,
+}
+
+impl BoundAddrs {
+	pub const REST: &'static str = "REST";
+	pub const FIN_VIEW: &'static str = "fin view";
+	pub const INDEXER_GRPC: &'static str = "indexer gRPC";
+	pub const HEALTH: &'static str = "health";
+	pub const METRICS: &'static str = "metrics";
+
+	/// Records the addresses `service` is bound to; Unix domain sockets are not listed.
+	pub(crate) fn record(&self, service: &'static str, addrs: Vec<SocketAddr>) {
+		self.addrs
+			.lock()
+			.expect("no thread panics holding the bound addresses")
+			.insert(service, addrs);
+		self.bound.notify_waiters();
+	}
+
+	/// Records the TCP addresses `acceptor` accepts on.
+	pub fn record_acceptor(&self, service: &'static str, acceptor: &impl Acceptor) {
+		let addrs = acceptor
+			.local_addr()
+			.iter()
+			.filter_map(|addr| addr.as_socket_addr().copied())
+			.collect();
+		self.record(service, addrs);
+	}
+
+	/// The addresses `service` is bound to, or `None` if it has not bound them yet.
+	pub fn get(&self, service: &str) -> Option<Vec<SocketAddr>> {
+		self.addrs
+			.lock()
+			.expect("no thread panics holding the bound addresses")
+			.get(service)
+			.cloned()
+	}
+
+	/// Waits until `service` has bound its addresses.
+	pub async fn wait_for(&self, service: &str) -> Vec<SocketAddr> {
+		loop {
+			// Registered before checking, so a recording in between is not missed.
+			let bound = self.bound.notified();
+			if let Some(addrs) = self.get(service) {
+				return addrs;
+			}
+			bound.await;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use maptos_execution_util::config::endpoints::{ConnectionLimits, ListenTarget};
+	use maptos_execution_util::listener;
+	use poem::listener::Listener;
+
+	#[tokio::test]
+	async fn test_port_zero_resolves_to_the_bound_port() -> Result<(), anyhow::Error> {
+		let bound_addrs = BoundAddrs::default();
+		assert_eq!(bound_addrs.get(BoundAddrs::REST), None);
+
+		let target = ListenTarget::Tcp("127.0.0.1:0".parse()?);
+		let acceptor = listener::bind(target, ConnectionLimits::new(0, 0))?.into_acceptor().await?;
+		let waiter = tokio::spawn({
+			let bound_addrs = bound_addrs.clone();
+			async move { bound_addrs.wait_for(BoundAddrs::REST).await }
+		});
+		bound_addrs.record_acceptor(BoundAddrs::REST, &acceptor);
+
+		let addrs = waiter.await?;
+		assert_eq!(addrs.len(), 1);
+		assert_ne!(addrs[0].port(), 0);
+		// The reported port is the one accepting connections.
+		tokio::net::TcpStream::connect(addrs[0]).await?;
+		Ok(())
+	}
+}
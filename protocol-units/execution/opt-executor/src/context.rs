@@ -1,6 +1,9 @@
+use crate::{BoundAddrs, Metrics, Warmup};
+
 use aptos_config::config::NodeConfig;
 use aptos_mempool::MempoolClientSender;
 use aptos_storage_interface::{DbReader, DbReaderWriter};
+use maptos_execution_util::config::reload::{ConfigReloader, ReloadSource, SharedRuntimeConfig};
 use maptos_execution_util::config::Config;
 
 use std::sync::Arc;
@@ -11,6 +14,10 @@ pub struct Context {
 	pub(crate) mempool_client_sender: MempoolClientSender,
 	pub(crate) maptos_config: Config,
 	pub(crate) node_config: NodeConfig,
+	pub(crate) bound_addrs: BoundAddrs,
+	pub(crate) reloader: Arc<ConfigReloader>,
+	pub(crate) metrics: Metrics,
+	pub(crate) warmup: Warmup,
 }
 
 impl Context {
@@ -19,8 +26,21 @@ impl Context {
 		mempool_client_sender: MempoolClientSender,
 		maptos_config: Config,
 		node_config: NodeConfig,
+		metrics: Metrics,
+		reload_source: ReloadSource,
 	) -> Self {
-		Context { db, mempool_client_sender, maptos_config, node_config }
+		let reloader = Arc::new(ConfigReloader::new(maptos_config.clone(), reload_source));
+		let warmup = Warmup::new(maptos_config.preload().is_some());
+		Context {
+			db,
+			mempool_client_sender,
+			maptos_config,
+			node_config,
+			bound_addrs: BoundAddrs::default(),
+			reloader,
+			metrics,
+			warmup,
+		}
 	}
 
 	/// Returns a reference on the data store reader.
@@ -40,4 +60,29 @@ impl Context {
 	pub fn node_config(&self) -> &NodeConfig {
 		&self.node_config
 	}
+
+	/// Where the services started from this context are bound, as they come up.
+	pub fn bound_addrs(&self) -> BoundAddrs {
+		self.bound_addrs.clone()
+	}
+
+	/// Reloads the reloadable fields of the config from where it was loaded.
+	pub fn reloader(&self) -> Arc<ConfigReloader> {
+		Arc::clone(&self.reloader)
+	}
+
+	/// The reloadable settings the services read, updated by every reload.
+	pub fn runtime_config(&self) -> SharedRuntimeConfig {
+		self.reloader.shared()
+	}
+
+	/// The counters served on `/metrics`, shared by the services which update them.
+	pub fn metrics(&self) -> Metrics {
+		self.metrics.clone()
+	}
+
+	/// Whether the caches are warm, which they are at once unless state is preloaded.
+	pub fn warmup(&self) -> Warmup {
+		self.warmup.clone()
+	}
 }
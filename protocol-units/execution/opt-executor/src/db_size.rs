@@ -0,0 +1,149 @@
+//! Watching the disk the Aptos databases use against `MAPTOS_MAX_DB_SIZE_BYTES`.
+
+use maptos_execution_util::config::chain;
+
+use tracing::{error, warn};
+
+use std::num::NonZeroU64;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+/// How often the databases are measured while the node runs.
+pub const DB_SIZE_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// The bytes the files under `dir` take up, 0 while it does not exist.
+pub fn dir_size(dir: &Path) -> Result<u64, std::io::Error> {
+	let entries = match std::fs::read_dir(dir) {
+		Ok(entries) => entries,
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+		Err(e) => return Err(e),
+	};
+	let mut size = 0;
+	for entry in entries {
+		let entry = entry?;
+		let file_type = entry.file_type()?;
+		if file_type.is_dir() {
+			size += dir_size(&entry.path())?;
+		} else if file_type.is_file() {
+			size += entry.metadata()?.len();
+		}
+	}
+	Ok(size)
+}
+
+/// Measures the databases periodically once they were opened.
+///
+/// Aptos fixes its pruner settings when the databases are opened, and the writer the executor
+/// holds has no way to start pruning afterwards. Storage nearing the cap while pruning is off
+/// therefore stops the node, which is the only way to prune: on restart the databases are
+/// opened with pruning forced on. Nothing is lost by stopping, as every committed block stays
+/// on disk and the DA is read again from the synced height. With pruning already on, the node
+/// keeps running and warns, as the prune windows are the least it keeps.
+#[derive(Debug, Clone)]
+pub struct DbSizeMonitor {
+	db_path: PathBuf,
+	config: chain::Config,
+	pruning_enabled: bool,
+}
+
+impl DbSizeMonitor {
+	/// The monitor of the databases at `config.db_path()`, or `None` without a cap.
+	pub fn new(config: &chain::Config, pruning_enabled: bool) -> Option<Self> {
+		(config.maptos_max_db_size_bytes != 0).then(|| Self {
+			db_path: config.db_path(),
+			config: config.clone(),
+			pruning_enabled,
+		})
+	}
+
+	fn max_db_size(&self) -> NonZeroU64 {
+		NonZeroU64::new(self.config.maptos_max_db_size_bytes).unwrap_or(NonZeroU64::MIN)
+	}
+
+	/// Measures the databases once, failing when the node should stop to prune them.
+	pub fn check(&self) -> Result<(), anyhow::Error> {
+		self.check_used(dir_size(&self.db_path)?)
+	}
+
+	fn check_used(&self, used: u64) -> Result<(), anyhow::Error> {
+		if !self.config.db_size_near_cap(used) {
+			return Ok(());
+		}
+		if self.pruning_enabled {
+			warn!(
+				"The databases at {} use {} of MAPTOS_MAX_DB_SIZE_BYTES={} bytes despite pruning \
+				 to the windows; lower the prune windows or raise the cap",
+				self.db_path.display(),
+				used,
+				self.max_db_size()
+			);
+			return Ok(());
+		}
+		error!(
+			"The databases at {} use {} of MAPTOS_MAX_DB_SIZE_BYTES={} bytes with pruning off, \
+			 stopping the node for it to restart with pruning on",
+			self.db_path.display(),
+			used,
+			self.max_db_size()
+		);
+		anyhow::bail!(
+			"the databases at {} use {} of the {} bytes allowed, restart the node to prune them",
+			self.db_path.display(),
+			used,
+			self.max_db_size()
+		)
+	}
+
+	/// Checks the databases every [`DB_SIZE_CHECK_INTERVAL`], returning only once the node
+	/// should stop.
+	pub async fn run(self) -> Result<(), anyhow::Error> {
+		let mut interval = tokio::time::interval(DB_SIZE_CHECK_INTERVAL);
+		loop {
+			interval.tick().await;
+			// The walk of the database directories blocks on every file it reads.
+			let db_path = self.db_path.clone();
+			let used = tokio::task::spawn_blocking(move || dir_size(&db_path)).await??;
+			self.check_used(used)?;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_storage_near_the_cap_stops_the_node_unless_pruning() -> Result<(), anyhow::Error> {
+		let tempdir = tempfile::tempdir()?;
+		let mut config = chain::Config::default();
+		config.maptos_data_dir = tempdir.path().to_path_buf();
+		config.maptos_max_db_size_bytes = 1000;
+		std::fs::create_dir_all(config.db_path().join("ledger_db"))?;
+		std::fs::write(config.db_path().join("ledger_db").join("000001.sst"), vec![0; 800])?;
+
+		let monitor = DbSizeMonitor::new(&config, false).expect("the size is capped");
+		monitor.check()?;
+
+		std::fs::write(config.db_path().join("000002.log"), vec![0; 100])?;
+		assert!(monitor.check().is_err());
+		DbSizeMonitor::new(&config, true).expect("the size is capped").check()?;
+
+		config.maptos_max_db_size_bytes = 0;
+		assert!(DbSizeMonitor::new(&config, false).is_none());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_monitor_stops_the_node_from_its_runtime() -> Result<(), anyhow::Error> {
+		let tempdir = tempfile::tempdir()?;
+		let mut config = chain::Config::default();
+		config.maptos_data_dir = tempdir.path().to_path_buf();
+		config.maptos_max_db_size_bytes = 1000;
+		std::fs::create_dir_all(config.db_path())?;
+		std::fs::write(config.db_path().join("000001.sst"), vec![0; 1000])?;
+
+		let monitor = DbSizeMonitor::new(&config, false).expect("the size is capped");
+		assert!(monitor.run().await.is_err());
+		Ok(())
+	}
+}
@@ -100,6 +100,11 @@ impl Executor {
 		Ok(new_block_event.height)
 	}
 
+	/// Gets the version of the latest committed ledger info.
+	pub fn get_ledger_version(&self) -> Result<u64, anyhow::Error> {
+		Ok(self.db().reader.get_latest_ledger_info()?.ledger_info().version())
+	}
+
 	pub async fn revert_block_head_to(&self, block_height: u64) -> Result<(), anyhow::Error> {
 		let (_start_ver, end_ver, block_event) =
 			self.db().reader.get_block_info_by_height(block_height)?;
@@ -166,7 +171,7 @@ impl Executor {
 	/// This should only be used for testing. The data availability layer should provide an initial transaction that rolls over the genesis block.
 	pub async fn rollover_genesis_now(&self) -> Result<(), anyhow::Error> {
 		// rollover timestamp needs to be within the epoch, by  default above this is one hour, so below is 59 minutes
-		let rollover_timestamp = chrono::Utc::now().timestamp_micros() as u64;
+		let rollover_timestamp = self.config.chain.clock().now_micros();
 		self.rollover_genesis(
 			rollover_timestamp,
 			// rollover_timestamp - (59 * 60 * 1000 * 1000), // 60 minutes
@@ -270,7 +275,7 @@ mod tests {
 			executor.signer.author(),
 			vec![],
 			vec![],
-			chrono::Utc::now().timestamp_micros() as u64,
+			executor.config().chain.clock().now_micros(),
 		));
 		let tx = SignatureVerifiedTransaction::Valid(Transaction::UserTransaction(
 			create_signed_transaction(0, context.config().chain.maptos_chain_id.clone()),
@@ -318,7 +323,7 @@ mod tests {
 			// Clone the signer from the executor for signing the metadata.
 			let signer = executor.signer.clone();
 			// Get the current time in microseconds for the block timestamp.
-			let current_time_microseconds = chrono::Utc::now().timestamp_micros() as u64;
+			let current_time_microseconds = executor.config().chain.clock().now_micros();
 
 			// Create a transaction factory with the chain ID of the executor, used for creating transactions.
 			let tx_factory =
@@ -425,7 +430,7 @@ mod tests {
 			// Clone the signer from the executor for signing the metadata.
 			let signer = executor.signer.clone();
 			// Get the current time in microseconds for the block timestamp.
-			let current_time_microseconds = chrono::Utc::now().timestamp_micros() as u64;
+			let current_time_microseconds = executor.config().chain.clock().now_micros();
 
 			// Create a block metadata transaction.
 			let block_metadata = Transaction::BlockMetadata(BlockMetadata::new(
@@ -472,4 +477,75 @@ mod tests {
 
 		Ok(())
 	}
+
+	#[tokio::test]
+	async fn test_send_buffer_sizes_the_indexer_stream_channel() -> Result<(), anyhow::Error> {
+		let tempdir = tempfile::tempdir()?;
+		let mut config = maptos_execution_util::config::Config::default();
+		config.chain.maptos_private_key = Ed25519PrivateKey::generate_for_testing();
+		config.chain.maptos_data_dir = tempdir.path().to_path_buf();
+		config.chain.maptos_db_path.replace(tempdir.path().to_path_buf());
+		config.indexer.maptos_indexer_grpc_send_buffer_size = 7;
+		let executor = Executor::try_from_config(&config)?;
+		let (tx_sender, _tx_receiver) = mpsc::channel(1);
+		let (context, _transaction_pipe) = executor.background(tx_sender)?;
+
+		// The indexer gRPC service is bootstrapped with the node config of the context, and
+		// each stream's processor tasks send on a channel of this size.
+		assert_eq!(context.node_config().indexer_grpc.transaction_channel_size, 7);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_max_page_size_caps_every_rest_page() -> Result<(), anyhow::Error> {
+		let tempdir = tempfile::tempdir()?;
+		let mut config = maptos_execution_util::config::Config::default();
+		config.chain.maptos_private_key = Ed25519PrivateKey::generate_for_testing();
+		config.chain.maptos_data_dir = tempdir.path().to_path_buf();
+		config.chain.maptos_db_path.replace(tempdir.path().to_path_buf());
+		config.chain.maptos_rest_max_page_size = 25;
+		let executor = Executor::try_from_config(&config)?;
+
+		let api = &executor.node_config.api;
+		for max_page_size in [
+			api.max_transactions_page_size,
+			api.max_block_transactions_page_size,
+			api.max_events_page_size,
+			api.max_account_resources_page_size,
+			api.max_account_modules_page_size,
+		] {
+			assert_eq!(max_page_size, 25);
+		}
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_grpc_batch_size_sizes_the_stream_messages() -> Result<(), anyhow::Error> {
+		let tempdir = tempfile::tempdir()?;
+		let mut config = maptos_execution_util::config::Config::default();
+		config.chain.maptos_private_key = Ed25519PrivateKey::generate_for_testing();
+		config.chain.maptos_data_dir = tempdir.path().to_path_buf();
+		config.chain.maptos_db_path.replace(tempdir.path().to_path_buf());
+		config.indexer.maptos_indexer_grpc_batch_size = 250;
+		let executor = Executor::try_from_config(&config)?;
+
+		let indexer_grpc = &executor.node_config.indexer_grpc;
+		assert_eq!(indexer_grpc.processor_batch_size, 250);
+		assert_eq!(indexer_grpc.output_batch_size, 250);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_grpc_send_buffer_sizes_the_stream_channel() -> Result<(), anyhow::Error> {
+		let tempdir = tempfile::tempdir()?;
+		let mut config = maptos_execution_util::config::Config::default();
+		config.chain.maptos_private_key = Ed25519PrivateKey::generate_for_testing();
+		config.chain.maptos_data_dir = tempdir.path().to_path_buf();
+		config.chain.maptos_db_path.replace(tempdir.path().to_path_buf());
+		config.indexer.maptos_indexer_grpc_send_buffer_size = 2;
+		let executor = Executor::try_from_config(&config)?;
+
+		assert_eq!(executor.node_config.indexer_grpc.transaction_channel_size, 2);
+		Ok(())
+	}
 }
@@ -1,25 +1,28 @@
 use super::Executor;
-use crate::{bootstrap, Context, TransactionPipe};
+use crate::db_size::{dir_size, DbSizeMonitor};
+use crate::{
+	bootstrap, Context, InFlightTracker, Metrics, OverflowHandling, TransactionLimits,
+	TransactionPipe,
+};
 
 use aptos_config::config::NodeConfig;
 #[cfg(test)]
-use aptos_crypto::ed25519::Ed25519PrivateKey;
-use aptos_crypto::PrivateKey;
+use aptos_crypto::{ed25519::Ed25519PrivateKey, PrivateKey as _};
 use aptos_executor::block_executor::BlockExecutor;
 use aptos_mempool::MempoolClientRequest;
 use aptos_types::transaction::SignedTransaction;
+use aptos_vm::AptosVM;
 use futures::FutureExt;
-use maptos_execution_util::config::Config;
+use maptos_execution_util::config::{reload::ReloadSource, Config};
 
-use anyhow::Context as _;
 use futures::channel::mpsc as futures_mpsc;
 use tokio::sync::mpsc;
+use tracing::{info, warn};
 
 #[cfg(test)]
 use tempfile::TempDir;
 
-use std::net::ToSocketAddrs;
-use std::sync::{atomic::AtomicU64, Arc};
+use std::sync::Arc;
 
 // Executor channel size.
 // Allow 2^16 transactions before appling backpressure given theoretical maximum TPS of 170k.
@@ -31,23 +34,68 @@ impl Executor {
 		let mut node_config = NodeConfig::default();
 
 		// pruning config
-		node_config.storage.storage_pruner_config.ledger_pruner_config.prune_window =
+		// Databases nearing MAPTOS_MAX_DB_SIZE_BYTES are pruned to the prune windows even when
+		// pruning is disabled, as the windows are the least the node keeps.
+		let storage_dir = maptos_config.chain.storage_dir();
+		let mut pruning_enabled = maptos_config.chain.pruning_enabled();
+		if let Some(max_db_size) = maptos_config.max_db_size() {
+			let db_path = maptos_config.chain.db_path();
+			let used = dir_size(&db_path)?;
+			if maptos_config.chain.db_size_near_cap(used) {
+				warn!(
+					"The databases at {} use {} of MAPTOS_MAX_DB_SIZE_BYTES={} bytes, pruning them to the windows",
+					db_path.display(),
+					used,
+					max_db_size
+				);
+				pruning_enabled = true;
+			}
+		}
+		let pruner_config = &mut node_config.storage.storage_pruner_config;
+		pruner_config.ledger_pruner_config.enable = pruning_enabled;
+		pruner_config.ledger_pruner_config.prune_window =
 			maptos_config.chain.maptos_ledger_prune_window;
-		node_config
-			.storage
-			.storage_pruner_config
-			.state_merkle_pruner_config
-			.prune_window = maptos_config.chain.maptos_state_merkle_prune_window;
-		node_config
-			.storage
-			.storage_pruner_config
-			.epoch_snapshot_pruner_config
-			.prune_window = maptos_config.chain.maptos_epoch_snapshot_prune_window;
+		pruner_config.state_merkle_pruner_config.enable = pruning_enabled;
+		pruner_config.state_merkle_pruner_config.prune_window =
+			maptos_config.chain.maptos_state_merkle_prune_window;
+		pruner_config.epoch_snapshot_pruner_config.enable = pruning_enabled;
+		pruner_config.epoch_snapshot_pruner_config.prune_window =
+			maptos_config.chain.maptos_epoch_snapshot_prune_window;
+		if !pruning_enabled {
+			info!("MAPTOS_ENABLE_PRUNING=false, keeping every version of the storage");
+		}
+
+		// rocksdb config
+		for rocksdb_config in [
+			&mut node_config.storage.rocksdb_configs.ledger_db_config,
+			&mut node_config.storage.rocksdb_configs.state_merkle_db_config,
+			&mut node_config.storage.rocksdb_configs.state_kv_db_config,
+		] {
+			rocksdb_config.block_cache_size = maptos_config.chain.db_block_cache_bytes();
+			// Aptos does not expose the memtable size; RocksDB flushes the memtables
+			// once the write-ahead log outgrows this, which bounds the buffered writes.
+			rocksdb_config.max_total_wal_size = maptos_config.chain.db_max_wal_bytes();
+		}
+
+		// Block-STM reads its thread count from a process-wide setting, fixed at the first call.
+		let execution_concurrency = maptos_config.execution_concurrency();
+		node_config.execution.concurrency_level =
+			u16::try_from(execution_concurrency).unwrap_or(u16::MAX);
+		AptosVM::set_concurrency_level_once(execution_concurrency);
+
+		// The REST handlers lower larger page sizes to these, whichever query parameter or
+		// route the page is asked for by.
+		let max_page_size = maptos_config.chain.rest_max_page_size();
+		node_config.api.max_transactions_page_size = max_page_size;
+		node_config.api.max_block_transactions_page_size = max_page_size;
+		node_config.api.max_events_page_size = max_page_size;
+		node_config.api.max_account_resources_page_size = max_page_size;
+		node_config.api.max_account_modules_page_size = max_page_size;
 
 		node_config.indexer.enabled = true;
 		// indexer config
 		node_config.indexer.postgres_uri =
-			Some(maptos_config.indexer_processor.postgres_connection_string.clone());
+			Some(maptos_config.postgres_connection_string().to_string());
 		node_config.indexer.processor = Some("default_processor".to_string());
 		node_config.indexer.check_chain_id = Some(false);
 		node_config.indexer.skip_migrations = Some(false);
@@ -57,41 +105,66 @@ impl Executor {
 		node_config.indexer.batch_size = Some(8);
 		node_config.indexer.gap_lookback_versions = Some(4);
 
-		node_config.indexer_grpc.enabled = true;
+		node_config.indexer_grpc.enabled = maptos_config.indexer_grpc_enabled();
 
 		// indexer_grpc config
-		node_config.indexer_grpc.processor_batch_size = 4;
+		// Each processor task converts a batch of transactions and sends it on in messages of up
+		// to the output batch size, so both are sized for a message to carry up to a full batch.
+		let batch_size = u16::try_from(maptos_config.indexer.batch_size()).unwrap_or(u16::MAX);
+		node_config.indexer_grpc.processor_batch_size = batch_size;
 		node_config.indexer_grpc.processor_task_count = 4;
-		node_config.indexer_grpc.output_batch_size = 4;
-		node_config.indexer_grpc.address = (
-			maptos_config.indexer.maptos_indexer_grpc_listen_hostname.as_str(),
-			maptos_config.indexer.maptos_indexer_grpc_listen_port,
-		)
-			.to_socket_addrs()?
-			.next()
-			.context("failed to resolve the value of maptos_indexer_grpc_listen_hostname")?;
+		node_config.indexer_grpc.output_batch_size = batch_size;
+		// The stream queues at most this many messages for a client, and waits for a slow one.
+		node_config.indexer_grpc.transaction_channel_size =
+			maptos_config.indexer_grpc_send_buffer().get();
+		// The indexer gRPC server binds its addresses itself, see `run_indexer_grpc_service`.
 		node_config.indexer_grpc.use_data_service_interface = true;
 
 		// indexer table info config
 		node_config.indexer_table_info.enabled = true;
-		node_config.storage.dir = "./.movement/maptos-storage".to_string().into();
+		maptos_config.ensure_data_dir()?;
+		maptos_config.chain.ensure_storage_not_moved()?;
+		info!("Storing the node state at {}", storage_dir.display());
+		node_config.storage.dir = storage_dir;
 		node_config.storage.set_data_dir(node_config.storage.dir.clone());
 
 		let (db, signer) = bootstrap::maybe_bootstrap_empty_db(
 			&node_config,
-			maptos_config.chain.maptos_db_path.as_ref().context("No db path provided.")?,
+			&maptos_config.chain.db_path(),
 			maptos_config.chain.maptos_chain_id.clone(),
+			// The validator signs blocks with the key read as Ed25519, whatever the scheme its
+			// account authenticates under.
 			&maptos_config.chain.maptos_private_key.public_key(),
+			bootstrap::GenesisSource {
+				genesis_blob_path: maptos_config.chain.maptos_genesis_blob_path.as_deref(),
+				framework_path: maptos_config.chain.maptos_framework_path.as_deref(),
+			},
 		)?;
 		Ok(Self {
 			block_executor: Arc::new(BlockExecutor::new(db.clone())),
 			signer,
-			transactions_in_flight: Arc::new(AtomicU64::new(0)),
+			transactions_in_flight: InFlightTracker::from_config(&maptos_config.load_shedding),
 			config: maptos_config.clone(),
 			node_config: node_config.clone(),
+			reload_source: ReloadSource::Env,
 		})
 	}
 
+	/// This executor with its config re-read from `source` on SIGHUP, rather than from the
+	/// environment.
+	pub fn with_reload_source(mut self, source: ReloadSource) -> Self {
+		self.reload_source = source;
+		self
+	}
+
+	/// The periodic check of the databases against `MAPTOS_MAX_DB_SIZE_BYTES`, or `None`
+	/// without a cap.
+	pub fn db_size_monitor(&self) -> Option<DbSizeMonitor> {
+		let pruning_enabled =
+			self.node_config.storage.storage_pruner_config.ledger_pruner_config.enable;
+		DbSizeMonitor::new(&self.config.chain, pruning_enabled)
+	}
+
 	pub fn try_from_config(maptos_config: &Config) -> Result<Self, anyhow::Error> {
 		Self::bootstrap(maptos_config)
 	}
@@ -105,7 +178,8 @@ impl Executor {
 		let mut maptos_config = Config::default();
 		maptos_config.chain.maptos_private_key = private_key;
 
-		// replace the db path with the temporary directory
+		// root the storage and the db under the temporary directory
+		maptos_config.chain.maptos_data_dir = tempdir.path().to_path_buf();
 		maptos_config.chain.maptos_db_path.replace(tempdir.path().to_path_buf());
 		let executor = Self::try_from_config(&maptos_config)?;
 		Ok((executor, maptos_config, tempdir))
@@ -130,8 +204,11 @@ impl Executor {
 			transaction_sender,
 			self.db().reader.clone(),
 			&node_config,
-			Arc::clone(&self.transactions_in_flight),
-			maptos_config.load_shedding.max_transactions_in_flight,
+			self.transactions_in_flight.clone(),
+			TransactionLimits::from_config(&maptos_config.chain),
+			OverflowHandling::from_config(&maptos_config),
+			maptos_config.mempool_ordering(),
+			maptos_config.chain.clock(),
 		);
 
 		let cx = Context::new(
@@ -139,6 +216,8 @@ impl Executor {
 			mempool_client_sender,
 			maptos_config.clone(),
 			node_config.clone(),
+			Metrics::new(self.transactions_in_flight.clone()),
+			self.reload_source.clone(),
 		);
 
 		Ok((cx, transaction_pipe))
@@ -12,8 +12,8 @@ use aptos_vm::AptosVM;
 
 use tracing::info;
 
-use maptos_execution_util::config::Config;
-use std::sync::atomic::{AtomicU64, Ordering};
+use crate::InFlightTracker;
+use maptos_execution_util::config::{reload::ReloadSource, Config};
 use std::sync::Arc;
 
 /// The `Executor` is responsible for executing blocks and managing the state of the execution
@@ -23,12 +23,14 @@ pub struct Executor {
 	pub block_executor: Arc<BlockExecutor<AptosVM>>,
 	/// The signer of the executor's transactions.
 	pub signer: ValidatorSigner,
-	// Shared reference on the counter of transactions in flight.
-	transactions_in_flight: Arc<AtomicU64>,
+	// The transactions in flight, shared with the transaction pipe.
+	transactions_in_flight: InFlightTracker,
 	// The config for the executor.
 	pub(crate) config: Config,
 	/// The node config derived from the maptos config.
 	pub(crate) node_config: NodeConfig,
+	/// Where the config is re-read from on SIGHUP.
+	pub(crate) reload_source: ReloadSource,
 }
 
 impl Executor {
@@ -41,19 +43,18 @@ impl Executor {
 	}
 
 	pub fn decrement_transactions_in_flight(&self, count: u64) {
-		// fetch sub mind the underflow
-		// a semaphore might be better here as this will rerun until the value does not change during the operation
-		self.transactions_in_flight
-			.fetch_update(Ordering::Relaxed, Ordering::Relaxed, |current| {
-				info!(
-					target: "movement_timing",
-					count,
-					current,
-					"decrementing_transactions_in_flight",
-				);
-				Some(current.saturating_sub(count))
-			})
-			.unwrap_or_else(|_| 0);
+		info!(
+			target: "movement_timing",
+			count,
+			current = self.transactions_in_flight.in_flight(),
+			"decrementing_transactions_in_flight",
+		);
+		self.transactions_in_flight.release(count);
+	}
+
+	/// The transactions admitted and not yet executed, for callers applying backpressure.
+	pub fn transactions_in_flight(&self) -> &InFlightTracker {
+		&self.transactions_in_flight
 	}
 
 	pub fn config(&self) -> &Config {
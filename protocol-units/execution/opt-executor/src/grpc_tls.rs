@@ -0,0 +1,127 @@
+//! A TLS front for the indexer gRPC server.
+//!
+//! The indexer gRPC bootstrap builds its own plaintext tonic server, so when TLS is configured
+//! that server is moved to a loopback port and this front serves the public address in its place,
+//! terminating TLS and piping every accepted connection through unchanged.
+//! With a client CA, every client needs a certificate signed by it, wherever it connects from;
+//! an indexer on the same host without one reads from the configured loopback port instead.
+
+use maptos_execution_util::config::tls::IndexerGrpcTls;
+
+use anyhow::Context;
+use tokio::net::{TcpListener, TcpStream};
+use tokio_rustls::rustls::server::AllowAnyAuthenticatedClient;
+use tokio_rustls::rustls::{Certificate, PrivateKey, RootCertStore, ServerConfig};
+use tokio_rustls::TlsAcceptor;
+use tracing::{debug, info, warn};
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+
+/// Terminates TLS on the public indexer gRPC addresses in front of the plaintext server.
+///
+/// Clones share the TLS setup, to serve each address with one of them.
+#[derive(Clone)]
+pub struct GrpcTlsFront {
+	/// Accepts every client, requiring a certificate when a client CA is set.
+	acceptor: TlsAcceptor,
+	upstream: SocketAddr,
+}
+
+impl GrpcTlsFront {
+	/// Loads the TLS material, failing early when any of it is unusable.
+	///
+	/// With a client CA configured, clients without a certificate signed by it are refused,
+	/// wherever they connect from.
+	pub fn new(tls: &IndexerGrpcTls, upstream: SocketAddr) -> Result<Self, anyhow::Error> {
+		let pem = tls.files.load()?;
+		let certs: Vec<Certificate> = rustls_pemfile::certs(&mut pem.cert.as_slice())?
+			.into_iter()
+			.map(Certificate)
+			.collect();
+		let key = rustls_pemfile::pkcs8_private_keys(&mut pem.key.as_slice())?
+			.into_iter()
+			.next()
+			.map(PrivateKey)
+			.context("no PKCS#8 private key found")?;
+
+		let config = match tls.load_client_ca()? {
+			Some(client_ca) => {
+				let mut roots = RootCertStore::empty();
+				for cert in rustls_pemfile::certs(&mut client_ca.as_slice())? {
+					roots.add(&Certificate(cert))?;
+				}
+				ServerConfig::builder()
+					.with_safe_defaults()
+					.with_client_cert_verifier(AllowAnyAuthenticatedClient::new(roots).boxed())
+					.with_single_cert(certs, key)?
+			}
+			None => ServerConfig::builder()
+				.with_safe_defaults()
+				.with_no_client_auth()
+				.with_single_cert(certs, key)?,
+		};
+		Ok(Self { acceptor: TlsAcceptor::from(Arc::new(config)), upstream })
+	}
+
+	/// Serves the front on `listener`, returning only when it cannot take it over.
+	pub async fn run(self, listener: std::net::TcpListener) -> Result<(), anyhow::Error> {
+		info!(
+			"Serving the indexer gRPC over TLS at {} in front of {}",
+			listener.local_addr()?,
+			self.upstream
+		);
+		listener.set_nonblocking(true)?;
+		let listener = TcpListener::from_std(listener)?;
+		loop {
+			let (client, remote_addr) = match listener.accept().await {
+				Ok(accepted) => accepted,
+				Err(e) => {
+					warn!("Failed to accept an indexer gRPC connection: {}", e);
+					continue;
+				}
+			};
+			let acceptor = self.acceptor.clone();
+			let upstream = self.upstream;
+			tokio::spawn(async move {
+				let piped = async {
+					let mut client = acceptor.accept(client).await?;
+					let mut server = TcpStream::connect(upstream).await?;
+					tokio::io::copy_bidirectional(&mut client, &mut server).await
+				};
+				// Failed handshakes, including clients without a trusted certificate, end up here.
+				if let Err(e) = piped.await {
+					debug!("Indexer gRPC connection from {} closed: {}", remote_addr, e);
+				}
+			});
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use maptos_execution_util::config::tls::TlsFiles;
+
+	use std::path::Path;
+
+	#[test]
+	fn test_front_loads_with_and_without_a_client_ca() -> Result<(), anyhow::Error> {
+		let testdata = Path::new(env!("CARGO_MANIFEST_DIR")).join("../util/testdata");
+		let mut tls = IndexerGrpcTls {
+			files: TlsFiles {
+				cert_path: testdata.join("server.crt"),
+				key_path: testdata.join("server.key"),
+			},
+			client_ca_path: Some(testdata.join("server.crt")),
+		};
+		GrpcTlsFront::new(&tls, "127.0.0.1:1".parse()?)?;
+
+		tls.client_ca_path = None;
+		GrpcTlsFront::new(&tls, "127.0.0.1:1".parse()?)?;
+
+		tls.client_ca_path = Some(testdata.join("server.key"));
+		assert!(GrpcTlsFront::new(&tls, "127.0.0.1:1".parse()?).is_err());
+		Ok(())
+	}
+}
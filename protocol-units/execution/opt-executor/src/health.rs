@@ -0,0 +1,261 @@
+use crate::{service::shutdown_signal, BoundAddrs, Context, Metrics, Warmup};
+
+use aptos_storage_interface::DbReader;
+use maptos_execution_util::config::{health::HealthMode, Config};
+use maptos_execution_util::listener;
+
+use poem::{
+	handler,
+	http::StatusCode,
+	listener::{Acceptor, Listener},
+	web::Data,
+	EndpointExt, Route, Server,
+};
+use tokio::net::TcpStream;
+use tracing::{info, warn};
+
+use std::future::Future;
+use std::sync::Arc;
+use std::time::{Duration, UNIX_EPOCH};
+
+/// How long the readiness probe waits for the indexer gRPC server to accept a connection.
+const INDEXER_GRPC_PROBE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Serves `/healthz` and `/readyz` on a port of its own, without the API middleware.
+///
+/// In [`HealthMode::Tcp`] it serves nothing, and accepting a connection is the health check.
+#[derive(Clone)]
+pub struct HealthService {
+	// Where the health endpoint is served
+	listen_hostname: String,
+	listen_port: u16,
+	// Storage checked by the readiness probe
+	db_reader: Arc<dyn DbReader>,
+	// Config naming the indexer gRPC address and lag bound checked by the readiness probe
+	maptos_config: Config,
+	// The indexer lag checked by the readiness probe
+	metrics: Metrics,
+	// Where the servers, this one included, are bound once started
+	bound_addrs: BoundAddrs,
+	// Whether the caches are warm, which the readiness probe waits for
+	warmup: Warmup,
+}
+
+struct ReadinessProbe {
+	db_reader: Arc<dyn DbReader>,
+	// Whether the indexer gRPC service runs and is waited for
+	indexer_grpc_enabled: bool,
+	maptos_config: Config,
+	metrics: Metrics,
+	bound_addrs: BoundAddrs,
+	warmup: Warmup,
+}
+
+impl ReadinessProbe {
+	/// Lists the dependencies which are not up.
+	async fn failures(&self) -> Vec<&'static str> {
+		let mut failures = Vec::new();
+		if !self.warmup.is_done() {
+			failures.push("warmup");
+		}
+		match self.db_reader.get_latest_ledger_info_option() {
+			Ok(Some(ledger_info)) => {
+				// Genesis carries no time, and a node which has committed nothing since is idle
+				// rather than stuck.
+				let timestamp_usecs = ledger_info.ledger_info().timestamp_usecs();
+				let latest_block = UNIX_EPOCH + Duration::from_micros(timestamp_usecs);
+				let now = self.maptos_config.chain.clock().now();
+				if timestamp_usecs != 0 && self.maptos_config.block_age_exceeded(latest_block, now)
+				{
+					failures.push("block_age");
+				}
+			}
+			_ => failures.push("db"),
+		}
+		if self.indexer_grpc_enabled {
+			// Not yet bound counts as down.
+			let indexer_grpc_addrs =
+				self.bound_addrs.get(BoundAddrs::INDEXER_GRPC).unwrap_or_default();
+			let indexer_grpc_up = tokio::time::timeout(
+				INDEXER_GRPC_PROBE_TIMEOUT,
+				TcpStream::connect(indexer_grpc_addrs.as_slice()),
+			)
+			.await
+			.map_or(false, |connected| connected.is_ok());
+			if !indexer_grpc_up {
+				failures.push("indexer_grpc");
+			}
+		}
+		if self.maptos_config.indexer_lag_exceeded(self.metrics.indexer_lag()) {
+			failures.push("indexer_lag");
+		}
+		failures
+	}
+}
+
+#[handler]
+fn healthz() -> &'static str {
+	"ok"
+}
+
+#[handler]
+async fn readyz(Data(probe): Data<&Arc<ReadinessProbe>>) -> (StatusCode, String) {
+	let failures = probe.failures().await;
+	if failures.is_empty() {
+		(StatusCode::OK, "ready".to_string())
+	} else {
+		(StatusCode::SERVICE_UNAVAILABLE, format!("not ready: {}", failures.join(", ")))
+	}
+}
+
+impl HealthService {
+	/// Creates the health service, or returns `None` if it is disabled in the config.
+	pub fn new(cx: &Context) -> Option<Self> {
+		let maptos_config = &cx.maptos_config;
+		if !maptos_config.health_enabled() {
+			return None;
+		}
+		Some(HealthService {
+			listen_hostname: maptos_config.health.maptos_health_listen_hostname.clone(),
+			listen_port: maptos_config.health.maptos_health_listen_port,
+			db_reader: cx.db_reader(),
+			maptos_config: maptos_config.clone(),
+			metrics: cx.metrics(),
+			bound_addrs: cx.bound_addrs(),
+			warmup: cx.warmup(),
+		})
+	}
+
+	pub fn run(&self) -> impl Future<Output = Result<(), anyhow::Error>> + Send {
+		info!(
+			"Starting maptos-opt-executor health service at: {}:{}",
+			self.listen_hostname, self.listen_port
+		);
+
+		let listener = listener::bind_hostname(
+			"MAPTOS_HEALTH_LISTEN_HOSTNAME",
+			&self.listen_hostname,
+			self.listen_port,
+		);
+		let db_reader = self.db_reader.clone();
+		let shutdown_grace = self.maptos_config.chain.shutdown_grace();
+		let indexer_grpc_enabled = self.maptos_config.indexer_grpc_enabled();
+		let maptos_config = self.maptos_config.clone();
+		let metrics = self.metrics.clone();
+		let bound_addrs = self.bound_addrs.clone();
+		let warmup = self.warmup.clone();
+		async move {
+			let mut acceptor = listener?.into_acceptor().await?;
+			bound_addrs.record_acceptor(BoundAddrs::HEALTH, &acceptor);
+			if maptos_config.health_mode() == HealthMode::Tcp {
+				let shutdown = shutdown_signal();
+				tokio::pin!(shutdown);
+				loop {
+					tokio::select! {
+						// Dropping the connection closes it.
+						accepted = acceptor.accept() => if let Err(e) = accepted {
+							warn!("Failed to accept a health check connection: {:?}", e);
+						},
+						_ = &mut shutdown => return Ok(()),
+					}
+				}
+			}
+			let probe = Arc::new(ReadinessProbe {
+				db_reader,
+				indexer_grpc_enabled,
+				maptos_config,
+				metrics,
+				bound_addrs,
+				warmup,
+			});
+			let app = Route::new()
+				.at("/healthz", poem::get(healthz))
+				.at("/readyz", poem::get(readyz))
+				.data(probe);
+			Server::new_with_acceptor(acceptor)
+				.run_with_graceful_shutdown(app, shutdown_signal(), Some(shutdown_grace))
+				.await
+				.map_err(|e| anyhow::anyhow!("Health server error: {:?}", e))
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Executor;
+	use aptos_vm_genesis::GENESIS_KEYPAIR;
+	use tokio::io::{AsyncReadExt, AsyncWriteExt};
+	use tokio::sync::mpsc;
+
+	/// Starts a health service in `mode`, returning where it is bound and the directory of
+	/// its executor's storage.
+	async fn serve(
+		mode: HealthMode,
+	) -> Result<(Vec<std::net::SocketAddr>, tempfile::TempDir), anyhow::Error> {
+		let (tx_sender, _tx_receiver) = mpsc::channel(16);
+		let (executor, _config, tempdir) = Executor::try_test_default(GENESIS_KEYPAIR.0.clone())?;
+		let (mut context, _transaction_pipe) = executor.background(tx_sender)?;
+		context.maptos_config.health.maptos_health_enabled = true;
+		context.maptos_config.health.maptos_health_listen_hostname = "127.0.0.1".to_string();
+		context.maptos_config.health.maptos_health_listen_port = 0;
+		context.maptos_config.health.maptos_health_mode = mode;
+		let service = HealthService::new(&context).expect("health enabled");
+		tokio::spawn(service.run());
+		Ok((context.bound_addrs().wait_for(BoundAddrs::HEALTH).await, tempdir))
+	}
+
+	#[tokio::test]
+	async fn test_tcp_mode_accepts_and_closes_connections() -> Result<(), anyhow::Error> {
+		let (addrs, _tempdir) = serve(HealthMode::Tcp).await?;
+		let mut stream = TcpStream::connect(addrs.as_slice()).await?;
+		let mut response = Vec::new();
+		tokio::time::timeout(Duration::from_secs(5), stream.read_to_end(&mut response)).await??;
+		assert!(response.is_empty());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_not_ready_until_the_warmup_finishes() -> Result<(), anyhow::Error> {
+		let (tx_sender, _tx_receiver) = mpsc::channel(16);
+		let (executor, _config, _tempdir) = Executor::try_test_default(GENESIS_KEYPAIR.0.clone())?;
+		let (context, _transaction_pipe) = executor.background(tx_sender)?;
+		let warmup = Warmup::new(true);
+		let probe = ReadinessProbe {
+			db_reader: context.db_reader(),
+			indexer_grpc_enabled: false,
+			maptos_config: context.maptos_config.clone(),
+			metrics: context.metrics(),
+			bound_addrs: context.bound_addrs(),
+			warmup: warmup.clone(),
+		};
+		assert!(probe.failures().await.contains(&"warmup"));
+
+		// Stands in for preloading the state, which finishes the warmup once done.
+		let (started, start) = tokio::sync::oneshot::channel::<()>();
+		let simulated = tokio::spawn({
+			let warmup = warmup.clone();
+			async move {
+				let _ = start.await;
+				warmup.finish();
+			}
+		});
+		assert!(probe.failures().await.contains(&"warmup"));
+		let _ = started.send(());
+		simulated.await?;
+		assert!(!probe.failures().await.contains(&"warmup"));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_http_mode_serves_healthz() -> Result<(), anyhow::Error> {
+		let (addrs, _tempdir) = serve(HealthMode::Http).await?;
+		let mut stream = TcpStream::connect(addrs.as_slice()).await?;
+		stream.write_all(b"GET /healthz HTTP/1.0\r\n\r\n").await?;
+		let mut response = String::new();
+		stream.read_to_string(&mut response).await?;
+		assert_eq!(response.split(' ').nth(1), Some("200"), "unexpected response: {}", response);
+		assert_eq!(response.split("\r\n\r\n").nth(1), Some("ok"));
+		Ok(())
+	}
+}
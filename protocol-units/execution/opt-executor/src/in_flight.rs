@@ -0,0 +1,185 @@
+//! Admission of transactions against the configured bound on transactions in flight.
+
+use maptos_execution_util::config::load_shedding;
+
+use tokio::sync::Notify;
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Counts transactions in flight against the configured bound, so that submitters
+/// are refused a slot once it is reached instead of overflowing the queue.
+///
+/// A transaction is in flight from its admission until it has been executed.
+/// Clones share the count.
+#[derive(Debug, Clone)]
+pub struct InFlightTracker {
+	in_flight: Arc<AtomicU64>,
+	max_in_flight: u64,
+	// Wakes those waiting for a slot whenever slots are freed.
+	freed: Arc<Notify>,
+}
+
+impl InFlightTracker {
+	pub fn new(max_in_flight: u64) -> Self {
+		Self {
+			in_flight: Arc::new(AtomicU64::new(0)),
+			max_in_flight,
+			freed: Arc::new(Notify::new()),
+		}
+	}
+
+	pub fn from_config(config: &load_shedding::Config) -> Self {
+		Self::new(config.max_transactions_in_flight)
+	}
+
+	/// Reserves a slot for one transaction, or returns `None` when all are taken.
+	pub fn try_reserve(&self) -> Option<InFlightPermit> {
+		self.in_flight
+			.fetch_update(Ordering::AcqRel, Ordering::Acquire, |current| {
+				(current < self.max_in_flight).then(|| current + 1)
+			})
+			.ok()
+			.map(|_| InFlightPermit { tracker: Some(self.clone()) })
+	}
+
+	/// Reserves a slot for one transaction, waiting up to `timeout` for one to be freed
+	/// when all are taken.
+	pub async fn reserve_within(&self, timeout: Duration) -> Option<InFlightPermit> {
+		self.reserve_until(tokio::time::Instant::now() + timeout).await
+	}
+
+	/// Reserves a slot for one transaction, waiting until `deadline` for one to be freed
+	/// when all are taken.
+	pub async fn reserve_until(&self, deadline: tokio::time::Instant) -> Option<InFlightPermit> {
+		loop {
+			let freed = self.freed.notified();
+			tokio::pin!(freed);
+			// Registered before trying, so that a slot freed in between is not missed.
+			freed.as_mut().enable();
+			if let Some(permit) = self.try_reserve() {
+				return Some(permit);
+			}
+			tokio::time::timeout_at(deadline, freed).await.ok()?;
+		}
+	}
+
+	/// Frees the slots of `count` held transactions once they have been executed.
+	pub fn release(&self, count: u64) {
+		// The closure always returns `Some`, so the update cannot fail.
+		let _ = self.in_flight.fetch_update(Ordering::AcqRel, Ordering::Acquire, |current| {
+			Some(current.saturating_sub(count))
+		});
+		self.freed.notify_waiters();
+	}
+
+	pub fn in_flight(&self) -> u64 {
+		self.in_flight.load(Ordering::Acquire)
+	}
+
+	pub fn max_in_flight(&self) -> u64 {
+		self.max_in_flight
+	}
+
+	/// The fraction of slots taken, from 0.0 to 1.0; a bound of 0 is always full.
+	pub fn utilization(&self) -> f64 {
+		if self.max_in_flight == 0 {
+			return 1.0;
+		}
+		(self.in_flight() as f64 / self.max_in_flight as f64).min(1.0)
+	}
+}
+
+/// A slot reserved for one transaction, freed when dropped unless it is held.
+#[must_use = "the slot is freed as soon as the permit is dropped"]
+#[derive(Debug)]
+pub struct InFlightPermit {
+	tracker: Option<InFlightTracker>,
+}
+
+impl InFlightPermit {
+	/// Keeps the slot taken after the transaction has been passed on;
+	/// it is freed by [`InFlightTracker::release`] once the transaction is executed.
+	pub fn hold(mut self) {
+		self.tracker = None;
+	}
+}
+
+impl Drop for InFlightPermit {
+	fn drop(&mut self) {
+		if let Some(tracker) = self.tracker.take() {
+			tracker.release(1);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_dropped_permits_free_their_slot() {
+		let tracker = InFlightTracker::new(2);
+		let first = tracker.try_reserve().expect("a slot is free");
+		tracker.try_reserve().expect("a slot is free").hold();
+		assert!(tracker.try_reserve().is_none());
+		assert_eq!(tracker.utilization(), 1.0);
+
+		drop(first);
+		assert_eq!(tracker.in_flight(), 1);
+		assert_eq!(tracker.utilization(), 0.5);
+
+		tracker.release(1);
+		assert_eq!(tracker.in_flight(), 0);
+		tracker.release(1);
+		assert_eq!(tracker.in_flight(), 0);
+	}
+
+	#[test]
+	fn test_parallel_reservations_respect_the_bound() {
+		let tracker = InFlightTracker::new(100);
+		let reserved: usize = std::thread::scope(|scope| {
+			let workers: Vec<_> = (0..8)
+				.map(|_| {
+					scope.spawn(|| {
+						(0..1000)
+							.filter_map(|_| tracker.try_reserve())
+							.map(InFlightPermit::hold)
+							.count()
+					})
+				})
+				.collect();
+			workers.into_iter().map(|worker| worker.join().unwrap()).sum()
+		});
+		assert_eq!(reserved, 100);
+		assert_eq!(tracker.in_flight(), 100);
+
+		tracker.release(40);
+		let reserved: usize = std::thread::scope(|scope| {
+			let workers: Vec<_> = (0..8)
+				.map(|_| scope.spawn(|| tracker.try_reserve().map(InFlightPermit::hold).is_some()))
+				.collect();
+			workers.into_iter().map(|worker| usize::from(worker.join().unwrap())).sum()
+		});
+		assert_eq!(reserved, 8);
+		assert_eq!(tracker.in_flight(), 68);
+	}
+
+	#[tokio::test]
+	async fn test_waiting_reservations_take_freed_slots() {
+		let tracker = InFlightTracker::new(1);
+		tracker.try_reserve().expect("a slot is free").hold();
+		assert!(tracker.reserve_within(Duration::from_millis(20)).await.is_none());
+
+		let executor = tracker.clone();
+		let release = tokio::spawn(async move {
+			tokio::time::sleep(Duration::from_millis(20)).await;
+			executor.release(1);
+		});
+		let permit = tracker.reserve_within(Duration::from_secs(10)).await;
+		release.await.unwrap();
+		assert!(permit.is_some());
+		assert_eq!(tracker.in_flight(), 1);
+	}
+}
@@ -1,16 +1,49 @@
 use super::Context;
-use aptos_indexer_grpc_fullnode::runtime::bootstrap as bootstrap_indexer_grpc;
+use crate::bound_addrs::BoundAddrs;
+use crate::grpc_tls::GrpcTlsFront;
+use aptos_indexer_grpc_fullnode::localnet_data_service::LocalnetDataService;
+use aptos_indexer_grpc_fullnode::ServiceContext;
 use aptos_indexer_grpc_table_info::runtime::bootstrap as bootstrap_table_info;
+use aptos_protos::indexer::v1::raw_data_server::RawDataServer;
+use maptos_execution_util::config::indexer::{indexer_lag, GrpcCompression};
+use maptos_execution_util::listener;
 
 use tokio::runtime::Runtime;
+use tonic::codec::CompressionEncoding;
+use tonic::transport::server::TcpIncoming;
+use tonic::transport::Server;
+
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often the lag of the table info indexer behind the ledger is sampled for the metrics.
+const INDEXER_LAG_INTERVAL: Duration = Duration::from_secs(1);
 
 /// Runtime handle for indexer services. This object should be kept alive
 /// while services are running.
+///
+/// Dropping it gives open indexer streams the configured shutdown grace period
+/// to finish before their tasks are aborted.
 pub struct IndexerRuntime {
 	// We only keep the runtimes around to drop them
-	_table_info_runtime: Runtime,
-	_indexer_grpc: Runtime,
+	table_info_runtime: Option<Runtime>,
+	indexer_grpc: Option<Runtime>,
+	grpc_tls_front: Option<Runtime>,
 	//	_indexer_stream: Runtime,
+	shutdown_grace: Duration,
+}
+
+impl Drop for IndexerRuntime {
+	fn drop(&mut self) {
+		for runtime in
+			[self.grpc_tls_front.take(), self.indexer_grpc.take(), self.table_info_runtime.take()]
+				.into_iter()
+				.flatten()
+		{
+			runtime.shutdown_timeout(self.shutdown_grace);
+		}
+	}
 }
 
 impl Context {
@@ -19,7 +52,7 @@ impl Context {
 		tracing::info!("Starting indexer gRPC with node config {:?}", self.node_config);
 
 		// bootstrap table info
-		let (_table_info_runtime, _async_indexer) = bootstrap_table_info(
+		let (table_info_runtime, async_indexer) = bootstrap_table_info(
 			&self.node_config,
 			self.maptos_config.chain.maptos_chain_id.clone(),
 			self.db.clone(),
@@ -27,16 +60,71 @@ impl Context {
 		)
 		.ok_or(anyhow::anyhow!("Failed to bootstrap table info runtime"))?;
 
-		// Bootstrap indexer grpc.
-		// this one actually serves the gRPC service
-		let _indexer_grpc = bootstrap_indexer_grpc(
-			&self.node_config,
-			self.maptos_config.chain.maptos_chain_id.clone(),
-			self.db.reader.clone(),
-			self.mempool_client_sender.clone(),
-			None,
-		)
-		.ok_or(anyhow::anyhow!("Failed to bootstrap indexer grpc runtime"))?;
+		let db_reader = self.db.reader.clone();
+		let metrics = self.metrics();
+		table_info_runtime.spawn(async move {
+			let mut interval = tokio::time::interval(INDEXER_LAG_INTERVAL);
+			loop {
+				interval.tick().await;
+				// Nothing is committed before genesis, so there is nothing to lag behind.
+				if let Ok(Some(ledger_info)) = db_reader.get_latest_ledger_info_option() {
+					let next_version = ledger_info.ledger_info().version() + 1;
+					metrics
+						.set_indexer_lag(indexer_lag(next_version, async_indexer.next_version()));
+				}
+			}
+		});
+
+		if !self.maptos_config.indexer_grpc_enabled() {
+			tracing::info!("MAPTOS_INDEXER_GRPC_ENABLED=false, not serving indexer gRPC");
+			return Ok(IndexerRuntime {
+				table_info_runtime: Some(table_info_runtime),
+				indexer_grpc: None,
+				grpc_tls_front: None,
+				shutdown_grace: self.maptos_config.chain.shutdown_grace(),
+			});
+		}
+
+		// The server serves plaintext, so with TLS configured it is moved to a loopback port and
+		// TLS is terminated by a front on every public address. That port is the configured
+		// loopback port, if any, for an indexer on the same host without a client certificate.
+		let listen_addrs = self.maptos_config.indexer_grpc_listen_socket_addrs()?;
+		let tls = self.maptos_config.indexer_grpc_tls();
+		let server_addrs = match tls {
+			Some(_) => {
+				let port = self.maptos_config.indexer_grpc_loopback_port().unwrap_or(0);
+				vec![SocketAddr::from((Ipv4Addr::LOCALHOST, port))]
+			}
+			None => listen_addrs.clone(),
+		};
+		let (indexer_grpc, indexer_grpc_addrs) = self.serve_indexer_grpc(&server_addrs)?;
+
+		let grpc_tls_front = match tls {
+			Some(tls) => {
+				let front = GrpcTlsFront::new(&tls, indexer_grpc_addrs[0])?;
+				let listeners = listener::bind_tcp(&listen_addrs)?;
+				let bound = listeners.iter().map(|listener| listener.local_addr()).collect();
+				self.bound_addrs.record(BoundAddrs::INDEXER_GRPC, bound?);
+				let runtime = tokio::runtime::Builder::new_multi_thread()
+					.thread_name("indexer-grpc-tls")
+					.worker_threads(self.maptos_config.indexer.worker_threads())
+					.enable_all()
+					.build()?;
+				for listener in listeners {
+					let front = front.clone();
+					runtime.spawn(async move {
+						if let Err(e) = front.run(listener).await {
+							tracing::error!("Indexer gRPC TLS front failed: {}", e);
+						}
+					});
+				}
+				Some(runtime)
+			}
+			None => {
+				self.bound_addrs.record(BoundAddrs::INDEXER_GRPC, indexer_grpc_addrs);
+				None
+			}
+		};
 
 		// Grpc stream works without the indexer.
 		// By default indexer is not started on Suzuka node.
@@ -51,6 +139,92 @@ impl Context {
 		// 	anyhow::anyhow!("Failed to bootstrap indexer stream runtime"),
 		// )?;
 
-		Ok(IndexerRuntime { _table_info_runtime, _indexer_grpc }) //, _indexer_stream
+		Ok(IndexerRuntime {
+			table_info_runtime: Some(table_info_runtime),
+			indexer_grpc: Some(indexer_grpc),
+			grpc_tls_front,
+			//, _indexer_stream
+			shutdown_grace: self.maptos_config.chain.shutdown_grace(),
+		})
+	}
+
+	/// Serves the indexer gRPC stream in plaintext on each of `addrs`, returning the addresses
+	/// it is bound to.
+	///
+	/// The Aptos bootstrap of this server fixes its compression and message limits and binds a
+	/// single address, so the node builds it from the same service with the configured ones.
+	fn serve_indexer_grpc(
+		&self,
+		addrs: &[SocketAddr],
+	) -> Result<(Runtime, Vec<SocketAddr>), anyhow::Error> {
+		let indexer = &self.maptos_config.indexer;
+		let indexer_grpc = &self.node_config.indexer_grpc;
+		let service_context = ServiceContext {
+			context: Arc::new(aptos_api::Context::new(
+				self.maptos_config.chain.maptos_chain_id.clone(),
+				self.db.reader.clone(),
+				self.mempool_client_sender.clone(),
+				self.node_config.clone(),
+				None,
+			)),
+			processor_task_count: indexer_grpc.processor_task_count,
+			processor_batch_size: indexer_grpc.processor_batch_size,
+			output_batch_size: indexer_grpc.output_batch_size,
+			transaction_channel_size: indexer_grpc.transaction_channel_size,
+		};
+		let mut service = RawDataServer::new(LocalnetDataService { service_context })
+			.max_decoding_message_size(indexer.max_message_bytes())
+			.max_encoding_message_size(indexer.max_message_bytes())
+			.accept_compressed(CompressionEncoding::Gzip)
+			.accept_compressed(CompressionEncoding::Zstd);
+		// The stream is compressed for the clients accepting it, the others get it as it is.
+		if let Some(encoding) = compression_encoding(self.maptos_config.indexer_grpc_compression())
+		{
+			service = service.send_compressed(encoding);
+		}
+		let server = Server::builder()
+			.http2_keepalive_interval(indexer.http2_keepalive())
+			.add_service(service);
+		let listeners = listener::bind_tcp(addrs)?;
+		let bound = listeners
+			.iter()
+			.map(|listener| listener.local_addr())
+			.collect::<Result<_, _>>()?;
+		let tcp_keepalive = indexer.tcp_keepalive();
+
+		let runtime = tokio::runtime::Builder::new_multi_thread()
+			.thread_name("indexer-grpc")
+			.worker_threads(indexer.worker_threads())
+			.enable_all()
+			.build()?;
+		tracing::info!("Serving the indexer gRPC at {:?}", bound);
+		runtime.spawn(async move {
+			let served = async {
+				let mut incoming = Vec::new();
+				for listener in listeners {
+					listener.set_nonblocking(true)?;
+					let listener = tokio::net::TcpListener::from_std(listener)?;
+					incoming.push(
+						TcpIncoming::from_listener(listener, false, tcp_keepalive)
+							.map_err(|e| anyhow::anyhow!(e))?,
+					);
+				}
+				server.serve_with_incoming(futures::stream::select_all(incoming)).await?;
+				Ok::<_, anyhow::Error>(())
+			};
+			if let Err(e) = served.await {
+				tracing::error!("Indexer gRPC server failed: {}", e);
+			}
+		});
+		Ok((runtime, bound))
+	}
+}
+
+/// The tonic encoding of a configured compression, `None` for uncompressed messages.
+fn compression_encoding(compression: GrpcCompression) -> Option<CompressionEncoding> {
+	match compression {
+		GrpcCompression::None => None,
+		GrpcCompression::Gzip => Some(CompressionEncoding::Gzip),
+		GrpcCompression::Zstd => Some(CompressionEncoding::Zstd),
 	}
 }
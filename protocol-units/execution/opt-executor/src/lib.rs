@@ -1,12 +1,29 @@
 pub mod bootstrap;
+pub mod bound_addrs;
 pub mod context;
+pub mod db_size;
 #[warn(unused_imports)]
 pub mod executor;
+pub mod grpc_tls;
+pub mod health;
+pub mod in_flight;
 pub mod indexer;
+pub mod metrics;
+pub mod ordering;
+pub mod reload;
+pub mod server_runtime;
 pub mod service;
+pub mod shutdown;
 pub mod transaction_pipe;
+pub mod version;
+pub mod warmup;
 
+pub use bound_addrs::BoundAddrs;
 pub use context::Context;
 pub use executor::Executor;
+pub use health::HealthService;
+pub use in_flight::{InFlightPermit, InFlightTracker};
+pub use metrics::{Metrics, MetricsService};
 pub use service::Service;
-pub use transaction_pipe::TransactionPipe;
+pub use transaction_pipe::{OverflowHandling, TransactionLimits, TransactionPipe};
+pub use warmup::Warmup;
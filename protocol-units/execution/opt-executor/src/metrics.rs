@@ -0,0 +1,252 @@
+//! The counters served to Prometheus on `/metrics`.
+
+use crate::{service::shutdown_signal, BoundAddrs, Context, InFlightTracker};
+
+use maptos_execution_util::listener;
+
+use poem::{
+	handler, http::StatusCode, listener::Listener, web::Data, EndpointExt, Response, Route, Server,
+};
+use tracing::info;
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The content type of the Prometheus text exposition format.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// The counters the node keeps, updated by the services and rendered on scrape.
+///
+/// Clones share the counters.
+#[derive(Debug, Clone)]
+pub struct Metrics {
+	// REST requests answered, by response status
+	rest_requests: Arc<Mutex<BTreeMap<u16, u64>>>,
+	transactions_in_flight: InFlightTracker,
+	// Committed versions the table info indexer has yet to process
+	indexer_lag: Arc<AtomicU64>,
+}
+
+impl Metrics {
+	pub fn new(transactions_in_flight: InFlightTracker) -> Self {
+		Self { rest_requests: Arc::default(), transactions_in_flight, indexer_lag: Arc::default() }
+	}
+
+	/// Counts one REST request answered with `status`.
+	pub fn record_rest_request(&self, status: StatusCode) {
+		*self
+			.rest_requests
+			.lock()
+			.expect("no thread panics holding the request counts")
+			.entry(status.as_u16())
+			.or_default() += 1;
+	}
+
+	/// Records how many committed versions the indexer has yet to process.
+	pub fn set_indexer_lag(&self, versions: u64) {
+		self.indexer_lag.store(versions, Ordering::Release);
+	}
+
+	pub fn indexer_lag(&self) -> u64 {
+		self.indexer_lag.load(Ordering::Acquire)
+	}
+
+	/// Renders every metric in the Prometheus text exposition format.
+	pub fn render(&self) -> String {
+		let mut out = String::new();
+		// Writing to a `String` cannot fail.
+		let mut metric = |name: &str, kind: &str, help: &str, samples: &[(String, u64)]| {
+			let _ = writeln!(out, "# HELP {} {}", name, help);
+			let _ = writeln!(out, "# TYPE {} {}", name, kind);
+			for (labels, value) in samples {
+				let _ = writeln!(out, "{}{} {}", name, labels, value);
+			}
+		};
+		let rest_requests: Vec<(String, u64)> = self
+			.rest_requests
+			.lock()
+			.expect("no thread panics holding the request counts")
+			.iter()
+			.map(|(status, count)| (format!("{{status=\"{}\"}}", status), *count))
+			.collect();
+		metric(
+			"maptos_rest_requests_total",
+			"counter",
+			"REST requests answered, by response status.",
+			&rest_requests,
+		);
+		metric(
+			"maptos_transactions_in_flight",
+			"gauge",
+			"Transactions admitted and not yet executed.",
+			&[(String::new(), self.transactions_in_flight.in_flight())],
+		);
+		metric(
+			"maptos_transactions_in_flight_max",
+			"gauge",
+			"The bound on transactions in flight.",
+			&[(String::new(), self.transactions_in_flight.max_in_flight())],
+		);
+		metric(
+			"maptos_indexer_lag_versions",
+			"gauge",
+			"Committed versions the indexer has yet to process.",
+			&[(String::new(), self.indexer_lag())],
+		);
+		out
+	}
+}
+
+#[handler]
+fn serve_metrics(Data(metrics): Data<&Metrics>) -> Response {
+	Response::builder()
+		.status(StatusCode::OK)
+		.content_type(CONTENT_TYPE)
+		.body(metrics.render())
+}
+
+/// Serves `/metrics` on a port of its own, without the API middleware.
+#[derive(Clone)]
+pub struct MetricsService {
+	// Where the metrics endpoint is served
+	listen_hostname: String,
+	listen_port: u16,
+	metrics: Metrics,
+	// Time allowed for in-flight scrapes to finish on shutdown
+	shutdown_grace: Duration,
+	// Where the servers, this one included, are bound once started
+	bound_addrs: BoundAddrs,
+}
+
+impl MetricsService {
+	/// Creates the metrics service, or returns `None` if it is disabled in the config.
+	pub fn new(cx: &Context) -> Option<Self> {
+		let maptos_config = &cx.maptos_config;
+		if !maptos_config.metrics_enabled() {
+			return None;
+		}
+		Some(MetricsService {
+			listen_hostname: maptos_config.metrics.maptos_metrics_listen_hostname.clone(),
+			listen_port: maptos_config.metrics.maptos_metrics_listen_port,
+			metrics: cx.metrics(),
+			shutdown_grace: maptos_config.chain.shutdown_grace(),
+			bound_addrs: cx.bound_addrs(),
+		})
+	}
+
+	pub fn run(&self) -> impl Future<Output = Result<(), anyhow::Error>> + Send {
+		info!(
+			"Starting maptos-opt-executor metrics service at: {}:{}",
+			self.listen_hostname, self.listen_port
+		);
+
+		let listener = listener::bind_hostname(
+			"MAPTOS_METRICS_LISTEN_HOSTNAME",
+			&self.listen_hostname,
+			self.listen_port,
+		);
+		let metrics = self.metrics.clone();
+		let shutdown_grace = self.shutdown_grace;
+		let bound_addrs = self.bound_addrs.clone();
+		async move {
+			let acceptor = listener?.into_acceptor().await?;
+			bound_addrs.record_acceptor(BoundAddrs::METRICS, &acceptor);
+			let app = Route::new().at("/metrics", poem::get(serve_metrics)).data(metrics);
+			Server::new_with_acceptor(acceptor)
+				.run_with_graceful_shutdown(app, shutdown_signal(), Some(shutdown_grace))
+				.await
+				.map_err(|e| anyhow::anyhow!("Metrics server error: {:?}", e))
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Executor;
+	use aptos_vm_genesis::GENESIS_KEYPAIR;
+	use tokio::io::{AsyncReadExt, AsyncWriteExt};
+	use tokio::net::TcpStream;
+	use tokio::sync::mpsc;
+
+	/// Checks `body` against the text exposition format: every sample is a metric
+	/// announced by `# TYPE`, with a valid name, optional labels and an integer value.
+	fn assert_valid_exposition(body: &str) {
+		let mut typed = Vec::new();
+		for line in body.lines() {
+			if let Some(comment) = line.strip_prefix("# ") {
+				let mut words = comment.splitn(3, ' ');
+				match (words.next(), words.next(), words.next()) {
+					(Some("TYPE"), Some(name), Some("counter" | "gauge")) => typed.push(name),
+					(Some("HELP"), Some(_), Some(_)) => {}
+					_ => panic!("malformed comment {:?}", line),
+				}
+				continue;
+			}
+			let (series, value) = line.rsplit_once(' ').expect("a sample has a value");
+			value.parse::<u64>().expect("the value is an integer");
+			let name = series.split('{').next().expect("split yields a first part");
+			assert!(
+				name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'),
+				"invalid metric name {:?}",
+				name
+			);
+			assert_eq!(typed.last(), Some(&name), "{:?} is not announced by # TYPE", name);
+			if let Some(labels) = series.strip_prefix(name).filter(|labels| !labels.is_empty()) {
+				assert!(labels.starts_with('{') && labels.ends_with('}'), "bad labels {:?}", line);
+			}
+		}
+		assert!(body.ends_with('\n'));
+	}
+
+	#[test]
+	fn test_render_is_valid_exposition() {
+		let tracker = InFlightTracker::new(10);
+		let metrics = Metrics::new(tracker.clone());
+		tracker.try_reserve().expect("a slot is free").hold();
+		metrics.record_rest_request(StatusCode::OK);
+		metrics.record_rest_request(StatusCode::OK);
+		metrics.record_rest_request(StatusCode::NOT_FOUND);
+		metrics.set_indexer_lag(3);
+
+		let body = metrics.render();
+		assert_valid_exposition(&body);
+		assert!(body.contains("maptos_rest_requests_total{status=\"200\"} 2\n"));
+		assert!(body.contains("maptos_rest_requests_total{status=\"404\"} 1\n"));
+		assert!(body.contains("maptos_transactions_in_flight 1\n"));
+		assert!(body.contains("maptos_transactions_in_flight_max 10\n"));
+		assert!(body.contains("maptos_indexer_lag_versions 3\n"));
+	}
+
+	#[tokio::test]
+	async fn test_metrics_are_served_only_when_enabled() -> Result<(), anyhow::Error> {
+		let (tx_sender, _tx_receiver) = mpsc::channel(16);
+		let (executor, _config, _tempdir) = Executor::try_test_default(GENESIS_KEYPAIR.0.clone())?;
+		let (mut context, _transaction_pipe) = executor.background(tx_sender)?;
+
+		context.maptos_config.metrics.maptos_metrics_enabled = false;
+		assert!(MetricsService::new(&context).is_none());
+
+		context.maptos_config.metrics.maptos_metrics_enabled = true;
+		context.maptos_config.metrics.maptos_metrics_listen_hostname = "127.0.0.1".to_string();
+		context.maptos_config.metrics.maptos_metrics_listen_port = 0;
+		let service = MetricsService::new(&context).expect("metrics are enabled");
+		let server = tokio::spawn(service.run());
+		let addrs = context.bound_addrs().wait_for(BoundAddrs::METRICS).await;
+
+		let mut stream = TcpStream::connect(addrs.as_slice()).await?;
+		stream.write_all(b"GET /metrics HTTP/1.0\r\n\r\n").await?;
+		let mut response = String::new();
+		stream.read_to_string(&mut response).await?;
+		let (head, body) = response.split_once("\r\n\r\n").expect("the response has a body");
+		assert!(head.starts_with("HTTP/1.0 200") || head.starts_with("HTTP/1.1 200"));
+		assert!(head.to_ascii_lowercase().contains("content-type: text/plain; version=0.0.4"));
+		assert_valid_exposition(body);
+		server.abort();
+		Ok(())
+	}
+}
@@ -0,0 +1,163 @@
+//! Queueing accepted transactions in the mempool ordering, both while they wait for room in
+//! the transaction channel and while the batches written to the DA are formed.
+
+use aptos_types::account_address::AccountAddress;
+use aptos_types::transaction::SignedTransaction;
+use maptos_execution_util::config::load_shedding::MempoolOrdering;
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+
+/// Transactions, each with a value `P` travelling along, taken out in the mempool ordering.
+///
+/// Under [`MempoolOrdering::GasPrice`] only the earliest queued transaction of each sender is a
+/// candidate, so that a sender's transactions keep their order. Taking one out costs
+/// `O(log n)` either way.
+pub struct OrderedTransactions<P = ()> {
+	ordering: MempoolOrdering,
+	// The order of arrival given to the next transaction, breaking ties between equal prices
+	next_arrival: u64,
+	// Every queued transaction, by sender, in the order they arrived
+	by_sender: HashMap<AccountAddress, VecDeque<(u64, SignedTransaction, P)>>,
+	// The earliest transaction of each sender, highest price then earliest arrival first
+	heads: BinaryHeap<(u64, Reverse<u64>, AccountAddress)>,
+	// Every queued transaction in the order they arrived, under `Fifo`
+	fifo: VecDeque<(SignedTransaction, P)>,
+	len: usize,
+}
+
+impl<P> OrderedTransactions<P> {
+	pub fn new(ordering: MempoolOrdering) -> Self {
+		Self {
+			ordering,
+			next_arrival: 0,
+			by_sender: HashMap::new(),
+			heads: BinaryHeap::new(),
+			fifo: VecDeque::new(),
+			len: 0,
+		}
+	}
+
+	pub fn len(&self) -> usize {
+		self.len
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len == 0
+	}
+
+	pub fn push(&mut self, transaction: SignedTransaction, value: P) {
+		self.len += 1;
+		if self.ordering == MempoolOrdering::Fifo {
+			self.fifo.push_back((transaction, value));
+			return;
+		}
+		let arrival = self.next_arrival;
+		self.next_arrival += 1;
+		let sender = transaction.sender();
+		let price = transaction.gas_unit_price();
+		let queue = self.by_sender.entry(sender).or_default();
+		if queue.is_empty() {
+			self.heads.push((price, Reverse(arrival), sender));
+		}
+		queue.push_back((arrival, transaction, value));
+	}
+
+	/// Takes the transaction which comes next under the mempool ordering.
+	pub fn pop_next(&mut self) -> Option<(SignedTransaction, P)> {
+		let next = match self.ordering {
+			MempoolOrdering::Fifo => self.fifo.pop_front(),
+			MempoolOrdering::GasPrice => {
+				let (_, _, sender) = self.heads.pop()?;
+				let queue = self.by_sender.get_mut(&sender)?;
+				let (_, transaction, value) = queue.pop_front()?;
+				match queue.front() {
+					Some((arrival, next, _)) => {
+						self.heads.push((next.gas_unit_price(), Reverse(*arrival), sender))
+					}
+					None => {
+						self.by_sender.remove(&sender);
+					}
+				}
+				Some((transaction, value))
+			}
+		};
+		if next.is_some() {
+			self.len -= 1;
+		}
+		next
+	}
+
+	/// Takes every transaction out, in the mempool ordering.
+	pub fn drain(&mut self) -> impl Iterator<Item = (SignedTransaction, P)> + '_ {
+		std::iter::from_fn(|| self.pop_next())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use aptos_types::test_helpers::transaction_test_helpers;
+	use aptos_vm_genesis::GENESIS_KEYPAIR;
+
+	fn priced_transaction(
+		sender: AccountAddress,
+		sequence_number: u64,
+		gas_unit_price: u64,
+	) -> SignedTransaction {
+		transaction_test_helpers::get_test_signed_transaction(
+			sender,
+			sequence_number,
+			&GENESIS_KEYPAIR.0,
+			GENESIS_KEYPAIR.1.clone(),
+			None,
+			u64::MAX,
+			gas_unit_price,
+			None,
+		)
+	}
+
+	fn dequeued(
+		ordering: MempoolOrdering,
+		transactions: &[(AccountAddress, u64, u64)],
+	) -> Vec<(u64, u64)> {
+		let mut queue = OrderedTransactions::new(ordering);
+		for (sender, sequence_number, price) in transactions {
+			queue.push(priced_transaction(*sender, *sequence_number, *price), ());
+		}
+		let dequeued = queue
+			.drain()
+			.map(|(transaction, ())| (transaction.sequence_number(), transaction.gas_unit_price()))
+			.collect();
+		assert!(queue.is_empty());
+		dequeued
+	}
+
+	#[test]
+	fn test_gas_price_ordering_dequeues_higher_prices_first() {
+		let (a, b, c) = (AccountAddress::ONE, AccountAddress::TWO, AccountAddress::THREE);
+		assert_eq!(
+			dequeued(MempoolOrdering::GasPrice, &[(a, 0, 100), (b, 0, 300), (c, 0, 200)]),
+			[(0, 300), (0, 200), (0, 100)]
+		);
+	}
+
+	#[test]
+	fn test_gas_price_ordering_keeps_each_senders_order() {
+		let (a, b) = (AccountAddress::ONE, AccountAddress::TWO);
+		// The sender's second transaction outbids everyone, but waits for its first.
+		assert_eq!(
+			dequeued(MempoolOrdering::GasPrice, &[(a, 0, 100), (a, 1, 500), (b, 0, 200)]),
+			[(0, 200), (0, 100), (1, 500)]
+		);
+	}
+
+	#[test]
+	fn test_fifo_ordering_preserves_insertion_order() {
+		let (a, b, c) = (AccountAddress::ONE, AccountAddress::TWO, AccountAddress::THREE);
+		assert_eq!(
+			dequeued(MempoolOrdering::Fifo, &[(a, 0, 100), (b, 0, 300), (c, 0, 200)]),
+			[(0, 100), (0, 300), (0, 200)]
+		);
+	}
+}
@@ -0,0 +1,33 @@
+//! Reloading the config when the process is sent SIGHUP.
+
+use maptos_execution_util::config::reload::ConfigReloader;
+
+use futures::future;
+use tokio::signal::unix::{signal, SignalKind};
+use tracing::{error, info, warn};
+
+use std::sync::Arc;
+
+/// Reloads the reloadable fields of the config every time the process receives SIGHUP.
+///
+/// Never resolves, so it is meant to run alongside the node's other tasks.
+pub async fn on_sighup(reloader: Arc<ConfigReloader>) {
+	let mut hangups = match signal(SignalKind::hangup()) {
+		Ok(hangups) => hangups,
+		Err(e) => {
+			error!("Failed to listen for SIGHUP, the config will not be reloaded: {:?}", e);
+			return future::pending().await;
+		}
+	};
+	while hangups.recv().await.is_some() {
+		match reloader.reload() {
+			Ok(report) => info!(
+				applied = report.applied.len(),
+				ignored = report.ignored.len(),
+				"Reloaded the config on SIGHUP"
+			),
+			Err(e) => warn!("Failed to reload the config, keeping the running one: {:?}", e),
+		}
+	}
+	future::pending().await
+}
@@ -0,0 +1,75 @@
+//! Runtimes of their own for the API servers, sized by their worker thread settings.
+
+use tokio::runtime::{Builder, Runtime};
+
+use std::future::Future;
+
+/// Runs `server` to completion on a runtime with `worker_threads` workers.
+///
+/// The runtime is shut down without waiting once the server returns or this future is dropped,
+/// so the server may be awaited from a task on another runtime.
+pub async fn run_on_own_runtime<F>(
+	thread_name: &str,
+	worker_threads: usize,
+	server: F,
+) -> Result<(), anyhow::Error>
+where
+	F: Future<Output = Result<(), anyhow::Error>> + Send + 'static,
+{
+	let runtime = OwnedRuntime(Some(
+		Builder::new_multi_thread()
+			.thread_name(thread_name)
+			.worker_threads(worker_threads)
+			.enable_all()
+			.build()?,
+	));
+	let server = runtime.0.as_ref().expect("the runtime is only taken on drop").spawn(server);
+	server.await?
+}
+
+/// Shuts its runtime down in the background when dropped,
+/// as dropping a runtime from within an async context panics.
+struct OwnedRuntime(Option<Runtime>);
+
+impl Drop for OwnedRuntime {
+	fn drop(&mut self) {
+		if let Some(runtime) = self.0.take() {
+			runtime.shutdown_background();
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[tokio::test]
+	async fn test_server_runs_on_its_own_runtime() -> Result<(), anyhow::Error> {
+		run_on_own_runtime("test-server", 2, async {
+			assert_eq!(std::thread::current().name(), Some("test-server"));
+			Ok(())
+		})
+		.await
+	}
+
+	#[tokio::test]
+	async fn test_server_runtime_has_the_worker_threads_configured() -> Result<(), anyhow::Error> {
+		// Both tasks block their worker until the other arrives, which needs two workers.
+		run_on_own_runtime("test-server", 2, async {
+			let barrier = std::sync::Arc::new(std::sync::Barrier::new(2));
+			let tasks: Vec<_> = (0..2)
+				.map(|_| {
+					let barrier = barrier.clone();
+					tokio::spawn(async move {
+						barrier.wait();
+					})
+				})
+				.collect();
+			for task in tasks {
+				task.await?;
+			}
+			Ok(())
+		})
+		.await
+	}
+}
@@ -1,4 +1,8 @@
-use crate::Context;
+use crate::{
+	server_runtime,
+	version::{self, VersionInfo},
+	warmup, BoundAddrs, Context, HealthService, Metrics, MetricsService, Warmup,
+};
 
 use aptos_api::{
 	get_api_service,
@@ -6,13 +10,278 @@ use aptos_api::{
 	set_failpoints,
 };
 use aptos_storage_interface::DbReaderWriter;
+use maptos_execution_util::config::{
+	chain::{AddressFormat, RestEncoding},
+	cors::CorsOrigins,
+	endpoints::{self, ConnectionLimits},
+	reload::SharedRuntimeConfig,
+	rest_routes::{RestRoute, RestRouteSet},
+	rest_scopes::{RestAccess, RestScopedTokens},
+	tls::TlsFiles,
+};
+use maptos_execution_util::listener;
 
-use futures::prelude::*;
-use poem::{http::Method, listener::TcpListener, middleware::Cors, EndpointExt, Route, Server};
-use tracing::info;
+use poem::{
+	http::{header, HeaderValue, Method, StatusCode, Uri},
+	listener::{Listener, RustlsCertificate, RustlsConfig},
+	middleware::{Cors, SizeLimit},
+	Endpoint, EndpointExt, IntoResponse, Route, Server,
+};
+use tracing::{info, warn};
 
 use std::future::Future;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// Whether a request submits transactions, which read-only nodes refuse.
+pub fn submits_transactions(method: &Method, path: &str) -> bool {
+	method == Method::POST
+		&& matches!(path.trim_end_matches('/'), "/v1/transactions" | "/v1/transactions/batch")
+}
+
+/// Whether a request calls a view function, which executes Move code against the ledger.
+pub fn calls_view_function(method: &Method, path: &str) -> bool {
+	method == Method::POST && path.trim_end_matches('/') == "/v1/view"
+}
+
+/// The response read-only nodes give to transaction submissions.
+pub fn read_only_rejection() -> poem::Response {
+	(StatusCode::FORBIDDEN, "this node is read-only and does not accept transactions")
+		.into_response()
+}
+
+/// The response to a transaction submission, with the 507 the API answers a submission
+/// refused as when the mempool is full with turned into 503, asking clients to retry later.
+///
+/// Submissions are refused so whenever the transactions in flight are at their bound.
+pub fn unavailable_when_full(mut response: poem::Response) -> poem::Response {
+	if response.status() == StatusCode::INSUFFICIENT_STORAGE {
+		response.set_status(StatusCode::SERVICE_UNAVAILABLE);
+		response
+			.headers_mut()
+			.insert(header::RETRY_AFTER, HeaderValue::from_static("1"));
+	}
+	response
+}
+
+/// The header on responses to paginated requests whose `limit` was lowered to the
+/// configured maximum page size, giving the limit applied.
+pub const PAGE_SIZE_CLAMPED_HEADER: &str = "x-maptos-page-size-clamped";
+
+/// The query string with each `limit` above `max_page_size` lowered to it,
+/// or `None` when no limit exceeds it.
+///
+/// The handlers cap every page at the same size themselves; this only lets the response
+/// tell the client so. Limits which are not numbers are left for the API to reject.
+pub fn clamp_page_size(query: &str, max_page_size: u16) -> Option<String> {
+	let mut clamped = false;
+	let pairs: Vec<String> = query
+		.split('&')
+		.map(|pair| match pair.split_once('=') {
+			Some(("limit", limit))
+				if limit.parse::<u64>().is_ok_and(|limit| limit > u64::from(max_page_size)) =>
+			{
+				clamped = true;
+				format!("limit={}", max_page_size)
+			}
+			_ => pair.to_string(),
+		})
+		.collect();
+	clamped.then(|| pairs.join("&"))
+}
+
+/// `uri` with its page size clamped by [`clamp_page_size`], or `None` when it needs none.
+fn clamp_page_size_uri(uri: &Uri, max_page_size: u16) -> Option<Uri> {
+	let query = clamp_page_size(uri.query()?, max_page_size)?;
+	let mut parts = uri.clone().into_parts();
+	parts.path_and_query = Some(format!("{}?{}", uri.path(), query).parse().ok()?);
+	Uri::from_parts(parts).ok()
+}
+
+/// The `Accept` header which has a request answered in `default`, or `None` when the request
+/// asks for an encoding of its own, which wins.
+///
+/// A request without an `Accept` header, or accepting any type, leaves the encoding to the node.
+pub fn default_accept(accept: Option<&str>, default: RestEncoding) -> Option<&'static str> {
+	let leaves_it_to_the_node = accept.map_or(true, |accept| {
+		accept.split(',').all(|media_range| {
+			let media_type = media_range.split(';').next().unwrap_or_default().trim();
+			matches!(media_type, "" | "*/*" | "application/*")
+		})
+	});
+	leaves_it_to_the_node.then(|| default.media_type())
+}
+
+/// The header on responses whose event lists were truncated to the configured maximum,
+/// giving the maximum applied.
+pub const EVENTS_TRUNCATED_HEADER: &str = "x-maptos-events-truncated";
+
+/// Where the event lists of a response are.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EventLists {
+	/// The response is itself a list of events.
+	Events,
+	/// The response is a transaction, a list of them or a block, each transaction listing its
+	/// events.
+	Transactions,
+}
+
+/// Where the response to a request lists events, or `None` when it lists none.
+pub fn event_lists(method: &Method, path: &str) -> Option<EventLists> {
+	match RestRoute::matching(method.as_str(), path)? {
+		// `/v1/accounts/{address}/events/...` and `/v1/accounts/{address}/transactions`
+		RestRoute::Accounts => match path.trim_end_matches('/').split('/').nth(4) {
+			Some("events") => Some(EventLists::Events),
+			Some("transactions") => Some(EventLists::Transactions),
+			_ => None,
+		},
+		RestRoute::Blocks | RestRoute::Transactions | RestRoute::SimulateTransaction => {
+			Some(EventLists::Transactions)
+		}
+		_ => None,
+	}
+}
+
+/// Truncates each event list of the JSON response `body` to `max_events` events, returning
+/// whether any was longer.
+pub fn truncate_events(body: &mut serde_json::Value, lists: EventLists, max_events: usize) -> bool {
+	fn truncate(list: Option<&mut serde_json::Value>, max_events: usize) -> bool {
+		match list {
+			Some(serde_json::Value::Array(events)) if events.len() > max_events => {
+				events.truncate(max_events);
+				true
+			}
+			_ => false,
+		}
+	}
+	fn truncate_each(transactions: &mut [serde_json::Value], max_events: usize) -> bool {
+		transactions.iter_mut().fold(false, |truncated, transaction| {
+			truncate(transaction.get_mut("events"), max_events) || truncated
+		})
+	}
+
+	match (lists, body) {
+		(EventLists::Events, body) => truncate(Some(body), max_events),
+		(EventLists::Transactions, serde_json::Value::Array(transactions)) => {
+			truncate_each(transactions, max_events)
+		}
+		(EventLists::Transactions, body) => match body.get_mut("transactions") {
+			Some(serde_json::Value::Array(transactions)) => truncate_each(transactions, max_events),
+			_ => truncate(body.get_mut("events"), max_events),
+		},
+	}
+}
+
+/// The fields of REST responses which hold an account address, or a list of them.
+const ADDRESS_FIELDS: &[&str] =
+	&["address", "sender", "account_address", "proposer", "fee_payer_address", "secondary_signers"];
+
+/// The fields of REST responses holding Move values, which are left as the API writes them.
+const MOVE_VALUE_FIELDS: &[&str] = &["data"];
+
+/// The fields of transaction payloads holding Move values, besides [`MOVE_VALUE_FIELDS`].
+const PAYLOAD_MOVE_VALUE_FIELDS: &[&str] = &["data", "arguments"];
+
+/// Rewrites the account addresses of the JSON response `body` in `format`, returning whether
+/// any changed.
+///
+/// Only the fields the API itself fills are rewritten: Move values, such as resource and event
+/// `data` or the `arguments` of a payload, and the addresses within Move type names are left
+/// as the API writes them.
+pub fn format_addresses(body: &mut serde_json::Value, format: AddressFormat) -> bool {
+	format_addresses_skipping(body, format, MOVE_VALUE_FIELDS)
+}
+
+fn format_addresses_skipping(
+	body: &mut serde_json::Value,
+	format: AddressFormat,
+	skipped: &[&str],
+) -> bool {
+	fn reformat(value: &mut serde_json::Value, format: AddressFormat) -> bool {
+		match value {
+			serde_json::Value::String(raw) => match format.reformat(raw) {
+				Some(formatted) if formatted != *raw => {
+					*raw = formatted;
+					true
+				}
+				_ => false,
+			},
+			serde_json::Value::Array(values) => values
+				.iter_mut()
+				.fold(false, |changed, value| reformat(value, format) || changed),
+			_ => false,
+		}
+	}
+
+	match body {
+		serde_json::Value::Object(fields) => {
+			fields.iter_mut().fold(false, |changed, (key, value)| {
+				let reformatted = match key.as_str() {
+					key if skipped.contains(&key) => false,
+					key if ADDRESS_FIELDS.contains(&key) => reformat(value, format),
+					"payload" => {
+						format_addresses_skipping(value, format, PAYLOAD_MOVE_VALUE_FIELDS)
+					}
+					_ => format_addresses_skipping(value, format, skipped),
+				};
+				reformatted || changed
+			})
+		}
+		serde_json::Value::Array(values) => values.iter_mut().fold(false, |changed, value| {
+			format_addresses_skipping(value, format, skipped) || changed
+		}),
+		_ => false,
+	}
+}
+
+/// `response` with its account addresses written by [`format_addresses`] and, for `events`,
+/// its event lists truncated by [`truncate_events`] and flagged with
+/// [`EVENTS_TRUNCATED_HEADER`] when any was.
+///
+/// Only successful JSON responses are read; BCS responses are passed on whole.
+async fn rewrite_json(
+	mut response: poem::Response,
+	events: Option<EventLists>,
+	max_events: usize,
+	address_format: AddressFormat,
+) -> poem::Result<poem::Response> {
+	let is_json = response.status().is_success()
+		&& response
+			.content_type()
+			.is_some_and(|content_type| content_type.starts_with("application/json"));
+	if !is_json {
+		return Ok(response);
+	}
+	let body = response.take_body().into_bytes().await?;
+	let mut value = match serde_json::from_slice::<serde_json::Value>(&body) {
+		Ok(value) => value,
+		Err(_) => {
+			response.set_body(body);
+			return Ok(response);
+		}
+	};
+	let truncated = events.is_some_and(|lists| truncate_events(&mut value, lists, max_events));
+	let reformatted = format_addresses(&mut value, address_format);
+	if truncated || reformatted {
+		let rewritten = serde_json::to_vec(&value).map_err(poem::error::InternalServerError)?;
+		response.headers_mut().remove(header::CONTENT_LENGTH);
+		if truncated {
+			response
+				.headers_mut()
+				.insert(EVENTS_TRUNCATED_HEADER, HeaderValue::from(max_events));
+		}
+		response.set_body(rewritten);
+	} else {
+		response.set_body(body);
+	}
+	Ok(response)
+}
+
+/// Resolves when the process is asked to stop.
+pub(crate) async fn shutdown_signal() {
+	crate::shutdown::signal().await;
+	info!("Shutdown requested, draining in-flight requests");
+}
 
 #[derive(Clone)]
 pub struct Service {
@@ -20,6 +289,57 @@ pub struct Service {
 	context: Arc<aptos_api::Context>,
 	// URL for the API endpoint
 	listen_url: String,
+	// Where the API accepts connections, resolved when the service starts
+	listen_hostname: String,
+	listen_port: u16,
+	// TLS files to serve the API with, if any
+	tls: Option<TlsFiles>,
+	// Origins allowed to call the API from a browser
+	cors_origins: CorsOrigins,
+	// The prefix the API routes are mounted under, empty for the root
+	base_path: String,
+	// The route groups served, the others are answered with 404
+	allowed_methods: RestRouteSet,
+	// The bearer tokens requests must present and the scopes each grants, none to serve all
+	scoped_tokens: RestScopedTokens,
+	// Largest request body accepted, larger ones are rejected with 413
+	max_body_bytes: usize,
+	// Most items a paginated request returns, larger limits are lowered to it
+	max_page_size: u16,
+	// Most events a response lists per transaction or events query, longer lists are truncated
+	max_events: usize,
+	// How account addresses are written in API responses
+	address_format: AddressFormat,
+	// The encoding of responses to API requests which accept any
+	default_encoding: RestEncoding,
+	// Concurrent connections served and the listen backlog
+	connection_limits: ConnectionLimits,
+	// How long a connection may sit idle before it is closed, `None` to keep it open
+	idle_timeout: Option<Duration>,
+	// Time allowed for in-flight requests to finish on shutdown
+	shutdown_grace: Duration,
+	// Health probes served alongside the API, if enabled
+	health: Option<HealthService>,
+	// Metrics endpoint served alongside the API, if enabled
+	metrics_service: Option<MetricsService>,
+	// Counters the API requests are recorded in
+	metrics: Metrics,
+	// Whether transaction submissions are refused
+	read_only: bool,
+	// The request timeout and log sample rate, read for every request so reloads apply
+	runtime_config: SharedRuntimeConfig,
+	// Worker threads of the runtime the API is served on
+	worker_threads: usize,
+	// Whether the API is served; the health probes and metrics are served regardless
+	enabled: bool,
+	// What `/version` reports, when it is served
+	version: Option<Arc<VersionInfo>>,
+	// Where the API, the health probes and the metrics are bound, once they are
+	bound_addrs: BoundAddrs,
+	// The latest versions whose state is preloaded before the API is served, if any
+	preload: Option<u64>,
+	// Finished once the state is preloaded
+	warmup: Warmup,
 }
 
 impl Service {
@@ -29,6 +349,10 @@ impl Service {
 			mempool_client_sender,
 			maptos_config,
 			node_config,
+			bound_addrs,
+			reloader,
+			metrics,
+			warmup,
 		} = cx;
 		let context = Arc::new(aptos_api::Context::new(
 			maptos_config.chain.maptos_chain_id.clone(),
@@ -37,12 +361,62 @@ impl Service {
 			node_config.clone(),
 			None,
 		));
-		let listen_url = format!(
-			"{}:{}",
-			maptos_config.chain.maptos_rest_listen_hostname,
-			maptos_config.chain.maptos_rest_listen_port
-		);
-		Service { context, listen_url }
+		let listen_hostname = maptos_config.chain.maptos_rest_listen_hostname.clone();
+		let listen_port = maptos_config.chain.maptos_rest_listen_port;
+		let listen_url = format!("{}:{}", listen_hostname, listen_port);
+		let tls = maptos_config.rest_tls();
+		let cors_origins = maptos_config.rest_cors_origins();
+		let base_path = maptos_config.chain.rest_base_path().to_string();
+		let allowed_methods = maptos_config.rest_allowed_methods().clone();
+		let scoped_tokens = maptos_config.rest_scoped_tokens().clone();
+		let max_body_bytes = maptos_config.chain.rest_max_body_bytes();
+		let max_page_size = maptos_config.chain.rest_max_page_size();
+		let max_events = maptos_config.chain.rest_max_events();
+		let address_format = maptos_config.chain.rest_address_format();
+		let default_encoding = maptos_config.chain.rest_default_encoding();
+		let connection_limits = maptos_config.chain.rest_connection_limits();
+		let idle_timeout = maptos_config.chain.rest_idle_timeout();
+		let shutdown_grace = maptos_config.chain.shutdown_grace();
+		let health = HealthService::new(cx);
+		let metrics_service = MetricsService::new(cx);
+		let read_only = maptos_config.is_read_only();
+		let worker_threads = maptos_config.chain.rest_worker_threads();
+		let enabled = maptos_config.rest_enabled();
+		let preload = maptos_config.preload();
+		let version = maptos_config
+			.chain
+			.expose_version()
+			.then(|| Arc::new(VersionInfo::new(maptos_config)));
+		Service {
+			context,
+			listen_url,
+			listen_hostname,
+			listen_port,
+			tls,
+			cors_origins,
+			base_path,
+			allowed_methods,
+			scoped_tokens,
+			max_body_bytes,
+			max_page_size,
+			max_events,
+			address_format,
+			default_encoding,
+			connection_limits,
+			idle_timeout,
+			shutdown_grace,
+			health,
+			metrics_service,
+			metrics: metrics.clone(),
+			read_only,
+			runtime_config: reloader.shared(),
+			worker_threads,
+			enabled,
+			version,
+			bound_addrs: bound_addrs.clone(),
+			preload,
+			warmup: warmup.clone(),
+		}
 	}
 
 	pub fn api_context(&self) -> Arc<aptos_api::Context> {
@@ -53,8 +427,17 @@ impl Service {
 		get_apis(self.api_context())
 	}
 
+	/// Where the servers started from the same context are bound, as they come up.
+	pub fn bound_addrs(&self) -> BoundAddrs {
+		self.bound_addrs.clone()
+	}
+
 	pub fn run(&self) -> impl Future<Output = Result<(), anyhow::Error>> + Send {
-		info!("Starting maptos-opt-executor services at: {:?}", self.listen_url);
+		if self.enabled {
+			info!("Starting maptos-opt-executor services at: {:?}", self.listen_url);
+		} else {
+			info!("MAPTOS_REST_ENABLED=false, not serving the maptos-opt-executor API");
+		}
 
 		let api_service =
 			get_api_service(self.api_context()).server(format!("http://{:?}", self.listen_url));
@@ -67,8 +450,46 @@ impl Service {
 		let cors = Cors::new()
 			.allow_methods(vec![Method::GET, Method::POST])
 			.allow_credentials(true);
-		let listener = TcpListener::bind(self.listen_url.clone());
-		let app = Route::new()
+		let (cors_enabled, cors) = match &self.cors_origins {
+			CorsOrigins::Disabled => (false, cors),
+			CorsOrigins::Any => (true, cors),
+			CorsOrigins::List(origins) => (true, cors.allow_origins(origins.iter().cloned())),
+		};
+		let listen_targets = endpoints::listen_targets(
+			"MAPTOS_API_LISTEN_HOSTNAME",
+			&self.listen_hostname,
+			self.listen_port,
+		);
+		let tls = self.tls.clone();
+		let connection_limits = self.connection_limits;
+		let idle_timeout = self.idle_timeout;
+		let shutdown_grace = self.shutdown_grace;
+		let read_only = self.read_only;
+		let max_page_size = self.max_page_size;
+		let max_events = self.max_events;
+		let address_format = self.address_format;
+		let default_encoding = self.default_encoding;
+		let allowed_methods = self.allowed_methods.clone();
+		let scoped_tokens = self.scoped_tokens.clone();
+		let runtime_config = self.runtime_config.clone();
+		let worker_threads = self.worker_threads;
+		let enabled = self.enabled;
+		let bound_addrs = self.bound_addrs.clone();
+		let preload = self.preload;
+		let warmup = self.warmup.clone();
+		let db_reader = self.context.db.clone();
+		let health = self.health.as_ref().map(HealthService::run);
+		let metrics_service = self.metrics_service.as_ref().map(MetricsService::run);
+		let side_servers = async move {
+			futures::try_join!(run_if_enabled(health), run_if_enabled(metrics_service)).map(|_| ())
+		};
+		let metrics = self.metrics.clone();
+		let app = Route::new();
+		let app = match &self.version {
+			Some(info) => app.at("/version", poem::get(version::version).data(info.clone())),
+			None => app,
+		};
+		let app = app
 			.at("/", poem::get(root_handler))
 			.nest("/v1", api_service)
 			.nest("/spec", ui)
@@ -78,11 +499,170 @@ impl Service {
 				"/set_failpoint",
 				poem::get(set_failpoints::set_failpoint_poem).data(self.api_context()),
 			)
-			.with(cors);
+			.with_if(cors_enabled, cors)
+			.with(SizeLimit::new(self.max_body_bytes))
+			.around(move |endpoint, mut request| {
+				let runtime = runtime_config.load_full();
+				let metrics = metrics.clone();
+				let allowed =
+					allowed_methods.allows(request.method().as_str(), request.uri().path());
+				let access = scoped_tokens.check(
+					request
+						.headers()
+						.get(header::AUTHORIZATION)
+						.and_then(|value| value.to_str().ok()),
+					request.method().as_str(),
+					request.uri().path(),
+				);
+				async move {
+					if !allowed {
+						metrics.record_rest_request(StatusCode::NOT_FOUND);
+						return Ok(StatusCode::NOT_FOUND.into_response());
+					}
+					let denied = match access {
+						RestAccess::Allowed => None,
+						RestAccess::Unauthorized => Some(StatusCode::UNAUTHORIZED),
+						RestAccess::Forbidden => Some(StatusCode::FORBIDDEN),
+					};
+					if let Some(status) = denied {
+						metrics.record_rest_request(status);
+						return Ok(status.into_response());
+					}
+					if read_only && submits_transactions(request.method(), request.uri().path()) {
+						metrics.record_rest_request(StatusCode::FORBIDDEN);
+						return Ok(read_only_rejection());
+					}
+					let clamped = clamp_page_size_uri(request.uri(), max_page_size);
+					if let Some(uri) = &clamped {
+						request.set_uri(uri.clone());
+					}
+					let is_api = request.uri().path().starts_with("/v1");
+					if is_api {
+						let accept = request.headers().get(header::ACCEPT);
+						let accept = accept.and_then(|accept| accept.to_str().ok());
+						if let Some(accept) = default_accept(accept, default_encoding) {
+							request
+								.headers_mut()
+								.insert(header::ACCEPT, HeaderValue::from_static(accept));
+						}
+					}
+					let events = event_lists(request.method(), request.uri().path());
+					let submission = submits_transactions(request.method(), request.uri().path());
+					let sampled = runtime.rest_log_sample_rate.sample().then(|| {
+						(request.method().clone(), request.uri().path().to_string(), Instant::now())
+					});
+					let response = match tokio::time::timeout(
+						runtime.rest_request_timeout,
+						endpoint.call(request),
+					)
+					.await
+					{
+						Ok(response) => response.map(IntoResponse::into_response),
+						Err(_) => Ok(StatusCode::GATEWAY_TIMEOUT.into_response()),
+					};
+					let response = match response {
+						Ok(response) if is_api => {
+							rewrite_json(response, events, max_events, address_format).await
+						}
+						response => response,
+					};
+					let response = match response {
+						Ok(response) if submission => Ok(unavailable_when_full(response)),
+						Err(e) if submission => Ok(unavailable_when_full(e.into_response())),
+						response => response,
+					};
+					let response = response.map(|mut response| {
+						if clamped.is_some() {
+							response
+								.headers_mut()
+								.insert(PAGE_SIZE_CLAMPED_HEADER, HeaderValue::from(max_page_size));
+						}
+						response
+					});
+					let status = match &response {
+						Ok(response) => response.status(),
+						Err(e) => e.status(),
+					};
+					metrics.record_rest_request(status);
+					if let Some((method, path, started)) = sampled {
+						info!(
+							%method,
+							%path,
+							status = status.as_u16(),
+							latency_ms = started.elapsed().as_millis() as u64,
+							"REST request"
+						);
+					}
+					response
+				}
+			});
+		// Nested routes see request paths with the prefix stripped, as the middleware expects.
+		let app = if self.base_path.is_empty() {
+			app.boxed()
+		} else {
+			Route::new().nest(self.base_path.clone(), app).boxed()
+		};
+
+		server_runtime::run_on_own_runtime("maptos-rest", worker_threads, async move {
+			if !enabled {
+				warmup.finish();
+				return side_servers.await;
+			}
+			// The health probes are served meanwhile, reporting the node not ready.
+			let server = async move {
+				if let Some(versions) = preload {
+					info!("Preloading the state of the latest {} versions", versions);
+					let preloaded = tokio::task::spawn_blocking(move || {
+						warmup::preload_recent_state(&db_reader, versions)
+					})
+					.await?;
+					match preloaded {
+						Ok(read) => info!("Preloaded {} state values", read),
+						// A cold cache only slows the first requests down.
+						Err(e) => {
+							warn!("Failed to preload state, serving with cold caches: {:?}", e)
+						}
+					}
+				}
+				warmup.finish();
+				let listener = listener::bind_all(listen_targets?, connection_limits)?;
+				let listener = match tls {
+					Some(tls) => {
+						let pem = tls.load()?;
+						info!("Serving maptos-opt-executor API over TLS");
+						listener
+							.rustls(
+								RustlsConfig::new()
+									.fallback(RustlsCertificate::new().cert(pem.cert).key(pem.key)),
+							)
+							.boxed()
+					}
+					None => listener.boxed(),
+				};
+				let acceptor = listener.into_acceptor().await?;
+				bound_addrs.record_acceptor(BoundAddrs::REST, &acceptor);
+				let server = Server::new_with_acceptor(acceptor);
+				let server = match idle_timeout {
+					Some(idle_timeout) => server.idle_timeout(idle_timeout),
+					None => server,
+				};
+				server
+					.run_with_graceful_shutdown(app, shutdown_signal(), Some(shutdown_grace))
+					.await
+					.map_err(|e| anyhow::anyhow!("Server error: {:?}", e))
+			};
+			futures::try_join!(server, side_servers).map(|_| ())
+		})
+	}
+}
 
-		Server::new(listener)
-			.run(app)
-			.map_err(|e| anyhow::anyhow!("Server error: {:?}", e))
+/// Runs `server` if it is enabled, or resolves at once.
+async fn run_if_enabled(
+	server: Option<impl Future<Output = Result<(), anyhow::Error>>>,
+) -> Result<(), anyhow::Error> {
+	match server {
+		Some(server) => server.await,
+		None => Ok(()),
 	}
 }
 
@@ -99,6 +679,8 @@ mod tests {
 	use futures::channel::oneshot;
 	use futures::SinkExt;
 	use maptos_execution_util::config::chain::Config;
+	use tokio::io::{AsyncReadExt, AsyncWriteExt};
+	use tokio::net::TcpStream;
 	use tokio::sync::mpsc;
 
 	fn create_signed_transaction(sequence_number: u64, chain_config: &Config) -> SignedTransaction {
@@ -112,6 +694,162 @@ mod tests {
 		)
 	}
 
+	#[test]
+	fn test_only_submissions_are_refused_when_read_only() {
+		assert!(submits_transactions(&Method::POST, "/v1/transactions"));
+		assert!(submits_transactions(&Method::POST, "/v1/transactions/batch/"));
+		assert!(!submits_transactions(&Method::POST, "/v1/transactions/simulate"));
+		assert!(!submits_transactions(&Method::POST, "/v1/view"));
+		assert!(!submits_transactions(&Method::GET, "/v1/transactions"));
+	}
+
+	#[test]
+	fn test_full_submissions_are_answered_as_unavailable() {
+		let full = unavailable_when_full(StatusCode::INSUFFICIENT_STORAGE.into_response());
+		assert_eq!(full.status(), StatusCode::SERVICE_UNAVAILABLE);
+		assert_eq!(full.headers().get(header::RETRY_AFTER), Some(&HeaderValue::from_static("1")));
+
+		let refused = unavailable_when_full(StatusCode::BAD_REQUEST.into_response());
+		assert_eq!(refused.status(), StatusCode::BAD_REQUEST);
+		assert!(refused.headers().get(header::RETRY_AFTER).is_none());
+	}
+
+	#[test]
+	fn test_page_sizes_above_the_maximum_are_clamped() {
+		assert_eq!(clamp_page_size("limit=5000", 1000), Some("limit=1000".to_string()));
+		assert_eq!(
+			clamp_page_size("start=10&limit=1001", 1000),
+			Some("start=10&limit=1000".to_string())
+		);
+		assert_eq!(clamp_page_size("limit=1000", 1000), None);
+		assert_eq!(clamp_page_size("start=5000", 1000), None);
+		// Limits which are not numbers are left for the API to reject.
+		assert_eq!(clamp_page_size("limit=lots", 1000), None);
+
+		let uri: Uri = "/v1/accounts/0x1/resources?limit=9999".parse().unwrap();
+		assert_eq!(
+			clamp_page_size_uri(&uri, 100),
+			Some("/v1/accounts/0x1/resources?limit=100".parse().unwrap())
+		);
+		assert_eq!(clamp_page_size_uri(&"/v1/transactions".parse().unwrap(), 100), None);
+	}
+
+	#[test]
+	fn test_default_encoding_applies_unless_one_is_accepted() {
+		assert_eq!(default_accept(None, RestEncoding::Bcs), Some("application/x-bcs"));
+		assert_eq!(default_accept(Some("*/*"), RestEncoding::Bcs), Some("application/x-bcs"));
+		assert_eq!(default_accept(Some(""), RestEncoding::Json), Some("application/json"));
+		assert_eq!(default_accept(Some("application/json"), RestEncoding::Bcs), None);
+		assert_eq!(default_accept(Some("application/x-bcs;q=0.9, */*"), RestEncoding::Json), None);
+	}
+
+	#[tokio::test]
+	async fn test_events_over_the_cap_are_truncated_and_flagged() -> Result<(), anyhow::Error> {
+		let transaction = serde_json::json!({
+			"version": "7",
+			"events": [{ "sequence_number": "0" }, { "sequence_number": "1" }, { "sequence_number": "2" }],
+		});
+		let response = poem::Response::builder()
+			.content_type("application/json")
+			.body(serde_json::to_vec(&transaction)?);
+		let mut response =
+			rewrite_json(response, Some(EventLists::Transactions), 2, AddressFormat::Long)
+				.await
+				.unwrap();
+		assert_eq!(response.headers().get(EVENTS_TRUNCATED_HEADER), Some(&HeaderValue::from(2)));
+		let body: serde_json::Value =
+			serde_json::from_slice(&response.take_body().into_bytes().await?)?;
+		assert_eq!(body["events"].as_array().map(Vec::len), Some(2));
+		assert_eq!(body["version"], "7");
+
+		// Lists within the cap are passed on unflagged.
+		let response = poem::Response::builder()
+			.content_type("application/json")
+			.body(serde_json::to_vec(&transaction)?);
+		let response =
+			rewrite_json(response, Some(EventLists::Transactions), 3, AddressFormat::Long)
+				.await
+				.unwrap();
+		assert!(response.headers().get(EVENTS_TRUNCATED_HEADER).is_none());
+
+		assert_eq!(
+			event_lists(
+				&Method::GET,
+				"/v1/accounts/0x1/events/0x1::coin::CoinStore/withdraw_events"
+			),
+			Some(EventLists::Events)
+		);
+		assert_eq!(
+			event_lists(&Method::GET, "/v1/blocks/by_height/5"),
+			Some(EventLists::Transactions)
+		);
+		assert_eq!(event_lists(&Method::GET, "/v1/accounts/0x1/resources"), None);
+		Ok(())
+	}
+
+	#[test]
+	fn test_addresses_are_written_in_the_configured_format() {
+		let long = "0x000000000000000000000000000000000000000000000000000000000000cafe";
+		let hash = "0x00000000000000000000000000000000000000000000000000000000000000ab";
+		let transaction = serde_json::json!({
+			"hash": hash,
+			"sender": "0xcafe",
+			"secondary_signers": ["0x1"],
+			"changes": [{ "address": long, "type": "write_resource" }],
+		});
+
+		let mut body = transaction.clone();
+		assert!(format_addresses(&mut body, AddressFormat::Long));
+		assert_eq!(body["sender"], long);
+		assert_eq!(
+			body["secondary_signers"][0],
+			"0x0000000000000000000000000000000000000000000000000000000000000001"
+		);
+		assert_eq!(body["changes"][0]["address"], long);
+		// Hashes are not addresses, whatever their digits.
+		assert_eq!(body["hash"], hash);
+
+		let mut body = transaction;
+		assert!(format_addresses(&mut body, AddressFormat::Short));
+		assert_eq!(body["sender"], "0xcafe");
+		assert_eq!(body["changes"][0]["address"], "0xcafe");
+		assert_eq!(body["hash"], hash);
+		assert!(!format_addresses(&mut body, AddressFormat::Short));
+	}
+
+	#[test]
+	fn test_move_values_keep_their_addresses() {
+		let long = "0x000000000000000000000000000000000000000000000000000000000000cafe";
+		let mut body = serde_json::json!({
+			"sender": "0xcafe",
+			"payload": {
+				"function": "0x1::aptos_account::transfer",
+				"arguments": ["0xcafe", "1"],
+				"type_arguments": [],
+			},
+			"changes": [{ "address": "0xcafe", "data": { "data": { "address": "0xcafe" } } }],
+			"events": [{ "data": { "account_address": "0xcafe" } }],
+		});
+		let expected_values = (
+			body["payload"]["arguments"].clone(),
+			body["changes"][0]["data"].clone(),
+			body["events"][0]["data"].clone(),
+		);
+
+		assert!(format_addresses(&mut body, AddressFormat::Long));
+		assert_eq!(body["sender"], long);
+		assert_eq!(body["changes"][0]["address"], long);
+		assert_eq!(
+			(
+				body["payload"]["arguments"].clone(),
+				body["changes"][0]["data"].clone(),
+				body["events"][0]["data"].clone(),
+			),
+			expected_values
+		);
+		assert_eq!(body["payload"]["function"], "0x1::aptos_account::transfer");
+	}
+
 	#[tokio::test]
 	async fn test_pipe_mempool_while_server_running() -> Result<(), anyhow::Error> {
 		let (tx_sender, mut tx_receiver) = mpsc::channel(16);
@@ -145,4 +883,139 @@ mod tests {
 
 		Ok(())
 	}
+
+	/// The response the server at `addrs` answers a GET of `path` with, headers included.
+	async fn get(addrs: &[std::net::SocketAddr], path: &str) -> Result<String, anyhow::Error> {
+		send(addrs, &format!("GET {} HTTP/1.0\r\n\r\n", path)).await
+	}
+
+	/// The response the server at `addrs` answers a raw HTTP/1.0 `request` with.
+	async fn send(addrs: &[std::net::SocketAddr], request: &str) -> Result<String, anyhow::Error> {
+		let mut stream = TcpStream::connect(addrs).await?;
+		stream.write_all(request.as_bytes()).await?;
+		let mut response = String::new();
+		stream.read_to_string(&mut response).await?;
+		Ok(response)
+	}
+
+	/// The status code the server at `addrs` answers a GET of `path` with.
+	async fn get_status(addrs: &[std::net::SocketAddr], path: &str) -> Result<u16, anyhow::Error> {
+		let response = get(addrs, path).await?;
+		let status = response.split(' ').nth(1).ok_or_else(|| anyhow::anyhow!("no status"))?;
+		Ok(status.parse()?)
+	}
+
+	#[tokio::test]
+	async fn test_version_is_served_unless_hidden() -> Result<(), anyhow::Error> {
+		let (tx_sender, _tx_receiver) = mpsc::channel(16);
+		let (executor, _config, _tempdir) = Executor::try_test_default(GENESIS_KEYPAIR.0.clone())?;
+		let (mut context, _transaction_pipe) = executor.background(tx_sender)?;
+		context.maptos_config.chain.maptos_rest_listen_hostname = "127.0.0.1".to_string();
+		context.maptos_config.chain.maptos_rest_listen_port = 0;
+		context.maptos_config.chain.maptos_expose_version = true;
+		let service = Service::new(&context);
+		let handle = tokio::spawn(service.run());
+		let addrs = context.bound_addrs().wait_for(BoundAddrs::REST).await;
+
+		let response = get(&addrs, "/version").await?;
+		let body = response.split("\r\n\r\n").nth(1).unwrap_or_default();
+		let info: serde_json::Value = serde_json::from_str(body)?;
+		assert_eq!(info["version"], env!("CARGO_PKG_VERSION"));
+		assert_eq!(info["chain_id"], context.maptos_config.chain.maptos_chain_id.id());
+		handle.abort();
+
+		context.maptos_config.chain.maptos_expose_version = false;
+		assert!(Service::new(&context).version.is_none());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_routes_are_served_under_the_base_path() -> Result<(), anyhow::Error> {
+		let (tx_sender, _tx_receiver) = mpsc::channel(16);
+		let (executor, _config, _tempdir) = Executor::try_test_default(GENESIS_KEYPAIR.0.clone())?;
+		let (mut context, _transaction_pipe) = executor.background(tx_sender)?;
+		context.maptos_config.chain.maptos_rest_listen_hostname = "127.0.0.1".to_string();
+		context.maptos_config.chain.maptos_rest_listen_port = 0;
+		context.maptos_config.chain.maptos_rest_base_path = "/node-a/".to_string();
+		let service = Service::new(&context);
+		let handle = tokio::spawn(service.run());
+		let addrs = context.bound_addrs().wait_for(BoundAddrs::REST).await;
+
+		assert_eq!(get_status(&addrs, "/node-a/v1").await?, 200);
+		assert_eq!(get_status(&addrs, "/node-a/spec.json").await?, 200);
+		assert_eq!(get_status(&addrs, "/v1").await?, 404);
+
+		handle.abort();
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_routes_outside_the_allowlist_are_not_found() -> Result<(), anyhow::Error> {
+		let (tx_sender, _tx_receiver) = mpsc::channel(16);
+		let (executor, _config, _tempdir) = Executor::try_test_default(GENESIS_KEYPAIR.0.clone())?;
+		let (mut context, _transaction_pipe) = executor.background(tx_sender)?;
+		context.maptos_config.chain.maptos_rest_listen_hostname = "127.0.0.1".to_string();
+		context.maptos_config.chain.maptos_rest_listen_port = 0;
+		context.maptos_config.chain.maptos_rest_method_allowlist = "index,accounts".parse()?;
+		let service = Service::new(&context);
+		let handle = tokio::spawn(service.run());
+		let addrs = context.bound_addrs().wait_for(BoundAddrs::REST).await;
+
+		assert_eq!(get_status(&addrs, "/v1").await?, 200);
+		assert_eq!(get_status(&addrs, "/spec.json").await?, 404);
+		assert_eq!(get_status(&addrs, "/v1/-/healthy").await?, 404);
+
+		handle.abort();
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_explicit_accept_overrides_the_default_encoding() -> Result<(), anyhow::Error> {
+		let (tx_sender, _tx_receiver) = mpsc::channel(16);
+		let (executor, _config, _tempdir) = Executor::try_test_default(GENESIS_KEYPAIR.0.clone())?;
+		let (mut context, _transaction_pipe) = executor.background(tx_sender)?;
+		context.maptos_config.chain.maptos_rest_listen_hostname = "127.0.0.1".to_string();
+		context.maptos_config.chain.maptos_rest_listen_port = 0;
+		context.maptos_config.chain.maptos_rest_default_encoding = RestEncoding::Bcs;
+		let service = Service::new(&context);
+		let handle = tokio::spawn(service.run());
+		let addrs = context.bound_addrs().wait_for(BoundAddrs::REST).await;
+
+		let content_type = |response: String| {
+			response.lines().find_map(|line| {
+				line.to_ascii_lowercase().strip_prefix("content-type: ").map(str::to_string)
+			})
+		};
+		let response = get(&addrs, "/v1").await?;
+		assert_eq!(content_type(response).as_deref(), Some("application/x-bcs"));
+		let response = send(&addrs, "GET /v1 HTTP/1.0\r\nAccept: application/json\r\n\r\n").await?;
+		assert!(content_type(response).unwrap_or_default().starts_with("application/json"));
+
+		handle.abort();
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_tokens_without_the_route_scope_are_forbidden() -> Result<(), anyhow::Error> {
+		let (tx_sender, _tx_receiver) = mpsc::channel(16);
+		let (executor, _config, _tempdir) = Executor::try_test_default(GENESIS_KEYPAIR.0.clone())?;
+		let (mut context, _transaction_pipe) = executor.background(tx_sender)?;
+		context.maptos_config.chain.maptos_rest_listen_hostname = "127.0.0.1".to_string();
+		context.maptos_config.chain.maptos_rest_listen_port = 0;
+		context.maptos_config.chain.maptos_rest_scoped_tokens = "reader-token:read".parse()?;
+		let service = Service::new(&context);
+		let handle = tokio::spawn(service.run());
+		let addrs = context.bound_addrs().wait_for(BoundAddrs::REST).await;
+
+		let status = |response: String| response.split(' ').nth(1).map(str::to_string);
+		let read = "GET /v1 HTTP/1.0\r\nAuthorization: Bearer reader-token\r\n\r\n";
+		assert_eq!(status(send(&addrs, read).await?).as_deref(), Some("200"));
+		let submit = "POST /v1/transactions HTTP/1.0\r\nAuthorization: Bearer reader-token\r\n\
+			Content-Length: 0\r\n\r\n";
+		assert_eq!(status(send(&addrs, submit).await?).as_deref(), Some("403"));
+		assert_eq!(get_status(&addrs, "/v1").await?, 401);
+
+		handle.abort();
+		Ok(())
+	}
 }
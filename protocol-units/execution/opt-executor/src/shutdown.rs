@@ -0,0 +1,45 @@
+//! The process-wide shutdown, asked for either by an interrupt or by the node itself.
+
+use futures::future;
+use tokio::signal::unix::{signal as unix_signal, SignalKind};
+use tokio::sync::watch;
+
+use std::sync::OnceLock;
+
+fn requests() -> &'static watch::Sender<bool> {
+	static REQUESTS: OnceLock<watch::Sender<bool>> = OnceLock::new();
+	REQUESTS.get_or_init(|| watch::Sender::new(false))
+}
+
+/// Asks every server waiting on [`signal`] to drain its in-flight work and stop.
+pub fn request() {
+	requests().send_replace(true);
+}
+
+/// Resolves once [`request`] has been called, at once if it already has been.
+pub async fn requested() {
+	// The sender is never dropped, so waiting only ends once shutdown is requested.
+	let _ = requests().subscribe().wait_for(|requested| *requested).await;
+}
+
+/// Resolves once the process receives `kind`, never when it cannot listen for it.
+async fn received(kind: SignalKind) {
+	match unix_signal(kind) {
+		Ok(mut signals) => {
+			signals.recv().await;
+		}
+		Err(e) => {
+			tracing::error!("Failed to listen for the {:?} shutdown signal: {:?}", kind, e);
+			future::pending::<()>().await;
+		}
+	}
+}
+
+/// Resolves when the process is interrupted or terminated, or a shutdown is requested.
+pub async fn signal() {
+	tokio::select! {
+		() = received(SignalKind::interrupt()) => {}
+		() = received(SignalKind::terminate()) => {}
+		() = requested() => {}
+	}
+}
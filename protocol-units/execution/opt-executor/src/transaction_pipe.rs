@@ -1,5 +1,8 @@
 //! Task processing incoming transactions for the opt API.
 
+use crate::ordering::OrderedTransactions;
+use crate::{InFlightPermit, InFlightTracker};
+
 use aptos_config::config::NodeConfig;
 use aptos_mempool::core_mempool::CoreMempool;
 use aptos_mempool::SubmissionStatus;
@@ -9,15 +12,22 @@ use aptos_types::mempool_status::{MempoolStatus, MempoolStatusCode};
 use aptos_types::transaction::SignedTransaction;
 use aptos_types::vm_status::DiscardedVMStatus;
 use aptos_vm_validator::vm_validator::{self, TransactionValidation, VMValidator};
-
-use futures::channel::mpsc as futures_mpsc;
+use maptos_execution_util::config::{
+	chain,
+	clock::Clock,
+	load_shedding::{MempoolOrdering, OverflowPolicy},
+	Config,
+};
+
+use futures::channel::{mpsc as futures_mpsc, oneshot};
 use futures::StreamExt;
 use thiserror::Error;
-use tokio::sync::mpsc;
-use tracing::{debug, info, info_span, warn, Instrument};
+use tokio::sync::mpsc::{self, error::TrySendError};
+use tracing::{debug, info, info_span, warn, Span};
 
-use std::sync::{atomic::AtomicU64, Arc};
-use std::time::{Duration, Instant};
+use std::collections::VecDeque;
+use std::sync::Arc;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 const GC_INTERVAL: Duration = Duration::from_secs(30);
 
@@ -38,6 +48,111 @@ impl From<anyhow::Error> for Error {
 	}
 }
 
+/// The largest transactions accepted for submission, checked before any validation runs.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TransactionLimits {
+	pub max_gas_amount: u64,
+	pub max_transaction_bytes: usize,
+	/// How far past the present a transaction may expire
+	pub max_future: Duration,
+}
+
+impl TransactionLimits {
+	pub fn from_config(config: &chain::Config) -> Self {
+		Self {
+			max_gas_amount: config.rest_max_gas_amount(),
+			max_transaction_bytes: config.rest_max_transaction_bytes(),
+			max_future: config.max_txn_future(),
+		}
+	}
+
+	/// The status `transaction` is refused with, if it exceeds a limit.
+	///
+	/// The API answers a refusal with 400 and the status, as for any invalid transaction.
+	pub fn check(&self, transaction: &SignedTransaction) -> Option<DiscardedVMStatus> {
+		if transaction.raw_txn_bytes_len() > self.max_transaction_bytes {
+			Some(DiscardedVMStatus::EXCEEDED_MAX_TRANSACTION_SIZE)
+		} else if transaction.max_gas_amount() > self.max_gas_amount {
+			Some(DiscardedVMStatus::MAX_GAS_UNITS_EXCEEDS_MAX_GAS_UNITS_BOUND)
+		} else {
+			None
+		}
+	}
+}
+
+/// What becomes of a submission made while every slot in flight is taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct OverflowHandling {
+	pub policy: OverflowPolicy,
+	/// The longest a submission waits for a slot under [`OverflowPolicy::Block`]
+	/// and [`OverflowPolicy::DropOldest`]
+	pub block_timeout: Duration,
+}
+
+impl OverflowHandling {
+	pub fn from_config(config: &Config) -> Self {
+		Self {
+			policy: config.load_shedding.inflight_overflow_policy,
+			block_timeout: config.chain.rest_request_timeout(),
+		}
+	}
+}
+
+/// Whether `transaction` has expired by `now`, as it would be by a block at that time.
+pub fn is_expired(transaction: &SignedTransaction, now: SystemTime) -> bool {
+	let now_secs = now.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs());
+	transaction.expiration_timestamp_secs() <= now_secs
+}
+
+/// Whether `transaction` expires more than `max_future` after `now`, which would let it
+/// linger in the mempool long after it was submitted.
+pub fn expires_too_far_ahead(
+	transaction: &SignedTransaction,
+	now: SystemTime,
+	max_future: Duration,
+) -> bool {
+	let now_secs = now.duration_since(UNIX_EPOCH).map_or(0, |since| since.as_secs());
+	transaction.expiration_timestamp_secs() > now_secs.saturating_add(max_future.as_secs())
+}
+
+/// A submission not answered yet, as it waits for a slot in flight.
+struct Submission {
+	transaction: SignedTransaction,
+	callback: oneshot::Sender<anyhow::Result<SubmissionStatus>>,
+	span: Span,
+	// When it stops waiting and is refused
+	deadline: tokio::time::Instant,
+}
+
+impl Submission {
+	fn answer(self, status: SubmissionStatus) {
+		self.callback.send(Ok(status)).unwrap_or_else(|_| {
+			debug!("SubmitTransaction request canceled");
+		});
+	}
+
+	/// Refuses the submission for lack of a slot in flight.
+	///
+	/// The API answers it with 507 as when the mempool is full, which the REST service turns
+	/// into 503 for clients to retry later.
+	fn shed(self) {
+		self.span.in_scope(|| {
+			info!(
+				target: "movement_timing",
+				"shedding_load"
+			);
+		});
+		self.answer((MempoolStatus::new(MempoolStatusCode::MempoolIsFull), None));
+	}
+}
+
+/// What a tick of the pipe woke up for, besides room in the transaction channel.
+enum Woken {
+	/// The slot the first waiting submission was waiting for, or `None` once it waited too long
+	Slot(Option<InFlightPermit>),
+	Request(MempoolClientRequest),
+}
+
 pub struct TransactionPipe {
 	// The receiver for the mempool client.
 	mempool_client_receiver: futures_mpsc::Receiver<MempoolClientRequest>,
@@ -47,22 +162,35 @@ pub struct TransactionPipe {
 	db_reader: Arc<dyn DbReader>,
 	// State of the Aptos mempool
 	core_mempool: CoreMempool,
-	// Shared reference on the counter of transactions in flight.
-	transactions_in_flight: Arc<AtomicU64>,
-	// The configured limit on transactions in flight
-	in_flight_limit: u64,
+	// The transactions in flight, shared with the executor which releases them.
+	transactions_in_flight: InFlightTracker,
+	// The largest transactions accepted
+	transaction_limits: TransactionLimits,
+	// What becomes of submissions once every slot in flight is taken
+	overflow: OverflowHandling,
+	// Accepted transactions waiting for room in the transaction channel, each with the slot in
+	// flight it holds
+	pending: OrderedTransactions<InFlightPermit>,
+	// Submissions waiting for a slot in flight, in the order they were made
+	waiting: VecDeque<Submission>,
+	// The clock transaction expiration is checked against
+	clock: Arc<dyn Clock>,
 	// Timestamp of the last garbage collection
 	last_gc: Instant,
 }
 
 impl TransactionPipe {
+	#[allow(clippy::too_many_arguments)]
 	pub(crate) fn new(
 		mempool_client_receiver: futures_mpsc::Receiver<MempoolClientRequest>,
 		transaction_sender: mpsc::Sender<SignedTransaction>,
 		db_reader: Arc<dyn DbReader>,
 		node_config: &NodeConfig,
-		transactions_in_flight: Arc<AtomicU64>,
-		transactions_in_flight_limit: u64,
+		transactions_in_flight: InFlightTracker,
+		transaction_limits: TransactionLimits,
+		overflow: OverflowHandling,
+		ordering: MempoolOrdering,
+		clock: Arc<dyn Clock>,
 	) -> Self {
 		TransactionPipe {
 			mempool_client_receiver,
@@ -70,7 +198,11 @@ impl TransactionPipe {
 			db_reader,
 			core_mempool: CoreMempool::new(node_config),
 			transactions_in_flight,
-			in_flight_limit: transactions_in_flight_limit,
+			transaction_limits,
+			overflow,
+			pending: OrderedTransactions::new(ordering),
+			waiting: VecDeque::new(),
+			clock,
 			last_gc: Instant::now(),
 		}
 	}
@@ -84,9 +216,33 @@ impl TransactionPipe {
 	/// Pipes a batch of transactions from the mempool to the transaction channel.
 	/// todo: it may be wise to move the batching logic up a level to the consuming structs.
 	pub(crate) async fn tick(&mut self) -> Result<(), Error> {
-		let next = self.mempool_client_receiver.next().await;
-		if let Some(request) = next {
-			match request {
+		let waiting_deadline = self.waiting.front().map(|submission| submission.deadline);
+		// Requests keep being served while submissions wait for a slot, so that a full
+		// pipe does not hold up lookups or the passing on of pending transactions.
+		let next = tokio::select! {
+			// Pending transactions are passed on as soon as the channel has room for them.
+			room = self.transaction_sender.reserve(), if !self.pending.is_empty() => {
+				room.map_err(|e| anyhow::anyhow!("Error sending transaction: {:?}", e))?;
+				None
+			}
+			permit = self.transactions_in_flight.reserve_until(
+				waiting_deadline.unwrap_or_else(tokio::time::Instant::now)
+			), if waiting_deadline.is_some() => {
+				Some(Woken::Slot(permit))
+			}
+			next = self.mempool_client_receiver.next() => next.map(Woken::Request),
+		};
+		self.pass_on_pending()?;
+		match next {
+			None => {}
+			Some(Woken::Slot(permit)) => {
+				let submission = self.waiting.pop_front().expect("a submission is waiting");
+				match permit {
+					Some(permit) => self.admit(submission, permit)?,
+					None => submission.shed(),
+				}
+			}
+			Some(Woken::Request(request)) => match request {
 				MempoolClientRequest::SubmitTransaction(transaction, callback) => {
 					let span = info_span!(
 						target: "movement_timing",
@@ -95,10 +251,8 @@ impl TransactionPipe {
 						sender = %transaction.sender(),
 						sequence_number = transaction.sequence_number(),
 					);
-					let status = self.submit_transaction(transaction).instrument(span).await?;
-					callback.send(Ok(status)).unwrap_or_else(|_| {
-						debug!("SubmitTransaction request canceled");
-					});
+					let deadline = tokio::time::Instant::now() + self.overflow.block_timeout;
+					self.submit(Submission { transaction, callback, span, deadline })?;
 				}
 				MempoolClientRequest::GetTransactionByHash(hash, sender) => {
 					let mempool_result = self.core_mempool.get_by_hash(hash);
@@ -106,7 +260,7 @@ impl TransactionPipe {
 						debug!("GetTransactionByHash request canceled");
 					});
 				}
-			}
+			},
 		}
 
 		if self.last_gc.elapsed() >= GC_INTERVAL {
@@ -117,26 +271,89 @@ impl TransactionPipe {
 		Ok(())
 	}
 
-	async fn submit_transaction(
-		&mut self,
-		transaction: SignedTransaction,
-	) -> Result<SubmissionStatus, Error> {
+	/// Answers a submission, or leaves it waiting for a slot in flight as the overflow policy
+	/// has it.
+	///
+	/// Nothing is added to the mempool before the submission has a slot, so that no
+	/// transaction is ever dropped after it was accepted.
+	fn submit(&mut self, submission: Submission) -> Result<(), Error> {
+		if let Some(status) = submission.span.in_scope(|| self.refusal(&submission.transaction)) {
+			submission.answer(status);
+			return Ok(());
+		}
+
 		// For now, we are going to consider a transaction in flight until it exits the mempool and is sent to the DA as is indicated by WriteBatch.
-		let in_flight = self.transactions_in_flight.load(std::sync::atomic::Ordering::Relaxed);
-		info!(
-			target: "movement_timing",
-			in_flight = %in_flight,
-			"transactions_in_flight"
-		);
-		if in_flight > self.in_flight_limit {
+		submission.span.in_scope(|| {
 			info!(
 				target: "movement_timing",
-				"shedding_load"
+				in_flight = %self.transactions_in_flight.in_flight(),
+				"transactions_in_flight"
 			);
-			let status = MempoolStatus::new(MempoolStatusCode::MempoolIsFull);
-			return Ok((status, None));
+		});
+		// Submissions already waiting take freed slots first.
+		if self.waiting.is_empty() {
+			if let Some(permit) = self.transactions_in_flight.try_reserve() {
+				return self.admit(submission, permit);
+			}
+		}
+		match self.overflow.policy {
+			OverflowPolicy::Reject => submission.shed(),
+			OverflowPolicy::Block => self.waiting.push_back(submission),
+			OverflowPolicy::DropOldest => {
+				self.waiting.push_back(submission);
+				// As many submissions may wait as may be in flight.
+				if self.waiting.len() as u64 > self.transactions_in_flight.max_in_flight() {
+					let oldest = self.waiting.pop_front().expect("a submission is waiting");
+					warn!(
+						tx_hash = %oldest.transaction.committed_hash(),
+						"refusing the oldest submission waiting for a slot to make room"
+					);
+					oldest.shed();
+				}
+			}
 		}
+		Ok(())
+	}
 
+	/// The status `transaction` is refused with before it takes a slot, as exceeding a limit
+	/// or expiring needs no validation to tell.
+	fn refusal(&self, transaction: &SignedTransaction) -> Option<SubmissionStatus> {
+		if let Some(vm_status) = self.transaction_limits.check(transaction) {
+			info!(?vm_status, "refusing transaction over the submission limits");
+			return Some((MempoolStatus::new(MempoolStatusCode::VmError), Some(vm_status)));
+		}
+		let now = self.clock.now();
+		if is_expired(transaction, now) {
+			info!("refusing expired transaction");
+			let status = MempoolStatus::new(MempoolStatusCode::VmError);
+			return Some((status, Some(DiscardedVMStatus::TRANSACTION_EXPIRED)));
+		}
+		if expires_too_far_ahead(transaction, now, self.transaction_limits.max_future) {
+			info!("refusing transaction expiring too far in the future");
+			// The VM has no status for an expiration too far ahead; it is reported with the
+			// one for an expiration out of bounds.
+			let status = MempoolStatus::new(MempoolStatusCode::VmError);
+			return Some((status, Some(DiscardedVMStatus::TRANSACTION_EXPIRED)));
+		}
+		None
+	}
+
+	/// Validates a submission given a slot and answers it.
+	fn admit(&mut self, submission: Submission, permit: InFlightPermit) -> Result<(), Error> {
+		let Submission { transaction, callback, span, .. } = submission;
+		let status = span.in_scope(|| self.submit_transaction(transaction, permit))?;
+		callback.send(Ok(status)).unwrap_or_else(|_| {
+			debug!("SubmitTransaction request canceled");
+		});
+		Ok(())
+	}
+
+	fn submit_transaction(
+		&mut self,
+		transaction: SignedTransaction,
+		// The slot is freed again unless the transaction is passed on.
+		permit: InFlightPermit,
+	) -> Result<SubmissionStatus, Error> {
 		// Pre-execute Tx to validate its content.
 		// Re-create the validator for each Tx because it uses a frozen version of the ledger.
 		let vm_validator = VMValidator::new(Arc::clone(&self.db_reader));
@@ -173,14 +390,9 @@ impl TransactionPipe {
 		match status.code {
 			MempoolStatusCode::Accepted => {
 				debug!("Transaction accepted: {:?}", transaction);
-				let sender = transaction.sender();
-				self.transaction_sender
-					.send(transaction)
-					.await
-					.map_err(|e| anyhow::anyhow!("Error sending transaction: {:?}", e))?;
-				// increment transactions in flight
-				self.transactions_in_flight.fetch_add(1, std::sync::atomic::Ordering::Relaxed);
-				self.core_mempool.commit_transaction(&sender, sequence_number);
+				self.core_mempool.commit_transaction(&transaction.sender(), sequence_number);
+				self.pending.push(transaction, permit);
+				self.pass_on_pending()?;
 			}
 			_ => {
 				warn!("Transaction not accepted: {:?}", status);
@@ -190,6 +402,26 @@ impl TransactionPipe {
 		// report status
 		Ok((status, None))
 	}
+
+	/// Passes on pending transactions, in the mempool ordering, for as long as the channel
+	/// has room.
+	fn pass_on_pending(&mut self) -> Result<(), Error> {
+		while !self.pending.is_empty() {
+			let room = match self.transaction_sender.try_reserve() {
+				Ok(room) => room,
+				Err(TrySendError::Full(())) => break,
+				Err(TrySendError::Closed(())) => {
+					return Err(anyhow::anyhow!("Error sending transaction: channel closed").into())
+				}
+			};
+			if let Some((transaction, permit)) = self.pending.pop_next() {
+				room.send(transaction);
+				// the executor releases the slot once the transaction is executed
+				permit.hold();
+			}
+		}
+		Ok(())
+	}
 }
 
 #[cfg(test)]
@@ -203,6 +435,7 @@ mod tests {
 	use aptos_crypto::HashValue;
 	use aptos_mempool::MempoolClientSender;
 	use aptos_types::{
+		account_address::AccountAddress,
 		account_config,
 		block_executor::partitioner::{ExecutableBlock, ExecutableTransactions},
 		block_metadata::BlockMetadata,
@@ -215,7 +448,7 @@ mod tests {
 	use aptos_vm_genesis::GENESIS_KEYPAIR;
 	use futures::channel::oneshot;
 	use futures::SinkExt;
-	use maptos_execution_util::config::chain::Config;
+	use maptos_execution_util::config::{chain::Config, clock::ManualClock};
 
 	fn setup() -> (TransactionPipe, MempoolClientSender, mpsc::Receiver<SignedTransaction>) {
 		let (tx_sender, tx_receiver) = mpsc::channel(16);
@@ -236,6 +469,55 @@ mod tests {
 		)
 	}
 
+	#[tokio::test]
+	async fn test_transactions_expire_as_the_clock_advances() -> Result<(), anyhow::Error> {
+		let (mut transaction_pipe, mut mempool_client_sender, _tx_receiver) = setup();
+		let user_transaction = create_signed_transaction(1, &Config::default());
+		let expiration =
+			UNIX_EPOCH + Duration::from_secs(user_transaction.expiration_timestamp_secs());
+		let clock = Arc::new(ManualClock::new(expiration - Duration::from_secs(1)));
+		transaction_pipe.clock = clock.clone();
+		assert!(!is_expired(&user_transaction, clock.now()));
+
+		clock.advance(Duration::from_secs(1));
+		assert!(is_expired(&user_transaction, clock.now()));
+
+		let (req_sender, callback) = oneshot::channel();
+		mempool_client_sender
+			.send(MempoolClientRequest::SubmitTransaction(user_transaction, req_sender))
+			.await?;
+		transaction_pipe.tick().await?;
+		let (status, vm_status) = callback.await??;
+		assert_eq!(status.code, MempoolStatusCode::VmError);
+		assert_eq!(vm_status, Some(DiscardedVMStatus::TRANSACTION_EXPIRED));
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_expiration_must_fall_within_the_future_window() -> Result<(), anyhow::Error> {
+		let (mut transaction_pipe, mut mempool_client_sender, _tx_receiver) = setup();
+		let user_transaction = create_signed_transaction(1, &Config::default());
+		let max_future = transaction_pipe.transaction_limits.max_future;
+		let expiration =
+			UNIX_EPOCH + Duration::from_secs(user_transaction.expiration_timestamp_secs());
+
+		// A second before the window reaches the expiration, the transaction is refused.
+		let clock = Arc::new(ManualClock::new(expiration - max_future - Duration::from_secs(1)));
+		transaction_pipe.clock = clock.clone();
+		let (status, vm_status) =
+			submit(&mut transaction_pipe, &mut mempool_client_sender, user_transaction.clone())
+				.await?;
+		assert_eq!(status.code, MempoolStatusCode::VmError);
+		assert_eq!(vm_status, Some(DiscardedVMStatus::TRANSACTION_EXPIRED));
+
+		// Expiring exactly at the end of the window is accepted.
+		clock.advance(Duration::from_secs(1));
+		let (status, _vm_status) =
+			submit(&mut transaction_pipe, &mut mempool_client_sender, user_transaction).await?;
+		assert_eq!(status.code, MempoolStatusCode::Accepted);
+		Ok(())
+	}
+
 	#[tokio::test]
 	async fn test_pipe_mempool() -> Result<(), anyhow::Error> {
 		// set up
@@ -263,6 +545,198 @@ mod tests {
 		Ok(())
 	}
 
+	#[test]
+	fn test_transaction_limits_are_inclusive() {
+		let transaction = create_signed_transaction(1, &Config::default());
+		let limits = TransactionLimits {
+			max_gas_amount: transaction.max_gas_amount(),
+			max_transaction_bytes: transaction.raw_txn_bytes_len(),
+			max_future: Duration::from_secs(600),
+		};
+		assert_eq!(limits.check(&transaction), None);
+
+		let gas_exceeded =
+			TransactionLimits { max_gas_amount: transaction.max_gas_amount() - 1, ..limits };
+		assert_eq!(
+			gas_exceeded.check(&transaction),
+			Some(DiscardedVMStatus::MAX_GAS_UNITS_EXCEEDS_MAX_GAS_UNITS_BOUND)
+		);
+
+		let size_exceeded = TransactionLimits {
+			max_transaction_bytes: transaction.raw_txn_bytes_len() - 1,
+			..limits
+		};
+		assert_eq!(
+			size_exceeded.check(&transaction),
+			Some(DiscardedVMStatus::EXCEEDED_MAX_TRANSACTION_SIZE)
+		);
+	}
+
+	#[tokio::test]
+	async fn test_transactions_over_the_limits_are_refused() -> Result<(), anyhow::Error> {
+		let (mut transaction_pipe, mut mempool_client_sender, mut tx_receiver) = setup();
+		let user_transaction = create_signed_transaction(1, &Config::default());
+		transaction_pipe.transaction_limits.max_gas_amount = user_transaction.max_gas_amount() - 1;
+
+		let (req_sender, callback) = oneshot::channel();
+		mempool_client_sender
+			.send(MempoolClientRequest::SubmitTransaction(user_transaction, req_sender))
+			.await?;
+		transaction_pipe.tick().await?;
+
+		let (status, vm_status) = callback.await??;
+		assert_eq!(status.code, MempoolStatusCode::VmError);
+		assert_eq!(vm_status, Some(DiscardedVMStatus::MAX_GAS_UNITS_EXCEEDS_MAX_GAS_UNITS_BOUND));
+		assert!(tx_receiver.try_recv().is_err());
+		assert_eq!(transaction_pipe.transactions_in_flight.in_flight(), 0);
+		Ok(())
+	}
+
+	async fn submit(
+		transaction_pipe: &mut TransactionPipe,
+		mempool_client_sender: &mut MempoolClientSender,
+		transaction: SignedTransaction,
+	) -> Result<SubmissionStatus, anyhow::Error> {
+		let (req_sender, callback) = oneshot::channel();
+		mempool_client_sender
+			.send(MempoolClientRequest::SubmitTransaction(transaction, req_sender))
+			.await?;
+		answered(transaction_pipe, callback).await
+	}
+
+	/// Ticks the pipe until the submission behind `callback` is answered.
+	async fn answered(
+		transaction_pipe: &mut TransactionPipe,
+		mut callback: oneshot::Receiver<anyhow::Result<SubmissionStatus>>,
+	) -> Result<SubmissionStatus, anyhow::Error> {
+		loop {
+			transaction_pipe.tick().await?;
+			if let Some(status) = callback.try_recv()? {
+				return status;
+			}
+		}
+	}
+
+	#[tokio::test]
+	async fn test_full_queue_rejects_submissions() -> Result<(), anyhow::Error> {
+		let (mut transaction_pipe, mut mempool_client_sender, mut tx_receiver) = setup();
+		transaction_pipe.transactions_in_flight = InFlightTracker::new(0);
+		transaction_pipe.overflow.policy = OverflowPolicy::Reject;
+
+		let user_transaction = create_signed_transaction(1, &Config::default());
+		let (status, vm_status) =
+			submit(&mut transaction_pipe, &mut mempool_client_sender, user_transaction).await?;
+		assert_eq!(status.code, MempoolStatusCode::MempoolIsFull);
+		assert_eq!(vm_status, None);
+		assert!(tx_receiver.try_recv().is_err());
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_full_queue_blocks_until_a_slot_is_freed() -> Result<(), anyhow::Error> {
+		let (mut transaction_pipe, mut mempool_client_sender, mut tx_receiver) = setup();
+		let tracker = InFlightTracker::new(1);
+		tracker.try_reserve().expect("a slot is free").hold();
+		transaction_pipe.transactions_in_flight = tracker.clone();
+		transaction_pipe.overflow = OverflowHandling {
+			policy: OverflowPolicy::Block,
+			block_timeout: Duration::from_millis(20),
+		};
+
+		// No slot is freed within the timeout.
+		let user_transaction = create_signed_transaction(1, &Config::default());
+		let (status, _) =
+			submit(&mut transaction_pipe, &mut mempool_client_sender, user_transaction.clone())
+				.await?;
+		assert_eq!(status.code, MempoolStatusCode::MempoolIsFull);
+
+		// The executor frees the slot while the submission waits.
+		transaction_pipe.overflow.block_timeout = Duration::from_secs(10);
+		let executor = tracker.clone();
+		let release = tokio::spawn(async move {
+			tokio::time::sleep(Duration::from_millis(20)).await;
+			executor.release(1);
+		});
+		let (status, _) =
+			submit(&mut transaction_pipe, &mut mempool_client_sender, user_transaction.clone())
+				.await?;
+		release.await?;
+		assert_eq!(status.code, MempoolStatusCode::Accepted);
+		assert_eq!(tx_receiver.recv().await, Some(user_transaction));
+		assert_eq!(tracker.in_flight(), 1);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_lookups_are_served_while_a_submission_waits() -> Result<(), anyhow::Error> {
+		let (mut transaction_pipe, mut mempool_client_sender, _tx_receiver) = setup();
+		let tracker = InFlightTracker::new(1);
+		tracker.try_reserve().expect("a slot is free").hold();
+		transaction_pipe.transactions_in_flight = tracker.clone();
+		transaction_pipe.overflow = OverflowHandling {
+			policy: OverflowPolicy::Block,
+			block_timeout: Duration::from_secs(10),
+		};
+
+		let user_transaction = create_signed_transaction(1, &Config::default());
+		let (req_sender, mut submitted) = oneshot::channel();
+		mempool_client_sender
+			.send(MempoolClientRequest::SubmitTransaction(user_transaction.clone(), req_sender))
+			.await?;
+		transaction_pipe.tick().await?;
+
+		let (req_sender, looked_up) = oneshot::channel();
+		mempool_client_sender
+			.send(MempoolClientRequest::GetTransactionByHash(
+				user_transaction.committed_hash(),
+				req_sender,
+			))
+			.await?;
+		transaction_pipe.tick().await?;
+		// Nothing was added to the mempool while the submission waits.
+		assert_eq!(looked_up.await?, None);
+		assert!(submitted.try_recv()?.is_none());
+
+		tracker.release(1);
+		let (status, _) = answered(&mut transaction_pipe, submitted).await?;
+		assert_eq!(status.code, MempoolStatusCode::Accepted);
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_drop_oldest_refuses_the_oldest_waiting_submission() -> Result<(), anyhow::Error> {
+		let (mut transaction_pipe, mut mempool_client_sender, mut tx_receiver) = setup();
+		let tracker = InFlightTracker::new(1);
+		tracker.try_reserve().expect("a slot is free").hold();
+		transaction_pipe.transactions_in_flight = tracker.clone();
+		transaction_pipe.overflow = OverflowHandling {
+			policy: OverflowPolicy::DropOldest,
+			block_timeout: Duration::from_secs(10),
+		};
+
+		let mut callbacks = Vec::new();
+		for sequence_number in [1, 2] {
+			let user_transaction = create_signed_transaction(sequence_number, &Config::default());
+			let (req_sender, callback) = oneshot::channel();
+			mempool_client_sender
+				.send(MempoolClientRequest::SubmitTransaction(user_transaction, req_sender))
+				.await?;
+			transaction_pipe.tick().await?;
+			callbacks.push(callback);
+		}
+		let mut newest = callbacks.pop().unwrap();
+		let (status, _) = callbacks.pop().unwrap().await??;
+		assert_eq!(status.code, MempoolStatusCode::MempoolIsFull);
+		assert!(newest.try_recv()?.is_none());
+
+		tracker.release(1);
+		let (status, _) = answered(&mut transaction_pipe, newest).await?;
+		assert_eq!(status.code, MempoolStatusCode::Accepted);
+		let received_transaction = tx_receiver.recv().await.unwrap();
+		assert_eq!(received_transaction.sequence_number(), 2);
+		Ok(())
+	}
+
 	#[tokio::test]
 	async fn test_pipe_mempool_cancellation() -> Result<(), anyhow::Error> {
 		// set up
@@ -420,7 +894,7 @@ mod tests {
 			executor.signer.author(),
 			vec![],
 			vec![],
-			chrono::Utc::now().timestamp_micros() as u64,
+			executor.config().chain.clock().now_micros(),
 		));
 		let txs = ExecutableTransactions::Unsharded(
 			[block_metadata, Transaction::UserTransaction(tx)]
@@ -0,0 +1,65 @@
+//! The version of the running node, served at `/version` unless `MAPTOS_EXPOSE_VERSION=false`.
+
+use maptos_execution_util::config::Config;
+
+use poem::{
+	handler,
+	web::{Data, Json},
+};
+use serde::Serialize;
+
+use std::sync::Arc;
+
+/// The env var naming the commit the binary was built from, read when the binary is built
+/// and otherwise when the node starts.
+pub const GIT_COMMIT_VAR: &str = "MAPTOS_GIT_COMMIT";
+
+/// What `/version` reports.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct VersionInfo {
+	/// The version of the node's crates
+	pub version: &'static str,
+	/// The commit the binary was built from, `unknown` when it was not given
+	pub git_commit: String,
+	pub chain_id: u8,
+	/// The servers the node runs
+	pub surfaces: Vec<&'static str>,
+}
+
+impl VersionInfo {
+	pub fn new(maptos_config: &Config) -> Self {
+		let git_commit = option_env!("MAPTOS_GIT_COMMIT")
+			.map(str::to_string)
+			.or_else(|| std::env::var(GIT_COMMIT_VAR).ok())
+			.filter(|commit| !commit.trim().is_empty())
+			.unwrap_or_else(|| "unknown".to_string());
+		VersionInfo {
+			version: env!("CARGO_PKG_VERSION"),
+			git_commit,
+			chain_id: maptos_config.chain.maptos_chain_id.id(),
+			surfaces: maptos_config.enabled_surfaces(),
+		}
+	}
+}
+
+#[handler]
+pub(crate) fn version(Data(info): Data<&Arc<VersionInfo>>) -> Json<VersionInfo> {
+	Json((**info).clone())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_version_reports_the_chain_and_surfaces() {
+		let mut config = Config::default();
+		config.chain.maptos_rest_enabled = true;
+		config.metrics.maptos_metrics_enabled = false;
+		let info = VersionInfo::new(&config);
+		assert_eq!(info.version, env!("CARGO_PKG_VERSION"));
+		assert_eq!(info.chain_id, config.chain.maptos_chain_id.id());
+		assert!(info.surfaces.contains(&"rest"));
+		assert!(!info.surfaces.contains(&"metrics"));
+	}
+}
@@ -0,0 +1,73 @@
+//! Preloading recent state into the storage caches before the API is served,
+//! so that the first requests to a cold node are not answered slowly.
+
+use aptos_storage_interface::{state_view::LatestDbStateCheckpointView as _, DbReader};
+use aptos_types::state_store::TStateView;
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+
+/// The most transaction outputs read from storage at once while preloading.
+const PRELOAD_BATCH: u64 = 100;
+
+/// Whether the node has finished warming its caches, which readiness waits for.
+/// Clones share the state.
+#[derive(Debug, Clone)]
+pub struct Warmup(Arc<AtomicBool>);
+
+impl Warmup {
+	/// A warmup which is still to run when `pending`, and otherwise already done.
+	pub fn new(pending: bool) -> Self {
+		Self(Arc::new(AtomicBool::new(!pending)))
+	}
+
+	pub fn is_done(&self) -> bool {
+		self.0.load(Ordering::Acquire)
+	}
+
+	pub(crate) fn finish(&self) {
+		self.0.store(true, Ordering::Release);
+	}
+}
+
+/// Reads the state written by the latest `versions` committed transactions, so that the
+/// storage caches hold it, and returns how many state values were read.
+pub fn preload_recent_state(
+	db_reader: &Arc<dyn DbReader>,
+	versions: u64,
+) -> Result<u64, anyhow::Error> {
+	let Some(ledger_info) = db_reader.get_latest_ledger_info_option()? else {
+		return Ok(0);
+	};
+	let ledger_version = ledger_info.ledger_info().version();
+	let state_view = db_reader.latest_state_checkpoint_view()?;
+	let mut read = 0;
+	let mut version = (ledger_version + 1).saturating_sub(versions);
+	while version <= ledger_version {
+		let limit = (ledger_version - version + 1).min(PRELOAD_BATCH);
+		let outputs = db_reader.get_transaction_outputs(version, limit, ledger_version)?;
+		for (_, output) in &outputs.transactions_and_outputs {
+			for (state_key, _) in output.write_set().iter() {
+				state_view.get_state_value(state_key)?;
+				read += 1;
+			}
+		}
+		version += limit;
+	}
+	Ok(read)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::Executor;
+	use aptos_vm_genesis::GENESIS_KEYPAIR;
+
+	#[tokio::test]
+	async fn test_preload_reads_the_state_of_recent_versions() -> Result<(), anyhow::Error> {
+		let (executor, _config, _tempdir) = Executor::try_test_default(GENESIS_KEYPAIR.0.clone())?;
+		let db_reader = executor.db_reader();
+		assert!(preload_recent_state(&db_reader, 1)? > 0);
+		Ok(())
+	}
+}
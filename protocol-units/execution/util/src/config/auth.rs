@@ -0,0 +1,65 @@
+//! Checking the auth tokens presented to the indexer.
+//!
+//! The scoped tokens presented to the REST API are checked in [`super::rest_scopes`].
+
+use super::Config;
+
+/// Compares two byte strings in time that depends only on their lengths.
+pub(crate) fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+	if a.len() != b.len() {
+		return false;
+	}
+	a.iter().zip(b).fold(0u8, |diff, (x, y)| diff | (x ^ y)) == 0
+}
+
+impl Config {
+	/// Every accepted indexer auth token, in the order they are configured.
+	///
+	/// The first token is the one presented by the indexer processors.
+	pub fn indexer_auth_tokens(&self) -> Vec<String> {
+		self.indexer_processor
+			.indexer_processor_auth_token
+			.split(',')
+			.map(str::trim)
+			.filter(|token| !token.is_empty())
+			.map(str::to_string)
+			.collect()
+	}
+
+	/// Whether `presented` is one of the accepted indexer auth tokens.
+	///
+	/// Every configured token is compared, so the timing does not reveal which one matched.
+	pub fn is_valid_auth_token(&self, presented: &str) -> bool {
+		self.indexer_auth_tokens().iter().fold(false, |valid, token| {
+			constant_time_eq(token.as_bytes(), presented.as_bytes()) | valid
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_single_token_is_unchanged() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.indexer_processor.indexer_processor_auth_token = "auth_token".to_string();
+
+		assert_eq!(config.indexer_auth_tokens(), vec!["auth_token".to_string()]);
+		assert!(config.is_valid_auth_token("auth_token"));
+		assert!(!config.is_valid_auth_token("auth_toke"));
+	}
+
+	#[test]
+	fn test_rotated_tokens_are_all_valid() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.indexer_processor.indexer_processor_auth_token = "new-token, old-token".to_string();
+
+		assert!(config.is_valid_auth_token("new-token"));
+		assert!(config.is_valid_auth_token("old-token"));
+		assert!(!config.is_valid_auth_token("new-token, old-token"));
+		assert!(!config.is_valid_auth_token(""));
+	}
+}
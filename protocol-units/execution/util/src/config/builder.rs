@@ -0,0 +1,1594 @@
+//! Programmatic construction of the Maptos config.
+//!
+//! Unlike [`Config::default`], which reads every field from the process environment,
+//! the builder only consults the environment for fields that were left unset.
+//! This allows several differently configured nodes to live in the same process.
+
+use super::chain::{AddressFormat, RestEncoding, SyncMode};
+use super::client::IndexerUpstream;
+use super::clock::ClockKind;
+use super::commit_batch::FsyncPolicy;
+use super::duration::DurationSecs;
+use super::error::ConfigError;
+use super::features::FeatureFlags;
+use super::health::HealthMode;
+use super::indexer_processor::ProcessorSet;
+use super::load_shedding::{MempoolOrdering, OverflowPolicy};
+use super::rest_routes::RestRouteSet;
+use super::rest_scopes::RestScopedTokens;
+use super::signer::KeyScheme;
+use super::validation::ValidationError;
+use super::{
+	chain, client, common, faucet, fin, health, indexer, indexer_processor, load_shedding,
+	logging::{self, LogFormat, LogLevel, SampleRate},
+	metrics, ports, provenance, Config,
+};
+
+use aptos_crypto::{ed25519::Ed25519PrivateKey, Uniform};
+use aptos_types::{account_address::AccountAddress, chain_id::ChainId};
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Debug, Clone, Default)]
+pub struct ConfigBuilder {
+	chain_id: Option<ChainId>,
+	private_key: Option<Ed25519PrivateKey>,
+	key_scheme: Option<KeyScheme>,
+	feature_flags: Option<FeatureFlags>,
+	data_dir: Option<PathBuf>,
+	db_path: Option<PathBuf>,
+	db_block_cache_bytes: Option<u64>,
+	db_max_wal_bytes: Option<u64>,
+	max_db_size_bytes: Option<u64>,
+	commit_batch: Option<(u32, u64)>,
+	fsync_policy: Option<FsyncPolicy>,
+	execution_concurrency: Option<u32>,
+	genesis_blob_path: Option<PathBuf>,
+	framework_path: Option<PathBuf>,
+	shutdown_grace: Option<Duration>,
+	dns_refresh: Option<Duration>,
+	stop_at_version: Option<u64>,
+	read_only: Option<bool>,
+	allow_chain_id_mismatch: Option<bool>,
+	gas_unit_price: Option<u64>,
+	clock: Option<ClockKind>,
+	sync_mode: Option<SyncMode>,
+	rest_listen: Option<(String, u16)>,
+	rest_connection: Option<(String, u16)>,
+	rest_tls: Option<(String, String)>,
+	rest_cors_allowed_origins: Option<Vec<String>>,
+	rest_base_path: Option<String>,
+	rest_method_allowlist: Option<RestRouteSet>,
+	rest_scoped_tokens: Option<RestScopedTokens>,
+	rest_default_encoding: Option<RestEncoding>,
+	rest_address_format: Option<AddressFormat>,
+	rest_request_timeout: Option<Duration>,
+	rest_idle_timeout: Option<Duration>,
+	rest_max_body_bytes: Option<u64>,
+	rest_max_gas_amount: Option<u64>,
+	max_txn_future: Option<Duration>,
+	rest_max_page_size: Option<u32>,
+	rest_max_events: Option<u32>,
+	rest_max_transaction_bytes: Option<u64>,
+	rest_connection_limits: Option<(u32, u32)>,
+	rest_log_sample_rate: Option<SampleRate>,
+	rest_worker_threads: Option<u32>,
+	rest_enabled: Option<bool>,
+	expose_version: Option<bool>,
+	preload_enabled: Option<bool>,
+	preload_versions: Option<u64>,
+	faucet_listen: Option<(String, u16)>,
+	faucet_connection: Option<(String, u16)>,
+	faucet_cors_allowed_origins: Option<Vec<String>>,
+	faucet_base_path: Option<String>,
+	faucet_max_body_bytes: Option<u64>,
+	faucet_idle_timeout: Option<Duration>,
+	faucet_connection_limits: Option<(u32, u32)>,
+	faucet_rate_limit_per_ip_per_hour: Option<u32>,
+	faucet_max_amount_per_request: Option<u64>,
+	faucet_mint_amount: Option<u64>,
+	faucet_txn_expiration: Option<Duration>,
+	faucet_cooldown: Option<Duration>,
+	faucet_require_captcha: Option<bool>,
+	faucet_private_key: Option<Ed25519PrivateKey>,
+	faucet_private_keys: Option<Vec<Ed25519PrivateKey>>,
+	faucet_blocked_addresses: Option<Vec<AccountAddress>>,
+	faucet_allowlist_addresses: Option<Vec<AccountAddress>>,
+	faucet_enabled: Option<bool>,
+	fin_listen: Option<(String, u16)>,
+	fin_connection: Option<(String, u16)>,
+	fin_request_timeout: Option<Duration>,
+	fin_connection_limits: Option<(u32, u32)>,
+	fin_worker_threads: Option<u32>,
+	fin_enabled: Option<bool>,
+	fin_max_concurrent_views: Option<u32>,
+	indexer_grpc_listen: Option<(String, u16)>,
+	indexer_grpc_connection: Option<(String, u16)>,
+	indexer_upstreams: Option<Vec<IndexerUpstream>>,
+	indexer_grpc_inactivity_timeout: Option<Duration>,
+	indexer_grpc_ping_interval: Option<Duration>,
+	indexer_grpc_max_message_bytes: Option<u64>,
+	indexer_grpc_tcp_keepalive: Option<Duration>,
+	indexer_grpc_http2_keepalive: Option<Duration>,
+	indexer_grpc_tls: Option<(String, String)>,
+	indexer_grpc_client_ca: Option<String>,
+	indexer_grpc_loopback_port: Option<u16>,
+	indexer_worker_threads: Option<u32>,
+	indexer_grpc_enabled: Option<bool>,
+	indexer_max_lag_versions: Option<u64>,
+	indexer_grpc_batch_size: Option<u32>,
+	indexer_grpc_send_buffer_size: Option<u32>,
+	indexer_grpc_compression: Option<indexer::GrpcCompression>,
+	pruning_enabled: Option<bool>,
+	ledger_prune_window: Option<u64>,
+	state_merkle_prune_window: Option<u64>,
+	epoch_snapshot_prune_window: Option<u64>,
+	postgres_connection_string: Option<String>,
+	postgres_max_connections: Option<u32>,
+	postgres_connection_timeout: Option<Duration>,
+	postgres_connect_retries: Option<(u32, Duration)>,
+	indexer_processor_auth_token: Option<String>,
+	indexer_processor_versions: Option<(u64, Option<u64>)>,
+	enabled_processors: Option<ProcessorSet>,
+	max_transactions_in_flight: Option<u64>,
+	inflight_overflow_policy: Option<OverflowPolicy>,
+	mempool_ordering: Option<MempoolOrdering>,
+	health_enabled: Option<bool>,
+	health_listen: Option<(String, u16)>,
+	health_mode: Option<HealthMode>,
+	max_block_age: Option<Duration>,
+	metrics_enabled: Option<bool>,
+	metrics_listen: Option<(String, u16)>,
+	log_format: Option<LogFormat>,
+	log_level: Option<LogLevel>,
+	otlp: Option<(String, SampleRate)>,
+}
+
+impl ConfigBuilder {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn chain_id(mut self, chain_id: ChainId) -> Self {
+		self.chain_id = Some(chain_id);
+		self
+	}
+
+	pub fn private_key(mut self, private_key: Ed25519PrivateKey) -> Self {
+		self.private_key = Some(private_key);
+		self
+	}
+
+	/// Sets the signature scheme the private key is read under.
+	pub fn key_scheme(mut self, scheme: KeyScheme) -> Self {
+		self.key_scheme = Some(scheme);
+		self
+	}
+
+	/// Sets the experimental behaviors switched on or off.
+	pub fn feature_flags(mut self, flags: FeatureFlags) -> Self {
+		self.feature_flags = Some(flags);
+		self
+	}
+
+	pub fn data_dir(mut self, data_dir: impl Into<PathBuf>) -> Self {
+		self.data_dir = Some(data_dir.into());
+		self
+	}
+
+	/// The database path, rooted under the data directory unless absolute.
+	pub fn db_path(mut self, db_path: impl Into<PathBuf>) -> Self {
+		self.db_path = Some(db_path.into());
+		self
+	}
+
+	/// The RocksDB block cache of each Aptos database, in bytes.
+	pub fn db_block_cache_bytes(mut self, bytes: u64) -> Self {
+		self.db_block_cache_bytes = Some(bytes);
+		self
+	}
+
+	/// Flushes the memtables of each Aptos database once its write-ahead log reaches `bytes`.
+	pub fn db_max_wal_bytes(mut self, bytes: u64) -> Self {
+		self.db_max_wal_bytes = Some(bytes);
+		self
+	}
+
+	/// Forces pruning on as the Aptos databases near `bytes` of disk; 0 leaves it unlimited.
+	pub fn max_db_size_bytes(mut self, bytes: u64) -> Self {
+		self.max_db_size_bytes = Some(bytes);
+		self
+	}
+
+	/// Groups up to `size` transactions per commit, flushing a partial batch after `max_delay`.
+	pub fn commit_batch(mut self, size: u32, max_delay: Duration) -> Self {
+		let max_delay_ms = u64::try_from(max_delay.as_millis()).unwrap_or(u64::MAX);
+		self.commit_batch = Some((size, max_delay_ms));
+		self
+	}
+
+	/// Syncs the storage commits to disk as `policy` says.
+	pub fn fsync_policy(mut self, policy: FsyncPolicy) -> Self {
+		self.fsync_policy = Some(policy);
+		self
+	}
+
+	/// Executes each block on `threads` threads in parallel, 0 for one per CPU.
+	pub fn execution_concurrency(mut self, threads: u32) -> Self {
+		self.execution_concurrency = Some(threads);
+		self
+	}
+
+	/// Bootstraps an empty database from the genesis transaction stored at `path`.
+	pub fn genesis_blob_path(mut self, path: impl Into<PathBuf>) -> Self {
+		self.genesis_blob_path = Some(path.into());
+		self
+	}
+
+	/// Encodes the built-in genesis with the framework release bundle stored at `path`.
+	pub fn framework_path(mut self, path: impl Into<PathBuf>) -> Self {
+		self.framework_path = Some(path.into());
+		self
+	}
+
+	/// Bounds how long servers drain in-flight work when stopping.
+	pub fn shutdown_grace(mut self, grace: Duration) -> Self {
+		self.shutdown_grace = Some(grace);
+		self
+	}
+
+	/// Reuses resolved connection addresses for `refresh`; 0 resolves on every dial.
+	pub fn dns_refresh(mut self, refresh: Duration) -> Self {
+		self.dns_refresh = Some(refresh);
+		self
+	}
+
+	/// Stops the node gracefully once it has committed `version`.
+	pub fn stop_at_version(mut self, version: u64) -> Self {
+		self.stop_at_version = Some(version);
+		self
+	}
+
+	/// Refuses transaction submissions and keeps the faucet from starting.
+	pub fn read_only(mut self, read_only: bool) -> Self {
+		self.read_only = Some(read_only);
+		self
+	}
+
+	/// Uses upstream nodes reporting another chain id than the configured one anyway.
+	pub fn allow_chain_id_mismatch(mut self, allow: bool) -> Self {
+		self.allow_chain_id_mismatch = Some(allow);
+		self
+	}
+
+	/// Prices the transactions the node builds itself at `price`, 0 for the gas estimate.
+	pub fn gas_unit_price(mut self, price: u64) -> Self {
+		self.gas_unit_price = Some(price);
+		self
+	}
+
+	/// Runs the node on the `clock` given, a manual one for tests.
+	pub fn clock(mut self, clock: ClockKind) -> Self {
+		self.clock = Some(clock);
+		self
+	}
+
+	/// Sets how the node catches up with the chain when it starts.
+	pub fn sync_mode(mut self, mode: SyncMode) -> Self {
+		self.sync_mode = Some(mode);
+		self
+	}
+
+	pub fn rest_listen(mut self, hostname: impl Into<String>, port: u16) -> Self {
+		self.rest_listen = Some((hostname.into(), port));
+		self
+	}
+
+	/// Serves the REST API over TLS with the given PEM certificate and private key paths.
+	pub fn rest_tls(mut self, cert_path: impl Into<String>, key_path: impl Into<String>) -> Self {
+		self.rest_tls = Some((cert_path.into(), key_path.into()));
+		self
+	}
+
+	/// Restricts browser access to the REST API; empty sends no CORS headers, `*` allows any.
+	pub fn rest_cors_allowed_origins(
+		mut self,
+		origins: impl IntoIterator<Item = impl Into<String>>,
+	) -> Self {
+		self.rest_cors_allowed_origins = Some(origins.into_iter().map(Into::into).collect());
+		self
+	}
+
+	/// Serves the REST routes under `path`, such as `/node-a`; empty serves them at the root.
+	pub fn rest_base_path(mut self, path: impl Into<String>) -> Self {
+		self.rest_base_path = Some(path.into());
+		self
+	}
+
+	/// Serves only the REST route groups in `routes`, answering others with 404.
+	pub fn rest_method_allowlist(mut self, routes: RestRouteSet) -> Self {
+		self.rest_method_allowlist = Some(routes);
+		self
+	}
+
+	pub fn rest_scoped_tokens(mut self, tokens: RestScopedTokens) -> Self {
+		self.rest_scoped_tokens = Some(tokens);
+		self
+	}
+
+	pub fn rest_default_encoding(mut self, encoding: RestEncoding) -> Self {
+		self.rest_default_encoding = Some(encoding);
+		self
+	}
+
+	pub fn rest_address_format(mut self, format: AddressFormat) -> Self {
+		self.rest_address_format = Some(format);
+		self
+	}
+
+	/// Bounds how long a single REST request may run.
+	pub fn rest_request_timeout(mut self, timeout: Duration) -> Self {
+		self.rest_request_timeout = Some(timeout);
+		self
+	}
+
+	/// Closes REST connections idle for longer than `timeout`; zero keeps them open.
+	pub fn rest_idle_timeout(mut self, timeout: Duration) -> Self {
+		self.rest_idle_timeout = Some(timeout);
+		self
+	}
+
+	/// Rejects REST requests whose body exceeds `max_body_bytes`.
+	pub fn rest_max_body_bytes(mut self, max_body_bytes: u64) -> Self {
+		self.rest_max_body_bytes = Some(max_body_bytes);
+		self
+	}
+
+	/// Refuses submitted transactions which reserve more than `max_gas_amount` gas.
+	pub fn rest_max_gas_amount(mut self, max_gas_amount: u64) -> Self {
+		self.rest_max_gas_amount = Some(max_gas_amount);
+		self
+	}
+
+	/// Refuses submitted transactions which expire more than `max_future` from now.
+	pub fn max_txn_future(mut self, max_future: Duration) -> Self {
+		self.max_txn_future = Some(max_future);
+		self
+	}
+
+	/// Serves paginated REST requests at most `max_page_size` items at a time.
+	pub fn rest_max_page_size(mut self, max_page_size: u32) -> Self {
+		self.rest_max_page_size = Some(max_page_size);
+		self
+	}
+
+	/// Lists at most `max_events` events per transaction, or per events query, in REST
+	/// responses.
+	pub fn rest_max_events(mut self, max_events: u32) -> Self {
+		self.rest_max_events = Some(max_events);
+		self
+	}
+
+	/// Refuses submitted transactions larger than `max_transaction_bytes`.
+	pub fn rest_max_transaction_bytes(mut self, max_transaction_bytes: u64) -> Self {
+		self.rest_max_transaction_bytes = Some(max_transaction_bytes);
+		self
+	}
+
+	/// Caps concurrent REST connections and sets the listen backlog; 0 leaves either unlimited.
+	pub fn rest_connection_limits(mut self, max_connections: u32, listen_backlog: u32) -> Self {
+		self.rest_connection_limits = Some((max_connections, listen_backlog));
+		self
+	}
+
+	/// Sets the fraction of REST requests which are logged, from 0.0 for none to 1.0 for all.
+	pub fn rest_log_sample_rate(mut self, rate: SampleRate) -> Self {
+		self.rest_log_sample_rate = Some(rate);
+		self
+	}
+
+	/// Sets the REST runtime worker threads; 0 runs one per CPU.
+	pub fn rest_worker_threads(mut self, worker_threads: u32) -> Self {
+		self.rest_worker_threads = Some(worker_threads);
+		self
+	}
+
+	pub fn rest_enabled(mut self, enabled: bool) -> Self {
+		self.rest_enabled = Some(enabled);
+		self
+	}
+
+	/// Whether the REST API serves the node's version at `/version`.
+	pub fn expose_version(mut self, expose: bool) -> Self {
+		self.expose_version = Some(expose);
+		self
+	}
+
+	pub fn preload_enabled(mut self, enabled: bool) -> Self {
+		self.preload_enabled = Some(enabled);
+		self
+	}
+
+	pub fn preload_versions(mut self, versions: u64) -> Self {
+		self.preload_versions = Some(versions);
+		self
+	}
+
+	/// Sets the REST endpoint used by clients, including the faucet.
+	///
+	/// The port is the one clients connect to, which need not be the listen port when the
+	/// node is reached through NAT or a port mapping.
+	pub fn rest_connection(mut self, hostname: impl Into<String>, port: u16) -> Self {
+		self.rest_connection = Some((hostname.into(), port));
+		self
+	}
+
+	pub fn faucet_listen(mut self, hostname: impl Into<String>, port: u16) -> Self {
+		self.faucet_listen = Some((hostname.into(), port));
+		self
+	}
+
+	pub fn faucet_connection(mut self, hostname: impl Into<String>, port: u16) -> Self {
+		self.faucet_connection = Some((hostname.into(), port));
+		self
+	}
+
+	/// Restricts browser access to the faucet API; empty sends no CORS headers, `*` allows any.
+	pub fn faucet_cors_allowed_origins(
+		mut self,
+		origins: impl IntoIterator<Item = impl Into<String>>,
+	) -> Self {
+		self.faucet_cors_allowed_origins = Some(origins.into_iter().map(Into::into).collect());
+		self
+	}
+
+	/// Serves the faucet routes under `path`, such as `/faucet`; empty serves them at the root.
+	pub fn faucet_base_path(mut self, path: impl Into<String>) -> Self {
+		self.faucet_base_path = Some(path.into());
+		self
+	}
+
+	/// Rejects faucet requests whose body exceeds `max_body_bytes`.
+	pub fn faucet_max_body_bytes(mut self, max_body_bytes: u64) -> Self {
+		self.faucet_max_body_bytes = Some(max_body_bytes);
+		self
+	}
+
+	/// Closes faucet connections idle for longer than `timeout`; zero keeps them open.
+	pub fn faucet_idle_timeout(mut self, timeout: Duration) -> Self {
+		self.faucet_idle_timeout = Some(timeout);
+		self
+	}
+
+	/// Caps concurrent faucet connections and sets the listen backlog; 0 leaves either unlimited.
+	pub fn faucet_connection_limits(mut self, max_connections: u32, listen_backlog: u32) -> Self {
+		self.faucet_connection_limits = Some((max_connections, listen_backlog));
+		self
+	}
+
+	/// Limits the faucet requests accepted per client IP per hour; 0 disables rate limiting.
+	pub fn faucet_rate_limit_per_ip_per_hour(mut self, limit: u32) -> Self {
+		self.faucet_rate_limit_per_ip_per_hour = Some(limit);
+		self
+	}
+
+	/// Caps the amount a single faucet request may ask for; 0 removes the cap.
+	pub fn faucet_max_amount_per_request(mut self, max_amount: u64) -> Self {
+		self.faucet_max_amount_per_request = Some(max_amount);
+		self
+	}
+
+	/// Sets the amount funded when a faucet request names none, in octas.
+	pub fn faucet_mint_amount(mut self, amount: u64) -> Self {
+		self.faucet_mint_amount = Some(amount);
+		self
+	}
+
+	/// Sets how long a faucet transaction may wait for inclusion before it expires.
+	pub fn faucet_txn_expiration(mut self, expiration: Duration) -> Self {
+		self.faucet_txn_expiration = Some(expiration);
+		self
+	}
+
+	/// Sets how long each client IP and funded address waits between faucet requests.
+	pub fn faucet_cooldown(mut self, cooldown: Duration) -> Self {
+		self.faucet_cooldown = Some(cooldown);
+		self
+	}
+
+	/// Requires faucet requests to carry a captcha token which passes verification.
+	pub fn faucet_require_captcha(mut self, require_captcha: bool) -> Self {
+		self.faucet_require_captcha = Some(require_captcha);
+		self
+	}
+
+	/// Funds faucet requests from `private_key` instead of the node's key.
+	pub fn faucet_private_key(mut self, private_key: Ed25519PrivateKey) -> Self {
+		self.faucet_private_key = Some(private_key);
+		self
+	}
+
+	/// Rotates the faucet across `private_keys`, each funding with its own sequence number.
+	pub fn faucet_private_keys(mut self, private_keys: Vec<Ed25519PrivateKey>) -> Self {
+		self.faucet_private_keys = Some(private_keys);
+		self
+	}
+
+	/// Refuses faucet requests funding any of `addresses`.
+	pub fn faucet_blocked_addresses(mut self, addresses: Vec<AccountAddress>) -> Self {
+		self.faucet_blocked_addresses = Some(addresses);
+		self
+	}
+
+	/// Exempts faucet requests funding any of `addresses` from the rate limit.
+	pub fn faucet_allowlist_addresses(mut self, addresses: Vec<AccountAddress>) -> Self {
+		self.faucet_allowlist_addresses = Some(addresses);
+		self
+	}
+
+	pub fn faucet_enabled(mut self, enabled: bool) -> Self {
+		self.faucet_enabled = Some(enabled);
+		self
+	}
+
+	pub fn fin_listen(mut self, hostname: impl Into<String>, port: u16) -> Self {
+		self.fin_listen = Some((hostname.into(), port));
+		self
+	}
+
+	pub fn fin_connection(mut self, hostname: impl Into<String>, port: u16) -> Self {
+		self.fin_connection = Some((hostname.into(), port));
+		self
+	}
+
+	/// Bounds how long a single fin view request may run.
+	pub fn fin_request_timeout(mut self, timeout: Duration) -> Self {
+		self.fin_request_timeout = Some(timeout);
+		self
+	}
+
+	/// Caps concurrent fin view connections and sets the listen backlog;
+	/// 0 leaves either unlimited.
+	pub fn fin_connection_limits(mut self, max_connections: u32, listen_backlog: u32) -> Self {
+		self.fin_connection_limits = Some((max_connections, listen_backlog));
+		self
+	}
+
+	/// Sets the fin view runtime worker threads; 0 runs one per CPU.
+	pub fn fin_worker_threads(mut self, worker_threads: u32) -> Self {
+		self.fin_worker_threads = Some(worker_threads);
+		self
+	}
+
+	pub fn fin_enabled(mut self, enabled: bool) -> Self {
+		self.fin_enabled = Some(enabled);
+		self
+	}
+
+	/// Caps the view function calls the fin view server executes at once; 0 leaves them
+	/// unlimited.
+	pub fn fin_max_concurrent_views(mut self, max: u32) -> Self {
+		self.fin_max_concurrent_views = Some(max);
+		self
+	}
+
+	pub fn indexer_grpc_listen(mut self, hostname: impl Into<String>, port: u16) -> Self {
+		self.indexer_grpc_listen = Some((hostname.into(), port));
+		self
+	}
+
+	pub fn indexer_grpc_connection(mut self, hostname: impl Into<String>, port: u16) -> Self {
+		self.indexer_grpc_connection = Some((hostname.into(), port));
+		self
+	}
+
+	pub fn indexer_upstreams(mut self, upstreams: Vec<IndexerUpstream>) -> Self {
+		self.indexer_upstreams = Some(upstreams);
+		self
+	}
+
+	pub fn indexer_grpc_inactivity_timeout(mut self, timeout: Duration) -> Self {
+		self.indexer_grpc_inactivity_timeout = Some(timeout);
+		self
+	}
+
+	pub fn indexer_grpc_ping_interval(mut self, interval: Duration) -> Self {
+		self.indexer_grpc_ping_interval = Some(interval);
+		self
+	}
+
+	pub fn indexer_grpc_max_message_bytes(mut self, max_message_bytes: u64) -> Self {
+		self.indexer_grpc_max_message_bytes = Some(max_message_bytes);
+		self
+	}
+
+	/// Sets the TCP keepalive interval of indexer gRPC clients; zero disables it.
+	pub fn indexer_grpc_tcp_keepalive(mut self, interval: Duration) -> Self {
+		self.indexer_grpc_tcp_keepalive = Some(interval);
+		self
+	}
+
+	/// Sets the HTTP/2 keepalive ping interval of indexer gRPC clients; zero disables it.
+	pub fn indexer_grpc_http2_keepalive(mut self, interval: Duration) -> Self {
+		self.indexer_grpc_http2_keepalive = Some(interval);
+		self
+	}
+
+	/// Serves the indexer gRPC over TLS; empty paths serve plaintext.
+	pub fn indexer_grpc_tls(
+		mut self,
+		cert_path: impl Into<String>,
+		key_path: impl Into<String>,
+	) -> Self {
+		self.indexer_grpc_tls = Some((cert_path.into(), key_path.into()));
+		self
+	}
+
+	/// Requires indexer gRPC clients to present a certificate signed by this CA.
+	pub fn indexer_grpc_client_ca(mut self, ca_path: impl Into<String>) -> Self {
+		self.indexer_grpc_client_ca = Some(ca_path.into());
+		self
+	}
+
+	/// Also serves the indexer gRPC in plaintext on this port of 127.0.0.1 when it is served
+	/// over TLS, for an indexer on the same host; 0 serves it only over TLS.
+	pub fn indexer_grpc_loopback_port(mut self, port: u16) -> Self {
+		self.indexer_grpc_loopback_port = Some(port);
+		self
+	}
+
+	/// Sets the worker threads of the indexer gRPC TLS runtime; 0 runs one per CPU.
+	pub fn indexer_worker_threads(mut self, worker_threads: u32) -> Self {
+		self.indexer_worker_threads = Some(worker_threads);
+		self
+	}
+
+	pub fn indexer_grpc_enabled(mut self, enabled: bool) -> Self {
+		self.indexer_grpc_enabled = Some(enabled);
+		self
+	}
+
+	/// Sets how many versions the indexer may lag before the node reports not ready; 0 never does.
+	pub fn indexer_max_lag_versions(mut self, max_lag_versions: u64) -> Self {
+		self.indexer_max_lag_versions = Some(max_lag_versions);
+		self
+	}
+
+	/// Sets the most transactions sent in one message of the indexer gRPC stream.
+	pub fn indexer_grpc_batch_size(mut self, batch_size: u32) -> Self {
+		self.indexer_grpc_batch_size = Some(batch_size);
+		self
+	}
+
+	/// Sets how many indexer gRPC stream messages are buffered for a client.
+	pub fn indexer_grpc_send_buffer_size(mut self, size: u32) -> Self {
+		self.indexer_grpc_send_buffer_size = Some(size);
+		self
+	}
+
+	/// Sets the compression of the indexer gRPC stream.
+	pub fn indexer_grpc_compression(mut self, compression: indexer::GrpcCompression) -> Self {
+		self.indexer_grpc_compression = Some(compression);
+		self
+	}
+
+	/// Disables pruning for an archival node, which then ignores the prune windows.
+	pub fn pruning_enabled(mut self, enabled: bool) -> Self {
+		self.pruning_enabled = Some(enabled);
+		self
+	}
+
+	pub fn ledger_prune_window(mut self, window: u64) -> Self {
+		self.ledger_prune_window = Some(window);
+		self
+	}
+
+	pub fn state_merkle_prune_window(mut self, window: u64) -> Self {
+		self.state_merkle_prune_window = Some(window);
+		self
+	}
+
+	pub fn epoch_snapshot_prune_window(mut self, window: u64) -> Self {
+		self.epoch_snapshot_prune_window = Some(window);
+		self
+	}
+
+	pub fn postgres_connection_string(mut self, connection_string: impl Into<String>) -> Self {
+		self.postgres_connection_string = Some(connection_string.into());
+		self
+	}
+
+	pub fn postgres_max_connections(mut self, max: u32) -> Self {
+		self.postgres_max_connections = Some(max);
+		self
+	}
+
+	pub fn postgres_connection_timeout(mut self, timeout: Duration) -> Self {
+		self.postgres_connection_timeout = Some(timeout);
+		self
+	}
+
+	/// Retries the initial Postgres connection `retries` times, starting at `delay` and doubling.
+	pub fn postgres_connect_retries(mut self, retries: u32, delay: Duration) -> Self {
+		self.postgres_connect_retries = Some((retries, delay));
+		self
+	}
+
+	pub fn indexer_processor_auth_token(mut self, auth_token: impl Into<String>) -> Self {
+		self.indexer_processor_auth_token = Some(auth_token.into());
+		self
+	}
+
+	/// Bounds the versions the indexer processors index; a starting version of 0
+	/// resumes where the database left off, and no ending version keeps indexing.
+	pub fn indexer_processor_versions(mut self, starting: u64, ending: Option<u64>) -> Self {
+		self.indexer_processor_versions = Some((starting, ending));
+		self
+	}
+
+	pub fn enabled_processors(mut self, processors: ProcessorSet) -> Self {
+		self.enabled_processors = Some(processors);
+		self
+	}
+
+	pub fn max_transactions_in_flight(mut self, max: u64) -> Self {
+		self.max_transactions_in_flight = Some(max);
+		self
+	}
+
+	/// Sets what happens to transactions submitted once the bound on those in flight is reached.
+	pub fn inflight_overflow_policy(mut self, policy: OverflowPolicy) -> Self {
+		self.inflight_overflow_policy = Some(policy);
+		self
+	}
+
+	/// Passes accepted transactions on for execution in the `ordering` given.
+	pub fn mempool_ordering(mut self, ordering: MempoolOrdering) -> Self {
+		self.mempool_ordering = Some(ordering);
+		self
+	}
+
+	pub fn health_enabled(mut self, enabled: bool) -> Self {
+		self.health_enabled = Some(enabled);
+		self
+	}
+
+	pub fn health_listen(mut self, hostname: impl Into<String>, port: u16) -> Self {
+		self.health_listen = Some((hostname.into(), port));
+		self
+	}
+
+	/// Sets what the health server answers on its port.
+	pub fn health_mode(mut self, mode: HealthMode) -> Self {
+		self.health_mode = Some(mode);
+		self
+	}
+
+	/// Bounds how old the latest committed block may be for the node to be ready,
+	/// zero to not check it.
+	pub fn max_block_age(mut self, max_age: Duration) -> Self {
+		self.max_block_age = Some(max_age);
+		self
+	}
+
+	pub fn metrics_enabled(mut self, enabled: bool) -> Self {
+		self.metrics_enabled = Some(enabled);
+		self
+	}
+
+	pub fn metrics_listen(mut self, hostname: impl Into<String>, port: u16) -> Self {
+		self.metrics_listen = Some((hostname.into(), port));
+		self
+	}
+
+	pub fn log_format(mut self, format: LogFormat) -> Self {
+		self.log_format = Some(format);
+		self
+	}
+
+	pub fn log_level(mut self, level: LogLevel) -> Self {
+		self.log_level = Some(level);
+		self
+	}
+
+	/// Exports the given fraction of traces to the OTLP collector at `endpoint`.
+	pub fn otlp(mut self, endpoint: impl Into<String>, sample_rate: SampleRate) -> Self {
+		self.otlp = Some((endpoint.into(), sample_rate));
+		self
+	}
+
+	/// Builds the config, falling back to the `common::default_*` functions for unset fields.
+	pub fn build(self) -> Config {
+		let (rest_listen_hostname, rest_listen_port) = self.rest_listen.unwrap_or_else(|| {
+			(
+				common::default_maptos_rest_listen_hostname(),
+				common::default_maptos_rest_listen_port(),
+			)
+		});
+		let (rest_connection_hostname, rest_connection_port) =
+			self.rest_connection.unwrap_or_else(|| {
+				(
+					common::default_maptos_rest_connection_hostname(),
+					common::default_maptos_rest_connection_port(),
+				)
+			});
+		let (rest_tls_cert_path, rest_tls_key_path) = self.rest_tls.unwrap_or_else(|| {
+			(
+				common::default_maptos_rest_tls_cert_path(),
+				common::default_maptos_rest_tls_key_path(),
+			)
+		});
+		let (indexer_grpc_tls_cert, indexer_grpc_tls_key) =
+			self.indexer_grpc_tls.unwrap_or_else(|| {
+				(
+					common::default_maptos_indexer_grpc_tls_cert(),
+					common::default_maptos_indexer_grpc_tls_key(),
+				)
+			});
+		let (rest_max_connections, rest_listen_backlog) =
+			self.rest_connection_limits.unwrap_or_else(|| {
+				(
+					common::default_maptos_rest_max_connections(),
+					common::default_maptos_rest_listen_backlog(),
+				)
+			});
+		let (faucet_max_connections, faucet_listen_backlog) =
+			self.faucet_connection_limits.unwrap_or_else(|| {
+				(
+					common::default_maptos_faucet_rest_max_connections(),
+					common::default_maptos_faucet_rest_listen_backlog(),
+				)
+			});
+		let (fin_max_connections, fin_listen_backlog) =
+			self.fin_connection_limits.unwrap_or_else(|| {
+				(
+					common::default_fin_rest_max_connections(),
+					common::default_fin_rest_listen_backlog(),
+				)
+			});
+		let (faucet_listen_hostname, faucet_listen_port) =
+			self.faucet_listen.unwrap_or_else(|| {
+				(
+					common::default_maptos_faucet_rest_listen_hostname(),
+					common::default_maptos_faucet_rest_listen_port(),
+				)
+			});
+		let (faucet_connection_hostname, faucet_connection_port) =
+			self.faucet_connection.unwrap_or_else(|| {
+				(
+					common::default_maptos_faucet_rest_connection_hostname(),
+					common::default_maptos_faucet_rest_connection_port(),
+				)
+			});
+		let (fin_listen_hostname, fin_listen_port) = self.fin_listen.unwrap_or_else(|| {
+			(common::default_fin_rest_listen_hostname(), common::default_fin_rest_listen_port())
+		});
+		let (fin_connection_hostname, fin_connection_port) =
+			self.fin_connection.unwrap_or_else(|| {
+				(
+					common::default_fin_rest_connection_hostname(),
+					common::default_fin_rest_connection_port(),
+				)
+			});
+		let (indexer_grpc_listen_hostname, indexer_grpc_listen_port) =
+			self.indexer_grpc_listen.unwrap_or_else(|| {
+				(
+					common::default_maptos_indexer_grpc_listen_hostname(),
+					common::default_maptos_indexer_grpc_listen_port(),
+				)
+			});
+		let (indexer_grpc_connection_hostname, indexer_grpc_connection_port) =
+			self.indexer_grpc_connection.unwrap_or_else(|| {
+				(
+					common::default_maptos_indexer_grpc_connection_hostname(),
+					common::default_maptos_indexer_grpc_connection_port(),
+				)
+			});
+
+		let (postgres_connect_retries, postgres_connect_retry_delay_ms) =
+			match self.postgres_connect_retries {
+				Some((retries, delay)) => (retries, delay.as_millis() as u64),
+				None => (
+					common::default_postgres_connect_retries(),
+					common::default_postgres_connect_retry_delay_ms(),
+				),
+			};
+
+		let (indexer_processor_starting_version, indexer_processor_ending_version) =
+			self.indexer_processor_versions.unwrap_or_else(|| {
+				(
+					common::default_indexer_processor_starting_version(),
+					common::default_indexer_processor_ending_version(),
+				)
+			});
+
+		let (health_listen_hostname, health_listen_port) =
+			self.health_listen.unwrap_or_else(|| {
+				(
+					common::default_maptos_health_listen_hostname(),
+					common::default_maptos_health_listen_port(),
+				)
+			});
+
+		let (metrics_listen_hostname, metrics_listen_port) =
+			self.metrics_listen.unwrap_or_else(|| {
+				(
+					common::default_maptos_metrics_listen_hostname(),
+					common::default_maptos_metrics_listen_port(),
+				)
+			});
+		let (otlp_endpoint, otlp_sample_rate) = self.otlp.unwrap_or_else(|| {
+			(common::default_maptos_otlp_endpoint(), common::default_maptos_otlp_sample_rate())
+		});
+		let (commit_batch_size, commit_batch_max_delay_ms) =
+			self.commit_batch.unwrap_or_else(|| {
+				(
+					common::default_maptos_commit_batch_size(),
+					common::default_maptos_commit_batch_max_delay_ms(),
+				)
+			});
+
+		Config {
+			chain: chain::Config {
+				maptos_chain_id: self.chain_id.unwrap_or_else(common::default_maptos_chain_id),
+				maptos_rest_listen_hostname: rest_listen_hostname,
+				maptos_rest_listen_port: rest_listen_port,
+				maptos_rest_tls_cert_path: rest_tls_cert_path,
+				maptos_rest_tls_key_path: rest_tls_key_path,
+				maptos_rest_cors_allowed_origins: self
+					.rest_cors_allowed_origins
+					.unwrap_or_else(common::default_maptos_rest_cors_allowed_origins),
+				maptos_rest_base_path: self
+					.rest_base_path
+					.unwrap_or_else(common::default_maptos_rest_base_path),
+				maptos_rest_method_allowlist: self
+					.rest_method_allowlist
+					.unwrap_or_else(common::default_maptos_rest_method_allowlist),
+				maptos_rest_scoped_tokens: self
+					.rest_scoped_tokens
+					.unwrap_or_else(common::default_maptos_rest_scoped_tokens),
+				maptos_rest_default_encoding: self
+					.rest_default_encoding
+					.unwrap_or_else(common::default_maptos_rest_default_encoding),
+				maptos_rest_address_format: self
+					.rest_address_format
+					.unwrap_or_else(common::default_maptos_rest_address_format),
+				maptos_rest_request_timeout_sec: self
+					.rest_request_timeout
+					.map(DurationSecs::from)
+					.unwrap_or_else(common::default_maptos_rest_request_timeout_sec),
+				maptos_rest_idle_timeout_sec: self
+					.rest_idle_timeout
+					.map(DurationSecs::from)
+					.unwrap_or_else(common::default_maptos_rest_idle_timeout_sec),
+				maptos_rest_max_body_bytes: self
+					.rest_max_body_bytes
+					.unwrap_or_else(common::default_maptos_rest_max_body_bytes),
+				maptos_rest_max_gas_amount: self
+					.rest_max_gas_amount
+					.unwrap_or_else(common::default_maptos_rest_max_gas_amount),
+				maptos_max_txn_future_sec: self
+					.max_txn_future
+					.map(DurationSecs::from)
+					.unwrap_or_else(common::default_maptos_max_txn_future_sec),
+				maptos_rest_max_page_size: self
+					.rest_max_page_size
+					.unwrap_or_else(common::default_maptos_rest_max_page_size),
+				maptos_rest_max_events_per_response: self
+					.rest_max_events
+					.unwrap_or_else(common::default_maptos_rest_max_events_per_response),
+				maptos_rest_max_transaction_bytes: self
+					.rest_max_transaction_bytes
+					.unwrap_or_else(common::default_maptos_rest_max_transaction_bytes),
+				maptos_rest_max_connections: rest_max_connections,
+				maptos_rest_listen_backlog: rest_listen_backlog,
+				maptos_rest_log_sample_rate: self
+					.rest_log_sample_rate
+					.unwrap_or_else(common::default_maptos_rest_log_sample_rate),
+				maptos_rest_worker_threads: self
+					.rest_worker_threads
+					.unwrap_or_else(common::default_maptos_rest_worker_threads),
+				maptos_rest_enabled: self
+					.rest_enabled
+					.unwrap_or_else(common::default_maptos_rest_enabled),
+				maptos_expose_version: self
+					.expose_version
+					.unwrap_or_else(common::default_maptos_expose_version),
+				maptos_preload_enabled: self
+					.preload_enabled
+					.unwrap_or_else(common::default_maptos_preload_enabled),
+				maptos_preload_versions: self
+					.preload_versions
+					.unwrap_or_else(common::default_maptos_preload_versions),
+				maptos_shutdown_grace_sec: self
+					.shutdown_grace
+					.map(DurationSecs::from)
+					.unwrap_or_else(common::default_maptos_shutdown_grace_sec),
+				maptos_stop_at_version: self
+					.stop_at_version
+					.or_else(common::default_maptos_stop_at_version),
+				maptos_read_only: self.read_only.unwrap_or_else(common::default_maptos_read_only),
+				maptos_allow_chain_id_mismatch: self
+					.allow_chain_id_mismatch
+					.unwrap_or_else(common::default_maptos_allow_chain_id_mismatch),
+				maptos_gas_unit_price: self
+					.gas_unit_price
+					.unwrap_or_else(common::default_maptos_gas_unit_price),
+				maptos_clock: self.clock.unwrap_or_else(common::default_maptos_clock),
+				manual_clock: Default::default(),
+				maptos_sync_mode: self.sync_mode.unwrap_or_else(common::default_maptos_sync_mode),
+				maptos_key_scheme: self
+					.key_scheme
+					.unwrap_or_else(common::default_maptos_key_scheme),
+				maptos_feature_flags: self
+					.feature_flags
+					.unwrap_or_else(common::default_maptos_feature_flags),
+				maptos_private_key: self
+					.private_key
+					.unwrap_or_else(common::default_maptos_private_key),
+				signer: Default::default(),
+				maptos_enable_pruning: self
+					.pruning_enabled
+					.unwrap_or_else(common::default_maptos_enable_pruning),
+				maptos_ledger_prune_window: self
+					.ledger_prune_window
+					.unwrap_or_else(common::default_maptos_ledger_prune_window),
+				maptos_epoch_snapshot_prune_window: self
+					.epoch_snapshot_prune_window
+					.unwrap_or_else(common::default_maptos_epoch_snapshot_prune_window),
+				maptos_state_merkle_prune_window: self
+					.state_merkle_prune_window
+					.unwrap_or_else(common::default_maptos_state_merkle_prune_window),
+				maptos_data_dir: self.data_dir.unwrap_or_else(common::default_maptos_data_dir),
+				maptos_db_path: self.db_path,
+				maptos_db_block_cache_bytes: self
+					.db_block_cache_bytes
+					.unwrap_or_else(common::default_maptos_db_block_cache_bytes),
+				maptos_db_max_wal_bytes: self
+					.db_max_wal_bytes
+					.unwrap_or_else(common::default_maptos_db_max_wal_bytes),
+				maptos_max_db_size_bytes: self
+					.max_db_size_bytes
+					.unwrap_or_else(common::default_maptos_max_db_size_bytes),
+				maptos_commit_batch_size: commit_batch_size,
+				maptos_commit_batch_max_delay_ms: commit_batch_max_delay_ms,
+				maptos_fsync_policy: self
+					.fsync_policy
+					.unwrap_or_else(common::default_maptos_fsync_policy),
+				maptos_execution_concurrency: self
+					.execution_concurrency
+					.unwrap_or_else(common::default_maptos_execution_concurrency),
+				maptos_genesis_blob_path: self
+					.genesis_blob_path
+					.or_else(common::default_maptos_genesis_blob_path),
+				maptos_framework_path: self
+					.framework_path
+					.or_else(common::default_maptos_framework_path),
+			},
+			indexer: indexer::Config {
+				maptos_indexer_grpc_listen_hostname: indexer_grpc_listen_hostname,
+				maptos_indexer_grpc_listen_port: indexer_grpc_listen_port,
+				maptos_indexer_grpc_inactivity_timeout: self
+					.indexer_grpc_inactivity_timeout
+					.map(DurationSecs::from)
+					.unwrap_or_else(common::default_maptos_indexer_grpc_inactivity_timeout),
+				maptos_indexer_grpc_inactivity_ping_interval: self
+					.indexer_grpc_ping_interval
+					.map(DurationSecs::from)
+					.unwrap_or_else(common::default_maptos_indexer_grpc_ping_interval),
+				maptos_indexer_grpc_max_message_bytes: self
+					.indexer_grpc_max_message_bytes
+					.unwrap_or_else(common::default_maptos_indexer_grpc_max_message_bytes),
+				maptos_indexer_grpc_tcp_keepalive_sec: self
+					.indexer_grpc_tcp_keepalive
+					.map(DurationSecs::from)
+					.unwrap_or_else(common::default_maptos_indexer_grpc_tcp_keepalive_sec),
+				maptos_indexer_grpc_http2_keepalive_sec: self
+					.indexer_grpc_http2_keepalive
+					.map(DurationSecs::from)
+					.unwrap_or_else(common::default_maptos_indexer_grpc_http2_keepalive_sec),
+				maptos_indexer_grpc_tls_cert: indexer_grpc_tls_cert,
+				maptos_indexer_grpc_tls_key: indexer_grpc_tls_key,
+				maptos_indexer_grpc_client_ca: self
+					.indexer_grpc_client_ca
+					.unwrap_or_else(common::default_maptos_indexer_grpc_client_ca),
+				maptos_indexer_grpc_loopback_port: self
+					.indexer_grpc_loopback_port
+					.unwrap_or_else(common::default_maptos_indexer_grpc_loopback_port),
+				maptos_indexer_worker_threads: self
+					.indexer_worker_threads
+					.unwrap_or_else(common::default_maptos_indexer_worker_threads),
+				maptos_indexer_grpc_enabled: self
+					.indexer_grpc_enabled
+					.unwrap_or_else(common::default_maptos_indexer_grpc_enabled),
+				maptos_indexer_max_lag_versions: self
+					.indexer_max_lag_versions
+					.unwrap_or_else(common::default_maptos_indexer_max_lag_versions),
+				maptos_indexer_grpc_batch_size: self
+					.indexer_grpc_batch_size
+					.unwrap_or_else(common::default_maptos_indexer_grpc_batch_size),
+				maptos_indexer_grpc_send_buffer_size: self
+					.indexer_grpc_send_buffer_size
+					.unwrap_or_else(common::default_maptos_indexer_grpc_send_buffer_size),
+				maptos_indexer_grpc_compression: self
+					.indexer_grpc_compression
+					.unwrap_or_else(common::default_maptos_indexer_grpc_compression),
+			},
+			indexer_processor: indexer_processor::Config {
+				postgres_connection_string: self
+					.postgres_connection_string
+					.unwrap_or_else(common::default_postgres_connection_string),
+				postgres_max_connections: self
+					.postgres_max_connections
+					.unwrap_or_else(common::default_postgres_max_connections),
+				postgres_connection_timeout_sec: self
+					.postgres_connection_timeout
+					.map(DurationSecs::from)
+					.unwrap_or_else(common::default_postgres_connection_timeout_sec),
+				postgres_connect_retries,
+				postgres_connect_retry_delay_ms,
+				indexer_processor_auth_token: self
+					.indexer_processor_auth_token
+					.unwrap_or_else(common::default_indexer_processor_auth_token),
+				indexer_processor_starting_version,
+				indexer_processor_ending_version,
+				indexer_processor_enabled_processors: self
+					.enabled_processors
+					.unwrap_or_else(common::default_indexer_enabled_processors),
+			},
+			client: client::Config {
+				maptos_rest_connection_hostname: rest_connection_hostname.clone(),
+				maptos_rest_connection_port: rest_connection_port,
+				maptos_faucet_rest_connection_hostname: faucet_connection_hostname,
+				maptos_faucet_rest_connection_port: faucet_connection_port,
+				maptos_indexer_grpc_connection_hostname: indexer_grpc_connection_hostname,
+				maptos_indexer_grpc_connection_port: indexer_grpc_connection_port,
+				maptos_indexer_upstreams: self
+					.indexer_upstreams
+					.unwrap_or_else(common::default_maptos_indexer_upstreams),
+				fin_rest_connection_hostname: fin_connection_hostname,
+				fin_rest_connection_port: fin_connection_port,
+				maptos_dns_refresh_sec: self
+					.dns_refresh
+					.map(DurationSecs::from)
+					.unwrap_or_else(common::default_maptos_dns_refresh_sec),
+			},
+			faucet: faucet::Config {
+				maptos_rest_connection_hostname: rest_connection_hostname,
+				maptos_rest_connection_port: rest_connection_port,
+				maptos_faucet_rest_listen_hostname: faucet_listen_hostname,
+				maptos_faucet_rest_listen_port: faucet_listen_port,
+				maptos_faucet_rest_cors_allowed_origins: self
+					.faucet_cors_allowed_origins
+					.unwrap_or_else(common::default_maptos_faucet_rest_cors_allowed_origins),
+				maptos_faucet_rest_base_path: self
+					.faucet_base_path
+					.unwrap_or_else(common::default_maptos_faucet_rest_base_path),
+				maptos_faucet_rest_max_body_bytes: self
+					.faucet_max_body_bytes
+					.unwrap_or_else(common::default_maptos_faucet_rest_max_body_bytes),
+				maptos_faucet_rest_idle_timeout_sec: self
+					.faucet_idle_timeout
+					.map(DurationSecs::from)
+					.unwrap_or_else(common::default_maptos_faucet_rest_idle_timeout_sec),
+				maptos_faucet_rest_max_connections: faucet_max_connections,
+				maptos_faucet_rest_listen_backlog: faucet_listen_backlog,
+				faucet_rate_limit_per_ip_per_hour: self
+					.faucet_rate_limit_per_ip_per_hour
+					.unwrap_or_else(common::default_faucet_rate_limit_per_ip_per_hour),
+				faucet_max_amount_per_request: self
+					.faucet_max_amount_per_request
+					.unwrap_or_else(common::default_faucet_max_amount_per_request),
+				faucet_mint_amount: self
+					.faucet_mint_amount
+					.unwrap_or_else(common::default_faucet_mint_amount),
+				faucet_txn_expiration_sec: self
+					.faucet_txn_expiration
+					.map(DurationSecs::from)
+					.unwrap_or_else(common::default_faucet_txn_expiration_sec),
+				faucet_cooldown_sec: self
+					.faucet_cooldown
+					.map(DurationSecs::from)
+					.unwrap_or_else(common::default_faucet_cooldown_sec),
+				faucet_require_captcha: self
+					.faucet_require_captcha
+					.unwrap_or_else(common::default_faucet_require_captcha),
+				faucet_private_key: self
+					.faucet_private_key
+					.or_else(common::default_faucet_private_key),
+				faucet_private_keys: self
+					.faucet_private_keys
+					.unwrap_or_else(common::default_faucet_private_keys),
+				faucet_blocked_addresses: self
+					.faucet_blocked_addresses
+					.unwrap_or_else(common::default_faucet_blocked_addresses),
+				faucet_allowlist_addresses: self
+					.faucet_allowlist_addresses
+					.unwrap_or_else(common::default_faucet_allowlist_addresses),
+				maptos_faucet_enabled: self
+					.faucet_enabled
+					.unwrap_or_else(common::default_maptos_faucet_enabled),
+			},
+			fin: fin::Config {
+				fin_rest_listen_hostname: fin_listen_hostname,
+				fin_rest_listen_port: fin_listen_port,
+				fin_rest_request_timeout_sec: self
+					.fin_request_timeout
+					.map(DurationSecs::from)
+					.unwrap_or_else(common::default_fin_rest_request_timeout_sec),
+				fin_rest_max_connections: fin_max_connections,
+				fin_rest_listen_backlog: fin_listen_backlog,
+				fin_rest_worker_threads: self
+					.fin_worker_threads
+					.unwrap_or_else(common::default_fin_rest_worker_threads),
+				fin_rest_enabled: self.fin_enabled.unwrap_or_else(common::default_fin_rest_enabled),
+				fin_rest_max_concurrent_views: self
+					.fin_max_concurrent_views
+					.unwrap_or_else(common::default_fin_rest_max_concurrent_views),
+			},
+			load_shedding: load_shedding::Config {
+				max_transactions_in_flight: self
+					.max_transactions_in_flight
+					.unwrap_or_else(common::default_max_transactions_in_flight),
+				inflight_overflow_policy: self
+					.inflight_overflow_policy
+					.unwrap_or_else(common::default_maptos_inflight_overflow_policy),
+				mempool_ordering: self
+					.mempool_ordering
+					.unwrap_or_else(common::default_maptos_mempool_ordering),
+			},
+			health: health::Config {
+				maptos_health_enabled: self
+					.health_enabled
+					.unwrap_or_else(common::default_maptos_health_enabled),
+				maptos_health_listen_hostname: health_listen_hostname,
+				maptos_health_listen_port: health_listen_port,
+				maptos_health_mode: self
+					.health_mode
+					.unwrap_or_else(common::default_maptos_health_mode),
+				maptos_max_block_age_sec: self
+					.max_block_age
+					.map(DurationSecs::from)
+					.unwrap_or_else(common::default_maptos_max_block_age_sec),
+			},
+			metrics: metrics::Config {
+				maptos_metrics_enabled: self
+					.metrics_enabled
+					.unwrap_or_else(common::default_maptos_metrics_enabled),
+				maptos_metrics_listen_hostname: metrics_listen_hostname,
+				maptos_metrics_listen_port: metrics_listen_port,
+			},
+			logging: logging::Config {
+				maptos_log_format: self
+					.log_format
+					.unwrap_or_else(common::default_maptos_log_format),
+				maptos_log_level: self.log_level.unwrap_or_else(common::default_maptos_log_level),
+				maptos_otlp_endpoint: otlp_endpoint,
+				maptos_otlp_sample_rate: otlp_sample_rate,
+			},
+			sources: provenance::Sources::from_env(),
+			port_probes: ports::PortProbes::default(),
+		}
+	}
+}
+
+/// The built-in profiles [`Config::load_with_profile`] accepts.
+///
+/// Only `local` is built in: a node joining a live network needs that network's endpoints
+/// and keys, which belong in its config file.
+pub const BUILT_IN_PROFILES: &[&str] = &["local"];
+
+impl Config {
+	/// Starts a [`ConfigBuilder`] with every field unset.
+	pub fn builder() -> ConfigBuilder {
+		ConfigBuilder::new()
+	}
+
+	/// A config for a single local node, for demos and tests.
+	///
+	/// Every server binds loopback on its default port and is reached there, in plaintext,
+	/// with an ephemeral private key and the local Postgres server at its default credentials.
+	/// Fields not named here still fall back to the environment, as with [`ConfigBuilder`].
+	pub fn for_local_devnet() -> Config {
+		const LOOPBACK: &str = "127.0.0.1";
+		const REST_PORT: u16 = 30731;
+		const FAUCET_PORT: u16 = 30732;
+		const FIN_PORT: u16 = 30733;
+		const INDEXER_GRPC_PORT: u16 = 30734;
+		const HEALTH_PORT: u16 = 30730;
+		const METRICS_PORT: u16 = 9464;
+
+		Config::builder()
+			.chain_id(common::parse_chain_id("local").expect("local is a chain id alias"))
+			.private_key(Ed25519PrivateKey::generate(&mut rand::thread_rng()))
+			.rest_listen(LOOPBACK, REST_PORT)
+			.rest_connection(LOOPBACK, REST_PORT)
+			.rest_tls("", "")
+			.faucet_listen(LOOPBACK, FAUCET_PORT)
+			.faucet_connection(LOOPBACK, FAUCET_PORT)
+			.fin_listen(LOOPBACK, FIN_PORT)
+			.fin_connection(LOOPBACK, FIN_PORT)
+			.indexer_grpc_listen(LOOPBACK, INDEXER_GRPC_PORT)
+			.indexer_grpc_connection(LOOPBACK, INDEXER_GRPC_PORT)
+			.indexer_grpc_tls("", "")
+			.health_listen(LOOPBACK, HEALTH_PORT)
+			.metrics_listen(LOOPBACK, METRICS_PORT)
+			.postgres_connection_string(common::postgres_connection_string_from_components(
+				"localhost",
+				common::DEFAULT_POSTGRES_PORT,
+				common::DEFAULT_POSTGRES_USER,
+				common::DEFAULT_POSTGRES_PASSWORD,
+				None,
+			))
+			.build()
+	}
+
+	/// The config of the built-in profile `profile`, one of [`BUILT_IN_PROFILES`]: `local` is
+	/// [`Config::for_local_devnet`].
+	///
+	/// Fields a profile does not name fall back to the environment, as with [`ConfigBuilder`].
+	pub fn for_profile(profile: &str) -> Result<Config, anyhow::Error> {
+		match profile.trim().to_ascii_lowercase().as_str() {
+			"local" => Ok(Config::for_local_devnet()),
+			_ => anyhow::bail!(
+				"unknown config profile {:?}, expected one of: {}",
+				profile,
+				BUILT_IN_PROFILES.join(", ")
+			),
+		}
+	}
+
+	/// Loads the built-in profile `profile`, then overrides each field whose env var is set,
+	/// so that the environment wins over the profile.
+	pub fn load_with_profile(profile: &str) -> Result<Config, anyhow::Error> {
+		let mut config = Config::for_profile(profile)?;
+		config.apply_env_overrides()?;
+		Ok(config)
+	}
+
+	/// A copy of this config for a second node on the same host, under a freshly generated
+	/// private key and with every listen port shifted up by `port_offset`.
+	///
+	/// A connection port is shifted with the listen port it equals. One advertising a port of
+	/// its own, as behind NAT, is left for the copy to advertise anew.
+	///
+	/// Ports left at 0 stay there, and the faucet keeps any key of its own. Fails when a port
+	/// would pass 65535, or when a server of the copy would bind a port this config binds.
+	pub fn try_clone_with_fresh_key(&self, port_offset: u16) -> Result<Config, anyhow::Error> {
+		let mut derived = self.clone();
+		derived.chain.maptos_private_key = Ed25519PrivateKey::generate(&mut rand::thread_rng());
+		let shift = |var: &'static str, port: &mut u16| -> Result<(), ConfigError> {
+			if *port == 0 {
+				return Ok(());
+			}
+			*port = port.checked_add(port_offset).ok_or_else(|| ConfigError::InvalidPort {
+				var,
+				raw: format!("{}+{}", port, port_offset),
+			})?;
+			Ok(())
+		};
+		// Connections are compared against the listen ports before those are shifted.
+		for (var, listen_port, port) in derived.connection_ports_mut() {
+			if *port == listen_port {
+				shift(var, port)?;
+			}
+		}
+		for (var, port) in derived.listen_ports_mut() {
+			shift(var, port)?;
+		}
+
+		let bound = self.listeners();
+		for listener in derived.listeners().into_iter().filter(|listener| listener.port != 0) {
+			if let Some(taken) = bound.iter().find(|taken| taken.port == listener.port) {
+				return Err(ValidationError::PortCollision {
+					port: listener.port,
+					fields: vec![taken.port_var, listener.port_var],
+				}
+				.into());
+			}
+		}
+		Ok(derived)
+	}
+
+	/// Every listen port, named by its env var.
+	pub(super) fn listen_ports_mut(&mut self) -> Vec<(&'static str, &mut u16)> {
+		vec![
+			("MAPTOS_API_LISTEN_PORT", &mut self.chain.maptos_rest_listen_port),
+			("FAUCET_API_LISTEN_PORT", &mut self.faucet.maptos_faucet_rest_listen_port),
+			("MAPTOS_FIN_VIEW_API_LISTEN_PORT", &mut self.fin.fin_rest_listen_port),
+			("MAPTOS_INDEXER_GRPC_LISTEN_PORT", &mut self.indexer.maptos_indexer_grpc_listen_port),
+			("MAPTOS_HEALTH_LISTEN_PORT", &mut self.health.maptos_health_listen_port),
+			("MAPTOS_METRICS_LISTEN_PORT", &mut self.metrics.maptos_metrics_listen_port),
+		]
+	}
+
+	/// Every connection port, named by its env var, with the listen port it targets.
+	pub(super) fn connection_ports_mut(&mut self) -> Vec<(&'static str, u16, &mut u16)> {
+		let rest = self.chain.maptos_rest_listen_port;
+		let faucet = self.faucet.maptos_faucet_rest_listen_port;
+		let indexer_grpc = self.indexer.maptos_indexer_grpc_listen_port;
+		let fin = self.fin.fin_rest_listen_port;
+		vec![
+			("MAPTOS_API_CONNECTION_PORT", rest, &mut self.client.maptos_rest_connection_port),
+			("MAPTOS_API_CONNECTION_PORT", rest, &mut self.faucet.maptos_rest_connection_port),
+			(
+				"FAUCET_API_CONNECTION_PORT",
+				faucet,
+				&mut self.client.maptos_faucet_rest_connection_port,
+			),
+			(
+				"MAPTOS_INDEXER_GRPC_CONNECTION_PORT",
+				indexer_grpc,
+				&mut self.client.maptos_indexer_grpc_connection_port,
+			),
+			("MAPTOS_FIN_VIEW_API_CONNECTION_PORT", fin, &mut self.client.fin_rest_connection_port),
+		]
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_builders_produce_independent_configs() {
+		let _env = godfig::env::read_lock();
+		let node = |offset: u16| {
+			Config::builder()
+				.chain_id(ChainId::new(42))
+				.rest_listen("127.0.0.1", 40000 + offset)
+				.rest_connection("127.0.0.1", 40000 + offset)
+				.faucet_listen("127.0.0.1", 40100 + offset)
+				.faucet_connection("127.0.0.1", 40100 + offset)
+				.fin_listen("127.0.0.1", 40200 + offset)
+				.fin_connection("127.0.0.1", 40200 + offset)
+				.indexer_grpc_listen("127.0.0.1", 40300 + offset)
+				.indexer_grpc_connection("127.0.0.1", 40300 + offset)
+				.private_key(Ed25519PrivateKey::generate(&mut rand::thread_rng()))
+				.build()
+		};
+		let first = node(0);
+		let second = node(1);
+
+		assert_eq!(first.chain.maptos_rest_listen_port, 40000);
+		assert_eq!(second.chain.maptos_rest_listen_port, 40001);
+		assert_eq!(second.faucet.maptos_rest_connection_port, 40001);
+		assert_eq!(second.chain.maptos_chain_id, ChainId::new(42));
+		assert_eq!(first.validate(), Ok(vec![]));
+		assert_eq!(second.validate(), Ok(vec![]));
+	}
+
+	#[test]
+	fn test_local_devnet_config_validates_cleanly() {
+		let config = Config::for_local_devnet();
+
+		assert_eq!(config.chain.maptos_chain_id, ChainId::new(27));
+		assert_eq!(config.chain.maptos_rest_listen_hostname, "127.0.0.1");
+		let report = config.validate_all();
+		assert!(!report.has_errors(), "{}", report);
+	}
+
+	#[test]
+	fn test_env_overrides_the_loaded_profile() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::lock();
+		std::env::set_var("MAPTOS_HEALTH_LISTEN_PORT", "30740");
+		let loaded = Config::load_with_profile("local");
+		std::env::remove_var("MAPTOS_HEALTH_LISTEN_PORT");
+		let config = loaded?;
+
+		assert_eq!(config.health.maptos_health_listen_port, 30740);
+		assert_eq!(config.chain.maptos_chain_id, ChainId::new(27));
+		assert_eq!(config.chain.maptos_rest_listen_hostname, "127.0.0.1");
+
+		let error = Config::load_with_profile("devnet").unwrap_err().to_string();
+		assert!(error.contains("expected one of: local"), "{}", error);
+		Ok(())
+	}
+
+	#[test]
+	fn test_cloned_node_gets_its_own_key_and_ports() -> Result<(), anyhow::Error> {
+		let config = Config::for_local_devnet();
+		let derived = config.try_clone_with_fresh_key(100)?;
+
+		assert_ne!(derived.chain.maptos_private_key, config.chain.maptos_private_key);
+		assert_ne!(
+			derived.signer_account_address().unwrap(),
+			config.signer_account_address().unwrap()
+		);
+		assert_eq!(derived.chain.maptos_rest_listen_port, 30831);
+		assert_eq!(derived.client.maptos_rest_connection_port, 30831);
+		assert_eq!(derived.faucet.maptos_rest_connection_port, 30831);
+		assert_eq!(derived.indexer.maptos_indexer_grpc_listen_port, 30834);
+		assert_eq!(derived.metrics.maptos_metrics_listen_port, 9564);
+		assert_eq!(derived.chain.maptos_chain_id, config.chain.maptos_chain_id);
+		assert!(!derived.validate_all().has_errors());
+		Ok(())
+	}
+
+	#[test]
+	fn test_cloning_rejects_overflowing_or_overlapping_ports() {
+		let mut config = Config::for_local_devnet();
+		// The REST port shifted by 1 lands on the faucet port of the original.
+		let error = config.try_clone_with_fresh_key(1).unwrap_err();
+		assert_eq!(
+			error.downcast_ref::<ValidationError>(),
+			Some(&ValidationError::PortCollision {
+				port: 30732,
+				fields: vec!["FAUCET_API_LISTEN_PORT", "MAPTOS_API_LISTEN_PORT"],
+			})
+		);
+
+		config.metrics.maptos_metrics_listen_port = 65500;
+		let error = config.try_clone_with_fresh_key(100).unwrap_err();
+		assert_eq!(
+			error.downcast_ref::<ConfigError>(),
+			Some(&ConfigError::InvalidPort {
+				var: "MAPTOS_METRICS_LISTEN_PORT",
+				raw: "65500+100".to_string(),
+			})
+		);
+	}
+
+	#[test]
+	fn test_connection_ports_are_independent_of_listen_ports() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::read_lock();
+		// Behind NAT, REST binds 30731 but is reached on 443.
+		let config = Config::builder()
+			.rest_listen("0.0.0.0", 30731)
+			.rest_connection("node.example.com", 443)
+			.build();
+
+		assert_eq!(config.chain.maptos_rest_listen_port, 30731);
+		assert_eq!(config.faucet.maptos_rest_connection_port, 443);
+		assert_eq!(config.rest_connection_url()?.as_str(), "http://node.example.com:443/");
+		assert!(!config.validate_all().has_errors());
+
+		// A copy binds its own ports but keeps advertising the mapped one.
+		let derived = config.try_clone_with_fresh_key(100)?;
+		assert_eq!(derived.chain.maptos_rest_listen_port, 30831);
+		assert_eq!(derived.client.maptos_rest_connection_port, 443);
+		assert_eq!(derived.client.fin_rest_connection_port, derived.fin.fin_rest_listen_port);
+		Ok(())
+	}
+
+	#[test]
+	fn test_durations_keep_their_precision() {
+		let _env = godfig::env::read_lock();
+		let config = Config::builder()
+			.indexer_grpc_inactivity_timeout(Duration::from_secs(90))
+			.indexer_grpc_ping_interval(Duration::from_millis(15_500))
+			.build();
+
+		assert_eq!(config.indexer.inactivity_timeout(), Duration::from_secs(90));
+		assert_eq!(config.indexer.ping_interval(), Duration::from_millis(15_500));
+	}
+
+	#[test]
+	fn test_servers_can_be_turned_off() {
+		let _env = godfig::env::read_lock();
+		let config = Config::builder()
+			.rest_enabled(false)
+			.faucet_enabled(false)
+			.fin_enabled(false)
+			.build();
+
+		assert!(!config.chain.maptos_rest_enabled);
+		assert!(!config.faucet.maptos_faucet_enabled);
+		assert!(!config.fin.fin_rest_enabled);
+		assert!(Config::builder().faucet_enabled(true).build().faucet.maptos_faucet_enabled);
+	}
+
+	#[test]
+	fn test_chain_id_mismatch_can_be_allowed() {
+		let _env = godfig::env::read_lock();
+		let config = Config::builder().chain_id(ChainId::new(27)).build();
+		assert!(config.chain.check_node_chain_id(ChainId::new(4)).is_err());
+
+		let config = Config::builder()
+			.chain_id(ChainId::new(27))
+			.allow_chain_id_mismatch(true)
+			.build();
+		assert_eq!(config.chain.check_node_chain_id(ChainId::new(4)), Ok(()));
+	}
+}
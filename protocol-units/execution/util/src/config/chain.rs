@@ -1,13 +1,47 @@
+use super::clock::{ClockKind, ManualClockHandle};
+use super::commit_batch::{CommitBatchPolicy, FsyncPolicy};
 use super::common::{
-	default_maptos_chain_id, default_maptos_epoch_snapshot_prune_window,
-	default_maptos_ledger_prune_window, default_maptos_private_key,
-	default_maptos_rest_listen_hostname, default_maptos_rest_listen_port,
-	default_maptos_state_merkle_prune_window,
+	default_maptos_allow_chain_id_mismatch, default_maptos_chain_id, default_maptos_clock,
+	default_maptos_commit_batch_max_delay_ms, default_maptos_commit_batch_size,
+	default_maptos_data_dir, default_maptos_db_block_cache_bytes, default_maptos_db_max_wal_bytes,
+	default_maptos_enable_pruning, default_maptos_epoch_snapshot_prune_window,
+	default_maptos_execution_concurrency, default_maptos_expose_version,
+	default_maptos_feature_flags, default_maptos_framework_path, default_maptos_fsync_policy,
+	default_maptos_gas_unit_price, default_maptos_genesis_blob_path, default_maptos_key_scheme,
+	default_maptos_ledger_prune_window, default_maptos_max_db_size_bytes,
+	default_maptos_max_txn_future_sec, default_maptos_preload_enabled,
+	default_maptos_preload_versions, default_maptos_private_key, default_maptos_read_only,
+	default_maptos_rest_address_format, default_maptos_rest_base_path,
+	default_maptos_rest_cors_allowed_origins, default_maptos_rest_default_encoding,
+	default_maptos_rest_enabled, default_maptos_rest_idle_timeout_sec,
+	default_maptos_rest_listen_backlog, default_maptos_rest_listen_hostname,
+	default_maptos_rest_listen_port, default_maptos_rest_log_sample_rate,
+	default_maptos_rest_max_body_bytes, default_maptos_rest_max_connections,
+	default_maptos_rest_max_events_per_response, default_maptos_rest_max_gas_amount,
+	default_maptos_rest_max_page_size, default_maptos_rest_max_transaction_bytes,
+	default_maptos_rest_method_allowlist, default_maptos_rest_request_timeout_sec,
+	default_maptos_rest_scoped_tokens, default_maptos_rest_tls_cert_path,
+	default_maptos_rest_tls_key_path, default_maptos_rest_worker_threads,
+	default_maptos_shutdown_grace_sec, default_maptos_state_merkle_prune_window,
+	default_maptos_stop_at_version, default_maptos_sync_mode, generate_ephemeral_private_key,
+	resolve_worker_threads, unconfigured_private_key,
 };
+use super::duration::DurationSecs;
+use super::endpoints::{normalize_base_path, ConnectionLimits};
+use super::features::FeatureFlags;
+use super::logging::SampleRate;
+use super::rest_routes::RestRouteSet;
+use super::rest_scopes::RestScopedTokens;
+use super::signer::{KeyScheme, SignerCache};
+use anyhow::Context;
 use aptos_crypto::ed25519::Ed25519PrivateKey;
-use aptos_types::chain_id::ChainId;
+use aptos_types::{account_address::AccountAddress, chain_id::ChainId};
 use serde::{Deserialize, Serialize};
-use std::path::PathBuf;
+use std::fmt;
+use std::num::NonZeroU64;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Config {
@@ -15,7 +49,7 @@ pub struct Config {
 	#[serde(default = "default_maptos_chain_id")]
 	pub maptos_chain_id: ChainId,
 
-	/// The URL of the Aptos REST server
+	/// The URL of the Aptos REST server, or `unix:/path` to listen on a Unix domain socket
 	#[serde(default = "default_maptos_rest_listen_hostname")]
 	pub maptos_rest_listen_hostname: String,
 
@@ -23,10 +57,159 @@ pub struct Config {
 	#[serde(default = "default_maptos_rest_listen_port")]
 	pub maptos_rest_listen_port: u16,
 
-	/// The private key for the Aptos node
+	/// The PEM certificate for serving the Aptos REST server over TLS
+	#[serde(default = "default_maptos_rest_tls_cert_path")]
+	pub maptos_rest_tls_cert_path: String,
+
+	/// The PEM private key for serving the Aptos REST server over TLS
+	#[serde(default = "default_maptos_rest_tls_key_path")]
+	pub maptos_rest_tls_key_path: String,
+
+	/// The origins allowed to call the Aptos REST server, `*` for any
+	#[serde(default = "default_maptos_rest_cors_allowed_origins")]
+	pub maptos_rest_cors_allowed_origins: Vec<String>,
+
+	/// The path prefix the Aptos REST server routes are served under, empty for the root
+	#[serde(default = "default_maptos_rest_base_path")]
+	pub maptos_rest_base_path: String,
+
+	/// The route groups the Aptos REST server serves; others are answered with 404
+	#[serde(default = "default_maptos_rest_method_allowlist")]
+	pub maptos_rest_method_allowlist: RestRouteSet,
+
+	/// The bearer tokens the Aptos REST server requires and the scopes each grants,
+	/// none to serve every request
+	#[serde(default = "default_maptos_rest_scoped_tokens")]
+	pub maptos_rest_scoped_tokens: RestScopedTokens,
+
+	/// The encoding of responses to Aptos REST requests which accept any
+	#[serde(default = "default_maptos_rest_default_encoding")]
+	pub maptos_rest_default_encoding: RestEncoding,
+
+	/// How account addresses are written in Aptos REST responses
+	#[serde(default = "default_maptos_rest_address_format")]
+	pub maptos_rest_address_format: AddressFormat,
+
+	/// The time limit for a single request to the Aptos REST server
+	#[serde(default = "default_maptos_rest_request_timeout_sec")]
+	pub maptos_rest_request_timeout_sec: DurationSecs,
+
+	/// How long a REST connection may sit idle before it is closed, 0 to keep it open
+	#[serde(default = "default_maptos_rest_idle_timeout_sec")]
+	pub maptos_rest_idle_timeout_sec: DurationSecs,
+
+	/// The largest request body accepted by the Aptos REST server, in bytes
+	#[serde(default = "default_maptos_rest_max_body_bytes")]
+	pub maptos_rest_max_body_bytes: u64,
+
+	/// The most gas a transaction submitted to the Aptos REST server may reserve
+	#[serde(default = "default_maptos_rest_max_gas_amount")]
+	pub maptos_rest_max_gas_amount: u64,
+
+	/// How far past the present a submitted transaction may set its expiration
+	#[serde(default = "default_maptos_max_txn_future_sec")]
+	pub maptos_max_txn_future_sec: DurationSecs,
+
+	/// The most items a paginated request to the Aptos REST server returns; larger page sizes
+	/// are lowered to it
+	#[serde(default = "default_maptos_rest_max_page_size")]
+	pub maptos_rest_max_page_size: u32,
+
+	/// The most events a response of the Aptos REST server lists for each transaction, or for
+	/// an events query; longer lists are truncated
+	#[serde(default = "default_maptos_rest_max_events_per_response")]
+	pub maptos_rest_max_events_per_response: u32,
+
+	/// The largest transaction accepted for submission by the Aptos REST server, in bytes
+	#[serde(default = "default_maptos_rest_max_transaction_bytes")]
+	pub maptos_rest_max_transaction_bytes: u64,
+
+	/// The cap on concurrent REST connections, 0 for unlimited
+	#[serde(default = "default_maptos_rest_max_connections")]
+	pub maptos_rest_max_connections: u32,
+
+	/// The fraction of REST requests which are logged, from 0.0 for none to 1.0 for all
+	#[serde(default = "default_maptos_rest_log_sample_rate")]
+	pub maptos_rest_log_sample_rate: SampleRate,
+
+	/// The REST runtime worker threads, 0 for one per CPU
+	#[serde(default = "default_maptos_rest_worker_threads")]
+	pub maptos_rest_worker_threads: u32,
+
+	/// Whether the node serves the Aptos REST server
+	#[serde(default = "default_maptos_rest_enabled")]
+	pub maptos_rest_enabled: bool,
+
+	/// Whether the Aptos REST server serves the node's version at `/version`
+	#[serde(default = "default_maptos_expose_version")]
+	pub maptos_expose_version: bool,
+
+	/// Whether recent state is preloaded into the storage caches before the Aptos REST server
+	/// accepts connections
+	#[serde(default = "default_maptos_preload_enabled")]
+	pub maptos_preload_enabled: bool,
+
+	/// The latest committed versions whose state is preloaded
+	#[serde(default = "default_maptos_preload_versions")]
+	pub maptos_preload_versions: u64,
+
+	/// The REST listen backlog, 0 for the system maximum
+	#[serde(default = "default_maptos_rest_listen_backlog")]
+	pub maptos_rest_listen_backlog: u32,
+
+	/// How long the servers wait for in-flight work to finish when stopping
+	#[serde(default = "default_maptos_shutdown_grace_sec")]
+	pub maptos_shutdown_grace_sec: DurationSecs,
+
+	/// The ledger version at which the node shuts down gracefully, if any
+	#[serde(default = "default_maptos_stop_at_version")]
+	pub maptos_stop_at_version: Option<u64>,
+
+	/// Whether the node refuses transaction submissions and runs no faucet
+	#[serde(default = "default_maptos_read_only")]
+	pub maptos_read_only: bool,
+
+	/// Whether an upstream node reporting another chain id than the configured one is used
+	/// anyway
+	#[serde(default = "default_maptos_allow_chain_id_mismatch")]
+	pub maptos_allow_chain_id_mismatch: bool,
+
+	/// The gas unit price of transactions the node builds itself, 0 for its gas estimate
+	#[serde(default = "default_maptos_gas_unit_price")]
+	pub maptos_gas_unit_price: u64,
+
+	/// The clock the node reads the time from
+	#[serde(default = "default_maptos_clock")]
+	pub maptos_clock: ClockKind,
+
+	/// The clock advanced by tests when `maptos_clock` is manual
+	#[serde(skip)]
+	pub(crate) manual_clock: ManualClockHandle,
+
+	/// How the node catches up with the chain when it starts
+	#[serde(default = "default_maptos_sync_mode")]
+	pub maptos_sync_mode: SyncMode,
+
+	/// The signature scheme of the private key
+	#[serde(default = "default_maptos_key_scheme")]
+	pub maptos_key_scheme: KeyScheme,
+
+	/// The experimental behaviors switched on or off
+	#[serde(default = "default_maptos_feature_flags")]
+	pub maptos_feature_flags: FeatureFlags,
+
+	/// The private key for the Aptos node, held as its 32 bytes whatever its scheme
 	#[serde(default = "default_maptos_private_key")]
 	pub maptos_private_key: Ed25519PrivateKey,
 
+	/// The identity derived from the private key, computed on first use
+	#[serde(skip)]
+	pub(crate) signer: SignerCache,
+
+	/// Whether the storage is pruned; when not, the prune windows are ignored
+	#[serde(default = "default_maptos_enable_pruning")]
+	pub maptos_enable_pruning: bool,
+
 	/// Ledger prune window
 	#[serde(default = "default_maptos_ledger_prune_window")]
 	pub maptos_ledger_prune_window: u64,
@@ -39,8 +222,50 @@ pub struct Config {
 	#[serde(default = "default_maptos_state_merkle_prune_window")]
 	pub maptos_state_merkle_prune_window: u64,
 
-	/// The path to the Aptos database
+	/// The directory the node stores its data under
+	#[serde(default = "default_maptos_data_dir")]
+	pub maptos_data_dir: PathBuf,
+
+	/// The path to the Aptos database, relative to the data directory unless absolute
 	pub maptos_db_path: Option<PathBuf>,
+
+	/// The RocksDB block cache of each Aptos database, in bytes
+	#[serde(default = "default_maptos_db_block_cache_bytes")]
+	pub maptos_db_block_cache_bytes: u64,
+
+	/// The size the RocksDB write-ahead log of each Aptos database grows to before its
+	/// memtables are flushed, in bytes
+	#[serde(default = "default_maptos_db_max_wal_bytes")]
+	pub maptos_db_max_wal_bytes: u64,
+
+	/// The disk the Aptos databases may use before pruning is forced on, 0 for unlimited
+	#[serde(default = "default_maptos_max_db_size_bytes")]
+	pub maptos_max_db_size_bytes: u64,
+
+	/// The most transactions grouped into one commit batch, 1 committing each on its own
+	#[serde(default = "default_maptos_commit_batch_size")]
+	pub maptos_commit_batch_size: u32,
+
+	/// The longest a partial commit batch is held before it is flushed, in milliseconds
+	#[serde(default = "default_maptos_commit_batch_max_delay_ms")]
+	pub maptos_commit_batch_max_delay_ms: u64,
+
+	/// When the storage commits are synced to disk; the DA DB follows it, while the Aptos
+	/// databases sync every commit
+	#[serde(default = "default_maptos_fsync_policy")]
+	pub maptos_fsync_policy: FsyncPolicy,
+
+	/// The threads executing each block in parallel, 0 for one per CPU
+	#[serde(default = "default_maptos_execution_concurrency")]
+	pub maptos_execution_concurrency: u32,
+
+	/// The genesis transaction to bootstrap an empty database from instead of the built-in one
+	#[serde(default = "default_maptos_genesis_blob_path")]
+	pub maptos_genesis_blob_path: Option<PathBuf>,
+
+	/// The framework release bundle the built-in genesis is encoded with, if not the bundled one
+	#[serde(default = "default_maptos_framework_path")]
+	pub maptos_framework_path: Option<PathBuf>,
 }
 
 impl Default for Config {
@@ -49,11 +274,828 @@ impl Default for Config {
 			maptos_chain_id: default_maptos_chain_id(),
 			maptos_rest_listen_hostname: default_maptos_rest_listen_hostname(),
 			maptos_rest_listen_port: default_maptos_rest_listen_port(),
+			maptos_rest_tls_cert_path: default_maptos_rest_tls_cert_path(),
+			maptos_rest_tls_key_path: default_maptos_rest_tls_key_path(),
+			maptos_rest_cors_allowed_origins: default_maptos_rest_cors_allowed_origins(),
+			maptos_rest_base_path: default_maptos_rest_base_path(),
+			maptos_rest_method_allowlist: default_maptos_rest_method_allowlist(),
+			maptos_rest_scoped_tokens: default_maptos_rest_scoped_tokens(),
+			maptos_rest_default_encoding: default_maptos_rest_default_encoding(),
+			maptos_rest_address_format: default_maptos_rest_address_format(),
+			maptos_rest_request_timeout_sec: default_maptos_rest_request_timeout_sec(),
+			maptos_rest_idle_timeout_sec: default_maptos_rest_idle_timeout_sec(),
+			maptos_rest_max_body_bytes: default_maptos_rest_max_body_bytes(),
+			maptos_rest_max_gas_amount: default_maptos_rest_max_gas_amount(),
+			maptos_max_txn_future_sec: default_maptos_max_txn_future_sec(),
+			maptos_rest_max_page_size: default_maptos_rest_max_page_size(),
+			maptos_rest_max_events_per_response: default_maptos_rest_max_events_per_response(),
+			maptos_rest_max_transaction_bytes: default_maptos_rest_max_transaction_bytes(),
+			maptos_rest_max_connections: default_maptos_rest_max_connections(),
+			maptos_rest_listen_backlog: default_maptos_rest_listen_backlog(),
+			maptos_rest_log_sample_rate: default_maptos_rest_log_sample_rate(),
+			maptos_rest_worker_threads: default_maptos_rest_worker_threads(),
+			maptos_rest_enabled: default_maptos_rest_enabled(),
+			maptos_expose_version: default_maptos_expose_version(),
+			maptos_preload_enabled: default_maptos_preload_enabled(),
+			maptos_preload_versions: default_maptos_preload_versions(),
+			maptos_shutdown_grace_sec: default_maptos_shutdown_grace_sec(),
+			maptos_stop_at_version: default_maptos_stop_at_version(),
+			maptos_read_only: default_maptos_read_only(),
+			maptos_allow_chain_id_mismatch: default_maptos_allow_chain_id_mismatch(),
+			maptos_gas_unit_price: default_maptos_gas_unit_price(),
+			maptos_clock: default_maptos_clock(),
+			manual_clock: ManualClockHandle::default(),
+			maptos_sync_mode: default_maptos_sync_mode(),
+			maptos_key_scheme: default_maptos_key_scheme(),
+			maptos_feature_flags: default_maptos_feature_flags(),
 			maptos_private_key: default_maptos_private_key(),
+			signer: SignerCache::default(),
+			maptos_enable_pruning: default_maptos_enable_pruning(),
 			maptos_ledger_prune_window: default_maptos_ledger_prune_window(),
 			maptos_epoch_snapshot_prune_window: default_maptos_epoch_snapshot_prune_window(),
 			maptos_state_merkle_prune_window: default_maptos_state_merkle_prune_window(),
+			maptos_data_dir: default_maptos_data_dir(),
 			maptos_db_path: None,
+			maptos_db_block_cache_bytes: default_maptos_db_block_cache_bytes(),
+			maptos_db_max_wal_bytes: default_maptos_db_max_wal_bytes(),
+			maptos_max_db_size_bytes: default_maptos_max_db_size_bytes(),
+			maptos_commit_batch_size: default_maptos_commit_batch_size(),
+			maptos_commit_batch_max_delay_ms: default_maptos_commit_batch_max_delay_ms(),
+			maptos_fsync_policy: default_maptos_fsync_policy(),
+			maptos_execution_concurrency: default_maptos_execution_concurrency(),
+			maptos_genesis_blob_path: default_maptos_genesis_blob_path(),
+			maptos_framework_path: default_maptos_framework_path(),
+		}
+	}
+}
+
+/// A node reports a different chain id than the one configured.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+	"MAPTOS_CHAIN_ID={} but the node reports chain id {}; \
+	set MAPTOS_ALLOW_CHAIN_ID_MISMATCH=true to connect anyway",
+	.configured.id(),
+	.reported.id()
+)]
+pub struct ChainIdMismatch {
+	pub configured: ChainId,
+	pub reported: ChainId,
+}
+
+/// Checks that `reported` matches `configured`, unless mismatches are allowed.
+pub fn check_chain_id(
+	configured: ChainId,
+	reported: ChainId,
+	allow_mismatch: bool,
+) -> Result<(), ChainIdMismatch> {
+	if configured == reported || allow_mismatch {
+		Ok(())
+	} else {
+		Err(ChainIdMismatch { configured, reported })
+	}
+}
+
+impl Config {
+	/// Checks the chain id reported by an upstream node against the configured one.
+	///
+	/// `maptos_allow_chain_id_mismatch` lets a mismatch through.
+	pub fn check_node_chain_id(&self, reported: ChainId) -> Result<(), ChainIdMismatch> {
+		check_chain_id(self.maptos_chain_id, reported, self.maptos_allow_chain_id_mismatch)
+	}
+
+	/// Whether the node has no private key of its own, but the placeholder of
+	/// [`unconfigured_private_key`].
+	pub fn private_key_is_unconfigured(&self) -> bool {
+		self.maptos_private_key == unconfigured_private_key()
+	}
+
+	/// Gives the node a random private key in place of the placeholder, as a local network set
+	/// up for development does, returning whether it did.
+	pub fn generate_private_key_if_unconfigured(&mut self) -> bool {
+		if !self.private_key_is_unconfigured() {
+			return false;
+		}
+		self.maptos_private_key = generate_ephemeral_private_key();
+		true
+	}
+
+	/// The prefix the REST routes are mounted under, normalized without a trailing slash:
+	/// empty to mount them at the root.
+	///
+	/// An invalid prefix, which validation rejects, mounts them at the root.
+	pub fn rest_base_path(&self) -> &str {
+		normalize_base_path(&self.maptos_rest_base_path).unwrap_or_default()
+	}
+
+	pub fn rest_request_timeout(&self) -> Duration {
+		self.maptos_rest_request_timeout_sec.get()
+	}
+
+	/// How long a REST connection may sit idle before it is closed, or `None` to keep idle
+	/// connections open.
+	pub fn rest_idle_timeout(&self) -> Option<Duration> {
+		(!self.maptos_rest_idle_timeout_sec.is_zero())
+			.then(|| self.maptos_rest_idle_timeout_sec.get())
+	}
+
+	/// The REST request body limit, saturating on targets where it exceeds the address space.
+	pub fn rest_max_body_bytes(&self) -> usize {
+		usize::try_from(self.maptos_rest_max_body_bytes).unwrap_or(usize::MAX)
+	}
+
+	/// Whether the storage is pruned to the prune windows, rather than keeping every version.
+	pub fn pruning_enabled(&self) -> bool {
+		self.maptos_enable_pruning
+	}
+
+	/// The encoding of responses to REST requests without an `Accept` header, or accepting any.
+	pub fn rest_default_encoding(&self) -> RestEncoding {
+		self.maptos_rest_default_encoding
+	}
+
+	/// How account addresses are written in REST responses.
+	pub fn rest_address_format(&self) -> AddressFormat {
+		self.maptos_rest_address_format
+	}
+
+	/// Whether `/version` is served alongside the REST API.
+	pub fn expose_version(&self) -> bool {
+		self.maptos_expose_version
+	}
+
+	/// The most gas a submitted transaction may reserve.
+	pub fn rest_max_gas_amount(&self) -> u64 {
+		self.maptos_rest_max_gas_amount
+	}
+
+	/// How far past the present a submitted transaction may expire; later ones are refused.
+	pub fn max_txn_future(&self) -> Duration {
+		self.maptos_max_txn_future_sec.into()
+	}
+
+	/// The page size limit of paginated REST requests, saturating at the largest page the
+	/// Aptos API can serve.
+	pub fn rest_max_page_size(&self) -> u16 {
+		u16::try_from(self.maptos_rest_max_page_size).unwrap_or(u16::MAX)
+	}
+
+	/// The most events a REST response lists for each transaction, or for an events query.
+	pub fn rest_max_events(&self) -> usize {
+		self.maptos_rest_max_events_per_response as usize
+	}
+
+	/// The submitted transaction size limit, saturating on targets where it exceeds the
+	/// address space.
+	pub fn rest_max_transaction_bytes(&self) -> usize {
+		usize::try_from(self.maptos_rest_max_transaction_bytes).unwrap_or(usize::MAX)
+	}
+
+	/// The admission limits of the REST listener.
+	pub fn rest_connection_limits(&self) -> ConnectionLimits {
+		ConnectionLimits::new(self.maptos_rest_max_connections, self.maptos_rest_listen_backlog)
+	}
+
+	/// The worker threads of the REST runtime, one per CPU unless configured.
+	pub fn rest_worker_threads(&self) -> usize {
+		resolve_worker_threads(self.maptos_rest_worker_threads)
+	}
+
+	/// The Aptos database directory: `maptos_db_path` rooted under the data directory,
+	/// or `maptos-db` in the data directory when no path is configured.
+	pub fn db_path(&self) -> PathBuf {
+		self.maptos_data_dir
+			.join(self.maptos_db_path.as_deref().unwrap_or(Path::new("maptos-db")))
+	}
+
+	/// The RocksDB block cache of each Aptos database, in bytes.
+	pub fn db_block_cache_bytes(&self) -> u64 {
+		self.maptos_db_block_cache_bytes
+	}
+
+	/// The size the RocksDB write-ahead log of each Aptos database grows to before its
+	/// memtables are flushed, in bytes.
+	///
+	/// Aptos does not expose the memtable size, so this is what bounds the writes buffered in
+	/// memory.
+	pub fn db_max_wal_bytes(&self) -> u64 {
+		self.maptos_db_max_wal_bytes
+	}
+
+	/// Whether `used` bytes of storage come close enough to the disk cap that pruning is
+	/// forced on, which it never is without a cap.
+	pub fn db_size_near_cap(&self, used: u64) -> bool {
+		self.maptos_max_db_size_bytes != 0
+			&& u128::from(used) * 100
+				>= u128::from(self.maptos_max_db_size_bytes) * u128::from(DB_SIZE_PRUNING_PERCENT)
+	}
+
+	/// The most transactions grouped into one commit batch.
+	pub fn commit_batch_size(&self) -> usize {
+		usize::try_from(self.maptos_commit_batch_size).unwrap_or(usize::MAX)
+	}
+
+	/// The longest a partial commit batch is held before it is flushed.
+	pub fn commit_batch_max_delay(&self) -> Duration {
+		Duration::from_millis(self.maptos_commit_batch_max_delay_ms)
+	}
+
+	/// When the commit loop flushes the batch it is collecting.
+	pub fn commit_batch_policy(&self) -> CommitBatchPolicy {
+		CommitBatchPolicy::new(self.commit_batch_size(), self.commit_batch_max_delay())
+	}
+
+	/// When the storage commits are synced to disk.
+	///
+	/// The commits of the DA DB, which records the DA blocks executed, follow it. The Aptos
+	/// storage syncs every commit of its databases, which no write option of the node relaxes.
+	pub fn fsync_policy(&self) -> FsyncPolicy {
+		self.maptos_fsync_policy
+	}
+
+	/// Creates the data directory if it is missing and checks that it is writable.
+	pub fn ensure_data_dir(&self) -> Result<(), anyhow::Error> {
+		ensure_writable_dir(&self.maptos_data_dir)
+	}
+
+	/// The directory of the node's storage, under the data directory.
+	pub fn storage_dir(&self) -> PathBuf {
+		self.maptos_data_dir.join("maptos-storage")
+	}
+
+	/// Fails when the storage is still at [`LEGACY_STORAGE_DIR`], where the node kept it before
+	/// it was rooted under the data directory, and nothing is at [`Config::storage_dir`] yet,
+	/// so that the node does not start over on an empty storage.
+	pub fn ensure_storage_not_moved(&self) -> Result<(), anyhow::Error> {
+		ensure_storage_not_moved(Path::new(LEGACY_STORAGE_DIR), &self.storage_dir())
+	}
+
+	/// How long to drain in-flight work before aborting; zero means stopping immediately.
+	pub fn shutdown_grace(&self) -> Duration {
+		self.maptos_shutdown_grace_sec.get()
+	}
+
+	/// The ledger version at which the node stops once it has committed it.
+	pub fn stop_at_version(&self) -> Option<u64> {
+		self.maptos_stop_at_version
+	}
+
+	/// The gas unit price of transactions the node builds itself, or `None` to estimate it.
+	pub fn gas_unit_price(&self) -> Option<u64> {
+		Some(self.maptos_gas_unit_price).filter(|price| *price > 0)
+	}
+}
+
+/// The share of the disk cap, in percent, the storage may fill before pruning is forced on.
+pub const DB_SIZE_PRUNING_PERCENT: u8 = 90;
+
+/// Where the node kept its storage, relative to its working directory, before
+/// `MAPTOS_DATA_DIR` rooted it.
+pub const LEGACY_STORAGE_DIR: &str = "./.movement/maptos-storage";
+
+fn ensure_storage_not_moved(legacy: &Path, storage_dir: &Path) -> Result<(), anyhow::Error> {
+	if storage_dir.exists() || !legacy.is_dir() {
+		return Ok(());
+	}
+	anyhow::bail!(
+		"the node storage is at {} but MAPTOS_DATA_DIR roots it at {}; move it there, \
+		 or set MAPTOS_DATA_DIR to {} to keep it in place",
+		legacy.display(),
+		storage_dir.display(),
+		legacy.parent().unwrap_or(legacy).display()
+	)
+}
+
+/// Creates `dir` and its parents if missing, then writes and removes a probe file in it.
+fn ensure_writable_dir(dir: &Path) -> Result<(), anyhow::Error> {
+	if dir.as_os_str().is_empty() {
+		anyhow::bail!("MAPTOS_DATA_DIR is empty");
+	}
+	std::fs::create_dir_all(dir)
+		.with_context(|| format!("failed to create the data directory {}", dir.display()))?;
+	let probe = dir.join(".maptos-write-check");
+	std::fs::write(&probe, b"")
+		.with_context(|| format!("the data directory {} is not writable", dir.display()))?;
+	std::fs::remove_file(&probe)
+		.with_context(|| format!("the data directory {} is not writable", dir.display()))
+}
+
+impl super::Config {
+	/// Creates the data directory if it is missing and checks that it is writable.
+	pub fn ensure_data_dir(&self) -> Result<(), anyhow::Error> {
+		self.chain.ensure_data_dir()
+	}
+
+	/// How many of the latest committed versions have their state preloaded before the REST
+	/// server accepts connections, or `None` when nothing is preloaded.
+	pub fn preload(&self) -> Option<u64> {
+		self.chain
+			.maptos_preload_enabled
+			.then_some(self.chain.maptos_preload_versions.max(1))
+	}
+
+	/// The disk the Aptos databases may use, or `None` when it is unlimited.
+	pub fn max_db_size(&self) -> Option<NonZeroU64> {
+		NonZeroU64::new(self.chain.maptos_max_db_size_bytes)
+	}
+
+	/// Whether the node serves reads only: transaction submissions are refused
+	/// and the faucet does not start.
+	pub fn is_read_only(&self) -> bool {
+		self.chain.maptos_read_only
+	}
+
+	/// The threads executing each block in parallel, one per CPU unless configured.
+	pub fn execution_concurrency(&self) -> usize {
+		resolve_worker_threads(self.chain.maptos_execution_concurrency)
+	}
+
+	/// The chain id as the number transactions are signed with.
+	pub fn chain_id_u8(&self) -> u8 {
+		self.chain.maptos_chain_id.id()
+	}
+
+	/// The name the chain id is known by, like `mainnet` or `local`, or `custom` if it has none.
+	pub fn chain_id_name(&self) -> &'static str {
+		super::common::chain_id_name(self.chain_id_u8())
+	}
+}
+
+/// How a node catches up with the chain when it starts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SyncMode {
+	/// Resumes from the last DA height the node has synced.
+	Fast,
+	/// Clears the node's databases and executes the DA again from its first height.
+	///
+	/// The databases are cleared on the first start only. `full-sync-started` in the data
+	/// directory records it, and is deleted to start the replay over.
+	Full,
+}
+
+impl SyncMode {
+	pub const ACCEPTED: &'static [&'static str] = &["fast", "full"];
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			SyncMode::Fast => "fast",
+			SyncMode::Full => "full",
+		}
+	}
+}
+
+impl FromStr for SyncMode {
+	type Err = anyhow::Error;
+
+	fn from_str(raw: &str) -> Result<Self, Self::Err> {
+		match raw.trim().to_ascii_lowercase().as_str() {
+			"fast" => Ok(SyncMode::Fast),
+			"full" => Ok(SyncMode::Full),
+			_ => anyhow::bail!(
+				"unknown sync mode {:?}, expected one of: {}",
+				raw,
+				Self::ACCEPTED.join(", ")
+			),
 		}
 	}
 }
+
+impl fmt::Display for SyncMode {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+/// How the Aptos REST server encodes responses to requests which do not ask for an encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RestEncoding {
+	Json,
+	Bcs,
+}
+
+impl RestEncoding {
+	pub const ACCEPTED: &'static [&'static str] = &["json", "bcs"];
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			RestEncoding::Json => "json",
+			RestEncoding::Bcs => "bcs",
+		}
+	}
+
+	/// The media type a request accepts to be answered in this encoding.
+	pub fn media_type(&self) -> &'static str {
+		match self {
+			RestEncoding::Json => "application/json",
+			RestEncoding::Bcs => "application/x-bcs",
+		}
+	}
+}
+
+impl FromStr for RestEncoding {
+	type Err = anyhow::Error;
+
+	fn from_str(raw: &str) -> Result<Self, Self::Err> {
+		match raw.trim().to_ascii_lowercase().as_str() {
+			"json" => Ok(RestEncoding::Json),
+			"bcs" => Ok(RestEncoding::Bcs),
+			_ => anyhow::bail!(
+				"unknown REST encoding {:?}, expected one of: {}",
+				raw,
+				Self::ACCEPTED.join(", ")
+			),
+		}
+	}
+}
+
+impl fmt::Display for RestEncoding {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+/// How the Aptos REST server writes account addresses.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AddressFormat {
+	/// Without leading zeros, such as `0x1`.
+	Short,
+	/// With all 64 hex digits, zero-padded.
+	Long,
+}
+
+impl AddressFormat {
+	pub const ACCEPTED: &'static [&'static str] = &["short", "long"];
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			AddressFormat::Short => "short",
+			AddressFormat::Long => "long",
+		}
+	}
+
+	pub fn render(&self, address: &AccountAddress) -> String {
+		match self {
+			AddressFormat::Short => address.to_hex_literal(),
+			AddressFormat::Long => format!("0x{}", address.to_hex()),
+		}
+	}
+
+	/// `raw` written in this format, or `None` when it is not a `0x`-prefixed address.
+	pub fn reformat(&self, raw: &str) -> Option<String> {
+		if !raw.starts_with("0x") {
+			return None;
+		}
+		AccountAddress::from_hex_literal(raw).ok().map(|address| self.render(&address))
+	}
+}
+
+impl FromStr for AddressFormat {
+	type Err = anyhow::Error;
+
+	fn from_str(raw: &str) -> Result<Self, Self::Err> {
+		match raw.trim().to_ascii_lowercase().as_str() {
+			"short" => Ok(AddressFormat::Short),
+			"long" => Ok(AddressFormat::Long),
+			_ => anyhow::bail!(
+				"unknown address format {:?}, expected one of: {}",
+				raw,
+				Self::ACCEPTED.join(", ")
+			),
+		}
+	}
+}
+
+impl fmt::Display for AddressFormat {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_rest_encodings_parse() {
+		assert_eq!("json".parse::<RestEncoding>().unwrap(), RestEncoding::Json);
+		assert_eq!(" BCS ".parse::<RestEncoding>().unwrap(), RestEncoding::Bcs);
+		assert!("protobuf".parse::<RestEncoding>().is_err());
+		assert_eq!(RestEncoding::Bcs.media_type(), "application/x-bcs");
+	}
+
+	#[test]
+	fn test_address_formats_render_a_known_address() {
+		let address = AccountAddress::from_hex_literal("0x1").unwrap();
+		assert_eq!(AddressFormat::Short.render(&address), "0x1");
+		assert_eq!(
+			AddressFormat::Long.render(&address),
+			"0x0000000000000000000000000000000000000000000000000000000000000001"
+		);
+		assert_eq!(
+			AddressFormat::Short
+				.reformat("0x000000000000000000000000000000000000000000000000000000000000cafe"),
+			Some("0xcafe".to_string())
+		);
+		assert_eq!(AddressFormat::Long.reformat("cafe"), None);
+		assert_eq!(" LONG ".parse::<AddressFormat>().unwrap(), AddressFormat::Long);
+		let error = "padded".parse::<AddressFormat>().unwrap_err();
+		assert!(error.to_string().contains("short, long"));
+	}
+
+	#[test]
+	fn test_sync_modes_parse() {
+		let _env = godfig::env::read_lock();
+		assert_eq!("fast".parse::<SyncMode>().unwrap(), SyncMode::Fast);
+		assert_eq!(" FULL ".parse::<SyncMode>().unwrap(), SyncMode::Full);
+		let error = "snapshot".parse::<SyncMode>().unwrap_err();
+		assert!(error.to_string().contains("expected one of: fast, full"), "{}", error);
+		assert_eq!(Config::default().maptos_sync_mode, SyncMode::Fast);
+	}
+
+	#[test]
+	fn test_read_only_is_read_from_env() {
+		let _env = godfig::env::lock();
+		std::env::set_var("MAPTOS_READ_ONLY", "true");
+		assert!(default_maptos_read_only());
+		std::env::set_var("MAPTOS_READ_ONLY", "yes");
+		assert!(!default_maptos_read_only());
+		std::env::remove_var("MAPTOS_READ_ONLY");
+		assert!(!default_maptos_read_only());
+	}
+
+	#[test]
+	fn test_chain_id_mismatch() {
+		assert_eq!(check_chain_id(ChainId::new(27), ChainId::new(27), false), Ok(()));
+
+		let err = check_chain_id(ChainId::new(27), ChainId::new(4), false).unwrap_err();
+		assert_eq!(
+			err,
+			ChainIdMismatch { configured: ChainId::new(27), reported: ChainId::new(4) }
+		);
+		let message = err.to_string();
+		assert!(message.contains("MAPTOS_CHAIN_ID=27"));
+		assert!(message.contains("chain id 4"));
+
+		assert_eq!(check_chain_id(ChainId::new(27), ChainId::new(4), true), Ok(()));
+
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.maptos_chain_id = ChainId::new(27);
+		assert!(config.check_node_chain_id(ChainId::new(4)).is_err());
+		config.maptos_allow_chain_id_mismatch = true;
+		assert_eq!(config.check_node_chain_id(ChainId::new(4)), Ok(()));
+	}
+
+	#[test]
+	fn test_chain_id_names() {
+		let _env = godfig::env::read_lock();
+		let mut config = super::super::Config::default();
+		config.chain.maptos_chain_id = ChainId::new(27);
+		assert_eq!(config.chain_id_u8(), 27);
+		assert_eq!(config.chain_id_name(), "local");
+
+		config.chain.maptos_chain_id = ChainId::new(1);
+		assert_eq!(config.chain_id_name(), "mainnet");
+
+		config.chain.maptos_chain_id = ChainId::new(126);
+		assert_eq!(config.chain_id_u8(), 126);
+		assert_eq!(config.chain_id_name(), "custom");
+	}
+
+	#[test]
+	fn test_ensure_data_dir_creates_the_directory() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::read_lock();
+		let tempdir = tempfile::tempdir()?;
+		let mut config = Config::default();
+		config.maptos_data_dir = tempdir.path().join("nested").join("maptos");
+
+		config.ensure_data_dir()?;
+		assert!(config.maptos_data_dir.is_dir());
+		assert_eq!(std::fs::read_dir(&config.maptos_data_dir)?.count(), 0);
+		// An existing directory is accepted as it is.
+		config.ensure_data_dir()?;
+		Ok(())
+	}
+
+	#[test]
+	fn test_ensure_data_dir_rejects_an_unusable_path() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::read_lock();
+		// A path beneath a regular file can never be created, even with elevated permissions.
+		let file = tempfile::NamedTempFile::new()?;
+		let mut config = Config::default();
+		config.maptos_data_dir = file.path().join("maptos");
+
+		let err = config.ensure_data_dir().unwrap_err();
+		assert!(err.to_string().contains(&config.maptos_data_dir.display().to_string()));
+		Ok(())
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn test_ensure_data_dir_rejects_a_read_only_directory() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::read_lock();
+		use std::os::unix::fs::PermissionsExt;
+
+		let tempdir = tempfile::tempdir()?;
+		std::fs::set_permissions(tempdir.path(), std::fs::Permissions::from_mode(0o555))?;
+		let mut config = Config::default();
+		config.maptos_data_dir = tempdir.path().to_path_buf();
+
+		// Permission bits do not bind a privileged user, so only check when they are enforced.
+		let enforced = std::fs::write(tempdir.path().join("probe"), b"").is_err();
+		let result = config.ensure_data_dir();
+		std::fs::set_permissions(tempdir.path(), std::fs::Permissions::from_mode(0o755))?;
+		if enforced {
+			let err = result.unwrap_err();
+			assert!(err.to_string().contains("is not writable"));
+			assert!(err.to_string().contains(&tempdir.path().display().to_string()));
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn test_storage_left_at_the_legacy_path_is_not_ignored() -> Result<(), anyhow::Error> {
+		let tempdir = tempfile::tempdir()?;
+		let legacy = tempdir.path().join(".movement").join("maptos-storage");
+		let storage_dir = tempdir.path().join("data").join("maptos-storage");
+		// Nothing to move.
+		ensure_storage_not_moved(&legacy, &storage_dir)?;
+
+		std::fs::create_dir_all(&legacy)?;
+		let err = ensure_storage_not_moved(&legacy, &storage_dir).unwrap_err();
+		assert!(err.to_string().contains(&legacy.display().to_string()));
+		assert!(err.to_string().contains(&storage_dir.display().to_string()));
+
+		// Once the storage is at the new path, the old one is left alone.
+		std::fs::create_dir_all(&storage_dir)?;
+		ensure_storage_not_moved(&legacy, &storage_dir)?;
+		Ok(())
+	}
+
+	#[test]
+	fn test_db_path_is_rooted_under_the_data_dir() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.maptos_data_dir = PathBuf::from("/var/lib/maptos");
+		assert_eq!(config.db_path(), PathBuf::from("/var/lib/maptos/maptos-db"));
+
+		config.maptos_db_path = Some(PathBuf::from("db"));
+		assert_eq!(config.db_path(), PathBuf::from("/var/lib/maptos/db"));
+
+		config.maptos_db_path = Some(PathBuf::from("/mnt/db"));
+		assert_eq!(config.db_path(), PathBuf::from("/mnt/db"));
+	}
+
+	#[test]
+	fn test_db_sizes_accept_unit_suffixes() {
+		let _env = godfig::env::lock();
+		std::env::set_var("MAPTOS_DB_BLOCK_CACHE_BYTES", "512MiB");
+		std::env::set_var("MAPTOS_DB_MAX_WAL_BYTES", "64 MB");
+		assert_eq!(default_maptos_db_block_cache_bytes(), 512 * 1024 * 1024);
+		assert_eq!(default_maptos_db_max_wal_bytes(), 64_000_000);
+
+		std::env::set_var("MAPTOS_DB_BLOCK_CACHE_BYTES", "2GiB");
+		std::env::set_var("MAPTOS_DB_MAX_WAL_BYTES", "1048576");
+		assert_eq!(default_maptos_db_block_cache_bytes(), 2 << 30);
+		assert_eq!(default_maptos_db_max_wal_bytes(), 1 << 20);
+
+		// An unparseable size falls back to the built-in default.
+		std::env::set_var("MAPTOS_DB_BLOCK_CACHE_BYTES", "lots");
+		assert_eq!(default_maptos_db_block_cache_bytes(), 8 << 20);
+
+		std::env::remove_var("MAPTOS_DB_BLOCK_CACHE_BYTES");
+		std::env::remove_var("MAPTOS_DB_MAX_WAL_BYTES");
+	}
+
+	#[test]
+	fn test_max_db_size_accepts_unit_suffixes() {
+		let _env = godfig::env::lock();
+		std::env::set_var("MAPTOS_MAX_DB_SIZE_BYTES", "500GiB");
+		let config = super::super::Config::default();
+		assert_eq!(config.max_db_size().map(NonZeroU64::get), Some(500 << 30));
+		assert!(!config.chain.db_size_near_cap(400 << 30));
+		assert!(config.chain.db_size_near_cap(450 << 30));
+
+		std::env::set_var("MAPTOS_MAX_DB_SIZE_BYTES", "1 TB");
+		assert_eq!(default_maptos_max_db_size_bytes(), 1_000_000_000_000);
+		std::env::remove_var("MAPTOS_MAX_DB_SIZE_BYTES");
+	}
+
+	#[test]
+	fn test_zero_max_db_size_is_unlimited() {
+		let _env = godfig::env::read_lock();
+		let mut config = super::super::Config::default();
+		config.chain.maptos_max_db_size_bytes = 0;
+		assert_eq!(config.max_db_size(), None);
+		assert!(!config.chain.db_size_near_cap(u64::MAX));
+	}
+
+	#[test]
+	fn test_transaction_limit_defaults() {
+		let _env = godfig::env::lock();
+		std::env::remove_var("MAPTOS_API_MAX_GAS_AMOUNT");
+		std::env::remove_var("MAPTOS_API_MAX_TRANSACTION_BYTES");
+		assert_eq!(default_maptos_rest_max_gas_amount(), 2_000_000);
+		assert_eq!(default_maptos_rest_max_transaction_bytes(), 64 * 1024);
+
+		std::env::set_var("MAPTOS_API_MAX_GAS_AMOUNT", "100000");
+		std::env::set_var("MAPTOS_API_MAX_TRANSACTION_BYTES", "16KiB");
+		assert_eq!(default_maptos_rest_max_gas_amount(), 100_000);
+		assert_eq!(default_maptos_rest_max_transaction_bytes(), 16 * 1024);
+		std::env::remove_var("MAPTOS_API_MAX_GAS_AMOUNT");
+		std::env::remove_var("MAPTOS_API_MAX_TRANSACTION_BYTES");
+	}
+
+	#[test]
+	fn test_max_page_size_defaults() {
+		let _env = godfig::env::lock();
+		std::env::remove_var("MAPTOS_API_MAX_PAGE_SIZE");
+		assert_eq!(default_maptos_rest_max_page_size(), 1000);
+		assert_eq!(Config::default().rest_max_page_size(), 1000);
+
+		std::env::set_var("MAPTOS_API_MAX_PAGE_SIZE", "250");
+		assert_eq!(default_maptos_rest_max_page_size(), 250);
+		std::env::remove_var("MAPTOS_API_MAX_PAGE_SIZE");
+
+		let config = Config { maptos_rest_max_page_size: 100_000, ..Config::default() };
+		assert_eq!(config.rest_max_page_size(), u16::MAX);
+	}
+
+	#[test]
+	fn test_gas_unit_price_defaults_to_the_estimate() {
+		let _env = godfig::env::lock();
+		std::env::remove_var("MAPTOS_GAS_UNIT_PRICE");
+		assert_eq!(default_maptos_gas_unit_price(), 0);
+		assert_eq!(Config::default().gas_unit_price(), None);
+
+		std::env::set_var("MAPTOS_GAS_UNIT_PRICE", "150");
+		assert_eq!(Config::default().gas_unit_price(), Some(150));
+		std::env::remove_var("MAPTOS_GAS_UNIT_PRICE");
+	}
+
+	#[test]
+	fn test_zero_execution_concurrency_means_one_per_cpu() {
+		let _env = godfig::env::read_lock();
+		let cpus = std::thread::available_parallelism().map_or(1, std::num::NonZeroUsize::get);
+		let mut config = super::super::Config::default();
+		config.chain.maptos_execution_concurrency = 0;
+		assert_eq!(config.execution_concurrency(), cpus);
+
+		config.chain.maptos_execution_concurrency = 8;
+		assert_eq!(config.execution_concurrency(), 8);
+	}
+
+	#[test]
+	fn test_shutdown_grace() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.maptos_shutdown_grace_sec = DurationSecs::from_secs(45);
+		assert_eq!(config.shutdown_grace(), Duration::from_secs(45));
+
+		config.maptos_shutdown_grace_sec = DurationSecs::ZERO;
+		assert!(config.shutdown_grace().is_zero());
+	}
+
+	#[test]
+	fn test_shutdown_grace_is_read_from_env() {
+		let _env = godfig::env::lock();
+		std::env::set_var("MAPTOS_SHUTDOWN_GRACE_SEC", "0");
+		assert_eq!(default_maptos_shutdown_grace_sec(), DurationSecs::ZERO);
+		std::env::set_var("MAPTOS_SHUTDOWN_GRACE_SEC", "2m");
+		assert_eq!(default_maptos_shutdown_grace_sec(), DurationSecs::from_secs(120));
+		std::env::set_var("MAPTOS_SHUTDOWN_GRACE_SEC", "not a number");
+		assert_eq!(default_maptos_shutdown_grace_sec(), DurationSecs::from_secs(30));
+		std::env::remove_var("MAPTOS_SHUTDOWN_GRACE_SEC");
+	}
+
+	#[test]
+	fn test_zero_rest_idle_timeout_keeps_connections_open() {
+		let _env = godfig::env::lock();
+		std::env::set_var("MAPTOS_API_IDLE_TIMEOUT_SEC", "90");
+		assert_eq!(default_maptos_rest_idle_timeout_sec(), DurationSecs::from_secs(90));
+		std::env::set_var("MAPTOS_API_IDLE_TIMEOUT_SEC", "0");
+		let mut config = Config::default();
+		std::env::remove_var("MAPTOS_API_IDLE_TIMEOUT_SEC");
+		assert_eq!(config.rest_idle_timeout(), None);
+
+		config.maptos_rest_idle_timeout_sec = default_maptos_rest_idle_timeout_sec();
+		assert_eq!(config.rest_idle_timeout(), Some(Duration::from_secs(60)));
+	}
+
+	#[test]
+	fn test_preload_is_off_unless_enabled() {
+		let _env = godfig::env::lock();
+		std::env::remove_var("MAPTOS_PRELOAD_ENABLED");
+		assert!(!default_maptos_preload_enabled());
+		assert_eq!(super::super::Config::default().preload(), None);
+
+		std::env::set_var("MAPTOS_PRELOAD_ENABLED", "true");
+		std::env::set_var("MAPTOS_PRELOAD_VERSIONS", "5000");
+		let config = super::super::Config::default();
+		std::env::remove_var("MAPTOS_PRELOAD_ENABLED");
+		std::env::remove_var("MAPTOS_PRELOAD_VERSIONS");
+		assert_eq!(config.preload(), Some(5000));
+	}
+}
@@ -1,9 +1,71 @@
 use super::common::{
-	default_maptos_faucet_rest_connection_hostname, default_maptos_faucet_rest_connection_port,
-	default_maptos_indexer_grpc_connection_hostname, default_maptos_indexer_grpc_connection_port,
+	default_fin_rest_connection_hostname, default_fin_rest_connection_port,
+	default_maptos_dns_refresh_sec, default_maptos_faucet_rest_connection_hostname,
+	default_maptos_faucet_rest_connection_port, default_maptos_indexer_grpc_connection_hostname,
+	default_maptos_indexer_grpc_connection_port, default_maptos_indexer_upstreams,
 	default_maptos_rest_connection_hostname, default_maptos_rest_connection_port,
 };
-use serde::{Deserialize, Serialize};
+use super::duration::DurationSecs;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// An indexer gRPC server to connect to, given as `host:port` with an IPv6 host in brackets.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IndexerUpstream {
+	pub hostname: String,
+	pub port: u16,
+}
+
+impl FromStr for IndexerUpstream {
+	type Err = anyhow::Error;
+
+	fn from_str(raw: &str) -> Result<Self, Self::Err> {
+		let raw = raw.trim();
+		let (hostname, port) =
+			raw.rsplit_once(':').ok_or_else(|| anyhow::anyhow!("expected host:port"))?;
+		let hostname = match hostname.strip_prefix('[') {
+			Some(bracketed) => bracketed
+				.strip_suffix(']')
+				.ok_or_else(|| anyhow::anyhow!("unclosed bracket in the host"))?,
+			None if hostname.contains(':') => {
+				anyhow::bail!("an IPv6 host must be in brackets")
+			}
+			None => hostname,
+		};
+		if hostname.is_empty() {
+			anyhow::bail!("expected host:port");
+		}
+		let port = port
+			.parse::<u16>()
+			.map_err(|_| anyhow::anyhow!("expected a port between 0 and 65535"))?;
+		Ok(IndexerUpstream { hostname: hostname.to_string(), port })
+	}
+}
+
+impl fmt::Display for IndexerUpstream {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.hostname.contains(':') {
+			write!(f, "[{}]:{}", self.hostname, self.port)
+		} else {
+			write!(f, "{}:{}", self.hostname, self.port)
+		}
+	}
+}
+
+impl Serialize for IndexerUpstream {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_str(self)
+	}
+}
+
+impl<'de> Deserialize<'de> for IndexerUpstream {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+	}
+}
 
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Config {
@@ -30,6 +92,23 @@ pub struct Config {
 	/// The port of the Aptos gRPC indexer server
 	#[serde(default = "default_maptos_indexer_grpc_connection_port")]
 	pub maptos_indexer_grpc_connection_port: u16,
+
+	/// The Aptos gRPC indexer servers to fail over between, in order,
+	/// empty for the single connection address
+	#[serde(default = "default_maptos_indexer_upstreams")]
+	pub maptos_indexer_upstreams: Vec<IndexerUpstream>,
+
+	/// The hostname of the fin view server
+	#[serde(default = "default_fin_rest_connection_hostname")]
+	pub fin_rest_connection_hostname: String,
+
+	/// The port of the fin view server
+	#[serde(default = "default_fin_rest_connection_port")]
+	pub fin_rest_connection_port: u16,
+
+	/// How long resolved connection addresses are reused, 0 to resolve on every dial
+	#[serde(default = "default_maptos_dns_refresh_sec")]
+	pub maptos_dns_refresh_sec: DurationSecs,
 }
 
 impl Default for Config {
@@ -43,6 +122,82 @@ impl Default for Config {
 			maptos_indexer_grpc_connection_hostname:
 				default_maptos_indexer_grpc_connection_hostname(),
 			maptos_indexer_grpc_connection_port: default_maptos_indexer_grpc_connection_port(),
+			maptos_indexer_upstreams: default_maptos_indexer_upstreams(),
+			fin_rest_connection_hostname: default_fin_rest_connection_hostname(),
+			fin_rest_connection_port: default_fin_rest_connection_port(),
+			maptos_dns_refresh_sec: default_maptos_dns_refresh_sec(),
+		}
+	}
+}
+
+impl Config {
+	/// How long the addresses a connection hostname resolves to are reused.
+	pub fn dns_refresh(&self) -> Duration {
+		self.maptos_dns_refresh_sec.get()
+	}
+}
+
+impl super::Config {
+	/// The indexer gRPC servers to connect to, in the order to fail over between them:
+	/// those listed by `MAPTOS_INDEXER_UPSTREAMS`, or else the single connection address.
+	pub fn indexer_upstreams(&self) -> Vec<IndexerUpstream> {
+		if !self.client.maptos_indexer_upstreams.is_empty() {
+			return self.client.maptos_indexer_upstreams.clone();
+		}
+		vec![IndexerUpstream {
+			hostname: self.client.maptos_indexer_grpc_connection_hostname.clone(),
+			port: self.client.maptos_indexer_grpc_connection_port,
+		}]
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::super::common::parse_indexer_upstreams;
+	use super::super::error::ConfigError;
+	use super::*;
+
+	#[test]
+	fn test_several_upstreams_parse_in_order() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::read_lock();
+		let upstreams = parse_indexer_upstreams(
+			"MAPTOS_INDEXER_UPSTREAMS",
+			" indexer-a.example:30734, 10.0.0.2:443,,[::1]:30734 ",
+		)?;
+		let listed: Vec<String> = upstreams.iter().map(ToString::to_string).collect();
+		assert_eq!(listed, ["indexer-a.example:30734", "10.0.0.2:443", "[::1]:30734"]);
+		assert_eq!(upstreams[2].hostname, "::1");
+
+		let mut config = super::super::Config::default();
+		config.client.maptos_indexer_upstreams = upstreams.clone();
+		assert_eq!(config.indexer_upstreams(), upstreams);
+		Ok(())
+	}
+
+	#[test]
+	fn test_no_upstreams_fall_back_to_the_connection_address() {
+		let _env = godfig::env::read_lock();
+		let mut config = super::super::Config::default();
+		config.client.maptos_indexer_upstreams = Vec::new();
+		config.client.maptos_indexer_grpc_connection_hostname = "indexer.example".to_string();
+		config.client.maptos_indexer_grpc_connection_port = 443;
+		assert_eq!(
+			config.indexer_upstreams(),
+			[IndexerUpstream { hostname: "indexer.example".to_string(), port: 443 }]
+		);
+	}
+
+	#[test]
+	fn test_invalid_upstream_is_named() {
+		for entry in ["indexer.example", "indexer.example:http", ":30734", "::1:30734"] {
+			let raw = format!("10.0.0.2:443,{}", entry);
+			match parse_indexer_upstreams("MAPTOS_INDEXER_UPSTREAMS", &raw) {
+				Err(ConfigError::InvalidAddress { var, entry: named, .. }) => {
+					assert_eq!(var, "MAPTOS_INDEXER_UPSTREAMS");
+					assert_eq!(named, entry);
+				}
+				other => panic!("{:?} parsed as {:?}", entry, other),
+			}
 		}
 	}
 }
@@ -0,0 +1,188 @@
+//! The clock the node reads the time from, which tests may stop and advance by hand.
+
+use super::chain;
+
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::{Arc, Mutex, OnceLock, PoisonError};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// A source of the current time.
+pub trait Clock: Send + Sync {
+	fn now(&self) -> SystemTime;
+
+	/// The current time in microseconds since the Unix epoch, as block timestamps are written.
+	fn now_micros(&self) -> u64 {
+		self.now()
+			.duration_since(UNIX_EPOCH)
+			.map_or(0, |since| since.as_micros() as u64)
+	}
+}
+
+/// Reads the wall clock.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+	fn now(&self) -> SystemTime {
+		SystemTime::now()
+	}
+}
+
+/// A clock which only moves when it is advanced or set.
+#[derive(Debug)]
+pub struct ManualClock(Mutex<SystemTime>);
+
+impl ManualClock {
+	pub fn new(now: SystemTime) -> Self {
+		Self(Mutex::new(now))
+	}
+
+	/// Moves the clock forward by `by`.
+	pub fn advance(&self, by: Duration) {
+		*self.time() += by;
+	}
+
+	/// Moves the clock to `now`, which may be in its past.
+	pub fn set(&self, now: SystemTime) {
+		*self.time() = now;
+	}
+
+	fn time(&self) -> std::sync::MutexGuard<'_, SystemTime> {
+		self.0.lock().unwrap_or_else(PoisonError::into_inner)
+	}
+}
+
+impl Clock for ManualClock {
+	fn now(&self) -> SystemTime {
+		*self.time()
+	}
+}
+
+/// Which clock the node runs on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClockKind {
+	/// The wall clock.
+	System,
+	/// A [`ManualClock`] starting at the time the node first reads it.
+	Manual,
+}
+
+impl ClockKind {
+	pub const ACCEPTED: &'static [&'static str] = &["system", "manual"];
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			ClockKind::System => "system",
+			ClockKind::Manual => "manual",
+		}
+	}
+}
+
+impl FromStr for ClockKind {
+	type Err = anyhow::Error;
+
+	fn from_str(raw: &str) -> Result<Self, Self::Err> {
+		match raw.trim().to_ascii_lowercase().as_str() {
+			"system" => Ok(ClockKind::System),
+			"manual" => Ok(ClockKind::Manual),
+			_ => anyhow::bail!(
+				"unknown clock {:?}, expected one of: {}",
+				raw,
+				Self::ACCEPTED.join(", ")
+			),
+		}
+	}
+}
+
+impl fmt::Display for ClockKind {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+/// The manual clock of a config, shared by its clones so that advancing it moves every
+/// part of the node reading the time from one of them.
+///
+/// The clock starts when it is first read. Handles compare equal when they share the clock,
+/// or when neither was started, as is the case for configs fresh from loading.
+#[derive(Debug, Clone, Default)]
+pub struct ManualClockHandle(Arc<OnceLock<Arc<ManualClock>>>);
+
+impl ManualClockHandle {
+	fn clock(&self) -> Arc<ManualClock> {
+		self.0.get_or_init(|| Arc::new(ManualClock::new(SystemTime::now()))).clone()
+	}
+}
+
+impl PartialEq for ManualClockHandle {
+	fn eq(&self, other: &Self) -> bool {
+		Arc::ptr_eq(&self.0, &other.0) || (self.0.get().is_none() && other.0.get().is_none())
+	}
+}
+
+impl Eq for ManualClockHandle {}
+
+impl chain::Config {
+	/// Which clock the node runs on.
+	pub fn clock_kind(&self) -> ClockKind {
+		self.maptos_clock
+	}
+
+	/// The clock the node reads the time from.
+	pub fn clock(&self) -> Arc<dyn Clock> {
+		match self.maptos_clock {
+			ClockKind::System => Arc::new(SystemClock),
+			ClockKind::Manual => self.manual_clock.clock(),
+		}
+	}
+
+	/// The clock to advance when the node runs on a manual one.
+	pub fn manual_clock(&self) -> Option<Arc<ManualClock>> {
+		match self.maptos_clock {
+			ClockKind::System => None,
+			ClockKind::Manual => Some(self.manual_clock.clock()),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_clock_kinds_parse() {
+		assert_eq!("system".parse::<ClockKind>().unwrap(), ClockKind::System);
+		assert_eq!(" Manual ".parse::<ClockKind>().unwrap(), ClockKind::Manual);
+		assert!("frozen".parse::<ClockKind>().is_err());
+	}
+
+	#[test]
+	fn test_manual_clock_is_shared_by_clones() {
+		let config = chain::Config { maptos_clock: ClockKind::Manual, ..Default::default() };
+		let clone = config.clone();
+		let start = config.clock().now();
+
+		config.manual_clock().unwrap().advance(Duration::from_secs(30));
+		assert_eq!(clone.clock().now(), start + Duration::from_secs(30));
+
+		let config = chain::Config { maptos_clock: ClockKind::System, ..config };
+		assert!(config.manual_clock().is_none());
+	}
+
+	#[test]
+	fn test_started_manual_clocks_compare_by_identity() {
+		let config = chain::Config { maptos_clock: ClockKind::Manual, ..Default::default() };
+		let other = chain::Config { maptos_clock: ClockKind::Manual, ..Default::default() };
+		assert_eq!(config, other);
+
+		let clone = config.clone();
+		let micros = config.clock().now_micros();
+		assert_eq!(config, clone);
+		assert_ne!(config, other);
+		assert_eq!(clone.clock().now_micros(), micros);
+	}
+}
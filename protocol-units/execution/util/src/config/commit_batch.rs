@@ -0,0 +1,155 @@
+//! Grouping transactions into batches which are flushed on size or age, and how durably
+//! the DA DB records the blocks executed.
+
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// When a batch of transactions being collected is flushed: once it holds `max_size`
+/// transactions, or once `max_delay` has passed since it was started, whichever is first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CommitBatchPolicy {
+	/// The most transactions in a batch; 0 counts as 1
+	pub max_size: usize,
+	/// The longest a partial batch is held before it is flushed
+	pub max_delay: Duration,
+}
+
+impl CommitBatchPolicy {
+	pub fn new(max_size: usize, max_delay: Duration) -> Self {
+		Self { max_size, max_delay }
+	}
+
+	/// This policy with its delay shortened to at most `cap`.
+	pub fn with_delay_cap(self, cap: Duration) -> Self {
+		Self { max_delay: self.max_delay.min(cap), ..self }
+	}
+
+	/// Whether a batch of `len` transactions is full.
+	pub fn is_full(&self, len: usize) -> bool {
+		len >= self.max_size.max(1)
+	}
+
+	/// The time left to collect a batch started `elapsed` ago, or `None` once it is due.
+	pub fn remaining(&self, elapsed: Duration) -> Option<Duration> {
+		self.max_delay.checked_sub(elapsed).filter(|remaining| !remaining.is_zero())
+	}
+
+	/// Whether a batch of `len` transactions started `elapsed` ago is flushed now.
+	///
+	/// An empty batch is never flushed, however long it has waited.
+	pub fn should_flush(&self, len: usize, elapsed: Duration) -> bool {
+		len > 0 && (self.is_full(len) || self.remaining(elapsed).is_none())
+	}
+}
+
+/// When the storage commits are synced to disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FsyncPolicy {
+	/// Every write is synced before it is acknowledged.
+	Always,
+	/// The write completing a batch is synced, which makes the writes before it durable too:
+	/// the DA DB is synced once per executed block.
+	Batch,
+	/// Nothing is synced; a crash of the host may lose writes the OS has not flushed yet.
+	Never,
+}
+
+impl FsyncPolicy {
+	pub const ACCEPTED: &'static [&'static str] = &["always", "batch", "never"];
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			FsyncPolicy::Always => "always",
+			FsyncPolicy::Batch => "batch",
+			FsyncPolicy::Never => "never",
+		}
+	}
+
+	/// Whether a write is synced, given whether it completes a batch.
+	pub fn syncs(&self, completes_batch: bool) -> bool {
+		match self {
+			FsyncPolicy::Always => true,
+			FsyncPolicy::Batch => completes_batch,
+			FsyncPolicy::Never => false,
+		}
+	}
+}
+
+impl FromStr for FsyncPolicy {
+	type Err = anyhow::Error;
+
+	fn from_str(raw: &str) -> Result<Self, Self::Err> {
+		match raw.trim().to_ascii_lowercase().as_str() {
+			"always" => Ok(FsyncPolicy::Always),
+			"batch" => Ok(FsyncPolicy::Batch),
+			"never" => Ok(FsyncPolicy::Never),
+			_ => anyhow::bail!(
+				"unknown fsync policy {:?}, expected one of: {}",
+				raw,
+				Self::ACCEPTED.join(", ")
+			),
+		}
+	}
+}
+
+impl fmt::Display for FsyncPolicy {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_flushes_on_size() {
+		let policy = CommitBatchPolicy::new(3, Duration::from_secs(1));
+		assert!(!policy.should_flush(1, Duration::ZERO));
+		assert!(!policy.should_flush(2, Duration::from_millis(999)));
+		assert!(policy.should_flush(3, Duration::ZERO));
+		assert!(policy.should_flush(4, Duration::ZERO));
+
+		// A size of 0 flushes every transaction on its own rather than never.
+		let policy = CommitBatchPolicy::new(0, Duration::from_secs(1));
+		assert!(policy.should_flush(1, Duration::ZERO));
+	}
+
+	#[test]
+	fn test_flushes_partial_batches_on_timeout() {
+		let policy = CommitBatchPolicy::new(100, Duration::from_millis(500));
+		assert_eq!(policy.remaining(Duration::from_millis(200)), Some(Duration::from_millis(300)));
+		assert!(!policy.should_flush(1, Duration::from_millis(499)));
+		assert!(policy.should_flush(1, Duration::from_millis(500)));
+		assert!(policy.should_flush(1, Duration::from_secs(60)));
+		assert_eq!(policy.remaining(Duration::from_millis(500)), None);
+
+		// Nothing is flushed while no transaction has arrived.
+		assert!(!policy.should_flush(0, Duration::from_secs(60)));
+	}
+
+	#[test]
+	fn test_delay_cap_only_shortens() {
+		let policy = CommitBatchPolicy::new(100, Duration::from_millis(500));
+		assert_eq!(policy.with_delay_cap(Duration::from_millis(200)).max_delay.as_millis(), 200);
+		assert_eq!(policy.with_delay_cap(Duration::from_secs(5)), policy);
+	}
+
+	#[test]
+	fn test_fsync_policies_parse() {
+		assert_eq!("always".parse::<FsyncPolicy>().unwrap(), FsyncPolicy::Always);
+		assert_eq!(" Batch ".parse::<FsyncPolicy>().unwrap(), FsyncPolicy::Batch);
+		assert_eq!("NEVER".parse::<FsyncPolicy>().unwrap(), FsyncPolicy::Never);
+		let err = "sometimes".parse::<FsyncPolicy>().unwrap_err().to_string();
+		assert!(err.contains("always, batch, never"), "{}", err);
+
+		assert!(FsyncPolicy::Always.syncs(false));
+		assert!(!FsyncPolicy::Batch.syncs(false));
+		assert!(FsyncPolicy::Batch.syncs(true));
+		assert!(!FsyncPolicy::Never.syncs(true));
+	}
+}
@@ -0,0 +1,68 @@
+//! Cross-origin access to the HTTP APIs.
+
+use super::Config;
+
+/// The origins allowed to read responses from an HTTP API.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CorsOrigins {
+	/// No CORS headers are sent.
+	Disabled,
+	/// Every origin is allowed.
+	Any,
+	/// Only the listed origins are allowed.
+	List(Vec<String>),
+}
+
+impl CorsOrigins {
+	pub fn from_allowed(origins: &[String]) -> Self {
+		if origins.is_empty() {
+			CorsOrigins::Disabled
+		} else if origins.iter().any(|origin| origin == "*") {
+			CorsOrigins::Any
+		} else {
+			CorsOrigins::List(origins.to_vec())
+		}
+	}
+}
+
+/// Splits a comma-separated list of origins, trimming whitespace and dropping empty entries.
+pub fn parse_allowed_origins(raw: &str) -> Vec<String> {
+	raw.split(',')
+		.map(str::trim)
+		.filter(|origin| !origin.is_empty())
+		.map(str::to_string)
+		.collect()
+}
+
+impl Config {
+	pub fn rest_cors_origins(&self) -> CorsOrigins {
+		CorsOrigins::from_allowed(&self.chain.maptos_rest_cors_allowed_origins)
+	}
+
+	pub fn faucet_cors_origins(&self) -> CorsOrigins {
+		CorsOrigins::from_allowed(&self.faucet.maptos_faucet_rest_cors_allowed_origins)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_origins_are_trimmed() {
+		assert_eq!(
+			parse_allowed_origins(" https://a.example , https://b.example,,"),
+			vec!["https://a.example".to_string(), "https://b.example".to_string()]
+		);
+	}
+
+	#[test]
+	fn test_empty_and_wildcard() {
+		assert_eq!(CorsOrigins::from_allowed(&parse_allowed_origins("")), CorsOrigins::Disabled);
+		assert_eq!(CorsOrigins::from_allowed(&parse_allowed_origins(" * ")), CorsOrigins::Any);
+		assert_eq!(
+			CorsOrigins::from_allowed(&parse_allowed_origins("https://a.example")),
+			CorsOrigins::List(vec!["https://a.example".to_string()])
+		);
+	}
+}
@@ -0,0 +1,299 @@
+//! Field-by-field comparison of two resolved configs.
+
+use super::Config;
+
+use serde_json::Value;
+
+use std::collections::BTreeMap;
+
+/// The env var backing each config field, keyed by the field's path in the serialized config.
+///
+/// Fields without an env var are named by their path.
+pub(crate) const FIELD_ENV_VARS: &[(&str, &str)] = &[
+	("chain.maptos_chain_id", "MAPTOS_CHAIN_ID"),
+	("chain.maptos_rest_listen_hostname", "MAPTOS_API_LISTEN_HOSTNAME"),
+	("chain.maptos_rest_listen_port", "MAPTOS_API_LISTEN_PORT"),
+	("chain.maptos_rest_tls_cert_path", "MAPTOS_API_TLS_CERT"),
+	("chain.maptos_rest_tls_key_path", "MAPTOS_API_TLS_KEY"),
+	("chain.maptos_rest_cors_allowed_origins", "MAPTOS_API_CORS_ORIGINS"),
+	("chain.maptos_rest_base_path", "MAPTOS_API_BASE_PATH"),
+	("chain.maptos_rest_method_allowlist", "MAPTOS_API_METHOD_ALLOWLIST"),
+	("chain.maptos_rest_scoped_tokens", "MAPTOS_REST_SCOPED_TOKENS"),
+	("chain.maptos_rest_default_encoding", "MAPTOS_API_DEFAULT_ENCODING"),
+	("chain.maptos_rest_address_format", "MAPTOS_API_ADDRESS_FORMAT"),
+	("chain.maptos_rest_request_timeout_sec", "MAPTOS_API_REQUEST_TIMEOUT_SEC"),
+	("chain.maptos_rest_idle_timeout_sec", "MAPTOS_API_IDLE_TIMEOUT_SEC"),
+	("chain.maptos_rest_max_body_bytes", "MAPTOS_API_MAX_BODY_BYTES"),
+	("chain.maptos_rest_max_gas_amount", "MAPTOS_API_MAX_GAS_AMOUNT"),
+	("chain.maptos_max_txn_future_sec", "MAPTOS_MAX_TXN_FUTURE_SEC"),
+	("chain.maptos_rest_max_page_size", "MAPTOS_API_MAX_PAGE_SIZE"),
+	("chain.maptos_rest_max_events_per_response", "MAPTOS_API_MAX_EVENTS_PER_RESPONSE"),
+	("chain.maptos_rest_max_transaction_bytes", "MAPTOS_API_MAX_TRANSACTION_BYTES"),
+	("chain.maptos_rest_max_connections", "MAPTOS_API_MAX_CONNECTIONS"),
+	("chain.maptos_rest_listen_backlog", "MAPTOS_API_LISTEN_BACKLOG"),
+	("chain.maptos_rest_log_sample_rate", "MAPTOS_API_LOG_SAMPLE_RATE"),
+	("chain.maptos_rest_worker_threads", "MAPTOS_API_WORKER_THREADS"),
+	("chain.maptos_rest_enabled", "MAPTOS_REST_ENABLED"),
+	("chain.maptos_expose_version", "MAPTOS_EXPOSE_VERSION"),
+	("chain.maptos_preload_enabled", "MAPTOS_PRELOAD_ENABLED"),
+	("chain.maptos_preload_versions", "MAPTOS_PRELOAD_VERSIONS"),
+	("chain.maptos_shutdown_grace_sec", "MAPTOS_SHUTDOWN_GRACE_SEC"),
+	("chain.maptos_stop_at_version", "MAPTOS_STOP_AT_VERSION"),
+	("chain.maptos_read_only", "MAPTOS_READ_ONLY"),
+	("chain.maptos_allow_chain_id_mismatch", "MAPTOS_ALLOW_CHAIN_ID_MISMATCH"),
+	("chain.maptos_gas_unit_price", "MAPTOS_GAS_UNIT_PRICE"),
+	("chain.maptos_clock", "MAPTOS_CLOCK"),
+	("chain.maptos_sync_mode", "MAPTOS_SYNC_MODE"),
+	("chain.maptos_key_scheme", "MAPTOS_KEY_SCHEME"),
+	("chain.maptos_feature_flags", "MAPTOS_FEATURE_FLAGS"),
+	("chain.maptos_private_key", "MAPTOS_PRIVATE_KEY"),
+	("chain.maptos_enable_pruning", "MAPTOS_ENABLE_PRUNING"),
+	("chain.maptos_ledger_prune_window", "MAPTOS_LEDGER_PRUNING_WINDOW"),
+	("chain.maptos_epoch_snapshot_prune_window", "MAPTOS_EPOCH_SNAPSHOT_PRUNING_WINDOW"),
+	("chain.maptos_state_merkle_prune_window", "MAPTOS_STATE_MERKLE_PRUNING_WINDOW"),
+	("chain.maptos_data_dir", "MAPTOS_DATA_DIR"),
+	("chain.maptos_db_block_cache_bytes", "MAPTOS_DB_BLOCK_CACHE_BYTES"),
+	("chain.maptos_db_max_wal_bytes", "MAPTOS_DB_MAX_WAL_BYTES"),
+	("chain.maptos_max_db_size_bytes", "MAPTOS_MAX_DB_SIZE_BYTES"),
+	("chain.maptos_commit_batch_size", "MAPTOS_COMMIT_BATCH_SIZE"),
+	("chain.maptos_commit_batch_max_delay_ms", "MAPTOS_COMMIT_BATCH_MAX_DELAY_MS"),
+	("chain.maptos_fsync_policy", "MAPTOS_FSYNC_POLICY"),
+	("chain.maptos_execution_concurrency", "MAPTOS_EXECUTION_CONCURRENCY"),
+	("chain.maptos_genesis_blob_path", "MAPTOS_GENESIS_BLOB_PATH"),
+	("chain.maptos_framework_path", "MAPTOS_FRAMEWORK_PATH"),
+	("indexer.maptos_indexer_grpc_listen_hostname", "MAPTOS_INDEXER_GRPC_LISTEN_HOSTNAME"),
+	("indexer.maptos_indexer_grpc_listen_port", "MAPTOS_INDEXER_GRPC_LISTEN_PORT"),
+	(
+		"indexer.maptos_indexer_grpc_inactivity_timeout",
+		"MAPTOS_INDEXER_GRPC_INACTIVITY_TIMEOUT_SEC",
+	),
+	(
+		"indexer.maptos_indexer_grpc_inactivity_ping_interval",
+		"MAPTOS_INDEXER_GRPC_PING_INTERVAL_SEC",
+	),
+	("indexer.maptos_indexer_grpc_max_message_bytes", "MAPTOS_INDEXER_GRPC_MAX_MESSAGE_BYTES"),
+	("indexer.maptos_indexer_grpc_tcp_keepalive_sec", "MAPTOS_INDEXER_GRPC_TCP_KEEPALIVE_SEC"),
+	("indexer.maptos_indexer_grpc_http2_keepalive_sec", "MAPTOS_INDEXER_GRPC_HTTP2_KEEPALIVE_SEC"),
+	("indexer.maptos_indexer_grpc_tls_cert", "MAPTOS_INDEXER_GRPC_TLS_CERT"),
+	("indexer.maptos_indexer_grpc_tls_key", "MAPTOS_INDEXER_GRPC_TLS_KEY"),
+	("indexer.maptos_indexer_grpc_client_ca", "MAPTOS_INDEXER_GRPC_CLIENT_CA"),
+	("indexer.maptos_indexer_grpc_loopback_port", "MAPTOS_INDEXER_GRPC_LOOPBACK_PORT"),
+	("indexer.maptos_indexer_worker_threads", "MAPTOS_INDEXER_WORKER_THREADS"),
+	("indexer.maptos_indexer_grpc_enabled", "MAPTOS_INDEXER_GRPC_ENABLED"),
+	("indexer.maptos_indexer_max_lag_versions", "MAPTOS_INDEXER_MAX_LAG_VERSIONS"),
+	("indexer.maptos_indexer_grpc_batch_size", "MAPTOS_INDEXER_GRPC_BATCH_SIZE"),
+	("indexer.maptos_indexer_grpc_send_buffer_size", "MAPTOS_INDEXER_GRPC_SEND_BUFFER_SIZE"),
+	("indexer.maptos_indexer_grpc_compression", "MAPTOS_INDEXER_GRPC_COMPRESSION"),
+	(
+		"indexer_processor.postgres_connection_string",
+		"INDEXER_PROCESSOR_POSTGRES_CONNECTION_STRING",
+	),
+	("indexer_processor.postgres_max_connections", "INDEXER_PROCESSOR_POSTGRES_MAX_CONNECTIONS"),
+	(
+		"indexer_processor.postgres_connection_timeout_sec",
+		"INDEXER_PROCESSOR_POSTGRES_CONNECTION_TIMEOUT_SEC",
+	),
+	("indexer_processor.postgres_connect_retries", "INDEXER_PROCESSOR_POSTGRES_CONNECT_RETRIES"),
+	(
+		"indexer_processor.postgres_connect_retry_delay_ms",
+		"INDEXER_PROCESSOR_POSTGRES_CONNECT_RETRY_DELAY_MS",
+	),
+	("indexer_processor.indexer_processor_auth_token", "INDEXER_PROCESSOR_AUTH_TOKEN"),
+	("indexer_processor.indexer_processor_starting_version", "INDEXER_PROCESSOR_STARTING_VERSION"),
+	("indexer_processor.indexer_processor_ending_version", "INDEXER_PROCESSOR_ENDING_VERSION"),
+	(
+		"indexer_processor.indexer_processor_enabled_processors",
+		"INDEXER_PROCESSOR_ENABLED_PROCESSORS",
+	),
+	("client.maptos_rest_connection_hostname", "MAPTOS_API_CONNECTION_HOSTNAME"),
+	("client.maptos_rest_connection_port", "MAPTOS_API_CONNECTION_PORT"),
+	("client.maptos_faucet_rest_connection_hostname", "FAUCET_API_CONNECTION_HOSTNAME"),
+	("client.maptos_faucet_rest_connection_port", "FAUCET_API_CONNECTION_PORT"),
+	("client.maptos_indexer_grpc_connection_hostname", "MAPTOS_INDEXER_GRPC_CONNECTION_HOSTNAME"),
+	("client.maptos_indexer_grpc_connection_port", "MAPTOS_INDEXER_GRPC_CONNECTION_PORT"),
+	("client.maptos_indexer_upstreams", "MAPTOS_INDEXER_UPSTREAMS"),
+	("client.fin_rest_connection_hostname", "MAPTOS_FIN_VIEW_API_CONNECTION_HOSTNAME"),
+	("client.fin_rest_connection_port", "MAPTOS_FIN_VIEW_API_CONNECTION_PORT"),
+	("client.maptos_dns_refresh_sec", "MAPTOS_DNS_REFRESH_SEC"),
+	("faucet.maptos_faucet_rest_listen_hostname", "FAUCET_API_LISTEN_HOSTNAME"),
+	("faucet.maptos_faucet_rest_listen_port", "FAUCET_API_LISTEN_PORT"),
+	("faucet.maptos_faucet_rest_cors_allowed_origins", "FAUCET_API_CORS_ORIGINS"),
+	("faucet.maptos_faucet_rest_base_path", "FAUCET_API_BASE_PATH"),
+	("faucet.maptos_faucet_rest_max_body_bytes", "FAUCET_API_MAX_BODY_BYTES"),
+	("faucet.maptos_faucet_rest_idle_timeout_sec", "FAUCET_API_IDLE_TIMEOUT_SEC"),
+	("faucet.maptos_faucet_rest_max_connections", "FAUCET_API_MAX_CONNECTIONS"),
+	("faucet.maptos_faucet_rest_listen_backlog", "FAUCET_API_LISTEN_BACKLOG"),
+	("faucet.faucet_rate_limit_per_ip_per_hour", "FAUCET_RATE_LIMIT_PER_IP_PER_HOUR"),
+	("faucet.faucet_max_amount_per_request", "FAUCET_MAX_AMOUNT_PER_REQUEST"),
+	("faucet.faucet_mint_amount", "FAUCET_MINT_AMOUNT"),
+	("faucet.faucet_txn_expiration_sec", "FAUCET_TXN_EXPIRATION_SEC"),
+	("faucet.faucet_cooldown_sec", "FAUCET_COOLDOWN_SEC"),
+	("faucet.faucet_require_captcha", "FAUCET_REQUIRE_CAPTCHA"),
+	("faucet.faucet_private_key", "FAUCET_PRIVATE_KEY"),
+	("faucet.faucet_private_keys", "FAUCET_PRIVATE_KEYS"),
+	("faucet.faucet_blocked_addresses", "FAUCET_BLOCKED_ADDRESSES"),
+	("faucet.faucet_allowlist_addresses", "FAUCET_ALLOWLIST_ADDRESSES"),
+	("faucet.maptos_faucet_enabled", "FAUCET_ENABLED"),
+	("fin.fin_rest_listen_hostname", "MAPTOS_FIN_VIEW_API_LISTEN_HOSTNAME"),
+	("fin.fin_rest_listen_port", "MAPTOS_FIN_VIEW_API_LISTEN_PORT"),
+	("fin.fin_rest_request_timeout_sec", "MAPTOS_FIN_VIEW_API_REQUEST_TIMEOUT_SEC"),
+	("fin.fin_rest_max_connections", "MAPTOS_FIN_VIEW_API_MAX_CONNECTIONS"),
+	("fin.fin_rest_listen_backlog", "MAPTOS_FIN_VIEW_API_LISTEN_BACKLOG"),
+	("fin.fin_rest_worker_threads", "MAPTOS_FIN_VIEW_API_WORKER_THREADS"),
+	("fin.fin_rest_enabled", "MAPTOS_FIN_VIEW_ENABLED"),
+	("fin.fin_rest_max_concurrent_views", "MAPTOS_FIN_VIEW_API_MAX_CONCURRENT_VIEWS"),
+	("load_shedding.max_transactions_in_flight", "MAPTOS_MAX_TRANSACTIONS_IN_FLIGHT"),
+	("load_shedding.inflight_overflow_policy", "MAPTOS_INFLIGHT_OVERFLOW_POLICY"),
+	("load_shedding.mempool_ordering", "MAPTOS_MEMPOOL_ORDERING"),
+	("health.maptos_health_enabled", "MAPTOS_HEALTH_ENABLED"),
+	("health.maptos_health_listen_hostname", "MAPTOS_HEALTH_LISTEN_HOSTNAME"),
+	("health.maptos_health_listen_port", "MAPTOS_HEALTH_LISTEN_PORT"),
+	("health.maptos_health_mode", "MAPTOS_HEALTH_MODE"),
+	("health.maptos_max_block_age_sec", "MAPTOS_MAX_BLOCK_AGE_SEC"),
+	("metrics.maptos_metrics_enabled", "MAPTOS_METRICS_ENABLED"),
+	("metrics.maptos_metrics_listen_hostname", "MAPTOS_METRICS_LISTEN_HOSTNAME"),
+	("metrics.maptos_metrics_listen_port", "MAPTOS_METRICS_LISTEN_PORT"),
+	("logging.maptos_log_format", "MAPTOS_LOG_FORMAT"),
+	("logging.maptos_log_level", "MAPTOS_LOG_LEVEL"),
+	("logging.maptos_otlp_endpoint", "MAPTOS_OTLP_ENDPOINT"),
+	("logging.maptos_otlp_sample_rate", "MAPTOS_OTLP_SAMPLE_RATE"),
+];
+
+/// Fields whose values are shown as a hash instead of verbatim.
+pub(crate) const SECRET_PATHS: &[&str] = &[
+	"chain.maptos_private_key",
+	"chain.maptos_rest_scoped_tokens",
+	"faucet.faucet_private_key",
+	"faucet.faucet_private_keys",
+	"indexer_processor.indexer_processor_auth_token",
+	"indexer_processor.postgres_connection_string",
+];
+
+/// A field whose value differs between two configs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigFieldDiff {
+	/// The env var backing the field, or its path when it has none.
+	pub field: String,
+	/// The path of the field in the serialized config.
+	pub path: String,
+	/// The value in the config `diff` was called on.
+	pub self_value: String,
+	/// The value in the config it was compared against.
+	pub other_value: String,
+}
+
+pub(crate) fn flatten(prefix: &str, value: &Value, out: &mut BTreeMap<String, Value>) {
+	match value {
+		Value::Object(fields) => {
+			for (key, value) in fields {
+				let path =
+					if prefix.is_empty() { key.clone() } else { format!("{}.{}", prefix, key) };
+				flatten(&path, value, out);
+			}
+		}
+		leaf => {
+			out.insert(prefix.to_string(), leaf.clone());
+		}
+	}
+}
+
+/// Renders a leaf value, hashing secrets so equal secrets can still be recognized.
+pub(crate) fn render(path: &str, value: Option<&Value>) -> String {
+	let Some(value) = value else {
+		return "<unset>".to_string();
+	};
+	if SECRET_PATHS.contains(&path) {
+		let digest = ring::digest::digest(&ring::digest::SHA256, value.to_string().as_bytes());
+		let hex: String = digest.as_ref()[..8].iter().map(|byte| format!("{:02x}", byte)).collect();
+		format!("<redacted sha256:{}>", hex)
+	} else {
+		value.to_string()
+	}
+}
+
+pub(crate) fn field_name(path: &str) -> String {
+	FIELD_ENV_VARS
+		.iter()
+		.find(|(field_path, _)| *field_path == path)
+		.map(|(_, var)| var.to_string())
+		.unwrap_or_else(|| path.to_string())
+}
+
+impl Config {
+	/// Lists every field whose value differs from `other`, in path order.
+	pub fn diff(&self, other: &Config) -> Vec<ConfigFieldDiff> {
+		let mut ours = BTreeMap::new();
+		let mut theirs = BTreeMap::new();
+		flatten(
+			"",
+			&serde_json::to_value(self).expect("the config is always serializable"),
+			&mut ours,
+		);
+		flatten(
+			"",
+			&serde_json::to_value(other).expect("the config is always serializable"),
+			&mut theirs,
+		);
+
+		let mut paths: Vec<&String> = ours.keys().chain(theirs.keys()).collect();
+		paths.sort();
+		paths.dedup();
+		paths
+			.into_iter()
+			.filter(|path| ours.get(*path) != theirs.get(*path))
+			.map(|path| ConfigFieldDiff {
+				field: field_name(path),
+				path: path.clone(),
+				self_value: render(path, ours.get(path)),
+				other_value: render(path, theirs.get(path)),
+			})
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use aptos_crypto::{ed25519::Ed25519PrivateKey, Uniform, ValidCryptoMaterialStringExt};
+
+	#[test]
+	fn test_identical_configs_have_no_diff() {
+		let _env = godfig::env::read_lock();
+		let config = Config::default();
+		assert_eq!(config.diff(&config.clone()), vec![]);
+	}
+
+	#[test]
+	fn test_changed_fields_are_named_by_env_var() {
+		let _env = godfig::env::read_lock();
+		let config = Config::default();
+		let mut other = config.clone();
+		other.chain.maptos_rest_listen_port = config.chain.maptos_rest_listen_port + 1;
+		other.chain.maptos_db_path = Some("/var/lib/maptos".into());
+
+		let diff = config.diff(&other);
+		let fields: Vec<&str> = diff.iter().map(|diff| diff.field.as_str()).collect();
+		assert_eq!(fields, vec!["chain.maptos_db_path", "MAPTOS_API_LISTEN_PORT"]);
+		assert_eq!(diff[0].self_value, "null");
+		assert_eq!(diff[1].other_value, (config.chain.maptos_rest_listen_port + 1).to_string());
+	}
+
+	#[test]
+	fn test_secrets_are_hashed() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::read_lock();
+		let config = Config::default();
+		let mut other = config.clone();
+		other.chain.maptos_private_key = Ed25519PrivateKey::generate(&mut rand::thread_rng());
+		other.indexer_processor.indexer_processor_auth_token = "sekret-token".to_string();
+
+		let diff = config.diff(&other);
+		assert_eq!(diff.len(), 2);
+		let encoded_key = other.chain.maptos_private_key.to_encoded_string()?;
+		for field in &diff {
+			assert!(field.other_value.starts_with("<redacted sha256:"));
+			assert!(!field.other_value.contains(encoded_key.trim_start_matches("0x")));
+			assert!(!field.other_value.contains("sekret-token"));
+		}
+		Ok(())
+	}
+}
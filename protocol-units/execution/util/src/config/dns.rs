@@ -0,0 +1,169 @@
+//! Resolving connection hostnames, with the addresses cached for a refresh interval.
+
+use super::endpoints::socket_addrs;
+
+use tracing::warn;
+
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+/// Resolves a hostname and port to the addresses to dial.
+pub trait Resolve: Send + Sync {
+	fn resolve(&self, hostname: &str, port: u16) -> Result<Vec<SocketAddr>, std::io::Error>;
+}
+
+/// Resolves with the system resolver.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct SystemResolver;
+
+impl Resolve for SystemResolver {
+	fn resolve(&self, hostname: &str, port: u16) -> Result<Vec<SocketAddr>, std::io::Error> {
+		socket_addrs(hostname, port)
+	}
+}
+
+struct Cached {
+	addrs: Vec<SocketAddr>,
+	resolved_at: Instant,
+}
+
+/// Resolves connection hostnames, reusing the addresses of each for `refresh` and resolving
+/// it again on the first dial after, so that a backend which moves is picked up.
+///
+/// A refresh of 0 disables the cache and resolves on every dial. When resolving again fails,
+/// the addresses last resolved are used until it succeeds.
+pub struct CachingResolver<R = SystemResolver> {
+	inner: R,
+	refresh: Duration,
+	cache: Mutex<HashMap<(String, u16), Cached>>,
+}
+
+impl CachingResolver {
+	/// A resolver caching the system resolver's answers for `refresh`.
+	pub fn new(refresh: Duration) -> Self {
+		Self::with_resolver(SystemResolver, refresh)
+	}
+}
+
+impl<R: Resolve> CachingResolver<R> {
+	pub fn with_resolver(inner: R, refresh: Duration) -> Self {
+		Self { inner, refresh, cache: Mutex::default() }
+	}
+
+	/// How long resolved addresses are reused, 0 for not at all.
+	pub fn refresh(&self) -> Duration {
+		self.refresh
+	}
+
+	/// The addresses to dial for `hostname:port`.
+	pub fn resolve(&self, hostname: &str, port: u16) -> Result<Vec<SocketAddr>, std::io::Error> {
+		self.resolve_at(hostname, port, Instant::now())
+	}
+
+	fn resolve_at(
+		&self,
+		hostname: &str,
+		port: u16,
+		now: Instant,
+	) -> Result<Vec<SocketAddr>, std::io::Error> {
+		if self.refresh.is_zero() {
+			return self.inner.resolve(hostname, port);
+		}
+		let key = (hostname.to_string(), port);
+		if let Some(cached) = self.cache().get(&key) {
+			if now.saturating_duration_since(cached.resolved_at) < self.refresh {
+				return Ok(cached.addrs.clone());
+			}
+		}
+		// Resolved without the lock held, as the system resolver blocks.
+		match self.inner.resolve(hostname, port) {
+			Ok(addrs) => {
+				self.cache().insert(key, Cached { addrs: addrs.clone(), resolved_at: now });
+				Ok(addrs)
+			}
+			Err(e) => match self.cache().get(&key) {
+				Some(cached) => {
+					warn!(
+						"Failed to resolve {} again, dialing its last addresses: {}",
+						hostname, e
+					);
+					Ok(cached.addrs.clone())
+				}
+				None => Err(e),
+			},
+		}
+	}
+
+	fn cache(&self) -> std::sync::MutexGuard<'_, HashMap<(String, u16), Cached>> {
+		self.cache.lock().expect("no thread panics holding the resolver cache")
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use std::sync::atomic::{AtomicBool, AtomicU16, Ordering};
+
+	/// Resolves every hostname to a loopback address whose port counts the resolutions,
+	/// failing while `fail` is set.
+	#[derive(Default)]
+	struct CountingResolver {
+		resolutions: AtomicU16,
+		fail: AtomicBool,
+	}
+
+	impl Resolve for CountingResolver {
+		fn resolve(&self, _hostname: &str, _port: u16) -> Result<Vec<SocketAddr>, std::io::Error> {
+			if self.fail.load(Ordering::SeqCst) {
+				return Err(std::io::Error::new(std::io::ErrorKind::Other, "no such host"));
+			}
+			let resolutions = self.resolutions.fetch_add(1, Ordering::SeqCst) + 1;
+			Ok(vec![SocketAddr::from(([127, 0, 0, 1], resolutions))])
+		}
+	}
+
+	fn port(addrs: Vec<SocketAddr>) -> u16 {
+		addrs[0].port()
+	}
+
+	#[test]
+	fn test_addresses_are_refreshed_after_the_interval() -> Result<(), std::io::Error> {
+		let resolver =
+			CachingResolver::with_resolver(CountingResolver::default(), Duration::from_secs(60));
+		let start = Instant::now();
+		assert_eq!(port(resolver.resolve_at("node", 30731, start)?), 1);
+		assert_eq!(port(resolver.resolve_at("node", 30731, start + Duration::from_secs(59))?), 1);
+		assert_eq!(port(resolver.resolve_at("node", 30731, start + Duration::from_secs(60))?), 2);
+		assert_eq!(port(resolver.resolve_at("node", 30731, start + Duration::from_secs(61))?), 2);
+
+		// Each hostname and port is cached on its own.
+		assert_eq!(port(resolver.resolve_at("other", 30731, start)?), 3);
+		Ok(())
+	}
+
+	#[test]
+	fn test_zero_refresh_resolves_every_dial() -> Result<(), std::io::Error> {
+		let resolver = CachingResolver::with_resolver(CountingResolver::default(), Duration::ZERO);
+		let now = Instant::now();
+		assert_eq!(port(resolver.resolve_at("node", 30731, now)?), 1);
+		assert_eq!(port(resolver.resolve_at("node", 30731, now)?), 2);
+		Ok(())
+	}
+
+	#[test]
+	fn test_last_addresses_are_kept_when_resolving_fails() -> Result<(), std::io::Error> {
+		let resolver =
+			CachingResolver::with_resolver(CountingResolver::default(), Duration::from_secs(60));
+		let start = Instant::now();
+		assert_eq!(port(resolver.resolve_at("node", 30731, start)?), 1);
+
+		resolver.inner.fail.store(true, Ordering::SeqCst);
+		let later = start + Duration::from_secs(120);
+		assert_eq!(port(resolver.resolve_at("node", 30731, later)?), 1);
+		assert!(resolver.resolve_at("never-resolved", 30731, later).is_err());
+		Ok(())
+	}
+}
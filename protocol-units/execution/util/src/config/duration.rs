@@ -0,0 +1,189 @@
+//! Durations given as a number of seconds or with a unit suffix.
+
+use serde::de::{self, Visitor};
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// The units accepted after a number, with their length in nanoseconds.
+const UNITS: &[(&str, u64)] = &[
+	("ns", 1),
+	("us", 1_000),
+	("ms", 1_000_000),
+	("s", 1_000_000_000),
+	("m", 60 * 1_000_000_000),
+	("h", 60 * 60 * 1_000_000_000),
+];
+
+/// A duration of whole seconds, like `30`, or of a number with a unit: `500ms`, `90s`, `2m`, `1h`.
+///
+/// Whole seconds display and serialize as a bare number, so existing config files
+/// and env values read the same as before units were accepted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct DurationSecs(Duration);
+
+impl DurationSecs {
+	pub const ZERO: DurationSecs = DurationSecs(Duration::ZERO);
+
+	pub const fn from_secs(secs: u64) -> Self {
+		DurationSecs(Duration::from_secs(secs))
+	}
+
+	pub const fn from_millis(millis: u64) -> Self {
+		DurationSecs(Duration::from_millis(millis))
+	}
+
+	pub fn get(&self) -> Duration {
+		self.0
+	}
+
+	pub fn is_zero(&self) -> bool {
+		self.0.is_zero()
+	}
+}
+
+impl From<Duration> for DurationSecs {
+	fn from(duration: Duration) -> Self {
+		DurationSecs(duration)
+	}
+}
+
+impl From<DurationSecs> for Duration {
+	fn from(duration: DurationSecs) -> Self {
+		duration.0
+	}
+}
+
+impl FromStr for DurationSecs {
+	type Err = anyhow::Error;
+
+	fn from_str(raw: &str) -> Result<Self, Self::Err> {
+		let invalid = || {
+			anyhow::anyhow!(
+				"invalid duration {:?}, expected seconds or a number with one of: {}",
+				raw,
+				UNITS.iter().map(|(unit, _)| *unit).collect::<Vec<_>>().join(", ")
+			)
+		};
+		let trimmed = raw.trim();
+		let split = trimmed.find(|c: char| !c.is_ascii_digit()).unwrap_or(trimmed.len());
+		let (number, unit) = trimmed.split_at(split);
+		let number: u64 = number.parse().map_err(|_| invalid())?;
+		if unit.is_empty() {
+			return Ok(Self::from_secs(number));
+		}
+		let (_, nanos) = UNITS.iter().find(|(name, _)| *name == unit).ok_or_else(invalid)?;
+		let nanos = u128::from(number) * u128::from(*nanos);
+		let secs = u64::try_from(nanos / 1_000_000_000)
+			.map_err(|_| anyhow::anyhow!("duration {:?} is too long", raw))?;
+		Ok(DurationSecs(Duration::new(secs, (nanos % 1_000_000_000) as u32)))
+	}
+}
+
+impl fmt::Display for DurationSecs {
+	/// Displays whole seconds as a bare number and anything finer in the largest exact unit.
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let nanos = self.0.subsec_nanos();
+		if nanos == 0 {
+			write!(f, "{}", self.0.as_secs())
+		} else if nanos % 1_000_000 == 0 {
+			write!(f, "{}ms", self.0.as_millis())
+		} else if nanos % 1_000 == 0 {
+			write!(f, "{}us", self.0.as_micros())
+		} else {
+			write!(f, "{}ns", self.0.as_nanos())
+		}
+	}
+}
+
+impl Serialize for DurationSecs {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		if self.0.subsec_nanos() == 0 {
+			serializer.serialize_u64(self.0.as_secs())
+		} else {
+			serializer.collect_str(self)
+		}
+	}
+}
+
+impl<'de> Deserialize<'de> for DurationSecs {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		struct DurationVisitor;
+
+		impl Visitor<'_> for DurationVisitor {
+			type Value = DurationSecs;
+
+			fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+				f.write_str("a number of seconds or a string like \"500ms\"")
+			}
+
+			fn visit_u64<E: de::Error>(self, secs: u64) -> Result<DurationSecs, E> {
+				Ok(DurationSecs::from_secs(secs))
+			}
+
+			fn visit_i64<E: de::Error>(self, secs: i64) -> Result<DurationSecs, E> {
+				u64::try_from(secs)
+					.map(DurationSecs::from_secs)
+					.map_err(|_| E::invalid_value(de::Unexpected::Signed(secs), &self))
+			}
+
+			fn visit_str<E: de::Error>(self, raw: &str) -> Result<DurationSecs, E> {
+				raw.parse().map_err(E::custom)
+			}
+		}
+
+		deserializer.deserialize_any(DurationVisitor)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_bare_numbers_are_seconds() -> Result<(), anyhow::Error> {
+		assert_eq!("30".parse::<DurationSecs>()?, DurationSecs::from_secs(30));
+		assert_eq!(" 0 ".parse::<DurationSecs>()?, DurationSecs::ZERO);
+		assert!("-1".parse::<DurationSecs>().is_err());
+		assert!("".parse::<DurationSecs>().is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_suffixes_are_parsed() -> Result<(), anyhow::Error> {
+		assert_eq!("500ms".parse::<DurationSecs>()?.get(), Duration::from_millis(500));
+		assert_eq!("90s".parse::<DurationSecs>()?.get(), Duration::from_secs(90));
+		assert_eq!("2m".parse::<DurationSecs>()?.get(), Duration::from_secs(120));
+		assert_eq!("1h".parse::<DurationSecs>()?.get(), Duration::from_secs(3600));
+		assert_eq!("250us".parse::<DurationSecs>()?.get(), Duration::from_micros(250));
+		assert_eq!("7ns".parse::<DurationSecs>()?.get(), Duration::from_nanos(7));
+		assert!("2 minutes".parse::<DurationSecs>().is_err());
+		assert!("1.5s".parse::<DurationSecs>().is_err());
+		assert!("ms".parse::<DurationSecs>().is_err());
+		assert!(format!("{}h", u64::MAX).parse::<DurationSecs>().is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_display_round_trips() -> Result<(), anyhow::Error> {
+		for raw in ["0", "30", "120", "500ms", "1500ms", "250us", "7ns"] {
+			let duration: DurationSecs = raw.parse()?;
+			assert_eq!(duration.to_string(), raw);
+		}
+		// A suffix of whole seconds displays as seconds.
+		assert_eq!("2m".parse::<DurationSecs>()?.to_string(), "120");
+		Ok(())
+	}
+
+	#[test]
+	fn test_whole_seconds_serialize_as_numbers() -> Result<(), anyhow::Error> {
+		assert_eq!(serde_json::to_value(DurationSecs::from_secs(60))?, serde_json::json!(60));
+		assert_eq!(serde_json::to_value(DurationSecs::from_millis(500))?, "500ms");
+		assert_eq!(serde_json::from_str::<DurationSecs>("60")?, DurationSecs::from_secs(60));
+		assert_eq!(serde_json::from_str::<DurationSecs>("\"2m\"")?, DurationSecs::from_secs(120));
+		assert!(serde_json::from_str::<DurationSecs>("-5").is_err());
+		Ok(())
+	}
+}
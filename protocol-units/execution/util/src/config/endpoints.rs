@@ -0,0 +1,552 @@
+//! Connection URLs and bind addresses for the services described by the config.
+
+use super::{dns::CachingResolver, Config};
+
+use anyhow::Context;
+use url::Url;
+
+use std::net::{Ipv6Addr, SocketAddr, ToSocketAddrs};
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
+
+/// The prefix marking a listen hostname as a Unix domain socket path.
+pub const UNIX_SOCKET_PREFIX: &str = "unix:";
+
+/// Where a server accepts connections.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ListenTarget {
+	Tcp(SocketAddr),
+	Unix(PathBuf),
+}
+
+/// Admission limits of a listener.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnectionLimits {
+	/// Connections served at once; further ones are closed as soon as they are accepted.
+	pub max_connections: Option<NonZeroUsize>,
+	/// Connections queued by the kernel before they are accepted; TCP listeners only.
+	pub listen_backlog: u32,
+}
+
+impl ConnectionLimits {
+	/// The largest backlog the socket API takes; kernels clamp it to their own maximum.
+	pub const MAX_LISTEN_BACKLOG: u32 = i32::MAX as u32;
+
+	/// Reads the limits as configured, where 0 means unlimited for both.
+	pub fn new(max_connections: u32, listen_backlog: u32) -> Self {
+		let listen_backlog = match listen_backlog {
+			0 => Self::MAX_LISTEN_BACKLOG,
+			backlog => backlog.min(Self::MAX_LISTEN_BACKLOG),
+		};
+		Self {
+			max_connections: usize::try_from(max_connections).ok().and_then(NonZeroUsize::new),
+			listen_backlog,
+		}
+	}
+}
+
+/// Whether the listen hostname names a Unix domain socket rather than a TCP host.
+pub fn is_unix_socket_hostname(hostname: &str) -> bool {
+	hostname.starts_with(UNIX_SOCKET_PREFIX)
+}
+
+/// Splits a listen hostname into the hostnames it lists, comma-separated for dual-stack binds
+/// such as `0.0.0.0,::`.
+///
+/// A Unix domain socket path is never split.
+pub fn listen_hostnames(hostname: &str) -> Vec<&str> {
+	if is_unix_socket_hostname(hostname) {
+		return vec![hostname];
+	}
+	hostname
+		.split(',')
+		.map(str::trim)
+		.filter(|hostname| !hostname.is_empty())
+		.collect()
+}
+
+/// Builds `scheme://hostname:port`, bracketing IPv6 literals.
+pub fn connection_url(scheme: &str, hostname: &str, port: u16) -> Result<Url, url::ParseError> {
+	let url = if hostname.parse::<Ipv6Addr>().is_ok() {
+		format!("{}://[{}]:{}", scheme, hostname, port)
+	} else {
+		format!("{}://{}:{}", scheme, hostname, port)
+	};
+	Url::parse(&url)
+}
+
+/// `url` with the routes of a server mounted under `base_path`, a base path as
+/// [`normalize_base_path`] returns it, appended, ending in a slash for further paths to be
+/// joined onto it.
+pub fn with_base_path(url: Url, base_path: &str) -> Result<Url, url::ParseError> {
+	url.join(&format!("{}/", base_path))
+}
+
+/// Resolves `hostname:port` to every address it names.
+///
+/// IP literals, including bracketed IPv6 literals, resolve to exactly themselves.
+pub fn socket_addrs(hostname: &str, port: u16) -> Result<Vec<SocketAddr>, std::io::Error> {
+	let hostname = hostname.strip_prefix('[').and_then(|h| h.strip_suffix(']')).unwrap_or(hostname);
+	Ok((hostname, port).to_socket_addrs()?.collect())
+}
+
+/// Resolves a single listen hostname to every address it names.
+fn resolve_hostname(
+	hostname_var: &str,
+	hostname: &str,
+	port: u16,
+) -> Result<Vec<SocketAddr>, anyhow::Error> {
+	let addrs = socket_addrs(hostname, port)
+		.with_context(|| format!("failed to resolve {}={}", hostname_var, hostname))?;
+	if addrs.is_empty() {
+		anyhow::bail!("{}={} resolved to no addresses", hostname_var, hostname);
+	}
+	Ok(addrs)
+}
+
+fn resolve_listener(
+	hostname_var: &str,
+	hostname: &str,
+	port: u16,
+) -> Result<Vec<SocketAddr>, anyhow::Error> {
+	let hostnames = listen_hostnames(hostname);
+	if hostnames.is_empty() {
+		anyhow::bail!("{}={:?} names no hostname", hostname_var, hostname);
+	}
+	let mut addrs = Vec::new();
+	for hostname in hostnames {
+		for addr in resolve_hostname(hostname_var, hostname, port)? {
+			if !addrs.contains(&addr) {
+				addrs.push(addr);
+			}
+		}
+	}
+	Ok(addrs)
+}
+
+/// Parses a listen hostname of the form `unix:/path/to/socket`,
+/// or resolves each of the comma-separated hostnames it lists with `port`.
+///
+/// The port is ignored for Unix domain sockets.
+/// A TCP hostname resolving to several addresses binds the first of them.
+pub fn listen_targets(
+	hostname_var: &str,
+	hostname: &str,
+	port: u16,
+) -> Result<Vec<ListenTarget>, anyhow::Error> {
+	if let Some(path) = hostname.strip_prefix(UNIX_SOCKET_PREFIX) {
+		if path.is_empty() {
+			anyhow::bail!("{}={} names no socket path", hostname_var, hostname);
+		}
+		return Ok(vec![ListenTarget::Unix(path.into())]);
+	}
+	let hostnames = listen_hostnames(hostname);
+	if hostnames.is_empty() {
+		anyhow::bail!("{}={:?} names no hostname", hostname_var, hostname);
+	}
+	let mut targets = Vec::new();
+	for hostname in hostnames {
+		let target = ListenTarget::Tcp(resolve_hostname(hostname_var, hostname, port)?[0]);
+		if !targets.contains(&target) {
+			targets.push(target);
+		}
+	}
+	Ok(targets)
+}
+
+/// The first of the [`listen_targets`], for servers which bind a single address.
+pub fn listen_target(
+	hostname_var: &str,
+	hostname: &str,
+	port: u16,
+) -> Result<ListenTarget, anyhow::Error> {
+	Ok(listen_targets(hostname_var, hostname, port)?.remove(0))
+}
+
+/// Removes a socket file left behind by a previous run, so that it can be bound again.
+///
+/// Anything at `path` other than a socket is left in place, and binding will then fail.
+#[cfg(unix)]
+pub fn remove_stale_unix_socket(path: &std::path::Path) -> Result<(), std::io::Error> {
+	use std::os::unix::fs::FileTypeExt;
+
+	match std::fs::symlink_metadata(path) {
+		Ok(metadata) if metadata.file_type().is_socket() => std::fs::remove_file(path),
+		Ok(_) => Ok(()),
+		Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+		Err(e) => Err(e),
+	}
+}
+
+/// A base path with its trailing slashes removed, so that `/v1/` and `/v1` serve the same
+/// routes and `/` serves them at the root.
+///
+/// Returns `None` unless the path is empty or starts with `/`, and has no query or fragment.
+pub fn normalize_base_path(raw: &str) -> Option<&str> {
+	let invalid = |c: char| c == '?' || c == '#' || c.is_whitespace();
+	if (!raw.is_empty() && !raw.starts_with('/')) || raw.contains(invalid) {
+		return None;
+	}
+	Some(raw.trim_end_matches('/'))
+}
+
+/// A hostname as pasted by an operator, such as ` HTTP://Example.COM/ `, with the surrounding
+/// whitespace, any scheme and trailing slashes removed and the host lowercased.
+///
+/// Each of the comma-separated hostnames a listen hostname lists is normalized on its own,
+/// and a Unix domain socket path is left as it is.
+pub fn normalize_hostname(raw: &str) -> String {
+	if is_unix_socket_hostname(raw.trim_start()) {
+		return raw.trim_start().to_string();
+	}
+	raw.split(',')
+		.map(|hostname| {
+			let hostname = hostname.trim();
+			let hostname = hostname.split_once("://").map_or(hostname, |(_, rest)| rest);
+			hostname.trim_end_matches('/').to_ascii_lowercase()
+		})
+		.collect::<Vec<_>>()
+		.join(",")
+}
+
+impl Config {
+	/// Normalizes every listen and connection hostname with [`normalize_hostname`], as the
+	/// strict loader does before validating the config.
+	pub fn normalize(&mut self) {
+		for hostname in [
+			&mut self.chain.maptos_rest_listen_hostname,
+			&mut self.client.maptos_rest_connection_hostname,
+			&mut self.client.maptos_faucet_rest_connection_hostname,
+			&mut self.client.maptos_indexer_grpc_connection_hostname,
+			&mut self.client.fin_rest_connection_hostname,
+			&mut self.faucet.maptos_rest_connection_hostname,
+			&mut self.faucet.maptos_faucet_rest_listen_hostname,
+			&mut self.fin.fin_rest_listen_hostname,
+			&mut self.health.maptos_health_listen_hostname,
+			&mut self.indexer.maptos_indexer_grpc_listen_hostname,
+			&mut self.metrics.maptos_metrics_listen_hostname,
+		] {
+			*hostname = normalize_hostname(hostname);
+		}
+	}
+
+	/// Where the Aptos REST server accepts connections.
+	pub fn rest_listen_targets(&self) -> Result<Vec<ListenTarget>, anyhow::Error> {
+		listen_targets(
+			"MAPTOS_API_LISTEN_HOSTNAME",
+			&self.chain.maptos_rest_listen_hostname,
+			self.chain.maptos_rest_listen_port,
+		)
+	}
+
+	/// Where the fin view server accepts connections.
+	pub fn fin_listen_targets(&self) -> Result<Vec<ListenTarget>, anyhow::Error> {
+		listen_targets(
+			"MAPTOS_FIN_VIEW_API_LISTEN_HOSTNAME",
+			&self.fin.fin_rest_listen_hostname,
+			self.fin.fin_rest_listen_port,
+		)
+	}
+
+	/// The addresses the Aptos REST server binds, for every hostname it lists.
+	pub fn rest_listen_socket_addrs(&self) -> Result<Vec<SocketAddr>, anyhow::Error> {
+		resolve_listener(
+			"MAPTOS_API_LISTEN_HOSTNAME",
+			&self.chain.maptos_rest_listen_hostname,
+			self.chain.maptos_rest_listen_port,
+		)
+	}
+
+	/// The addresses the Aptos Faucet server binds.
+	pub fn faucet_listen_socket_addrs(&self) -> Result<Vec<SocketAddr>, anyhow::Error> {
+		resolve_listener(
+			"FAUCET_API_LISTEN_HOSTNAME",
+			&self.faucet.maptos_faucet_rest_listen_hostname,
+			self.faucet.maptos_faucet_rest_listen_port,
+		)
+	}
+
+	/// The addresses the fin view server binds.
+	pub fn fin_listen_socket_addrs(&self) -> Result<Vec<SocketAddr>, anyhow::Error> {
+		resolve_listener(
+			"MAPTOS_FIN_VIEW_API_LISTEN_HOSTNAME",
+			&self.fin.fin_rest_listen_hostname,
+			self.fin.fin_rest_listen_port,
+		)
+	}
+
+	/// The addresses the gRPC indexer server binds.
+	pub fn indexer_grpc_listen_socket_addrs(&self) -> Result<Vec<SocketAddr>, anyhow::Error> {
+		resolve_listener(
+			"MAPTOS_INDEXER_GRPC_LISTEN_HOSTNAME",
+			&self.indexer.maptos_indexer_grpc_listen_hostname,
+			self.indexer.maptos_indexer_grpc_listen_port,
+		)
+	}
+
+	/// The resolver clients dial connection hostnames through, refreshing the addresses
+	/// they resolve to every `MAPTOS_DNS_REFRESH_SEC`.
+	pub fn connection_resolver(&self) -> CachingResolver {
+		CachingResolver::new(self.client.dns_refresh())
+	}
+
+	/// The URL clients use to reach the Aptos REST server, under `MAPTOS_API_BASE_PATH`.
+	///
+	/// The scheme is `https` when the REST server is configured with TLS.
+	pub fn rest_connection_url(&self) -> Result<Url, url::ParseError> {
+		let scheme = if self.rest_tls_enabled() { "https" } else { "http" };
+		let url = connection_url(
+			scheme,
+			&self.client.maptos_rest_connection_hostname,
+			self.client.maptos_rest_connection_port,
+		)?;
+		with_base_path(url, self.chain.rest_base_path())
+	}
+
+	/// The URL clients use to reach the Aptos Faucet server.
+	pub fn faucet_connection_url(&self) -> Result<Url, url::ParseError> {
+		connection_url(
+			"http",
+			&self.client.maptos_faucet_rest_connection_hostname,
+			self.client.maptos_faucet_rest_connection_port,
+		)
+	}
+
+	/// The URL clients use to reach the fin view server.
+	pub fn fin_connection_url(&self) -> Result<Url, url::ParseError> {
+		connection_url(
+			"http",
+			&self.client.fin_rest_connection_hostname,
+			self.client.fin_rest_connection_port,
+		)
+	}
+
+	/// The URL clients use to reach the gRPC indexer server.
+	///
+	/// The scheme is `https` when the gRPC indexer server is configured with TLS.
+	pub fn indexer_grpc_connection_url(&self) -> Result<Url, url::ParseError> {
+		let scheme = if self.indexer_grpc_tls_enabled() { "https" } else { "http" };
+		connection_url(
+			scheme,
+			&self.client.maptos_indexer_grpc_connection_hostname,
+			self.client.maptos_indexer_grpc_connection_port,
+		)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_zero_connection_limits_are_unlimited() {
+		let unlimited = ConnectionLimits::new(0, 0);
+		assert_eq!(unlimited.max_connections, None);
+		assert_eq!(unlimited.listen_backlog, ConnectionLimits::MAX_LISTEN_BACKLOG);
+
+		let limited = ConnectionLimits::new(512, 128);
+		assert_eq!(limited.max_connections, NonZeroUsize::new(512));
+		assert_eq!(limited.listen_backlog, 128);
+		assert_eq!(ConnectionLimits::new(0, u32::MAX).listen_backlog, i32::MAX as u32);
+	}
+
+	#[test]
+	fn test_base_paths_are_normalized() {
+		assert_eq!(normalize_base_path(""), Some(""));
+		assert_eq!(normalize_base_path("/"), Some(""));
+		assert_eq!(normalize_base_path("/v1"), Some("/v1"));
+		assert_eq!(normalize_base_path("/node-a/"), Some("/node-a"));
+		assert_eq!(normalize_base_path("/node-a/api//"), Some("/node-a/api"));
+		assert_eq!(normalize_base_path("node-a"), None);
+		assert_eq!(normalize_base_path("/node a"), None);
+		assert_eq!(normalize_base_path("/node-a?x=1"), None);
+		assert_eq!(normalize_base_path("/node-a#top"), None);
+	}
+
+	#[test]
+	fn test_connection_limits_are_read_from_env() {
+		let _env = godfig::env::lock();
+		std::env::set_var("MAPTOS_FIN_VIEW_API_MAX_CONNECTIONS", "256");
+		std::env::set_var("MAPTOS_FIN_VIEW_API_LISTEN_BACKLOG", "64");
+		let limits = crate::config::fin::Config::default().connection_limits();
+		std::env::remove_var("MAPTOS_FIN_VIEW_API_MAX_CONNECTIONS");
+		std::env::remove_var("MAPTOS_FIN_VIEW_API_LISTEN_BACKLOG");
+
+		assert_eq!(limits.max_connections, NonZeroUsize::new(256));
+		assert_eq!(limits.listen_backlog, 64);
+	}
+
+	#[test]
+	fn test_ipv6_hostname_is_bracketed() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.client.maptos_rest_connection_hostname = "::1".to_string();
+		config.client.maptos_rest_connection_port = 30731;
+
+		// `Url` normalizes an empty path to `/`.
+		assert_eq!(config.rest_connection_url()?.as_str(), "http://[::1]:30731/");
+
+		// The routes are reached under the base path they are mounted at.
+		config.chain.maptos_rest_base_path = "/node-a/".to_string();
+		let url = config.rest_connection_url()?;
+		assert_eq!(url.as_str(), "http://[::1]:30731/node-a/");
+		assert_eq!(url.join("v1")?.as_str(), "http://[::1]:30731/node-a/v1");
+		Ok(())
+	}
+
+	#[test]
+	fn test_fin_connection_url() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.client.fin_rest_connection_hostname = "127.0.0.1".to_string();
+		config.client.fin_rest_connection_port = 40200;
+
+		assert_eq!(config.fin_connection_url()?.as_str(), "http://127.0.0.1:40200/");
+		Ok(())
+	}
+
+	#[test]
+	fn test_ipv4_and_dns_hostnames() -> Result<(), anyhow::Error> {
+		assert_eq!(connection_url("http", "127.0.0.1", 30734)?.as_str(), "http://127.0.0.1:30734/");
+		assert_eq!(
+			connection_url("https", "node.example.com", 443)?.host_str(),
+			Some("node.example.com")
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn test_unspecified_listener_resolves_to_itself() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.chain.maptos_rest_listen_hostname = "0.0.0.0".to_string();
+		config.chain.maptos_rest_listen_port = 30731;
+
+		assert_eq!(config.rest_listen_socket_addrs()?, vec!["0.0.0.0:30731".parse()?]);
+		Ok(())
+	}
+
+	#[test]
+	fn test_dual_stack_listen_hostnames() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::read_lock();
+		assert_eq!(listen_hostnames("0.0.0.0, ::"), vec!["0.0.0.0", "::"]);
+		assert_eq!(listen_hostnames("0.0.0.0"), vec!["0.0.0.0"]);
+		assert_eq!(listen_hostnames("unix:/run/a,b.sock"), vec!["unix:/run/a,b.sock"]);
+
+		let mut config = Config::default();
+		config.chain.maptos_rest_listen_hostname = "0.0.0.0,::".to_string();
+		config.chain.maptos_rest_listen_port = 30731;
+		assert_eq!(
+			config.rest_listen_socket_addrs()?,
+			vec!["0.0.0.0:30731".parse::<SocketAddr>()?, "[::]:30731".parse()?]
+		);
+		assert_eq!(
+			config.rest_listen_targets()?,
+			vec![
+				ListenTarget::Tcp("0.0.0.0:30731".parse()?),
+				ListenTarget::Tcp("[::]:30731".parse()?)
+			]
+		);
+
+		config.chain.maptos_rest_listen_hostname = " , ".to_string();
+		assert!(config.rest_listen_socket_addrs().is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_ipv6_listener_resolves_with_or_without_brackets() -> Result<(), anyhow::Error> {
+		let expected: Vec<SocketAddr> = vec!["[::1]:30734".parse()?];
+		assert_eq!(socket_addrs("::1", 30734)?, expected);
+		assert_eq!(socket_addrs("[::1]", 30734)?, expected);
+		Ok(())
+	}
+
+	#[test]
+	fn test_localhost_resolves_to_loopback() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::read_lock();
+		let addrs = socket_addrs("localhost", 30733)?;
+		assert!(!addrs.is_empty());
+		assert!(addrs.iter().all(|addr| addr.ip().is_loopback() && addr.port() == 30733));
+		Ok(())
+	}
+
+	#[test]
+	fn test_unix_listen_target() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.chain.maptos_rest_listen_hostname = "unix:/run/maptos/rest.sock".to_string();
+		assert_eq!(
+			config.rest_listen_targets()?,
+			vec![ListenTarget::Unix("/run/maptos/rest.sock".into())]
+		);
+
+		config.fin.fin_rest_listen_hostname = "unix:relative/fin.sock".to_string();
+		assert_eq!(
+			config.fin_listen_targets()?,
+			vec![ListenTarget::Unix("relative/fin.sock".into())]
+		);
+
+		assert!(listen_target("MAPTOS_API_LISTEN_HOSTNAME", "unix:", 30731).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_tcp_listen_target() -> Result<(), anyhow::Error> {
+		assert_eq!(
+			listen_target("MAPTOS_API_LISTEN_HOSTNAME", "127.0.0.1", 30731)?,
+			ListenTarget::Tcp("127.0.0.1:30731".parse()?)
+		);
+		Ok(())
+	}
+
+	#[cfg(unix)]
+	#[test]
+	fn test_stale_socket_is_removed() -> Result<(), anyhow::Error> {
+		let dir = tempfile::tempdir()?;
+		let socket = dir.path().join("rest.sock");
+		drop(std::os::unix::net::UnixListener::bind(&socket)?);
+		assert!(socket.exists());
+		remove_stale_unix_socket(&socket)?;
+		assert!(!socket.exists());
+
+		let file = dir.path().join("not-a-socket");
+		std::fs::write(&file, "keep")?;
+		remove_stale_unix_socket(&file)?;
+		assert!(file.exists());
+		Ok(())
+	}
+
+	#[test]
+	fn test_rest_scheme_follows_tls() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.chain.maptos_rest_tls_cert_path = "/etc/maptos/rest.crt".to_string();
+		config.chain.maptos_rest_tls_key_path = "/etc/maptos/rest.key".to_string();
+
+		assert_eq!(config.rest_connection_url()?.scheme(), "https");
+		Ok(())
+	}
+
+	#[test]
+	fn test_messy_hostnames_are_normalized() {
+		let _env = godfig::env::read_lock();
+		assert_eq!(normalize_hostname(" HTTP://Example.COM:30731/ "), "example.com:30731");
+		assert_eq!(normalize_hostname("0.0.0.0, ::"), "0.0.0.0,::");
+
+		let mut config = Config::default();
+		config.client.maptos_rest_connection_hostname = " https://Node.Example.com// ".to_string();
+		config.normalize();
+		assert_eq!(config.client.maptos_rest_connection_hostname, "node.example.com");
+	}
+
+	#[test]
+	fn test_unix_targets_are_preserved() {
+		let _env = godfig::env::read_lock();
+		assert_eq!(normalize_hostname("unix:/run/Maptos/rest.sock"), "unix:/run/Maptos/rest.sock");
+
+		let mut config = Config::default();
+		config.chain.maptos_rest_listen_hostname = "unix:/run/a,b.sock".to_string();
+		config.normalize();
+		assert_eq!(config.chain.maptos_rest_listen_hostname, "unix:/run/a,b.sock");
+	}
+}
@@ -0,0 +1,134 @@
+//! The environment which reproduces a resolved config.
+
+use super::diff::{flatten, FIELD_ENV_VARS, SECRET_PATHS};
+use super::Config;
+
+use aptos_crypto::ValidCryptoMaterialStringExt;
+use serde_json::Value;
+
+use std::collections::BTreeMap;
+
+/// Emitted in place of a secret's value.
+pub const REDACTED: &str = "<redacted>";
+
+/// Renders a leaf value the way its env var is parsed; lists are comma-separated.
+fn env_value(value: &Value) -> String {
+	match value {
+		Value::String(value) => value.clone(),
+		Value::Array(items) => items.iter().map(env_value).collect::<Vec<_>>().join(","),
+		Value::Null => String::new(),
+		other => other.to_string(),
+	}
+}
+
+impl Config {
+	/// Lists the env vars which, set on a fresh process, resolve to this config.
+	///
+	/// Secrets are emitted as [`REDACTED`] unless `include_secrets` is set.
+	/// Fields without an env var, such as the database path, are not listed.
+	pub fn to_env_vars(&self, include_secrets: bool) -> Vec<(String, String)> {
+		let mut fields = BTreeMap::new();
+		flatten(
+			"",
+			&serde_json::to_value(self).expect("the config is always serializable"),
+			&mut fields,
+		);
+		FIELD_ENV_VARS
+			.iter()
+			.map(|(path, var)| {
+				let value = if SECRET_PATHS.contains(path) && !include_secrets {
+					REDACTED.to_string()
+				} else {
+					match *path {
+						"chain.maptos_chain_id" => self.chain.maptos_chain_id.id().to_string(),
+						"chain.maptos_private_key" => self
+							.chain
+							.maptos_private_key
+							.to_encoded_string()
+							.expect("a private key always encodes"),
+						"faucet.faucet_private_key" => self
+							.faucet
+							.faucet_private_key
+							.as_ref()
+							.map(|private_key| {
+								private_key
+									.to_encoded_string()
+									.expect("a private key always encodes")
+							})
+							.unwrap_or_default(),
+						"faucet.faucet_private_keys" => self
+							.faucet
+							.faucet_private_keys
+							.iter()
+							.map(|private_key| {
+								private_key
+									.to_encoded_string()
+									.expect("a private key always encodes")
+							})
+							.collect::<Vec<_>>()
+							.join(","),
+						path => fields.get(path).map(env_value).unwrap_or_default(),
+					}
+				};
+				(var.to_string(), value)
+			})
+			.collect()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::config::logging::{LogFormat, LogLevel};
+	use aptos_types::chain_id::ChainId;
+
+	#[test]
+	fn test_secrets_are_redacted_unless_included() {
+		let _env = godfig::env::read_lock();
+		let config = Config::default();
+		let value = |include_secrets: bool| {
+			config
+				.to_env_vars(include_secrets)
+				.into_iter()
+				.find(|(var, _)| var == "MAPTOS_PRIVATE_KEY")
+				.map(|(_, value)| value)
+		};
+		assert_eq!(value(false).as_deref(), Some(REDACTED));
+		assert_eq!(value(true), config.chain.maptos_private_key.to_encoded_string().ok());
+	}
+
+	#[test]
+	fn test_env_vars_reproduce_the_config() {
+		let _env = godfig::env::lock();
+		let mut source = Config::default();
+		source.chain.maptos_chain_id = ChainId::new(4);
+		source.chain.maptos_rest_cors_allowed_origins =
+			vec!["https://a.example".to_string(), "https://b.example".to_string()];
+		source.chain.maptos_data_dir = "/var/lib/maptos".into();
+		source.faucet.faucet_rate_limit_per_ip_per_hour = 10;
+		source.load_shedding.max_transactions_in_flight = 1000;
+		source.logging.maptos_log_format = LogFormat::Json;
+		source.logging.maptos_log_level = LogLevel::Debug;
+
+		let vars = source.to_env_vars(false);
+		for (var, value) in &vars {
+			if value != REDACTED {
+				std::env::set_var(var, value);
+			}
+		}
+		let mut restored = Config::default();
+		for (var, _) in &vars {
+			std::env::remove_var(var);
+		}
+
+		// Secrets and fields without an env var are not reproduced.
+		restored.chain.maptos_private_key = source.chain.maptos_private_key.clone();
+		restored.chain.maptos_db_path = source.chain.maptos_db_path.clone();
+		restored.faucet.faucet_private_key = source.faucet.faucet_private_key.clone();
+		restored.indexer_processor.postgres_connection_string =
+			source.indexer_processor.postgres_connection_string.clone();
+		restored.indexer_processor.indexer_processor_auth_token =
+			source.indexer_processor.indexer_processor_auth_token.clone();
+		assert_eq!(restored, source);
+	}
+}
@@ -0,0 +1,627 @@
+//! Errors reported while loading the config, in place of panics or silently ignored values.
+
+use super::file::CONFIG_JSON_VAR;
+use super::indexer_processor::ProcessorSet;
+use super::provenance::Sources;
+use super::rest_routes::RestRouteSet;
+use super::rest_scopes::RestScopedTokens;
+use super::secrets::secret_provider;
+use super::validation::{ValidationError, ValidationIssue, ValidationReport};
+use super::{common, common::EnvParser, Config};
+
+/// The types of env var which [`Config::try_load`] checks with an error of their own.
+const CHECKED_BY_TRY_LOAD: &[&str] = &["u16", "ProcessorSet", "RestRouteSet", "RestScopedTokens"];
+
+/// A config value which cannot be used.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ConfigError {
+	#[error("invalid private key in {var}: {reason}")]
+	InvalidPrivateKey { var: &'static str, reason: String },
+
+	#[error(
+		"no private key configured; set MAPTOS_PRIVATE_KEY, MAPTOS_PRIVATE_KEY_FILE \
+		or MAPTOS_PRIVATE_KEY_SEED, or set MAPTOS_ALLOW_EPHEMERAL_PRIVATE_KEY=true to generate a random one"
+	)]
+	MissingPrivateKey,
+
+	#[error(
+		"invalid MAPTOS_CHAIN_ID {raw:?}, expected a number or one of: {}",
+		common::CHAIN_ID_ALIASES.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(", ")
+	)]
+	InvalidChainId { raw: String },
+
+	#[error("invalid {var} {raw:?}, expected a port between 0 and 65535")]
+	InvalidPort { var: &'static str, raw: String },
+
+	#[error("invalid indexer auth token: {reason}")]
+	InvalidAuthToken { reason: String },
+
+	#[error("invalid address {entry:?} in {var}: {reason}")]
+	InvalidAddress { var: &'static str, entry: String, reason: String },
+
+	#[error("invalid INDEXER_PROCESSOR_ENABLED_PROCESSORS: {reason}")]
+	InvalidProcessors { reason: String },
+
+	#[error("invalid MAPTOS_API_METHOD_ALLOWLIST: {reason}")]
+	InvalidRestRoutes { reason: String },
+
+	/// Reported without the value, which holds the tokens.
+	#[error("invalid MAPTOS_REST_SCOPED_TOKENS: {reason}")]
+	InvalidScopedTokens { reason: String },
+
+	#[error("invalid {var} {raw:?}: {reason}")]
+	InvalidValue { var: &'static str, raw: String, reason: String },
+
+	#[error("unknown MAPTOS_SECRET_PROVIDER {name:?}, expected env or a registered provider")]
+	UnknownSecretProvider { name: String },
+
+	#[error("invalid MAPTOS_CONFIG_JSON: {reason}")]
+	InvalidConfigJson { reason: String },
+}
+
+/// Every [`ConfigError`] found by [`Config::try_load`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ConfigErrors {
+	pub errors: Vec<ConfigError>,
+}
+
+/// Lists one error per line.
+impl std::fmt::Display for ConfigErrors {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		for error in &self.errors {
+			writeln!(f, "{}", error)?;
+		}
+		Ok(())
+	}
+}
+
+impl std::error::Error for ConfigErrors {}
+
+/// Checks a port as `env_default!` would parse it; empty values count as unset.
+fn check_port(var: &'static str, raw: Option<String>) -> Result<(), ConfigError> {
+	match raw {
+		Some(raw) if !raw.is_empty() && raw.parse::<u16>().is_err() => {
+			Err(ConfigError::InvalidPort { var, raw })
+		}
+		_ => Ok(()),
+	}
+}
+
+/// Checks a value with the validation of its default, as [`env_parsers`](common::env_parsers)
+/// runs it; empty values count as unset.
+fn check_value(parser: &EnvParser, raw: Option<String>) -> Result<(), ConfigError> {
+	match raw {
+		Some(raw) if !raw.is_empty() => (parser.parse)(&raw)
+			.map_err(|reason| ConfigError::InvalidValue { var: parser.env_var, raw, reason }),
+		_ => Ok(()),
+	}
+}
+
+/// Keeps the value of `result`, or records its error.
+fn collect<T>(errors: &mut Vec<ConfigError>, result: Result<T, ConfigError>) -> Option<T> {
+	result.map_err(|error| errors.push(error)).ok()
+}
+
+/// Checks every port env var, as parsed and then as validated by its default.
+fn check_ports(errors: &mut Vec<ConfigError>) {
+	for parser in common::env_parsers() {
+		if parser.type_name == "u16" {
+			let raw = godfig::env::var(parser.env_var).ok();
+			if collect(errors, check_port(parser.env_var, raw.clone())).is_some() {
+				collect(errors, check_value(&parser, raw));
+			}
+		}
+	}
+}
+
+/// Checks every secret as the defaults read it from the selected provider, reporting an unknown
+/// provider once in place of an error for each secret.
+fn check_secrets(errors: &mut Vec<ConfigError>) {
+	if let Err(error) = secret_provider() {
+		errors.push(error);
+		return;
+	}
+	let invalid_key = |var| {
+		move |e: anyhow::Error| ConfigError::InvalidPrivateKey { var, reason: format!("{:#}", e) }
+	};
+	collect(
+		errors,
+		common::maptos_private_key_from_env().map_err(invalid_key("MAPTOS_PRIVATE_KEY")),
+	);
+	collect(
+		errors,
+		common::faucet_private_key_from_env().map_err(invalid_key("FAUCET_PRIVATE_KEY")),
+	);
+	collect(
+		errors,
+		common::faucet_private_keys_from_env().map_err(invalid_key("FAUCET_PRIVATE_KEYS")),
+	);
+	collect(
+		errors,
+		common::indexer_processor_auth_token_from_env()
+			.map_err(|e| ConfigError::InvalidAuthToken { reason: format!("{:#}", e) }),
+	);
+}
+
+/// Checks the env vars the defaults parse as lists, which they would otherwise fall back from.
+fn check_lists(errors: &mut Vec<ConfigError>) {
+	// An address list which falls back to empty would let the faucet fund blocked addresses.
+	for var in ["FAUCET_BLOCKED_ADDRESSES", "FAUCET_ALLOWLIST_ADDRESSES"] {
+		collect(errors, common::try_address_list_from_env(var));
+	}
+	collect(errors, common::try_indexer_upstreams_from_env());
+	if let Ok(raw) = godfig::env::var("INDEXER_PROCESSOR_ENABLED_PROCESSORS") {
+		collect(
+			errors,
+			raw.parse::<ProcessorSet>()
+				.map_err(|e| ConfigError::InvalidProcessors { reason: format!("{:#}", e) }),
+		);
+	}
+}
+
+/// Checks the scoped REST tokens, whose default would otherwise fall back to none, leaving the
+/// routes they guard open.
+fn check_scoped_tokens(errors: &mut Vec<ConfigError>) {
+	if let Ok(raw) = godfig::env::var("MAPTOS_REST_SCOPED_TOKENS") {
+		collect(
+			errors,
+			raw.parse::<RestScopedTokens>()
+				.map_err(|e| ConfigError::InvalidScopedTokens { reason: format!("{:#}", e) }),
+		);
+	}
+}
+
+/// Checks the REST route allowlist, whose default would otherwise fall back to every route.
+fn check_rest_routes(errors: &mut Vec<ConfigError>) {
+	if let Ok(raw) = godfig::env::var("MAPTOS_API_METHOD_ALLOWLIST") {
+		collect(
+			errors,
+			raw.parse::<RestRouteSet>()
+				.map_err(|e| ConfigError::InvalidRestRoutes { reason: format!("{:#}", e) }),
+		);
+	}
+}
+
+/// The report of a config which could not be loaded.
+fn load_report(errors: Vec<ConfigError>) -> ValidationReport {
+	ValidationReport {
+		issues: errors
+			.into_iter()
+			.map(|error| ValidationIssue::Error(ValidationError::Load(error)))
+			.collect(),
+	}
+}
+
+impl Config {
+	/// Reads the config from the environment as [`Config::default`] does, but reports every
+	/// malformed private key, chain id, port, auth token, address, processor list, REST route
+	/// list and scoped REST token list, and every port its default rejects, instead of panicking
+	/// or falling back to the default.
+	///
+	/// When [`CONFIG_JSON_VAR`] is set, the config is instead loaded from it with
+	/// [`Config::from_json`], under the env vars which are set.
+	pub fn try_load() -> Result<Config, ConfigErrors> {
+		let mut errors = Vec::new();
+		if let Some(json) =
+			godfig::env::var(CONFIG_JSON_VAR).ok().filter(|json| !json.trim().is_empty())
+		{
+			check_ports(&mut errors);
+			let config = collect(
+				&mut errors,
+				Config::from_json(&json)
+					.map_err(|e| ConfigError::InvalidConfigJson { reason: format!("{:#}", e) }),
+			);
+			return match config {
+				Some(config) if errors.is_empty() => Ok(config),
+				_ => Err(ConfigErrors { errors }),
+			};
+		}
+		let chain_id = collect(&mut errors, common::try_default_maptos_chain_id());
+		let private_key = collect(&mut errors, common::try_default_maptos_private_key());
+		let faucet_private_key = collect(
+			&mut errors,
+			common::faucet_private_key_from_env().map_err(|e| ConfigError::InvalidPrivateKey {
+				var: "FAUCET_PRIVATE_KEY",
+				reason: format!("{:#}", e),
+			}),
+		);
+		let faucet_private_keys = collect(
+			&mut errors,
+			common::faucet_private_keys_from_env().map_err(|e| ConfigError::InvalidPrivateKey {
+				var: "FAUCET_PRIVATE_KEYS",
+				reason: format!("{:#}", e),
+			}),
+		);
+		collect(
+			&mut errors,
+			common::indexer_processor_auth_token_from_env()
+				.map_err(|e| ConfigError::InvalidAuthToken { reason: format!("{:#}", e) }),
+		);
+		check_lists(&mut errors);
+		check_rest_routes(&mut errors);
+		check_scoped_tokens(&mut errors);
+		check_ports(&mut errors);
+
+		match (chain_id, private_key, faucet_private_key, faucet_private_keys) {
+			(
+				Some(chain_id),
+				Some(private_key),
+				Some(faucet_private_key),
+				Some(faucet_private_keys),
+			) if errors.is_empty() => {
+				let mut builder = Config::builder()
+					.chain_id(chain_id)
+					.private_key(private_key)
+					.faucet_private_keys(faucet_private_keys);
+				if let Some(faucet_private_key) = faucet_private_key {
+					builder = builder.faucet_private_key(faucet_private_key);
+				}
+				Ok(builder.build())
+			}
+			_ => Err(ConfigErrors { errors }),
+		}
+	}
+}
+
+/// Parses every env var backing a default, reporting those [`Config::try_load`] does not check;
+/// empty values count as unset.
+fn check_env_values() -> Vec<ConfigError> {
+	common::env_parsers()
+		.into_iter()
+		.filter(|parser| !CHECKED_BY_TRY_LOAD.contains(&parser.type_name))
+		.filter_map(|parser| check_value(&parser, godfig::env::var(parser.env_var).ok()).err())
+		.collect()
+}
+
+impl Config {
+	/// Reads and checks the whole config up front, so that a binary fails before doing any work
+	/// rather than at the first bad value it happens to read: the recommended entry point for
+	/// binaries.
+	///
+	/// Every value is parsed as by [`Config::try_load`], as is every other env var, which the
+	/// defaults would otherwise ignore when malformed. Once every value parses, its hostnames
+	/// are tidied with [`Config::normalize`] and the config is checked with
+	/// [`Config::validate_all`]. All problems are reported together, and the
+	/// warnings of a config which is accepted are logged, as is where each setting not left at
+	/// its default was taken from.
+	pub fn load_strict() -> Result<Config, ValidationReport> {
+		let (config, mut errors) = match Config::try_load() {
+			Ok(config) => (Some(config), Vec::new()),
+			Err(ConfigErrors { errors }) => (None, errors),
+		};
+		errors.extend(check_env_values());
+		let mut config = match config {
+			Some(config) if errors.is_empty() => config,
+			_ => return Err(load_report(errors)),
+		};
+		config.normalize();
+		let report = config.validate_all();
+		if report.has_errors() {
+			return Err(report);
+		}
+		for issue in &report.issues {
+			tracing::warn!("{}", issue);
+		}
+		config.log_provenance();
+		Ok(config)
+	}
+
+	/// Checks a config which a binary deserialized, as from its config file, before it is used,
+	/// as [`Config::load_strict`] checks one read from the environment: the node and the
+	/// services run beside it load their config this way.
+	///
+	/// The defaults which filled in what the file left out cannot fail, so every env var they
+	/// read is checked here, as is every secret of the selected
+	/// [`SecretProvider`](super::secrets::SecretProvider), which must have been registered
+	/// before the config was deserialized. A config left with no private key of its own, which
+	/// `MAPTOS_ALLOW_EPHEMERAL_PRIVATE_KEY` did not let the defaults generate, is refused.
+	/// Then its hostnames are tidied with [`Config::normalize`] and the config is checked with
+	/// [`Config::validate_all`]. All problems are reported together, and the warnings of a
+	/// config which is accepted are logged.
+	///
+	/// When [`CONFIG_JSON_VAR`] is set, the config is loaded from it with [`Config::from_json`]
+	/// in place of the deserialized one, as [`Config::try_load`] does. Otherwise each field
+	/// which differs from its default is recorded as set by the file, for
+	/// [`Config::provenance`] and the checks which depend on it. The settings not left at their
+	/// default are logged with where they came from.
+	pub fn finish_loading(self) -> Result<Config, ValidationReport> {
+		let mut errors = Vec::new();
+		let config =
+			match godfig::env::var(CONFIG_JSON_VAR).ok().filter(|json| !json.trim().is_empty()) {
+				Some(json) => collect(
+					&mut errors,
+					Config::from_json(&json)
+						.map_err(|e| ConfigError::InvalidConfigJson { reason: format!("{:#}", e) }),
+				),
+				None => Some(self.with_recorded_sources()),
+			};
+		collect(&mut errors, common::try_default_maptos_chain_id());
+		check_secrets(&mut errors);
+		check_lists(&mut errors);
+		check_scoped_tokens(&mut errors);
+		check_rest_routes(&mut errors);
+		check_ports(&mut errors);
+		errors.extend(check_env_values());
+		// The defaults only stand a placeholder in for a key none may generate.
+		if config.as_ref().is_some_and(|config| config.chain.private_key_is_unconfigured()) {
+			errors.push(ConfigError::MissingPrivateKey);
+		}
+		let mut config = match config {
+			Some(config) if errors.is_empty() => config,
+			_ => return Err(load_report(errors)),
+		};
+		config.normalize();
+		let report = config.validate_all();
+		if report.has_errors() {
+			return Err(report);
+		}
+		for issue in &report.issues {
+			tracing::warn!("{}", issue);
+		}
+		config.log_provenance();
+		Ok(config)
+	}
+
+	/// This config with the sources of a config deserialized from a file, unless it recorded
+	/// its own.
+	fn with_recorded_sources(mut self) -> Config {
+		if self.sources.is_empty() {
+			self.sources = Sources::of_deserialized(&self);
+		}
+		self
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::config::signer::KeyScheme;
+	use aptos_crypto::{ed25519::Ed25519PrivateKey, Uniform};
+	use aptos_types::account_address::AccountAddress;
+
+	fn parser(var: &str) -> EnvParser {
+		common::env_parsers()
+			.into_iter()
+			.find(|parser| parser.env_var == var)
+			.expect("the env var should be registered")
+	}
+
+	#[test]
+	fn test_out_of_range_values_are_reported() {
+		let error = check_value(&parser("MAPTOS_API_CONNECTION_PORT"), Some("0".to_string()));
+		assert_eq!(
+			error,
+			Err(ConfigError::InvalidValue {
+				var: "MAPTOS_API_CONNECTION_PORT",
+				raw: "0".to_string(),
+				reason: "expected a port between 1 and 65535".to_string(),
+			})
+		);
+		assert_eq!(
+			error.unwrap_err().to_string(),
+			"invalid MAPTOS_API_CONNECTION_PORT \"0\": expected a port between 1 and 65535"
+		);
+		// Servers listening on port 0 are given a free one.
+		assert_eq!(check_value(&parser("MAPTOS_API_LISTEN_PORT"), Some("0".to_string())), Ok(()));
+
+		for var in ["MAPTOS_API_REQUEST_TIMEOUT_SEC", "MAPTOS_LEDGER_PRUNING_WINDOW"] {
+			assert!(matches!(
+				check_value(&parser(var), Some("0".to_string())),
+				Err(ConfigError::InvalidValue { .. })
+			));
+			assert_eq!(check_value(&parser(var), Some("30".to_string())), Ok(()));
+		}
+		assert_eq!(check_value(&parser("MAPTOS_LEDGER_PRUNING_WINDOW"), None), Ok(()));
+	}
+
+	#[test]
+	fn test_malformed_port_is_reported() {
+		assert_eq!(
+			check_port("MAPTOS_API_LISTEN_PORT", Some("70000".to_string())),
+			Err(ConfigError::InvalidPort {
+				var: "MAPTOS_API_LISTEN_PORT",
+				raw: "70000".to_string()
+			})
+		);
+		assert_eq!(check_port("MAPTOS_API_LISTEN_PORT", Some("30731".to_string())), Ok(()));
+		assert_eq!(check_port("MAPTOS_API_LISTEN_PORT", Some(String::new())), Ok(()));
+		assert_eq!(check_port("MAPTOS_API_LISTEN_PORT", None), Ok(()));
+	}
+
+	#[test]
+	fn test_malformed_chain_id_is_reported() {
+		assert_eq!(
+			common::chain_id_or_default(Some("suzuka".to_string())),
+			Err(ConfigError::InvalidChainId { raw: "suzuka".to_string() })
+		);
+		let error = ConfigError::InvalidChainId { raw: "suzuka".to_string() };
+		assert!(error.to_string().contains("mainnet, testnet, devnet, local"));
+	}
+
+	#[test]
+	fn test_address_lists_are_parsed() {
+		assert_eq!(
+			common::parse_address_list("FAUCET_BLOCKED_ADDRESSES", " 0x1, 0x2 ,,"),
+			Ok(vec![AccountAddress::ONE, AccountAddress::TWO])
+		);
+		assert_eq!(common::parse_address_list("FAUCET_BLOCKED_ADDRESSES", ""), Ok(Vec::new()));
+		assert!(matches!(
+			common::parse_address_list("FAUCET_ALLOWLIST_ADDRESSES", "0x1,0xnope"),
+			Err(ConfigError::InvalidAddress { var: "FAUCET_ALLOWLIST_ADDRESSES", entry, .. })
+				if entry == "0xnope"
+		));
+	}
+
+	#[test]
+	fn test_load_strict_reports_every_bad_value() {
+		let _env = godfig::env::lock();
+		let bad = [
+			("MAPTOS_HEALTH_LISTEN_PORT", "70000"),
+			("MAPTOS_DNS_REFRESH_SEC", "often"),
+			("MAPTOS_COMMIT_BATCH_MAX_DELAY_MS", "-5"),
+			("MAPTOS_METRICS_ENABLED", "yes"),
+			("MAPTOS_LOG_FORMAT", "xml"),
+		];
+		for (var, raw) in bad {
+			std::env::set_var(var, raw);
+		}
+		let report = Config::load_strict().unwrap_err();
+		for (var, _) in bad {
+			std::env::remove_var(var);
+		}
+
+		let reported: Vec<&str> = report
+			.issues
+			.iter()
+			.filter_map(|issue| match issue {
+				ValidationIssue::Error(ValidationError::Load(ConfigError::InvalidPort {
+					var,
+					..
+				}))
+				| ValidationIssue::Error(ValidationError::Load(ConfigError::InvalidValue {
+					var,
+					..
+				})) => Some(*var),
+				_ => None,
+			})
+			.collect();
+		for (var, _) in bad {
+			assert_eq!(reported.iter().filter(|reported| **reported == var).count(), 1, "{}", var);
+		}
+		assert!(report.has_errors());
+		assert!(report.to_string().contains("invalid MAPTOS_LOG_FORMAT \"xml\""), "{}", report);
+	}
+
+	#[test]
+	fn test_unknown_secret_provider_fails_loading() {
+		let _env = godfig::env::lock();
+		std::env::set_var("MAPTOS_SECRET_PROVIDER", "vault");
+		// The defaults cannot report it, so they leave the secrets unset.
+		let config = Config::default();
+		let report = config.finish_loading().unwrap_err();
+		std::env::remove_var("MAPTOS_SECRET_PROVIDER");
+
+		assert!(report.issues.contains(&ValidationIssue::Error(ValidationError::Load(
+			ConfigError::UnknownSecretProvider { name: "vault".to_string() }
+		))));
+		assert!(report.has_errors());
+	}
+
+	#[test]
+	fn test_malformed_scoped_tokens_fail_loading() {
+		let _env = godfig::env::lock();
+		std::env::set_var("MAPTOS_REST_SCOPED_TOKENS", "no-scope-given");
+		let config = Config::default();
+		let report = config.finish_loading().unwrap_err();
+		std::env::remove_var("MAPTOS_REST_SCOPED_TOKENS");
+
+		assert!(report.issues.iter().any(|issue| matches!(
+			issue,
+			ValidationIssue::Error(ValidationError::Load(ConfigError::InvalidScopedTokens { .. }))
+		)));
+		// The value holds the tokens, so it is not reported.
+		assert!(!report.to_string().contains("no-scope-given"), "{}", report);
+	}
+
+	#[test]
+	fn test_malformed_route_allowlist_fails_loading() {
+		let _env = godfig::env::lock();
+		std::env::set_var("MAPTOS_API_METHOD_ALLOWLIST", "view,everything");
+		let config = Config::default();
+		let report = config.finish_loading().unwrap_err();
+		std::env::remove_var("MAPTOS_API_METHOD_ALLOWLIST");
+
+		assert!(report.issues.iter().any(|issue| matches!(
+			issue,
+			ValidationIssue::Error(ValidationError::Load(ConfigError::InvalidRestRoutes { .. }))
+		)));
+	}
+
+	#[test]
+	fn test_missing_private_key_fails_loading_unless_ephemeral_keys_are_allowed() {
+		let _env = godfig::env::lock();
+		for var in ["MAPTOS_PRIVATE_KEY", "MAPTOS_PRIVATE_KEY_FILE", "MAPTOS_PRIVATE_KEY_SEED"] {
+			std::env::remove_var(var);
+		}
+		let config = Config::default();
+		assert!(config.chain.private_key_is_unconfigured());
+		let report = config.finish_loading().unwrap_err();
+		assert!(report.issues.contains(&ValidationIssue::Error(ValidationError::Load(
+			ConfigError::MissingPrivateKey
+		))));
+
+		std::env::set_var("MAPTOS_ALLOW_EPHEMERAL_PRIVATE_KEY", "true");
+		let config = Config::default();
+		std::env::remove_var("MAPTOS_ALLOW_EPHEMERAL_PRIVATE_KEY");
+		assert!(!config.chain.private_key_is_unconfigured());
+
+		// A local network generates the key it was set up without.
+		let mut config = Config::default();
+		assert!(config.chain.generate_private_key_if_unconfigured());
+		assert!(!config.chain.private_key_is_unconfigured());
+		assert!(!config.chain.generate_private_key_if_unconfigured());
+	}
+
+	#[test]
+	fn test_malformed_blocklist_fails_loading() {
+		let _env = godfig::env::lock();
+		std::env::set_var("FAUCET_BLOCKED_ADDRESSES", "0x1,0xnope");
+		let config = Config::default();
+		let report = config.finish_loading().unwrap_err();
+		std::env::remove_var("FAUCET_BLOCKED_ADDRESSES");
+
+		assert!(report.issues.iter().any(|issue| matches!(
+			issue,
+			ValidationIssue::Error(ValidationError::Load(ConfigError::InvalidAddress {
+				var: "FAUCET_BLOCKED_ADDRESSES",
+				..
+			}))
+		)));
+	}
+
+	#[test]
+	fn test_config_json_replaces_the_deserialized_config() {
+		let _env = godfig::env::lock();
+		let json = r#"{
+			"chain": {
+				"private_key": "0x0000000000000000000000000000000000000000000000000000000000000001"
+			},
+			"fin": { "listen_port": 40010 }
+		}"#;
+		std::env::set_var(CONFIG_JSON_VAR, json);
+		let config = Config::default().finish_loading();
+		std::env::set_var(CONFIG_JSON_VAR, r#"{"fin": {"listen_prot": 40010}}"#);
+		let report = Config::default().finish_loading().unwrap_err();
+		std::env::remove_var(CONFIG_JSON_VAR);
+
+		assert_eq!(config.expect("the JSON config is valid").fin.fin_rest_listen_port, 40010);
+		assert!(report.issues.iter().any(|issue| matches!(
+			issue,
+			ValidationIssue::Error(ValidationError::Load(ConfigError::InvalidConfigJson { .. }))
+		)));
+	}
+
+	#[test]
+	fn test_deserialized_hostnames_are_normalized() {
+		let _env = godfig::env::lock();
+		std::env::remove_var(CONFIG_JSON_VAR);
+		let mut config = Config::default();
+		config.chain.maptos_private_key = Ed25519PrivateKey::generate(&mut rand::thread_rng());
+		config.fin.fin_rest_listen_hostname = " HTTP://LocalHost/ ".to_string();
+
+		let config = config.finish_loading().expect("the config is valid");
+		assert_eq!(config.fin.fin_rest_listen_hostname, "localhost");
+	}
+
+	#[test]
+	fn test_malformed_private_key_is_reported() {
+		assert!(matches!(
+			common::configured_private_key(
+				Some("0xnot-a-key".to_string()),
+				None,
+				KeyScheme::Ed25519
+			),
+			Err(ConfigError::InvalidPrivateKey { var: "MAPTOS_PRIVATE_KEY", .. })
+		));
+	}
+}
@@ -1,9 +1,24 @@
 use super::common::{
+	default_faucet_allowlist_addresses, default_faucet_blocked_addresses,
+	default_faucet_cooldown_sec, default_faucet_max_amount_per_request, default_faucet_mint_amount,
+	default_faucet_private_key, default_faucet_private_keys,
+	default_faucet_rate_limit_per_ip_per_hour, default_faucet_require_captcha,
+	default_faucet_txn_expiration_sec, default_maptos_faucet_enabled,
+	default_maptos_faucet_rest_base_path, default_maptos_faucet_rest_cors_allowed_origins,
+	default_maptos_faucet_rest_idle_timeout_sec, default_maptos_faucet_rest_listen_backlog,
 	default_maptos_faucet_rest_listen_hostname, default_maptos_faucet_rest_listen_port,
+	default_maptos_faucet_rest_max_body_bytes, default_maptos_faucet_rest_max_connections,
 	default_maptos_rest_connection_hostname, default_maptos_rest_connection_port,
 };
+use super::duration::DurationSecs;
+use super::endpoints::{normalize_base_path, ConnectionLimits};
+use aptos_crypto::ed25519::Ed25519PrivateKey;
+use aptos_types::account_address::AccountAddress;
 use serde::{Deserialize, Serialize};
 
+use std::num::{NonZeroU32, NonZeroU64};
+use std::time::Duration;
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Config {
 	/// The URL of the Aptos REST server
@@ -21,6 +36,75 @@ pub struct Config {
 	/// The port of the Aptos REST server
 	#[serde(default = "default_maptos_faucet_rest_listen_port")]
 	pub maptos_faucet_rest_listen_port: u16,
+
+	/// The origins allowed to call the Aptos Faucet server, `*` for any
+	#[serde(default = "default_maptos_faucet_rest_cors_allowed_origins")]
+	pub maptos_faucet_rest_cors_allowed_origins: Vec<String>,
+
+	/// The path prefix the Aptos Faucet server routes are served under, empty for the root
+	#[serde(default = "default_maptos_faucet_rest_base_path")]
+	pub maptos_faucet_rest_base_path: String,
+
+	/// The largest request body accepted by the Aptos Faucet server, in bytes
+	#[serde(default = "default_maptos_faucet_rest_max_body_bytes")]
+	pub maptos_faucet_rest_max_body_bytes: u64,
+
+	/// How long a faucet connection may sit idle before it is closed, 0 to keep it open
+	#[serde(default = "default_maptos_faucet_rest_idle_timeout_sec")]
+	pub maptos_faucet_rest_idle_timeout_sec: DurationSecs,
+
+	/// The cap on concurrent faucet connections, 0 for unlimited
+	#[serde(default = "default_maptos_faucet_rest_max_connections")]
+	pub maptos_faucet_rest_max_connections: u32,
+
+	/// The faucet listen backlog, 0 for the system maximum
+	#[serde(default = "default_maptos_faucet_rest_listen_backlog")]
+	pub maptos_faucet_rest_listen_backlog: u32,
+
+	/// The requests the Aptos Faucet server accepts per client IP per hour, 0 for no limit
+	#[serde(default = "default_faucet_rate_limit_per_ip_per_hour")]
+	pub faucet_rate_limit_per_ip_per_hour: u32,
+
+	/// The largest amount a single request to the Aptos Faucet server may ask for, 0 for no cap
+	#[serde(default = "default_faucet_max_amount_per_request")]
+	pub faucet_max_amount_per_request: u64,
+
+	/// The amount the Aptos Faucet server funds when a request names none, in octas
+	#[serde(default = "default_faucet_mint_amount")]
+	pub faucet_mint_amount: u64,
+
+	/// How long a faucet transaction may wait for inclusion before it expires
+	#[serde(default = "default_faucet_txn_expiration_sec")]
+	pub faucet_txn_expiration_sec: DurationSecs,
+
+	/// How long each client IP and funded address waits between requests, 0 for no cooldown
+	#[serde(default = "default_faucet_cooldown_sec")]
+	pub faucet_cooldown_sec: DurationSecs,
+
+	/// Whether requests must carry a captcha token which passes verification
+	#[serde(default = "default_faucet_require_captcha")]
+	pub faucet_require_captcha: bool,
+
+	/// The private key the Aptos Faucet server funds from, `None` to use the node's
+	#[serde(default = "default_faucet_private_key")]
+	pub faucet_private_key: Option<Ed25519PrivateKey>,
+
+	/// The private keys the Aptos Faucet server rotates across, none to fund from
+	/// `faucet_private_key` alone
+	#[serde(default = "default_faucet_private_keys")]
+	pub faucet_private_keys: Vec<Ed25519PrivateKey>,
+
+	/// The addresses the Aptos Faucet server refuses to fund
+	#[serde(default = "default_faucet_blocked_addresses")]
+	pub faucet_blocked_addresses: Vec<AccountAddress>,
+
+	/// The addresses funded without counting toward the rate limit
+	#[serde(default = "default_faucet_allowlist_addresses")]
+	pub faucet_allowlist_addresses: Vec<AccountAddress>,
+
+	/// Whether the Aptos Faucet server starts
+	#[serde(default = "default_maptos_faucet_enabled")]
+	pub maptos_faucet_enabled: bool,
 }
 
 impl Default for Config {
@@ -30,6 +114,145 @@ impl Default for Config {
 			maptos_rest_connection_port: default_maptos_rest_connection_port(),
 			maptos_faucet_rest_listen_hostname: default_maptos_faucet_rest_listen_hostname(),
 			maptos_faucet_rest_listen_port: default_maptos_faucet_rest_listen_port(),
+			maptos_faucet_rest_cors_allowed_origins:
+				default_maptos_faucet_rest_cors_allowed_origins(),
+			maptos_faucet_rest_base_path: default_maptos_faucet_rest_base_path(),
+			maptos_faucet_rest_max_body_bytes: default_maptos_faucet_rest_max_body_bytes(),
+			maptos_faucet_rest_idle_timeout_sec: default_maptos_faucet_rest_idle_timeout_sec(),
+			maptos_faucet_rest_max_connections: default_maptos_faucet_rest_max_connections(),
+			maptos_faucet_rest_listen_backlog: default_maptos_faucet_rest_listen_backlog(),
+			faucet_rate_limit_per_ip_per_hour: default_faucet_rate_limit_per_ip_per_hour(),
+			faucet_max_amount_per_request: default_faucet_max_amount_per_request(),
+			faucet_mint_amount: default_faucet_mint_amount(),
+			faucet_txn_expiration_sec: default_faucet_txn_expiration_sec(),
+			faucet_cooldown_sec: default_faucet_cooldown_sec(),
+			faucet_require_captcha: default_faucet_require_captcha(),
+			faucet_private_key: default_faucet_private_key(),
+			faucet_private_keys: default_faucet_private_keys(),
+			faucet_blocked_addresses: default_faucet_blocked_addresses(),
+			faucet_allowlist_addresses: default_faucet_allowlist_addresses(),
+			maptos_faucet_enabled: default_maptos_faucet_enabled(),
 		}
 	}
 }
+
+impl Config {
+	/// The prefix the faucet routes are served under, normalized without a trailing slash:
+	/// empty to serve them at the root.
+	///
+	/// An invalid prefix, which validation rejects, serves them at the root.
+	pub fn base_path(&self) -> &str {
+		normalize_base_path(&self.maptos_faucet_rest_base_path).unwrap_or_default()
+	}
+
+	/// The faucet request body limit, saturating on targets where it exceeds the address space.
+	pub fn max_body_bytes(&self) -> usize {
+		usize::try_from(self.maptos_faucet_rest_max_body_bytes).unwrap_or(usize::MAX)
+	}
+
+	/// How long a faucet connection may sit idle before it is closed, or `None` to keep idle
+	/// connections open.
+	pub fn idle_timeout(&self) -> Option<Duration> {
+		(!self.maptos_faucet_rest_idle_timeout_sec.is_zero())
+			.then(|| self.maptos_faucet_rest_idle_timeout_sec.get())
+	}
+
+	/// The admission limits of the faucet listener.
+	pub fn connection_limits(&self) -> ConnectionLimits {
+		ConnectionLimits::new(
+			self.maptos_faucet_rest_max_connections,
+			self.maptos_faucet_rest_listen_backlog,
+		)
+	}
+
+	/// The requests accepted per client IP per hour, or `None` when rate limiting is disabled.
+	pub fn rate_limit_per_ip_per_hour(&self) -> Option<NonZeroU32> {
+		NonZeroU32::new(self.faucet_rate_limit_per_ip_per_hour)
+	}
+
+	/// The largest amount a single request may ask for, or `None` when amounts are not capped.
+	pub fn max_amount_per_request(&self) -> Option<NonZeroU64> {
+		NonZeroU64::new(self.faucet_max_amount_per_request)
+	}
+
+	/// The wait between requests of a client IP or for an address, or `None` when disabled.
+	pub fn cooldown(&self) -> Option<Duration> {
+		(!self.faucet_cooldown_sec.is_zero()).then(|| self.faucet_cooldown_sec.get())
+	}
+}
+
+impl super::Config {
+	/// How long past the current ledger time a faucet transaction stays valid for inclusion.
+	pub fn faucet_txn_expiration(&self) -> Duration {
+		self.faucet.faucet_txn_expiration_sec.get()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_txn_expiration_default() {
+		let _env = godfig::env::lock();
+		std::env::remove_var("FAUCET_TXN_EXPIRATION_SEC");
+		assert_eq!(default_faucet_txn_expiration_sec(), DurationSecs::from_secs(30));
+		assert_eq!(
+			super::super::Config::default().faucet_txn_expiration(),
+			Duration::from_secs(30)
+		);
+
+		std::env::set_var("FAUCET_TXN_EXPIRATION_SEC", "2m");
+		assert_eq!(default_faucet_txn_expiration_sec().get(), Duration::from_secs(120));
+		std::env::remove_var("FAUCET_TXN_EXPIRATION_SEC");
+	}
+
+	#[test]
+	fn test_zero_disables_limits() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.faucet_rate_limit_per_ip_per_hour = 0;
+		config.faucet_max_amount_per_request = 0;
+		assert_eq!(config.rate_limit_per_ip_per_hour(), None);
+		assert_eq!(config.max_amount_per_request(), None);
+
+		config.faucet_rate_limit_per_ip_per_hour = 5;
+		config.faucet_max_amount_per_request = 100_000_000;
+		assert_eq!(config.rate_limit_per_ip_per_hour(), NonZeroU32::new(5));
+		assert_eq!(config.max_amount_per_request(), NonZeroU64::new(100_000_000));
+	}
+
+	#[test]
+	fn test_cooldown_is_disabled_by_default() {
+		let _env = godfig::env::lock();
+		std::env::remove_var("FAUCET_COOLDOWN_SEC");
+		let mut config = Config::default();
+		assert_eq!(config.cooldown(), None);
+
+		config.faucet_cooldown_sec = DurationSecs::from_secs(90);
+		assert_eq!(config.cooldown(), Some(Duration::from_secs(90)));
+	}
+
+	#[test]
+	fn test_zero_idle_timeout_keeps_connections_open() {
+		let _env = godfig::env::lock();
+		std::env::set_var("FAUCET_API_IDLE_TIMEOUT_SEC", "2m");
+		assert_eq!(default_maptos_faucet_rest_idle_timeout_sec(), DurationSecs::from_secs(120));
+		let mut config = Config::default();
+		std::env::remove_var("FAUCET_API_IDLE_TIMEOUT_SEC");
+		assert_eq!(config.idle_timeout(), Some(Duration::from_secs(120)));
+
+		config.maptos_faucet_rest_idle_timeout_sec = DurationSecs::ZERO;
+		assert_eq!(config.idle_timeout(), None);
+	}
+
+	#[test]
+	fn test_rate_limit_is_read_from_env() {
+		let _env = godfig::env::lock();
+		std::env::set_var("FAUCET_RATE_LIMIT_PER_IP_PER_HOUR", "12");
+		assert_eq!(default_faucet_rate_limit_per_ip_per_hour(), 12);
+		std::env::set_var("FAUCET_RATE_LIMIT_PER_IP_PER_HOUR", "-1");
+		assert_eq!(default_faucet_rate_limit_per_ip_per_hour(), 0);
+		std::env::remove_var("FAUCET_RATE_LIMIT_PER_IP_PER_HOUR");
+	}
+}
@@ -0,0 +1,138 @@
+//! Flags switching experimental node behaviors on and off without rebuilding the node.
+
+use super::Config;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::str::FromStr;
+
+/// The experimental behaviors a node may switch on, given as a comma-separated list of
+/// `name=true` or `name=false` entries for `MAPTOS_FEATURE_FLAGS`.
+///
+/// A flag left out is off. Names the node does not know are kept, so that a flag can be set
+/// ahead of the release reading it, and are off unless set.
+///
+/// The flags are only read here: no behavior of the node is switched by one yet.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FeatureFlags(BTreeMap<String, bool>);
+
+impl FeatureFlags {
+	/// Reserved for running view functions in parallel; nothing reads it yet.
+	pub const PARALLEL_VIEW: &'static str = "parallel_view";
+	/// Reserved for charging gas under a revised schedule; nothing reads it yet.
+	pub const NEW_GAS: &'static str = "new_gas";
+
+	/// Whether the flag `name` is on, which it is only when set to `true`.
+	pub fn is_enabled(&self, name: &str) -> bool {
+		self.0.get(name).copied().unwrap_or(false)
+	}
+
+	pub fn parallel_view(&self) -> bool {
+		self.is_enabled(Self::PARALLEL_VIEW)
+	}
+
+	pub fn new_gas(&self) -> bool {
+		self.is_enabled(Self::NEW_GAS)
+	}
+
+	/// The flags which are set, on or off, in order of their names.
+	pub fn iter(&self) -> impl Iterator<Item = (&str, bool)> + '_ {
+		self.0.iter().map(|(name, enabled)| (name.as_str(), *enabled))
+	}
+}
+
+impl FromStr for FeatureFlags {
+	type Err = anyhow::Error;
+
+	fn from_str(raw: &str) -> Result<Self, Self::Err> {
+		let mut flags = BTreeMap::new();
+		for entry in raw.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+			let malformed = || {
+				anyhow::anyhow!(
+					"invalid feature flag {:?}, expected name=true or name=false",
+					entry
+				)
+			};
+			let (name, value) = entry.split_once('=').ok_or_else(malformed)?;
+			let name = name.trim().to_ascii_lowercase();
+			if name.is_empty() || !name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+				return Err(malformed());
+			}
+			let enabled = match value.trim().to_ascii_lowercase().as_str() {
+				"true" => true,
+				"false" => false,
+				_ => return Err(malformed()),
+			};
+			flags.insert(name, enabled);
+		}
+		Ok(FeatureFlags(flags))
+	}
+}
+
+impl fmt::Display for FeatureFlags {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let entries: Vec<String> =
+			self.iter().map(|(name, enabled)| format!("{}={}", name, enabled)).collect();
+		f.write_str(&entries.join(","))
+	}
+}
+
+impl Serialize for FeatureFlags {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_str(self)
+	}
+}
+
+impl<'de> Deserialize<'de> for FeatureFlags {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+	}
+}
+
+impl Config {
+	/// The experimental behaviors switched on or off by `MAPTOS_FEATURE_FLAGS`.
+	pub fn features(&self) -> &FeatureFlags {
+		&self.chain.maptos_feature_flags
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_several_flags_parse() -> Result<(), anyhow::Error> {
+		let flags: FeatureFlags = " parallel_view=true, NEW_GAS=False,,".parse()?;
+		assert!(flags.parallel_view());
+		assert!(!flags.new_gas());
+		assert_eq!(flags.to_string(), "new_gas=false,parallel_view=true");
+		assert_eq!(flags.to_string().parse::<FeatureFlags>()?, flags);
+		Ok(())
+	}
+
+	#[test]
+	fn test_unset_flags_are_off() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::read_lock();
+		assert!(!Config::default().features().parallel_view());
+
+		let flags: FeatureFlags = "future_flag=true".parse()?;
+		assert!(flags.is_enabled("future_flag"));
+		assert!(!flags.parallel_view());
+		assert!(!flags.is_enabled("never_set"));
+		Ok(())
+	}
+
+	#[test]
+	fn test_malformed_entries_are_named() {
+		for (raw, token) in [
+			("parallel_view=true,new_gas", "new_gas"),
+			("parallel_view=yes", "parallel_view=yes"),
+			("=true", "=true"),
+		] {
+			let err = raw.parse::<FeatureFlags>().unwrap_err().to_string();
+			assert!(err.contains(&format!("{:?}", token)), "{} for {:?}", err, raw);
+		}
+	}
+}
@@ -0,0 +1,1446 @@
+//! Loading the Maptos config from a TOML file, or in the same shape from JSON held by
+//! [`CONFIG_JSON_VAR`] for platforms which inject one secret more easily than many env vars.
+//!
+//! The file groups settings by service rather than mirroring the in-memory [`Config`] layout.
+//! Every key is optional. For each field the value is resolved in this order:
+//!
+//! 1. the environment variable backing the field's default, when it is set;
+//! 2. the value in the file, when present;
+//! 3. the built-in default.
+//!
+//! The private key has no built-in default: without one, loading fails
+//! unless `MAPTOS_ALLOW_EPHEMERAL_PRIVATE_KEY` permits generating a random key.
+
+use super::chain::{AddressFormat, RestEncoding, SyncMode};
+use super::client::IndexerUpstream;
+use super::clock::ClockKind;
+use super::commit_batch::FsyncPolicy;
+use super::duration::DurationSecs;
+use super::features::FeatureFlags;
+use super::health::HealthMode;
+use super::indexer_processor::ProcessorSet;
+use super::load_shedding::{MempoolOrdering, OverflowPolicy};
+use super::rest_routes::RestRouteSet;
+use super::rest_scopes::RestScopedTokens;
+use super::secrets::{optional_secret, secret_provider};
+use super::signer::{decode_private_key, KeyScheme};
+use super::{
+	chain, client, common, faucet, fin, health, indexer, indexer_processor, load_shedding,
+	logging::{self, LogFormat, LogLevel, SampleRate},
+	metrics, ports, provenance, Config,
+};
+
+use anyhow::Context;
+use aptos_crypto::{ed25519::Ed25519PrivateKey, ValidCryptoMaterialStringExt};
+use aptos_types::account_address::AccountAddress;
+use serde::{Deserialize, Serialize};
+
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+
+/// The env var holding the whole config as JSON, in the shape of the config file.
+pub const CONFIG_JSON_VAR: &str = "MAPTOS_CONFIG_JSON";
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ConfigFile {
+	pub chain: ChainSection,
+	pub rest: RestSection,
+	pub faucet: FaucetSection,
+	pub fin: FinSection,
+	pub indexer_grpc: IndexerGrpcSection,
+	pub pruning: PruningSection,
+	pub postgres: PostgresSection,
+	pub processor: ProcessorSection,
+	pub load_shedding: LoadSheddingSection,
+	pub health: HealthSection,
+	pub metrics: MetricsSection,
+	pub logging: LoggingSection,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ChainSection {
+	/// Parsed the same way as `MAPTOS_CHAIN_ID`.
+	pub chain_id: Option<String>,
+	/// Parsed the same way as `MAPTOS_PRIVATE_KEY`.
+	pub private_key: Option<String>,
+	pub data_dir: Option<PathBuf>,
+	/// Relative to `data_dir` unless absolute.
+	pub db_path: Option<PathBuf>,
+	pub db_block_cache_bytes: Option<u64>,
+	pub db_max_wal_bytes: Option<u64>,
+	/// 0 leaves the disk the databases use unlimited.
+	pub max_db_size_bytes: Option<u64>,
+	pub commit_batch_size: Option<u32>,
+	pub commit_batch_max_delay_ms: Option<u64>,
+	/// `always`, `batch` or `never`.
+	pub fsync_policy: Option<FsyncPolicy>,
+	/// 0 runs one execution thread per CPU.
+	pub execution_concurrency: Option<u32>,
+	pub genesis_blob_path: Option<PathBuf>,
+	pub framework_path: Option<PathBuf>,
+	pub shutdown_grace_sec: Option<DurationSecs>,
+	/// 0 resolves connection hostnames on every dial.
+	pub dns_refresh_sec: Option<DurationSecs>,
+	pub stop_at_version: Option<u64>,
+	pub read_only: Option<bool>,
+	pub allow_chain_id_mismatch: Option<bool>,
+	pub gas_unit_price: Option<u64>,
+	/// `system`, or `manual` for tests.
+	pub clock: Option<ClockKind>,
+	/// `fast` or `full`.
+	pub sync_mode: Option<SyncMode>,
+	/// `ed25519` or `secp256k1`, the scheme `private_key` is a key of.
+	pub key_scheme: Option<KeyScheme>,
+	/// Parsed the same way as `MAPTOS_FEATURE_FLAGS`.
+	pub feature_flags: Option<FeatureFlags>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RestSection {
+	pub listen_hostname: Option<String>,
+	pub listen_port: Option<u16>,
+	pub tls_cert_path: Option<String>,
+	pub tls_key_path: Option<String>,
+	pub cors_allowed_origins: Option<Vec<String>>,
+	pub base_path: Option<String>,
+	/// `all`, or the comma-separated route groups served.
+	pub method_allowlist: Option<RestRouteSet>,
+	/// `token:scope1|scope2` entries, comma-separated.
+	pub scoped_tokens: Option<RestScopedTokens>,
+	pub default_encoding: Option<RestEncoding>,
+	pub address_format: Option<AddressFormat>,
+	pub request_timeout_sec: Option<DurationSecs>,
+	/// 0 keeps idle connections open.
+	pub idle_timeout_sec: Option<DurationSecs>,
+	pub max_body_bytes: Option<u64>,
+	pub max_gas_amount: Option<u64>,
+	pub max_txn_future_sec: Option<DurationSecs>,
+	pub max_page_size: Option<u32>,
+	pub max_events_per_response: Option<u32>,
+	pub max_transaction_bytes: Option<u64>,
+	pub max_connections: Option<u32>,
+	pub listen_backlog: Option<u32>,
+	/// From 0.0 to log no requests to 1.0 to log every one.
+	pub log_sample_rate: Option<SampleRate>,
+	/// 0 runs one worker thread per CPU.
+	pub worker_threads: Option<u32>,
+	pub enabled: Option<bool>,
+	pub expose_version: Option<bool>,
+	pub preload_enabled: Option<bool>,
+	pub preload_versions: Option<u64>,
+	pub connection_hostname: Option<String>,
+	pub connection_port: Option<u16>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FaucetSection {
+	pub listen_hostname: Option<String>,
+	pub listen_port: Option<u16>,
+	pub cors_allowed_origins: Option<Vec<String>>,
+	pub base_path: Option<String>,
+	pub max_body_bytes: Option<u64>,
+	/// 0 keeps idle connections open.
+	pub idle_timeout_sec: Option<DurationSecs>,
+	pub max_connections: Option<u32>,
+	pub listen_backlog: Option<u32>,
+	pub rate_limit_per_ip_per_hour: Option<u32>,
+	pub max_amount_per_request: Option<u64>,
+	pub mint_amount: Option<u64>,
+	pub txn_expiration_sec: Option<DurationSecs>,
+	pub cooldown_sec: Option<DurationSecs>,
+	pub require_captcha: Option<bool>,
+	/// Parsed the same way as `FAUCET_PRIVATE_KEY`; unset to fund from `chain.private_key`.
+	pub private_key: Option<String>,
+	/// Parsed the same way as `FAUCET_PRIVATE_KEYS`, comma-separated.
+	pub private_keys: Option<String>,
+	pub blocked_addresses: Option<Vec<AccountAddress>>,
+	pub allowlist_addresses: Option<Vec<AccountAddress>>,
+	pub enabled: Option<bool>,
+	pub connection_hostname: Option<String>,
+	pub connection_port: Option<u16>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct FinSection {
+	pub listen_hostname: Option<String>,
+	pub listen_port: Option<u16>,
+	pub connection_hostname: Option<String>,
+	pub connection_port: Option<u16>,
+	pub request_timeout_sec: Option<DurationSecs>,
+	pub max_connections: Option<u32>,
+	pub listen_backlog: Option<u32>,
+	/// 0 runs one worker thread per CPU.
+	pub worker_threads: Option<u32>,
+	pub enabled: Option<bool>,
+	/// 0 leaves view function calls unlimited.
+	pub max_concurrent_views: Option<u32>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IndexerGrpcSection {
+	pub listen_hostname: Option<String>,
+	pub listen_port: Option<u16>,
+	pub connection_hostname: Option<String>,
+	pub connection_port: Option<u16>,
+	/// Empty connects to the single connection address.
+	pub upstreams: Option<Vec<IndexerUpstream>>,
+	pub inactivity_timeout_sec: Option<DurationSecs>,
+	pub ping_interval_sec: Option<DurationSecs>,
+	pub max_message_bytes: Option<u64>,
+	/// 0 disables TCP keepalive.
+	pub tcp_keepalive_sec: Option<DurationSecs>,
+	/// 0 disables HTTP/2 keepalive pings.
+	pub http2_keepalive_sec: Option<DurationSecs>,
+	pub tls_cert: Option<String>,
+	pub tls_key: Option<String>,
+	pub client_ca: Option<String>,
+	/// 0 serves no plaintext loopback listener.
+	pub loopback_port: Option<u16>,
+	pub auth_token: Option<String>,
+	/// 0 runs one worker thread per CPU.
+	pub worker_threads: Option<u32>,
+	pub enabled: Option<bool>,
+	/// 0 never reports the node not ready for indexer lag.
+	pub max_lag_versions: Option<u64>,
+	pub batch_size: Option<u32>,
+	pub send_buffer_size: Option<u32>,
+	pub compression: Option<indexer::GrpcCompression>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PruningSection {
+	/// `false` keeps every version, ignoring the windows.
+	pub enabled: Option<bool>,
+	pub ledger_window: Option<u64>,
+	pub state_merkle_window: Option<u64>,
+	pub epoch_snapshot_window: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct PostgresSection {
+	pub connection_string: Option<String>,
+	pub max_connections: Option<u32>,
+	pub connection_timeout_sec: Option<DurationSecs>,
+	pub connect_retries: Option<u32>,
+	pub connect_retry_delay_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ProcessorSection {
+	/// 0 resumes where the database left off.
+	pub starting_version: Option<u64>,
+	pub ending_version: Option<u64>,
+	/// Parsed the same way as `INDEXER_PROCESSOR_ENABLED_PROCESSORS`.
+	pub enabled_processors: Option<ProcessorSet>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoadSheddingSection {
+	pub max_transactions_in_flight: Option<u64>,
+	/// Parsed the same way as `MAPTOS_INFLIGHT_OVERFLOW_POLICY`.
+	pub overflow_policy: Option<OverflowPolicy>,
+	/// `fifo` or `gas_price`.
+	pub mempool_ordering: Option<MempoolOrdering>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct HealthSection {
+	pub enabled: Option<bool>,
+	pub listen_hostname: Option<String>,
+	pub listen_port: Option<u16>,
+	/// One of `http` or `tcp`.
+	pub mode: Option<HealthMode>,
+	pub max_block_age_sec: Option<DurationSecs>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MetricsSection {
+	pub enabled: Option<bool>,
+	pub listen_hostname: Option<String>,
+	pub listen_port: Option<u16>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LoggingSection {
+	pub format: Option<LogFormat>,
+	pub level: Option<LogLevel>,
+	/// Empty to export no spans.
+	pub otlp_endpoint: Option<String>,
+	pub otlp_sample_rate: Option<SampleRate>,
+}
+
+/// Layers the file over the defaults field by field, recording the env vars of the fields
+/// the file set.
+#[derive(Default)]
+struct Layers {
+	from_file: BTreeSet<&'static str>,
+}
+
+impl Layers {
+	/// Picks the env value when `var` is set, otherwise the file value, otherwise the default.
+	///
+	/// `default` is one of the `common::default_*` functions, which read `var` themselves.
+	fn layer<T>(&mut self, var: &'static str, file: Option<T>, default: impl FnOnce() -> T) -> T {
+		match (godfig::env::var_os(var), file) {
+			(None, Some(value)) => self.from_file(var, value),
+			_ => default(),
+		}
+	}
+
+	/// Like [`Layers::layer`], but rejects an env value which does not parse instead of
+	/// ignoring it.
+	fn layer_strict<T>(
+		&mut self,
+		var: &'static str,
+		file: Option<T>,
+		default: impl FnOnce() -> T,
+	) -> Result<T, anyhow::Error>
+	where
+		T: std::str::FromStr<Err = anyhow::Error>,
+	{
+		match (godfig::env::var(var), file) {
+			(Ok(raw), _) => raw.parse().with_context(|| format!("invalid {}", var)),
+			(Err(_), Some(value)) => Ok(self.from_file(var, value)),
+			(Err(_), None) => Ok(default()),
+		}
+	}
+
+	/// Records that the file set the field of `var` to `value`.
+	fn from_file<T>(&mut self, var: &'static str, value: T) -> T {
+		self.from_file.insert(var);
+		value
+	}
+}
+
+impl ConfigFile {
+	/// Reads and parses a config file without resolving it.
+	pub fn read(path: &Path) -> Result<Self, anyhow::Error> {
+		let contents = std::fs::read_to_string(path)
+			.with_context(|| format!("failed to read config file {}", path.display()))?;
+		toml::from_str(&contents)
+			.with_context(|| format!("failed to parse config file {}", path.display()))
+	}
+
+	/// Parses a config given as JSON, in the shape of the config file, without resolving it.
+	pub fn from_json(json: &str) -> Result<Self, anyhow::Error> {
+		serde_json::from_str(json).context("failed to parse the config JSON")
+	}
+
+	/// Resolves the file against the environment and the built-in defaults.
+	pub fn resolve(self) -> Result<Config, anyhow::Error> {
+		let ConfigFile {
+			chain,
+			rest,
+			faucet,
+			fin,
+			indexer_grpc,
+			pruning,
+			postgres,
+			processor,
+			load_shedding,
+			health,
+			metrics,
+			logging,
+		} = self;
+
+		let mut layers = Layers::default();
+		let maptos_chain_id = match (godfig::env::var_os("MAPTOS_CHAIN_ID"), chain.chain_id) {
+			(None, Some(raw)) => layers.from_file(
+				"MAPTOS_CHAIN_ID",
+				common::parse_chain_id(&raw)
+					.with_context(|| format!("invalid chain.chain_id {:?}", raw))?,
+			),
+			_ => common::try_default_maptos_chain_id()?,
+		};
+		// Secrets held by the selected provider take precedence over the file, as env vars do.
+		let secrets = secret_provider()?;
+		let private_key_env_set = optional_secret(&*secrets, "MAPTOS_PRIVATE_KEY")?.is_some()
+			|| godfig::env::var_os("MAPTOS_PRIVATE_KEY_FILE").is_some();
+		let maptos_key_scheme = layers.layer_strict(
+			"MAPTOS_KEY_SCHEME",
+			chain.key_scheme,
+			common::default_maptos_key_scheme,
+		)?;
+		let maptos_private_key = match (private_key_env_set, chain.private_key) {
+			(false, Some(raw)) => layers.from_file(
+				"MAPTOS_PRIVATE_KEY",
+				decode_private_key(&raw, maptos_key_scheme).context("invalid chain.private_key")?,
+			),
+			_ => common::try_maptos_private_key(maptos_key_scheme)?,
+		};
+
+		let faucet_private_key_env_set =
+			optional_secret(&*secrets, "FAUCET_PRIVATE_KEY")?.is_some()
+				|| godfig::env::var_os("FAUCET_PRIVATE_KEY_FILE").is_some();
+		let faucet_private_key = match (faucet_private_key_env_set, faucet.private_key) {
+			(false, Some(raw)) => layers.from_file(
+				"FAUCET_PRIVATE_KEY",
+				Some(
+					Ed25519PrivateKey::from_encoded_string(&raw)
+						.context("invalid faucet.private_key")?,
+				),
+			),
+			_ => common::faucet_private_key_from_env()?,
+		};
+		let faucet_private_keys = match (
+			optional_secret(&*secrets, "FAUCET_PRIVATE_KEYS")?.is_some(),
+			faucet.private_keys,
+		) {
+			(false, Some(raw)) => layers.from_file(
+				"FAUCET_PRIVATE_KEYS",
+				common::parse_faucet_private_keys(&raw).context("invalid faucet.private_keys")?,
+			),
+			_ => common::faucet_private_keys_from_env()?,
+		};
+
+		let auth_token_env_set =
+			optional_secret(&*secrets, "INDEXER_PROCESSOR_AUTH_TOKEN")?.is_some()
+				|| godfig::env::var_os("INDEXER_PROCESSOR_AUTH_TOKEN_FILE").is_some();
+		let indexer_processor_auth_token = match (auth_token_env_set, indexer_grpc.auth_token) {
+			(false, Some(token)) => layers.from_file("INDEXER_PROCESSOR_AUTH_TOKEN", token),
+			_ => common::indexer_processor_auth_token_from_env()?
+				.unwrap_or_else(common::default_indexer_processor_auth_token),
+		};
+
+		Ok(Config {
+			chain: chain::Config {
+				maptos_chain_id,
+				maptos_rest_listen_hostname: layers.layer(
+					"MAPTOS_API_LISTEN_HOSTNAME",
+					rest.listen_hostname,
+					common::default_maptos_rest_listen_hostname,
+				),
+				maptos_rest_listen_port: layers.layer(
+					"MAPTOS_API_LISTEN_PORT",
+					rest.listen_port,
+					common::default_maptos_rest_listen_port,
+				),
+				maptos_rest_tls_cert_path: layers.layer(
+					"MAPTOS_API_TLS_CERT",
+					rest.tls_cert_path,
+					common::default_maptos_rest_tls_cert_path,
+				),
+				maptos_rest_tls_key_path: layers.layer(
+					"MAPTOS_API_TLS_KEY",
+					rest.tls_key_path,
+					common::default_maptos_rest_tls_key_path,
+				),
+				maptos_rest_cors_allowed_origins: layers.layer(
+					"MAPTOS_API_CORS_ORIGINS",
+					rest.cors_allowed_origins,
+					common::default_maptos_rest_cors_allowed_origins,
+				),
+				maptos_rest_base_path: layers.layer(
+					"MAPTOS_API_BASE_PATH",
+					rest.base_path,
+					common::default_maptos_rest_base_path,
+				),
+				maptos_rest_method_allowlist: layers.layer_strict(
+					"MAPTOS_API_METHOD_ALLOWLIST",
+					rest.method_allowlist,
+					common::default_maptos_rest_method_allowlist,
+				)?,
+				maptos_rest_scoped_tokens: layers.layer_strict(
+					"MAPTOS_REST_SCOPED_TOKENS",
+					rest.scoped_tokens,
+					common::default_maptos_rest_scoped_tokens,
+				)?,
+				maptos_rest_default_encoding: layers.layer_strict(
+					"MAPTOS_API_DEFAULT_ENCODING",
+					rest.default_encoding,
+					common::default_maptos_rest_default_encoding,
+				)?,
+				maptos_rest_address_format: layers.layer_strict(
+					"MAPTOS_API_ADDRESS_FORMAT",
+					rest.address_format,
+					common::default_maptos_rest_address_format,
+				)?,
+				maptos_rest_request_timeout_sec: layers.layer(
+					"MAPTOS_API_REQUEST_TIMEOUT_SEC",
+					rest.request_timeout_sec,
+					common::default_maptos_rest_request_timeout_sec,
+				),
+				maptos_rest_idle_timeout_sec: layers.layer(
+					"MAPTOS_API_IDLE_TIMEOUT_SEC",
+					rest.idle_timeout_sec,
+					common::default_maptos_rest_idle_timeout_sec,
+				),
+				maptos_rest_max_body_bytes: layers.layer(
+					"MAPTOS_API_MAX_BODY_BYTES",
+					rest.max_body_bytes,
+					common::default_maptos_rest_max_body_bytes,
+				),
+				maptos_rest_max_gas_amount: layers.layer(
+					"MAPTOS_API_MAX_GAS_AMOUNT",
+					rest.max_gas_amount,
+					common::default_maptos_rest_max_gas_amount,
+				),
+				maptos_max_txn_future_sec: layers.layer(
+					"MAPTOS_MAX_TXN_FUTURE_SEC",
+					rest.max_txn_future_sec,
+					common::default_maptos_max_txn_future_sec,
+				),
+				maptos_rest_max_page_size: layers.layer(
+					"MAPTOS_API_MAX_PAGE_SIZE",
+					rest.max_page_size,
+					common::default_maptos_rest_max_page_size,
+				),
+				maptos_rest_max_events_per_response: layers.layer(
+					"MAPTOS_API_MAX_EVENTS_PER_RESPONSE",
+					rest.max_events_per_response,
+					common::default_maptos_rest_max_events_per_response,
+				),
+				maptos_rest_max_transaction_bytes: layers.layer(
+					"MAPTOS_API_MAX_TRANSACTION_BYTES",
+					rest.max_transaction_bytes,
+					common::default_maptos_rest_max_transaction_bytes,
+				),
+				maptos_rest_max_connections: layers.layer(
+					"MAPTOS_API_MAX_CONNECTIONS",
+					rest.max_connections,
+					common::default_maptos_rest_max_connections,
+				),
+				maptos_rest_listen_backlog: layers.layer(
+					"MAPTOS_API_LISTEN_BACKLOG",
+					rest.listen_backlog,
+					common::default_maptos_rest_listen_backlog,
+				),
+				maptos_rest_log_sample_rate: layers.layer(
+					"MAPTOS_API_LOG_SAMPLE_RATE",
+					rest.log_sample_rate,
+					common::default_maptos_rest_log_sample_rate,
+				),
+				maptos_rest_worker_threads: layers.layer(
+					"MAPTOS_API_WORKER_THREADS",
+					rest.worker_threads,
+					common::default_maptos_rest_worker_threads,
+				),
+				maptos_rest_enabled: layers.layer(
+					"MAPTOS_REST_ENABLED",
+					rest.enabled,
+					common::default_maptos_rest_enabled,
+				),
+				maptos_expose_version: layers.layer(
+					"MAPTOS_EXPOSE_VERSION",
+					rest.expose_version,
+					common::default_maptos_expose_version,
+				),
+				maptos_preload_enabled: layers.layer(
+					"MAPTOS_PRELOAD_ENABLED",
+					rest.preload_enabled,
+					common::default_maptos_preload_enabled,
+				),
+				maptos_preload_versions: layers.layer(
+					"MAPTOS_PRELOAD_VERSIONS",
+					rest.preload_versions,
+					common::default_maptos_preload_versions,
+				),
+				maptos_shutdown_grace_sec: layers.layer(
+					"MAPTOS_SHUTDOWN_GRACE_SEC",
+					chain.shutdown_grace_sec,
+					common::default_maptos_shutdown_grace_sec,
+				),
+				maptos_stop_at_version: layers.layer(
+					"MAPTOS_STOP_AT_VERSION",
+					chain.stop_at_version.map(Some),
+					common::default_maptos_stop_at_version,
+				),
+				maptos_read_only: layers.layer(
+					"MAPTOS_READ_ONLY",
+					chain.read_only,
+					common::default_maptos_read_only,
+				),
+				maptos_allow_chain_id_mismatch: layers.layer(
+					"MAPTOS_ALLOW_CHAIN_ID_MISMATCH",
+					chain.allow_chain_id_mismatch,
+					common::default_maptos_allow_chain_id_mismatch,
+				),
+				maptos_gas_unit_price: layers.layer(
+					"MAPTOS_GAS_UNIT_PRICE",
+					chain.gas_unit_price,
+					common::default_maptos_gas_unit_price,
+				),
+				maptos_clock: layers.layer_strict(
+					"MAPTOS_CLOCK",
+					chain.clock,
+					common::default_maptos_clock,
+				)?,
+				manual_clock: Default::default(),
+				maptos_sync_mode: layers.layer_strict(
+					"MAPTOS_SYNC_MODE",
+					chain.sync_mode,
+					common::default_maptos_sync_mode,
+				)?,
+				maptos_key_scheme,
+				maptos_feature_flags: layers.layer_strict(
+					"MAPTOS_FEATURE_FLAGS",
+					chain.feature_flags,
+					common::default_maptos_feature_flags,
+				)?,
+				maptos_private_key,
+				signer: Default::default(),
+				maptos_enable_pruning: layers.layer(
+					"MAPTOS_ENABLE_PRUNING",
+					pruning.enabled,
+					common::default_maptos_enable_pruning,
+				),
+				maptos_ledger_prune_window: layers.layer(
+					"MAPTOS_LEDGER_PRUNING_WINDOW",
+					pruning.ledger_window,
+					common::default_maptos_ledger_prune_window,
+				),
+				maptos_epoch_snapshot_prune_window: layers.layer(
+					"MAPTOS_EPOCH_SNAPSHOT_PRUNING_WINDOW",
+					pruning.epoch_snapshot_window,
+					common::default_maptos_epoch_snapshot_prune_window,
+				),
+				maptos_state_merkle_prune_window: layers.layer(
+					"MAPTOS_STATE_MERKLE_PRUNING_WINDOW",
+					pruning.state_merkle_window,
+					common::default_maptos_state_merkle_prune_window,
+				),
+				maptos_data_dir: layers.layer(
+					"MAPTOS_DATA_DIR",
+					chain.data_dir,
+					common::default_maptos_data_dir,
+				),
+				maptos_db_path: chain.db_path,
+				maptos_db_block_cache_bytes: layers.layer(
+					"MAPTOS_DB_BLOCK_CACHE_BYTES",
+					chain.db_block_cache_bytes,
+					common::default_maptos_db_block_cache_bytes,
+				),
+				maptos_db_max_wal_bytes: layers.layer(
+					"MAPTOS_DB_MAX_WAL_BYTES",
+					chain.db_max_wal_bytes,
+					common::default_maptos_db_max_wal_bytes,
+				),
+				maptos_max_db_size_bytes: layers.layer(
+					"MAPTOS_MAX_DB_SIZE_BYTES",
+					chain.max_db_size_bytes,
+					common::default_maptos_max_db_size_bytes,
+				),
+				maptos_commit_batch_size: layers.layer(
+					"MAPTOS_COMMIT_BATCH_SIZE",
+					chain.commit_batch_size,
+					common::default_maptos_commit_batch_size,
+				),
+				maptos_commit_batch_max_delay_ms: layers.layer(
+					"MAPTOS_COMMIT_BATCH_MAX_DELAY_MS",
+					chain.commit_batch_max_delay_ms,
+					common::default_maptos_commit_batch_max_delay_ms,
+				),
+				maptos_fsync_policy: layers.layer_strict(
+					"MAPTOS_FSYNC_POLICY",
+					chain.fsync_policy,
+					common::default_maptos_fsync_policy,
+				)?,
+				maptos_execution_concurrency: layers.layer(
+					"MAPTOS_EXECUTION_CONCURRENCY",
+					chain.execution_concurrency,
+					common::default_maptos_execution_concurrency,
+				),
+				maptos_genesis_blob_path: layers.layer(
+					"MAPTOS_GENESIS_BLOB_PATH",
+					chain.genesis_blob_path.map(Some),
+					common::default_maptos_genesis_blob_path,
+				),
+				maptos_framework_path: layers.layer(
+					"MAPTOS_FRAMEWORK_PATH",
+					chain.framework_path.map(Some),
+					common::default_maptos_framework_path,
+				),
+			},
+			indexer: indexer::Config {
+				maptos_indexer_grpc_listen_hostname: layers.layer(
+					"MAPTOS_INDEXER_GRPC_LISTEN_HOSTNAME",
+					indexer_grpc.listen_hostname,
+					common::default_maptos_indexer_grpc_listen_hostname,
+				),
+				maptos_indexer_grpc_listen_port: layers.layer(
+					"MAPTOS_INDEXER_GRPC_LISTEN_PORT",
+					indexer_grpc.listen_port,
+					common::default_maptos_indexer_grpc_listen_port,
+				),
+				maptos_indexer_grpc_inactivity_timeout: layers.layer(
+					"MAPTOS_INDEXER_GRPC_INACTIVITY_TIMEOUT_SEC",
+					indexer_grpc.inactivity_timeout_sec,
+					common::default_maptos_indexer_grpc_inactivity_timeout,
+				),
+				maptos_indexer_grpc_inactivity_ping_interval: layers.layer(
+					"MAPTOS_INDEXER_GRPC_PING_INTERVAL_SEC",
+					indexer_grpc.ping_interval_sec,
+					common::default_maptos_indexer_grpc_ping_interval,
+				),
+				maptos_indexer_grpc_max_message_bytes: layers.layer(
+					"MAPTOS_INDEXER_GRPC_MAX_MESSAGE_BYTES",
+					indexer_grpc.max_message_bytes,
+					common::default_maptos_indexer_grpc_max_message_bytes,
+				),
+				maptos_indexer_grpc_tcp_keepalive_sec: layers.layer(
+					"MAPTOS_INDEXER_GRPC_TCP_KEEPALIVE_SEC",
+					indexer_grpc.tcp_keepalive_sec,
+					common::default_maptos_indexer_grpc_tcp_keepalive_sec,
+				),
+				maptos_indexer_grpc_http2_keepalive_sec: layers.layer(
+					"MAPTOS_INDEXER_GRPC_HTTP2_KEEPALIVE_SEC",
+					indexer_grpc.http2_keepalive_sec,
+					common::default_maptos_indexer_grpc_http2_keepalive_sec,
+				),
+				maptos_indexer_grpc_tls_cert: layers.layer(
+					"MAPTOS_INDEXER_GRPC_TLS_CERT",
+					indexer_grpc.tls_cert,
+					common::default_maptos_indexer_grpc_tls_cert,
+				),
+				maptos_indexer_grpc_tls_key: layers.layer(
+					"MAPTOS_INDEXER_GRPC_TLS_KEY",
+					indexer_grpc.tls_key,
+					common::default_maptos_indexer_grpc_tls_key,
+				),
+				maptos_indexer_grpc_client_ca: layers.layer(
+					"MAPTOS_INDEXER_GRPC_CLIENT_CA",
+					indexer_grpc.client_ca,
+					common::default_maptos_indexer_grpc_client_ca,
+				),
+				maptos_indexer_grpc_loopback_port: layers.layer(
+					"MAPTOS_INDEXER_GRPC_LOOPBACK_PORT",
+					indexer_grpc.loopback_port,
+					common::default_maptos_indexer_grpc_loopback_port,
+				),
+				maptos_indexer_worker_threads: layers.layer(
+					"MAPTOS_INDEXER_WORKER_THREADS",
+					indexer_grpc.worker_threads,
+					common::default_maptos_indexer_worker_threads,
+				),
+				maptos_indexer_grpc_enabled: layers.layer(
+					"MAPTOS_INDEXER_GRPC_ENABLED",
+					indexer_grpc.enabled,
+					common::default_maptos_indexer_grpc_enabled,
+				),
+				maptos_indexer_max_lag_versions: layers.layer(
+					"MAPTOS_INDEXER_MAX_LAG_VERSIONS",
+					indexer_grpc.max_lag_versions,
+					common::default_maptos_indexer_max_lag_versions,
+				),
+				maptos_indexer_grpc_batch_size: layers.layer(
+					"MAPTOS_INDEXER_GRPC_BATCH_SIZE",
+					indexer_grpc.batch_size,
+					common::default_maptos_indexer_grpc_batch_size,
+				),
+				maptos_indexer_grpc_send_buffer_size: layers.layer(
+					"MAPTOS_INDEXER_GRPC_SEND_BUFFER_SIZE",
+					indexer_grpc.send_buffer_size,
+					common::default_maptos_indexer_grpc_send_buffer_size,
+				),
+				maptos_indexer_grpc_compression: layers.layer(
+					"MAPTOS_INDEXER_GRPC_COMPRESSION",
+					indexer_grpc.compression,
+					common::default_maptos_indexer_grpc_compression,
+				),
+			},
+			indexer_processor: indexer_processor::Config {
+				postgres_connection_string: layers.layer(
+					"INDEXER_PROCESSOR_POSTGRES_CONNECTION_STRING",
+					postgres.connection_string,
+					common::default_postgres_connection_string,
+				),
+				postgres_max_connections: layers.layer(
+					"INDEXER_PROCESSOR_POSTGRES_MAX_CONNECTIONS",
+					postgres.max_connections,
+					common::default_postgres_max_connections,
+				),
+				postgres_connection_timeout_sec: layers.layer(
+					"INDEXER_PROCESSOR_POSTGRES_CONNECTION_TIMEOUT_SEC",
+					postgres.connection_timeout_sec,
+					common::default_postgres_connection_timeout_sec,
+				),
+				postgres_connect_retries: layers.layer(
+					"INDEXER_PROCESSOR_POSTGRES_CONNECT_RETRIES",
+					postgres.connect_retries,
+					common::default_postgres_connect_retries,
+				),
+				postgres_connect_retry_delay_ms: layers.layer(
+					"INDEXER_PROCESSOR_POSTGRES_CONNECT_RETRY_DELAY_MS",
+					postgres.connect_retry_delay_ms,
+					common::default_postgres_connect_retry_delay_ms,
+				),
+				indexer_processor_auth_token,
+				indexer_processor_starting_version: layers.layer(
+					"INDEXER_PROCESSOR_STARTING_VERSION",
+					processor.starting_version,
+					common::default_indexer_processor_starting_version,
+				),
+				indexer_processor_ending_version: layers.layer(
+					"INDEXER_PROCESSOR_ENDING_VERSION",
+					processor.ending_version.map(Some),
+					common::default_indexer_processor_ending_version,
+				),
+				indexer_processor_enabled_processors: layers.layer_strict(
+					"INDEXER_PROCESSOR_ENABLED_PROCESSORS",
+					processor.enabled_processors,
+					common::default_indexer_enabled_processors,
+				)?,
+			},
+			client: client::Config {
+				maptos_rest_connection_hostname: layers.layer(
+					"MAPTOS_API_CONNECTION_HOSTNAME",
+					rest.connection_hostname.clone(),
+					common::default_maptos_rest_connection_hostname,
+				),
+				maptos_rest_connection_port: layers.layer(
+					"MAPTOS_API_CONNECTION_PORT",
+					rest.connection_port,
+					common::default_maptos_rest_connection_port,
+				),
+				maptos_faucet_rest_connection_hostname: layers.layer(
+					"FAUCET_API_CONNECTION_HOSTNAME",
+					faucet.connection_hostname,
+					common::default_maptos_faucet_rest_connection_hostname,
+				),
+				maptos_faucet_rest_connection_port: layers.layer(
+					"FAUCET_API_CONNECTION_PORT",
+					faucet.connection_port,
+					common::default_maptos_faucet_rest_connection_port,
+				),
+				maptos_indexer_grpc_connection_hostname: layers.layer(
+					"MAPTOS_INDEXER_GRPC_CONNECTION_HOSTNAME",
+					indexer_grpc.connection_hostname,
+					common::default_maptos_indexer_grpc_connection_hostname,
+				),
+				maptos_indexer_grpc_connection_port: layers.layer(
+					"MAPTOS_INDEXER_GRPC_CONNECTION_PORT",
+					indexer_grpc.connection_port,
+					common::default_maptos_indexer_grpc_connection_port,
+				),
+				maptos_indexer_upstreams: layers.layer(
+					"MAPTOS_INDEXER_UPSTREAMS",
+					indexer_grpc.upstreams,
+					common::default_maptos_indexer_upstreams,
+				),
+				fin_rest_connection_hostname: layers.layer(
+					"MAPTOS_FIN_VIEW_API_CONNECTION_HOSTNAME",
+					fin.connection_hostname,
+					common::default_fin_rest_connection_hostname,
+				),
+				fin_rest_connection_port: layers.layer(
+					"MAPTOS_FIN_VIEW_API_CONNECTION_PORT",
+					fin.connection_port,
+					common::default_fin_rest_connection_port,
+				),
+				maptos_dns_refresh_sec: layers.layer(
+					"MAPTOS_DNS_REFRESH_SEC",
+					chain.dns_refresh_sec,
+					common::default_maptos_dns_refresh_sec,
+				),
+			},
+			faucet: faucet::Config {
+				maptos_rest_connection_hostname: layers.layer(
+					"MAPTOS_API_CONNECTION_HOSTNAME",
+					rest.connection_hostname,
+					common::default_maptos_rest_connection_hostname,
+				),
+				maptos_rest_connection_port: layers.layer(
+					"MAPTOS_API_CONNECTION_PORT",
+					rest.connection_port,
+					common::default_maptos_rest_connection_port,
+				),
+				maptos_faucet_rest_listen_hostname: layers.layer(
+					"FAUCET_API_LISTEN_HOSTNAME",
+					faucet.listen_hostname,
+					common::default_maptos_faucet_rest_listen_hostname,
+				),
+				maptos_faucet_rest_listen_port: layers.layer(
+					"FAUCET_API_LISTEN_PORT",
+					faucet.listen_port,
+					common::default_maptos_faucet_rest_listen_port,
+				),
+				maptos_faucet_rest_cors_allowed_origins: layers.layer(
+					"FAUCET_API_CORS_ORIGINS",
+					faucet.cors_allowed_origins,
+					common::default_maptos_faucet_rest_cors_allowed_origins,
+				),
+				maptos_faucet_rest_base_path: layers.layer(
+					"FAUCET_API_BASE_PATH",
+					faucet.base_path,
+					common::default_maptos_faucet_rest_base_path,
+				),
+				maptos_faucet_rest_max_body_bytes: layers.layer(
+					"FAUCET_API_MAX_BODY_BYTES",
+					faucet.max_body_bytes,
+					common::default_maptos_faucet_rest_max_body_bytes,
+				),
+				maptos_faucet_rest_idle_timeout_sec: layers.layer(
+					"FAUCET_API_IDLE_TIMEOUT_SEC",
+					faucet.idle_timeout_sec,
+					common::default_maptos_faucet_rest_idle_timeout_sec,
+				),
+				maptos_faucet_rest_max_connections: layers.layer(
+					"FAUCET_API_MAX_CONNECTIONS",
+					faucet.max_connections,
+					common::default_maptos_faucet_rest_max_connections,
+				),
+				maptos_faucet_rest_listen_backlog: layers.layer(
+					"FAUCET_API_LISTEN_BACKLOG",
+					faucet.listen_backlog,
+					common::default_maptos_faucet_rest_listen_backlog,
+				),
+				faucet_rate_limit_per_ip_per_hour: layers.layer(
+					"FAUCET_RATE_LIMIT_PER_IP_PER_HOUR",
+					faucet.rate_limit_per_ip_per_hour,
+					common::default_faucet_rate_limit_per_ip_per_hour,
+				),
+				faucet_max_amount_per_request: layers.layer(
+					"FAUCET_MAX_AMOUNT_PER_REQUEST",
+					faucet.max_amount_per_request,
+					common::default_faucet_max_amount_per_request,
+				),
+				faucet_mint_amount: layers.layer(
+					"FAUCET_MINT_AMOUNT",
+					faucet.mint_amount,
+					common::default_faucet_mint_amount,
+				),
+				faucet_txn_expiration_sec: layers.layer(
+					"FAUCET_TXN_EXPIRATION_SEC",
+					faucet.txn_expiration_sec,
+					common::default_faucet_txn_expiration_sec,
+				),
+				faucet_cooldown_sec: layers.layer(
+					"FAUCET_COOLDOWN_SEC",
+					faucet.cooldown_sec,
+					common::default_faucet_cooldown_sec,
+				),
+				faucet_require_captcha: layers.layer(
+					"FAUCET_REQUIRE_CAPTCHA",
+					faucet.require_captcha,
+					common::default_faucet_require_captcha,
+				),
+				faucet_private_key,
+				faucet_private_keys,
+				faucet_blocked_addresses: layers.layer(
+					"FAUCET_BLOCKED_ADDRESSES",
+					faucet.blocked_addresses,
+					common::default_faucet_blocked_addresses,
+				),
+				faucet_allowlist_addresses: layers.layer(
+					"FAUCET_ALLOWLIST_ADDRESSES",
+					faucet.allowlist_addresses,
+					common::default_faucet_allowlist_addresses,
+				),
+				maptos_faucet_enabled: layers.layer(
+					"FAUCET_ENABLED",
+					faucet.enabled,
+					common::default_maptos_faucet_enabled,
+				),
+			},
+			fin: fin::Config {
+				fin_rest_listen_hostname: layers.layer(
+					"MAPTOS_FIN_VIEW_API_LISTEN_HOSTNAME",
+					fin.listen_hostname,
+					common::default_fin_rest_listen_hostname,
+				),
+				fin_rest_listen_port: layers.layer(
+					"MAPTOS_FIN_VIEW_API_LISTEN_PORT",
+					fin.listen_port,
+					common::default_fin_rest_listen_port,
+				),
+				fin_rest_request_timeout_sec: layers.layer(
+					"MAPTOS_FIN_VIEW_API_REQUEST_TIMEOUT_SEC",
+					fin.request_timeout_sec,
+					common::default_fin_rest_request_timeout_sec,
+				),
+				fin_rest_max_connections: layers.layer(
+					"MAPTOS_FIN_VIEW_API_MAX_CONNECTIONS",
+					fin.max_connections,
+					common::default_fin_rest_max_connections,
+				),
+				fin_rest_listen_backlog: layers.layer(
+					"MAPTOS_FIN_VIEW_API_LISTEN_BACKLOG",
+					fin.listen_backlog,
+					common::default_fin_rest_listen_backlog,
+				),
+				fin_rest_worker_threads: layers.layer(
+					"MAPTOS_FIN_VIEW_API_WORKER_THREADS",
+					fin.worker_threads,
+					common::default_fin_rest_worker_threads,
+				),
+				fin_rest_enabled: layers.layer(
+					"MAPTOS_FIN_VIEW_ENABLED",
+					fin.enabled,
+					common::default_fin_rest_enabled,
+				),
+				fin_rest_max_concurrent_views: layers.layer(
+					"MAPTOS_FIN_VIEW_API_MAX_CONCURRENT_VIEWS",
+					fin.max_concurrent_views,
+					common::default_fin_rest_max_concurrent_views,
+				),
+			},
+			load_shedding: load_shedding::Config {
+				max_transactions_in_flight: layers.layer(
+					"MAPTOS_MAX_TRANSACTIONS_IN_FLIGHT",
+					load_shedding.max_transactions_in_flight,
+					common::default_max_transactions_in_flight,
+				),
+				inflight_overflow_policy: layers.layer_strict(
+					"MAPTOS_INFLIGHT_OVERFLOW_POLICY",
+					load_shedding.overflow_policy,
+					common::default_maptos_inflight_overflow_policy,
+				)?,
+				mempool_ordering: layers.layer_strict(
+					"MAPTOS_MEMPOOL_ORDERING",
+					load_shedding.mempool_ordering,
+					common::default_maptos_mempool_ordering,
+				)?,
+			},
+			health: health::Config {
+				maptos_health_enabled: layers.layer(
+					"MAPTOS_HEALTH_ENABLED",
+					health.enabled,
+					common::default_maptos_health_enabled,
+				),
+				maptos_health_listen_hostname: layers.layer(
+					"MAPTOS_HEALTH_LISTEN_HOSTNAME",
+					health.listen_hostname,
+					common::default_maptos_health_listen_hostname,
+				),
+				maptos_health_listen_port: layers.layer(
+					"MAPTOS_HEALTH_LISTEN_PORT",
+					health.listen_port,
+					common::default_maptos_health_listen_port,
+				),
+				maptos_health_mode: layers.layer_strict(
+					"MAPTOS_HEALTH_MODE",
+					health.mode,
+					common::default_maptos_health_mode,
+				)?,
+				maptos_max_block_age_sec: layers.layer(
+					"MAPTOS_MAX_BLOCK_AGE_SEC",
+					health.max_block_age_sec,
+					common::default_maptos_max_block_age_sec,
+				),
+			},
+			metrics: metrics::Config {
+				maptos_metrics_enabled: layers.layer(
+					"MAPTOS_METRICS_ENABLED",
+					metrics.enabled,
+					common::default_maptos_metrics_enabled,
+				),
+				maptos_metrics_listen_hostname: layers.layer(
+					"MAPTOS_METRICS_LISTEN_HOSTNAME",
+					metrics.listen_hostname,
+					common::default_maptos_metrics_listen_hostname,
+				),
+				maptos_metrics_listen_port: layers.layer(
+					"MAPTOS_METRICS_LISTEN_PORT",
+					metrics.listen_port,
+					common::default_maptos_metrics_listen_port,
+				),
+			},
+			logging: logging::Config {
+				maptos_log_format: layers.layer_strict(
+					"MAPTOS_LOG_FORMAT",
+					logging.format,
+					common::default_maptos_log_format,
+				)?,
+				maptos_log_level: layers.layer_strict(
+					"MAPTOS_LOG_LEVEL",
+					logging.level,
+					common::default_maptos_log_level,
+				)?,
+				maptos_otlp_endpoint: layers.layer(
+					"MAPTOS_OTLP_ENDPOINT",
+					logging.otlp_endpoint,
+					common::default_maptos_otlp_endpoint,
+				),
+				maptos_otlp_sample_rate: layers.layer(
+					"MAPTOS_OTLP_SAMPLE_RATE",
+					logging.otlp_sample_rate,
+					common::default_maptos_otlp_sample_rate,
+				),
+			},
+			sources: provenance::Sources::from_env().with_file(layers.from_file),
+			port_probes: ports::PortProbes::default(),
+		})
+	}
+}
+
+impl TryFrom<&Config> for ConfigFile {
+	type Error = anyhow::Error;
+
+	/// Captures every resolved value of `config`, so the file reproduces it exactly.
+	fn try_from(config: &Config) -> Result<Self, Self::Error> {
+		Ok(ConfigFile {
+			chain: ChainSection {
+				chain_id: Some(config.chain.maptos_chain_id.id().to_string()),
+				private_key: Some(config.chain.maptos_private_key.to_encoded_string()?),
+				data_dir: Some(config.chain.maptos_data_dir.clone()),
+				db_path: config.chain.maptos_db_path.clone(),
+				db_block_cache_bytes: Some(config.chain.maptos_db_block_cache_bytes),
+				db_max_wal_bytes: Some(config.chain.maptos_db_max_wal_bytes),
+				max_db_size_bytes: Some(config.chain.maptos_max_db_size_bytes),
+				commit_batch_size: Some(config.chain.maptos_commit_batch_size),
+				commit_batch_max_delay_ms: Some(config.chain.maptos_commit_batch_max_delay_ms),
+				fsync_policy: Some(config.chain.maptos_fsync_policy),
+				execution_concurrency: Some(config.chain.maptos_execution_concurrency),
+				genesis_blob_path: config.chain.maptos_genesis_blob_path.clone(),
+				framework_path: config.chain.maptos_framework_path.clone(),
+				shutdown_grace_sec: Some(config.chain.maptos_shutdown_grace_sec),
+				dns_refresh_sec: Some(config.client.maptos_dns_refresh_sec),
+				stop_at_version: config.chain.maptos_stop_at_version,
+				read_only: Some(config.chain.maptos_read_only),
+				allow_chain_id_mismatch: Some(config.chain.maptos_allow_chain_id_mismatch),
+				gas_unit_price: Some(config.chain.maptos_gas_unit_price),
+				clock: Some(config.chain.maptos_clock),
+				sync_mode: Some(config.chain.maptos_sync_mode),
+				key_scheme: Some(config.chain.maptos_key_scheme),
+				feature_flags: Some(config.chain.maptos_feature_flags.clone()),
+			},
+			rest: RestSection {
+				listen_hostname: Some(config.chain.maptos_rest_listen_hostname.clone()),
+				listen_port: Some(config.chain.maptos_rest_listen_port),
+				tls_cert_path: Some(config.chain.maptos_rest_tls_cert_path.clone()),
+				tls_key_path: Some(config.chain.maptos_rest_tls_key_path.clone()),
+				cors_allowed_origins: Some(config.chain.maptos_rest_cors_allowed_origins.clone()),
+				base_path: Some(config.chain.maptos_rest_base_path.clone()),
+				method_allowlist: Some(config.chain.maptos_rest_method_allowlist.clone()),
+				scoped_tokens: Some(config.chain.maptos_rest_scoped_tokens.clone()),
+				default_encoding: Some(config.chain.maptos_rest_default_encoding),
+				address_format: Some(config.chain.maptos_rest_address_format),
+				request_timeout_sec: Some(config.chain.maptos_rest_request_timeout_sec),
+				idle_timeout_sec: Some(config.chain.maptos_rest_idle_timeout_sec),
+				max_body_bytes: Some(config.chain.maptos_rest_max_body_bytes),
+				max_gas_amount: Some(config.chain.maptos_rest_max_gas_amount),
+				max_txn_future_sec: Some(config.chain.maptos_max_txn_future_sec),
+				max_page_size: Some(config.chain.maptos_rest_max_page_size),
+				max_events_per_response: Some(config.chain.maptos_rest_max_events_per_response),
+				max_transaction_bytes: Some(config.chain.maptos_rest_max_transaction_bytes),
+				max_connections: Some(config.chain.maptos_rest_max_connections),
+				listen_backlog: Some(config.chain.maptos_rest_listen_backlog),
+				log_sample_rate: Some(config.chain.maptos_rest_log_sample_rate),
+				worker_threads: Some(config.chain.maptos_rest_worker_threads),
+				enabled: Some(config.chain.maptos_rest_enabled),
+				expose_version: Some(config.chain.maptos_expose_version),
+				preload_enabled: Some(config.chain.maptos_preload_enabled),
+				preload_versions: Some(config.chain.maptos_preload_versions),
+				connection_hostname: Some(config.client.maptos_rest_connection_hostname.clone()),
+				connection_port: Some(config.client.maptos_rest_connection_port),
+			},
+			faucet: FaucetSection {
+				listen_hostname: Some(config.faucet.maptos_faucet_rest_listen_hostname.clone()),
+				listen_port: Some(config.faucet.maptos_faucet_rest_listen_port),
+				cors_allowed_origins: Some(
+					config.faucet.maptos_faucet_rest_cors_allowed_origins.clone(),
+				),
+				base_path: Some(config.faucet.maptos_faucet_rest_base_path.clone()),
+				max_body_bytes: Some(config.faucet.maptos_faucet_rest_max_body_bytes),
+				idle_timeout_sec: Some(config.faucet.maptos_faucet_rest_idle_timeout_sec),
+				max_connections: Some(config.faucet.maptos_faucet_rest_max_connections),
+				listen_backlog: Some(config.faucet.maptos_faucet_rest_listen_backlog),
+				rate_limit_per_ip_per_hour: Some(config.faucet.faucet_rate_limit_per_ip_per_hour),
+				max_amount_per_request: Some(config.faucet.faucet_max_amount_per_request),
+				mint_amount: Some(config.faucet.faucet_mint_amount),
+				txn_expiration_sec: Some(config.faucet.faucet_txn_expiration_sec),
+				cooldown_sec: Some(config.faucet.faucet_cooldown_sec),
+				require_captcha: Some(config.faucet.faucet_require_captcha),
+				private_key: config
+					.faucet
+					.faucet_private_key
+					.as_ref()
+					.map(|private_key| private_key.to_encoded_string())
+					.transpose()?,
+				private_keys: Some(
+					config
+						.faucet
+						.faucet_private_keys
+						.iter()
+						.map(|private_key| private_key.to_encoded_string())
+						.collect::<Result<Vec<_>, _>>()?
+						.join(","),
+				)
+				.filter(|private_keys| !private_keys.is_empty()),
+				blocked_addresses: Some(config.faucet.faucet_blocked_addresses.clone()),
+				allowlist_addresses: Some(config.faucet.faucet_allowlist_addresses.clone()),
+				enabled: Some(config.faucet.maptos_faucet_enabled),
+				connection_hostname: Some(
+					config.client.maptos_faucet_rest_connection_hostname.clone(),
+				),
+				connection_port: Some(config.client.maptos_faucet_rest_connection_port),
+			},
+			fin: FinSection {
+				listen_hostname: Some(config.fin.fin_rest_listen_hostname.clone()),
+				listen_port: Some(config.fin.fin_rest_listen_port),
+				connection_hostname: Some(config.client.fin_rest_connection_hostname.clone()),
+				connection_port: Some(config.client.fin_rest_connection_port),
+				request_timeout_sec: Some(config.fin.fin_rest_request_timeout_sec),
+				max_connections: Some(config.fin.fin_rest_max_connections),
+				listen_backlog: Some(config.fin.fin_rest_listen_backlog),
+				worker_threads: Some(config.fin.fin_rest_worker_threads),
+				enabled: Some(config.fin.fin_rest_enabled),
+				max_concurrent_views: Some(config.fin.fin_rest_max_concurrent_views),
+			},
+			indexer_grpc: IndexerGrpcSection {
+				listen_hostname: Some(config.indexer.maptos_indexer_grpc_listen_hostname.clone()),
+				listen_port: Some(config.indexer.maptos_indexer_grpc_listen_port),
+				connection_hostname: Some(
+					config.client.maptos_indexer_grpc_connection_hostname.clone(),
+				),
+				connection_port: Some(config.client.maptos_indexer_grpc_connection_port),
+				upstreams: Some(config.client.maptos_indexer_upstreams.clone()),
+				inactivity_timeout_sec: Some(config.indexer.maptos_indexer_grpc_inactivity_timeout),
+				ping_interval_sec: Some(
+					config.indexer.maptos_indexer_grpc_inactivity_ping_interval,
+				),
+				max_message_bytes: Some(config.indexer.maptos_indexer_grpc_max_message_bytes),
+				tcp_keepalive_sec: Some(config.indexer.maptos_indexer_grpc_tcp_keepalive_sec),
+				http2_keepalive_sec: Some(config.indexer.maptos_indexer_grpc_http2_keepalive_sec),
+				tls_cert: Some(config.indexer.maptos_indexer_grpc_tls_cert.clone()),
+				tls_key: Some(config.indexer.maptos_indexer_grpc_tls_key.clone()),
+				client_ca: Some(config.indexer.maptos_indexer_grpc_client_ca.clone()),
+				loopback_port: Some(config.indexer.maptos_indexer_grpc_loopback_port),
+				auth_token: Some(config.indexer_processor.indexer_processor_auth_token.clone()),
+				worker_threads: Some(config.indexer.maptos_indexer_worker_threads),
+				enabled: Some(config.indexer.maptos_indexer_grpc_enabled),
+				max_lag_versions: Some(config.indexer.maptos_indexer_max_lag_versions),
+				batch_size: Some(config.indexer.maptos_indexer_grpc_batch_size),
+				send_buffer_size: Some(config.indexer.maptos_indexer_grpc_send_buffer_size),
+				compression: Some(config.indexer.maptos_indexer_grpc_compression),
+			},
+			pruning: PruningSection {
+				enabled: Some(config.chain.maptos_enable_pruning),
+				ledger_window: Some(config.chain.maptos_ledger_prune_window),
+				state_merkle_window: Some(config.chain.maptos_state_merkle_prune_window),
+				epoch_snapshot_window: Some(config.chain.maptos_epoch_snapshot_prune_window),
+			},
+			postgres: PostgresSection {
+				connection_string: Some(
+					config.indexer_processor.postgres_connection_string.clone(),
+				),
+				max_connections: Some(config.indexer_processor.postgres_max_connections),
+				connection_timeout_sec: Some(
+					config.indexer_processor.postgres_connection_timeout_sec,
+				),
+				connect_retries: Some(config.indexer_processor.postgres_connect_retries),
+				connect_retry_delay_ms: Some(
+					config.indexer_processor.postgres_connect_retry_delay_ms,
+				),
+			},
+			processor: ProcessorSection {
+				starting_version: Some(config.indexer_processor.indexer_processor_starting_version),
+				ending_version: config.indexer_processor.indexer_processor_ending_version,
+				enabled_processors: Some(
+					config.indexer_processor.indexer_processor_enabled_processors.clone(),
+				),
+			},
+			load_shedding: LoadSheddingSection {
+				max_transactions_in_flight: Some(config.load_shedding.max_transactions_in_flight),
+				overflow_policy: Some(config.load_shedding.inflight_overflow_policy),
+				mempool_ordering: Some(config.load_shedding.mempool_ordering),
+			},
+			health: HealthSection {
+				enabled: Some(config.health.maptos_health_enabled),
+				listen_hostname: Some(config.health.maptos_health_listen_hostname.clone()),
+				listen_port: Some(config.health.maptos_health_listen_port),
+				mode: Some(config.health.maptos_health_mode),
+				max_block_age_sec: Some(config.health.maptos_max_block_age_sec),
+			},
+			metrics: MetricsSection {
+				enabled: Some(config.metrics.maptos_metrics_enabled),
+				listen_hostname: Some(config.metrics.maptos_metrics_listen_hostname.clone()),
+				listen_port: Some(config.metrics.maptos_metrics_listen_port),
+			},
+			logging: LoggingSection {
+				format: Some(config.logging.maptos_log_format),
+				level: Some(config.logging.maptos_log_level),
+				otlp_endpoint: Some(config.logging.maptos_otlp_endpoint.clone()),
+				otlp_sample_rate: Some(config.logging.maptos_otlp_sample_rate),
+			},
+		})
+	}
+}
+
+impl Config {
+	/// Loads the config from a TOML file, letting explicitly set env vars override it.
+	///
+	/// See the [module documentation](self) for the precedence rules.
+	pub fn from_toml_file(path: &Path) -> Result<Config, anyhow::Error> {
+		ConfigFile::read(path)?.resolve()
+	}
+
+	/// Loads the config from JSON in the shape of the config file, letting explicitly set env
+	/// vars override it as they do a file.
+	pub fn from_json(json: &str) -> Result<Config, anyhow::Error> {
+		ConfigFile::from_json(json)?.resolve()
+	}
+
+	/// Overrides each field whose env var is set with the env value, leaving the others as
+	/// they are, as if the config had been loaded from a file with the current environment.
+	///
+	/// The config is left unchanged when an env var is malformed.
+	pub fn apply_env_overrides(&mut self) -> Result<(), anyhow::Error> {
+		// The file captures every field, so only the env overrides change where one came from.
+		let sources = self.sources.clone();
+		*self = ConfigFile::try_from(&*self)?.resolve()?;
+		self.sources = sources.overridden_by_env();
+		Ok(())
+	}
+
+	/// Writes every resolved value of the config to a TOML file.
+	pub fn write_toml_file(&self, path: &Path) -> Result<(), anyhow::Error> {
+		let contents = toml::to_string(&ConfigFile::try_from(self)?)?;
+		std::fs::write(path, contents)
+			.with_context(|| format!("failed to write config file {}", path.display()))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::config::provenance::Provenance;
+	use aptos_types::chain_id::ChainId;
+
+	use std::collections::HashMap;
+
+	#[test]
+	fn test_toml_round_trip() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.chain.maptos_chain_id = ChainId::new(42);
+		config.chain.maptos_db_path = Some(PathBuf::from("/var/lib/maptos"));
+		config.chain.maptos_state_merkle_prune_window = 250_000;
+
+		let dir = tempfile::tempdir()?;
+		let path = dir.path().join("maptos.toml");
+		config.write_toml_file(&path)?;
+
+		assert_eq!(Config::from_toml_file(&path)?, config);
+		Ok(())
+	}
+
+	#[test]
+	fn test_env_overrides_only_touch_set_vars() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::lock();
+		let mut config = Config::default();
+		config.chain.maptos_chain_id = ChainId::new(42);
+		config.metrics.maptos_metrics_listen_port = 9464;
+		let before = config.clone();
+
+		std::env::set_var("MAPTOS_METRICS_LISTEN_PORT", "9500");
+		let applied = config.apply_env_overrides();
+		std::env::remove_var("MAPTOS_METRICS_LISTEN_PORT");
+		applied?;
+
+		assert_eq!(config.metrics.maptos_metrics_listen_port, 9500);
+		let fields: Vec<String> = config.diff(&before).into_iter().map(|diff| diff.field).collect();
+		assert_eq!(fields, vec!["MAPTOS_METRICS_LISTEN_PORT"]);
+		Ok(())
+	}
+
+	#[test]
+	fn test_json_config_is_applied_under_set_env_vars() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::lock();
+		let json = r#"{
+			"chain": {
+				"chain_id": "42",
+				"private_key": "0x0000000000000000000000000000000000000000000000000000000000000001"
+			},
+			"fin": { "listen_port": 40010, "max_concurrent_views": 8 },
+			"load_shedding": { "overflow_policy": "block" }
+		}"#;
+
+		std::env::set_var("MAPTOS_FIN_VIEW_API_MAX_CONCURRENT_VIEWS", "4");
+		let config = Config::from_json(json);
+		std::env::remove_var("MAPTOS_FIN_VIEW_API_MAX_CONCURRENT_VIEWS");
+		let config = config?;
+
+		assert_eq!(config.chain.maptos_chain_id, ChainId::new(42));
+		assert_eq!(config.fin.fin_rest_listen_port, 40010);
+		assert_eq!(config.load_shedding.inflight_overflow_policy, OverflowPolicy::Block);
+		// An individual env var still takes precedence over the JSON.
+		assert_eq!(config.fin.fin_rest_max_concurrent_views, 4);
+
+		let error = Config::from_json("{\"fin\": {\"listen_port\": }").unwrap_err();
+		assert!(format!("{:#}", error).contains("failed to parse the config JSON"), "{:#}", error);
+		assert!(Config::from_json(r#"{"fin": {"listen_prot": 40010}}"#).is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_missing_keys_fall_back_to_defaults() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::read_lock();
+		let dir = tempfile::tempdir()?;
+		let path = dir.path().join("maptos.toml");
+		std::fs::write(
+			&path,
+			"[chain]\n\
+			private_key = \"0x0000000000000000000000000000000000000000000000000000000000000001\"\n\
+			[fin]\nlisten_port = 40000\n",
+		)?;
+
+		let config = Config::from_toml_file(&path)?;
+		assert_eq!(config.fin.fin_rest_listen_port, 40000);
+		assert_eq!(config.chain.maptos_rest_listen_port, common::default_maptos_rest_listen_port());
+		Ok(())
+	}
+
+	#[test]
+	fn test_fields_set_by_the_file_report_file() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::lock();
+		let dir = tempfile::tempdir()?;
+		let path = dir.path().join("maptos.toml");
+		std::fs::write(
+			&path,
+			"[chain]\n\
+			private_key = \"0x0000000000000000000000000000000000000000000000000000000000000001\"\n\
+			[fin]\nlisten_port = 40000\nworker_threads = 2\n",
+		)?;
+
+		std::env::set_var("MAPTOS_FIN_VIEW_API_WORKER_THREADS", "4");
+		std::env::remove_var("MAPTOS_FIN_VIEW_API_LISTEN_PORT");
+		std::env::remove_var("MAPTOS_FIN_VIEW_API_LISTEN_HOSTNAME");
+		let config = Config::from_toml_file(&path);
+		std::env::remove_var("MAPTOS_FIN_VIEW_API_WORKER_THREADS");
+		let provenance: HashMap<String, Provenance> = config?.provenance().into_iter().collect();
+
+		assert_eq!(provenance["MAPTOS_FIN_VIEW_API_LISTEN_PORT"], Provenance::File);
+		assert_eq!(provenance["MAPTOS_FIN_VIEW_API_WORKER_THREADS"], Provenance::Env);
+		assert_eq!(provenance["MAPTOS_FIN_VIEW_API_LISTEN_HOSTNAME"], Provenance::Default);
+		// Secrets report where they came from, though their value is never listed.
+		assert_eq!(provenance["MAPTOS_PRIVATE_KEY"], Provenance::File);
+		Ok(())
+	}
+
+	#[test]
+	fn test_durations_accept_bare_seconds_and_units() -> Result<(), anyhow::Error> {
+		let file: ConfigFile = toml::from_str(
+			"[rest]\nrequest_timeout_sec = 45\n\
+			[indexer_grpc]\nping_interval_sec = \"500ms\"\ninactivity_timeout_sec = \"2m\"\n",
+		)?;
+		assert_eq!(file.rest.request_timeout_sec, Some(DurationSecs::from_secs(45)));
+		assert_eq!(file.indexer_grpc.ping_interval_sec, Some(DurationSecs::from_millis(500)));
+		assert_eq!(file.indexer_grpc.inactivity_timeout_sec, Some(DurationSecs::from_secs(120)));
+		assert!(toml::from_str::<ConfigFile>("[rest]\nrequest_timeout_sec = \"soon\"\n").is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_invalid_chain_id_is_rejected() -> Result<(), anyhow::Error> {
+		let dir = tempfile::tempdir()?;
+		let path = dir.path().join("maptos.toml");
+		std::fs::write(&path, "[chain]\nchain_id = \"not-a-chain\"\n")?;
+
+		assert!(Config::from_toml_file(&path).is_err());
+		Ok(())
+	}
+}
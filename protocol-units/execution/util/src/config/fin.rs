@@ -1,15 +1,49 @@
-use super::common::{default_fin_rest_listen_hostname, default_fin_rest_listen_port};
+use super::common::{
+	default_fin_rest_enabled, default_fin_rest_listen_backlog, default_fin_rest_listen_hostname,
+	default_fin_rest_listen_port, default_fin_rest_max_concurrent_views,
+	default_fin_rest_max_connections, default_fin_rest_request_timeout_sec,
+	default_fin_rest_worker_threads, resolve_worker_threads,
+};
+use super::duration::DurationSecs;
+use super::endpoints::ConnectionLimits;
 use serde::{Deserialize, Serialize};
 
+use std::num::NonZeroUsize;
+use std::time::Duration;
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Config {
-	/// The URL of the Aptos REST server
+	/// The URL of the Aptos REST server, or `unix:/path` to listen on a Unix domain socket
 	#[serde(default = "default_fin_rest_listen_hostname")]
 	pub fin_rest_listen_hostname: String,
 
 	/// The port of the Aptos REST server
 	#[serde(default = "default_fin_rest_listen_port")]
 	pub fin_rest_listen_port: u16,
+
+	/// The time limit for a single request to the fin view server
+	#[serde(default = "default_fin_rest_request_timeout_sec")]
+	pub fin_rest_request_timeout_sec: DurationSecs,
+
+	/// The cap on concurrent fin view connections, 0 for unlimited
+	#[serde(default = "default_fin_rest_max_connections")]
+	pub fin_rest_max_connections: u32,
+
+	/// The fin view listen backlog, 0 for the system maximum
+	#[serde(default = "default_fin_rest_listen_backlog")]
+	pub fin_rest_listen_backlog: u32,
+
+	/// The fin view runtime worker threads, 0 for one per CPU
+	#[serde(default = "default_fin_rest_worker_threads")]
+	pub fin_rest_worker_threads: u32,
+
+	/// Whether the node serves the fin view server
+	#[serde(default = "default_fin_rest_enabled")]
+	pub fin_rest_enabled: bool,
+
+	/// The cap on view function calls executing at once, 0 for unlimited
+	#[serde(default = "default_fin_rest_max_concurrent_views")]
+	pub fin_rest_max_concurrent_views: u32,
 }
 
 impl Default for Config {
@@ -17,6 +51,33 @@ impl Default for Config {
 		Self {
 			fin_rest_listen_hostname: default_fin_rest_listen_hostname(),
 			fin_rest_listen_port: default_fin_rest_listen_port(),
+			fin_rest_request_timeout_sec: default_fin_rest_request_timeout_sec(),
+			fin_rest_max_connections: default_fin_rest_max_connections(),
+			fin_rest_listen_backlog: default_fin_rest_listen_backlog(),
+			fin_rest_worker_threads: default_fin_rest_worker_threads(),
+			fin_rest_enabled: default_fin_rest_enabled(),
+			fin_rest_max_concurrent_views: default_fin_rest_max_concurrent_views(),
 		}
 	}
 }
+
+impl Config {
+	pub fn request_timeout(&self) -> Duration {
+		self.fin_rest_request_timeout_sec.get()
+	}
+
+	/// The admission limits of the fin view listener.
+	pub fn connection_limits(&self) -> ConnectionLimits {
+		ConnectionLimits::new(self.fin_rest_max_connections, self.fin_rest_listen_backlog)
+	}
+
+	/// The worker threads of the fin view runtime, one per CPU unless configured.
+	pub fn worker_threads(&self) -> usize {
+		resolve_worker_threads(self.fin_rest_worker_threads)
+	}
+
+	/// The cap on view function calls executing at once, `None` when unlimited.
+	pub fn max_concurrent_views(&self) -> Option<NonZeroUsize> {
+		NonZeroUsize::new(usize::try_from(self.fin_rest_max_concurrent_views).unwrap_or(usize::MAX))
+	}
+}
@@ -0,0 +1,60 @@
+//! A fingerprint of the resolved config, for noticing when it changes between restarts.
+
+use super::diff::{flatten, render};
+use super::Config;
+
+use std::collections::BTreeMap;
+
+impl Config {
+	/// Hashes every field into a value which is stable across processes and releases
+	/// for as long as the fields are unchanged.
+	///
+	/// Fields are hashed in path order, so the hash does not depend on how they are declared
+	/// or serialized, and secrets contribute only the fingerprint [`Config::diff`] shows for them.
+	pub fn stable_hash(&self) -> u64 {
+		let mut fields = BTreeMap::new();
+		flatten(
+			"",
+			&serde_json::to_value(self).expect("the config is always serializable"),
+			&mut fields,
+		);
+		let mut context = ring::digest::Context::new(&ring::digest::SHA256);
+		for (path, value) in &fields {
+			// Separated by NUL, which neither paths nor rendered values contain unescaped.
+			context.update(path.as_bytes());
+			context.update(b"\0");
+			context.update(render(path, Some(value)).as_bytes());
+			context.update(b"\0");
+		}
+		let digest = context.finish();
+		let mut prefix = [0u8; 8];
+		prefix.copy_from_slice(&digest.as_ref()[..8]);
+		u64::from_be_bytes(prefix)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use aptos_crypto::{ed25519::Ed25519PrivateKey, Uniform};
+
+	#[test]
+	fn test_equal_configs_hash_equally() {
+		let _env = godfig::env::read_lock();
+		let config = Config::default();
+		assert_eq!(config.stable_hash(), config.clone().stable_hash());
+	}
+
+	#[test]
+	fn test_a_changed_field_changes_the_hash() {
+		let _env = godfig::env::read_lock();
+		let config = Config::default();
+		let mut changed = config.clone();
+		changed.fin.fin_rest_listen_port += 1;
+		assert_ne!(config.stable_hash(), changed.stable_hash());
+
+		let mut rekeyed = config.clone();
+		rekeyed.chain.maptos_private_key = Ed25519PrivateKey::generate(&mut rand::thread_rng());
+		assert_ne!(config.stable_hash(), rekeyed.stable_hash());
+	}
+}
@@ -0,0 +1,139 @@
+//! Configuration for the health and readiness probes.
+
+use super::common::{
+	default_maptos_health_enabled, default_maptos_health_listen_hostname,
+	default_maptos_health_listen_port, default_maptos_health_mode,
+	default_maptos_max_block_age_sec,
+};
+use super::duration::DurationSecs;
+
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+/// What the health server answers on its port.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum HealthMode {
+	/// Serves `/healthz` and `/readyz`.
+	Http,
+	/// Accepts each connection and closes it at once, for checkers which only open one.
+	Tcp,
+}
+
+impl HealthMode {
+	pub const ACCEPTED: &'static [&'static str] = &["http", "tcp"];
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			HealthMode::Http => "http",
+			HealthMode::Tcp => "tcp",
+		}
+	}
+}
+
+impl FromStr for HealthMode {
+	type Err = anyhow::Error;
+
+	fn from_str(raw: &str) -> Result<Self, Self::Err> {
+		match raw.trim().to_ascii_lowercase().as_str() {
+			"http" => Ok(HealthMode::Http),
+			"tcp" => Ok(HealthMode::Tcp),
+			_ => anyhow::bail!(
+				"unknown health mode {:?}, expected one of: {}",
+				raw,
+				Self::ACCEPTED.join(", ")
+			),
+		}
+	}
+}
+
+impl fmt::Display for HealthMode {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Config {
+	/// Whether `/healthz` and `/readyz` are served
+	#[serde(default = "default_maptos_health_enabled")]
+	pub maptos_health_enabled: bool,
+
+	/// The hostname the health server binds
+	#[serde(default = "default_maptos_health_listen_hostname")]
+	pub maptos_health_listen_hostname: String,
+
+	/// The port the health server binds, separate from the API ports
+	#[serde(default = "default_maptos_health_listen_port")]
+	pub maptos_health_listen_port: u16,
+
+	/// What the health server answers on its port
+	#[serde(default = "default_maptos_health_mode")]
+	pub maptos_health_mode: HealthMode,
+
+	/// How old the latest committed block may be before the node reports not ready,
+	/// 0, the default, to never report it
+	#[serde(default = "default_maptos_max_block_age_sec")]
+	pub maptos_max_block_age_sec: DurationSecs,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self {
+			maptos_health_enabled: default_maptos_health_enabled(),
+			maptos_health_listen_hostname: default_maptos_health_listen_hostname(),
+			maptos_health_listen_port: default_maptos_health_listen_port(),
+			maptos_health_mode: default_maptos_health_mode(),
+			maptos_max_block_age_sec: default_maptos_max_block_age_sec(),
+		}
+	}
+}
+
+impl super::Config {
+	/// How old the latest committed block may be before the node reports not ready,
+	/// `None` when its age is not checked.
+	pub fn max_block_age(&self) -> Option<Duration> {
+		Some(self.health.maptos_max_block_age_sec.get()).filter(|max_age| !max_age.is_zero())
+	}
+
+	/// Whether a latest committed block from `latest_block` is too old at `now` for the node
+	/// to be ready. A block from the future is not.
+	pub fn block_age_exceeded(&self, latest_block: SystemTime, now: SystemTime) -> bool {
+		self.max_block_age().map_or(false, |max_age| {
+			now.duration_since(latest_block).map_or(false, |age| age > max_age)
+		})
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_health_modes_parse() {
+		assert_eq!("http".parse::<HealthMode>().unwrap(), HealthMode::Http);
+		assert_eq!(" TCP ".parse::<HealthMode>().unwrap(), HealthMode::Tcp);
+		assert!("grpc".parse::<HealthMode>().is_err());
+	}
+
+	#[test]
+	fn test_stale_blocks_exceed_the_max_age() {
+		let _env = godfig::env::read_lock();
+		let mut config = super::super::Config::default();
+		// An idle chain commits no blocks, so their age is not checked by default.
+		assert_eq!(config.max_block_age(), None);
+		config.health.maptos_max_block_age_sec = DurationSecs::from_secs(60);
+		let latest_block = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+		assert!(!config.block_age_exceeded(latest_block, latest_block + Duration::from_secs(60)));
+		assert!(config.block_age_exceeded(latest_block, latest_block + Duration::from_secs(61)));
+		assert!(!config.block_age_exceeded(latest_block, latest_block - Duration::from_secs(5)));
+
+		config.health.maptos_max_block_age_sec = DurationSecs::ZERO;
+		assert_eq!(config.max_block_age(), None);
+		assert!(!config.block_age_exceeded(latest_block, latest_block + Duration::from_secs(3600)));
+	}
+}
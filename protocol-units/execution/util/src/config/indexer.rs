@@ -1,9 +1,70 @@
 use super::common::{
+	default_maptos_indexer_grpc_batch_size, default_maptos_indexer_grpc_client_ca,
+	default_maptos_indexer_grpc_compression, default_maptos_indexer_grpc_enabled,
+	default_maptos_indexer_grpc_http2_keepalive_sec,
 	default_maptos_indexer_grpc_inactivity_timeout, default_maptos_indexer_grpc_listen_hostname,
-	default_maptos_indexer_grpc_listen_port, default_maptos_indexer_grpc_ping_interval,
+	default_maptos_indexer_grpc_listen_port, default_maptos_indexer_grpc_loopback_port,
+	default_maptos_indexer_grpc_max_message_bytes, default_maptos_indexer_grpc_ping_interval,
+	default_maptos_indexer_grpc_send_buffer_size, default_maptos_indexer_grpc_tcp_keepalive_sec,
+	default_maptos_indexer_grpc_tls_cert, default_maptos_indexer_grpc_tls_key,
+	default_maptos_indexer_max_lag_versions, default_maptos_indexer_worker_threads,
+	resolve_worker_threads,
 };
+use super::duration::DurationSecs;
 use serde::{Deserialize, Serialize};
 
+use std::fmt;
+use std::num::NonZeroUsize;
+use std::str::FromStr;
+use std::time::Duration;
+
+/// How the messages of the indexer gRPC stream are compressed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GrpcCompression {
+	/// Messages are sent as they are.
+	None,
+	/// Understood by every gRPC client.
+	Gzip,
+	/// Compresses better than gzip for the same CPU, at the cost of support in older clients.
+	Zstd,
+}
+
+impl GrpcCompression {
+	pub const ACCEPTED: &'static [&'static str] = &["none", "gzip", "zstd"];
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			GrpcCompression::None => "none",
+			GrpcCompression::Gzip => "gzip",
+			GrpcCompression::Zstd => "zstd",
+		}
+	}
+}
+
+impl FromStr for GrpcCompression {
+	type Err = anyhow::Error;
+
+	fn from_str(raw: &str) -> Result<Self, Self::Err> {
+		match raw.trim().to_ascii_lowercase().as_str() {
+			"none" => Ok(GrpcCompression::None),
+			"gzip" => Ok(GrpcCompression::Gzip),
+			"zstd" => Ok(GrpcCompression::Zstd),
+			_ => anyhow::bail!(
+				"unknown gRPC compression {:?}, expected one of: {}",
+				raw,
+				Self::ACCEPTED.join(", ")
+			),
+		}
+	}
+}
+
+impl fmt::Display for GrpcCompression {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Config {
 	/// The URL of the Suzuka node gRPC indexer server
@@ -16,11 +77,67 @@ pub struct Config {
 
 	/// Inactivity timeout of the gRpc connection
 	#[serde(default = "default_maptos_indexer_grpc_inactivity_timeout")]
-	pub maptos_indexer_grpc_inactivity_timeout: u64,
+	pub maptos_indexer_grpc_inactivity_timeout: DurationSecs,
 
 	/// Ping interval of the gRpc connection
 	#[serde(default = "default_maptos_indexer_grpc_ping_interval")]
-	pub maptos_indexer_grpc_inactivity_ping_interval: u64,
+	pub maptos_indexer_grpc_inactivity_ping_interval: DurationSecs,
+
+	/// The largest single message on the gRpc connection, in bytes
+	#[serde(default = "default_maptos_indexer_grpc_max_message_bytes")]
+	pub maptos_indexer_grpc_max_message_bytes: u64,
+
+	/// TCP keepalive interval of client connections, 0 to disable
+	#[serde(default = "default_maptos_indexer_grpc_tcp_keepalive_sec")]
+	pub maptos_indexer_grpc_tcp_keepalive_sec: DurationSecs,
+
+	/// HTTP/2 keepalive ping interval of client connections, 0 to disable
+	#[serde(default = "default_maptos_indexer_grpc_http2_keepalive_sec")]
+	pub maptos_indexer_grpc_http2_keepalive_sec: DurationSecs,
+
+	/// Path to the PEM certificate chain for serving gRpc over TLS, empty for plaintext
+	#[serde(default = "default_maptos_indexer_grpc_tls_cert")]
+	pub maptos_indexer_grpc_tls_cert: String,
+
+	/// Path to the PEM private key for serving gRpc over TLS, empty for plaintext
+	#[serde(default = "default_maptos_indexer_grpc_tls_key")]
+	pub maptos_indexer_grpc_tls_key: String,
+
+	/// Path to the PEM CA certificate which must sign every client certificate, empty to accept
+	/// clients without one
+	#[serde(default = "default_maptos_indexer_grpc_client_ca")]
+	pub maptos_indexer_grpc_client_ca: String,
+
+	/// The port on 127.0.0.1 the gRpc is also served on in plaintext when it is served over
+	/// TLS, for an indexer on the same host without a client certificate; 0 for none
+	#[serde(default = "default_maptos_indexer_grpc_loopback_port")]
+	pub maptos_indexer_grpc_loopback_port: u16,
+
+	/// The worker threads of each runtime serving gRpc, and of the TLS front, 0 for one per CPU
+	#[serde(default = "default_maptos_indexer_worker_threads")]
+	pub maptos_indexer_worker_threads: u32,
+
+	/// Whether the node serves the gRpc indexer stream
+	#[serde(default = "default_maptos_indexer_grpc_enabled")]
+	pub maptos_indexer_grpc_enabled: bool,
+
+	/// The committed versions the indexer may fall behind before the node reports not ready,
+	/// 0 to never report it
+	#[serde(default = "default_maptos_indexer_max_lag_versions")]
+	pub maptos_indexer_max_lag_versions: u64,
+
+	/// The most transactions sent in one message of the gRpc stream
+	#[serde(default = "default_maptos_indexer_grpc_batch_size")]
+	pub maptos_indexer_grpc_batch_size: u32,
+
+	/// The gRpc stream messages buffered for a client before the stream waits for it to
+	/// read them
+	#[serde(default = "default_maptos_indexer_grpc_send_buffer_size")]
+	pub maptos_indexer_grpc_send_buffer_size: u32,
+
+	/// The compression of the gRpc stream
+	#[serde(default = "default_maptos_indexer_grpc_compression")]
+	pub maptos_indexer_grpc_compression: GrpcCompression,
 }
 
 impl Default for Config {
@@ -32,6 +149,276 @@ impl Default for Config {
 			),
 			maptos_indexer_grpc_inactivity_ping_interval: default_maptos_indexer_grpc_ping_interval(
 			),
+			maptos_indexer_grpc_max_message_bytes: default_maptos_indexer_grpc_max_message_bytes(),
+			maptos_indexer_grpc_tcp_keepalive_sec: default_maptos_indexer_grpc_tcp_keepalive_sec(),
+			maptos_indexer_grpc_http2_keepalive_sec:
+				default_maptos_indexer_grpc_http2_keepalive_sec(),
+			maptos_indexer_grpc_tls_cert: default_maptos_indexer_grpc_tls_cert(),
+			maptos_indexer_grpc_tls_key: default_maptos_indexer_grpc_tls_key(),
+			maptos_indexer_grpc_client_ca: default_maptos_indexer_grpc_client_ca(),
+			maptos_indexer_grpc_loopback_port: default_maptos_indexer_grpc_loopback_port(),
+			maptos_indexer_worker_threads: default_maptos_indexer_worker_threads(),
+			maptos_indexer_grpc_enabled: default_maptos_indexer_grpc_enabled(),
+			maptos_indexer_max_lag_versions: default_maptos_indexer_max_lag_versions(),
+			maptos_indexer_grpc_batch_size: default_maptos_indexer_grpc_batch_size(),
+			maptos_indexer_grpc_send_buffer_size: default_maptos_indexer_grpc_send_buffer_size(),
+			maptos_indexer_grpc_compression: default_maptos_indexer_grpc_compression(),
 		}
 	}
 }
+
+impl Config {
+	pub fn inactivity_timeout(&self) -> Duration {
+		self.maptos_indexer_grpc_inactivity_timeout.get()
+	}
+
+	pub fn ping_interval(&self) -> Duration {
+		self.maptos_indexer_grpc_inactivity_ping_interval.get()
+	}
+
+	/// The gRpc message size limit, saturating on targets where it exceeds the address space.
+	pub fn max_message_bytes(&self) -> usize {
+		usize::try_from(self.maptos_indexer_grpc_max_message_bytes).unwrap_or(usize::MAX)
+	}
+
+	/// The TCP keepalive interval of client connections, or `None` when disabled.
+	pub fn tcp_keepalive(&self) -> Option<Duration> {
+		keepalive(self.maptos_indexer_grpc_tcp_keepalive_sec)
+	}
+
+	/// The HTTP/2 keepalive ping interval of client connections, or `None` when disabled.
+	///
+	/// Unlike the ping interval, which the server uses to keep streams alive,
+	/// these pings come from the client and also detect a dead connection while idle.
+	pub fn http2_keepalive(&self) -> Option<Duration> {
+		keepalive(self.maptos_indexer_grpc_http2_keepalive_sec)
+	}
+
+	/// The most transactions sent in one message of the gRpc stream, at least 1.
+	pub fn batch_size(&self) -> usize {
+		self.maptos_indexer_grpc_batch_size.max(1) as usize
+	}
+
+	/// The worker threads of each runtime serving gRpc, one per CPU unless configured.
+	pub fn worker_threads(&self) -> usize {
+		resolve_worker_threads(self.maptos_indexer_worker_threads)
+	}
+}
+
+fn keepalive(interval: DurationSecs) -> Option<Duration> {
+	(!interval.is_zero()).then(|| interval.get())
+}
+
+/// The committed versions the indexer has yet to process, given the next version each expects.
+pub fn indexer_lag(ledger_next_version: u64, indexer_next_version: u64) -> u64 {
+	ledger_next_version.saturating_sub(indexer_next_version)
+}
+
+/// What a consumer of the indexer gRPC service needs to reach it and keep its stream alive,
+/// as served to them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct IndexerGrpcMetadata {
+	/// The hostname clients connect to, which need not be the one the server listens on
+	pub hostname: String,
+
+	/// The port clients connect to
+	pub port: u16,
+
+	/// Whether the service is served over TLS
+	pub tls: bool,
+
+	/// The chain id of the transactions served
+	pub chain_id: u8,
+
+	/// How often the server pings an idle stream, in seconds unless it has a unit
+	pub ping_interval_sec: DurationSecs,
+
+	/// How long a stream may stay idle before it is closed, in seconds unless it has a unit
+	pub inactivity_timeout_sec: DurationSecs,
+}
+
+impl super::Config {
+	/// The port on 127.0.0.1 the gRpc is served on in plaintext behind the TLS front,
+	/// or `None` when it is not served over TLS or only the front reaches it.
+	pub fn indexer_grpc_loopback_port(&self) -> Option<u16> {
+		let port = self.indexer.maptos_indexer_grpc_loopback_port;
+		(self.indexer_grpc_tls_enabled() && port != 0).then_some(port)
+	}
+
+	/// How many gRpc stream messages are buffered for a slow client before the stream waits
+	/// for it, so that it slows the node producing them rather than piling them up.
+	pub fn indexer_grpc_send_buffer(&self) -> NonZeroUsize {
+		usize::try_from(self.indexer.maptos_indexer_grpc_send_buffer_size)
+			.ok()
+			.and_then(NonZeroUsize::new)
+			.unwrap_or(NonZeroUsize::MIN)
+	}
+
+	/// How many committed versions the indexer may fall behind before the node reports
+	/// not ready, or `None` when the lag is not checked.
+	pub fn indexer_max_lag(&self) -> Option<u64> {
+		Some(self.indexer.maptos_indexer_max_lag_versions).filter(|max_lag| *max_lag != 0)
+	}
+
+	/// Whether an indexer `lag` versions behind is too far behind for the node to be ready.
+	pub fn indexer_lag_exceeded(&self, lag: u64) -> bool {
+		self.indexer_max_lag().map_or(false, |max_lag| lag > max_lag)
+	}
+
+	/// The metadata of the indexer gRPC service as its consumers see it,
+	/// with the advertised connection address rather than the listen address.
+	pub fn indexer_grpc_metadata(&self) -> IndexerGrpcMetadata {
+		IndexerGrpcMetadata {
+			hostname: self.client.maptos_indexer_grpc_connection_hostname.clone(),
+			port: self.client.maptos_indexer_grpc_connection_port,
+			tls: self.indexer_grpc_tls_enabled(),
+			chain_id: self.chain.maptos_chain_id.id(),
+			ping_interval_sec: self.indexer.maptos_indexer_grpc_inactivity_ping_interval,
+			inactivity_timeout_sec: self.indexer.maptos_indexer_grpc_inactivity_timeout,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_max_message_bytes_default() {
+		let _env = godfig::env::lock();
+		std::env::remove_var("MAPTOS_INDEXER_GRPC_MAX_MESSAGE_BYTES");
+		assert_eq!(default_maptos_indexer_grpc_max_message_bytes(), 64 * 1024 * 1024);
+
+		std::env::set_var("MAPTOS_INDEXER_GRPC_MAX_MESSAGE_BYTES", "128MiB");
+		assert_eq!(default_maptos_indexer_grpc_max_message_bytes(), 128 * 1024 * 1024);
+		std::env::remove_var("MAPTOS_INDEXER_GRPC_MAX_MESSAGE_BYTES");
+	}
+
+	#[test]
+	fn test_loopback_port_needs_tls() {
+		let _env = godfig::env::read_lock();
+		let mut config = super::super::Config::default();
+		config.indexer.maptos_indexer_grpc_loopback_port = 30735;
+		assert_eq!(config.indexer_grpc_loopback_port(), None);
+
+		config.indexer.maptos_indexer_grpc_tls_cert = "/etc/maptos/grpc.crt".to_string();
+		config.indexer.maptos_indexer_grpc_tls_key = "/etc/maptos/grpc.key".to_string();
+		assert_eq!(config.indexer_grpc_loopback_port(), Some(30735));
+
+		config.indexer.maptos_indexer_grpc_loopback_port = 0;
+		assert_eq!(config.indexer_grpc_loopback_port(), None);
+	}
+
+	#[test]
+	fn test_batch_size_defaults_and_is_at_least_one() {
+		let _env = godfig::env::lock();
+		std::env::remove_var("MAPTOS_INDEXER_GRPC_BATCH_SIZE");
+		assert_eq!(default_maptos_indexer_grpc_batch_size(), 1000);
+
+		let mut config = Config::default();
+		assert_eq!(config.batch_size(), 1000);
+		// A batch size of 0 still makes progress, a transaction at a time.
+		config.maptos_indexer_grpc_batch_size = 0;
+		assert_eq!(config.batch_size(), 1);
+	}
+
+	#[test]
+	fn test_compression_parses_each_accepted_value() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::lock();
+		std::env::remove_var("MAPTOS_INDEXER_GRPC_COMPRESSION");
+		assert_eq!(default_maptos_indexer_grpc_compression(), GrpcCompression::None);
+
+		for (raw, compression) in [
+			("none", GrpcCompression::None),
+			("gzip", GrpcCompression::Gzip),
+			(" ZSTD ", GrpcCompression::Zstd),
+		] {
+			assert_eq!(raw.parse::<GrpcCompression>()?, compression);
+			assert_eq!(compression.to_string().parse::<GrpcCompression>()?, compression);
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn test_unknown_compression_lists_the_accepted_values() {
+		let error = "brotli".parse::<GrpcCompression>().unwrap_err();
+		assert_eq!(
+			error.to_string(),
+			"unknown gRPC compression \"brotli\", expected one of: none, gzip, zstd"
+		);
+	}
+
+	#[test]
+	fn test_zero_keepalive_is_disabled() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.maptos_indexer_grpc_tcp_keepalive_sec = DurationSecs::from_secs(30);
+		config.maptos_indexer_grpc_http2_keepalive_sec = DurationSecs::ZERO;
+		assert_eq!(config.tcp_keepalive(), Some(Duration::from_secs(30)));
+		assert_eq!(config.http2_keepalive(), None);
+
+		config.maptos_indexer_grpc_tcp_keepalive_sec = DurationSecs::ZERO;
+		config.maptos_indexer_grpc_http2_keepalive_sec = DurationSecs::from_millis(15_500);
+		assert_eq!(config.tcp_keepalive(), None);
+		assert_eq!(config.http2_keepalive(), Some(Duration::from_millis(15_500)));
+	}
+
+	#[test]
+	fn test_indexer_lag_beyond_the_maximum_is_not_ready() {
+		let _env = godfig::env::read_lock();
+		let mut config = super::super::Config::default();
+		config.indexer.maptos_indexer_max_lag_versions = 1000;
+		assert_eq!(config.indexer_max_lag(), Some(1000));
+		for (ledger_next_version, indexer_next_version, exceeded) in [
+			(5_000, 5_000, false),
+			(5_000, 4_000, false),
+			(5_001, 4_000, true),
+			(100_000, 0, true),
+			// An indexer ahead of the ledger it reads, as while a new ledger info lands, is not behind.
+			(4_000, 5_000, false),
+		] {
+			let lag = indexer_lag(ledger_next_version, indexer_next_version);
+			assert_eq!(
+				config.indexer_lag_exceeded(lag),
+				exceeded,
+				"ledger at {}, indexer at {}",
+				ledger_next_version,
+				indexer_next_version
+			);
+		}
+
+		config.indexer.maptos_indexer_max_lag_versions = 0;
+		assert_eq!(config.indexer_max_lag(), None);
+		assert!(!config.indexer_lag_exceeded(indexer_lag(u64::MAX, 0)));
+	}
+
+	#[test]
+	fn test_metadata_advertises_the_connection_address() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::read_lock();
+		let mut config = super::super::Config::default();
+		config.indexer.maptos_indexer_grpc_listen_hostname = "0.0.0.0".to_string();
+		config.indexer.maptos_indexer_grpc_listen_port = 30734;
+		config.indexer.maptos_indexer_grpc_tls_cert = String::new();
+		config.indexer.maptos_indexer_grpc_inactivity_ping_interval = DurationSecs::from_secs(10);
+		config.indexer.maptos_indexer_grpc_inactivity_timeout = DurationSecs::from_secs(60);
+		config.client.maptos_indexer_grpc_connection_hostname = "indexer.example".to_string();
+		config.client.maptos_indexer_grpc_connection_port = 443;
+		config.chain.maptos_chain_id = aptos_types::chain_id::ChainId::new(250);
+
+		let metadata = config.indexer_grpc_metadata();
+		assert_eq!(
+			metadata,
+			IndexerGrpcMetadata {
+				hostname: "indexer.example".to_string(),
+				port: 443,
+				tls: false,
+				chain_id: 250,
+				ping_interval_sec: DurationSecs::from_secs(10),
+				inactivity_timeout_sec: DurationSecs::from_secs(60),
+			}
+		);
+		let served: IndexerGrpcMetadata = serde_json::from_value(serde_json::to_value(&metadata)?)?;
+		assert_eq!(served, metadata);
+		Ok(())
+	}
+}
@@ -1,19 +1,189 @@
 //! Configuration for load-sheding limits.
 
-use super::common::default_max_transactions_in_flight;
+use super::common::{
+	default_maptos_inflight_overflow_policy, default_maptos_mempool_ordering,
+	default_max_transactions_in_flight,
+};
 
 use serde::{Deserialize, Serialize};
 
+use std::fmt;
+use std::str::FromStr;
+
+/// What happens to a transaction submitted while every slot in flight is taken.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OverflowPolicy {
+	/// The transaction is refused, and the REST API answers the submission with 503.
+	Reject,
+	/// The submission waits for a slot to be freed, up to the REST request timeout,
+	/// and is refused as for `reject` if none is.
+	Block,
+	/// The submission waits for a slot as for `block`, but no more submissions wait than
+	/// may be in flight: once that many do, the one waiting longest is refused as for
+	/// `reject` to make room. Accepted transactions are never dropped.
+	DropOldest,
+}
+
+impl OverflowPolicy {
+	pub const ACCEPTED: &'static [&'static str] = &["reject", "block", "drop_oldest"];
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			OverflowPolicy::Reject => "reject",
+			OverflowPolicy::Block => "block",
+			OverflowPolicy::DropOldest => "drop_oldest",
+		}
+	}
+}
+
+impl FromStr for OverflowPolicy {
+	type Err = anyhow::Error;
+
+	fn from_str(raw: &str) -> Result<Self, Self::Err> {
+		match raw.trim().to_ascii_lowercase().as_str() {
+			"reject" => Ok(OverflowPolicy::Reject),
+			"block" => Ok(OverflowPolicy::Block),
+			"drop_oldest" => Ok(OverflowPolicy::DropOldest),
+			_ => anyhow::bail!(
+				"unknown overflow policy {:?}, expected one of: {}",
+				raw,
+				Self::ACCEPTED.join(", ")
+			),
+		}
+	}
+}
+
+impl fmt::Display for OverflowPolicy {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+/// The order accepted transactions waiting for execution are passed on in.
+///
+/// The transactions of one sender are passed on in the order they were accepted either way,
+/// so that a sender's sequence numbers are never executed out of turn.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MempoolOrdering {
+	/// The order they were accepted in.
+	Fifo,
+	/// The highest gas unit price first, the earliest accepted first among equal prices.
+	GasPrice,
+}
+
+impl MempoolOrdering {
+	pub const ACCEPTED: &'static [&'static str] = &["fifo", "gas_price"];
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			MempoolOrdering::Fifo => "fifo",
+			MempoolOrdering::GasPrice => "gas_price",
+		}
+	}
+}
+
+impl FromStr for MempoolOrdering {
+	type Err = anyhow::Error;
+
+	fn from_str(raw: &str) -> Result<Self, Self::Err> {
+		match raw.trim().to_ascii_lowercase().as_str() {
+			"fifo" => Ok(MempoolOrdering::Fifo),
+			"gas_price" => Ok(MempoolOrdering::GasPrice),
+			_ => anyhow::bail!(
+				"unknown mempool ordering {:?}, expected one of: {}",
+				raw,
+				Self::ACCEPTED.join(", ")
+			),
+		}
+	}
+}
+
+impl fmt::Display for MempoolOrdering {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Config {
 	/// The maximum number of transactions permitted to be in flight
 	/// before new transactions are rejected.
 	#[serde(default = "default_max_transactions_in_flight")]
 	pub max_transactions_in_flight: u64,
+
+	/// What happens to transactions submitted once `max_transactions_in_flight` is reached
+	#[serde(default = "default_maptos_inflight_overflow_policy")]
+	pub inflight_overflow_policy: OverflowPolicy,
+
+	/// The order accepted transactions are passed on for execution in
+	#[serde(default = "default_maptos_mempool_ordering")]
+	pub mempool_ordering: MempoolOrdering,
 }
 
 impl Default for Config {
 	fn default() -> Self {
-		Self { max_transactions_in_flight: default_max_transactions_in_flight() }
+		Self {
+			max_transactions_in_flight: default_max_transactions_in_flight(),
+			inflight_overflow_policy: default_maptos_inflight_overflow_policy(),
+			mempool_ordering: default_maptos_mempool_ordering(),
+		}
+	}
+}
+
+/// How many state versions the state merkle prune window should keep per transaction in flight.
+///
+/// Every in-flight transaction commits at least one version, and a full batch may be admitted
+/// while the previous one is still executing against the state it read. Keeping twice the
+/// in-flight bound leaves room for both before the state they read is pruned.
+pub const STATE_MERKLE_WINDOW_PER_IN_FLIGHT: u64 = 2;
+
+impl Config {
+	/// The smallest state merkle prune window which keeps the state read by in-flight
+	/// transactions, by the heuristic of [`STATE_MERKLE_WINDOW_PER_IN_FLIGHT`].
+	pub fn recommended_min_state_merkle_prune_window(&self) -> u64 {
+		self.max_transactions_in_flight
+			.saturating_mul(STATE_MERKLE_WINDOW_PER_IN_FLIGHT)
+	}
+}
+
+impl super::Config {
+	/// The order accepted transactions are passed on for execution in.
+	pub fn mempool_ordering(&self) -> MempoolOrdering {
+		self.load_shedding.mempool_ordering
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_overflow_policies_parse() {
+		let _env = godfig::env::read_lock();
+		assert_eq!("reject".parse::<OverflowPolicy>().unwrap(), OverflowPolicy::Reject);
+		assert_eq!(" Drop_Oldest ".parse::<OverflowPolicy>().unwrap(), OverflowPolicy::DropOldest);
+		assert!("drop-newest".parse::<OverflowPolicy>().is_err());
+		assert_eq!(Config::default().inflight_overflow_policy, OverflowPolicy::Reject);
+	}
+
+	#[test]
+	fn test_mempool_orderings_parse() {
+		let _env = godfig::env::read_lock();
+		assert_eq!("fifo".parse::<MempoolOrdering>().unwrap(), MempoolOrdering::Fifo);
+		assert_eq!(" Gas_Price ".parse::<MempoolOrdering>().unwrap(), MempoolOrdering::GasPrice);
+		let err = "lifo".parse::<MempoolOrdering>().unwrap_err().to_string();
+		assert!(err.contains("fifo, gas_price"), "{}", err);
+		assert_eq!(super::super::Config::default().mempool_ordering(), MempoolOrdering::GasPrice);
+	}
+
+	#[test]
+	fn test_recommended_window_scales_with_in_flight() {
+		let config = Config { max_transactions_in_flight: 12_000, ..Default::default() };
+		assert_eq!(config.recommended_min_state_merkle_prune_window(), 24_000);
+
+		let config = Config { max_transactions_in_flight: u64::MAX, ..Default::default() };
+		assert_eq!(config.recommended_min_state_merkle_prune_window(), u64::MAX);
 	}
 }
@@ -0,0 +1,436 @@
+//! Configuration for the tracing subscriber.
+
+use super::common::{
+	default_maptos_log_format, default_maptos_log_level, default_maptos_otlp_endpoint,
+	default_maptos_otlp_sample_rate,
+};
+use super::validation::ValidationError;
+
+use opentelemetry_sdk::trace::Sampler;
+use serde::{Deserialize, Serialize};
+use tracing_subscriber::{
+	layer::SubscriberExt as _, util::SubscriberInitExt as _, EnvFilter, Layer,
+};
+use url::Url;
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::OnceLock;
+
+/// How log lines are rendered.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogFormat {
+	/// One JSON object per line, for log collectors.
+	Json,
+	/// Multi-line, human readable output.
+	Pretty,
+	/// One human readable line per event.
+	Compact,
+}
+
+impl LogFormat {
+	pub const ACCEPTED: &'static [&'static str] = &["json", "pretty", "compact"];
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			LogFormat::Json => "json",
+			LogFormat::Pretty => "pretty",
+			LogFormat::Compact => "compact",
+		}
+	}
+}
+
+impl FromStr for LogFormat {
+	type Err = anyhow::Error;
+
+	fn from_str(raw: &str) -> Result<Self, Self::Err> {
+		match raw.trim().to_ascii_lowercase().as_str() {
+			"json" => Ok(LogFormat::Json),
+			"pretty" => Ok(LogFormat::Pretty),
+			"compact" => Ok(LogFormat::Compact),
+			_ => anyhow::bail!(
+				"unknown log format {:?}, expected one of: {}",
+				raw,
+				Self::ACCEPTED.join(", ")
+			),
+		}
+	}
+}
+
+impl fmt::Display for LogFormat {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+/// The least severe events which are logged.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum LogLevel {
+	Trace,
+	Debug,
+	Info,
+	Warn,
+	Error,
+}
+
+impl LogLevel {
+	pub const ACCEPTED: &'static [&'static str] = &["trace", "debug", "info", "warn", "error"];
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			LogLevel::Trace => "trace",
+			LogLevel::Debug => "debug",
+			LogLevel::Info => "info",
+			LogLevel::Warn => "warn",
+			LogLevel::Error => "error",
+		}
+	}
+}
+
+impl FromStr for LogLevel {
+	type Err = anyhow::Error;
+
+	fn from_str(raw: &str) -> Result<Self, Self::Err> {
+		match raw.trim().to_ascii_lowercase().as_str() {
+			"trace" => Ok(LogLevel::Trace),
+			"debug" => Ok(LogLevel::Debug),
+			"info" => Ok(LogLevel::Info),
+			"warn" => Ok(LogLevel::Warn),
+			"error" => Ok(LogLevel::Error),
+			_ => anyhow::bail!(
+				"unknown log level {:?}, expected one of: {}",
+				raw,
+				Self::ACCEPTED.join(", ")
+			),
+		}
+	}
+}
+
+impl fmt::Display for LogLevel {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+/// The fraction of requests which are logged, from 0.0 for none to 1.0 for all.
+///
+/// Any number parses; [`Config::validate`](super::Config::validate) rejects those out of range.
+#[derive(Debug, Clone, Copy, PartialEq, PartialOrd, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct SampleRate(f64);
+
+// Rates out of range, NaN among them, fail validation, so the rates in use compare reflexively.
+impl Eq for SampleRate {}
+
+impl SampleRate {
+	pub const OFF: SampleRate = SampleRate(0.0);
+	pub const ALL: SampleRate = SampleRate(1.0);
+
+	pub fn new(rate: f64) -> Self {
+		SampleRate(rate)
+	}
+
+	pub fn get(&self) -> f64 {
+		self.0
+	}
+
+	pub fn is_valid(&self) -> bool {
+		(0.0..=1.0).contains(&self.0)
+	}
+
+	/// Decides at random whether to log one request.
+	pub fn sample(&self) -> bool {
+		self.0 > 0.0 && rand::random::<f64>() < self.0
+	}
+}
+
+impl FromStr for SampleRate {
+	type Err = anyhow::Error;
+
+	fn from_str(raw: &str) -> Result<Self, Self::Err> {
+		raw.trim()
+			.parse()
+			.map(SampleRate)
+			.map_err(|_| anyhow::anyhow!("invalid sample rate {:?}, expected 0.0 to 1.0", raw))
+	}
+}
+
+impl fmt::Display for SampleRate {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		self.0.fmt(f)
+	}
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Config {
+	/// How log lines are rendered
+	#[serde(default = "default_maptos_log_format")]
+	pub maptos_log_format: LogFormat,
+
+	/// The least severe events which are logged
+	#[serde(default = "default_maptos_log_level")]
+	pub maptos_log_level: LogLevel,
+
+	/// The OTLP collector spans are exported to, empty to export none
+	#[serde(default = "default_maptos_otlp_endpoint")]
+	pub maptos_otlp_endpoint: String,
+
+	/// The fraction of traces exported over OTLP
+	#[serde(default = "default_maptos_otlp_sample_rate")]
+	pub maptos_otlp_sample_rate: SampleRate,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self {
+			maptos_log_format: default_maptos_log_format(),
+			maptos_log_level: default_maptos_log_level(),
+			maptos_otlp_endpoint: default_maptos_otlp_endpoint(),
+			maptos_otlp_sample_rate: default_maptos_otlp_sample_rate(),
+		}
+	}
+}
+
+/// Where spans are exported over OTLP, and how many.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct OtlpConfig {
+	/// The gRPC endpoint of the collector, such as `http://localhost:4317`.
+	pub endpoint: String,
+	pub sample_rate: SampleRate,
+}
+
+impl OtlpConfig {
+	/// Whether the endpoint is an http or https URL, as the exporter needs.
+	pub fn endpoint_is_valid(&self) -> bool {
+		Url::parse(&self.endpoint)
+			.is_ok_and(|url| matches!(url.scheme(), "http" | "https") && url.has_host())
+	}
+
+	/// The first reason this config cannot export spans, as validation reports it.
+	fn check(&self) -> Result<(), ValidationError> {
+		if !self.endpoint_is_valid() {
+			return Err(ValidationError::InvalidOtlpEndpoint {
+				var: "MAPTOS_OTLP_ENDPOINT",
+				value: self.endpoint.clone(),
+			});
+		}
+		if !self.sample_rate.is_valid() {
+			return Err(ValidationError::OutOfRange {
+				var: "MAPTOS_OTLP_SAMPLE_RATE",
+				value: self.sample_rate.to_string(),
+				expected: "between 0.0 and 1.0",
+			});
+		}
+		Ok(())
+	}
+}
+
+/// Flushes the spans not yet exported over OTLP and stops exporting them when dropped.
+///
+/// Returned by [`Config::init_tracing`](super::Config::init_tracing), and held until the
+/// process exits.
+#[must_use = "spans stop being exported when the guard is dropped"]
+#[derive(Debug)]
+pub struct TracingGuard {
+	exporting: bool,
+}
+
+impl Drop for TracingGuard {
+	fn drop(&mut self) {
+		if self.exporting {
+			opentelemetry::global::shutdown_tracer_provider();
+		}
+	}
+}
+
+/// Sets the level of the installed tracing subscriber.
+type LogLevelHandle = Box<dyn Fn(LogLevel) -> Result<(), anyhow::Error> + Send + Sync>;
+
+/// How the level of the installed tracing subscriber is changed, once one registered.
+static LOG_LEVEL_HANDLE: OnceLock<LogLevelHandle> = OnceLock::new();
+
+/// Registers how the level of the tracing subscriber a binary installed is changed, for
+/// [`apply_log_level`] to set the configured level. Only the first registration is kept.
+///
+/// [`Config::init_tracing`](super::Config::init_tracing) registers its own subscriber's.
+pub fn register_log_level_handle(
+	handle: impl Fn(LogLevel) -> Result<(), anyhow::Error> + Send + Sync + 'static,
+) {
+	if LOG_LEVEL_HANDLE.set(Box::new(handle)).is_err() {
+		tracing::warn!("A log level handle is already registered, ignoring another");
+	}
+}
+
+/// Sets the level of the installed tracing subscriber, as when the config is loaded or
+/// reloaded, returning whether a handle was registered to set it.
+pub fn apply_log_level(level: LogLevel) -> bool {
+	let Some(handle) = LOG_LEVEL_HANDLE.get() else {
+		return false;
+	};
+	match handle(level) {
+		Ok(()) => tracing::info!("Logging at level {}", level),
+		Err(e) => tracing::warn!("Failed to set the log level to {}: {:#}", level, e),
+	}
+	true
+}
+
+/// The filter logging at `level`, unless `RUST_LOG` is set.
+fn level_filter(level: LogLevel) -> EnvFilter {
+	EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new(level.as_str()))
+}
+
+/// The runtime exporting spans when tracing is installed outside of one, kept for the life
+/// of the process.
+static OTLP_RUNTIME: OnceLock<tokio::runtime::Runtime> = OnceLock::new();
+
+/// A tracing layer exporting the spans of the sampled traces to the collector.
+fn otlp_layer<S>(otlp: &OtlpConfig) -> Result<impl Layer<S>, anyhow::Error>
+where
+	S: tracing::Subscriber + for<'span> tracing_subscriber::registry::LookupSpan<'span>,
+{
+	// The exporter spawns its connection onto the current runtime.
+	let _guard = match tokio::runtime::Handle::try_current() {
+		Ok(_) => None,
+		Err(_) => {
+			let runtime = tokio::runtime::Builder::new_multi_thread()
+				.worker_threads(1)
+				.thread_name("otlp-export")
+				.enable_all()
+				.build()?;
+			Some(OTLP_RUNTIME.get_or_init(|| runtime).enter())
+		}
+	};
+	let sampler =
+		Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(otlp.sample_rate.get())));
+	let tracer = opentelemetry_otlp::new_pipeline()
+		.tracing()
+		.with_exporter(opentelemetry_otlp::new_exporter().tonic().with_endpoint(&otlp.endpoint))
+		.with_trace_config(opentelemetry_sdk::trace::config().with_sampler(sampler))
+		.install_batch(opentelemetry_sdk::runtime::Tokio)
+		.map_err(|e| anyhow::anyhow!("failed to export spans to {}: {}", otlp.endpoint, e))?;
+	Ok(tracing_opentelemetry::layer().with_tracer(tracer))
+}
+
+impl super::Config {
+	/// Where spans are exported over OTLP, `None` unless `MAPTOS_OTLP_ENDPOINT` is set.
+	pub fn otlp(&self) -> Option<OtlpConfig> {
+		let endpoint = self.logging.maptos_otlp_endpoint.trim();
+		(!endpoint.is_empty()).then(|| OtlpConfig {
+			endpoint: endpoint.to_string(),
+			sample_rate: self.logging.maptos_otlp_sample_rate,
+		})
+	}
+
+	/// Installs the global tracing subscriber for the configured format and level, exporting
+	/// spans over OTLP as well when [`Config::otlp`](super::Config::otlp) is set.
+	///
+	/// `RUST_LOG`, when set, takes precedence over the level for per-target filtering.
+	/// The level can be changed later with [`apply_log_level`].
+	/// Fails if a global subscriber is already installed, or if the OTLP endpoint or sample
+	/// rate is invalid, as the config may not have been validated yet.
+	pub fn init_tracing(&self) -> Result<TracingGuard, anyhow::Error> {
+		let otlp = self.otlp();
+		if let Some(otlp) = &otlp {
+			otlp.check()?;
+		}
+		let (filter, handle) =
+			tracing_subscriber::reload::Layer::new(level_filter(self.logging.maptos_log_level));
+		let fmt = tracing_subscriber::fmt::layer();
+		let fmt = match self.logging.maptos_log_format {
+			LogFormat::Json => fmt.json().boxed(),
+			LogFormat::Pretty => fmt.pretty().boxed(),
+			LogFormat::Compact => fmt.compact().boxed(),
+		};
+		let exporting = otlp.is_some();
+		let otlp = otlp.map(|otlp| otlp_layer(&otlp)).transpose()?;
+		tracing_subscriber::registry()
+			.with(fmt)
+			.with(otlp)
+			.with(filter)
+			.try_init()
+			.map_err(|e| anyhow::anyhow!("failed to install the tracing subscriber: {}", e))?;
+		register_log_level_handle(move |level| Ok(handle.reload(level_filter(level))?));
+		Ok(TracingGuard { exporting })
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_accepted_formats_parse() -> Result<(), anyhow::Error> {
+		for accepted in LogFormat::ACCEPTED {
+			assert_eq!(accepted.parse::<LogFormat>()?.as_str(), *accepted);
+		}
+		assert_eq!(" JSON ".parse::<LogFormat>()?, LogFormat::Json);
+		Ok(())
+	}
+
+	#[test]
+	fn test_unknown_format_lists_accepted() {
+		let err = "logfmt".parse::<LogFormat>().unwrap_err();
+		assert!(err.to_string().contains("json, pretty, compact"));
+	}
+
+	#[test]
+	fn test_sample_rate_parses() -> Result<(), anyhow::Error> {
+		assert_eq!(" 0.25 ".parse::<SampleRate>()?, SampleRate::new(0.25));
+		assert_eq!("1".parse::<SampleRate>()?, SampleRate::ALL);
+		assert!("often".parse::<SampleRate>().is_err());
+		assert!(!"1.5".parse::<SampleRate>()?.is_valid());
+		Ok(())
+	}
+
+	#[test]
+	fn test_zero_sample_rate_logs_nothing() {
+		assert!((0..10_000).all(|_| !SampleRate::OFF.sample()));
+		assert!((0..10_000).all(|_| SampleRate::ALL.sample()));
+	}
+
+	#[test]
+	fn test_levels_parse() -> Result<(), anyhow::Error> {
+		for accepted in LogLevel::ACCEPTED {
+			assert_eq!(accepted.parse::<LogLevel>()?.as_str(), *accepted);
+		}
+		let err = "verbose".parse::<LogLevel>().unwrap_err();
+		assert!(err.to_string().contains("trace, debug, info, warn, error"));
+		Ok(())
+	}
+
+	#[test]
+	fn test_otlp_is_disabled_by_default() {
+		let _env = godfig::env::read_lock();
+		let mut config = super::super::Config::default();
+		assert_eq!(config.otlp(), None);
+
+		config.logging.maptos_otlp_endpoint = "http://localhost:4317".to_string();
+		config.logging.maptos_otlp_sample_rate = SampleRate::new(0.1);
+		assert_eq!(
+			config.otlp(),
+			Some(OtlpConfig {
+				endpoint: "http://localhost:4317".to_string(),
+				sample_rate: SampleRate::new(0.1),
+			})
+		);
+	}
+
+	#[test]
+	fn test_invalid_otlp_config_is_refused_before_exporting() {
+		let otlp = |endpoint: &str, sample_rate: f64| OtlpConfig {
+			endpoint: endpoint.to_string(),
+			sample_rate: SampleRate::new(sample_rate),
+		};
+		assert_eq!(otlp("http://localhost:4317", 0.5).check(), Ok(()));
+		assert!(matches!(
+			otlp("localhost:4317", 0.5).check(),
+			Err(ValidationError::InvalidOtlpEndpoint { .. })
+		));
+		assert!(matches!(
+			otlp("http://localhost:4317", f64::NAN).check(),
+			Err(ValidationError::OutOfRange { var: "MAPTOS_OTLP_SAMPLE_RATE", .. })
+		));
+	}
+}
@@ -0,0 +1,33 @@
+//! Configuration for the Prometheus metrics endpoint.
+
+use super::common::{
+	default_maptos_metrics_enabled, default_maptos_metrics_listen_hostname,
+	default_maptos_metrics_listen_port,
+};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Config {
+	/// Whether `/metrics` is served
+	#[serde(default = "default_maptos_metrics_enabled")]
+	pub maptos_metrics_enabled: bool,
+
+	/// The hostname the metrics server binds
+	#[serde(default = "default_maptos_metrics_listen_hostname")]
+	pub maptos_metrics_listen_hostname: String,
+
+	/// The port the metrics server binds, separate from the API ports
+	#[serde(default = "default_maptos_metrics_listen_port")]
+	pub maptos_metrics_listen_port: u16,
+}
+
+impl Default for Config {
+	fn default() -> Self {
+		Self {
+			maptos_metrics_enabled: default_maptos_metrics_enabled(),
+			maptos_metrics_listen_hostname: default_maptos_metrics_listen_hostname(),
+			maptos_metrics_listen_port: default_maptos_metrics_listen_port(),
+		}
+	}
+}
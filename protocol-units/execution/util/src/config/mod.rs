@@ -1,14 +1,52 @@
+pub mod auth;
+pub mod builder;
 pub mod chain;
 pub mod client;
+pub mod clock;
+pub mod commit_batch;
 pub mod common;
+pub mod cors;
+pub mod diff;
+pub mod dns;
+pub mod duration;
+pub mod endpoints;
+pub mod env_vars;
+pub mod error;
 pub mod faucet;
+pub mod features;
+pub mod file;
 pub mod fin;
+pub mod fingerprint;
+pub mod health;
 pub mod indexer;
 pub mod indexer_processor;
 pub mod load_shedding;
+pub mod logging;
+pub mod mask;
+pub mod metrics;
+pub mod network;
+pub mod partial;
+pub mod ports;
+pub mod production;
+pub mod provenance;
+pub mod readiness;
+pub mod reload;
+pub mod rest_routes;
+pub mod rest_scopes;
+pub mod retry;
+pub mod schema;
+pub mod secrets;
+pub mod signer;
+pub mod snapshot;
+pub mod tls;
+pub mod validation;
+
+pub use signer::validate_private_key;
 
 use serde::{Deserialize, Serialize};
 
+use std::num::NonZeroUsize;
+
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Config {
 	/// The chain configuration
@@ -38,6 +76,26 @@ pub struct Config {
 	/// The load shedding parameters
 	#[serde(default)]
 	pub load_shedding: load_shedding::Config,
+
+	/// The health probe configuration
+	#[serde(default)]
+	pub health: health::Config,
+
+	/// The metrics endpoint configuration
+	#[serde(default)]
+	pub metrics: metrics::Config,
+
+	/// The logging configuration
+	#[serde(default)]
+	pub logging: logging::Config,
+
+	/// Where each field was taken from when the config was loaded
+	#[serde(skip)]
+	pub(crate) sources: provenance::Sources,
+
+	/// The sockets holding the ports allocated by [`Config::with_allocated_ports`]
+	#[serde(skip)]
+	pub(crate) port_probes: ports::PortProbes,
 }
 
 impl Default for Config {
@@ -50,7 +108,127 @@ impl Default for Config {
 			faucet: faucet::Config::default(),
 			fin: fin::Config::default(),
 			load_shedding: load_shedding::Config::default(),
+			health: health::Config::default(),
+			metrics: metrics::Config::default(),
+			logging: logging::Config::default(),
+			sources: provenance::Sources::from_env(),
+			port_probes: ports::PortProbes::default(),
+		}
+	}
+}
+
+impl Config {
+	/// The TLS files for the REST API, if it is served over TLS.
+	pub fn rest_tls(&self) -> Option<tls::TlsFiles> {
+		if !self.rest_tls_enabled() {
+			return None;
+		}
+		Some(tls::TlsFiles {
+			cert_path: self.chain.maptos_rest_tls_cert_path.clone().into(),
+			key_path: self.chain.maptos_rest_tls_key_path.clone().into(),
+		})
+	}
+
+	/// The TLS setup for the indexer gRPC server, if it is served over TLS.
+	pub fn indexer_grpc_tls(&self) -> Option<tls::IndexerGrpcTls> {
+		if !self.indexer_grpc_tls_enabled() {
+			return None;
 		}
+		let client_ca = &self.indexer.maptos_indexer_grpc_client_ca;
+		Some(tls::IndexerGrpcTls {
+			files: tls::TlsFiles {
+				cert_path: self.indexer.maptos_indexer_grpc_tls_cert.clone().into(),
+				key_path: self.indexer.maptos_indexer_grpc_tls_key.clone().into(),
+			},
+			client_ca_path: (!client_ca.is_empty()).then(|| client_ca.into()),
+		})
+	}
+
+	/// Whether the indexer gRPC server is served over TLS.
+	pub fn indexer_grpc_tls_enabled(&self) -> bool {
+		!self.indexer.maptos_indexer_grpc_tls_cert.is_empty()
+			&& !self.indexer.maptos_indexer_grpc_tls_key.is_empty()
+	}
+
+	/// What the health server answers on its port.
+	pub fn health_mode(&self) -> health::HealthMode {
+		self.health.maptos_health_mode
+	}
+
+	/// How the node catches up with the chain when it starts.
+	pub fn sync_mode(&self) -> chain::SyncMode {
+		self.chain.maptos_sync_mode
+	}
+
+	/// The compression of the indexer gRPC stream.
+	pub fn indexer_grpc_compression(&self) -> indexer::GrpcCompression {
+		self.indexer.maptos_indexer_grpc_compression
+	}
+
+	/// The Postgres connection string, as given in full or assembled from its components.
+	pub fn postgres_connection_string(&self) -> &str {
+		&self.indexer_processor.postgres_connection_string
+	}
+
+	/// The indexer processors to run.
+	pub fn enabled_processors(&self) -> &indexer_processor::ProcessorSet {
+		&self.indexer_processor.indexer_processor_enabled_processors
+	}
+
+	/// Whether the health server is enabled.
+	pub fn health_enabled(&self) -> bool {
+		self.health.maptos_health_enabled
+	}
+
+	/// Whether the metrics server is enabled.
+	pub fn metrics_enabled(&self) -> bool {
+		self.metrics.maptos_metrics_enabled
+	}
+
+	/// Whether the REST API is served.
+	pub fn rest_enabled(&self) -> bool {
+		self.chain.maptos_rest_enabled
+	}
+
+	/// Whether the faucet is enabled; it still does not start on a read-only node.
+	pub fn faucet_enabled(&self) -> bool {
+		self.faucet.maptos_faucet_enabled
+	}
+
+	/// Whether the fin view API is served.
+	pub fn fin_view_enabled(&self) -> bool {
+		self.fin.fin_rest_enabled
+	}
+
+	/// The cap on view function calls the fin view API executes at once, `None` when unlimited.
+	pub fn fin_max_concurrent_views(&self) -> Option<NonZeroUsize> {
+		self.fin.max_concurrent_views()
+	}
+
+	/// Whether the indexer gRPC service is served.
+	pub fn indexer_grpc_enabled(&self) -> bool {
+		self.indexer.maptos_indexer_grpc_enabled
+	}
+
+	/// The servers the node runs, by name.
+	pub fn enabled_surfaces(&self) -> Vec<&'static str> {
+		[
+			("rest", self.rest_enabled()),
+			("faucet", self.faucet_enabled() && !self.is_read_only()),
+			("fin_view", self.fin_view_enabled()),
+			("indexer_grpc", self.indexer_grpc_enabled()),
+			("health", self.health_enabled()),
+			("metrics", self.metrics_enabled()),
+		]
+		.into_iter()
+		.filter_map(|(surface, enabled)| enabled.then_some(surface))
+		.collect()
+	}
+
+	/// Whether both a certificate and a key are configured for the REST API.
+	pub fn rest_tls_enabled(&self) -> bool {
+		!self.chain.maptos_rest_tls_cert_path.is_empty()
+			&& !self.chain.maptos_rest_tls_key_path.is_empty()
 	}
 }
 
@@ -0,0 +1,185 @@
+//! A registry of known networks, so a node can be pointed at one by name.
+//!
+//! The registry maps each network name to its chain id and genesis, as TOML:
+//!
+//! ```toml
+//! [testnet]
+//! chain_id = 250
+//! genesis_url = "file:///etc/movement/testnet-genesis.blob"
+//! seed_peers = ["https://testnet.example:30731"]
+//! ```
+//!
+//! or the same table as JSON.
+
+use super::{common, Config};
+
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use url::Url;
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+
+/// The settings a known network implies.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Network {
+	pub chain_id: u8,
+
+	/// The genesis transaction as a path or `file:` URL, unset for the built-in genesis
+	#[serde(default)]
+	pub genesis_url: Option<String>,
+
+	/// The nodes to sync from, for clients and tooling; the node itself does not peer
+	#[serde(default)]
+	pub seed_peers: Vec<String>,
+}
+
+impl Network {
+	/// The local path of the genesis transaction, if the network has one.
+	///
+	/// Remote URLs are refused, as the genesis must be in place before the node starts.
+	pub fn genesis_blob_path(&self) -> Result<Option<PathBuf>, anyhow::Error> {
+		let Some(genesis_url) = self.genesis_url.as_deref() else {
+			return Ok(None);
+		};
+		match Url::parse(genesis_url) {
+			Ok(url) if url.scheme() == "file" => url
+				.to_file_path()
+				.map(Some)
+				.map_err(|()| anyhow::anyhow!("invalid genesis URL {:?}", genesis_url)),
+			Ok(_) => anyhow::bail!(
+				"genesis URL {:?} is not local; download it and set MAPTOS_GENESIS_BLOB_PATH",
+				genesis_url
+			),
+			Err(_) => Ok(Some(PathBuf::from(genesis_url))),
+		}
+	}
+}
+
+/// Known networks by name.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct NetworkRegistry {
+	networks: BTreeMap<String, Network>,
+}
+
+impl NetworkRegistry {
+	pub fn from_toml_str(contents: &str) -> Result<Self, anyhow::Error> {
+		toml::from_str(contents).context("failed to parse the network registry")
+	}
+
+	pub fn from_json_str(contents: &str) -> Result<Self, anyhow::Error> {
+		serde_json::from_str(contents).context("failed to parse the network registry")
+	}
+
+	/// Reads a registry file, as JSON when its extension is `.json` and as TOML otherwise.
+	pub fn read(path: &Path) -> Result<Self, anyhow::Error> {
+		let contents = std::fs::read_to_string(path)
+			.with_context(|| format!("failed to read network registry {}", path.display()))?;
+		let registry = if path.extension().is_some_and(|extension| extension == "json") {
+			Self::from_json_str(&contents)
+		} else {
+			Self::from_toml_str(&contents)
+		};
+		registry.with_context(|| format!("in network registry {}", path.display()))
+	}
+
+	/// The names of the known networks, sorted.
+	pub fn names(&self) -> impl Iterator<Item = &str> {
+		self.networks.keys().map(String::as_str)
+	}
+
+	/// The network called `name`, or an error listing the known networks.
+	pub fn get(&self, name: &str) -> Result<&Network, anyhow::Error> {
+		self.networks.get(name).with_context(|| {
+			format!(
+				"unknown network {:?}, expected one of: {}",
+				name,
+				self.names().collect::<Vec<_>>().join(", ")
+			)
+		})
+	}
+}
+
+impl Config {
+	/// Sets the chain id and genesis from the registry entry for the network called `name`.
+	///
+	/// An entry without a genesis selects the built-in one.
+	pub fn apply_network(
+		&mut self,
+		name: &str,
+		registry: &NetworkRegistry,
+	) -> Result<(), anyhow::Error> {
+		let network = registry.get(name)?;
+		let genesis_blob_path = network
+			.genesis_blob_path()
+			.with_context(|| format!("invalid genesis for network {:?}", name))?;
+		self.chain.maptos_chain_id = common::chain_id_from_u8(network.chain_id)
+			.with_context(|| format!("invalid chain id for network {:?}", name))?;
+		self.chain.maptos_genesis_blob_path = genesis_blob_path;
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use aptos_types::chain_id::ChainId;
+
+	const REGISTRY: &str = r#"
+		[testnet]
+		chain_id = 250
+		genesis_url = "file:///etc/movement/testnet-genesis.blob"
+		seed_peers = ["https://testnet.example:30731"]
+
+		[local]
+		chain_id = 27
+	"#;
+
+	#[test]
+	fn test_network_sets_chain_id_and_genesis() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::read_lock();
+		let registry = NetworkRegistry::from_toml_str(REGISTRY)?;
+		let mut config = Config::default();
+		config.apply_network("testnet", &registry)?;
+		assert_eq!(config.chain.maptos_chain_id, ChainId::new(250));
+		assert_eq!(
+			config.chain.maptos_genesis_blob_path,
+			Some(PathBuf::from("/etc/movement/testnet-genesis.blob"))
+		);
+
+		config.apply_network("local", &registry)?;
+		assert_eq!(config.chain.maptos_chain_id, ChainId::new(27));
+		assert_eq!(config.chain.maptos_genesis_blob_path, None);
+		Ok(())
+	}
+
+	#[test]
+	fn test_unknown_network_lists_the_known_ones() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::read_lock();
+		let registry = NetworkRegistry::from_json_str(r#"{"testnet": {"chain_id": 250}}"#)?;
+		let err = Config::default().apply_network("mainnet", &registry).unwrap_err();
+		assert_eq!(err.to_string(), r#"unknown network "mainnet", expected one of: testnet"#);
+		Ok(())
+	}
+
+	#[test]
+	fn test_chain_id_zero_is_refused() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::read_lock();
+		let registry = NetworkRegistry::from_json_str(r#"{"broken": {"chain_id": 0}}"#)?;
+		let err = Config::default().apply_network("broken", &registry).unwrap_err();
+		assert!(format!("{:#}", err).contains("chain id 0 is reserved"), "{:#}", err);
+		Ok(())
+	}
+
+	#[test]
+	fn test_remote_genesis_is_refused() {
+		let network = Network {
+			chain_id: 250,
+			genesis_url: Some("https://example.com/genesis.blob".to_string()),
+			seed_peers: Vec::new(),
+		};
+		assert!(network.genesis_blob_path().is_err());
+	}
+}
@@ -0,0 +1,397 @@
+//! Overrides layered over an already resolved [`Config`].
+//!
+//! Unlike the [`ConfigBuilder`](super::builder::ConfigBuilder), which resolves every unset field
+//! from the environment, merging a [`PartialConfig`] leaves unset fields exactly as they were.
+
+use super::chain::{AddressFormat, RestEncoding, SyncMode};
+use super::client::IndexerUpstream;
+use super::clock::ClockKind;
+use super::commit_batch::FsyncPolicy;
+use super::duration::DurationSecs;
+use super::features::FeatureFlags;
+use super::health::HealthMode;
+use super::indexer_processor::ProcessorSet;
+use super::load_shedding::{MempoolOrdering, OverflowPolicy};
+use super::rest_routes::RestRouteSet;
+use super::rest_scopes::RestScopedTokens;
+use super::signer::KeyScheme;
+use super::{
+	chain, client, faucet, fin, health, indexer, indexer_processor, load_shedding,
+	logging::{self, LogFormat, LogLevel, SampleRate},
+	metrics, Config,
+};
+
+use aptos_crypto::ed25519::Ed25519PrivateKey;
+use aptos_types::account_address::AccountAddress;
+use aptos_types::chain_id::ChainId;
+
+use std::path::PathBuf;
+
+/// Declares the partial form of a config section, whose fields are all optional.
+///
+/// Every field of the section must be listed either as overridable or as skipped;
+/// the exhaustive destructuring in `From` fails to compile when a new field is left out.
+macro_rules! partial_section {
+	(
+		$(#[$meta:meta])*
+		$name:ident for $module:ident {
+			$($field:ident: $ty:ty,)*
+		}
+		$(skip { $($skipped:ident),* })?
+	) => {
+		$(#[$meta])*
+		#[derive(Debug, Clone, Default, PartialEq, Eq)]
+		pub struct $name {
+			$(pub $field: Option<$ty>,)*
+		}
+
+		impl $name {
+			fn merge_into(self, section: &mut $module::Config) {
+				$(if let Some(value) = self.$field {
+					section.$field = value;
+				})*
+			}
+		}
+
+		impl From<$module::Config> for $name {
+			/// Sets every field, so that merging reproduces `section` exactly.
+			fn from(section: $module::Config) -> Self {
+				let $module::Config { $($field,)* $($($skipped: _,)*)? } = section;
+				Self { $($field: Some($field),)* }
+			}
+		}
+	};
+}
+
+partial_section! {
+	/// Overrides for [`chain::Config`].
+	///
+	/// `maptos_db_path` is doubly optional: `Some(None)` clears the path.
+	PartialChainConfig for chain {
+		maptos_chain_id: ChainId,
+		maptos_rest_listen_hostname: String,
+		maptos_rest_listen_port: u16,
+		maptos_rest_tls_cert_path: String,
+		maptos_rest_tls_key_path: String,
+		maptos_rest_cors_allowed_origins: Vec<String>,
+		maptos_rest_base_path: String,
+		maptos_rest_method_allowlist: RestRouteSet,
+		maptos_rest_scoped_tokens: RestScopedTokens,
+		maptos_rest_default_encoding: RestEncoding,
+		maptos_rest_address_format: AddressFormat,
+		maptos_rest_request_timeout_sec: DurationSecs,
+		maptos_rest_idle_timeout_sec: DurationSecs,
+		maptos_rest_max_body_bytes: u64,
+		maptos_rest_max_gas_amount: u64,
+		maptos_max_txn_future_sec: DurationSecs,
+		maptos_rest_max_page_size: u32,
+		maptos_rest_max_events_per_response: u32,
+		maptos_rest_max_transaction_bytes: u64,
+		maptos_rest_max_connections: u32,
+		maptos_rest_listen_backlog: u32,
+		maptos_rest_log_sample_rate: SampleRate,
+		maptos_rest_worker_threads: u32,
+		maptos_rest_enabled: bool,
+		maptos_expose_version: bool,
+		maptos_preload_enabled: bool,
+		maptos_preload_versions: u64,
+		maptos_shutdown_grace_sec: DurationSecs,
+		maptos_stop_at_version: Option<u64>,
+		maptos_read_only: bool,
+		maptos_allow_chain_id_mismatch: bool,
+		maptos_gas_unit_price: u64,
+		maptos_clock: ClockKind,
+		maptos_sync_mode: SyncMode,
+		maptos_key_scheme: KeyScheme,
+		maptos_feature_flags: FeatureFlags,
+		maptos_private_key: Ed25519PrivateKey,
+		maptos_enable_pruning: bool,
+		maptos_ledger_prune_window: u64,
+		maptos_epoch_snapshot_prune_window: u64,
+		maptos_state_merkle_prune_window: u64,
+		maptos_data_dir: PathBuf,
+		maptos_db_path: Option<PathBuf>,
+		maptos_db_block_cache_bytes: u64,
+		maptos_db_max_wal_bytes: u64,
+		maptos_max_db_size_bytes: u64,
+		maptos_commit_batch_size: u32,
+		maptos_commit_batch_max_delay_ms: u64,
+		maptos_fsync_policy: FsyncPolicy,
+		maptos_execution_concurrency: u32,
+		maptos_genesis_blob_path: Option<PathBuf>,
+		maptos_framework_path: Option<PathBuf>,
+	}
+	skip { signer, manual_clock }
+}
+
+partial_section! {
+	/// Overrides for [`indexer::Config`].
+	PartialIndexerConfig for indexer {
+		maptos_indexer_grpc_listen_hostname: String,
+		maptos_indexer_grpc_listen_port: u16,
+		maptos_indexer_grpc_inactivity_timeout: DurationSecs,
+		maptos_indexer_grpc_inactivity_ping_interval: DurationSecs,
+		maptos_indexer_grpc_max_message_bytes: u64,
+		maptos_indexer_grpc_tcp_keepalive_sec: DurationSecs,
+		maptos_indexer_grpc_http2_keepalive_sec: DurationSecs,
+		maptos_indexer_grpc_tls_cert: String,
+		maptos_indexer_grpc_tls_key: String,
+		maptos_indexer_grpc_client_ca: String,
+		maptos_indexer_grpc_loopback_port: u16,
+		maptos_indexer_worker_threads: u32,
+		maptos_indexer_grpc_enabled: bool,
+		maptos_indexer_max_lag_versions: u64,
+		maptos_indexer_grpc_batch_size: u32,
+		maptos_indexer_grpc_send_buffer_size: u32,
+		maptos_indexer_grpc_compression: indexer::GrpcCompression,
+	}
+}
+
+partial_section! {
+	/// Overrides for [`indexer_processor::Config`].
+	PartialIndexerProcessorConfig for indexer_processor {
+		postgres_connection_string: String,
+		postgres_max_connections: u32,
+		postgres_connection_timeout_sec: DurationSecs,
+		postgres_connect_retries: u32,
+		postgres_connect_retry_delay_ms: u64,
+		indexer_processor_auth_token: String,
+		indexer_processor_starting_version: u64,
+		indexer_processor_ending_version: Option<u64>,
+		indexer_processor_enabled_processors: ProcessorSet,
+	}
+}
+
+partial_section! {
+	/// Overrides for [`client::Config`].
+	PartialClientConfig for client {
+		maptos_rest_connection_hostname: String,
+		maptos_rest_connection_port: u16,
+		maptos_faucet_rest_connection_hostname: String,
+		maptos_faucet_rest_connection_port: u16,
+		maptos_indexer_grpc_connection_hostname: String,
+		maptos_indexer_grpc_connection_port: u16,
+		maptos_indexer_upstreams: Vec<IndexerUpstream>,
+		fin_rest_connection_hostname: String,
+		fin_rest_connection_port: u16,
+		maptos_dns_refresh_sec: DurationSecs,
+	}
+}
+
+partial_section! {
+	/// Overrides for [`faucet::Config`].
+	PartialFaucetConfig for faucet {
+		maptos_rest_connection_hostname: String,
+		maptos_rest_connection_port: u16,
+		maptos_faucet_rest_listen_hostname: String,
+		maptos_faucet_rest_listen_port: u16,
+		maptos_faucet_rest_cors_allowed_origins: Vec<String>,
+		maptos_faucet_rest_base_path: String,
+		maptos_faucet_rest_max_body_bytes: u64,
+		maptos_faucet_rest_idle_timeout_sec: DurationSecs,
+		maptos_faucet_rest_max_connections: u32,
+		maptos_faucet_rest_listen_backlog: u32,
+		faucet_rate_limit_per_ip_per_hour: u32,
+		faucet_max_amount_per_request: u64,
+		faucet_mint_amount: u64,
+		faucet_txn_expiration_sec: DurationSecs,
+		faucet_cooldown_sec: DurationSecs,
+		faucet_require_captcha: bool,
+		faucet_private_key: Option<Ed25519PrivateKey>,
+		faucet_private_keys: Vec<Ed25519PrivateKey>,
+		faucet_blocked_addresses: Vec<AccountAddress>,
+		faucet_allowlist_addresses: Vec<AccountAddress>,
+		maptos_faucet_enabled: bool,
+	}
+}
+
+partial_section! {
+	/// Overrides for [`fin::Config`].
+	PartialFinConfig for fin {
+		fin_rest_listen_hostname: String,
+		fin_rest_listen_port: u16,
+		fin_rest_request_timeout_sec: DurationSecs,
+		fin_rest_max_connections: u32,
+		fin_rest_listen_backlog: u32,
+		fin_rest_worker_threads: u32,
+		fin_rest_enabled: bool,
+		fin_rest_max_concurrent_views: u32,
+	}
+}
+
+partial_section! {
+	/// Overrides for [`load_shedding::Config`].
+	PartialLoadSheddingConfig for load_shedding {
+		max_transactions_in_flight: u64,
+		inflight_overflow_policy: OverflowPolicy,
+		mempool_ordering: MempoolOrdering,
+	}
+}
+
+partial_section! {
+	/// Overrides for [`health::Config`].
+	PartialHealthConfig for health {
+		maptos_health_enabled: bool,
+		maptos_health_listen_hostname: String,
+		maptos_health_listen_port: u16,
+		maptos_health_mode: HealthMode,
+		maptos_max_block_age_sec: DurationSecs,
+	}
+}
+
+partial_section! {
+	/// Overrides for [`metrics::Config`].
+	PartialMetricsConfig for metrics {
+		maptos_metrics_enabled: bool,
+		maptos_metrics_listen_hostname: String,
+		maptos_metrics_listen_port: u16,
+	}
+}
+
+partial_section! {
+	/// Overrides for [`logging::Config`].
+	PartialLoggingConfig for logging {
+		maptos_log_format: LogFormat,
+		maptos_log_level: LogLevel,
+		maptos_otlp_endpoint: String,
+		maptos_otlp_sample_rate: SampleRate,
+	}
+}
+
+/// A set of overrides for a [`Config`], with every field optional.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PartialConfig {
+	pub chain: PartialChainConfig,
+	pub indexer: PartialIndexerConfig,
+	pub indexer_processor: PartialIndexerProcessorConfig,
+	pub client: PartialClientConfig,
+	pub faucet: PartialFaucetConfig,
+	pub fin: PartialFinConfig,
+	pub load_shedding: PartialLoadSheddingConfig,
+	pub health: PartialHealthConfig,
+	pub metrics: PartialMetricsConfig,
+	pub logging: PartialLoggingConfig,
+}
+
+impl From<Config> for PartialConfig {
+	fn from(config: Config) -> Self {
+		let Config {
+			chain,
+			indexer,
+			indexer_processor,
+			client,
+			faucet,
+			fin,
+			load_shedding,
+			health,
+			metrics,
+			logging,
+			sources: _,
+			port_probes: _,
+		} = config;
+		PartialConfig {
+			chain: chain.into(),
+			indexer: indexer.into(),
+			indexer_processor: indexer_processor.into(),
+			client: client.into(),
+			faucet: faucet.into(),
+			fin: fin.into(),
+			load_shedding: load_shedding.into(),
+			health: health.into(),
+			metrics: metrics.into(),
+			logging: logging.into(),
+		}
+	}
+}
+
+impl Config {
+	/// Replaces the fields set in `overrides`, leaving every other field as it is.
+	///
+	/// Secrets are replaced like any other field; the signer identity follows a new private key.
+	pub fn merge(mut self, overrides: PartialConfig) -> Config {
+		let PartialConfig {
+			chain,
+			indexer,
+			indexer_processor,
+			client,
+			faucet,
+			fin,
+			load_shedding,
+			health,
+			metrics,
+			logging,
+		} = overrides;
+		chain.merge_into(&mut self.chain);
+		indexer.merge_into(&mut self.indexer);
+		indexer_processor.merge_into(&mut self.indexer_processor);
+		client.merge_into(&mut self.client);
+		faucet.merge_into(&mut self.faucet);
+		fin.merge_into(&mut self.fin);
+		load_shedding.merge_into(&mut self.load_shedding);
+		health.merge_into(&mut self.health);
+		metrics.merge_into(&mut self.metrics);
+		logging.merge_into(&mut self.logging);
+		self
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use aptos_crypto::Uniform;
+
+	#[test]
+	fn test_chain_id_override_leaves_other_fields_untouched() {
+		let _env = godfig::env::read_lock();
+		let base = Config::default();
+		let overrides = PartialConfig {
+			chain: PartialChainConfig {
+				maptos_chain_id: Some(ChainId::new(4)),
+				..Default::default()
+			},
+			..Default::default()
+		};
+
+		let merged = base.clone().merge(overrides);
+		let diff = base.diff(&merged);
+		assert_eq!(diff.len(), 1);
+		assert_eq!(diff[0].field, "MAPTOS_CHAIN_ID");
+		assert_eq!(merged.chain.maptos_chain_id, ChainId::new(4));
+	}
+
+	#[test]
+	fn test_empty_overrides_are_a_no_op() {
+		let _env = godfig::env::read_lock();
+		let base = Config::default();
+		assert_eq!(base.clone().merge(PartialConfig::default()), base);
+	}
+
+	#[test]
+	fn test_private_key_override_moves_the_signer_and_db_path_clears() {
+		let _env = godfig::env::read_lock();
+		let mut base = Config::default();
+		base.chain.maptos_db_path = Some("/var/lib/maptos".into());
+		let private_key = Ed25519PrivateKey::generate(&mut rand::thread_rng());
+		let base_address = base.signer_account_address().unwrap();
+
+		let merged = base.merge(PartialConfig {
+			chain: PartialChainConfig {
+				maptos_private_key: Some(private_key.clone()),
+				maptos_db_path: Some(None),
+				..Default::default()
+			},
+			..Default::default()
+		});
+		assert_eq!(merged.chain.maptos_private_key, private_key);
+		assert_eq!(merged.chain.maptos_db_path, None);
+		assert_ne!(merged.signer_account_address().unwrap(), base_address);
+	}
+
+	#[test]
+	fn test_full_overrides_reproduce_the_source() {
+		let _env = godfig::env::read_lock();
+		let source = Config::default();
+		let merged = Config::default().merge(source.clone().into());
+		assert_eq!(merged, source);
+	}
+}
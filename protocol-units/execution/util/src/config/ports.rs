@@ -0,0 +1,135 @@
+//! Ports assigned by the OS, so that tests running several nodes at once do not collide.
+
+use super::endpoints::{self, is_unix_socket_hostname, ListenTarget};
+use super::Config;
+use crate::listener;
+
+use std::collections::BTreeMap;
+use std::net::{Ipv4Addr, SocketAddr, TcpListener};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// The sockets bound to the ports a config was allocated, holding them until
+/// [`Config::commit_ports`] hands them over to the servers.
+///
+/// Clones share the sockets. It holds no configuration of its own and always compares equal.
+#[derive(Debug, Clone, Default)]
+pub struct PortProbes(Arc<Mutex<Vec<TcpListener>>>);
+
+impl PortProbes {
+	/// Binds a socket to `addr` with a port the OS picks, and returns the port.
+	fn allocate(&self, addr: SocketAddr) -> Result<u16, anyhow::Error> {
+		let probe = TcpListener::bind(addr)?;
+		let port = probe.local_addr()?.port();
+		self.0.lock().unwrap_or_else(PoisonError::into_inner).push(probe);
+		Ok(port)
+	}
+
+	/// How many ports are still held.
+	pub fn len(&self) -> usize {
+		self.0.lock().unwrap_or_else(PoisonError::into_inner).len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.len() == 0
+	}
+
+	fn hand_over(&self) -> Result<(), anyhow::Error> {
+		for probe in self.0.lock().unwrap_or_else(PoisonError::into_inner).drain(..) {
+			listener::hand_over(probe)?;
+		}
+		Ok(())
+	}
+}
+
+impl PartialEq for PortProbes {
+	fn eq(&self, _other: &Self) -> bool {
+		true
+	}
+}
+
+impl Eq for PortProbes {}
+
+impl Config {
+	/// This config with every server on a port of its own, picked by the OS, and every
+	/// connection port set to the port of the server it targets.
+	///
+	/// The ports stay bound, on the first address each server listens on, so that nothing
+	/// else is given them; the servers take the sockets over once [`Config::commit_ports`] hands
+	/// them on. Servers bound to a Unix domain socket keep their port.
+	pub fn with_allocated_ports(mut self) -> Result<Config, anyhow::Error> {
+		let mut unix_socket_ports = Vec::new();
+		let mut listen_addrs = BTreeMap::new();
+		for listener in self.listeners() {
+			if is_unix_socket_hostname(&listener.hostname) {
+				unix_socket_ports.push(listener.port_var);
+			} else if let Ok(targets) =
+				endpoints::listen_targets(listener.hostname_var, &listener.hostname, 0)
+			{
+				if let Some(ListenTarget::Tcp(addr)) = targets.into_iter().next() {
+					listen_addrs.insert(listener.port_var, addr);
+				}
+			}
+		}
+		let probes = PortProbes::default();
+		for (var, port) in self.listen_ports_mut() {
+			if !unix_socket_ports.contains(&var) {
+				// Disabled servers bind nothing, so any address holds their port.
+				let addr = listen_addrs
+					.get(var)
+					.copied()
+					.unwrap_or_else(|| SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)));
+				*port = probes.allocate(addr)?;
+			}
+		}
+		for (_, listen_port, port) in self.connection_ports_mut() {
+			*port = listen_port;
+		}
+		self.port_probes = probes;
+		Ok(self)
+	}
+
+	/// Hands the sockets held since [`Config::with_allocated_ports`] over to the servers this
+	/// process starts, which accept on them rather than binding the ports again.
+	/// Does nothing for a config whose ports were not allocated.
+	pub fn commit_ports(&self) -> Result<(), anyhow::Error> {
+		self.port_probes.hand_over()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use std::collections::BTreeSet;
+
+	#[test]
+	fn test_allocated_ports_are_distinct_and_held() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.chain.maptos_rest_listen_hostname = "127.0.0.1".to_string();
+		config.faucet.maptos_faucet_rest_listen_hostname = "127.0.0.1".to_string();
+		config.fin.fin_rest_listen_hostname = "127.0.0.1".to_string();
+		config.indexer.maptos_indexer_grpc_listen_hostname = "127.0.0.1".to_string();
+		let mut config = config.with_allocated_ports()?;
+
+		let ports: Vec<u16> =
+			config.listen_ports_mut().into_iter().map(|(_, port)| *port).collect();
+		assert!(ports.iter().all(|port| *port != 0));
+		assert_eq!(ports.iter().collect::<BTreeSet<_>>().len(), ports.len());
+		for (var, listen_port, port) in config.connection_ports_mut() {
+			assert_eq!(*port, listen_port, "{}", var);
+		}
+		assert_eq!(config.validate(), Ok(vec![]));
+
+		// The ports cannot be bound by anything else, before or after they are committed.
+		let addr = SocketAddr::from(([127, 0, 0, 1], config.chain.maptos_rest_listen_port));
+		assert!(TcpListener::bind(addr).is_err());
+		assert_eq!(config.port_probes.len(), ports.len());
+		config.clone().commit_ports()?;
+		assert!(config.port_probes.is_empty());
+		assert!(TcpListener::bind(addr).is_err());
+		// The server binding the address accepts on the socket handed over.
+		assert_eq!(listener::bind_tcp(&[addr])?[0].local_addr()?, addr);
+		Ok(())
+	}
+}
@@ -0,0 +1,324 @@
+//! Opinions on which settings are unsafe in a production deployment, enforced
+//! when `MAPTOS_PROFILE=production`.
+
+use super::{
+	clock::ClockKind,
+	common,
+	cors::CorsOrigins,
+	endpoints::listen_hostnames,
+	provenance::Provenance,
+	validation::{is_unspecified, ValidationIssue, ValidationWarning},
+	Config,
+};
+
+/// The env vars which would carry a private key, or the seed of one, in plain text.
+const PLAINTEXT_PRIVATE_KEY_VARS: &[&str] =
+	&["MAPTOS_PRIVATE_KEY", "MAPTOS_PRIVATE_KEY_SEED", "FAUCET_PRIVATE_KEY", "FAUCET_PRIVATE_KEYS"];
+
+/// The listeners scraped by the infrastructure, which may bind every interface without TLS.
+const INFRASTRUCTURE_HOSTNAME_VARS: &[&str] =
+	&["MAPTOS_HEALTH_LISTEN_HOSTNAME", "MAPTOS_METRICS_LISTEN_HOSTNAME"];
+
+/// A setting which works but should not be used in production.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ProductionWarning {
+	/// Any issue found by [`Config::validate_all`], warnings included, other than the
+	/// unprotected public binds, which are reported as `UnspecifiedBindWithoutTls` instead.
+	#[error("{0}")]
+	Invalid(ValidationIssue),
+
+	#[error("MAPTOS_ALLOW_EPHEMERAL_PRIVATE_KEY=true lets the node start under a random identity")]
+	EphemeralPrivateKeyAllowed,
+
+	#[error(
+		"MAPTOS_PRIVATE_KEY was generated at startup rather than configured, \
+		so the node runs under a random identity"
+	)]
+	EphemeralPrivateKey,
+
+	#[error("{var} allows every origin; list the origins which may call the API instead")]
+	WildcardCors { var: &'static str },
+
+	#[error("{hostname_var}={hostname} binds every interface without TLS")]
+	UnspecifiedBindWithoutTls { hostname_var: &'static str, hostname: String },
+
+	#[error("INDEXER_PROCESSOR_AUTH_TOKEN is left at its built-in default")]
+	DefaultAuthToken,
+
+	#[error("{var} is set in the environment in plain text; read the key from a file instead")]
+	PlaintextPrivateKey { var: &'static str },
+
+	#[error("MAPTOS_CLOCK=manual stops the node's clock; it is only meant for tests")]
+	ManualClock,
+}
+
+/// Whether `MAPTOS_PROFILE` selects the production profile.
+pub fn is_production_profile() -> bool {
+	common::default_maptos_profile().trim().eq_ignore_ascii_case("production")
+}
+
+impl Config {
+	/// Checks the config and the environment it was loaded from against the production profile,
+	/// returning every violation rather than the first.
+	///
+	/// On top of the issues [`Config::validate_all`] reports, this refuses an ephemeral
+	/// private key, whether `MAPTOS_ALLOW_EPHEMERAL_PRIVATE_KEY` allows one or the key was
+	/// generated because nothing configured it, CORS open to every origin, the default indexer
+	/// auth token, private keys given inline in the environment, a manual clock, and servers
+	/// other than the health and metrics probes bound to every interface without TLS.
+	pub fn assert_production_safe(&self) -> Result<(), Vec<ProductionWarning>> {
+		self.assert_production_safe_with(|var| godfig::env::var(var).ok())
+	}
+
+	/// Like [`Config::assert_production_safe`], reading env vars through `env`.
+	fn assert_production_safe_with(
+		&self,
+		env: impl Fn(&str) -> Option<String>,
+	) -> Result<(), Vec<ProductionWarning>> {
+		let mut warnings: Vec<ProductionWarning> = self
+			.validate_all()
+			.issues
+			.into_iter()
+			.filter(|issue| {
+				!matches!(
+					issue,
+					ValidationIssue::Warning(ValidationWarning::UnprotectedPublicBind { .. })
+				)
+			})
+			.map(ProductionWarning::Invalid)
+			.collect();
+
+		let allow_ephemeral = env("MAPTOS_ALLOW_EPHEMERAL_PRIVATE_KEY")
+			.and_then(|value| value.parse::<bool>().ok())
+			.unwrap_or(false);
+		if allow_ephemeral {
+			warnings.push(ProductionWarning::EphemeralPrivateKeyAllowed);
+		}
+		// Neither the environment nor the file gave the key, so the defaults generated it.
+		if self.sources.get("MAPTOS_PRIVATE_KEY") == Provenance::Default
+			&& !self.chain.private_key_is_unconfigured()
+		{
+			warnings.push(ProductionWarning::EphemeralPrivateKey);
+		}
+
+		for (var, origins) in [
+			("MAPTOS_API_CORS_ORIGINS", self.rest_cors_origins()),
+			("FAUCET_API_CORS_ORIGINS", self.faucet_cors_origins()),
+		] {
+			if origins == CorsOrigins::Any {
+				warnings.push(ProductionWarning::WildcardCors { var });
+			}
+		}
+
+		for listener in self.listeners() {
+			if INFRASTRUCTURE_HOSTNAME_VARS.contains(&listener.hostname_var)
+				|| self.listener_tls_enabled(listener.hostname_var)
+			{
+				continue;
+			}
+			if listen_hostnames(&listener.hostname).into_iter().any(is_unspecified) {
+				warnings.push(ProductionWarning::UnspecifiedBindWithoutTls {
+					hostname_var: listener.hostname_var,
+					hostname: listener.hostname,
+				});
+			}
+		}
+
+		if self
+			.indexer_auth_tokens()
+			.iter()
+			.any(|token| token == common::DEFAULT_INDEXER_PROCESSOR_AUTH_TOKEN)
+		{
+			warnings.push(ProductionWarning::DefaultAuthToken);
+		}
+
+		for var in PLAINTEXT_PRIVATE_KEY_VARS {
+			if env(var).is_some_and(|value| !value.is_empty()) {
+				warnings.push(ProductionWarning::PlaintextPrivateKey { var });
+			}
+		}
+
+		if self.chain.clock_kind() == ClockKind::Manual {
+			warnings.push(ProductionWarning::ManualClock);
+		}
+
+		if warnings.is_empty() {
+			Ok(())
+		} else {
+			Err(warnings)
+		}
+	}
+
+	/// Whether the server bound through `hostname_var` serves TLS.
+	fn listener_tls_enabled(&self, hostname_var: &str) -> bool {
+		match hostname_var {
+			"MAPTOS_API_LISTEN_HOSTNAME" => self.rest_tls_enabled(),
+			"MAPTOS_INDEXER_GRPC_LISTEN_HOSTNAME" => self.indexer_grpc_tls_enabled(),
+			_ => false,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::config::provenance::Sources;
+	use crate::config::validation::ValidationError;
+	use aptos_crypto::{ed25519::Ed25519PrivateKey, Uniform};
+
+	use std::collections::HashMap;
+
+	/// A config passing every production check.
+	fn hardened() -> Config {
+		let mut config = Config::default();
+		config.chain.maptos_rest_cors_allowed_origins = vec!["https://app.example".to_string()];
+		config.chain.maptos_rest_tls_cert_path = "/etc/maptos/rest.crt".to_string();
+		config.chain.maptos_rest_tls_key_path = "/etc/maptos/rest.key".to_string();
+		config.faucet.maptos_faucet_rest_cors_allowed_origins = vec![];
+		config.faucet.maptos_faucet_rest_listen_hostname = "127.0.0.1".to_string();
+		config.fin.fin_rest_listen_hostname = "127.0.0.1".to_string();
+		config.indexer.maptos_indexer_grpc_tls_cert = "/etc/maptos/grpc.crt".to_string();
+		config.indexer.maptos_indexer_grpc_tls_key = "/etc/maptos/grpc.key".to_string();
+		config.indexer_processor.indexer_processor_auth_token = "s3cr3t-token".to_string();
+		config.chain.maptos_private_key = Ed25519PrivateKey::generate(&mut rand::thread_rng());
+		config.sources = Sources::default().with_file(["MAPTOS_PRIVATE_KEY"].into());
+		config
+	}
+
+	fn env<'a>(vars: &'a [(&'a str, &'a str)]) -> impl Fn(&str) -> Option<String> + 'a {
+		let vars: HashMap<&str, &str> = vars.iter().copied().collect();
+		move |var| vars.get(var).map(|value| value.to_string())
+	}
+
+	#[test]
+	fn test_hardened_config_passes() {
+		let config = hardened();
+		assert_eq!(config.assert_production_safe_with(env(&[])), Ok(()));
+		assert_eq!(
+			config.assert_production_safe_with(env(&[
+				("MAPTOS_PRIVATE_KEY_FILE", "/run/secrets/maptos.key"),
+				("MAPTOS_ALLOW_EPHEMERAL_PRIVATE_KEY", "false"),
+				("MAPTOS_PRIVATE_KEY", ""),
+			])),
+			Ok(())
+		);
+	}
+
+	#[test]
+	fn test_each_unsafe_setting_is_detected() {
+		let check = |config: Config| config.assert_production_safe_with(env(&[]));
+
+		let mut config = hardened();
+		config.chain.maptos_rest_cors_allowed_origins = vec!["*".to_string()];
+		assert_eq!(
+			check(config),
+			Err(vec![ProductionWarning::WildcardCors { var: "MAPTOS_API_CORS_ORIGINS" }])
+		);
+
+		let mut config = hardened();
+		config.chain.maptos_rest_tls_key_path = String::new();
+		config.chain.maptos_rest_tls_cert_path = String::new();
+		assert_eq!(
+			check(config),
+			Err(vec![ProductionWarning::UnspecifiedBindWithoutTls {
+				hostname_var: "MAPTOS_API_LISTEN_HOSTNAME",
+				hostname: "0.0.0.0".to_string(),
+			}])
+		);
+
+		// Listing an unspecified address among others still binds every interface.
+		let mut config = hardened();
+		config.fin.fin_rest_listen_hostname = "127.0.0.1, ::".to_string();
+		assert_eq!(
+			check(config),
+			Err(vec![ProductionWarning::UnspecifiedBindWithoutTls {
+				hostname_var: "MAPTOS_FIN_VIEW_API_LISTEN_HOSTNAME",
+				hostname: "127.0.0.1, ::".to_string(),
+			}])
+		);
+
+		let mut config = hardened();
+		config.indexer_processor.indexer_processor_auth_token = "new-token, auth_token".to_string();
+		assert_eq!(check(config), Err(vec![ProductionWarning::DefaultAuthToken]));
+
+		let mut config = hardened();
+		config.chain.maptos_clock = ClockKind::Manual;
+		assert_eq!(check(config), Err(vec![ProductionWarning::ManualClock]));
+
+		// The probes bind every interface by default and are left to the network policy.
+		let mut config = hardened();
+		config.health.maptos_health_enabled = true;
+		config.metrics.maptos_metrics_enabled = true;
+		assert_eq!(config.health.maptos_health_listen_hostname, "0.0.0.0");
+		assert_eq!(config.metrics.maptos_metrics_listen_hostname, "0.0.0.0");
+		assert_eq!(check(config), Ok(()));
+	}
+
+	#[test]
+	fn test_private_keys_in_env_are_detected() {
+		let config = hardened();
+		assert_eq!(
+			config.assert_production_safe_with(env(&[(
+				"MAPTOS_ALLOW_EPHEMERAL_PRIVATE_KEY",
+				"true"
+			)])),
+			Err(vec![ProductionWarning::EphemeralPrivateKeyAllowed])
+		);
+		assert_eq!(
+			config.assert_production_safe_with(env(&[
+				("MAPTOS_PRIVATE_KEY", "0x1234"),
+				("FAUCET_PRIVATE_KEY", "0x5678"),
+			])),
+			Err(vec![
+				ProductionWarning::PlaintextPrivateKey { var: "MAPTOS_PRIVATE_KEY" },
+				ProductionWarning::PlaintextPrivateKey { var: "FAUCET_PRIVATE_KEY" },
+			])
+		);
+		assert_eq!(
+			config.assert_production_safe_with(env(&[("MAPTOS_PRIVATE_KEY_SEED", "test")])),
+			Err(vec![ProductionWarning::PlaintextPrivateKey { var: "MAPTOS_PRIVATE_KEY_SEED" }])
+		);
+	}
+
+	#[test]
+	fn test_generated_private_key_is_detected() {
+		let mut config = hardened();
+		config.sources = Sources::default();
+		assert_eq!(
+			config.assert_production_safe_with(env(&[])),
+			Err(vec![ProductionWarning::EphemeralPrivateKey])
+		);
+	}
+
+	#[test]
+	fn test_every_violation_is_returned() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.chain.maptos_rest_tls_cert_path = "/etc/maptos/rest.crt".to_string();
+		config.chain.maptos_rest_cors_allowed_origins = vec!["*".to_string()];
+		config.indexer_processor.indexer_processor_auth_token = "auth_token".to_string();
+		let warnings = config
+			.assert_production_safe_with(env(&[("MAPTOS_ALLOW_EPHEMERAL_PRIVATE_KEY", "true")]))
+			.unwrap_err();
+
+		// Validation issues come first, then the production opinions in a fixed order.
+		assert_eq!(
+			warnings[0],
+			ProductionWarning::Invalid(ValidationIssue::Error(
+				ValidationError::TlsHalfConfigured {
+					set_var: "MAPTOS_API_TLS_CERT",
+					missing_var: "MAPTOS_API_TLS_KEY",
+				}
+			))
+		);
+		assert!(warnings.contains(&ProductionWarning::EphemeralPrivateKeyAllowed));
+		assert!(
+			warnings.contains(&ProductionWarning::WildcardCors { var: "MAPTOS_API_CORS_ORIGINS" })
+		);
+		assert!(warnings.contains(&ProductionWarning::DefaultAuthToken));
+		assert!(warnings.contains(&ProductionWarning::UnspecifiedBindWithoutTls {
+			hostname_var: "MAPTOS_API_LISTEN_HOSTNAME",
+			hostname: "0.0.0.0".to_string(),
+		}));
+	}
+}
@@ -0,0 +1,251 @@
+//! Where each field of a loaded config took its value from.
+
+use super::diff::FIELD_ENV_VARS;
+use super::env_vars::REDACTED;
+use super::secrets::{optional_secret, secret_provider};
+use super::{common, Config};
+
+use tracing::info;
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+
+/// The secrets, which a [`SecretProvider`](super::secrets::SecretProvider) may hold
+/// in place of the environment.
+const SECRET_VARS: &[&str] = &[
+	"MAPTOS_PRIVATE_KEY",
+	"FAUCET_PRIVATE_KEY",
+	"FAUCET_PRIVATE_KEYS",
+	"INDEXER_PROCESSOR_AUTH_TOKEN",
+];
+
+/// The env vars which set a field in place of its own.
+const ALTERNATE_VARS: &[(&str, &str)] = &[
+	("MAPTOS_PRIVATE_KEY", "MAPTOS_PRIVATE_KEY_FILE"),
+	("MAPTOS_PRIVATE_KEY", "MAPTOS_PRIVATE_KEY_SEED"),
+	("FAUCET_PRIVATE_KEY", "FAUCET_PRIVATE_KEY_FILE"),
+	("INDEXER_PROCESSOR_AUTH_TOKEN", "INDEXER_PROCESSOR_AUTH_TOKEN_FILE"),
+];
+
+/// Where a field took its value from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Provenance {
+	/// Its env var, or the secret provider for a secret.
+	Env,
+	/// The config file, with its env var unset.
+	File,
+	/// The built-in default.
+	Default,
+}
+
+impl fmt::Display for Provenance {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(match self {
+			Provenance::Env => "env",
+			Provenance::File => "file",
+			Provenance::Default => "default",
+		})
+	}
+}
+
+/// The env vars which were set, and those whose fields a config file set, when a config
+/// was loaded.
+///
+/// It holds no configuration of its own and always compares equal.
+#[derive(Debug, Clone, Default)]
+pub struct Sources {
+	env: BTreeSet<&'static str>,
+	file: BTreeSet<&'static str>,
+}
+
+impl Sources {
+	/// Records which of the env vars backing a field are set, as the defaults read them.
+	pub(crate) fn from_env() -> Self {
+		let secrets = secret_provider().ok();
+		let env = FIELD_ENV_VARS
+			.iter()
+			.map(|(_, var)| *var)
+			.filter(|var| {
+				godfig::env::var_os(var).is_some()
+					|| ALTERNATE_VARS.iter().any(|(field_var, alternate)| {
+						field_var == var && godfig::env::var_os(alternate).is_some()
+					}) || (SECRET_VARS.contains(var)
+					&& secrets.as_ref().is_some_and(|secrets| {
+						matches!(optional_secret(&**secrets, var), Ok(Some(_)))
+					}))
+			})
+			.collect();
+		Self { env, file: BTreeSet::new() }
+	}
+
+	/// The sources of a config deserialized from a file: the fields whose value differs from
+	/// the defaults were set by the file, the others by the env vars set, if any.
+	///
+	/// The private key is compared to none, as the defaults generate or stand a placeholder in
+	/// for one. It was set by the file unless it is the placeholder, it was taken from the
+	/// environment, or the defaults may have generated it.
+	pub(crate) fn of_deserialized(config: &Config) -> Self {
+		let defaults: HashMap<String, String> = Config::builder()
+			.private_key(config.chain.maptos_private_key.clone())
+			.build()
+			.to_env_vars(true)
+			.into_iter()
+			.collect();
+		let values: HashMap<String, String> = config.to_env_vars(true).into_iter().collect();
+		let mut sources = Self::from_env();
+		let mut file: BTreeSet<&'static str> = FIELD_ENV_VARS
+			.iter()
+			.map(|(_, var)| *var)
+			.filter(|var| values.get(*var) != defaults.get(*var))
+			.collect();
+		if !config.chain.private_key_is_unconfigured()
+			&& !sources.env.contains("MAPTOS_PRIVATE_KEY")
+			&& !common::default_maptos_allow_ephemeral_private_key()
+		{
+			file.insert("MAPTOS_PRIVATE_KEY");
+		}
+		sources.env.retain(|var| !file.contains(var));
+		sources.with_file(file)
+	}
+
+	/// Whether no field was recorded as set, as for a config deserialized directly.
+	pub(crate) fn is_empty(&self) -> bool {
+		self.env.is_empty() && self.file.is_empty()
+	}
+
+	/// These sources with the fields of `file` set by a config file.
+	pub(crate) fn with_file(self, file: BTreeSet<&'static str>) -> Self {
+		Self { file, ..self }
+	}
+
+	/// These sources with the env vars set now taking precedence, as when the environment is
+	/// applied over a loaded config.
+	pub(crate) fn overridden_by_env(mut self) -> Self {
+		self.env.extend(Self::from_env().env);
+		self
+	}
+
+	pub(crate) fn get(&self, var: &str) -> Provenance {
+		if self.env.contains(var) {
+			Provenance::Env
+		} else if self.file.contains(var) {
+			Provenance::File
+		} else {
+			Provenance::Default
+		}
+	}
+}
+
+impl PartialEq for Sources {
+	fn eq(&self, _other: &Self) -> bool {
+		true
+	}
+}
+
+impl Eq for Sources {}
+
+impl Config {
+	/// Where each field took its value from when the config was loaded, named by its env var.
+	///
+	/// The environment is recorded by [`Config::default`], the builder and the config file
+	/// loaders, and [`Config::finish_loading`] records a deserialized config's. A config
+	/// deserialized directly records nothing until then and reports every field as
+	/// [`Provenance::Default`].
+	pub fn provenance(&self) -> Vec<(String, Provenance)> {
+		FIELD_ENV_VARS
+			.iter()
+			.map(|(_, var)| (var.to_string(), self.sources.get(var)))
+			.collect()
+	}
+
+	/// Logs each field not left at its default, with its value and where it was taken from.
+	/// Secrets are logged as [`REDACTED`].
+	pub fn log_provenance(&self) {
+		let values: HashMap<String, String> = self.to_env_vars(false).into_iter().collect();
+		for (var, provenance) in self.provenance() {
+			if provenance == Provenance::Default {
+				continue;
+			}
+			let value = values.get(&var).map(String::as_str).unwrap_or(REDACTED);
+			info!("{}={} (from {})", var, value, provenance);
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use aptos_crypto::{ed25519::Ed25519PrivateKey, Uniform};
+
+	fn provenance_of(config: &Config, var: &str) -> Option<Provenance> {
+		config
+			.provenance()
+			.into_iter()
+			.find(|(field, _)| field == var)
+			.map(|(_, provenance)| provenance)
+	}
+
+	#[test]
+	fn test_set_env_vars_report_env() {
+		let _env = godfig::env::lock();
+		std::env::set_var("MAPTOS_INDEXER_MAX_LAG_VERSIONS", "5000");
+		std::env::remove_var("INDEXER_PROCESSOR_POSTGRES_CONNECT_RETRIES");
+		let config = Config::default();
+		std::env::remove_var("MAPTOS_INDEXER_MAX_LAG_VERSIONS");
+
+		assert_eq!(
+			provenance_of(&config, "MAPTOS_INDEXER_MAX_LAG_VERSIONS"),
+			Some(Provenance::Env)
+		);
+		assert_eq!(
+			provenance_of(&config, "INDEXER_PROCESSOR_POSTGRES_CONNECT_RETRIES"),
+			Some(Provenance::Default)
+		);
+		// The environment is recorded when the config is loaded, not when it is asked.
+		assert_eq!(
+			provenance_of(&config, "MAPTOS_INDEXER_MAX_LAG_VERSIONS"),
+			Some(Provenance::Env)
+		);
+	}
+
+	#[test]
+	fn test_deserialized_fields_report_the_file() {
+		let _env = godfig::env::lock();
+		std::env::set_var("MAPTOS_INDEXER_MAX_LAG_VERSIONS", "5000");
+		std::env::remove_var("MAPTOS_ALLOW_EPHEMERAL_PRIVATE_KEY");
+		std::env::remove_var("MAPTOS_PRIVATE_KEY");
+		let mut config = Config::default();
+		config.chain.maptos_private_key = Ed25519PrivateKey::generate(&mut rand::thread_rng());
+		config.fin.fin_rest_listen_port = 40010;
+		// As serde leaves it for a config read from a file.
+		config.sources = Sources::default();
+		config.sources = Sources::of_deserialized(&config);
+		std::env::remove_var("MAPTOS_INDEXER_MAX_LAG_VERSIONS");
+
+		assert_eq!(
+			provenance_of(&config, "MAPTOS_FIN_VIEW_API_LISTEN_PORT"),
+			Some(Provenance::File)
+		);
+		assert_eq!(provenance_of(&config, "MAPTOS_PRIVATE_KEY"), Some(Provenance::File));
+		assert_eq!(
+			provenance_of(&config, "MAPTOS_INDEXER_MAX_LAG_VERSIONS"),
+			Some(Provenance::Env)
+		);
+		assert_eq!(
+			provenance_of(&config, "MAPTOS_EPOCH_SNAPSHOT_PRUNING_WINDOW"),
+			Some(Provenance::Default)
+		);
+	}
+
+	#[test]
+	fn test_secrets_report_provenance() {
+		let _env = godfig::env::lock();
+		std::env::set_var("FAUCET_PRIVATE_KEY_FILE", "/run/secrets/faucet.key");
+		let sources = Sources::from_env();
+		std::env::remove_var("FAUCET_PRIVATE_KEY_FILE");
+
+		assert_eq!(sources.get("FAUCET_PRIVATE_KEY"), Provenance::Env);
+		// Equal configs stay equal whatever they were loaded from.
+		assert_eq!(sources, Sources::default());
+	}
+}
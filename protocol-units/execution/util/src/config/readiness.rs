@@ -0,0 +1,200 @@
+//! Waiting for the services a config describes to accept connections.
+
+use super::endpoints::socket_addrs;
+use super::Config;
+
+use std::fmt;
+use std::net::TcpStream;
+use std::time::{Duration, Instant};
+
+/// The longest a single connection attempt may take, so that each service is retried
+/// several times within the deadline.
+const CONNECT_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// The pause between attempts to reach a service.
+const RETRY_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A service which could not be reached before the deadline.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Unreachable {
+	pub service: &'static str,
+	/// The connection address, as `hostname:port`.
+	pub address: String,
+	/// Why the last attempt failed.
+	pub reason: String,
+}
+
+impl fmt::Display for Unreachable {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "{} at {}: {}", self.service, self.address, self.reason)
+	}
+}
+
+/// Services which were still unreachable when [`Config::wait_until_ready`] gave up.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error(
+	"not reachable within {timeout:?}: {}",
+	.unreachable.iter().map(ToString::to_string).collect::<Vec<_>>().join("; ")
+)]
+pub struct NotReady {
+	pub timeout: Duration,
+	pub unreachable: Vec<Unreachable>,
+}
+
+/// Retries connecting to `hostname:port` until it accepts a connection or `deadline` passes.
+fn probe(hostname: &str, port: u16, deadline: Instant) -> Result<(), String> {
+	loop {
+		let attempt = socket_addrs(hostname, port).map_err(|e| e.to_string()).and_then(|addrs| {
+			let mut last_error = "resolved to no addresses".to_string();
+			for addr in addrs {
+				let remaining = deadline.saturating_duration_since(Instant::now());
+				match TcpStream::connect_timeout(
+					&addr,
+					remaining.clamp(RETRY_INTERVAL, CONNECT_TIMEOUT),
+				) {
+					Ok(_) => return Ok(()),
+					Err(e) => last_error = e.to_string(),
+				}
+			}
+			Err(last_error)
+		});
+		match attempt {
+			Ok(()) => return Ok(()),
+			Err(reason) if Instant::now() + RETRY_INTERVAL >= deadline => return Err(reason),
+			Err(_) => std::thread::sleep(RETRY_INTERVAL),
+		}
+	}
+}
+
+impl Config {
+	/// The services a node with this config serves, with their connection hostname and port.
+	///
+	/// Disabled services are left out, as is the faucet on read-only nodes, where it does not run.
+	fn probed_services(&self) -> Vec<(&'static str, &str, u16)> {
+		let client = &self.client;
+		[
+			(
+				self.rest_enabled(),
+				"REST",
+				client.maptos_rest_connection_hostname.as_str(),
+				client.maptos_rest_connection_port,
+			),
+			(
+				self.fin_view_enabled(),
+				"fin view",
+				client.fin_rest_connection_hostname.as_str(),
+				client.fin_rest_connection_port,
+			),
+			(
+				self.indexer_grpc_enabled(),
+				"indexer gRPC",
+				client.maptos_indexer_grpc_connection_hostname.as_str(),
+				client.maptos_indexer_grpc_connection_port,
+			),
+			(
+				self.faucet_enabled() && !self.is_read_only(),
+				"faucet",
+				client.maptos_faucet_rest_connection_hostname.as_str(),
+				client.maptos_faucet_rest_connection_port,
+			),
+		]
+		.into_iter()
+		.filter_map(|(enabled, service, hostname, port)| {
+			enabled.then_some((service, hostname, port))
+		})
+		.collect()
+	}
+
+	/// Blocks until every enabled service accepts connections on its connection address,
+	/// probing them concurrently, or until `timeout` has elapsed.
+	///
+	/// A service counts as ready once it accepts a TCP connection.
+	pub fn wait_until_ready(&self, timeout: Duration) -> Result<(), NotReady> {
+		let deadline = Instant::now() + timeout;
+		let unreachable: Vec<Unreachable> = std::thread::scope(|scope| {
+			let probes: Vec<_> = self
+				.probed_services()
+				.into_iter()
+				.map(|(service, hostname, port)| {
+					let handle = scope.spawn(move || probe(hostname, port, deadline));
+					(service, hostname, port, handle)
+				})
+				.collect();
+			probes
+				.into_iter()
+				.filter_map(|(service, hostname, port, handle)| {
+					let reason = match handle.join() {
+						Ok(Ok(())) => return None,
+						Ok(Err(reason)) => reason,
+						Err(_) => "the probe panicked".to_string(),
+					};
+					Some(Unreachable { service, address: format!("{}:{}", hostname, port), reason })
+				})
+				.collect()
+		});
+		if unreachable.is_empty() {
+			Ok(())
+		} else {
+			Err(NotReady { timeout, unreachable })
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use std::net::TcpListener;
+
+	/// A port nothing listens on, as far as a test can tell.
+	fn free_port() -> Result<u16, anyhow::Error> {
+		Ok(TcpListener::bind("127.0.0.1:0")?.local_addr()?.port())
+	}
+
+	fn config_with_every_service_on(port: u16) -> Config {
+		let mut config = Config::default();
+		config.chain.maptos_read_only = false;
+		config.chain.maptos_rest_enabled = true;
+		config.faucet.maptos_faucet_enabled = true;
+		config.fin.fin_rest_enabled = true;
+		config.indexer.maptos_indexer_grpc_enabled = true;
+		let client = &mut config.client;
+		client.maptos_rest_connection_hostname = "127.0.0.1".to_string();
+		client.maptos_rest_connection_port = port;
+		client.maptos_faucet_rest_connection_hostname = "127.0.0.1".to_string();
+		client.maptos_faucet_rest_connection_port = port;
+		client.fin_rest_connection_hostname = "127.0.0.1".to_string();
+		client.fin_rest_connection_port = port;
+		client.maptos_indexer_grpc_connection_hostname = "127.0.0.1".to_string();
+		client.maptos_indexer_grpc_connection_port = port;
+		config
+	}
+
+	#[test]
+	fn test_waits_for_a_server_which_starts_late() -> Result<(), anyhow::Error> {
+		let port = free_port()?;
+		let server = std::thread::spawn(move || -> Result<(), std::io::Error> {
+			std::thread::sleep(Duration::from_millis(300));
+			let listener = TcpListener::bind(("127.0.0.1", port))?;
+			for _ in 0..4 {
+				listener.accept()?;
+			}
+			Ok(())
+		});
+
+		config_with_every_service_on(port).wait_until_ready(Duration::from_secs(10))?;
+		server.join().expect("the stub server does not panic")?;
+		Ok(())
+	}
+
+	#[test]
+	fn test_unreachable_services_are_named() -> Result<(), anyhow::Error> {
+		let mut config = config_with_every_service_on(free_port()?);
+		config.chain.maptos_read_only = true;
+
+		let err = config.wait_until_ready(Duration::from_millis(300)).unwrap_err();
+		let services: Vec<&str> = err.unreachable.iter().map(|service| service.service).collect();
+		// The faucet does not run on a read-only node, so it is not waited for.
+		assert_eq!(services, vec!["REST", "fin view", "indexer gRPC"]);
+		Ok(())
+	}
+}
@@ -0,0 +1,278 @@
+//! Reloading the settings which can change while the node runs.
+//!
+//! Only the [reloadable fields](Config::reloadable_fields) are taken from a reloaded config.
+//! Changes to any other field, such as ports, keys or the chain id, are logged and ignored
+//! until the node restarts.
+
+use super::diff::ConfigFieldDiff;
+use super::logging::{self, LogLevel, SampleRate};
+use super::Config;
+
+use anyhow::Context;
+use arc_swap::ArcSwap;
+use tracing::{info, warn};
+
+use std::path::PathBuf;
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// The paths of the fields a reload may change, as the config serializes.
+const RELOADABLE_FIELDS: &[&str] = &[
+	"logging.maptos_log_level",
+	"chain.maptos_rest_log_sample_rate",
+	"faucet.faucet_rate_limit_per_ip_per_hour",
+	"chain.maptos_rest_request_timeout_sec",
+	"fin.fin_rest_request_timeout_sec",
+];
+
+/// The reloadable settings, as the running services read them.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RuntimeConfig {
+	pub log_level: LogLevel,
+	pub rest_log_sample_rate: SampleRate,
+	/// 0 for unlimited
+	pub faucet_rate_limit_per_ip_per_hour: u32,
+	pub rest_request_timeout: Duration,
+	pub fin_request_timeout: Duration,
+}
+
+/// The runtime config shared with the services, replaced whole on every reload.
+pub type SharedRuntimeConfig = Arc<ArcSwap<RuntimeConfig>>;
+
+impl Config {
+	/// The paths of the fields a reload may change; every other field is fixed at startup.
+	pub fn reloadable_fields() -> &'static [&'static str] {
+		RELOADABLE_FIELDS
+	}
+
+	pub fn runtime_config(&self) -> RuntimeConfig {
+		RuntimeConfig {
+			log_level: self.logging.maptos_log_level,
+			rest_log_sample_rate: self.chain.maptos_rest_log_sample_rate,
+			faucet_rate_limit_per_ip_per_hour: self.faucet.faucet_rate_limit_per_ip_per_hour,
+			rest_request_timeout: self.chain.rest_request_timeout(),
+			fin_request_timeout: self.fin.request_timeout(),
+		}
+	}
+
+	/// Copies the reloadable fields of `reloaded` over those of this config.
+	fn take_reloadable_fields(&mut self, reloaded: &Config) {
+		self.logging.maptos_log_level = reloaded.logging.maptos_log_level;
+		self.chain.maptos_rest_log_sample_rate = reloaded.chain.maptos_rest_log_sample_rate;
+		self.faucet.faucet_rate_limit_per_ip_per_hour =
+			reloaded.faucet.faucet_rate_limit_per_ip_per_hour;
+		self.chain.maptos_rest_request_timeout_sec = reloaded.chain.maptos_rest_request_timeout_sec;
+		self.fin.fin_rest_request_timeout_sec = reloaded.fin.fin_rest_request_timeout_sec;
+	}
+}
+
+/// What a reload changed.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReloadReport {
+	/// Changes to reloadable fields, now in effect.
+	pub applied: Vec<ConfigFieldDiff>,
+	/// Changes to fields which only take effect on restart.
+	pub ignored: Vec<ConfigFieldDiff>,
+}
+
+/// Where a reload re-reads the config from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReloadSource {
+	/// The environment, as [`Config::default`] reads it.
+	Env,
+	/// A TOML config file, as [`Config::from_toml_file`] reads it.
+	Toml(PathBuf),
+	/// The config at `pointer` in a JSON file, as the node's `config.json` holds it under
+	/// `/maptos_config`.
+	Json { path: PathBuf, pointer: String },
+}
+
+impl ReloadSource {
+	/// The config in the node's `config.json` at `path`.
+	pub fn node_config_json(path: PathBuf) -> Self {
+		ReloadSource::Json { path, pointer: "/maptos_config".to_string() }
+	}
+
+	fn load(&self) -> Result<Config, anyhow::Error> {
+		match self {
+			ReloadSource::Env => Ok(Config::default()),
+			ReloadSource::Toml(path) => Config::from_toml_file(path),
+			ReloadSource::Json { path, pointer } => {
+				let file = std::fs::read_to_string(path)
+					.with_context(|| format!("failed to read {}", path.display()))?;
+				let mut json: serde_json::Value = serde_json::from_str(&file)
+					.with_context(|| format!("failed to parse {}", path.display()))?;
+				let config =
+					json.pointer_mut(pointer).map(serde_json::Value::take).with_context(|| {
+						format!("{} holds no config at {}", path.display(), pointer)
+					})?;
+				serde_json::from_value(config)
+					.with_context(|| format!("invalid config at {} in {}", pointer, path.display()))
+			}
+		}
+	}
+}
+
+/// The config a node is running with, updated by reloads of its reloadable fields.
+pub struct ConfigReloader {
+	running: Mutex<Config>,
+	shared: SharedRuntimeConfig,
+	source: ReloadSource,
+}
+
+impl ConfigReloader {
+	/// The reloader of `config`, which was loaded from `source`.
+	pub fn new(config: Config, source: ReloadSource) -> Self {
+		let shared = Arc::new(ArcSwap::from_pointee(config.runtime_config()));
+		Self { running: Mutex::new(config), shared, source }
+	}
+
+	/// The runtime config the services read, updated in place by every reload.
+	pub fn shared(&self) -> SharedRuntimeConfig {
+		Arc::clone(&self.shared)
+	}
+
+	/// The config currently in effect.
+	pub fn running(&self) -> Config {
+		self.running
+			.lock()
+			.expect("no thread panics holding the running config")
+			.clone()
+	}
+
+	/// Re-reads the config from its source and applies it.
+	pub fn reload(&self) -> Result<ReloadReport, anyhow::Error> {
+		let reloaded = self.source.load()?;
+		self.apply(&reloaded)
+	}
+
+	/// Takes the reloadable fields of `reloaded` and swaps them in for the services at once,
+	/// setting the level of the tracing subscriber as well.
+	///
+	/// Nothing changes when the result would not validate.
+	pub fn apply(&self, reloaded: &Config) -> Result<ReloadReport, anyhow::Error> {
+		let mut running = self.running.lock().expect("no thread panics holding the running config");
+		let mut updated = running.clone();
+		updated.take_reloadable_fields(reloaded);
+		for warning in updated.validate().context("the reloaded config is invalid")? {
+			warn!("{}", warning);
+		}
+
+		let (applied, ignored): (Vec<_>, Vec<_>) = running
+			.diff(reloaded)
+			.into_iter()
+			.partition(|change| RELOADABLE_FIELDS.contains(&change.path.as_str()));
+		for change in &applied {
+			info!("Reloaded {}: {} -> {}", change.field, change.self_value, change.other_value);
+		}
+		for change in &ignored {
+			info!("{} changed but cannot be reloaded; restart the node to apply it", change.field);
+		}
+
+		if updated.logging.maptos_log_level != running.logging.maptos_log_level {
+			logging::apply_log_level(updated.logging.maptos_log_level);
+		}
+		self.shared.store(Arc::new(updated.runtime_config()));
+		*running = updated;
+		Ok(ReloadReport { applied, ignored })
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::config::duration::DurationSecs;
+
+	#[test]
+	fn test_reloadable_fields_are_all_taken() {
+		let _env = godfig::env::read_lock();
+		let running = Config::default();
+		let mut reloaded = running.clone();
+		reloaded.logging.maptos_log_level = LogLevel::Trace;
+		reloaded.chain.maptos_rest_log_sample_rate = SampleRate::new(0.5);
+		reloaded.faucet.faucet_rate_limit_per_ip_per_hour = 7;
+		reloaded.chain.maptos_rest_request_timeout_sec = DurationSecs::from_secs(11);
+		reloaded.fin.fin_rest_request_timeout_sec = DurationSecs::from_secs(13);
+
+		let changed: Vec<String> =
+			running.diff(&reloaded).into_iter().map(|change| change.path).collect();
+		assert_eq!(changed.len(), RELOADABLE_FIELDS.len());
+		let mut updated = running.clone();
+		updated.take_reloadable_fields(&reloaded);
+		assert_eq!(updated, reloaded);
+	}
+
+	#[test]
+	fn test_reload_swaps_only_reloadable_fields() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.faucet.faucet_rate_limit_per_ip_per_hour = 100;
+		config.chain.maptos_rest_listen_port = 30731;
+		let dir = tempfile::tempdir()?;
+		let path = dir.path().join("maptos.toml");
+		std::fs::write(
+			&path,
+			"[chain]\n\
+			private_key = \"0x0000000000000000000000000000000000000000000000000000000000000001\"\n\
+			[faucet]\nrate_limit_per_ip_per_hour = 7\n\
+			[rest]\nlisten_port = 40000\n",
+		)?;
+
+		let reloader = ConfigReloader::new(config.clone(), ReloadSource::Toml(path));
+		let shared = reloader.shared();
+		let report = reloader.reload()?;
+
+		assert_eq!(shared.load().faucet_rate_limit_per_ip_per_hour, 7);
+		assert!(report
+			.applied
+			.iter()
+			.any(|change| change.path == "faucet.faucet_rate_limit_per_ip_per_hour"));
+		assert!(report
+			.ignored
+			.iter()
+			.any(|change| change.path == "chain.maptos_rest_listen_port"));
+		let running = reloader.running();
+		assert_eq!(running.chain.maptos_rest_listen_port, 30731);
+		assert_eq!(running.chain.maptos_private_key, config.chain.maptos_private_key);
+		Ok(())
+	}
+
+	#[test]
+	fn test_reload_reads_the_node_config_json() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.faucet.faucet_rate_limit_per_ip_per_hour = 100;
+		let mut reloaded = config.clone();
+		reloaded.faucet.faucet_rate_limit_per_ip_per_hour = 7;
+		let dir = tempfile::tempdir()?;
+		let path = dir.path().join("config.json");
+		std::fs::write(
+			&path,
+			serde_json::to_string(&serde_json::json!({
+				"maptos_config": reloaded,
+				"mcr": {},
+			}))?,
+		)?;
+
+		let reloader = ConfigReloader::new(config, ReloadSource::node_config_json(path.clone()));
+		reloader.reload()?;
+		assert_eq!(reloader.shared().load().faucet_rate_limit_per_ip_per_hour, 7);
+
+		let missing = ReloadSource::Json { path, pointer: "/execution".to_string() };
+		assert!(missing.load().is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_invalid_reload_changes_nothing() {
+		let _env = godfig::env::read_lock();
+		let config = Config::default();
+		let reloader = ConfigReloader::new(config.clone(), ReloadSource::Env);
+		let mut reloaded = config.clone();
+		reloaded.fin.fin_rest_request_timeout_sec = DurationSecs::ZERO;
+
+		assert!(reloader.apply(&reloaded).is_err());
+		assert_eq!(*reloader.shared().load_full(), config.runtime_config());
+		assert_eq!(reloader.running(), config);
+	}
+}
@@ -0,0 +1,273 @@
+//! The groups of REST API routes a node can be limited to serving.
+
+use super::Config;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// A group of REST API routes, named for `MAPTOS_API_METHOD_ALLOWLIST`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RestRoute {
+	/// The ledger info at `/` and `/v1`
+	Index,
+	/// `/v1/-/healthy`
+	Healthy,
+	/// `/v1/info`
+	Info,
+	/// Account resources, modules, transactions and events under `/v1/accounts`
+	Accounts,
+	/// `/v1/blocks`
+	Blocks,
+	/// Reading committed transactions under `/v1/transactions`
+	Transactions,
+	/// `POST /v1/transactions` and `POST /v1/transactions/batch`
+	SubmitTransaction,
+	/// `/v1/transactions/simulate`
+	SimulateTransaction,
+	/// `/v1/transactions/encode_submission`
+	EncodeSubmission,
+	/// `/v1/estimate_gas_price`
+	EstimateGasPrice,
+	/// `/v1/tables`
+	Tables,
+	/// `/v1/view`
+	View,
+	/// The OpenAPI spec and its UI
+	Spec,
+	/// `/set_failpoint`
+	SetFailpoint,
+	/// The node's version at `/version`
+	Version,
+}
+
+impl RestRoute {
+	pub const ALL: &'static [RestRoute] = &[
+		RestRoute::Index,
+		RestRoute::Healthy,
+		RestRoute::Info,
+		RestRoute::Accounts,
+		RestRoute::Blocks,
+		RestRoute::Transactions,
+		RestRoute::SubmitTransaction,
+		RestRoute::SimulateTransaction,
+		RestRoute::EncodeSubmission,
+		RestRoute::EstimateGasPrice,
+		RestRoute::Tables,
+		RestRoute::View,
+		RestRoute::Spec,
+		RestRoute::SetFailpoint,
+		RestRoute::Version,
+	];
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			RestRoute::Index => "index",
+			RestRoute::Healthy => "healthy",
+			RestRoute::Info => "info",
+			RestRoute::Accounts => "accounts",
+			RestRoute::Blocks => "blocks",
+			RestRoute::Transactions => "transactions",
+			RestRoute::SubmitTransaction => "submit_transaction",
+			RestRoute::SimulateTransaction => "simulate_transaction",
+			RestRoute::EncodeSubmission => "encode_submission",
+			RestRoute::EstimateGasPrice => "estimate_gas_price",
+			RestRoute::Tables => "tables",
+			RestRoute::View => "view",
+			RestRoute::Spec => "spec",
+			RestRoute::SetFailpoint => "set_failpoint",
+			RestRoute::Version => "version",
+		}
+	}
+
+	/// The route group serving a request, or `None` for a path no route serves.
+	///
+	/// `path` is relative to the API base path.
+	pub fn matching(method: &str, path: &str) -> Option<RestRoute> {
+		let path = path.trim_end_matches('/');
+		match path {
+			"" => return Some(RestRoute::Index),
+			"/set_failpoint" => return Some(RestRoute::SetFailpoint),
+			"/version" => return Some(RestRoute::Version),
+			"/spec.json" | "/spec.yaml" => return Some(RestRoute::Spec),
+			_ if path == "/spec" || path.starts_with("/spec/") => return Some(RestRoute::Spec),
+			_ => {}
+		}
+		let path = path.strip_prefix("/v1")?;
+		if path.is_empty() {
+			return Some(RestRoute::Index);
+		}
+		let mut segments = path.strip_prefix('/')?.split('/');
+		let post = method.eq_ignore_ascii_case("POST");
+		let route = match (segments.next()?, segments.next()) {
+			("-", Some("healthy")) => RestRoute::Healthy,
+			("info", None) => RestRoute::Info,
+			("accounts", _) => RestRoute::Accounts,
+			("blocks", _) => RestRoute::Blocks,
+			("transactions", None | Some("batch")) if post => RestRoute::SubmitTransaction,
+			("transactions", Some("simulate")) => RestRoute::SimulateTransaction,
+			("transactions", Some("encode_submission")) => RestRoute::EncodeSubmission,
+			("transactions", _) => RestRoute::Transactions,
+			("estimate_gas_price", None) => RestRoute::EstimateGasPrice,
+			("tables", _) => RestRoute::Tables,
+			("view", None) => RestRoute::View,
+			("spec" | "spec.json" | "spec.yaml", _) => RestRoute::Spec,
+			_ => return None,
+		};
+		Some(route)
+	}
+}
+
+impl FromStr for RestRoute {
+	type Err = anyhow::Error;
+
+	fn from_str(raw: &str) -> Result<Self, Self::Err> {
+		let name = raw.trim().to_ascii_lowercase();
+		Self::ALL.iter().copied().find(|route| route.as_str() == name).ok_or_else(|| {
+			anyhow::anyhow!(
+				"unknown REST route {:?}, expected \"all\" or some of: {}",
+				raw,
+				Self::ALL.iter().map(RestRoute::as_str).collect::<Vec<_>>().join(", ")
+			)
+		})
+	}
+}
+
+impl fmt::Display for RestRoute {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+/// The REST routes a node serves, given as `all` or a comma-separated list of names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RestRouteSet(BTreeSet<RestRoute>);
+
+impl RestRouteSet {
+	pub fn all() -> Self {
+		RestRouteSet(RestRoute::ALL.iter().copied().collect())
+	}
+
+	pub fn is_all(&self) -> bool {
+		*self == Self::all()
+	}
+
+	pub fn contains(&self, route: RestRoute) -> bool {
+		self.0.contains(&route)
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = RestRoute> + '_ {
+		self.0.iter().copied()
+	}
+
+	/// Whether a request is served; with anything short of `all`, paths no route serves are not.
+	pub fn allows(&self, method: &str, path: &str) -> bool {
+		self.is_all()
+			|| RestRoute::matching(method, path).map_or(false, |route| self.contains(route))
+	}
+}
+
+impl FromIterator<RestRoute> for RestRouteSet {
+	fn from_iter<I: IntoIterator<Item = RestRoute>>(routes: I) -> Self {
+		RestRouteSet(routes.into_iter().collect())
+	}
+}
+
+impl FromStr for RestRouteSet {
+	type Err = anyhow::Error;
+
+	fn from_str(raw: &str) -> Result<Self, Self::Err> {
+		if raw.trim().eq_ignore_ascii_case("all") {
+			return Ok(Self::all());
+		}
+		let routes = raw
+			.split(',')
+			.map(str::trim)
+			.filter(|name| !name.is_empty())
+			.map(str::parse)
+			.collect::<Result<RestRouteSet, _>>()?;
+		if routes.0.is_empty() {
+			anyhow::bail!("no REST routes listed, expected \"all\" or a comma-separated list");
+		}
+		Ok(routes)
+	}
+}
+
+impl fmt::Display for RestRouteSet {
+	/// Displays the full set as `all`, and any other as a comma-separated list.
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		if self.is_all() {
+			return f.write_str("all");
+		}
+		let names: Vec<&str> = self.iter().map(|route| route.as_str()).collect();
+		f.write_str(&names.join(","))
+	}
+}
+
+impl Serialize for RestRouteSet {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_str(self)
+	}
+}
+
+impl<'de> Deserialize<'de> for RestRouteSet {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+	}
+}
+
+impl Config {
+	/// The REST route groups served, every one unless `MAPTOS_API_METHOD_ALLOWLIST` limits them.
+	pub fn rest_allowed_methods(&self) -> &RestRouteSet {
+		&self.chain.maptos_rest_method_allowlist
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_all_allows_every_request() -> Result<(), anyhow::Error> {
+		let routes: RestRouteSet = " ALL ".parse()?;
+		assert!(routes.is_all());
+		assert_eq!(routes.to_string(), "all");
+		assert!(routes.allows("POST", "/v1/transactions"));
+		assert!(routes.allows("GET", "/set_failpoint"));
+		// Filtering is off, so even paths no route serves are left to the router.
+		assert!(routes.allows("GET", "/nonexistent"));
+		Ok(())
+	}
+
+	#[test]
+	fn test_subset_blocks_other_routes() -> Result<(), anyhow::Error> {
+		let routes: RestRouteSet = "index, accounts,transactions,".parse()?;
+		assert_eq!(routes.to_string(), "index,accounts,transactions");
+		assert!(routes.allows("GET", "/"));
+		assert!(routes.allows("GET", "/v1"));
+		assert!(routes.allows("GET", "/v1/accounts/0x1/resources"));
+		assert!(routes.allows("GET", "/v1/transactions/by_hash/0xabc"));
+		assert!(routes.allows("GET", "/v1/transactions/"));
+
+		assert!(!routes.allows("POST", "/v1/transactions"));
+		assert!(!routes.allows("POST", "/v1/transactions/batch"));
+		assert!(!routes.allows("POST", "/v1/transactions/simulate"));
+		assert!(!routes.allows("POST", "/v1/view"));
+		assert!(!routes.allows("GET", "/set_failpoint"));
+		assert!(!routes.allows("GET", "/version"));
+		assert!(!routes.allows("GET", "/spec.json"));
+		assert!(!routes.allows("GET", "/v1accounts"));
+		assert!(!routes.allows("GET", "/nonexistent"));
+		Ok(())
+	}
+
+	#[test]
+	fn test_unknown_route_names_are_rejected() {
+		let err = "accounts,transfers".parse::<RestRouteSet>().unwrap_err();
+		assert!(err.to_string().contains("\"transfers\""), "{}", err);
+		assert!(err.to_string().contains("submit_transaction"), "{}", err);
+		assert!(" , ".parse::<RestRouteSet>().is_err());
+	}
+}
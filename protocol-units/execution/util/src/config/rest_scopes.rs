@@ -0,0 +1,253 @@
+//! Bearer tokens which each permit part of the REST API, for `MAPTOS_REST_SCOPED_TOKENS`.
+
+use super::auth::constant_time_eq;
+use super::rest_routes::RestRoute;
+use super::Config;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::str::FromStr;
+
+/// The part of the REST API a scoped token permits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum RestScope {
+	/// Every request which does not change the node, simulations and view functions included.
+	Read,
+	/// Submitting transactions.
+	Submit,
+	/// `/set_failpoint`.
+	Admin,
+}
+
+impl RestScope {
+	pub const ACCEPTED: &'static [&'static str] = &["read", "submit", "admin"];
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			RestScope::Read => "read",
+			RestScope::Submit => "submit",
+			RestScope::Admin => "admin",
+		}
+	}
+
+	/// The scope a request needs; `path` is relative to the API base path.
+	pub fn required_for(method: &str, path: &str) -> RestScope {
+		match RestRoute::matching(method, path) {
+			Some(RestRoute::SubmitTransaction) => RestScope::Submit,
+			Some(RestRoute::SetFailpoint) => RestScope::Admin,
+			_ => RestScope::Read,
+		}
+	}
+}
+
+impl FromStr for RestScope {
+	type Err = anyhow::Error;
+
+	fn from_str(raw: &str) -> Result<Self, Self::Err> {
+		match raw.trim().to_ascii_lowercase().as_str() {
+			"read" => Ok(RestScope::Read),
+			"submit" => Ok(RestScope::Submit),
+			"admin" => Ok(RestScope::Admin),
+			_ => anyhow::bail!(
+				"unknown REST scope {:?}, expected one of: {}",
+				raw,
+				Self::ACCEPTED.join(", ")
+			),
+		}
+	}
+}
+
+impl fmt::Display for RestScope {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+/// Whether a request may be served under the scoped tokens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RestAccess {
+	Allowed,
+	/// No configured token was presented, answered with 401.
+	Unauthorized,
+	/// The token presented lacks the scope the request needs, answered with 403.
+	Forbidden,
+}
+
+/// The tokens REST requests may present as `Authorization: Bearer <token>`, and the scopes
+/// each grants, given as a comma-separated list of `token:scope1|scope2` entries.
+///
+/// Without any token every request is served, as it is without scoped tokens at all.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RestScopedTokens(Vec<(String, BTreeSet<RestScope>)>);
+
+impl RestScopedTokens {
+	pub fn is_empty(&self) -> bool {
+		self.0.is_empty()
+	}
+
+	/// The scopes `presented` grants, or `None` when it is not a configured token.
+	///
+	/// Every configured token is compared, so the timing does not reveal which one matched.
+	pub fn scopes_of(&self, presented: &str) -> Option<&BTreeSet<RestScope>> {
+		self.0.iter().fold(None, |matched, (token, scopes)| {
+			if constant_time_eq(token.as_bytes(), presented.as_bytes()) {
+				Some(scopes)
+			} else {
+				matched
+			}
+		})
+	}
+
+	/// Whether a request may be served, given the value of its `Authorization` header.
+	pub fn check(&self, authorization: Option<&str>, method: &str, path: &str) -> RestAccess {
+		if self.is_empty() {
+			return RestAccess::Allowed;
+		}
+		let presented = authorization.and_then(|value| {
+			let (scheme, token) = value.trim().split_once(' ')?;
+			scheme.eq_ignore_ascii_case("bearer").then(|| token.trim())
+		});
+		match presented.and_then(|token| self.scopes_of(token)) {
+			None => RestAccess::Unauthorized,
+			Some(scopes) if scopes.contains(&RestScope::required_for(method, path)) => {
+				RestAccess::Allowed
+			}
+			Some(_) => RestAccess::Forbidden,
+		}
+	}
+}
+
+impl FromStr for RestScopedTokens {
+	type Err = anyhow::Error;
+
+	/// Parses the tokens, naming a malformed entry by its position so that the error does not
+	/// reveal a token.
+	fn from_str(raw: &str) -> Result<Self, Self::Err> {
+		let mut tokens = Vec::new();
+		for (index, entry) in
+			raw.split(',').map(str::trim).filter(|entry| !entry.is_empty()).enumerate()
+		{
+			let malformed = |reason: &str| {
+				anyhow::anyhow!("invalid scoped token entry {}: {}", index + 1, reason)
+			};
+			let (token, scopes) =
+				entry.rsplit_once(':').ok_or_else(|| malformed("expected token:scopes"))?;
+			let token = token.trim();
+			if token.is_empty() {
+				return Err(malformed("the token is empty"));
+			}
+			let scopes = scopes
+				.split('|')
+				.map(str::trim)
+				.filter(|scope| !scope.is_empty())
+				.map(str::parse)
+				.collect::<Result<BTreeSet<RestScope>, _>>()
+				.map_err(|e| malformed(&e.to_string()))?;
+			if scopes.is_empty() {
+				return Err(malformed("no scopes listed"));
+			}
+			if tokens.iter().any(|(existing, _)| existing == token) {
+				return Err(malformed("the token is listed more than once"));
+			}
+			tokens.push((token.to_string(), scopes));
+		}
+		Ok(RestScopedTokens(tokens))
+	}
+}
+
+impl fmt::Display for RestScopedTokens {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		let entries: Vec<String> = self
+			.0
+			.iter()
+			.map(|(token, scopes)| {
+				let scopes: Vec<&str> = scopes.iter().map(RestScope::as_str).collect();
+				format!("{}:{}", token, scopes.join("|"))
+			})
+			.collect();
+		f.write_str(&entries.join(","))
+	}
+}
+
+impl Serialize for RestScopedTokens {
+	fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+		serializer.collect_str(self)
+	}
+}
+
+impl<'de> Deserialize<'de> for RestScopedTokens {
+	fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+		String::deserialize(deserializer)?.parse().map_err(serde::de::Error::custom)
+	}
+}
+
+impl Config {
+	/// The scoped tokens REST requests must present, none unless `MAPTOS_REST_SCOPED_TOKENS`
+	/// lists some.
+	pub fn rest_scoped_tokens(&self) -> &RestScopedTokens {
+		&self.chain.maptos_rest_scoped_tokens
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn tokens() -> RestScopedTokens {
+		"reader-token:read, submitter-token:read|submit".parse().unwrap()
+	}
+
+	#[test]
+	fn test_token_without_the_scope_is_forbidden() {
+		let tokens = tokens();
+		assert_eq!(
+			tokens.check(Some("Bearer reader-token"), "POST", "/v1/transactions"),
+			RestAccess::Forbidden
+		);
+		assert_eq!(
+			tokens.check(Some("Bearer submitter-token"), "GET", "/set_failpoint"),
+			RestAccess::Forbidden
+		);
+		assert_eq!(
+			tokens.check(Some("Bearer other-token"), "GET", "/v1"),
+			RestAccess::Unauthorized
+		);
+		assert_eq!(tokens.check(None, "GET", "/v1"), RestAccess::Unauthorized);
+	}
+
+	#[test]
+	fn test_token_with_the_scope_is_allowed() {
+		let tokens = tokens();
+		assert_eq!(
+			tokens.check(Some("bearer submitter-token"), "POST", "/v1/transactions"),
+			RestAccess::Allowed
+		);
+		assert_eq!(
+			tokens.check(Some("Bearer reader-token"), "POST", "/v1/view"),
+			RestAccess::Allowed
+		);
+		// Without scoped tokens nothing is checked.
+		assert_eq!(
+			RestScopedTokens::default().check(None, "POST", "/v1/transactions"),
+			RestAccess::Allowed
+		);
+	}
+
+	#[test]
+	fn test_malformed_entries_do_not_reveal_tokens() {
+		for raw in [
+			"reader-token:read,secret-token",
+			"reader-token:read,secret-token:write",
+			"reader-token:read,secret-token:",
+			"reader-token:read,:read",
+			"reader-token:read,reader-token:submit",
+		] {
+			let err = raw.parse::<RestScopedTokens>().unwrap_err().to_string();
+			assert!(err.starts_with("invalid scoped token entry 2"), "{} for {:?}", err, raw);
+			assert!(!err.contains("-token"), "{} for {:?}", err, raw);
+		}
+		assert_eq!(tokens().to_string().parse::<RestScopedTokens>().unwrap(), tokens());
+	}
+}
@@ -0,0 +1,187 @@
+//! Retrying fallible operations with bounded exponential backoff.
+
+use super::common::{
+	default_maptos_retry_base_delay_sec, default_maptos_retry_jitter,
+	default_maptos_retry_max_attempts, default_maptos_retry_max_delay_sec,
+};
+
+use rand::Rng;
+
+use std::future::Future;
+use std::time::Duration;
+
+/// How often and how far apart an operation is attempted before its error is returned.
+///
+/// The delay before the `n`th retry is `base_delay * 2^n`, capped at `max_delay`,
+/// less a random fraction of up to `jitter` of itself so that clients started together
+/// do not retry in lockstep.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct RetryPolicy {
+	/// Attempts made in all, the first one included; 0 counts as 1
+	pub max_attempts: u32,
+	/// The delay before the first retry
+	pub base_delay: Duration,
+	/// The longest delay between two attempts
+	pub max_delay: Duration,
+	/// The fraction of each delay which is randomized, from 0.0 to 1.0
+	pub jitter: f64,
+}
+
+impl Default for RetryPolicy {
+	fn default() -> Self {
+		Self {
+			max_attempts: default_maptos_retry_max_attempts(),
+			base_delay: default_maptos_retry_base_delay_sec().get(),
+			max_delay: default_maptos_retry_max_delay_sec().get(),
+			jitter: default_maptos_retry_jitter(),
+		}
+	}
+}
+
+impl RetryPolicy {
+	/// A policy retrying at a fixed `delay`, without jitter.
+	pub fn fixed(max_attempts: u32, delay: Duration) -> Self {
+		Self { max_attempts, base_delay: delay, max_delay: delay, jitter: 0.0 }
+	}
+
+	/// The attempts made before giving up, at least 1.
+	pub fn attempts(&self) -> u32 {
+		self.max_attempts.max(1)
+	}
+
+	/// The delay before the `retry`th retry, counting from 0, before jitter is applied.
+	pub fn backoff(&self, retry: u32) -> Duration {
+		let factor = 1u32.checked_shl(retry).unwrap_or(u32::MAX);
+		self.base_delay.saturating_mul(factor).min(self.max_delay)
+	}
+
+	/// The delay before the `retry`th retry, counting from 0, with jitter applied.
+	pub fn delay(&self, retry: u32) -> Duration {
+		let backoff = self.backoff(retry);
+		// A jitter set in code rather than parsed may be out of range or NaN, which counts as 0.
+		if self.jitter.is_nan() || self.jitter <= 0.0 {
+			return backoff;
+		}
+		backoff.mul_f64(1.0 - self.jitter.min(1.0) * rand::thread_rng().gen::<f64>())
+	}
+
+	/// Runs `op` until it succeeds or every attempt has failed, returning the last error.
+	pub async fn retry<T, E, F, Fut>(&self, op: F) -> Result<T, E>
+	where
+		F: FnMut() -> Fut,
+		Fut: Future<Output = Result<T, E>>,
+	{
+		self.retry_notify(op, |_, _| {}).await
+	}
+
+	/// Like [`RetryPolicy::retry`], calling `notify` with each error which is retried
+	/// and the delay before the next attempt.
+	pub async fn retry_notify<T, E, F, Fut, N>(&self, mut op: F, mut notify: N) -> Result<T, E>
+	where
+		F: FnMut() -> Fut,
+		Fut: Future<Output = Result<T, E>>,
+		N: FnMut(&E, Duration),
+	{
+		let mut retry = 0;
+		loop {
+			let err = match op().await {
+				Ok(value) => return Ok(value),
+				Err(err) => err,
+			};
+			if retry + 1 >= self.attempts() {
+				return Err(err);
+			}
+			let delay = self.delay(retry);
+			notify(&err, delay);
+			tokio::time::sleep(delay).await;
+			retry += 1;
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[tokio::test]
+	async fn test_every_attempt_is_made() {
+		let policy = RetryPolicy::fixed(4, Duration::from_millis(1));
+		let mut calls = 0;
+		let mut notified = 0;
+		let result = policy
+			.retry_notify(
+				|| {
+					calls += 1;
+					async { Err::<(), _>("refused") }
+				},
+				|_, _| notified += 1,
+			)
+			.await;
+		assert_eq!(result, Err("refused"));
+		assert_eq!(calls, 4);
+		// The last failure is returned rather than retried.
+		assert_eq!(notified, 3);
+
+		let mut calls = 0;
+		let result = policy
+			.retry(|| {
+				calls += 1;
+				let succeeds = calls == 2;
+				async move {
+					if succeeds {
+						Ok(calls)
+					} else {
+						Err("refused")
+					}
+				}
+			})
+			.await;
+		assert_eq!(result, Ok(2));
+
+		let mut calls = 0;
+		let _ = RetryPolicy::fixed(0, Duration::ZERO)
+			.retry(|| {
+				calls += 1;
+				async { Err::<(), _>("refused") }
+			})
+			.await;
+		assert_eq!(calls, 1);
+	}
+
+	#[test]
+	fn test_backoff_doubles_up_to_the_cap() {
+		let policy = RetryPolicy {
+			max_attempts: 10,
+			base_delay: Duration::from_millis(250),
+			max_delay: Duration::from_secs(2),
+			jitter: 0.0,
+		};
+		let delays: Vec<Duration> = (0..6).map(|retry| policy.delay(retry)).collect();
+		assert_eq!(delays, [250, 500, 1000, 2000, 2000, 2000].map(Duration::from_millis));
+		assert_eq!(policy.backoff(u32::MAX), Duration::from_secs(2));
+	}
+
+	#[test]
+	fn test_jitter_stays_within_bounds() {
+		let policy = RetryPolicy {
+			max_attempts: 10,
+			base_delay: Duration::from_secs(1),
+			max_delay: Duration::from_secs(8),
+			jitter: 0.25,
+		};
+		for retry in 0..5 {
+			let backoff = policy.backoff(retry);
+			for _ in 0..1000 {
+				let delay = policy.delay(retry);
+				assert!(delay <= backoff, "{:?} exceeds {:?}", delay, backoff);
+				assert!(delay >= backoff.mul_f64(0.75), "{:?} is below the jitter", delay);
+			}
+		}
+
+		// Out of range jitter is clamped, so delays never go negative or grow.
+		let policy = RetryPolicy { jitter: 4.0, ..policy };
+		assert!((0..1000).all(|_| policy.delay(0) <= Duration::from_secs(1)));
+		let policy = RetryPolicy { jitter: f64::NAN, ..policy };
+		assert_eq!(policy.delay(0), Duration::from_secs(1));
+	}
+}
@@ -0,0 +1,271 @@
+//! A JSON schema of the config, for tooling which generates or checks config files.
+
+use super::chain::{AddressFormat, RestEncoding, SyncMode};
+use super::clock::ClockKind;
+use super::commit_batch::FsyncPolicy;
+use super::common::{
+	config_defaults, ConfigDefault, MAX_EXECUTION_CONCURRENCY, MAX_INDEXER_GRPC_BATCH_SIZE,
+	MAX_WORKER_THREADS,
+};
+use super::diff::{flatten, FIELD_ENV_VARS, SECRET_PATHS};
+use super::health::HealthMode;
+use super::indexer::GrpcCompression;
+use super::load_shedding::{MempoolOrdering, OverflowPolicy};
+use super::logging::{LogFormat, LogLevel};
+use super::signer::KeyScheme;
+use super::validation::GRPC_MAX_MESSAGE_BYTES_RANGE;
+use super::Config;
+
+use serde_json::{json, Map, Value};
+
+use std::collections::BTreeMap;
+
+/// Fields which only take effect when all of them are set.
+const REQUIRED_TOGETHER: &[&[&str]] = &[
+	&["chain.maptos_rest_tls_cert_path", "chain.maptos_rest_tls_key_path"],
+	&["indexer.maptos_indexer_grpc_tls_cert", "indexer.maptos_indexer_grpc_tls_key"],
+];
+
+/// Fields which are unset by default and strings when set.
+const NULLABLE: &[&str] = &[
+	"chain.maptos_db_path",
+	"chain.maptos_genesis_blob_path",
+	"chain.maptos_framework_path",
+	"faucet.faucet_private_key",
+];
+
+/// Lower bounds enforced by [`Config::validate`] beyond those of the field types.
+const MINIMUMS: &[(&str, u64)] = &[
+	("chain.maptos_rest_request_timeout_sec", 1),
+	("chain.maptos_rest_max_body_bytes", 1),
+	("chain.maptos_rest_max_gas_amount", 1),
+	("chain.maptos_rest_max_page_size", 1),
+	("chain.maptos_rest_max_transaction_bytes", 1),
+	("chain.maptos_db_block_cache_bytes", 1),
+	("chain.maptos_db_max_wal_bytes", 1),
+	("chain.maptos_commit_batch_size", 1),
+	("fin.fin_rest_request_timeout_sec", 1),
+	("faucet.faucet_txn_expiration_sec", 1),
+	("faucet.maptos_faucet_rest_max_body_bytes", 1),
+	("indexer_processor.postgres_max_connections", 1),
+	("indexer.maptos_indexer_grpc_max_message_bytes", *GRPC_MAX_MESSAGE_BYTES_RANGE.start()),
+	("indexer.maptos_indexer_grpc_batch_size", 1),
+];
+
+/// Upper bounds enforced by [`Config::validate`] beyond those of the field types.
+const MAXIMUMS: &[(&str, u64)] = &[
+	("chain.maptos_execution_concurrency", MAX_EXECUTION_CONCURRENCY as u64),
+	("chain.maptos_rest_max_page_size", u16::MAX as u64),
+	("chain.maptos_rest_worker_threads", MAX_WORKER_THREADS as u64),
+	("fin.fin_rest_worker_threads", MAX_WORKER_THREADS as u64),
+	("indexer.maptos_indexer_grpc_max_message_bytes", *GRPC_MAX_MESSAGE_BYTES_RANGE.end()),
+	("indexer.maptos_indexer_worker_threads", MAX_WORKER_THREADS as u64),
+	("indexer.maptos_indexer_grpc_batch_size", MAX_INDEXER_GRPC_BATCH_SIZE as u64),
+];
+
+/// Describes every config field with its type, built-in default, env var and constraints.
+///
+/// Properties are keyed by section and then by field, as the config serializes.
+/// The output is sorted and defaults are the built-in ones rather than those read from the
+/// environment, so it can be diffed between releases.
+/// Besides standard keywords, fields carry `x-env-var`, secrets are `writeOnly` without a default,
+/// and `x-required-together` lists the field paths which must be set together.
+pub fn config_json_schema() -> Value {
+	let mut fields = BTreeMap::new();
+	flatten(
+		"",
+		&serde_json::to_value(Config::default()).expect("the config is always serializable"),
+		&mut fields,
+	);
+	let defaults: BTreeMap<&str, ConfigDefault> = config_defaults()
+		.into_iter()
+		.map(|default| (default.env_var, default))
+		.collect();
+
+	let mut sections: BTreeMap<&str, Map<String, Value>> = BTreeMap::new();
+	for (path, value) in &fields {
+		let (section, field) = path.split_once('.').expect("every field belongs to a section");
+		let env_var = FIELD_ENV_VARS
+			.iter()
+			.find(|(field_path, _)| field_path == path)
+			.map(|(_, var)| *var);
+		let default = env_var.and_then(|var| defaults.get(var));
+		sections
+			.entry(section)
+			.or_default()
+			.insert(field.to_string(), field_schema(path, value, env_var, default));
+	}
+
+	let properties: Map<String, Value> = sections
+		.into_iter()
+		.map(|(section, properties)| {
+			(
+				section.to_string(),
+				json!({
+					"type": "object",
+					"properties": properties,
+					"additionalProperties": false,
+				}),
+			)
+		})
+		.collect();
+
+	json!({
+		"$schema": "https://json-schema.org/draft/2020-12/schema",
+		"title": "Config",
+		"type": "object",
+		"properties": properties,
+		"additionalProperties": false,
+		"x-required-together": REQUIRED_TOGETHER,
+	})
+}
+
+fn field_schema(
+	path: &str,
+	value: &Value,
+	env_var: Option<&str>,
+	default: Option<&ConfigDefault>,
+) -> Value {
+	let mut schema = Map::new();
+	let type_name = default.map(|default| default.type_name);
+	match value {
+		_ if NULLABLE.contains(&path) => {
+			schema.insert("type".into(), json!(["string", "null"]));
+		}
+		Value::Bool(_) => {
+			schema.insert("type".into(), "boolean".into());
+		}
+		// Durations are whole seconds unless they carry a unit.
+		_ if type_name == Some("DurationSecs") => {
+			schema.insert(
+				"oneOf".into(),
+				json!([
+					{ "type": "integer", "minimum": minimum(path) },
+					{ "type": "string", "pattern": "^[0-9]+(ns|us|ms|s|m|h)$" },
+				]),
+			);
+		}
+		// Only sample rates are fractional.
+		Value::Number(number) if number.is_f64() => {
+			schema.insert("type".into(), "number".into());
+			schema.insert("minimum".into(), 0.0.into());
+			schema.insert("maximum".into(), 1.0.into());
+		}
+		Value::Number(_) => {
+			schema.insert("type".into(), "integer".into());
+			schema.insert("minimum".into(), minimum(path).into());
+			let maximum = maximum(path).or(match type_name {
+				Some("u16") => Some(u64::from(u16::MAX)),
+				Some("u32") => Some(u64::from(u32::MAX)),
+				_ => None,
+			});
+			if let Some(maximum) = maximum {
+				schema.insert("maximum".into(), maximum.into());
+			}
+		}
+		Value::Array(_) => {
+			schema.insert("type".into(), "array".into());
+			schema.insert("items".into(), json!({ "type": "string" }));
+		}
+		Value::Null if type_name == Some("Option<u64>") => {
+			schema.insert("type".into(), json!(["integer", "null"]));
+			schema.insert("minimum".into(), minimum(path).into());
+		}
+		Value::Null => {
+			schema.insert("type".into(), json!(["string", "null"]));
+		}
+		_ => {
+			schema.insert("type".into(), "string".into());
+			let accepted = match type_name {
+				Some("LogFormat") => Some(LogFormat::ACCEPTED),
+				Some("LogLevel") => Some(LogLevel::ACCEPTED),
+				Some("GrpcCompression") => Some(GrpcCompression::ACCEPTED),
+				Some("ClockKind") => Some(ClockKind::ACCEPTED),
+				Some("SyncMode") => Some(SyncMode::ACCEPTED),
+				Some("RestEncoding") => Some(RestEncoding::ACCEPTED),
+				Some("AddressFormat") => Some(AddressFormat::ACCEPTED),
+				Some("OverflowPolicy") => Some(OverflowPolicy::ACCEPTED),
+				Some("MempoolOrdering") => Some(MempoolOrdering::ACCEPTED),
+				Some("HealthMode") => Some(HealthMode::ACCEPTED),
+				Some("KeyScheme") => Some(KeyScheme::ACCEPTED),
+				Some("FsyncPolicy") => Some(FsyncPolicy::ACCEPTED),
+				_ => None,
+			};
+			if let Some(accepted) = accepted {
+				schema.insert("enum".into(), json!(accepted));
+			}
+		}
+	}
+
+	if SECRET_PATHS.contains(&path) {
+		schema.insert("writeOnly".into(), true.into());
+	} else if let Some(default) = default {
+		schema.insert("default".into(), default_value(value, &default.default_display));
+	}
+	if let Some(env_var) = env_var {
+		schema.insert("x-env-var".into(), env_var.into());
+	}
+	Value::Object(schema)
+}
+
+fn minimum(path: &str) -> u64 {
+	MINIMUMS
+		.iter()
+		.find(|(field, _)| *field == path)
+		.map_or(0, |(_, minimum)| *minimum)
+}
+
+fn maximum(path: &str) -> Option<u64> {
+	MAXIMUMS.iter().find(|(field, _)| *field == path).map(|(_, maximum)| *maximum)
+}
+
+/// Types a default as the field serializes, since the registry only records how it displays.
+fn default_value(value: &Value, display: &str) -> Value {
+	match value {
+		Value::Bool(_) => display.parse::<bool>().map_or_else(|_| display.into(), Value::from),
+		Value::Number(number) if number.is_f64() => {
+			display.parse::<f64>().map_or_else(|_| display.into(), Value::from)
+		}
+		Value::Number(_) => display.parse::<u64>().map_or_else(|_| display.into(), Value::from),
+		Value::Null if display.is_empty() => Value::Null,
+		Value::Array(_) if display.is_empty() => json!([]),
+		Value::Array(_) => json!(display.split(',').map(str::trim).collect::<Vec<_>>()),
+		_ => display.into(),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	#[test]
+	fn test_chain_id_is_annotated_with_its_env_var() {
+		let schema = config_json_schema();
+		let chain_id = &schema["properties"]["chain"]["properties"]["maptos_chain_id"];
+		assert_eq!(chain_id["x-env-var"], "MAPTOS_CHAIN_ID");
+		assert_eq!(chain_id["type"], "integer");
+		assert_eq!(chain_id["default"], 27);
+	}
+
+	#[test]
+	fn test_constraints_are_described() {
+		let schema = config_json_schema();
+		let field =
+			|section: &str, field: &str| schema["properties"][section]["properties"][field].clone();
+		assert_eq!(field("chain", "maptos_rest_listen_port")["maximum"], 65535);
+		assert_eq!(field("fin", "fin_rest_worker_threads")["maximum"], MAX_WORKER_THREADS);
+		assert_eq!(field("chain", "maptos_rest_log_sample_rate")["type"], "number");
+		assert_eq!(field("chain", "maptos_rest_log_sample_rate")["default"], 0.0);
+		assert_eq!(field("chain", "maptos_private_key")["writeOnly"], true);
+		assert!(field("chain", "maptos_private_key").get("default").is_none());
+		assert_eq!(field("logging", "maptos_log_level")["enum"], json!(LogLevel::ACCEPTED));
+		let timeout = field("chain", "maptos_rest_request_timeout_sec");
+		assert_eq!(timeout["oneOf"][0], json!({ "type": "integer", "minimum": 1 }));
+		assert_eq!(timeout["oneOf"][1]["type"], "string");
+		assert_eq!(timeout["default"], 30);
+		assert_eq!(
+			schema["x-required-together"][0],
+			json!(["chain.maptos_rest_tls_cert_path", "chain.maptos_rest_tls_key_path"])
+		);
+	}
+}
@@ -0,0 +1,141 @@
+//! Looking up secrets, such as the private keys and auth tokens, from a pluggable backend.
+//!
+//! `MAPTOS_SECRET_PROVIDER` names the backend: `env`, the default, reads each secret from the
+//! env var it is named after, and others are registered with [`register_secret_provider`]
+//! before the config is loaded. The paths given by the `_FILE` variants are not secrets
+//! and are always read from the environment.
+
+use super::common::default_maptos_secret_provider;
+use super::error::ConfigError;
+
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, PoisonError, RwLock};
+
+/// The name of the provider reading secrets from the environment.
+pub const ENV_SECRET_PROVIDER: &str = "env";
+
+/// A backend holding secrets under the names of the env vars they would otherwise be set in.
+pub trait SecretProvider: Send + Sync {
+	/// The secret stored under `key`, failing with [`SecretNotFound`] when there is none.
+	fn get_secret(&self, key: &str) -> Result<String, anyhow::Error>;
+}
+
+/// The error of a [`SecretProvider`] holding no secret under `key`.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+#[error("no secret named {key}")]
+pub struct SecretNotFound {
+	pub key: String,
+}
+
+/// Reads each secret from the env var of the same name.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EnvSecretProvider;
+
+impl SecretProvider for EnvSecretProvider {
+	fn get_secret(&self, key: &str) -> Result<String, anyhow::Error> {
+		godfig::env::var(key).map_err(|_| SecretNotFound { key: key.to_string() }.into())
+	}
+}
+
+type Registry = RwLock<HashMap<String, Arc<dyn SecretProvider>>>;
+
+fn registry() -> &'static Registry {
+	static REGISTRY: OnceLock<Registry> = OnceLock::new();
+	REGISTRY.get_or_init(Registry::default)
+}
+
+/// Makes `provider` selectable as `MAPTOS_SECRET_PROVIDER=name`, replacing any registered
+/// under that name before. The `env` provider cannot be replaced.
+pub fn register_secret_provider(name: impl Into<String>, provider: Arc<dyn SecretProvider>) {
+	let name = name.into();
+	if name == ENV_SECRET_PROVIDER {
+		tracing::warn!("The {} secret provider is built in and cannot be replaced", name);
+		return;
+	}
+	registry()
+		.write()
+		.unwrap_or_else(PoisonError::into_inner)
+		.insert(name, provider);
+}
+
+/// The provider registered as `name`.
+pub fn secret_provider_named(name: &str) -> Result<Arc<dyn SecretProvider>, ConfigError> {
+	if name == ENV_SECRET_PROVIDER {
+		return Ok(Arc::new(EnvSecretProvider));
+	}
+	registry()
+		.read()
+		.unwrap_or_else(PoisonError::into_inner)
+		.get(name)
+		.cloned()
+		.ok_or_else(|| ConfigError::UnknownSecretProvider { name: name.to_string() })
+}
+
+/// The provider selected by `MAPTOS_SECRET_PROVIDER`.
+pub fn secret_provider() -> Result<Arc<dyn SecretProvider>, ConfigError> {
+	secret_provider_named(&default_maptos_secret_provider())
+}
+
+/// The secret under `key`, or `None` when `provider` holds none.
+pub(crate) fn optional_secret(
+	provider: &dyn SecretProvider,
+	key: &str,
+) -> Result<Option<String>, anyhow::Error> {
+	match provider.get_secret(key) {
+		Ok(secret) => Ok(Some(secret)),
+		Err(e) if e.is::<SecretNotFound>() => Ok(None),
+		Err(e) => Err(e.context(format!("failed to read the secret {}", key))),
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::config::common;
+
+	use aptos_crypto::{ed25519::Ed25519PrivateKey, Uniform, ValidCryptoMaterialStringExt};
+
+	/// Holds a fixed set of secrets, failing on `unreachable`.
+	struct MockProvider(HashMap<&'static str, String>);
+
+	impl SecretProvider for MockProvider {
+		fn get_secret(&self, key: &str) -> Result<String, anyhow::Error> {
+			if key == "unreachable" {
+				anyhow::bail!("the secret store is unreachable");
+			}
+			self.0
+				.get(key)
+				.cloned()
+				.ok_or_else(|| SecretNotFound { key: key.to_string() }.into())
+		}
+	}
+
+	#[test]
+	fn test_secrets_are_read_from_the_provider() -> Result<(), anyhow::Error> {
+		let private_key = Ed25519PrivateKey::generate(&mut rand::thread_rng());
+		let provider = MockProvider(HashMap::from([
+			("MAPTOS_PRIVATE_KEY", private_key.to_encoded_string()?),
+			("INDEXER_PROCESSOR_AUTH_TOKEN", "from-the-vault".to_string()),
+		]));
+		assert_eq!(common::maptos_private_key_from(&provider)?, Some(private_key));
+		assert_eq!(
+			common::indexer_processor_auth_token_from(&provider)?,
+			Some("from-the-vault".to_string())
+		);
+		assert_eq!(common::faucet_private_key_from(&provider)?, None);
+
+		assert!(optional_secret(&provider, "unreachable").is_err());
+		Ok(())
+	}
+
+	#[test]
+	fn test_providers_are_selected_by_name() {
+		register_secret_provider("mock", Arc::new(MockProvider(HashMap::new())));
+		assert!(secret_provider_named("mock").is_ok());
+		assert!(secret_provider_named(ENV_SECRET_PROVIDER).is_ok());
+		assert_eq!(
+			secret_provider_named("vault").err(),
+			Some(ConfigError::UnknownSecretProvider { name: "vault".to_string() })
+		);
+	}
+}
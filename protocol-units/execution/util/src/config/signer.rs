@@ -0,0 +1,573 @@
+//! The public identity of the configured signer.
+
+use super::Config;
+
+use anyhow::Context;
+use aptos_crypto::{
+	ed25519::{Ed25519PrivateKey, Ed25519PublicKey},
+	secp256k1_ecdsa, PrivateKey, ValidCryptoMaterialStringExt,
+};
+use aptos_types::{
+	account_address::AccountAddress,
+	transaction::authenticator::{AnyPublicKey, AuthenticationKey},
+};
+use serde::{Deserialize, Serialize};
+
+use std::fmt;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, PoisonError};
+
+/// The signature scheme of the node's private key.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum KeyScheme {
+	Ed25519,
+	Secp256k1,
+}
+
+impl KeyScheme {
+	pub const ACCEPTED: &'static [&'static str] = &["ed25519", "secp256k1"];
+
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			KeyScheme::Ed25519 => "ed25519",
+			KeyScheme::Secp256k1 => "secp256k1",
+		}
+	}
+}
+
+impl FromStr for KeyScheme {
+	type Err = anyhow::Error;
+
+	fn from_str(raw: &str) -> Result<Self, Self::Err> {
+		match raw.trim().to_ascii_lowercase().as_str() {
+			"ed25519" => Ok(KeyScheme::Ed25519),
+			"secp256k1" => Ok(KeyScheme::Secp256k1),
+			_ => anyhow::bail!(
+				"unknown key scheme {:?}, expected one of: {}",
+				raw,
+				Self::ACCEPTED.join(", ")
+			),
+		}
+	}
+}
+
+impl fmt::Display for KeyScheme {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		f.write_str(self.as_str())
+	}
+}
+
+/// What separates the scheme from the key in an encoded key such as `ed25519-priv-0x…`.
+const SCHEME_TAG_SEPARATOR: &str = "-priv-";
+
+/// A private key of either scheme the node signs with.
+pub enum SignerKey {
+	Ed25519(Ed25519PrivateKey),
+	Secp256k1(secp256k1_ecdsa::PrivateKey),
+}
+
+impl SignerKey {
+	/// Parses a hex encoded key of `scheme`, which may be tagged with its scheme as in
+	/// `secp256k1-priv-0x…`. A key tagged with another scheme is rejected.
+	pub fn parse(scheme: KeyScheme, encoded: &str) -> Result<Self, anyhow::Error> {
+		let encoded = match encoded.trim().split_once(SCHEME_TAG_SEPARATOR) {
+			Some((tag, key)) => {
+				let tagged: KeyScheme = tag.parse()?;
+				if tagged != scheme {
+					anyhow::bail!("the key is a {} key, but the key scheme is {}", tagged, scheme);
+				}
+				key
+			}
+			None => encoded.trim(),
+		};
+		Ok(match scheme {
+			KeyScheme::Ed25519 => SignerKey::Ed25519(
+				Ed25519PrivateKey::from_encoded_string(encoded)
+					.context("malformed ed25519 private key")?,
+			),
+			KeyScheme::Secp256k1 => SignerKey::Secp256k1(
+				secp256k1_ecdsa::PrivateKey::from_encoded_string(encoded)
+					.context("malformed secp256k1 private key")?,
+			),
+		})
+	}
+
+	/// Reads the 32 bytes of a key of `scheme`.
+	pub fn from_bytes(scheme: KeyScheme, bytes: &[u8]) -> Result<Self, anyhow::Error> {
+		Ok(match scheme {
+			KeyScheme::Ed25519 => SignerKey::Ed25519(
+				Ed25519PrivateKey::try_from(bytes).context("malformed ed25519 private key")?,
+			),
+			KeyScheme::Secp256k1 => SignerKey::Secp256k1(
+				secp256k1_ecdsa::PrivateKey::try_from(bytes)
+					.context("malformed secp256k1 private key")?,
+			),
+		})
+	}
+
+	pub fn scheme(&self) -> KeyScheme {
+		match self {
+			SignerKey::Ed25519(_) => KeyScheme::Ed25519,
+			SignerKey::Secp256k1(_) => KeyScheme::Secp256k1,
+		}
+	}
+
+	pub fn to_bytes(&self) -> [u8; 32] {
+		match self {
+			SignerKey::Ed25519(private_key) => private_key.to_bytes(),
+			SignerKey::Secp256k1(private_key) => private_key.to_bytes(),
+		}
+	}
+
+	pub fn public_key(&self) -> SignerPublicKey {
+		match self {
+			SignerKey::Ed25519(private_key) => SignerPublicKey::Ed25519(private_key.public_key()),
+			SignerKey::Secp256k1(private_key) => {
+				SignerPublicKey::Secp256k1(private_key.public_key())
+			}
+		}
+	}
+
+	/// The account address the key authenticates: an Ed25519 account, or a single key
+	/// account for secp256k1.
+	pub fn account_address(&self) -> AccountAddress {
+		match self {
+			SignerKey::Ed25519(private_key) => {
+				AuthenticationKey::ed25519(&private_key.public_key()).account_address()
+			}
+			SignerKey::Secp256k1(private_key) => {
+				AuthenticationKey::any_key(AnyPublicKey::secp256k1_ecdsa(private_key.public_key()))
+					.account_address()
+			}
+		}
+	}
+}
+
+impl fmt::Debug for SignerKey {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "SignerKey::{}(<redacted>)", self.scheme())
+	}
+}
+
+/// The public key of a [`SignerKey`], of the same scheme.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SignerPublicKey {
+	Ed25519(Ed25519PublicKey),
+	Secp256k1(secp256k1_ecdsa::PublicKey),
+}
+
+impl SignerPublicKey {
+	pub fn scheme(&self) -> KeyScheme {
+		match self {
+			SignerPublicKey::Ed25519(_) => KeyScheme::Ed25519,
+			SignerPublicKey::Secp256k1(_) => KeyScheme::Secp256k1,
+		}
+	}
+}
+
+impl fmt::Display for SignerPublicKey {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		match self {
+			SignerPublicKey::Ed25519(public_key) => public_key.fmt(f),
+			SignerPublicKey::Secp256k1(public_key) => public_key.fmt(f),
+		}
+	}
+}
+
+/// Parses an encoded key of `scheme` into the 32 bytes the config holds it as.
+pub(crate) fn decode_private_key(
+	encoded: &str,
+	scheme: KeyScheme,
+) -> Result<Ed25519PrivateKey, anyhow::Error> {
+	let bytes = SignerKey::parse(scheme, encoded)?.to_bytes();
+	Ok(Ed25519PrivateKey::try_from(bytes.as_slice())
+		.expect("any 32 bytes are an Ed25519 private key"))
+}
+
+/// Checks that `encoded` is an Ed25519 private key as `MAPTOS_PRIVATE_KEY` takes it,
+/// returning the public key it derives, without loading the rest of the config.
+pub fn validate_private_key(encoded: &str) -> Result<Ed25519PublicKey, anyhow::Error> {
+	let private_key =
+		decode_private_key(encoded, KeyScheme::Ed25519).context("malformed private key")?;
+	Ok(private_key.public_key())
+}
+
+/// The configured key read under its scheme, with the identity derived from it.
+///
+/// [`SignerKey`] redacts itself from `Debug`, so logging a config does not reveal the key.
+#[derive(Debug, Clone)]
+struct Signer {
+	key: Arc<SignerKey>,
+	public_key: SignerPublicKey,
+	account_address: AccountAddress,
+}
+
+/// The public key and account address derived from the configured private key.
+///
+/// The cache remembers which key it was derived from,
+/// so it stays correct if the private key is replaced after the first lookup.
+/// It holds no configuration of its own and always compares equal.
+#[derive(Debug, Default)]
+pub struct SignerCache(Mutex<Option<Signer>>);
+
+impl SignerCache {
+	/// The signer of `private_key` read under `scheme`, failing when its bytes are not a key
+	/// of the scheme.
+	fn get(
+		&self,
+		private_key: &Ed25519PrivateKey,
+		scheme: KeyScheme,
+	) -> Result<Signer, anyhow::Error> {
+		let private_key_bytes = private_key.to_bytes();
+		let mut cached = self.0.lock().unwrap_or_else(PoisonError::into_inner);
+		match cached.as_ref() {
+			Some(signer)
+				if signer.key.scheme() == scheme && signer.key.to_bytes() == private_key_bytes =>
+			{
+				Ok(signer.clone())
+			}
+			_ => {
+				let key = SignerKey::from_bytes(scheme, &private_key_bytes)
+					.with_context(|| format!("the private key is not a {} key", scheme))?;
+				let signer = Signer {
+					public_key: key.public_key(),
+					account_address: key.account_address(),
+					key: Arc::new(key),
+				};
+				*cached = Some(signer.clone());
+				Ok(signer)
+			}
+		}
+	}
+}
+
+impl Clone for SignerCache {
+	fn clone(&self) -> Self {
+		Self(Mutex::new(self.0.lock().unwrap_or_else(PoisonError::into_inner).clone()))
+	}
+}
+
+impl PartialEq for SignerCache {
+	fn eq(&self, _other: &Self) -> bool {
+		true
+	}
+}
+
+impl Eq for SignerCache {}
+
+/// The key the faucet funds from, with the identity derived from it.
+#[derive(Debug, Clone)]
+pub struct FaucetSigner {
+	pub private_key: Ed25519PrivateKey,
+	pub public_key: Ed25519PublicKey,
+	pub account_address: AccountAddress,
+}
+
+impl FaucetSigner {
+	fn new(private_key: &Ed25519PrivateKey) -> Self {
+		let public_key = private_key.public_key();
+		let account_address = AuthenticationKey::ed25519(&public_key).account_address();
+		FaucetSigner { private_key: private_key.clone(), public_key, account_address }
+	}
+}
+
+/// A faucet account with the sequence number of the next transaction it funds with.
+#[derive(Debug)]
+pub struct FaucetAccount {
+	pub signer: FaucetSigner,
+	sequence_number: AtomicU64,
+}
+
+impl FaucetAccount {
+	/// Sets the next sequence number, as read from the chain.
+	pub fn set_sequence_number(&self, sequence_number: u64) {
+		self.sequence_number.store(sequence_number, Ordering::SeqCst);
+	}
+
+	/// Takes the sequence number of the next transaction, advancing it.
+	pub fn next_sequence_number(&self) -> u64 {
+		self.sequence_number.fetch_add(1, Ordering::SeqCst)
+	}
+}
+
+/// The accounts the faucet funds from, taken in turn so that mints from different accounts
+/// proceed in parallel rather than queueing on one sequence number.
+#[derive(Debug)]
+pub struct FaucetSigners {
+	accounts: Vec<FaucetAccount>,
+	next: AtomicUsize,
+}
+
+impl FaucetSigners {
+	fn new(signers: Vec<FaucetSigner>) -> Self {
+		let accounts = signers
+			.into_iter()
+			.map(|signer| FaucetAccount { signer, sequence_number: AtomicU64::new(0) })
+			.collect();
+		Self { accounts, next: AtomicUsize::new(0) }
+	}
+
+	/// The account to fund the next request from, round-robin.
+	pub fn next(&self) -> &FaucetAccount {
+		let index = self.next.fetch_add(1, Ordering::Relaxed) % self.accounts.len();
+		&self.accounts[index]
+	}
+
+	pub fn len(&self) -> usize {
+		self.accounts.len()
+	}
+
+	/// Never true, as the faucet always has its own or the node's key.
+	pub fn is_empty(&self) -> bool {
+		self.accounts.is_empty()
+	}
+
+	pub fn iter(&self) -> impl Iterator<Item = &FaucetAccount> {
+		self.accounts.iter()
+	}
+}
+
+impl Config {
+	/// The faucet's own key when one is configured, otherwise the node's key.
+	pub fn faucet_signer(&self) -> FaucetSigner {
+		FaucetSigner::new(
+			self.faucet
+				.faucet_private_key
+				.as_ref()
+				.unwrap_or(&self.chain.maptos_private_key),
+		)
+	}
+
+	/// The accounts the faucet rotates across: those of `FAUCET_PRIVATE_KEYS` when it lists
+	/// any, otherwise the single account of [`Config::faucet_signer`].
+	pub fn faucet_signers(&self) -> FaucetSigners {
+		if self.faucet.faucet_private_keys.is_empty() {
+			return FaucetSigners::new(vec![self.faucet_signer()]);
+		}
+		FaucetSigners::new(self.faucet.faucet_private_keys.iter().map(FaucetSigner::new).collect())
+	}
+
+	/// The configured private key, read according to `MAPTOS_KEY_SCHEME`.
+	pub fn signer_key(&self) -> Result<SignerKey, anyhow::Error> {
+		SignerKey::from_bytes(
+			self.chain.maptos_key_scheme,
+			&self.chain.maptos_private_key.to_bytes(),
+		)
+	}
+
+	/// The public key of the configured signer under its key scheme.
+	///
+	/// Fails if the private key is not a key of the scheme.
+	pub fn signer_public_key(&self) -> Result<SignerPublicKey, anyhow::Error> {
+		Ok(self.signer()?.public_key)
+	}
+
+	/// The account address authenticated by the configured signer under its key scheme.
+	///
+	/// Fails if the private key is not a key of the scheme.
+	pub fn signer_account_address(&self) -> Result<AccountAddress, anyhow::Error> {
+		Ok(self.signer()?.account_address)
+	}
+
+	fn signer(&self) -> Result<Signer, anyhow::Error> {
+		self.chain
+			.signer
+			.get(&self.chain.maptos_private_key, self.chain.maptos_key_scheme)
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use aptos_crypto::Uniform;
+
+	use std::str::FromStr;
+
+	#[test]
+	fn test_known_key_yields_expected_address() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.chain.maptos_private_key = Ed25519PrivateKey::from_encoded_string(
+			"0x0000000000000000000000000000000000000000000000000000000000000001",
+		)?;
+
+		assert_eq!(
+			config.signer_public_key()?,
+			SignerPublicKey::Ed25519(Ed25519PublicKey::from_encoded_string(
+				"0x4cb5abf6ad79fbf5abbccafcc269d85cd2651ed4b885b5869f241aedf0a5ba29"
+			)?)
+		);
+		assert_eq!(
+			config.signer_account_address()?,
+			AccountAddress::from_str(
+				"0xf90391c81027f03cdea491ed8b36ffaced26b6df208a9b569e5baf2590eb9b16"
+			)?
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn test_faucet_rotates_across_its_keys() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::read_lock();
+		let keys: Vec<Ed25519PrivateKey> =
+			(0..3).map(|_| Ed25519PrivateKey::generate(&mut rand::thread_rng())).collect();
+		let encoded = keys
+			.iter()
+			.map(|key| key.to_encoded_string())
+			.collect::<Result<Vec<_>, _>>()?
+			.join(", ");
+		let mut config = Config::default();
+		config.faucet.faucet_private_keys =
+			super::super::common::parse_faucet_private_keys(&encoded)?;
+		assert_eq!(config.faucet.faucet_private_keys, keys);
+
+		let signers = config.faucet_signers();
+		assert_eq!(signers.len(), 3);
+		let picked: Vec<AccountAddress> =
+			(0..4).map(|_| signers.next().signer.account_address).collect();
+		let expected: Vec<AccountAddress> =
+			keys.iter().map(|key| FaucetSigner::new(key).account_address).collect();
+		assert_eq!(picked, [expected[0], expected[1], expected[2], expected[0]]);
+
+		// Each account counts its own sequence numbers.
+		let account = signers.next();
+		account.set_sequence_number(7);
+		assert_eq!(account.next_sequence_number(), 7);
+		assert_eq!(account.next_sequence_number(), 8);
+		assert_eq!(signers.next().next_sequence_number(), 0);
+		Ok(())
+	}
+
+	#[test]
+	fn test_faucet_signers_fall_back_to_the_single_key() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.faucet.faucet_private_keys = Vec::new();
+		let signers = config.faucet_signers();
+		assert_eq!(signers.len(), 1);
+		assert_eq!(signers.next().signer.account_address, config.faucet_signer().account_address);
+
+		let error = super::super::common::parse_faucet_private_keys(
+			"0x0000000000000000000000000000000000000000000000000000000000000001,0xnotakey",
+		)
+		.unwrap_err();
+		assert!(error.to_string().contains("index 1"), "{}", error);
+		assert!(!format!("{:#}", error).contains("notakey"), "{:#}", error);
+	}
+
+	#[test]
+	fn test_ed25519_keys_parse_under_the_default_scheme() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::read_lock();
+		assert_eq!(Config::default().chain.maptos_key_scheme, KeyScheme::Ed25519);
+		let encoded = "0x0000000000000000000000000000000000000000000000000000000000000001";
+		let key = SignerKey::parse(KeyScheme::Ed25519, encoded)?;
+		let tagged = SignerKey::parse(KeyScheme::Ed25519, &format!("ed25519-priv-{}", encoded))?;
+		assert_eq!(key.scheme(), KeyScheme::Ed25519);
+		assert_eq!(tagged.to_bytes(), key.to_bytes());
+		assert_eq!(
+			key.account_address(),
+			AccountAddress::from_str(
+				"0xf90391c81027f03cdea491ed8b36ffaced26b6df208a9b569e5baf2590eb9b16"
+			)?
+		);
+		Ok(())
+	}
+
+	#[test]
+	fn test_validated_keys_yield_their_public_key() -> Result<(), anyhow::Error> {
+		let public_key = validate_private_key(
+			"0x0000000000000000000000000000000000000000000000000000000000000001",
+		)?;
+		assert_eq!(
+			public_key.to_encoded_string()?,
+			"0x4cb5abf6ad79fbf5abbccafcc269d85cd2651ed4b885b5869f241aedf0a5ba29"
+		);
+
+		for malformed in ["", "0x01", "not a key"] {
+			let err = validate_private_key(malformed).unwrap_err();
+			assert!(err.to_string().contains("malformed private key"), "{}", err);
+		}
+		Ok(())
+	}
+
+	#[test]
+	fn test_keys_of_another_scheme_are_rejected() -> Result<(), anyhow::Error> {
+		let _env = godfig::env::read_lock();
+		let encoded = "0x0000000000000000000000000000000000000000000000000000000000000001";
+		let err = SignerKey::parse(KeyScheme::Ed25519, &format!("secp256k1-priv-{}", encoded))
+			.unwrap_err();
+		assert!(err.to_string().contains("secp256k1 key"), "unexpected error: {}", err);
+		assert!("rsa"
+			.parse::<KeyScheme>()
+			.unwrap_err()
+			.to_string()
+			.contains("ed25519, secp256k1"));
+
+		// The same bytes authenticate a different account under secp256k1.
+		let mut config = Config::default();
+		config.chain.maptos_private_key = Ed25519PrivateKey::from_encoded_string(encoded)?;
+		let ed25519_address = config.signer_account_address()?;
+		config.chain.maptos_key_scheme = KeyScheme::Secp256k1;
+		assert_eq!(config.signer_key()?.scheme(), KeyScheme::Secp256k1);
+		assert_ne!(config.signer_account_address()?, ed25519_address);
+		assert_eq!(config.signer_public_key()?.scheme(), KeyScheme::Secp256k1);
+
+		// Bytes past the order of the secp256k1 curve are no key of the scheme.
+		config.chain.maptos_private_key = Ed25519PrivateKey::from_encoded_string(
+			"0xffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+		)?;
+		let err = config.signer_account_address().unwrap_err();
+		assert!(err.to_string().contains("not a secp256k1 key"), "{}", err);
+		Ok(())
+	}
+
+	#[test]
+	fn test_faucet_signer_falls_back_to_the_node_key() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.faucet.faucet_private_key = None;
+		let signer = config.faucet_signer();
+		assert_eq!(signer.private_key, config.chain.maptos_private_key);
+		assert_eq!(signer.account_address, config.signer_account_address().unwrap());
+
+		let faucet_key = Ed25519PrivateKey::generate(&mut rand::thread_rng());
+		config.faucet.faucet_private_key = Some(faucet_key.clone());
+		let signer = config.faucet_signer();
+		assert_eq!(signer.private_key, faucet_key);
+		assert_eq!(signer.public_key, faucet_key.public_key());
+		assert_ne!(signer.account_address, config.signer_account_address().unwrap());
+	}
+
+	#[test]
+	fn test_cached_signer_does_not_debug_the_key() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.chain.maptos_private_key = Ed25519PrivateKey::from_encoded_string(
+			"0x00000000000000000000000000000000000000000000000000000000000000ff",
+		)
+		.unwrap();
+		config.signer_account_address().unwrap();
+		let debugged = format!("{:?}", config.chain.signer);
+		assert!(debugged.contains("<redacted>"), "{}", debugged);
+		assert!(!debugged.contains("0, 0, 0"), "{}", debugged);
+	}
+
+	#[test]
+	fn test_replaced_key_is_rederived() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		let first = config.signer_account_address().unwrap();
+
+		config.chain.maptos_private_key = Ed25519PrivateKey::generate(&mut rand::thread_rng());
+		let second = config.signer_account_address().unwrap();
+
+		assert_ne!(first, second);
+		assert_eq!(
+			second,
+			AuthenticationKey::ed25519(&config.chain.maptos_private_key.public_key())
+				.account_address()
+		);
+	}
+}
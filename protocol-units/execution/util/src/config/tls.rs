@@ -0,0 +1,144 @@
+//! TLS material referenced by the config.
+
+use anyhow::Context;
+use ring::signature::{self, KeyPair};
+
+use std::path::{Path, PathBuf};
+
+/// The certificate and private key files for serving a listener over TLS.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TlsFiles {
+	pub cert_path: PathBuf,
+	pub key_path: PathBuf,
+}
+
+/// The TLS setup of the indexer gRPC listener.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexerGrpcTls {
+	pub files: TlsFiles,
+	/// The CA which must sign the certificates of clients off loopback; without one, clients
+	/// are not authenticated.
+	pub client_ca_path: Option<PathBuf>,
+}
+
+impl IndexerGrpcTls {
+	/// Reads the client CA, checking that it holds at least one PEM certificate.
+	pub fn load_client_ca(&self) -> Result<Option<Vec<u8>>, anyhow::Error> {
+		let Some(path) = &self.client_ca_path else {
+			return Ok(None);
+		};
+		let ca = read(path)?;
+		let certs = rustls_pemfile::certs(&mut ca.as_slice())
+			.with_context(|| format!("malformed PEM certificate {}", path.display()))?;
+		if certs.is_empty() {
+			anyhow::bail!("no certificate found in {}", path.display());
+		}
+		Ok(Some(ca))
+	}
+}
+
+/// A PEM certificate chain and PKCS#8 private key which were checked to belong together.
+#[derive(Debug, Clone)]
+pub struct TlsPem {
+	pub cert: Vec<u8>,
+	pub key: Vec<u8>,
+}
+
+impl TlsFiles {
+	/// Reads both files and checks that the leaf certificate was issued for the private key.
+	pub fn load(&self) -> Result<TlsPem, anyhow::Error> {
+		let cert = read(&self.cert_path)?;
+		let key = read(&self.key_path)?;
+
+		let certs = rustls_pemfile::certs(&mut cert.as_slice())
+			.with_context(|| format!("malformed PEM certificate {}", self.cert_path.display()))?;
+		let leaf = certs
+			.first()
+			.with_context(|| format!("no certificate found in {}", self.cert_path.display()))?;
+		let keys = rustls_pemfile::pkcs8_private_keys(&mut key.as_slice())
+			.with_context(|| format!("malformed PEM private key {}", self.key_path.display()))?;
+		let private_key = keys.first().with_context(|| {
+			format!("no PKCS#8 private key found in {}", self.key_path.display())
+		})?;
+
+		let public_key = public_key_of(private_key)
+			.with_context(|| format!("unusable private key {}", self.key_path.display()))?;
+		if !leaf.windows(public_key.len()).any(|window| window == public_key.as_slice()) {
+			anyhow::bail!(
+				"the certificate {} does not match the private key {}",
+				self.cert_path.display(),
+				self.key_path.display()
+			);
+		}
+
+		Ok(TlsPem { cert, key })
+	}
+}
+
+fn read(path: &Path) -> Result<Vec<u8>, anyhow::Error> {
+	std::fs::read(path).with_context(|| format!("failed to read {}", path.display()))
+}
+
+/// Derives the public key of a PKCS#8 private key, encoded as in a certificate's key info.
+fn public_key_of(pkcs8: &[u8]) -> Result<Vec<u8>, anyhow::Error> {
+	if let Ok(key_pair) = signature::Ed25519KeyPair::from_pkcs8_maybe_unchecked(pkcs8) {
+		return Ok(key_pair.public_key().as_ref().to_vec());
+	}
+	let rng = ring::rand::SystemRandom::new();
+	for algorithm in
+		[&signature::ECDSA_P256_SHA256_ASN1_SIGNING, &signature::ECDSA_P384_SHA384_ASN1_SIGNING]
+	{
+		if let Ok(key_pair) = signature::EcdsaKeyPair::from_pkcs8(algorithm, pkcs8, &rng) {
+			return Ok(key_pair.public_key().as_ref().to_vec());
+		}
+	}
+	let key_pair = signature::RsaKeyPair::from_pkcs8(pkcs8)
+		.map_err(|e| anyhow::anyhow!("unsupported key type: {}", e))?;
+	Ok(key_pair.public_key().as_ref().to_vec())
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	fn testdata(name: &str) -> PathBuf {
+		Path::new(env!("CARGO_MANIFEST_DIR")).join("testdata").join(name)
+	}
+
+	#[test]
+	fn test_matching_pair_loads() -> Result<(), anyhow::Error> {
+		let files =
+			TlsFiles { cert_path: testdata("server.crt"), key_path: testdata("server.key") };
+		files.load()?;
+		Ok(())
+	}
+
+	#[test]
+	fn test_mismatched_pair_is_rejected() {
+		let files = TlsFiles { cert_path: testdata("server.crt"), key_path: testdata("other.key") };
+		let err = files.load().unwrap_err();
+		assert!(err.to_string().contains("does not match"));
+	}
+
+	#[test]
+	fn test_client_ca_needs_a_certificate() -> Result<(), anyhow::Error> {
+		let mut tls = IndexerGrpcTls {
+			files: TlsFiles { cert_path: testdata("server.crt"), key_path: testdata("server.key") },
+			client_ca_path: Some(testdata("server.crt")),
+		};
+		assert!(tls.load_client_ca()?.is_some());
+
+		tls.client_ca_path = Some(testdata("server.key"));
+		let err = tls.load_client_ca().unwrap_err();
+		assert!(err.to_string().contains("no certificate found"));
+		Ok(())
+	}
+
+	#[test]
+	fn test_missing_file_names_path() {
+		let files =
+			TlsFiles { cert_path: testdata("missing.crt"), key_path: testdata("server.key") };
+		let err = files.load().unwrap_err();
+		assert!(err.to_string().contains("missing.crt"));
+	}
+}
@@ -0,0 +1,1824 @@
+//! Consistency checks over a fully resolved [`Config`].
+
+use super::{
+	commit_batch::FsyncPolicy,
+	common,
+	duration::DurationSecs,
+	endpoints::{is_unix_socket_hostname, listen_hostnames, normalize_base_path},
+	error::ConfigError,
+	production::is_production_profile,
+	provenance::Provenance,
+	signer::KeyScheme,
+	Config,
+};
+
+use std::net::IpAddr;
+use std::path::PathBuf;
+
+/// The prune windows, which are ignored when pruning is disabled.
+const PRUNE_WINDOW_VARS: &[&str] = &[
+	"MAPTOS_LEDGER_PRUNING_WINDOW",
+	"MAPTOS_STATE_MERKLE_PRUNING_WINDOW",
+	"MAPTOS_EPOCH_SNAPSHOT_PRUNING_WINDOW",
+];
+
+/// A bound address as seen by the validator, named by the env vars that configure it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Listener {
+	pub hostname_var: &'static str,
+	pub port_var: &'static str,
+	pub hostname: String,
+	pub port: u16,
+}
+
+/// A client-side endpoint expected to reach one of the [`Listener`]s.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Connection {
+	pub hostname_var: &'static str,
+	pub port_var: &'static str,
+	pub hostname: String,
+	pub port: u16,
+	/// The port env var of the listener this connection targets.
+	pub listener_port_var: &'static str,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ValidationError {
+	#[error("port {port} is bound on overlapping addresses by {}", .fields.join(", "))]
+	PortCollision { port: u16, fields: Vec<&'static str> },
+
+	#[error(
+		"{connection_var}={connection_port} cannot reach {listen_var}={listen_port} over loopback"
+	)]
+	LoopbackPortMismatch {
+		listen_var: &'static str,
+		listen_port: u16,
+		connection_var: &'static str,
+		connection_port: u16,
+	},
+
+	#[error("{set_var} is set but {missing_var} is not; TLS needs both a certificate and a key")]
+	TlsHalfConfigured { set_var: &'static str, missing_var: &'static str },
+
+	#[error("{set_var} is set but {missing_var} is not; client authentication needs TLS")]
+	ClientCaWithoutTls { set_var: &'static str, missing_var: &'static str },
+
+	#[error("{var}={value} is out of range, expected {expected}")]
+	OutOfRange { var: &'static str, value: String, expected: &'static str },
+
+	#[error(
+		"MAPTOS_INDEXER_GRPC_PING_INTERVAL_SEC={ping_interval} must be at most half of \
+		MAPTOS_INDEXER_GRPC_INACTIVITY_TIMEOUT_SEC={inactivity_timeout}"
+	)]
+	PingIntervalTooLong { ping_interval: DurationSecs, inactivity_timeout: DurationSecs },
+
+	#[error(
+		"{hostname_var} names a Unix domain socket, so {port_var}={port} has no effect; \
+		leave it at its default or set it to 0"
+	)]
+	UnixSocketWithPort { hostname_var: &'static str, port_var: &'static str, port: u16 },
+
+	#[error("{var}={window} exceeds MAPTOS_LEDGER_PRUNING_WINDOW={ledger_window}")]
+	PruneWindowExceedsLedger { var: &'static str, window: u64, ledger_window: u64 },
+
+	#[error(
+		"INDEXER_PROCESSOR_ENDING_VERSION={ending} is below \
+		INDEXER_PROCESSOR_STARTING_VERSION={starting}"
+	)]
+	EndingVersionBeforeStarting { starting: u64, ending: u64 },
+
+	#[error("{var}={} does not exist", .path.display())]
+	MissingPath { var: &'static str, path: PathBuf },
+
+	#[error(
+		"{var}={value:?} must be empty or a path starting with /, without a query or fragment"
+	)]
+	InvalidBasePath { var: &'static str, value: String },
+
+	#[error("{var}={value:?} must be empty or an http or https URL such as http://localhost:4317")]
+	InvalidOtlpEndpoint { var: &'static str, value: String },
+
+	#[error("MAPTOS_PRIVATE_KEY is not a {scheme} key, as MAPTOS_KEY_SCHEME={scheme} requires")]
+	KeyNotOfScheme { scheme: KeyScheme },
+
+	/// A value which could not be read, found by [`Config::load_strict`] or
+	/// [`Config::finish_loading`].
+	#[error("{0}")]
+	Load(ConfigError),
+}
+
+/// A setting which is accepted but likely to misbehave.
+#[derive(Debug, Clone, PartialEq, Eq, thiserror::Error)]
+pub enum ValidationWarning {
+	#[error(
+		"MAPTOS_STATE_MERKLE_PRUNING_WINDOW={window} is below {recommended}, \
+		the recommended minimum for MAPTOS_MAX_TRANSACTIONS_IN_FLIGHT={in_flight}; \
+		state may be pruned while transactions that read it are still in flight"
+	)]
+	StateMerkleWindowBelowInFlight { window: u64, in_flight: u64, recommended: u64 },
+
+	#[error(
+		"MAPTOS_READ_ONLY=true keeps the faucet from starting, so {} has no effect",
+		.vars.join(", ")
+	)]
+	FaucetSettingsOnReadOnlyNode { vars: Vec<&'static str> },
+
+	#[error(
+		"MAPTOS_ENABLE_PRUNING=false keeps every version, so {} has no effect",
+		.vars.join(", ")
+	)]
+	PruneWindowsIgnored { vars: Vec<&'static str> },
+
+	#[error(
+		"{hostname_var}={hostname} exposes the {surface} on every interface \
+		with neither TLS nor authentication configured"
+	)]
+	UnprotectedPublicBind { surface: &'static str, hostname_var: &'static str, hostname: String },
+
+	#[error(
+		"FAUCET_API_LISTEN_HOSTNAME={hostname} exposes the faucet on every interface \
+		with neither FAUCET_RATE_LIMIT_PER_IP_PER_HOUR nor FAUCET_COOLDOWN_SEC set, \
+		so any client may drain it"
+	)]
+	PublicFaucetWithoutRateLimit { hostname: String },
+
+	#[error(
+		"MAPTOS_FSYNC_POLICY=never under MAPTOS_PROFILE=production never syncs the DA DB to \
+		disk; a crash of the host may lose which blocks were executed, and replay them"
+	)]
+	FsyncNeverInProduction,
+}
+
+/// How much a [`ValidationIssue`] matters.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+	/// The config cannot be brought up as it is.
+	Error,
+	/// The config works but is likely to misbehave.
+	Warning,
+}
+
+/// A single problem found by [`Config::validate_all`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValidationIssue {
+	Error(ValidationError),
+	Warning(ValidationWarning),
+}
+
+impl ValidationIssue {
+	pub fn severity(&self) -> Severity {
+		match self {
+			ValidationIssue::Error(_) => Severity::Error,
+			ValidationIssue::Warning(_) => Severity::Warning,
+		}
+	}
+}
+
+impl std::fmt::Display for ValidationIssue {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		match self {
+			ValidationIssue::Error(error) => write!(f, "error: {}", error),
+			ValidationIssue::Warning(warning) => write!(f, "warning: {}", warning),
+		}
+	}
+}
+
+/// Every problem found in a config, errors first, in the order the checks ran.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ValidationReport {
+	pub issues: Vec<ValidationIssue>,
+}
+
+impl ValidationReport {
+	/// Whether any issue should stop the node from starting.
+	pub fn has_errors(&self) -> bool {
+		self.issues.iter().any(|issue| issue.severity() == Severity::Error)
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.issues.is_empty()
+	}
+}
+
+/// Lists one issue per line.
+impl std::fmt::Display for ValidationReport {
+	fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+		for issue in &self.issues {
+			writeln!(f, "{}", issue)?;
+		}
+		Ok(())
+	}
+}
+
+/// Whether the hostname is an unspecified (wildcard) bind address.
+pub(crate) fn is_unspecified(hostname: &str) -> bool {
+	hostname.parse::<IpAddr>().map(|ip| ip.is_unspecified()).unwrap_or(false)
+}
+
+/// Whether connecting to the hostname always lands on the local machine.
+///
+/// Connecting to the unspecified address is routed to loopback on the platforms we run on.
+fn is_local(hostname: &str) -> bool {
+	hostname.eq_ignore_ascii_case("localhost")
+		|| hostname
+			.parse::<IpAddr>()
+			.map(|ip| ip.is_loopback() || ip.is_unspecified())
+			.unwrap_or(false)
+}
+
+/// The accepted gRPC message size limits: gRPC encodes message lengths in 32 bits,
+/// and a limit below a megabyte would reject ordinary transaction batches.
+pub(crate) const GRPC_MAX_MESSAGE_BYTES_RANGE: std::ops::RangeInclusive<u64> =
+	(1 << 20)..=(2 << 30);
+
+/// The listeners which may be bound to a Unix domain socket instead of a TCP port.
+const UNIX_SOCKET_HOSTNAME_VARS: &[&str] =
+	&["MAPTOS_API_LISTEN_HOSTNAME", "MAPTOS_FIN_VIEW_API_LISTEN_HOSTNAME"];
+
+/// Whether the port is one a user chose, rather than the built-in default or 0.
+fn is_meaningful_port(port_var: &str, port: u16) -> bool {
+	let is_default = common::config_defaults()
+		.iter()
+		.any(|default| default.env_var == port_var && default.default_display == port.to_string());
+	port != 0 && !is_default
+}
+
+/// Whether two bind hostnames can claim the same port on the same interface,
+/// comparing each hostname either of them lists.
+fn overlaps(a: &str, b: &str) -> bool {
+	listen_hostnames(a).into_iter().any(|a| {
+		listen_hostnames(b)
+			.into_iter()
+			.any(|b| is_unspecified(a) || is_unspecified(b) || a.eq_ignore_ascii_case(b))
+	})
+}
+
+impl Config {
+	/// Every address the node binds, in startup order; disabled services bind nothing.
+	pub fn listeners(&self) -> Vec<Listener> {
+		[
+			(
+				self.rest_enabled(),
+				Listener {
+					hostname_var: "MAPTOS_API_LISTEN_HOSTNAME",
+					port_var: "MAPTOS_API_LISTEN_PORT",
+					hostname: self.chain.maptos_rest_listen_hostname.clone(),
+					port: self.chain.maptos_rest_listen_port,
+				},
+			),
+			(
+				self.faucet_enabled(),
+				Listener {
+					hostname_var: "FAUCET_API_LISTEN_HOSTNAME",
+					port_var: "FAUCET_API_LISTEN_PORT",
+					hostname: self.faucet.maptos_faucet_rest_listen_hostname.clone(),
+					port: self.faucet.maptos_faucet_rest_listen_port,
+				},
+			),
+			(
+				self.fin_view_enabled(),
+				Listener {
+					hostname_var: "MAPTOS_FIN_VIEW_API_LISTEN_HOSTNAME",
+					port_var: "MAPTOS_FIN_VIEW_API_LISTEN_PORT",
+					hostname: self.fin.fin_rest_listen_hostname.clone(),
+					port: self.fin.fin_rest_listen_port,
+				},
+			),
+			(
+				self.indexer_grpc_enabled(),
+				Listener {
+					hostname_var: "MAPTOS_INDEXER_GRPC_LISTEN_HOSTNAME",
+					port_var: "MAPTOS_INDEXER_GRPC_LISTEN_PORT",
+					hostname: self.indexer.maptos_indexer_grpc_listen_hostname.clone(),
+					port: self.indexer.maptos_indexer_grpc_listen_port,
+				},
+			),
+			(
+				self.health_enabled(),
+				Listener {
+					hostname_var: "MAPTOS_HEALTH_LISTEN_HOSTNAME",
+					port_var: "MAPTOS_HEALTH_LISTEN_PORT",
+					hostname: self.health.maptos_health_listen_hostname.clone(),
+					port: self.health.maptos_health_listen_port,
+				},
+			),
+			(
+				self.metrics_enabled(),
+				Listener {
+					hostname_var: "MAPTOS_METRICS_LISTEN_HOSTNAME",
+					port_var: "MAPTOS_METRICS_LISTEN_PORT",
+					hostname: self.metrics.maptos_metrics_listen_hostname.clone(),
+					port: self.metrics.maptos_metrics_listen_port,
+				},
+			),
+		]
+		.into_iter()
+		.filter_map(|(enabled, listener)| enabled.then_some(listener))
+		.collect()
+	}
+
+	/// Every client-side endpoint pointing back at one of the [`Config::listeners`].
+	pub fn connections(&self) -> Vec<Connection> {
+		vec![
+			Connection {
+				hostname_var: "MAPTOS_API_CONNECTION_HOSTNAME",
+				port_var: "MAPTOS_API_CONNECTION_PORT",
+				hostname: self.client.maptos_rest_connection_hostname.clone(),
+				port: self.client.maptos_rest_connection_port,
+				listener_port_var: "MAPTOS_API_LISTEN_PORT",
+			},
+			Connection {
+				hostname_var: "MAPTOS_API_CONNECTION_HOSTNAME",
+				port_var: "MAPTOS_API_CONNECTION_PORT",
+				hostname: self.faucet.maptos_rest_connection_hostname.clone(),
+				port: self.faucet.maptos_rest_connection_port,
+				listener_port_var: "MAPTOS_API_LISTEN_PORT",
+			},
+			Connection {
+				hostname_var: "FAUCET_API_CONNECTION_HOSTNAME",
+				port_var: "FAUCET_API_CONNECTION_PORT",
+				hostname: self.client.maptos_faucet_rest_connection_hostname.clone(),
+				port: self.client.maptos_faucet_rest_connection_port,
+				listener_port_var: "FAUCET_API_LISTEN_PORT",
+			},
+			Connection {
+				hostname_var: "MAPTOS_INDEXER_GRPC_CONNECTION_HOSTNAME",
+				port_var: "MAPTOS_INDEXER_GRPC_CONNECTION_PORT",
+				hostname: self.client.maptos_indexer_grpc_connection_hostname.clone(),
+				port: self.client.maptos_indexer_grpc_connection_port,
+				listener_port_var: "MAPTOS_INDEXER_GRPC_LISTEN_PORT",
+			},
+			Connection {
+				hostname_var: "MAPTOS_FIN_VIEW_API_CONNECTION_HOSTNAME",
+				port_var: "MAPTOS_FIN_VIEW_API_CONNECTION_PORT",
+				hostname: self.client.fin_rest_connection_hostname.clone(),
+				port: self.client.fin_rest_connection_port,
+				listener_port_var: "MAPTOS_FIN_VIEW_API_LISTEN_PORT",
+			},
+		]
+	}
+
+	/// Checks that the config can actually be brought up on a single host.
+	///
+	/// Two listeners collide when they share a port and their hostnames overlap,
+	/// where the unspecified address overlaps with every hostname.
+	/// A connection to a local hostname must use the port its listener binds.
+	/// Only the REST and fin view servers may listen on a Unix domain socket,
+	/// and their port must then be left at its default or set to 0.
+	/// The state merkle and epoch snapshot prune windows must not exceed the ledger prune window.
+	///
+	/// Returns the warnings for settings that are accepted but likely to misbehave.
+	pub fn validate(&self) -> Result<Vec<ValidationWarning>, ValidationError> {
+		let mut warnings = Vec::new();
+		for issue in self.validate_all().issues {
+			match issue {
+				ValidationIssue::Error(error) => return Err(error),
+				ValidationIssue::Warning(warning) => warnings.push(warning),
+			}
+		}
+		Ok(warnings)
+	}
+
+	/// Runs every check of [`Config::validate`], reporting all problems instead of the first.
+	pub fn validate_all(&self) -> ValidationReport {
+		let mut errors = Vec::new();
+		let mut warnings = Vec::new();
+		self.check(&mut errors, &mut warnings);
+		ValidationReport {
+			issues: errors
+				.into_iter()
+				.map(ValidationIssue::Error)
+				.chain(warnings.into_iter().map(ValidationIssue::Warning))
+				.collect(),
+		}
+	}
+
+	fn check(&self, errors: &mut Vec<ValidationError>, warnings: &mut Vec<ValidationWarning>) {
+		let all_listeners = self.listeners();
+		for listener in &all_listeners {
+			if !is_unix_socket_hostname(&listener.hostname) {
+				continue;
+			}
+			if !UNIX_SOCKET_HOSTNAME_VARS.contains(&listener.hostname_var) {
+				errors.push(ValidationError::OutOfRange {
+					var: listener.hostname_var,
+					value: listener.hostname.clone(),
+					expected: "a TCP hostname",
+				});
+			}
+			if is_meaningful_port(listener.port_var, listener.port) {
+				errors.push(ValidationError::UnixSocketWithPort {
+					hostname_var: listener.hostname_var,
+					port_var: listener.port_var,
+					port: listener.port,
+				});
+			}
+		}
+
+		// Unix domain socket listeners bind no port,
+		// and those on port 0 are given a free one of their own when they bind.
+		let listeners: Vec<Listener> = all_listeners
+			.into_iter()
+			.filter(|listener| !is_unix_socket_hostname(&listener.hostname) && listener.port != 0)
+			.collect();
+		// A listener already reported in a collision is not reported again with the rest.
+		let mut collided: Vec<&'static str> = Vec::new();
+		for (i, listener) in listeners.iter().enumerate() {
+			if collided.contains(&listener.port_var) {
+				continue;
+			}
+			let mut fields = vec![listener.port_var];
+			fields.extend(
+				listeners[i + 1..]
+					.iter()
+					.filter(|other| {
+						other.port == listener.port && overlaps(&listener.hostname, &other.hostname)
+					})
+					.map(|other| other.port_var),
+			);
+			if fields.len() > 1 {
+				collided.extend(&fields);
+				errors.push(ValidationError::PortCollision { port: listener.port, fields });
+			}
+		}
+
+		// Only a loopback connection must use its listen port; any other may be mapped to a port
+		// of its own, as behind NAT.
+		for connection in self.connections() {
+			if !is_local(&connection.hostname) {
+				continue;
+			}
+			let Some(listener) = listeners
+				.iter()
+				.find(|listener| listener.port_var == connection.listener_port_var)
+			else {
+				continue;
+			};
+			if connection.port != listener.port {
+				errors.push(ValidationError::LoopbackPortMismatch {
+					listen_var: listener.port_var,
+					listen_port: listener.port,
+					connection_var: connection.port_var,
+					connection_port: connection.port,
+				});
+			}
+		}
+
+		match (
+			self.chain.maptos_rest_tls_cert_path.is_empty(),
+			self.chain.maptos_rest_tls_key_path.is_empty(),
+		) {
+			(false, true) => errors.push(ValidationError::TlsHalfConfigured {
+				set_var: "MAPTOS_API_TLS_CERT",
+				missing_var: "MAPTOS_API_TLS_KEY",
+			}),
+			(true, false) => errors.push(ValidationError::TlsHalfConfigured {
+				set_var: "MAPTOS_API_TLS_KEY",
+				missing_var: "MAPTOS_API_TLS_CERT",
+			}),
+			_ => {}
+		}
+
+		match (
+			self.indexer.maptos_indexer_grpc_tls_cert.is_empty(),
+			self.indexer.maptos_indexer_grpc_tls_key.is_empty(),
+		) {
+			(false, true) => errors.push(ValidationError::TlsHalfConfigured {
+				set_var: "MAPTOS_INDEXER_GRPC_TLS_CERT",
+				missing_var: "MAPTOS_INDEXER_GRPC_TLS_KEY",
+			}),
+			(true, false) => errors.push(ValidationError::TlsHalfConfigured {
+				set_var: "MAPTOS_INDEXER_GRPC_TLS_KEY",
+				missing_var: "MAPTOS_INDEXER_GRPC_TLS_CERT",
+			}),
+			(true, true) if !self.indexer.maptos_indexer_grpc_client_ca.is_empty() => {
+				errors.push(ValidationError::ClientCaWithoutTls {
+					set_var: "MAPTOS_INDEXER_GRPC_CLIENT_CA",
+					missing_var: "MAPTOS_INDEXER_GRPC_TLS_CERT",
+				})
+			}
+			_ => {}
+		}
+
+		// Leave room for at least one missed ping before the connection is considered idle.
+		if self.indexer.ping_interval() * 2 > self.indexer.inactivity_timeout() {
+			errors.push(ValidationError::PingIntervalTooLong {
+				ping_interval: self.indexer.maptos_indexer_grpc_inactivity_ping_interval,
+				inactivity_timeout: self.indexer.maptos_indexer_grpc_inactivity_timeout,
+			});
+		}
+
+		// The windows are ignored when nothing is pruned.
+		let ledger_window = self.chain.maptos_ledger_prune_window;
+		let windows = [
+			("MAPTOS_STATE_MERKLE_PRUNING_WINDOW", self.chain.maptos_state_merkle_prune_window),
+			("MAPTOS_EPOCH_SNAPSHOT_PRUNING_WINDOW", self.chain.maptos_epoch_snapshot_prune_window),
+		];
+		for (var, window) in windows {
+			if self.chain.pruning_enabled() && window > ledger_window {
+				errors.push(ValidationError::PruneWindowExceedsLedger {
+					var,
+					window,
+					ledger_window,
+				});
+			}
+		}
+
+		for (var, timeout) in [
+			("MAPTOS_API_REQUEST_TIMEOUT_SEC", self.chain.maptos_rest_request_timeout_sec),
+			("MAPTOS_FIN_VIEW_API_REQUEST_TIMEOUT_SEC", self.fin.fin_rest_request_timeout_sec),
+			("FAUCET_TXN_EXPIRATION_SEC", self.faucet.faucet_txn_expiration_sec),
+			("MAPTOS_MAX_TXN_FUTURE_SEC", self.chain.maptos_max_txn_future_sec),
+			(
+				"MAPTOS_INDEXER_GRPC_INACTIVITY_TIMEOUT_SEC",
+				self.indexer.maptos_indexer_grpc_inactivity_timeout,
+			),
+			(
+				"INDEXER_PROCESSOR_POSTGRES_CONNECTION_TIMEOUT_SEC",
+				self.indexer_processor.postgres_connection_timeout_sec,
+			),
+		] {
+			if timeout.is_zero() {
+				errors.push(ValidationError::OutOfRange {
+					var,
+					value: timeout.to_string(),
+					expected: "more than 0",
+				});
+			}
+		}
+
+		for (var, max_body_bytes) in [
+			("MAPTOS_API_MAX_BODY_BYTES", self.chain.maptos_rest_max_body_bytes),
+			("FAUCET_API_MAX_BODY_BYTES", self.faucet.maptos_faucet_rest_max_body_bytes),
+		] {
+			if max_body_bytes == 0 {
+				errors.push(ValidationError::OutOfRange {
+					var,
+					value: max_body_bytes.to_string(),
+					expected: "at least 1",
+				});
+			}
+		}
+
+		for (var, bytes) in [
+			("MAPTOS_API_MAX_GAS_AMOUNT", self.chain.maptos_rest_max_gas_amount),
+			("MAPTOS_API_MAX_TRANSACTION_BYTES", self.chain.maptos_rest_max_transaction_bytes),
+			("MAPTOS_DB_BLOCK_CACHE_BYTES", self.chain.maptos_db_block_cache_bytes),
+			("MAPTOS_DB_MAX_WAL_BYTES", self.chain.maptos_db_max_wal_bytes),
+		] {
+			if bytes == 0 {
+				errors.push(ValidationError::OutOfRange {
+					var,
+					value: bytes.to_string(),
+					expected: "at least 1",
+				});
+			}
+		}
+
+		let max_page_size = self.chain.maptos_rest_max_page_size;
+		if !(1..=u32::from(u16::MAX)).contains(&max_page_size) {
+			errors.push(ValidationError::OutOfRange {
+				var: "MAPTOS_API_MAX_PAGE_SIZE",
+				value: max_page_size.to_string(),
+				expected: "between 1 and 65535",
+			});
+		}
+
+		let gas_unit_price = self.chain.maptos_gas_unit_price;
+		if gas_unit_price > 0 && gas_unit_price < common::MIN_GAS_UNIT_PRICE {
+			errors.push(ValidationError::OutOfRange {
+				var: "MAPTOS_GAS_UNIT_PRICE",
+				value: gas_unit_price.to_string(),
+				expected: "0 or at least 100",
+			});
+		}
+
+		for (var, count) in [
+			(
+				"MAPTOS_INDEXER_GRPC_SEND_BUFFER_SIZE",
+				self.indexer.maptos_indexer_grpc_send_buffer_size,
+			),
+			("MAPTOS_API_MAX_EVENTS_PER_RESPONSE", self.chain.maptos_rest_max_events_per_response),
+		] {
+			if count == 0 {
+				errors.push(ValidationError::OutOfRange {
+					var,
+					value: count.to_string(),
+					expected: "at least 1",
+				});
+			}
+		}
+
+		for (var, window) in [
+			("MAPTOS_PRELOAD_VERSIONS", self.chain.maptos_preload_versions),
+			("MAPTOS_LEDGER_PRUNING_WINDOW", self.chain.maptos_ledger_prune_window),
+			("MAPTOS_STATE_MERKLE_PRUNING_WINDOW", self.chain.maptos_state_merkle_prune_window),
+			("MAPTOS_EPOCH_SNAPSHOT_PRUNING_WINDOW", self.chain.maptos_epoch_snapshot_prune_window),
+		] {
+			if window == 0 {
+				errors.push(ValidationError::OutOfRange {
+					var,
+					value: window.to_string(),
+					expected: "at least 1 version",
+				});
+			}
+		}
+
+		// A connection to a Unix domain socket has no port to check.
+		for connection in self.connections() {
+			if connection.port == 0 && !is_unix_socket_hostname(&connection.hostname) {
+				errors.push(ValidationError::OutOfRange {
+					var: connection.port_var,
+					value: connection.port.to_string(),
+					expected: "between 1 and 65535",
+				});
+			}
+		}
+
+		if self.chain.maptos_commit_batch_size == 0 {
+			errors.push(ValidationError::OutOfRange {
+				var: "MAPTOS_COMMIT_BATCH_SIZE",
+				value: self.chain.maptos_commit_batch_size.to_string(),
+				expected: "at least 1",
+			});
+		}
+
+		if self.signer_key().is_err() {
+			errors.push(ValidationError::KeyNotOfScheme { scheme: self.chain.maptos_key_scheme });
+		}
+
+		for (var, base_path) in [
+			("MAPTOS_API_BASE_PATH", &self.chain.maptos_rest_base_path),
+			("FAUCET_API_BASE_PATH", &self.faucet.maptos_faucet_rest_base_path),
+		] {
+			if normalize_base_path(base_path).is_none() {
+				errors.push(ValidationError::InvalidBasePath { var, value: base_path.clone() });
+			}
+		}
+
+		let log_sample_rate = self.chain.maptos_rest_log_sample_rate;
+		if !log_sample_rate.is_valid() {
+			errors.push(ValidationError::OutOfRange {
+				var: "MAPTOS_API_LOG_SAMPLE_RATE",
+				value: log_sample_rate.to_string(),
+				expected: "between 0.0 and 1.0",
+			});
+		}
+
+		if let Some(otlp) = self.otlp() {
+			if !otlp.endpoint_is_valid() {
+				errors.push(ValidationError::InvalidOtlpEndpoint {
+					var: "MAPTOS_OTLP_ENDPOINT",
+					value: otlp.endpoint,
+				});
+			}
+		}
+		let otlp_sample_rate = self.logging.maptos_otlp_sample_rate;
+		if !otlp_sample_rate.is_valid() {
+			errors.push(ValidationError::OutOfRange {
+				var: "MAPTOS_OTLP_SAMPLE_RATE",
+				value: otlp_sample_rate.to_string(),
+				expected: "between 0.0 and 1.0",
+			});
+		}
+
+		for (var, worker_threads) in [
+			("MAPTOS_API_WORKER_THREADS", self.chain.maptos_rest_worker_threads),
+			("MAPTOS_FIN_VIEW_API_WORKER_THREADS", self.fin.fin_rest_worker_threads),
+			("MAPTOS_INDEXER_WORKER_THREADS", self.indexer.maptos_indexer_worker_threads),
+		] {
+			if worker_threads > common::MAX_WORKER_THREADS {
+				errors.push(ValidationError::OutOfRange {
+					var,
+					value: worker_threads.to_string(),
+					expected: "at most 1024",
+				});
+			}
+		}
+
+		let execution_concurrency = self.chain.maptos_execution_concurrency;
+		if execution_concurrency > common::MAX_EXECUTION_CONCURRENCY {
+			errors.push(ValidationError::OutOfRange {
+				var: "MAPTOS_EXECUTION_CONCURRENCY",
+				value: execution_concurrency.to_string(),
+				expected: "at most 256",
+			});
+		}
+
+		let max_message_bytes = self.indexer.maptos_indexer_grpc_max_message_bytes;
+		if !GRPC_MAX_MESSAGE_BYTES_RANGE.contains(&max_message_bytes) {
+			errors.push(ValidationError::OutOfRange {
+				var: "MAPTOS_INDEXER_GRPC_MAX_MESSAGE_BYTES",
+				value: max_message_bytes.to_string(),
+				expected: "between 1 MiB and 2 GiB",
+			});
+		}
+
+		let batch_size = self.indexer.maptos_indexer_grpc_batch_size;
+		if !(1..=common::MAX_INDEXER_GRPC_BATCH_SIZE).contains(&batch_size) {
+			errors.push(ValidationError::OutOfRange {
+				var: "MAPTOS_INDEXER_GRPC_BATCH_SIZE",
+				value: batch_size.to_string(),
+				expected: "between 1 and 10000",
+			});
+		}
+
+		if let (Some(starting), Some(ending)) =
+			(self.indexer_processor.starting_version(), self.indexer_processor.ending_version())
+		{
+			if ending < starting {
+				errors.push(ValidationError::EndingVersionBeforeStarting { starting, ending });
+			}
+		}
+
+		for (var, path) in [
+			("MAPTOS_GENESIS_BLOB_PATH", &self.chain.maptos_genesis_blob_path),
+			("MAPTOS_FRAMEWORK_PATH", &self.chain.maptos_framework_path),
+		] {
+			if let Some(path) = path.as_ref().filter(|path| !path.exists()) {
+				errors.push(ValidationError::MissingPath { var, path: path.clone() });
+			}
+		}
+
+		if self.indexer_processor.postgres_max_connections < 1 {
+			errors.push(ValidationError::OutOfRange {
+				var: "INDEXER_PROCESSOR_POSTGRES_MAX_CONNECTIONS",
+				value: self.indexer_processor.postgres_max_connections.to_string(),
+				expected: "at least 1",
+			});
+		}
+
+		if self.is_read_only() {
+			let faucet = &self.faucet;
+			let vars: Vec<&'static str> = [
+				(
+					"FAUCET_RATE_LIMIT_PER_IP_PER_HOUR",
+					faucet.faucet_rate_limit_per_ip_per_hour != 0,
+				),
+				("FAUCET_MAX_AMOUNT_PER_REQUEST", faucet.faucet_max_amount_per_request != 0),
+				("FAUCET_COOLDOWN_SEC", !faucet.faucet_cooldown_sec.is_zero()),
+				("FAUCET_REQUIRE_CAPTCHA", faucet.faucet_require_captcha),
+				(
+					"FAUCET_MINT_AMOUNT",
+					faucet.faucet_mint_amount != common::DEFAULT_FAUCET_MINT_AMOUNT,
+				),
+				("FAUCET_PRIVATE_KEY", faucet.faucet_private_key.is_some()),
+				("FAUCET_PRIVATE_KEYS", !faucet.faucet_private_keys.is_empty()),
+				("FAUCET_BLOCKED_ADDRESSES", !faucet.faucet_blocked_addresses.is_empty()),
+				("FAUCET_ALLOWLIST_ADDRESSES", !faucet.faucet_allowlist_addresses.is_empty()),
+			]
+			.into_iter()
+			.filter_map(|(var, set)| set.then_some(var))
+			.collect();
+			if !vars.is_empty() {
+				warnings.push(ValidationWarning::FaucetSettingsOnReadOnlyNode { vars });
+			}
+		}
+
+		for listener in self.listeners() {
+			if !listen_hostnames(&listener.hostname).into_iter().any(is_unspecified) {
+				continue;
+			}
+			let is_faucet = listener.hostname_var == "FAUCET_API_LISTEN_HOSTNAME";
+			if is_faucet && self.is_read_only() {
+				continue;
+			}
+			if let Some((surface, false)) = self.exposed_surface(listener.hostname_var) {
+				warnings.push(ValidationWarning::UnprotectedPublicBind {
+					surface,
+					hostname_var: listener.hostname_var,
+					hostname: listener.hostname.clone(),
+				});
+			}
+			if is_faucet
+				&& self.faucet.faucet_rate_limit_per_ip_per_hour == 0
+				&& self.faucet.faucet_cooldown_sec.is_zero()
+			{
+				warnings.push(ValidationWarning::PublicFaucetWithoutRateLimit {
+					hostname: listener.hostname,
+				});
+			}
+		}
+
+		if self.chain.fsync_policy() == FsyncPolicy::Never && is_production_profile() {
+			warnings.push(ValidationWarning::FsyncNeverInProduction);
+		}
+
+		// The state merkle window essentially needs to exceed TPS,
+		// for which the in-flight transaction bound is the closest proxy we have.
+		let recommended = self.load_shedding.recommended_min_state_merkle_prune_window();
+		if self.chain.pruning_enabled() && self.chain.maptos_state_merkle_prune_window < recommended
+		{
+			warnings.push(ValidationWarning::StateMerkleWindowBelowInFlight {
+				window: self.chain.maptos_state_merkle_prune_window,
+				in_flight: self.load_shedding.max_transactions_in_flight,
+				recommended,
+			});
+		}
+
+		if !self.chain.pruning_enabled() {
+			let vars: Vec<&'static str> = PRUNE_WINDOW_VARS
+				.iter()
+				.copied()
+				.filter(|var| self.sources.get(var) != Provenance::Default)
+				.collect();
+			if !vars.is_empty() {
+				warnings.push(ValidationWarning::PruneWindowsIgnored { vars });
+			}
+		}
+	}
+
+	/// The name of the server bound through `hostname_var` and whether TLS or authentication
+	/// protects it, or `None` for the health and metrics probes, which are left to the
+	/// network policy.
+	fn exposed_surface(&self, hostname_var: &str) -> Option<(&'static str, bool)> {
+		match hostname_var {
+			"MAPTOS_API_LISTEN_HOSTNAME" => Some(("Maptos API", self.rest_tls_enabled())),
+			"FAUCET_API_LISTEN_HOSTNAME" => Some(("faucet", self.faucet.faucet_require_captcha)),
+			"MAPTOS_FIN_VIEW_API_LISTEN_HOSTNAME" => Some(("fin view API", false)),
+			"MAPTOS_INDEXER_GRPC_LISTEN_HOSTNAME" => {
+				// The built-in token is public, so it authenticates no one.
+				let tokens = self.indexer_auth_tokens();
+				let authenticated = !tokens.is_empty()
+					&& !tokens
+						.iter()
+						.any(|token| token == common::DEFAULT_INDEXER_PROCESSOR_AUTH_TOKEN);
+				Some(("indexer gRPC", self.indexer_grpc_tls_enabled() || authenticated))
+			}
+			_ => None,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::config::logging::SampleRate;
+	use crate::config::provenance::Sources;
+	use aptos_crypto::ed25519::Ed25519PrivateKey;
+
+	/// The default config with the servers that would warn about binding every interface
+	/// bound to loopback instead.
+	fn loopback() -> Config {
+		let mut config = Config::default();
+		config.chain.maptos_rest_listen_hostname = "127.0.0.1".to_string();
+		config.faucet.maptos_faucet_rest_listen_hostname = "127.0.0.1".to_string();
+		config.fin.fin_rest_listen_hostname = "127.0.0.1".to_string();
+		config.indexer.maptos_indexer_grpc_listen_hostname = "127.0.0.1".to_string();
+		config
+	}
+
+	#[test]
+	fn test_default_config_is_valid() {
+		let _env = godfig::env::read_lock();
+		// The defaults bind every interface, which is warned about rather than refused.
+		assert!(!Config::default().validate_all().has_errors());
+		assert_eq!(loopback().validate(), Ok(vec![]));
+		assert!(loopback().validate_all().is_empty());
+	}
+
+	#[test]
+	fn test_unprotected_public_binds_warn() {
+		let _env = godfig::env::read_lock();
+		let unprotected = |surface, hostname_var| ValidationWarning::UnprotectedPublicBind {
+			surface,
+			hostname_var,
+			hostname: "0.0.0.0".to_string(),
+		};
+
+		let mut config = loopback();
+		config.chain.maptos_rest_listen_hostname = "0.0.0.0".to_string();
+		assert_eq!(
+			config.validate(),
+			Ok(vec![unprotected("Maptos API", "MAPTOS_API_LISTEN_HOSTNAME")])
+		);
+		config.chain.maptos_rest_tls_cert_path = "/etc/maptos/rest.crt".to_string();
+		config.chain.maptos_rest_tls_key_path = "/etc/maptos/rest.key".to_string();
+		assert_eq!(config.validate(), Ok(vec![]));
+
+		let mut config = loopback();
+		config.fin.fin_rest_listen_hostname = "0.0.0.0".to_string();
+		assert_eq!(
+			config.validate(),
+			Ok(vec![unprotected("fin view API", "MAPTOS_FIN_VIEW_API_LISTEN_HOSTNAME")])
+		);
+
+		let mut config = loopback();
+		config.indexer.maptos_indexer_grpc_listen_hostname = "0.0.0.0".to_string();
+		assert_eq!(
+			config.validate(),
+			Ok(vec![unprotected("indexer gRPC", "MAPTOS_INDEXER_GRPC_LISTEN_HOSTNAME")])
+		);
+		config.indexer_processor.indexer_processor_auth_token = "s3cr3t-token".to_string();
+		assert_eq!(config.validate(), Ok(vec![]));
+
+		// The probes are left to the network policy.
+		let mut config = loopback();
+		config.health.maptos_health_enabled = true;
+		config.metrics.maptos_metrics_enabled = true;
+		config.health.maptos_health_listen_hostname = "0.0.0.0".to_string();
+		config.metrics.maptos_metrics_listen_hostname = "0.0.0.0".to_string();
+		assert_eq!(config.validate(), Ok(vec![]));
+	}
+
+	#[test]
+	fn test_public_faucet_warns_without_captcha_or_rate_limit() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		config.faucet.maptos_faucet_rest_listen_hostname = "::".to_string();
+		assert_eq!(
+			config.validate(),
+			Ok(vec![
+				ValidationWarning::UnprotectedPublicBind {
+					surface: "faucet",
+					hostname_var: "FAUCET_API_LISTEN_HOSTNAME",
+					hostname: "::".to_string(),
+				},
+				ValidationWarning::PublicFaucetWithoutRateLimit { hostname: "::".to_string() },
+			])
+		);
+
+		config.faucet.faucet_require_captcha = true;
+		assert_eq!(
+			config.validate(),
+			Ok(vec![ValidationWarning::PublicFaucetWithoutRateLimit {
+				hostname: "::".to_string()
+			}])
+		);
+		config.faucet.faucet_rate_limit_per_ip_per_hour = 10;
+		assert_eq!(config.validate(), Ok(vec![]));
+
+		// A read-only node starts no faucet to expose.
+		let mut config = loopback();
+		config.faucet.maptos_faucet_rest_listen_hostname = "0.0.0.0".to_string();
+		config.chain.maptos_read_only = true;
+		assert_eq!(config.validate(), Ok(vec![]));
+	}
+
+	#[test]
+	fn test_loopback_binds_do_not_warn() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		config.chain.maptos_rest_listen_hostname = "::1".to_string();
+		config.faucet.maptos_faucet_rest_listen_hostname = "localhost".to_string();
+		config.fin.fin_rest_listen_hostname = "127.0.0.1, ::1".to_string();
+		assert_eq!(config.validate(), Ok(vec![]));
+	}
+
+	#[test]
+	fn test_report_collects_every_problem() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.faucet.maptos_faucet_rest_listen_port = config.chain.maptos_rest_listen_port;
+		config.client.maptos_faucet_rest_connection_port = config.chain.maptos_rest_listen_port;
+		config.chain.maptos_state_merkle_prune_window = config.chain.maptos_ledger_prune_window + 1;
+		config.indexer.maptos_indexer_grpc_inactivity_ping_interval =
+			config.indexer.maptos_indexer_grpc_inactivity_timeout;
+		config.load_shedding.max_transactions_in_flight = u64::MAX;
+
+		let report = config.validate_all();
+		assert!(report.has_errors());
+		assert!(report.issues.iter().any(|issue| matches!(
+			issue,
+			ValidationIssue::Error(ValidationError::PortCollision { .. })
+		)));
+		assert!(report.issues.iter().any(|issue| matches!(
+			issue,
+			ValidationIssue::Error(ValidationError::PruneWindowExceedsLedger {
+				var: "MAPTOS_STATE_MERKLE_PRUNING_WINDOW",
+				..
+			})
+		)));
+		assert!(report.issues.iter().any(|issue| matches!(
+			issue,
+			ValidationIssue::Error(ValidationError::PingIntervalTooLong { .. })
+		)));
+		assert_eq!(report.issues.last().map(ValidationIssue::severity), Some(Severity::Warning));
+
+		// `validate` still stops at the first error.
+		assert_eq!(
+			config.validate(),
+			Err(match &report.issues[0] {
+				ValidationIssue::Error(error) => error.clone(),
+				ValidationIssue::Warning(_) => unreachable!("errors are listed first"),
+			})
+		);
+	}
+
+	#[test]
+	fn test_three_way_collision_is_reported_once() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		let port = config.chain.maptos_rest_listen_port;
+		config.faucet.maptos_faucet_rest_listen_port = port;
+		config.fin.fin_rest_listen_port = port;
+		config.client.maptos_faucet_rest_connection_port = port;
+		config.client.fin_rest_connection_port = port;
+
+		let collisions: Vec<_> = config
+			.validate_all()
+			.issues
+			.into_iter()
+			.filter(|issue| {
+				matches!(issue, ValidationIssue::Error(ValidationError::PortCollision { .. }))
+			})
+			.collect();
+		assert_eq!(
+			collisions,
+			vec![ValidationIssue::Error(ValidationError::PortCollision {
+				port,
+				fields: vec![
+					"MAPTOS_API_LISTEN_PORT",
+					"FAUCET_API_LISTEN_PORT",
+					"MAPTOS_FIN_VIEW_API_LISTEN_PORT"
+				],
+			})]
+		);
+	}
+
+	#[test]
+	fn test_unix_socket_with_explicit_port_is_rejected() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		config.chain.maptos_rest_listen_hostname = "unix:/run/maptos/rest.sock".to_string();
+		config.chain.maptos_rest_listen_port = 0;
+		assert_eq!(config.validate(), Ok(vec![]));
+
+		config.chain.maptos_rest_listen_port = 8080;
+		assert_eq!(
+			config.validate(),
+			Err(ValidationError::UnixSocketWithPort {
+				hostname_var: "MAPTOS_API_LISTEN_HOSTNAME",
+				port_var: "MAPTOS_API_LISTEN_PORT",
+				port: 8080,
+			})
+		);
+	}
+
+	#[test]
+	fn test_unix_socket_is_rest_and_fin_only() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.faucet.maptos_faucet_rest_listen_hostname =
+			"unix:/run/maptos/faucet.sock".to_string();
+		assert!(matches!(
+			config.validate(),
+			Err(ValidationError::OutOfRange { var: "FAUCET_API_LISTEN_HOSTNAME", .. })
+		));
+	}
+
+	#[test]
+	fn test_unspecified_binds_on_same_port_collide() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.chain.maptos_rest_listen_hostname = "0.0.0.0".to_string();
+		config.fin.fin_rest_listen_hostname = "0.0.0.0".to_string();
+		config.fin.fin_rest_listen_port = config.chain.maptos_rest_listen_port;
+
+		assert_eq!(
+			config.validate(),
+			Err(ValidationError::PortCollision {
+				port: config.chain.maptos_rest_listen_port,
+				fields: vec!["MAPTOS_API_LISTEN_PORT", "MAPTOS_FIN_VIEW_API_LISTEN_PORT"],
+			})
+		);
+	}
+
+	#[test]
+	fn test_health_port_collides_only_when_enabled() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		config.health.maptos_health_listen_hostname = "0.0.0.0".to_string();
+		config.health.maptos_health_listen_port = config.fin.fin_rest_listen_port;
+
+		config.health.maptos_health_enabled = false;
+		assert_eq!(config.validate(), Ok(vec![]));
+
+		config.health.maptos_health_enabled = true;
+		assert!(matches!(
+			config.validate(),
+			Err(ValidationError::PortCollision { fields, .. })
+				if fields.contains(&"MAPTOS_HEALTH_LISTEN_PORT")
+		));
+	}
+
+	#[test]
+	fn test_metrics_port_collides_only_when_enabled() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		config.chain.maptos_rest_enabled = true;
+		config.metrics.maptos_metrics_listen_hostname = "0.0.0.0".to_string();
+		config.metrics.maptos_metrics_listen_port = config.chain.maptos_rest_listen_port;
+
+		config.metrics.maptos_metrics_enabled = false;
+		assert!(!config
+			.listeners()
+			.iter()
+			.any(|listener| listener.port_var == "MAPTOS_METRICS_LISTEN_PORT"));
+		assert_eq!(config.validate(), Ok(vec![]));
+
+		config.metrics.maptos_metrics_enabled = true;
+		assert!(matches!(
+			config.validate(),
+			Err(ValidationError::PortCollision { fields, .. })
+				if fields.contains(&"MAPTOS_METRICS_LISTEN_PORT")
+		));
+	}
+
+	#[test]
+	fn test_disabled_services_bind_nothing() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		config.chain.maptos_rest_enabled = true;
+		config.fin.fin_rest_enabled = true;
+		config.faucet.maptos_faucet_enabled = false;
+		config.indexer.maptos_indexer_grpc_enabled = false;
+		config.health.maptos_health_enabled = false;
+		let port_vars: Vec<&str> =
+			config.listeners().iter().map(|listener| listener.port_var).collect();
+		assert_eq!(port_vars, vec!["MAPTOS_API_LISTEN_PORT", "MAPTOS_FIN_VIEW_API_LISTEN_PORT"]);
+
+		// A disabled service may share its port with an enabled one.
+		config.chain.maptos_rest_listen_hostname = "0.0.0.0".to_string();
+		config.fin.fin_rest_listen_hostname = "0.0.0.0".to_string();
+		config.fin.fin_rest_listen_port = config.chain.maptos_rest_listen_port;
+		assert!(matches!(config.validate(), Err(ValidationError::PortCollision { .. })));
+
+		config.chain.maptos_rest_enabled = false;
+		assert_eq!(
+			config.validate(),
+			Ok(vec![ValidationWarning::UnprotectedPublicBind {
+				surface: "fin view API",
+				hostname_var: "MAPTOS_FIN_VIEW_API_LISTEN_HOSTNAME",
+				hostname: "0.0.0.0".to_string(),
+			}])
+		);
+	}
+
+	#[test]
+	fn test_port_zero_never_collides() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		config.chain.maptos_rest_listen_hostname = "127.0.0.1".to_string();
+		config.chain.maptos_rest_listen_port = 0;
+		config.fin.fin_rest_listen_hostname = "127.0.0.1".to_string();
+		config.fin.fin_rest_listen_port = 0;
+		// The connections cannot know the picked port, so they are not held to it.
+		config.client.maptos_rest_connection_hostname = "127.0.0.1".to_string();
+		config.client.fin_rest_connection_hostname = "127.0.0.1".to_string();
+		assert_eq!(config.validate(), Ok(vec![]));
+	}
+
+	#[test]
+	fn test_unspecified_overlaps_specific_host() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.faucet.maptos_faucet_rest_listen_hostname = "127.0.0.1".to_string();
+		config.indexer.maptos_indexer_grpc_listen_hostname = "0.0.0.0".to_string();
+		config.indexer.maptos_indexer_grpc_listen_port =
+			config.faucet.maptos_faucet_rest_listen_port;
+
+		assert!(matches!(config.validate(), Err(ValidationError::PortCollision { .. })));
+	}
+
+	#[test]
+	fn test_distinct_specific_hosts_do_not_collide() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		config.chain.maptos_rest_listen_hostname = "10.0.0.1".to_string();
+		config.fin.fin_rest_listen_hostname = "10.0.0.2".to_string();
+		config.fin.fin_rest_listen_port = config.chain.maptos_rest_listen_port;
+		config.client.fin_rest_connection_port = config.fin.fin_rest_listen_port;
+
+		assert_eq!(config.validate(), Ok(vec![]));
+	}
+
+	#[test]
+	fn test_listed_hostnames_collide_with_any_overlap() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.chain.maptos_rest_listen_hostname = "10.0.0.1,10.0.0.2".to_string();
+		config.fin.fin_rest_listen_hostname = "10.0.0.2".to_string();
+		config.fin.fin_rest_listen_port = config.chain.maptos_rest_listen_port;
+		config.client.fin_rest_connection_port = config.fin.fin_rest_listen_port;
+
+		assert!(matches!(config.validate(), Err(ValidationError::PortCollision { .. })));
+	}
+
+	#[test]
+	fn test_rest_tls_needs_cert_and_key() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		config.chain.maptos_rest_tls_cert_path = "/etc/maptos/rest.crt".to_string();
+		config.chain.maptos_rest_tls_key_path = String::new();
+
+		assert_eq!(
+			config.validate(),
+			Err(ValidationError::TlsHalfConfigured {
+				set_var: "MAPTOS_API_TLS_CERT",
+				missing_var: "MAPTOS_API_TLS_KEY",
+			})
+		);
+
+		config.chain.maptos_rest_tls_key_path = "/etc/maptos/rest.key".to_string();
+		assert_eq!(config.validate(), Ok(vec![]));
+		assert!(config.rest_tls_enabled());
+	}
+
+	#[test]
+	fn test_indexer_grpc_tls_is_disabled_by_default() {
+		let _env = godfig::env::read_lock();
+		let config = Config::default();
+		assert_eq!(config.indexer_grpc_tls(), None);
+		assert_eq!(config.indexer_grpc_connection_url().unwrap().scheme(), "http");
+	}
+
+	#[test]
+	fn test_indexer_grpc_tls_needs_cert_and_key() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		config.indexer.maptos_indexer_grpc_tls_key = "/etc/maptos/grpc.key".to_string();
+
+		assert_eq!(
+			config.validate(),
+			Err(ValidationError::TlsHalfConfigured {
+				set_var: "MAPTOS_INDEXER_GRPC_TLS_KEY",
+				missing_var: "MAPTOS_INDEXER_GRPC_TLS_CERT",
+			})
+		);
+		assert_eq!(config.indexer_grpc_tls(), None);
+
+		config.indexer.maptos_indexer_grpc_tls_cert = "/etc/maptos/grpc.crt".to_string();
+		config.indexer.maptos_indexer_grpc_client_ca = "/etc/maptos/clients.crt".to_string();
+		assert_eq!(config.validate(), Ok(vec![]));
+		assert_eq!(
+			config.indexer_grpc_tls().and_then(|tls| tls.client_ca_path),
+			Some("/etc/maptos/clients.crt".into())
+		);
+		assert_eq!(config.indexer_grpc_connection_url().unwrap().scheme(), "https");
+	}
+
+	#[test]
+	fn test_client_ca_needs_indexer_grpc_tls() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.indexer.maptos_indexer_grpc_client_ca = "/etc/maptos/clients.crt".to_string();
+
+		assert_eq!(
+			config.validate(),
+			Err(ValidationError::ClientCaWithoutTls {
+				set_var: "MAPTOS_INDEXER_GRPC_CLIENT_CA",
+				missing_var: "MAPTOS_INDEXER_GRPC_TLS_CERT",
+			})
+		);
+	}
+
+	#[test]
+	fn test_prune_windows_cannot_exceed_ledger() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.chain.maptos_ledger_prune_window = 1_000_000;
+		config.chain.maptos_state_merkle_prune_window = 2_000_000;
+
+		assert_eq!(
+			config.validate(),
+			Err(ValidationError::PruneWindowExceedsLedger {
+				var: "MAPTOS_STATE_MERKLE_PRUNING_WINDOW",
+				window: 2_000_000,
+				ledger_window: 1_000_000,
+			})
+		);
+
+		config.chain.maptos_state_merkle_prune_window = 100_000;
+		config.chain.maptos_epoch_snapshot_prune_window = 1_000_001;
+		assert!(matches!(
+			config.validate(),
+			Err(ValidationError::PruneWindowExceedsLedger {
+				var: "MAPTOS_EPOCH_SNAPSHOT_PRUNING_WINDOW",
+				..
+			})
+		));
+	}
+
+	#[test]
+	fn test_disabled_pruning_ignores_the_windows() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		config.chain.maptos_ledger_prune_window = 1_000;
+		config.chain.maptos_state_merkle_prune_window = 2_000;
+		config.chain.maptos_epoch_snapshot_prune_window = 3_000;
+		assert!(matches!(config.validate(), Err(ValidationError::PruneWindowExceedsLedger { .. })));
+
+		config.chain.maptos_enable_pruning = false;
+		assert_eq!(config.validate(), Ok(vec![]));
+
+		// Windows which were configured are noted as having no effect.
+		config.sources = Sources::default().with_file(
+			["MAPTOS_LEDGER_PRUNING_WINDOW", "MAPTOS_EPOCH_SNAPSHOT_PRUNING_WINDOW"].into(),
+		);
+		assert_eq!(
+			config.validate(),
+			Ok(vec![ValidationWarning::PruneWindowsIgnored {
+				vars: vec!["MAPTOS_LEDGER_PRUNING_WINDOW", "MAPTOS_EPOCH_SNAPSHOT_PRUNING_WINDOW"],
+			}])
+		);
+	}
+
+	#[test]
+	fn test_values_the_env_would_reject_are_rejected() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.chain.maptos_state_merkle_prune_window = 0;
+		config.client.fin_rest_connection_port = 0;
+		config.indexer_processor.postgres_connection_timeout_sec = DurationSecs::from_secs(0);
+
+		let out_of_range: Vec<&'static str> = config
+			.validate_all()
+			.issues
+			.into_iter()
+			.filter_map(|issue| match issue {
+				ValidationIssue::Error(ValidationError::OutOfRange { var, .. }) => Some(var),
+				_ => None,
+			})
+			.collect();
+		assert_eq!(
+			out_of_range,
+			vec![
+				"INDEXER_PROCESSOR_POSTGRES_CONNECTION_TIMEOUT_SEC",
+				"MAPTOS_STATE_MERKLE_PRUNING_WINDOW",
+				"MAPTOS_FIN_VIEW_API_CONNECTION_PORT",
+			]
+		);
+	}
+
+	#[test]
+	fn test_ping_interval_equal_to_inactivity_timeout_is_rejected() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.indexer.maptos_indexer_grpc_inactivity_timeout = DurationSecs::from_secs(60);
+		config.indexer.maptos_indexer_grpc_inactivity_ping_interval = DurationSecs::from_secs(60);
+
+		assert_eq!(
+			config.validate(),
+			Err(ValidationError::PingIntervalTooLong {
+				ping_interval: DurationSecs::from_secs(60),
+				inactivity_timeout: DurationSecs::from_secs(60),
+			})
+		);
+	}
+
+	#[test]
+	fn test_ping_interval_of_half_the_inactivity_timeout_is_accepted() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		config.indexer.maptos_indexer_grpc_inactivity_timeout = DurationSecs::from_secs(60);
+		config.indexer.maptos_indexer_grpc_inactivity_ping_interval = DurationSecs::from_secs(30);
+
+		assert_eq!(config.validate(), Ok(vec![]));
+
+		// Sub-second intervals are compared exactly rather than in whole seconds.
+		config.indexer.maptos_indexer_grpc_inactivity_timeout = DurationSecs::from_millis(1500);
+		config.indexer.maptos_indexer_grpc_inactivity_ping_interval =
+			DurationSecs::from_millis(800);
+		assert!(matches!(config.validate(), Err(ValidationError::PingIntervalTooLong { .. })));
+	}
+
+	#[test]
+	fn test_grpc_max_message_bytes_range() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		config.indexer.maptos_indexer_grpc_max_message_bytes = 0;
+		assert!(matches!(
+			config.validate(),
+			Err(ValidationError::OutOfRange { var: "MAPTOS_INDEXER_GRPC_MAX_MESSAGE_BYTES", .. })
+		));
+
+		config.indexer.maptos_indexer_grpc_max_message_bytes = 4 << 30;
+		assert!(config.validate().is_err());
+
+		config.indexer.maptos_indexer_grpc_max_message_bytes = 64 << 20;
+		assert_eq!(config.validate(), Ok(vec![]));
+	}
+
+	#[test]
+	fn test_over_large_execution_concurrency_is_rejected() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		config.chain.maptos_execution_concurrency = common::MAX_EXECUTION_CONCURRENCY + 1;
+		assert!(matches!(
+			config.validate(),
+			Err(ValidationError::OutOfRange { var: "MAPTOS_EXECUTION_CONCURRENCY", .. })
+		));
+
+		config.chain.maptos_execution_concurrency = common::MAX_EXECUTION_CONCURRENCY;
+		assert_eq!(config.validate(), Ok(vec![]));
+	}
+
+	#[test]
+	fn test_max_page_size_range() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		for max_page_size in [0, 65_536] {
+			config.chain.maptos_rest_max_page_size = max_page_size;
+			assert!(matches!(
+				config.validate(),
+				Err(ValidationError::OutOfRange { var: "MAPTOS_API_MAX_PAGE_SIZE", .. })
+			));
+		}
+
+		config.chain.maptos_rest_max_page_size = 1;
+		assert_eq!(config.validate(), Ok(vec![]));
+	}
+
+	#[test]
+	fn test_grpc_batch_size_range() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		for batch_size in [0, common::MAX_INDEXER_GRPC_BATCH_SIZE + 1] {
+			config.indexer.maptos_indexer_grpc_batch_size = batch_size;
+			assert!(matches!(
+				config.validate(),
+				Err(ValidationError::OutOfRange { var: "MAPTOS_INDEXER_GRPC_BATCH_SIZE", .. })
+			));
+		}
+
+		for batch_size in [1, common::MAX_INDEXER_GRPC_BATCH_SIZE] {
+			config.indexer.maptos_indexer_grpc_batch_size = batch_size;
+			assert_eq!(config.validate(), Ok(vec![]));
+		}
+	}
+
+	#[test]
+	fn test_zero_send_buffer_is_rejected() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.indexer.maptos_indexer_grpc_send_buffer_size = 0;
+		assert!(matches!(
+			config.validate(),
+			Err(ValidationError::OutOfRange { var: "MAPTOS_INDEXER_GRPC_SEND_BUFFER_SIZE", .. })
+		));
+	}
+
+	#[test]
+	fn test_zero_max_body_bytes_is_rejected() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.chain.maptos_rest_max_body_bytes = 0;
+		assert!(matches!(
+			config.validate(),
+			Err(ValidationError::OutOfRange { var: "MAPTOS_API_MAX_BODY_BYTES", .. })
+		));
+	}
+
+	#[test]
+	fn test_base_paths_must_start_with_a_slash() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.chain.maptos_rest_base_path = "/node-a/".to_string();
+		config.faucet.maptos_faucet_rest_base_path = "/".to_string();
+		assert!(config.validate().is_ok());
+
+		config.chain.maptos_rest_base_path = "node-a".to_string();
+		assert!(matches!(
+			config.validate(),
+			Err(ValidationError::InvalidBasePath { var: "MAPTOS_API_BASE_PATH", .. })
+		));
+
+		config.chain.maptos_rest_base_path = String::new();
+		config.faucet.maptos_faucet_rest_base_path = "/faucet?x=1".to_string();
+		assert!(matches!(
+			config.validate(),
+			Err(ValidationError::InvalidBasePath { var: "FAUCET_API_BASE_PATH", .. })
+		));
+	}
+
+	#[test]
+	fn test_commit_batch_size_must_be_positive() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.chain.maptos_commit_batch_size = 0;
+		assert!(matches!(
+			config.validate(),
+			Err(ValidationError::OutOfRange { var: "MAPTOS_COMMIT_BATCH_SIZE", .. })
+		));
+
+		config.chain.maptos_commit_batch_size = 1;
+		assert!(config.validate().is_ok());
+	}
+
+	#[test]
+	fn test_db_sizes_must_be_positive() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		config.chain.maptos_db_block_cache_bytes = 512 << 20;
+		config.chain.maptos_db_max_wal_bytes = 0;
+		assert!(matches!(
+			config.validate(),
+			Err(ValidationError::OutOfRange { var: "MAPTOS_DB_MAX_WAL_BYTES", .. })
+		));
+
+		config.chain.maptos_db_max_wal_bytes = 64 << 20;
+		config.chain.maptos_db_block_cache_bytes = 0;
+		assert!(matches!(
+			config.validate(),
+			Err(ValidationError::OutOfRange { var: "MAPTOS_DB_BLOCK_CACHE_BYTES", .. })
+		));
+
+		config.chain.maptos_db_block_cache_bytes = 512 << 20;
+		assert_eq!(config.validate(), Ok(vec![]));
+	}
+
+	#[test]
+	fn test_transaction_limits_must_be_positive() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		config.chain.maptos_rest_max_gas_amount = 0;
+		config.chain.maptos_rest_max_transaction_bytes = 64 * 1024;
+		assert!(matches!(
+			config.validate(),
+			Err(ValidationError::OutOfRange { var: "MAPTOS_API_MAX_GAS_AMOUNT", .. })
+		));
+
+		config.chain.maptos_rest_max_gas_amount = 2_000_000;
+		config.chain.maptos_rest_max_transaction_bytes = 0;
+		assert!(matches!(
+			config.validate(),
+			Err(ValidationError::OutOfRange { var: "MAPTOS_API_MAX_TRANSACTION_BYTES", .. })
+		));
+
+		config.chain.maptos_rest_max_transaction_bytes = 1;
+		assert_eq!(config.validate(), Ok(vec![]));
+	}
+
+	#[test]
+	fn test_gas_unit_price_below_the_minimum_is_rejected() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		config.chain.maptos_gas_unit_price = 99;
+		assert!(matches!(
+			config.validate(),
+			Err(ValidationError::OutOfRange { var: "MAPTOS_GAS_UNIT_PRICE", .. })
+		));
+
+		for price in [0, common::MIN_GAS_UNIT_PRICE, 1_000] {
+			config.chain.maptos_gas_unit_price = price;
+			assert_eq!(config.validate(), Ok(vec![]));
+		}
+	}
+
+	#[test]
+	fn test_zero_request_timeout_is_rejected() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.fin.fin_rest_request_timeout_sec = DurationSecs::ZERO;
+
+		assert!(matches!(
+			config.validate(),
+			Err(ValidationError::OutOfRange { var: "MAPTOS_FIN_VIEW_API_REQUEST_TIMEOUT_SEC", .. })
+		));
+	}
+
+	#[test]
+	fn test_zero_faucet_txn_expiration_is_rejected() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		config.faucet.faucet_txn_expiration_sec = DurationSecs::ZERO;
+		assert!(matches!(
+			config.validate(),
+			Err(ValidationError::OutOfRange { var: "FAUCET_TXN_EXPIRATION_SEC", .. })
+		));
+
+		config.faucet.faucet_txn_expiration_sec = DurationSecs::from_millis(500);
+		assert_eq!(config.validate(), Ok(vec![]));
+	}
+
+	#[test]
+	fn test_log_sample_rate_range() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		for rate in [0.0, 0.5, 1.0] {
+			config.chain.maptos_rest_log_sample_rate = SampleRate::new(rate);
+			assert_eq!(config.validate(), Ok(vec![]));
+		}
+		for rate in [-0.1, 1.01, f64::NAN] {
+			config.chain.maptos_rest_log_sample_rate = SampleRate::new(rate);
+			assert!(matches!(
+				config.validate(),
+				Err(ValidationError::OutOfRange { var: "MAPTOS_API_LOG_SAMPLE_RATE", .. })
+			));
+		}
+	}
+
+	#[test]
+	fn test_key_must_be_of_the_scheme() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		config.chain.maptos_key_scheme = KeyScheme::Secp256k1;
+		config.chain.maptos_private_key =
+			Ed25519PrivateKey::try_from([0xff; 32].as_slice()).unwrap();
+		assert_eq!(
+			config.validate(),
+			Err(ValidationError::KeyNotOfScheme { scheme: KeyScheme::Secp256k1 })
+		);
+	}
+
+	#[test]
+	fn test_otlp_sample_rate_range_and_endpoint() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		config.logging.maptos_otlp_endpoint = "http://localhost:4317".to_string();
+		for rate in [0.0, 0.5, 1.0] {
+			config.logging.maptos_otlp_sample_rate = SampleRate::new(rate);
+			assert_eq!(config.validate(), Ok(vec![]));
+		}
+		for rate in [-0.1, 1.01, f64::NAN] {
+			config.logging.maptos_otlp_sample_rate = SampleRate::new(rate);
+			assert!(matches!(
+				config.validate(),
+				Err(ValidationError::OutOfRange { var: "MAPTOS_OTLP_SAMPLE_RATE", .. })
+			));
+		}
+
+		config.logging.maptos_otlp_sample_rate = SampleRate::ALL;
+		for endpoint in ["localhost:4317", "collector", "ftp://collector:4317"] {
+			config.logging.maptos_otlp_endpoint = endpoint.to_string();
+			assert!(matches!(
+				config.validate(),
+				Err(ValidationError::InvalidOtlpEndpoint { var: "MAPTOS_OTLP_ENDPOINT", .. })
+			));
+		}
+	}
+
+	#[test]
+	fn test_absurd_worker_thread_counts_are_rejected() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		config.fin.fin_rest_worker_threads = common::MAX_WORKER_THREADS;
+		assert_eq!(config.validate(), Ok(vec![]));
+
+		config.fin.fin_rest_worker_threads = 100_000;
+		assert!(matches!(
+			config.validate(),
+			Err(ValidationError::OutOfRange { var: "MAPTOS_FIN_VIEW_API_WORKER_THREADS", .. })
+		));
+	}
+
+	#[test]
+	fn test_postgres_pool_needs_a_connection() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.indexer_processor.postgres_max_connections = 0;
+
+		assert!(matches!(
+			config.validate(),
+			Err(ValidationError::OutOfRange {
+				var: "INDEXER_PROCESSOR_POSTGRES_MAX_CONNECTIONS",
+				..
+			})
+		));
+	}
+
+	#[test]
+	fn test_faucet_settings_on_a_read_only_node_warn() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		config.chain.maptos_read_only = false;
+		config.faucet.faucet_rate_limit_per_ip_per_hour = 10;
+		assert_eq!(config.validate(), Ok(vec![]));
+
+		config.chain.maptos_read_only = true;
+		assert_eq!(
+			config.validate(),
+			Ok(vec![ValidationWarning::FaucetSettingsOnReadOnlyNode {
+				vars: vec!["FAUCET_RATE_LIMIT_PER_IP_PER_HOUR"]
+			}])
+		);
+	}
+
+	#[test]
+	fn test_genesis_override_paths_must_exist() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		assert_eq!(config.chain.maptos_genesis_blob_path, None);
+		assert_eq!(config.chain.maptos_framework_path, None);
+
+		config.chain.maptos_genesis_blob_path = Some("/nonexistent/genesis.blob".into());
+		config.chain.maptos_framework_path = Some(std::env::temp_dir());
+		assert_eq!(
+			config.validate(),
+			Err(ValidationError::MissingPath {
+				var: "MAPTOS_GENESIS_BLOB_PATH",
+				path: "/nonexistent/genesis.blob".into(),
+			})
+		);
+	}
+
+	#[test]
+	fn test_ending_version_cannot_precede_starting_version() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		config.indexer_processor.indexer_processor_starting_version = 2_000;
+		config.indexer_processor.indexer_processor_ending_version = Some(1_000);
+		assert_eq!(
+			config.validate(),
+			Err(ValidationError::EndingVersionBeforeStarting { starting: 2_000, ending: 1_000 })
+		);
+
+		config.indexer_processor.indexer_processor_ending_version = Some(2_000);
+		assert_eq!(config.validate(), Ok(vec![]));
+
+		// Resuming from the database leaves the ending version unchecked.
+		config.indexer_processor.indexer_processor_starting_version = 0;
+		config.indexer_processor.indexer_processor_ending_version = Some(0);
+		assert_eq!(config.validate(), Ok(vec![]));
+	}
+
+	#[test]
+	fn test_small_state_merkle_window_warns() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		config.chain.maptos_state_merkle_prune_window = 1_000;
+		config.load_shedding.max_transactions_in_flight = 12_000;
+
+		assert_eq!(
+			config.validate(),
+			Ok(vec![ValidationWarning::StateMerkleWindowBelowInFlight {
+				window: 1_000,
+				in_flight: 12_000,
+				recommended: 24_000,
+			}])
+		);
+	}
+
+	#[test]
+	fn test_never_fsync_warns_under_the_production_profile() {
+		let _env = godfig::env::lock();
+		let mut config = loopback();
+		config.chain.maptos_fsync_policy = FsyncPolicy::Never;
+		let warning = ValidationIssue::Warning(ValidationWarning::FsyncNeverInProduction);
+		assert!(!config.validate_all().issues.contains(&warning));
+
+		std::env::set_var("MAPTOS_PROFILE", "production");
+		let never = config.validate_all();
+		config.chain.maptos_fsync_policy = FsyncPolicy::Batch;
+		let batch = config.validate_all();
+		std::env::remove_var("MAPTOS_PROFILE");
+
+		assert!(never.issues.contains(&warning));
+		assert!(!batch.issues.contains(&warning));
+	}
+
+	#[test]
+	fn test_aggressive_in_flight_bound_warns() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		// Above the default window, but short of the recommended multiple of the bound.
+		config.load_shedding.max_transactions_in_flight = 60_000;
+
+		let report = config.validate_all();
+		assert!(!report.has_errors());
+		assert!(report.issues.contains(&ValidationIssue::Warning(
+			ValidationWarning::StateMerkleWindowBelowInFlight {
+				window: 100_000,
+				in_flight: 60_000,
+				recommended: 120_000,
+			}
+		)));
+	}
+
+	#[test]
+	fn test_loopback_connection_port_mismatch() {
+		let _env = godfig::env::read_lock();
+		let mut config = Config::default();
+		config.client.maptos_indexer_grpc_connection_hostname = "127.0.0.1".to_string();
+		config.client.maptos_indexer_grpc_connection_port =
+			config.indexer.maptos_indexer_grpc_listen_port + 1;
+
+		assert!(matches!(
+			config.validate(),
+			Err(ValidationError::LoopbackPortMismatch {
+				connection_var: "MAPTOS_INDEXER_GRPC_CONNECTION_PORT",
+				..
+			})
+		));
+	}
+
+	#[test]
+	fn test_fin_connection_must_follow_the_fin_listener() {
+		let _env = godfig::env::read_lock();
+		let mut config = loopback();
+		config.fin.fin_rest_listen_port = 40200;
+
+		assert!(matches!(
+			config.validate(),
+			Err(ValidationError::LoopbackPortMismatch {
+				listen_var: "MAPTOS_FIN_VIEW_API_LISTEN_PORT",
+				connection_var: "MAPTOS_FIN_VIEW_API_CONNECTION_PORT",
+				..
+			})
+		));
+
+		config.client.fin_rest_connection_port = 40200;
+		assert_eq!(config.validate(), Ok(vec![]));
+	}
+}
@@ -0,0 +1,298 @@
+//! Listeners which hold their connections to the configured admission limits.
+
+use crate::config::common::DEFAULT_LISTEN_BACKLOG;
+use crate::config::endpoints::{self, ConnectionLimits, ListenTarget};
+
+use poem::{
+	http::uri::Scheme,
+	listener::{Acceptor, BoxListener, Listener, TcpAcceptor, UnixListener},
+	web::{LocalAddr, RemoteAddr},
+};
+use socket2::{Domain, Protocol, Socket, Type};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+use tokio::net::TcpListener;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::debug;
+
+use std::collections::BTreeMap;
+use std::io;
+use std::net::SocketAddr;
+use std::num::NonZeroUsize;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex, PoisonError};
+use std::task::{Context, Poll};
+
+/// Sockets bound ahead of the servers of this process, by address, until the server binding
+/// the address takes its socket over.
+static HANDED_OVER: Mutex<BTreeMap<SocketAddr, std::net::TcpListener>> =
+	Mutex::new(BTreeMap::new());
+
+/// Leaves `listener` to the first server of this process binding its address, which accepts
+/// on it rather than binding the address again.
+pub fn hand_over(listener: std::net::TcpListener) -> io::Result<()> {
+	let addr = listener.local_addr()?;
+	HANDED_OVER
+		.lock()
+		.unwrap_or_else(PoisonError::into_inner)
+		.insert(addr, listener);
+	Ok(())
+}
+
+fn take_handed_over(addr: SocketAddr) -> Option<std::net::TcpListener> {
+	HANDED_OVER.lock().unwrap_or_else(PoisonError::into_inner).remove(&addr)
+}
+
+/// Binds a TCP listener to each of `addrs`, or takes over the socket handed over for it.
+///
+/// Servers which must know their ports before serving bind here and serve on the listeners,
+/// so that a port the OS picks is never released for another process to take first.
+/// As in [`bind_all`], IPv6 sockets only accept IPv6 connections when several are bound.
+pub fn bind_tcp(addrs: &[SocketAddr]) -> io::Result<Vec<std::net::TcpListener>> {
+	let only_v6 = addrs.len() > 1;
+	addrs
+		.iter()
+		.map(|addr| {
+			let backlog = i32::try_from(DEFAULT_LISTEN_BACKLOG).unwrap_or(i32::MAX);
+			bind_socket(*addr, backlog, only_v6).map(std::net::TcpListener::from)
+		})
+		.collect()
+}
+
+/// A socket listening on `addr` with an accept queue of `backlog`, taking over the one
+/// handed over for it.
+fn bind_socket(addr: SocketAddr, backlog: i32, only_v6: bool) -> io::Result<Socket> {
+	if let Some(listener) = take_handed_over(addr) {
+		let socket = Socket::from(listener);
+		// Listening again resizes the accept queue of the socket.
+		socket.listen(backlog)?;
+		return Ok(socket);
+	}
+	let socket = Socket::new(Domain::for_address(addr), Type::STREAM, Some(Protocol::TCP))?;
+	if addr.is_ipv6() {
+		socket.set_only_v6(only_v6)?;
+	}
+	// As the standard listener does, so that a restarted server can rebind at once.
+	#[cfg(unix)]
+	socket.set_reuse_address(true)?;
+	socket.bind(&addr.into())?;
+	socket.listen(backlog)?;
+	Ok(socket)
+}
+
+/// Binds a listener for the target with the given limits,
+/// replacing a stale Unix domain socket left by a previous run.
+///
+/// The backlog only applies to TCP listeners.
+pub fn bind(target: ListenTarget, limits: ConnectionLimits) -> Result<BoxListener, anyhow::Error> {
+	bind_all(vec![target], limits)
+}
+
+/// Binds a listener on every [`endpoints::listen_targets`] of the hostname, without a
+/// connection limit.
+pub fn bind_hostname(
+	hostname_var: &str,
+	hostname: &str,
+	port: u16,
+) -> Result<BoxListener, anyhow::Error> {
+	let targets = endpoints::listen_targets(hostname_var, hostname, port)?;
+	bind_all(targets, ConnectionLimits::new(0, DEFAULT_LISTEN_BACKLOG))
+}
+
+/// Binds a listener accepting on every target, with the limits shared between them.
+///
+/// When several targets are bound, IPv6 sockets only accept IPv6 connections,
+/// so that `0.0.0.0` and `::` can be bound side by side on the same port.
+pub fn bind_all(
+	targets: Vec<ListenTarget>,
+	limits: ConnectionLimits,
+) -> Result<BoxListener, anyhow::Error> {
+	let only_v6 = targets.len() > 1;
+	let mut listeners = targets.into_iter().map(|target| match target {
+		ListenTarget::Tcp(addr) => {
+			Ok(TcpBacklogListener { addr, backlog: limits.listen_backlog, only_v6 }.boxed())
+		}
+		ListenTarget::Unix(path) => {
+			endpoints::remove_stale_unix_socket(&path).map_err(|e| {
+				anyhow::anyhow!("failed to remove stale socket {}: {}", path.display(), e)
+			})?;
+			Ok(UnixListener::bind(path).boxed())
+		}
+	});
+	let first = listeners.next().ok_or_else(|| anyhow::anyhow!("no address to listen on"))??;
+	let listener = listeners.try_fold(first, |combined, listener| {
+		Ok::<_, anyhow::Error>(combined.combine(listener?).boxed())
+	})?;
+	Ok(match limits.max_connections {
+		Some(max_connections) => LimitedListener::new(listener, max_connections).boxed(),
+		None => listener,
+	})
+}
+
+/// A TCP listener whose kernel accept queue holds `backlog` connections.
+pub struct TcpBacklogListener {
+	addr: SocketAddr,
+	backlog: u32,
+	/// Whether an IPv6 socket refuses IPv4-mapped connections.
+	only_v6: bool,
+}
+
+#[async_trait::async_trait]
+impl Listener for TcpBacklogListener {
+	type Acceptor = TcpAcceptor;
+
+	async fn into_acceptor(self) -> io::Result<Self::Acceptor> {
+		// The backlog is clamped to `i32::MAX` by `ConnectionLimits`.
+		let backlog = i32::try_from(self.backlog).unwrap_or(i32::MAX);
+		let socket = bind_socket(self.addr, backlog, self.only_v6)?;
+		socket.set_nonblocking(true)?;
+		TcpAcceptor::from_tokio(TcpListener::from_std(socket.into())?)
+	}
+}
+
+/// Serves at most a fixed number of connections at once.
+///
+/// Connections over the limit are closed as soon as they are accepted,
+/// so clients see backpressure at once rather than queueing behind a saturated server.
+pub struct LimitedListener<L> {
+	inner: L,
+	permits: Arc<Semaphore>,
+}
+
+impl<L> LimitedListener<L> {
+	pub fn new(inner: L, max_connections: NonZeroUsize) -> Self {
+		Self { inner, permits: Arc::new(Semaphore::new(max_connections.get())) }
+	}
+}
+
+#[async_trait::async_trait]
+impl<L: Listener> Listener for LimitedListener<L> {
+	type Acceptor = LimitedAcceptor<L::Acceptor>;
+
+	async fn into_acceptor(self) -> io::Result<Self::Acceptor> {
+		Ok(LimitedAcceptor { inner: self.inner.into_acceptor().await?, permits: self.permits })
+	}
+}
+
+pub struct LimitedAcceptor<A> {
+	inner: A,
+	permits: Arc<Semaphore>,
+}
+
+#[async_trait::async_trait]
+impl<A: Acceptor> Acceptor for LimitedAcceptor<A> {
+	type Io = LimitedIo<A::Io>;
+
+	fn local_addr(&self) -> Vec<LocalAddr> {
+		self.inner.local_addr()
+	}
+
+	async fn accept(&mut self) -> io::Result<(Self::Io, LocalAddr, RemoteAddr, Scheme)> {
+		loop {
+			let (io, local_addr, remote_addr, scheme) = self.inner.accept().await?;
+			match Arc::clone(&self.permits).try_acquire_owned() {
+				Ok(permit) => {
+					return Ok((LimitedIo { io, _permit: permit }, local_addr, remote_addr, scheme))
+				}
+				Err(_) => {
+					debug!("Closing connection from {} over the connection limit", remote_addr)
+				}
+			}
+		}
+	}
+}
+
+/// A connection holding one of its listener's permits until it is dropped.
+pub struct LimitedIo<T> {
+	io: T,
+	_permit: OwnedSemaphorePermit,
+}
+
+impl<T: AsyncRead + Unpin> AsyncRead for LimitedIo<T> {
+	fn poll_read(
+		mut self: Pin<&mut Self>,
+		cx: &mut Context<'_>,
+		buf: &mut ReadBuf<'_>,
+	) -> Poll<io::Result<()>> {
+		Pin::new(&mut self.io).poll_read(cx, buf)
+	}
+}
+
+impl<T: AsyncWrite + Unpin> AsyncWrite for LimitedIo<T> {
+	fn poll_write(
+		mut self: Pin<&mut Self>,
+		cx: &mut Context<'_>,
+		buf: &[u8],
+	) -> Poll<io::Result<usize>> {
+		Pin::new(&mut self.io).poll_write(cx, buf)
+	}
+
+	fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Pin::new(&mut self.io).poll_flush(cx)
+	}
+
+	fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+		Pin::new(&mut self.io).poll_shutdown(cx)
+	}
+
+	fn poll_write_vectored(
+		mut self: Pin<&mut Self>,
+		cx: &mut Context<'_>,
+		bufs: &[io::IoSlice<'_>],
+	) -> Poll<io::Result<usize>> {
+		Pin::new(&mut self.io).poll_write_vectored(cx, bufs)
+	}
+
+	fn is_write_vectored(&self) -> bool {
+		self.io.is_write_vectored()
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use tokio::io::AsyncReadExt;
+
+	#[tokio::test]
+	async fn test_connections_over_the_limit_are_closed() -> Result<(), anyhow::Error> {
+		let listener = LimitedListener::new(
+			TcpBacklogListener { addr: "127.0.0.1:0".parse()?, backlog: 16, only_v6: false },
+			NonZeroUsize::new(1).unwrap(),
+		);
+		let mut acceptor = listener.into_acceptor().await?;
+		let addr = acceptor.local_addr()[0]
+			.as_socket_addr()
+			.copied()
+			.expect("a TCP listener has a socket address");
+
+		let _first = tokio::net::TcpStream::connect(addr).await?;
+		let (held, _, _, _) = acceptor.accept().await?;
+
+		let mut second = tokio::net::TcpStream::connect(addr).await?;
+		let accept_third = tokio::spawn(async move {
+			let third = acceptor.accept().await;
+			(acceptor, third)
+		});
+		// The second connection is closed rather than left waiting for a permit.
+		assert_eq!(second.read(&mut [0u8; 1]).await?, 0);
+
+		drop(held);
+		let _third = tokio::net::TcpStream::connect(addr).await?;
+		let (_acceptor, third) = accept_third.await?;
+		third?;
+		Ok(())
+	}
+
+	#[tokio::test]
+	async fn test_a_handed_over_socket_is_taken_over() -> Result<(), anyhow::Error> {
+		let held = std::net::TcpListener::bind("127.0.0.1:0")?;
+		let addr = held.local_addr()?;
+		hand_over(held)?;
+
+		let listener = TcpBacklogListener { addr, backlog: 16, only_v6: false };
+		let mut acceptor = listener.into_acceptor().await?;
+		assert_eq!(acceptor.local_addr()[0].as_socket_addr(), Some(&addr));
+		let _client = tokio::net::TcpStream::connect(addr).await?;
+		acceptor.accept().await?;
+		Ok(())
+	}
+}
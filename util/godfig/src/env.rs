@@ -0,0 +1,50 @@
+//! Env var lookups namespaced by an optional prefix, so that several instances can share a host.
+
+use std::env::VarError;
+use std::ffi::OsString;
+use std::sync::{PoisonError, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+/// The env var naming the prefix tried before every bare variable name.
+pub const ENV_PREFIX_VAR: &str = "MAPTOS_ENV_PREFIX";
+
+/// The configured prefix, `None` when unset or empty.
+pub fn prefix() -> Option<String> {
+	std::env::var(ENV_PREFIX_VAR).ok().filter(|prefix| !prefix.is_empty())
+}
+
+/// The name `name` is read under with `prefix`, e.g. `NODEA_MAPTOS_API_LISTEN_PORT`.
+pub fn prefixed(prefix: &str, name: &str) -> String {
+	format!("{}_{}", prefix, name)
+}
+
+/// Reads `name`, preferring `<PREFIX>_<name>` when [`ENV_PREFIX_VAR`] is set.
+pub fn var(name: &str) -> Result<String, VarError> {
+	if let Some(prefix) = prefix() {
+		match std::env::var(prefixed(&prefix, name)) {
+			Err(VarError::NotPresent) => {}
+			found => return found,
+		}
+	}
+	std::env::var(name)
+}
+
+/// As [`var`], without requiring the value to be valid unicode.
+pub fn var_os(name: &str) -> Option<OsString> {
+	prefix()
+		.and_then(|prefix| std::env::var_os(prefixed(&prefix, name)))
+		.or_else(|| std::env::var_os(name))
+}
+
+/// Guards the environment, which every thread of the process shares, for the tests of a crate.
+static ENV_LOCK: RwLock<()> = RwLock::new(());
+
+/// Held by a test for as long as it sets env vars, so that no other test reads them meanwhile.
+pub fn lock() -> RwLockWriteGuard<'static, ()> {
+	ENV_LOCK.write().unwrap_or_else(PoisonError::into_inner)
+}
+
+/// Held by a test which reads the environment, such as through defaults read from it, so that
+/// it never sees the env vars another test sets.
+pub fn read_lock() -> RwLockReadGuard<'static, ()> {
+	ENV_LOCK.read().unwrap_or_else(PoisonError::into_inner)
+}
@@ -1,4 +1,5 @@
 pub mod backend;
+pub mod env;
 pub mod godfig;
 pub use godfig::*;
 
@@ -7,13 +8,13 @@ macro_rules! env_default {
 	// Case with default value
 	($name:ident, $env:expr, $ty:ty, $default:expr) => {
 		pub fn $name() -> $ty {
-			std::env::var($env).ok().and_then(|v| v.parse().ok()).unwrap_or($default)
+			$crate::env::var($env).ok().and_then(|v| v.parse().ok()).unwrap_or($default)
 		}
 	};
 	// Case without default value
 	($name:ident, $env:expr, $ty:ty) => {
 		pub fn $name() -> Option<$ty> {
-			std::env::var($env).ok().and_then(|v| v.parse().ok())
+			$crate::env::var($env).ok().and_then(|v| v.parse().ok())
 		}
 	};
 }
@@ -23,7 +24,7 @@ macro_rules! env_short_default {
 	// Case with default value
 	($name:ident, $ty:ty, $default:expr) => {
 		pub fn $name() -> $ty {
-			std::env::var(stringify!($name).to_uppercase())
+			$crate::env::var(&stringify!($name).to_uppercase())
 				.ok()
 				.and_then(|v| v.parse::<$ty>().ok())
 				.unwrap_or_else(|| $default.into())
@@ -37,7 +38,7 @@ macro_rules! env_or_none {
         pub fn $fname() -> Option<$ty> {
             let vars_set = vec![
                 $(
-                    std::env::var(stringify!($name).to_uppercase()).ok().filter(|v| !v.is_empty())
+                    $crate::env::var(&stringify!($name).to_uppercase()).ok().filter(|v| !v.is_empty())
                 ),*
             ];
 
@@ -55,6 +56,7 @@ mod tests {
 
 	#[test]
 	fn test_env_default_with_env() {
+		let _env = crate::env::lock();
 		std::env::set_var("TEST_ENV_DEFAULT_1", "42");
 
 		// without default value
@@ -70,6 +72,7 @@ mod tests {
 
 	#[test]
 	fn test_env_default_without_env() {
+		let _env = crate::env::lock();
 		std::env::remove_var("TEST_ENV_DEFAULT_2");
 
 		// without default value
@@ -83,8 +86,28 @@ mod tests {
 		assert_eq!(result, 0);
 	}
 
+	#[test]
+	fn test_prefixed_env_wins_over_bare_env() {
+		let _env = crate::env::lock();
+		std::env::set_var("TEST_ENV_DEFAULT_3", "1");
+		std::env::set_var("NODEA_TEST_ENV_DEFAULT_3", "42");
+		env_default!(my_env_with_default, "TEST_ENV_DEFAULT_3", i32, 0);
+
+		std::env::set_var(crate::env::ENV_PREFIX_VAR, "NODEA");
+		let prefixed = my_env_with_default();
+		// Instances whose prefixed var is unset fall back to the bare one.
+		std::env::set_var(crate::env::ENV_PREFIX_VAR, "NODEB");
+		let bare = my_env_with_default();
+		std::env::remove_var(crate::env::ENV_PREFIX_VAR);
+
+		assert_eq!(prefixed, 42);
+		assert_eq!(bare, 1);
+		assert_eq!(my_env_with_default(), 1);
+	}
+
 	#[test]
 	fn test_short_env_or_none_with_env() {
+		let _env = crate::env::lock();
 		env_short_default!(my_short_env, i32, 0);
 		std::env::set_var("MY_SHORT_ENV", "42");
 		env_short_default!(my_short_env_2, i32, 0);
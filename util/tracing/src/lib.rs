@@ -2,7 +2,9 @@ use tracing_appender::non_blocking::WorkerGuard as AppenderGuard;
 use tracing_subscriber::filter::{self, EnvFilter, LevelFilter};
 use tracing_subscriber::fmt::format::FmtSpan;
 use tracing_subscriber::prelude::*;
+use tracing_subscriber::{reload, Registry};
 
+use std::sync::OnceLock;
 use std::{env, fs::File, path::PathBuf};
 
 const TIMING_ENV: &str = "MOVEMENT_TIMING";
@@ -12,6 +14,9 @@ const TIMING_ENV: &str = "MOVEMENT_TIMING";
 /// environment variable is set, the log file with this name will be created.
 pub const DEFAULT_TIMING_LOG_FILE: &str = "movement-timing.log";
 
+/// Changes the filter of the log output installed by `init_tracing_subscriber`.
+static LOG_FILTER: OnceLock<reload::Handle<EnvFilter, Registry>> = OnceLock::new();
+
 /// A guard for background log appender(s) returned by `init_tracing_subscriber`.
 pub struct WorkerGuard {
 	_drop_me: Option<AppenderGuard>,
@@ -36,6 +41,8 @@ pub fn init_tracing_subscriber(config: Config) -> WorkerGuard {
 	let env_filter = EnvFilter::builder()
 		.with_default_directive(LevelFilter::INFO.into())
 		.from_env_lossy();
+	let (env_filter, log_filter) = reload::Layer::new(env_filter);
+	let _ = LOG_FILTER.set(log_filter);
 	let log_layer = tracing_subscriber::fmt::layer().with_filter(env_filter);
 
 	let (timing_layer, timing_writer_guard) = match env::var(TIMING_ENV) {
@@ -76,3 +83,15 @@ pub fn init_tracing_subscriber(config: Config) -> WorkerGuard {
 
 	WorkerGuard { _drop_me: timing_writer_guard }
 }
+
+/// Sets the level the log output defaults to, such as `debug`, for the targets the
+/// `RUST_LOG` directives do not name.
+///
+/// Fails if `init_tracing_subscriber` has not installed the subscriber, or the level
+/// does not parse.
+pub fn set_default_level(level: &str) -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+	let handle = LOG_FILTER.get().ok_or("the tracing subscriber is not installed")?;
+	let level: LevelFilter = level.parse()?;
+	handle.reload(EnvFilter::builder().with_default_directive(level.into()).from_env_lossy())?;
+	Ok(())
+}